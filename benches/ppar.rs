@@ -63,6 +63,18 @@ fn bench_get_100K(b: &mut Bencher) {
     println!("bench_get_100K n:{} mem_ratio:{}%", arr.len(), ratio);
 }
 
+#[bench]
+#[allow(non_snake_case)]
+fn bench_finger_get_100K(b: &mut Bencher) {
+    let arr: Vector<u64> = (0..100_000).collect();
+    let mut finger = arr.finger();
+    let mut off = 0;
+    b.iter(|| {
+        finger.get(off).unwrap();
+        off = (off + 1) % arr.len();
+    });
+}
+
 #[bench]
 #[allow(non_snake_case)]
 fn bench_update_100K(b: &mut Bencher) {
@@ -0,0 +1,105 @@
+//! Derive macros for [ppar](https://docs.rs/ppar), use them through the
+//! `derive` feature of `ppar`.
+
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields};
+
+/// Derive `ppar::Columns` for a struct with named fields.
+///
+/// For a record `Record`, a companion struct `RecordColumns` is generated,
+/// with the same visibility and generics, holding a `ppar::Vector` per
+/// field under the field's name. Vectors are edited copy-on-write, hence
+/// `Columns` is implemented for field types that implement `Clone`.
+#[proc_macro_derive(Columns)]
+pub fn derive_columns(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match columns(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn columns(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            _ => Err(Error::new_spanned(
+                &input.ident,
+                "Columns can only be derived for structs with named fields",
+            ))?,
+        },
+        _ => Err(Error::new_spanned(
+            &input.ident,
+            "Columns can only be derived for structs",
+        ))?,
+    };
+
+    let (vis, name, generics) = (&input.vis, &input.ident, &input.generics);
+    let cols_name = format_ident!("{}Columns", name);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let names: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let doc = format!("Columns of [{}], a vector per field.", name);
+
+    // vectors are edited copy-on-write, which clones their items.
+    let mut bounded = generics.clone();
+    let predicates = &mut bounded.make_where_clause().predicates;
+    types
+        .iter()
+        .for_each(|ty| predicates.push(syn::parse_quote!(#ty: ::std::clone::Clone)));
+    let bounds = &bounded.where_clause;
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #cols_name #generics #where_clause {
+            #(pub #names: ::ppar::Vector<#types>,)*
+        }
+
+        // written by hand, derive would bound the type parameters by Clone
+        // and Default, while vectors need only their items to be Clone.
+        impl #impl_generics ::std::clone::Clone for #cols_name #ty_generics #bounds {
+            fn clone(&self) -> Self {
+                #cols_name { #(#names: ::std::clone::Clone::clone(&self.#names),)* }
+            }
+        }
+
+        impl #impl_generics ::std::default::Default for #cols_name #ty_generics #where_clause {
+            fn default() -> Self {
+                #cols_name { #(#names: ::ppar::Vector::default(),)* }
+            }
+        }
+
+        impl #impl_generics ::ppar::Columns for #name #ty_generics #bounds {
+            type Vectors = #cols_name #ty_generics;
+
+            fn insert(
+                cols: &mut Self::Vectors,
+                off: usize,
+                record: Self,
+            ) -> ::ppar::Result<()> {
+                #(cols.#names.insert(off, record.#names)?;)*
+                Ok(())
+            }
+
+            fn remove(cols: &mut Self::Vectors, off: usize) -> ::ppar::Result<Self> {
+                #(let #names = cols.#names.remove(off)?;)*
+                Ok(#name { #(#names,)* })
+            }
+
+            fn get(cols: &Self::Vectors, off: usize) -> ::ppar::Result<Self> {
+                #(let #names = ::std::clone::Clone::clone(cols.#names.get(off)?);)*
+                Ok(#name { #(#names,)* })
+            }
+
+            fn update(
+                cols: &mut Self::Vectors,
+                off: usize,
+                record: Self,
+            ) -> ::ppar::Result<Self> {
+                #(let #names = cols.#names.update(off, record.#names)?;)*
+                Ok(#name { #(#names,)* })
+            }
+        }
+    })
+}
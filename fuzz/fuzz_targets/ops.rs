@@ -0,0 +1,107 @@
+//! Decode a tape of operations from fuzzer input, apply them on Vector and
+//! on a reference Vec, and validate the tree after each operation.
+//!
+//! cargo +nightly fuzz run ops
+
+#![no_main]
+
+use arbitrary::{Arbitrary, Unstructured};
+use libfuzzer_sys::fuzz_target;
+
+use ppar::rc::Vector;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Insert(u16, u8),
+    InsertMut(u16, u8),
+    Update(u16, u8),
+    UpdateMut(u16, u8),
+    Remove(u16),
+    RemoveMut(u16),
+    SplitOff(u16),
+    Append(Vec<u8>),
+    Rebalance(bool),
+    Clone,
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+
+    let (leaf_size, auto_rebalance, vals) = match decode_initial(&mut u) {
+        Ok(val) => val,
+        Err(_) => return,
+    };
+    let mut arr = Vector::from_slice(&vals, Some(leaf_size));
+    arr.set_auto_rebalance(auto_rebalance);
+    let mut refv = vals;
+
+    // versions kept alive, so that copy-on-write paths are exercised.
+    let mut versions = vec![];
+
+    while !u.is_empty() {
+        let op = match Op::arbitrary(&mut u) {
+            Ok(op) => op,
+            Err(_) => break,
+        };
+        let n = refv.len();
+        match op {
+            Op::Insert(off, val) => match arr.insert(off as usize, val) {
+                Ok(()) => refv.insert(off as usize, val),
+                Err(_) => assert!(off as usize > n),
+            },
+            Op::InsertMut(off, val) => match arr.insert_mut(off as usize, val) {
+                Ok(()) => refv.insert(off as usize, val),
+                Err(_) => assert!(off as usize > n),
+            },
+            Op::Update(off, val) => match arr.update(off as usize, val) {
+                Ok(old) => assert_eq!(old, std::mem::replace(&mut refv[off as usize], val)),
+                Err(_) => assert!(off as usize >= n),
+            },
+            Op::UpdateMut(off, val) => match arr.update_mut(off as usize, val) {
+                Ok(old) => assert_eq!(old, std::mem::replace(&mut refv[off as usize], val)),
+                Err(_) => assert!(off as usize >= n),
+            },
+            Op::Remove(off) => match arr.remove(off as usize) {
+                Ok(old) => assert_eq!(old, refv.remove(off as usize)),
+                Err(_) => assert!(off as usize >= n),
+            },
+            Op::RemoveMut(off) => match arr.remove_mut(off as usize) {
+                Ok(old) => assert_eq!(old, refv.remove(off as usize)),
+                Err(_) => assert!(off as usize >= n),
+            },
+            Op::SplitOff(off) => match arr.split_off(off as usize) {
+                Ok(a) => {
+                    let b = refv.split_off(off as usize);
+                    a.validate().unwrap();
+                    assert!(a.iter().eq(b.iter()));
+                    arr.append(a);
+                    refv.extend(b);
+                }
+                Err(_) => assert!(off as usize > n),
+            },
+            Op::Append(vals) => {
+                arr.append(Vector::from_slice(&vals, Some(leaf_size)));
+                refv.extend(vals);
+            }
+            Op::Rebalance(packed) => arr = arr.rebalance(packed).unwrap(),
+            Op::Clone => versions.push((arr.clone(), refv.clone())),
+        }
+
+        if let Err(err) = arr.validate() {
+            panic!("{} after {} items", err, refv.len())
+        }
+        assert_eq!(arr.len(), refv.len());
+    }
+
+    assert!(arr.iter().eq(refv.iter()));
+    for (arr, refv) in versions.into_iter() {
+        assert!(arr.iter().eq(refv.iter()));
+    }
+});
+
+fn decode_initial(u: &mut Unstructured) -> arbitrary::Result<(usize, bool, Vec<u8>)> {
+    let leaf_size = *u.choose(&[1, 2, 16, 256, 4096])?;
+    let auto_rebalance = bool::arbitrary(u)?;
+    let vals = Vec::<u8>::arbitrary(u)?;
+    Ok((leaf_size, auto_rebalance, vals))
+}
@@ -11,4 +11,5 @@ date; time cargo +nightly bench -- --nocapture || exit $?
 date; time cargo +stable bench -- --nocapture || exit $?
 
 date; time cargo +nightly run --release --bin perf --features=perf -- --loads 100000 --ops 10000 || exit $?
+date; time cargo +nightly run --release --bin perf --features=perf -- --copy-vs-clone --loads 1000000 --ops 100000 || exit $?
 date; valgrind --leak-check=full --show-leak-kinds=all --track-origins=yes $PERF --loads 10000 --ops 10000 || exit $?
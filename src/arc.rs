@@ -1,7 +1,10 @@
 //! Module implement thread-safe persistent array.
 
 use std::sync::Arc as Ref;
+#[cfg(feature = "ipld")]
+use std::sync::Weak;
 
+#[allow(clippy::duplicate_mod)]
 #[path = "./ppar.rs"]
 mod ppar;
 
@@ -9,6 +12,156 @@ mod ppar;
 pub use self::ppar::Vector;
 pub use self::ppar::*;
 
+#[allow(clippy::duplicate_mod)]
+#[path = "./measured.rs"]
+mod measured;
+
+pub use self::measured::{MeasuredIter, MeasuredVector};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./text.rs"]
+mod text;
+
+pub use self::text::RopeString;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./stream.rs"]
+mod stream;
+
+pub use self::stream::{RopeReader, RopeWriter};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./bits.rs"]
+mod bits;
+
+pub use self::bits::BitRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./sorted.rs"]
+mod sorted;
+
+pub use self::sorted::SortedRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./rle.rs"]
+mod rle;
+
+pub use self::rle::RleRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./marker.rs"]
+mod marker;
+
+pub use self::marker::{MarkedVector, MarkerId};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./kv.rs"]
+mod kv;
+
+pub use self::kv::KvRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./time.rs"]
+mod time;
+
+pub use self::time::TimeRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./deque.rs"]
+mod deque;
+
+pub use self::deque::PersistentDeque;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./ring.rs"]
+mod ring;
+
+pub use self::ring::SnapshotRing;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./diff.rs"]
+mod diff;
+
+pub use self::diff::{Conflict, MergeResult};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./history.rs"]
+mod history;
+
+pub use self::history::History;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./snapshots.rs"]
+mod snapshots;
+
+pub use self::snapshots::Snapshots;
+
+#[cfg(feature = "crdt")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./crdt.rs"]
+mod crdt;
+
+#[cfg(feature = "crdt")]
+pub use self::crdt::RgaVector;
+
+#[cfg(feature = "ipld")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./dag.rs"]
+mod dag;
+
+#[cfg(feature = "ipld")]
+pub use self::dag::Flushed;
+
+#[cfg(feature = "lz4")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./compressed.rs"]
+mod compressed;
+
+#[cfg(feature = "lz4")]
+pub use self::compressed::CompressedVector;
+
+#[cfg(feature = "shared")]
+#[path = "./shared.rs"]
+mod shared;
+
+#[cfg(feature = "shared")]
+pub use self::shared::{Change, SharedVector};
+
+#[cfg(feature = "epoch")]
+#[path = "./epoch.rs"]
+mod epoch;
+
+#[cfg(feature = "epoch")]
+pub use self::epoch::EpochVector;
+
+#[cfg(feature = "rayon")]
+#[path = "./par.rs"]
+mod par;
+
+// compile time check, thread-safe types shall remain Send and Sync.
+const _: fn() = || {
+    fn is_send_sync<T: Send + Sync>() {}
+
+    is_send_sync::<Vector<u64>>();
+    is_send_sync::<IntoIter<u64>>();
+    is_send_sync::<Focus<u64>>();
+    #[cfg(feature = "shared")]
+    is_send_sync::<SharedVector<u64>>();
+    is_send_sync::<RopeString>();
+    is_send_sync::<RopeReader>();
+    is_send_sync::<BitRope>();
+    is_send_sync::<SortedRope<u64>>();
+    is_send_sync::<RleRope<u64>>();
+    is_send_sync::<MarkedVector<u64>>();
+    is_send_sync::<KvRope<u64, u64>>();
+    is_send_sync::<PersistentDeque<u64>>();
+    is_send_sync::<SnapshotRing<u64>>();
+    is_send_sync::<History<u64>>();
+    is_send_sync::<Snapshots<u64>>();
+    #[cfg(feature = "crdt")]
+    is_send_sync::<RgaVector<u64>>();
+};
+
 impl<T> Vector<T>
 where
     T: Clone,
@@ -30,6 +30,9 @@ pub struct Opt {
 
     #[structopt(long = "leaf-size")]
     leaf_size: Option<usize>,
+
+    #[structopt(long = "copy-vs-clone")]
+    copy_vs_clone: bool,
 }
 
 fn main() {
@@ -42,6 +45,11 @@ fn main() {
         SmallRng::from_seed(seed.to_le_bytes())
     };
 
+    if opts.copy_vs_clone {
+        copy_vs_clone(&opts, &mut rng);
+        return;
+    }
+
     let arrs = if opts.im {
         vec![(Array::<u64>::new_im(), "im::Vector")]
     } else if opts.std_vec {
@@ -64,6 +72,76 @@ fn main() {
     }
 }
 
+// same size as u64, Clone but not Copy, leaf items of Copy types are
+// copied with memcpy, while these are cloned one by one.
+#[derive(Clone)]
+struct Item(#[allow(dead_code)] u64);
+
+impl rand::distributions::Distribution<Item> for rand::distributions::Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Item {
+        Item(rng.gen())
+    }
+}
+
+// compare Copy and Clone items, on the same seed, for operations copying
+// leaf items as slices.
+fn copy_vs_clone(opts: &Opt, rng: &mut SmallRng) {
+    // first run of each warms up the allocator and caches.
+    let seed: u128 = rng.gen();
+    copy_ops::<u64>(opts, seed);
+    copy_ops::<Item>(opts, seed);
+    let copy = copy_ops::<u64>(opts, seed);
+    let clone = copy_ops::<Item>(opts, seed);
+
+    println!(
+        "Copy vs Clone items for ppar::rc::Vector, {} items",
+        opts.loads
+    );
+    println!("--------------------------------------");
+    println!("{:14} {:>12} {:>12}", "op", "Copy", "Clone");
+    for ((op, a), (_, b)) in copy.iter().zip(clone.iter()) {
+        println!("{:14} {:>12?} {:>12?}", op, a, b);
+    }
+}
+
+fn copy_ops<T>(opts: &Opt, seed: u128) -> Vec<(&'static str, time::Duration)>
+where
+    T: Clone,
+    rand::distributions::Standard: rand::distributions::Distribution<T>,
+{
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+    let leaf_size = opts.leaf_size.unwrap_or(ppar::LEAF_CAP);
+    let vals: Vec<T> = (0..opts.loads).map(|_| rng.gen()).collect();
+    let mut arr = ppar::rc::Vector::from_slice(&vals, Some(leaf_size));
+    let mut stats = vec![];
+
+    // copy-on-write insert copies the leaf node, splitting it once full.
+    let start = time::Instant::now();
+    for _i in 0..opts.ops {
+        let off = rng.gen::<usize>() % arr.len();
+        arr.insert(off, rng.gen()).unwrap();
+    }
+    stats.push(("insert", start.elapsed() / (opts.ops as u32)));
+
+    // copy-on-write remove copies the leaf node, merging underfull ones.
+    let start = time::Instant::now();
+    for _i in 0..opts.ops {
+        let off = rng.gen::<usize>() % arr.len();
+        arr.remove(off).unwrap();
+    }
+    stats.push(("remove", start.elapsed() / (opts.ops as u32)));
+
+    let n = std::cmp::max(opts.ops / 10_000, 1);
+    let start = time::Instant::now();
+    for _i in 0..n {
+        let vals: Vec<T> = arr.clone().into();
+        assert_eq!(vals.len(), arr.len());
+    }
+    stats.push(("to_vec", start.elapsed() / (n as u32)));
+
+    stats
+}
+
 fn mem_ratio<T>(mem: usize, n: usize) -> f64 {
     let s = std::mem::size_of::<T>();
     ((((mem as f64) / (n as f64)) - (s as f64)) / s as f64) * 100_f64
@@ -0,0 +1,304 @@
+use std::{iter::FromIterator, ops::Range};
+
+use super::measured::MeasuredVector;
+use crate::{Error, Measure, Result};
+
+// chunk of bits shall not exceed this size.
+const CHUNK_BITS: usize = 1024;
+
+// measure of a chunk of bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BitMeasure {
+    bits: usize,
+    ones: usize,
+}
+
+impl Measure<Bits> for BitMeasure {
+    fn from_item(chunk: &Bits) -> Self {
+        BitMeasure {
+            bits: chunk.len,
+            ones: chunk.count_ones(chunk.len),
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        BitMeasure {
+            bits: self.bits + other.bits,
+            ones: self.ones + other.ones,
+        }
+    }
+}
+
+/// Persistent sequence of bits.
+///
+/// Bits are packed into u64 words, within chunks of up to 1024 bits, held
+/// in a B-tree whose nodes cache the number of bits and the number of set
+/// bits under them. Hence get, set, insert and remove by bit index, along
+/// with counting the set bits within a range, are O(log n). Cloning is
+/// cheap and shares the tree, edits on a clone copy only the path from the
+/// root to the edited chunk.
+#[derive(Clone, Default)]
+pub struct BitRope {
+    chunks: MeasuredVector<Bits, BitMeasure>,
+}
+
+impl FromIterator<bool> for BitRope {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let mut chunks = vec![];
+        let mut chunk = Bits::default();
+        for bit in iter {
+            if chunk.len == CHUNK_BITS {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+            chunk.insert(chunk.len, bit);
+        }
+        if chunk.len > 0 {
+            chunks.push(chunk);
+        }
+        BitRope {
+            chunks: MeasuredVector::from_iter(chunks),
+        }
+    }
+}
+
+impl BitRope {
+    /// Create an empty sequence.
+    pub fn new() -> BitRope {
+        BitRope::default()
+    }
+
+    /// Return the number of bits.
+    pub fn len(&self) -> usize {
+        self.chunks.total().bits
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the bit at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<bool> {
+        let (k, before) = self.search(index)?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(index))?;
+        Ok(chunk.get(index - before.bits))
+    }
+
+    /// Return the number of set bits within `range`, or `IndexOutOfBounds`
+    /// error.
+    pub fn count_ones(&self, range: Range<usize>) -> Result<usize> {
+        let n = self.len();
+        if range.start > range.end || range.end > n {
+            Err(Error::IndexOutOfBounds {
+                index: std::cmp::max(range.start, range.end),
+                len: n,
+            })?
+        }
+        Ok(self.rank(range.end)? - self.rank(range.start)?)
+    }
+
+    /// Set the bit at `index` and return the old bit, or `IndexOutOfBounds`
+    /// error.
+    pub fn set(&mut self, index: usize, bit: bool) -> Result<bool> {
+        let (k, before) = self.search(index)?;
+        let mut chunk = self.chunks.get(k).map_err(|_| corrupted(index))?.clone();
+        let old = chunk.set(index - before.bits, bit);
+        self.chunks.update(k, chunk)?;
+        Ok(old)
+    }
+
+    /// Insert `bit` at `index`, or `IndexOutOfBounds` error.
+    pub fn insert(&mut self, index: usize, bit: bool) -> Result<()> {
+        let n = self.len();
+        let (k, off) = if index > n {
+            Err(Error::IndexOutOfBounds { index, len: n })?
+        } else if self.chunks.is_empty() {
+            self.chunks.insert(0, Bits::default())?;
+            (0, 0)
+        } else if index == n {
+            // inserting at the end appends to the last chunk.
+            let k = self.chunks.len() - 1;
+            let last = self.chunks.get(k).map_err(|_| corrupted(index))?;
+            (k, last.len)
+        } else {
+            let (k, before) = self.search(index)?;
+            (k, index - before.bits)
+        };
+
+        let mut chunk = self.chunks.get(k).map_err(|_| corrupted(index))?.clone();
+        chunk.insert(off, bit);
+        if chunk.len > CHUNK_BITS {
+            let right = chunk.split_off(chunk.len / 2);
+            self.chunks.insert(k + 1, right)?;
+        }
+        self.chunks.update(k, chunk)?;
+        Ok(())
+    }
+
+    /// Append `bit` at the end.
+    pub fn push(&mut self, bit: bool) {
+        // insert at the end never fails.
+        self.insert(self.len(), bit).ok();
+    }
+
+    /// Remove the bit at `index` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, index: usize) -> Result<bool> {
+        let (k, before) = self.search(index)?;
+        let mut chunk = self.chunks.get(k).map_err(|_| corrupted(index))?.clone();
+        let bit = chunk.remove(index - before.bits);
+
+        match self.chunks.get(k + 1) {
+            _ if chunk.len == 0 => {
+                self.chunks.remove(k)?;
+            }
+            Ok(next) if chunk.len + next.len <= (CHUNK_BITS / 2) => {
+                let next = self.chunks.remove(k + 1)?;
+                (0..next.len).for_each(|i| chunk.insert(chunk.len, next.get(i)));
+                self.chunks.update(k, chunk)?;
+            }
+            _ => {
+                self.chunks.update(k, chunk)?;
+            }
+        }
+        Ok(bit)
+    }
+
+    /// Return an iterator over bits.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|chunk| (0..chunk.len).map(move |i| chunk.get(i)))
+    }
+
+    // return the number of set bits before `index`.
+    fn rank(&self, index: usize) -> Result<usize> {
+        if index == self.len() {
+            return Ok(self.chunks.total().ones);
+        }
+        let (k, before) = self.search(index)?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(index))?;
+        Ok(before.ones + chunk.count_ones(index - before.bits))
+    }
+
+    // return (offset, measure before) of the chunk holding `index`.
+    fn search(&self, index: usize) -> Result<(usize, BitMeasure)> {
+        match self.chunks.search_by_measure(|m| m.bits > index) {
+            Some(res) => Ok(res),
+            None => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            }),
+        }
+    }
+}
+
+// chunk of bits, packed into words, bits beyond `len` are always zero.
+#[derive(Clone, Default)]
+struct Bits {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bits {
+    fn get(&self, i: usize) -> bool {
+        ((self.words[i / 64] >> (i % 64)) & 1) == 1
+    }
+
+    fn set(&mut self, i: usize, bit: bool) -> bool {
+        let old = self.get(i);
+        let mask = 1 << (i % 64);
+        match bit {
+            true => self.words[i / 64] |= mask,
+            false => self.words[i / 64] &= !mask,
+        }
+        old
+    }
+
+    // insert bit at `i`, shifting the bits after it by one.
+    fn insert(&mut self, i: usize, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        let (w, b) = (i / 64, i % 64);
+
+        let x = self.words[w];
+        let mut carry = x >> 63;
+        let low = x & low_mask(b);
+        let high = (x & !low_mask(b)) << 1;
+        self.words[w] = low | high | ((bit as u64) << b);
+        for word in self.words[(w + 1)..].iter_mut() {
+            let out = *word >> 63;
+            *word = (*word << 1) | carry;
+            carry = out;
+        }
+        self.len += 1;
+    }
+
+    // remove bit at `i`, shifting the bits after it by one.
+    fn remove(&mut self, i: usize) -> bool {
+        let bit = self.get(i);
+        let (w, b) = (i / 64, i % 64);
+
+        let mut carry = 0;
+        for word in self.words[(w + 1)..].iter_mut().rev() {
+            let out = *word & 1;
+            *word = (*word >> 1) | (carry << 63);
+            carry = out;
+        }
+        let x = self.words[w];
+        let low = x & low_mask(b);
+        let high = (x >> 1) & !low_mask(b);
+        self.words[w] = low | high | (carry << 63);
+
+        self.len -= 1;
+        if self.len.is_multiple_of(64) {
+            self.words.pop();
+        }
+        bit
+    }
+
+    // split off bits from `at`, into a new chunk.
+    fn split_off(&mut self, at: usize) -> Bits {
+        let mut right = Bits::default();
+        (at..self.len).for_each(|i| right.insert(right.len, self.get(i)));
+        while self.len > at {
+            self.remove(self.len - 1);
+        }
+        right
+    }
+
+    // return the number of set bits before `n`.
+    fn count_ones(&self, n: usize) -> usize {
+        let (w, b) = (n / 64, n % 64);
+        let ones: usize = self.words[..w]
+            .iter()
+            .map(|x| x.count_ones() as usize)
+            .sum();
+        match b {
+            0 => ones,
+            b => ones + (self.words[w] & low_mask(b)).count_ones() as usize,
+        }
+    }
+}
+
+// mask for bits below `b`.
+fn low_mask(b: usize) -> u64 {
+    match b {
+        0 => 0,
+        b => u64::MAX >> (64 - b),
+    }
+}
+
+fn corrupted(index: usize) -> Error {
+    Error::Corrupted {
+        detail: format!("missing chunk for bit {}", index),
+    }
+}
+
+#[cfg(test)]
+#[path = "bits_test.rs"]
+mod bits_test;
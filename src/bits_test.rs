@@ -0,0 +1,81 @@
+use rand::{rngs::SmallRng, Rng};
+
+use super::*;
+use crate::harness::{check_ops, seeded_rng};
+
+fn validate(bits: &BitRope, refv: &[bool]) {
+    assert_eq!(bits.len(), refv.len());
+    assert!(bits.iter().eq(refv.iter().cloned()));
+    let ones = refv.iter().filter(|b| **b).count();
+    assert_eq!(bits.count_ones(0..refv.len()).unwrap(), ones);
+    for chunk in bits.chunks.iter() {
+        assert!(chunk.len > 0 && chunk.len <= CHUNK_BITS, "{}", chunk.len);
+        assert_eq!(chunk.words.len(), chunk.len.div_ceil(64));
+    }
+}
+
+#[test]
+fn test_bits_ops() {
+    let mut rng = seeded_rng("test_bits_ops");
+
+    let mut refv: Vec<bool> = (0..10_000).map(|_| rng.gen()).collect();
+    let mut bits: BitRope = refv.iter().cloned().collect();
+
+    let op = |rng: &mut SmallRng, bits: &mut BitRope, refv: &mut Vec<bool>, _| {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        let bit = rng.gen::<bool>();
+        match rng.gen::<u8>() % 8 {
+            0 | 1 => {
+                bits.insert(off, bit).unwrap();
+                refv.insert(off, bit);
+            }
+            2 => {
+                bits.push(bit);
+                refv.push(bit);
+            }
+            3 if off < n => {
+                assert_eq!(bits.set(off, bit).unwrap(), refv[off]);
+                refv[off] = bit;
+            }
+            4 | 5 if off < n => {
+                assert_eq!(bits.remove(off).unwrap(), refv.remove(off));
+            }
+            6 => {
+                let end = off + (rng.gen::<usize>() % (n - off + 1));
+                let ones = refv[off..end].iter().filter(|b| **b).count();
+                assert_eq!(bits.count_ones(off..end).unwrap(), ones);
+            }
+            _ if off < n => assert_eq!(bits.get(off).unwrap(), refv[off]),
+            _ => assert!(bits.get(off).is_err()),
+        }
+    };
+    check_ops(&mut rng, &mut bits, &mut refv, 100_000, op, validate);
+
+    while !refv.is_empty() {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(bits.remove(off).unwrap(), refv.remove(off));
+    }
+    validate(&bits, &refv);
+    assert!(bits.is_empty());
+}
+
+#[test]
+fn test_bits_errors() {
+    let mut bits: BitRope = (0..100).map(|i| i % 3 == 0).collect();
+    let err = Error::IndexOutOfBounds {
+        index: 101,
+        len: 100,
+    };
+    assert_eq!(bits.insert(101, true).err(), Some(err.clone()));
+    assert_eq!(bits.count_ones(0..101).err(), Some(err));
+    assert!(bits.get(100).is_err());
+    assert!(bits.set(100, true).is_err());
+    assert!(bits.remove(100).is_err());
+    assert_eq!(bits.count_ones(10..10).unwrap(), 0);
+    assert_eq!(bits.count_ones(0..100).unwrap(), 34);
+
+    let mut bits = BitRope::new();
+    bits.insert(0, true).unwrap();
+    assert_eq!(bits.count_ones(0..1).unwrap(), 1);
+}
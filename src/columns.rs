@@ -0,0 +1,158 @@
+use std::iter::FromIterator;
+
+use crate::{Error, Result};
+
+/// Record stored as parallel columns, one vector per field, refer
+/// [ColumnVector].
+///
+/// Implementations split a record into its fields, held in a tuple or
+/// struct of vectors, `Vectors`, and join them back on access. For
+/// example, a record `{id: u64, name: String}` can be stored in
+/// `(Vector<u64>, Vector<String>)`, where scanning ids touches only the
+/// first vector.
+pub trait Columns: Sized {
+    /// Vectors holding the columns, one per field.
+    type Vectors: Clone + Default;
+
+    /// Insert the fields of `record` at `off`, in every column.
+    fn insert(cols: &mut Self::Vectors, off: usize, record: Self) -> Result<()>;
+
+    /// Remove the fields at `off`, from every column, and return them as a
+    /// record.
+    fn remove(cols: &mut Self::Vectors, off: usize) -> Result<Self>;
+
+    /// Return the record at `off`, gathered from every column.
+    fn get(cols: &Self::Vectors, off: usize) -> Result<Self>;
+
+    /// Replace the record at `off` and return the old record. Default
+    /// implementation removes and inserts the record.
+    fn update(cols: &mut Self::Vectors, off: usize, record: Self) -> Result<Self> {
+        let old = Self::remove(cols, off)?;
+        Self::insert(cols, off, record)?;
+        Ok(old)
+    }
+}
+
+/// Persistent sequence of records, stored column wise, as a vector per
+/// field, refer [Columns].
+///
+/// Scanning or mapping a single field iterates over its column, via
+/// [ColumnVector::columns], leaving the other columns untouched. Cloning is
+/// cheap and shares all the columns.
+#[derive(Clone)]
+pub struct ColumnVector<R>
+where
+    R: Columns,
+{
+    cols: R::Vectors,
+    len: usize,
+}
+
+impl<R> Default for ColumnVector<R>
+where
+    R: Columns,
+{
+    fn default() -> Self {
+        ColumnVector {
+            cols: R::Vectors::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<R> FromIterator<R> for ColumnVector<R>
+where
+    R: Columns,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+    {
+        let mut arr = ColumnVector::default();
+        for record in iter {
+            // insert at the end never fails.
+            arr.push(record).ok();
+        }
+        arr
+    }
+}
+
+impl<R> ColumnVector<R>
+where
+    R: Columns,
+{
+    /// Create an empty sequence.
+    pub fn new() -> ColumnVector<R> {
+        ColumnVector::default()
+    }
+
+    /// Return the number of records.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the columns, for scanning individual fields.
+    pub fn columns(&self) -> &R::Vectors {
+        &self.cols
+    }
+
+    /// Return the record at `off`, or `IndexOutOfBounds` error.
+    pub fn get(&self, off: usize) -> Result<R> {
+        self.check_bounds(off, self.len)?;
+        R::get(&self.cols, off)
+    }
+
+    /// Insert `record` at `off`, or `IndexOutOfBounds` error.
+    pub fn insert(&mut self, off: usize, record: R) -> Result<()> {
+        self.check_bounds(off, self.len + 1)?;
+        R::insert(&mut self.cols, off, record)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Append `record` at the end.
+    pub fn push(&mut self, record: R) -> Result<()> {
+        self.insert(self.len, record)
+    }
+
+    /// Replace the record at `off` and return the old record, or
+    /// `IndexOutOfBounds` error.
+    pub fn update(&mut self, off: usize, record: R) -> Result<R> {
+        self.check_bounds(off, self.len)?;
+        R::update(&mut self.cols, off, record)
+    }
+
+    /// Remove the record at `off` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, off: usize) -> Result<R> {
+        self.check_bounds(off, self.len)?;
+        let record = R::remove(&mut self.cols, off)?;
+        self.len -= 1;
+        Ok(record)
+    }
+
+    /// Return an iterator over records, each gathered from all columns.
+    pub fn iter(&self) -> impl Iterator<Item = Result<R>> + '_ {
+        (0..self.len).map(move |off| R::get(&self.cols, off))
+    }
+
+    // shared bounds check, so that implementations see valid offsets.
+    fn check_bounds(&self, off: usize, limit: usize) -> Result<()> {
+        match off < limit {
+            true => Ok(()),
+            false => Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "columns_test.rs"]
+mod columns_test;
@@ -0,0 +1,123 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::rc::Vector;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Record {
+    id: u64,
+    name: String,
+}
+
+impl Columns for Record {
+    type Vectors = (Vector<u64>, Vector<String>);
+
+    fn insert(cols: &mut Self::Vectors, off: usize, record: Self) -> Result<()> {
+        cols.0.insert(off, record.id)?;
+        cols.1.insert(off, record.name)
+    }
+
+    fn remove(cols: &mut Self::Vectors, off: usize) -> Result<Self> {
+        let id = cols.0.remove(off)?;
+        let name = cols.1.remove(off)?;
+        Ok(Record { id, name })
+    }
+
+    fn get(cols: &Self::Vectors, off: usize) -> Result<Self> {
+        let id = *cols.0.get(off)?;
+        let name = cols.1.get(off)?.clone();
+        Ok(Record { id, name })
+    }
+}
+
+fn validate(arr: &ColumnVector<Record>, refv: &[Record]) {
+    assert_eq!(arr.len(), refv.len());
+    let records: Vec<Record> = arr.iter().map(|r| r.unwrap()).collect();
+    assert_eq!(records, refv);
+    // scan a single column.
+    let ids: Vec<u64> = arr.columns().0.iter().copied().collect();
+    assert_eq!(ids, refv.iter().map(|r| r.id).collect::<Vec<u64>>());
+}
+
+#[test]
+fn test_columns_ops() {
+    let seed: u128 = random();
+    println!("test_columns_ops seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let record = |id: u64| Record {
+        id,
+        name: format!("name-{}", id),
+    };
+    let mut refv: Vec<Record> = (0..1000).map(record).collect();
+    let mut arr: ColumnVector<Record> = refv.iter().cloned().collect();
+    let mut versions = vec![];
+
+    for i in 0..10_000 {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        match rng.gen::<u8>() % 4 {
+            0 => {
+                arr.insert(off, record(i)).unwrap();
+                refv.insert(off, record(i));
+            }
+            1 if off < n => {
+                assert_eq!(arr.update(off, record(i)).unwrap(), refv[off]);
+                refv[off] = record(i);
+            }
+            2 if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            _ if off < n => assert_eq!(arr.get(off).unwrap(), refv[off]),
+            _ => (),
+        }
+        if i % 1000 == 0 {
+            validate(&arr, &refv);
+            versions.push((arr.clone(), refv.clone()));
+        }
+    }
+    validate(&arr, &refv);
+
+    // older versions are left untouched by edits.
+    for (arr, refv) in versions.iter() {
+        validate(arr, refv);
+    }
+
+    let n = arr.len();
+    let err = Error::IndexOutOfBounds { index: n, len: n };
+    assert_eq!(arr.get(n).err(), Some(err));
+    assert!(arr.insert(n + 1, record(0)).is_err());
+    assert!(arr.update(n, record(0)).is_err());
+    assert!(arr.remove(n).is_err());
+    assert!(ColumnVector::<Record>::new().is_empty());
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_columns_derive() {
+    #[derive(Clone, Debug, PartialEq, crate::Columns)]
+    struct Event<T> {
+        seqno: u64,
+        payload: T,
+    }
+
+    let event = |seqno: u64| Event {
+        seqno,
+        payload: format!("payload-{}", seqno),
+    };
+    let mut refv: Vec<Event<String>> = (0..100).map(event).collect();
+    let mut arr: ColumnVector<Event<String>> = refv.iter().cloned().collect();
+
+    arr.insert(10, event(1000)).unwrap();
+    refv.insert(10, event(1000));
+    assert_eq!(arr.update(20, event(2000)).unwrap(), refv[20]);
+    refv[20] = event(2000);
+    assert_eq!(arr.remove(30).unwrap(), refv.remove(30));
+
+    let records: Vec<Event<String>> = arr.iter().map(|r| r.unwrap()).collect();
+    assert_eq!(records, refv);
+    // scan a single column, by field name.
+    let cols: &EventColumns<String> = arr.columns();
+    let seqnos: Vec<u64> = cols.seqno.iter().copied().collect();
+    assert_eq!(seqnos, refv.iter().map(|e| e.seqno).collect::<Vec<u64>>());
+}
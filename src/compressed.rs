@@ -0,0 +1,379 @@
+use std::{iter::FromIterator, mem, sync::Mutex};
+
+use super::{measured::MeasuredVector, Ref};
+use crate::{CompressedFootprint, Compression, Error, Measure, Pack, Result};
+
+// chunk of consecutive items, cold chunks hold their items packed and lz4
+// compressed.
+#[derive(Clone)]
+enum Chunk<T> {
+    Hot(Ref<Vec<T>>),
+    Cold { data: Ref<Vec<u8>>, len: usize },
+}
+
+// measure of chunks, the number of items in them and the bytes they hold.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct ChunkMeasure {
+    len: usize,
+    stored: usize,
+}
+
+impl<T> Measure<Chunk<T>> for ChunkMeasure {
+    fn from_item(chunk: &Chunk<T>) -> Self {
+        match chunk {
+            Chunk::Hot(items) => ChunkMeasure {
+                len: items.len(),
+                stored: items.len() * mem::size_of::<T>(),
+            },
+            Chunk::Cold { data, len } => ChunkMeasure {
+                len: *len,
+                stored: data.len(),
+            },
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        ChunkMeasure {
+            len: self.len + other.len,
+            stored: self.stored + other.stored,
+        }
+    }
+
+    fn update(&mut self, old: Option<&Chunk<T>>, new: Option<&Chunk<T>>) -> bool {
+        let old = old.map(Self::from_item).unwrap_or_default();
+        let new = new.map(Self::from_item).unwrap_or_default();
+        self.len = self.len - old.len + new.len;
+        self.stored = self.stored - old.stored + new.stored;
+        true
+    }
+}
+
+/// Persistent sequence, storing cold items compressed, with lz4.
+///
+/// Items are held in chunks, in a B-tree whose nodes cache the number of
+/// items under them. Chunks towards the end are hot, holding items as is,
+/// while older chunks are cold, holding items packed, refer [Pack], and
+/// compressed. Suits append-mostly logs, where most of the items are
+/// rarely read or edited. Refer [Compression] for the policy, and
+/// [CompressedVector::footprint] for the savings.
+///
+/// Reading an item from a cold chunk decompresses the whole chunk, which
+/// is cached, so that reads in the vicinity of each other decompress it
+/// only once. Edits on a cold chunk decompress it, and compress it back.
+/// Chunks are split when full, and dropped when empty. Cloning is cheap
+/// and shares the chunks, though not the cache.
+pub struct CompressedVector<T> {
+    chunks: MeasuredVector<Chunk<T>, ChunkMeasure>,
+    policy: Compression,
+    // decompressed cold chunks, most recently used last. Compressed bytes
+    // are held along, so that their allocation is not reused while cached.
+    cache: Mutex<Vec<CacheEntry<T>>>,
+}
+
+type CacheEntry<T> = (Ref<Vec<u8>>, Ref<Vec<T>>);
+
+impl<T> Clone for CompressedVector<T> {
+    fn clone(&self) -> Self {
+        CompressedVector {
+            chunks: self.chunks.clone(),
+            policy: self.policy,
+            cache: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<T> Default for CompressedVector<T> {
+    fn default() -> Self {
+        CompressedVector {
+            chunks: MeasuredVector::default(),
+            policy: Compression::default(),
+            cache: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for CompressedVector<T>
+where
+    T: Clone + Pack,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        CompressedVector::build(iter.into_iter().collect(), Compression::default())
+    }
+}
+
+impl<T> CompressedVector<T>
+where
+    T: Clone + Pack,
+{
+    /// Create an empty sequence, with the default [Compression] policy.
+    pub fn new() -> CompressedVector<T> {
+        CompressedVector::default()
+    }
+
+    /// Create an empty sequence, with the `policy` for compression.
+    pub fn with_compression(policy: Compression) -> CompressedVector<T> {
+        CompressedVector {
+            policy,
+            ..CompressedVector::default()
+        }
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.chunks.total().len
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of chunks.
+    pub fn n_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Return the compression policy.
+    pub fn compression(&self) -> Compression {
+        self.policy
+    }
+
+    /// Set the compression policy, and re-chunk the items as per the new
+    /// policy, which is O(n).
+    pub fn set_compression(&mut self, policy: Compression) -> Result<()> {
+        let items = self.iter().collect::<Result<Vec<T>>>()?;
+        *self = CompressedVector::build(items, policy);
+        Ok(())
+    }
+
+    /// Return the logical size of items, and their size as stored. Both
+    /// are maintained along with the tree, hence this is O(1).
+    pub fn footprint(&self) -> CompressedFootprint {
+        CompressedFootprint {
+            logical: self.len() * mem::size_of::<T>(),
+            stored: self.chunks.total().stored,
+        }
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<T> {
+        let (k, off) = self.search(index)?;
+        let items = self.items(self.chunks.get(k)?, true)?;
+        items.get(off).cloned().ok_or_else(|| corrupted(index))
+    }
+
+    /// Append `value` at the end. Appends go to the last chunk, which is
+    /// hot, unless compression is set for every chunk.
+    pub fn push(&mut self, value: T) -> Result<()> {
+        let k = match self.chunks.len() {
+            0 => None,
+            n => Some(n - 1),
+        };
+        match k.map(|k| self.chunks.get(k)).transpose()? {
+            Some(Chunk::Hot(items)) if items.len() < self.chunk_items() => {
+                let k = self.chunks.len() - 1;
+                self.edit(k, |items| items.push(value))
+            }
+            _ => {
+                let n = self.chunks.len();
+                self.put(n, vec![value])
+            }
+        }
+    }
+
+    /// Insert `value` at `index`, or `IndexOutOfBounds` error.
+    pub fn insert(&mut self, index: usize, value: T) -> Result<()> {
+        match self.len() {
+            n if index > n => Err(Error::IndexOutOfBounds { index, len: n }),
+            n if index == n => self.push(value),
+            _ => {
+                let (k, off) = self.search(index)?;
+                self.edit(k, |items| items.insert(off, value))
+            }
+        }
+    }
+
+    /// Replace the item at `index` with `value` and return the old item, or
+    /// `IndexOutOfBounds` error.
+    pub fn update(&mut self, index: usize, value: T) -> Result<T> {
+        let (k, off) = self.search(index)?;
+        self.edit(k, |items| mem::replace(&mut items[off], value))
+    }
+
+    /// Remove the item at `index` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, index: usize) -> Result<T> {
+        let (k, off) = self.search(index)?;
+        self.edit(k, |items| items.remove(off))
+    }
+
+    /// Return an iterator over items. Cold chunks are decompressed one at a
+    /// time, bypassing the cache, and a chunk that fails to decompress
+    /// yields an error.
+    pub fn iter(&self) -> impl Iterator<Item = Result<T>> + '_ {
+        self.chunks.iter().flat_map(move |chunk| {
+            let (items, err) = match self.items(chunk, false) {
+                Ok(items) => (items, None),
+                Err(err) => (Ref::new(vec![]), Some(Err(err))),
+            };
+            let n = items.len();
+            err.into_iter()
+                .chain((0..n).map(move |i| Ok(items[i].clone())))
+        })
+    }
+
+    fn build(items: Vec<T>, policy: Compression) -> CompressedVector<T> {
+        let mut arr = CompressedVector::with_compression(policy);
+        let n = arr.chunk_items();
+        let n_chunks = items.len().div_ceil(n);
+        let chunks: Vec<Chunk<T>> = items
+            .chunks(n)
+            .enumerate()
+            .map(|(k, items)| arr.chunk(items.to_vec(), arr.is_cold(k, n_chunks)))
+            .collect();
+        arr.chunks = MeasuredVector::from_iter(chunks);
+        arr
+    }
+
+    // apply `f` on the items of chunk `k`, and put them back. The chunk is
+    // taken out of the tree, so that a hot chunk not shared with other
+    // versions is edited in-place.
+    fn edit<F, R>(&mut self, k: usize, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Vec<T>) -> R,
+    {
+        // cold chunk is decompressed before it is taken out of the tree.
+        let cold = match self.chunks.get(k)? {
+            Chunk::Hot(_) => None,
+            chunk => Some(self.items(chunk, false)?),
+        };
+        let items = match (self.chunks.remove(k)?, cold) {
+            (Chunk::Hot(items), _) | (_, Some(items)) => items,
+            (Chunk::Cold { .. }, None) => Err(corrupted(k))?,
+        };
+
+        let mut items = Ref::try_unwrap(items).unwrap_or_else(|items| (*items).clone());
+        let res = f(&mut items);
+        self.put(k, items)?;
+        Ok(res)
+    }
+
+    // insert `items` as chunk `k`, split in two if more than a chunk, or
+    // none if empty. Chunks at the boundary of the hot chunks are
+    // compressed or decompressed, since the number of chunks has changed.
+    fn put(&mut self, k: usize, mut items: Vec<T>) -> Result<()> {
+        let mut pieces = vec![];
+        if items.len() > self.chunk_items() {
+            let right = items.split_off(items.len() / 2);
+            pieces.push(items);
+            pieces.push(right);
+        } else if !items.is_empty() {
+            pieces.push(items);
+        }
+
+        let n_chunks = self.chunks.len() + pieces.len();
+        for (i, items) in pieces.into_iter().enumerate() {
+            let chunk = self.chunk(items, self.is_cold(k + i, n_chunks));
+            self.chunks.insert(k + i, chunk)?;
+        }
+
+        // an edit changes the number of chunks by at most one, hence only
+        // the chunks on either side of the boundary change temperature.
+        let boundary = n_chunks.saturating_sub(self.policy.hot_chunks);
+        let ks = [boundary.checked_sub(1), Some(boundary)];
+        for k in ks.iter().flatten().copied().filter(|k| *k < n_chunks) {
+            let chunk = match (self.chunks.get(k)?, self.is_cold(k, n_chunks)) {
+                (Chunk::Hot(items), true) => self.chunk(items.to_vec(), true),
+                (chunk @ Chunk::Cold { .. }, false) => {
+                    Chunk::Hot(self.items(chunk, false)?)
+                }
+                _ => continue,
+            };
+            self.chunks.update(k, chunk)?;
+        }
+        Ok(())
+    }
+
+    fn chunk(&self, items: Vec<T>, cold: bool) -> Chunk<T> {
+        match cold {
+            true => {
+                let mut buf = vec![];
+                T::pack(&items, &mut buf);
+                let data = lz4_flex::compress_prepend_size(&buf);
+                Chunk::Cold {
+                    data: Ref::new(data),
+                    len: items.len(),
+                }
+            }
+            false => Chunk::Hot(Ref::new(items)),
+        }
+    }
+
+    // return the items of `chunk`, decompressing cold chunks, through the
+    // cache if `cache` is true.
+    fn items(&self, chunk: &Chunk<T>, cache: bool) -> Result<Ref<Vec<T>>> {
+        let (data, len) = match chunk {
+            Chunk::Hot(items) => return Ok(Ref::clone(items)),
+            Chunk::Cold { data, len } => (data, *len),
+        };
+
+        let mut entries = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(i) = entries.iter().position(|(d, _)| Ref::ptr_eq(d, data)) {
+            let entry = entries.remove(i);
+            let items = Ref::clone(&entry.1);
+            entries.push(entry);
+            return Ok(items);
+        }
+
+        let buf = lz4_flex::decompress_size_prepended(data).map_err(|err| {
+            let detail = format!("lz4 chunk {}", err);
+            Error::Corrupted { detail }
+        })?;
+        let items = T::unpack(&buf)?;
+        if items.len() != len {
+            let detail = format!("chunk unpacked {} items, want {}", items.len(), len);
+            Err(Error::Corrupted { detail })?
+        }
+
+        let items = Ref::new(items);
+        if cache && self.policy.cache_chunks > 0 {
+            if entries.len() >= self.policy.cache_chunks {
+                entries.remove(0);
+            }
+            entries.push((Ref::clone(data), Ref::clone(&items)));
+        }
+        Ok(items)
+    }
+
+    fn is_cold(&self, k: usize, n_chunks: usize) -> bool {
+        k < n_chunks.saturating_sub(self.policy.hot_chunks)
+    }
+
+    fn chunk_items(&self) -> usize {
+        std::cmp::max(self.policy.chunk_items, 1)
+    }
+
+    // return (chunk, offset within the chunk) holding `index`.
+    fn search(&self, index: usize) -> Result<(usize, usize)> {
+        match self.chunks.search_by_measure(|m| m.len > index) {
+            Some((k, before)) => Ok((k, index - before.len)),
+            None => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            }),
+        }
+    }
+}
+
+fn corrupted(index: usize) -> Error {
+    Error::Corrupted {
+        detail: format!("missing chunk for item {}", index),
+    }
+}
+
+#[cfg(test)]
+#[path = "compressed_test.rs"]
+mod compressed_test;
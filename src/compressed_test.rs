@@ -0,0 +1,161 @@
+use rand::{rngs::SmallRng, Rng};
+
+use super::*;
+use crate::harness::{check_ops, seeded_rng};
+
+fn validate(arr: &CompressedVector<u64>, refv: &[u64]) {
+    arr.chunks.validate().unwrap();
+    assert_eq!(arr.len(), refv.len());
+    let items: Vec<u64> = arr.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(items, refv);
+
+    // chunks are never empty nor more than full, and only the last few
+    // are hot.
+    let n_chunks = arr.n_chunks();
+    for (k, chunk) in arr.chunks.iter().enumerate() {
+        let (cold, len) = match chunk {
+            Chunk::Hot(items) => (false, items.len()),
+            Chunk::Cold { len, .. } => (true, *len),
+        };
+        assert_eq!(cold, arr.is_cold(k, n_chunks), "chunk {}/{}", k, n_chunks);
+        assert!(len > 0 && len <= arr.chunk_items());
+    }
+}
+
+#[test]
+fn test_compressed_ops() {
+    let mut rng = seeded_rng("test_compressed_ops");
+
+    let policy = Compression {
+        chunk_items: 1 + rng.gen::<usize>() % 64,
+        hot_chunks: rng.gen::<usize>() % 4,
+        cache_chunks: rng.gen::<usize>() % 4,
+    };
+    println!("test_compressed_ops {:?}", policy);
+
+    let mut refv: Vec<u64> = (0..1000).map(|i| i % 10).collect();
+    let mut arr = CompressedVector::build(refv.clone(), policy);
+
+    let op =
+        |rng: &mut SmallRng, arr: &mut CompressedVector<u64>, refv: &mut Vec<u64>, _| {
+            let n = refv.len();
+            let off = rng.gen::<usize>() % (n + 1);
+            let val = rng.gen::<u64>() % 10;
+            match rng.gen::<u8>() % 8 {
+                0 | 1 => {
+                    arr.insert(off, val).unwrap();
+                    refv.insert(off, val);
+                }
+                2 => {
+                    arr.push(val).unwrap();
+                    refv.push(val);
+                }
+                3 if off < n => {
+                    assert_eq!(arr.update(off, val).unwrap(), refv[off]);
+                    refv[off] = val;
+                }
+                4 | 5 if off < n => {
+                    assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+                }
+                _ => assert_eq!(arr.get(off).ok(), refv.get(off).copied()),
+            }
+        };
+    check_ops(&mut rng, &mut arr, &mut refv, 10_000, op, validate);
+
+    let n = refv.len();
+    let err = Error::IndexOutOfBounds { index: n, len: n };
+    assert_eq!(arr.get(n).err(), Some(err));
+    assert!(arr.insert(n + 1, 0).is_err());
+    assert!(arr.update(n, 0).is_err());
+    assert!(arr.remove(n).is_err());
+
+    while !refv.is_empty() {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+    }
+    validate(&arr, &refv);
+}
+
+#[test]
+fn test_compressed_footprint() {
+    // append-mostly log, of repetitive items.
+    let mut arr: CompressedVector<u64> = CompressedVector::new();
+    for i in 0..100_000 {
+        arr.push(i % 100).unwrap();
+    }
+    let policy = arr.compression();
+    assert_eq!(arr.n_chunks(), 100_000 / policy.chunk_items + 1);
+
+    let fp = arr.footprint();
+    assert_eq!(fp.logical, 100_000 * 8);
+    assert!(fp.stored * 4 < fp.logical, "{:?}", fp);
+
+    // without compression, items are stored as is.
+    let mut plain = arr.clone();
+    plain
+        .set_compression(Compression {
+            hot_chunks: usize::MAX,
+            ..policy
+        })
+        .unwrap();
+    assert_eq!(plain.footprint().stored, fp.logical);
+    assert!(plain
+        .iter()
+        .map(|x| x.unwrap())
+        .eq(arr.iter().map(|x| x.unwrap())));
+
+    // re-chunk with larger chunks.
+    arr.set_compression(Compression {
+        chunk_items: 4096,
+        ..policy
+    })
+    .unwrap();
+    assert_eq!(arr.n_chunks(), 25);
+    assert!(
+        arr.footprint().stored * 4 < fp.logical,
+        "{:?}",
+        arr.footprint()
+    );
+    for i in (0..100_000).step_by(997) {
+        assert_eq!(arr.get(i).unwrap(), (i as u64) % 100);
+    }
+}
+
+#[test]
+fn test_compressed_cache() {
+    let policy = Compression {
+        chunk_items: 100,
+        hot_chunks: 1,
+        cache_chunks: 2,
+    };
+    let arr = CompressedVector::build((0..1000_u64).collect(), policy);
+
+    let cached = |arr: &CompressedVector<u64>| arr.cache.lock().unwrap().len();
+    for i in 0..100 {
+        assert_eq!(arr.get(i).unwrap(), i as u64);
+    }
+    assert_eq!(cached(&arr), 1);
+    assert_eq!(arr.get(150).unwrap(), 150);
+    assert_eq!(arr.get(250).unwrap(), 250);
+    assert_eq!(cached(&arr), 2);
+    // hot chunk is not cached.
+    assert_eq!(arr.get(950).unwrap(), 950);
+    assert_eq!(cached(&arr), 2);
+
+    // cache is not shared with clones, and iteration bypasses it.
+    let arr = arr.clone();
+    assert_eq!(arr.iter().count(), 1000);
+    assert_eq!(cached(&arr), 0);
+}
+
+#[test]
+fn test_compressed_strings() {
+    let refv: Vec<String> = (0..5000).map(|i| format!("event-{}", i % 50)).collect();
+    let mut arr: CompressedVector<String> = refv.iter().cloned().collect();
+    assert!(arr.iter().map(|s| s.unwrap()).eq(refv.iter().cloned()));
+
+    arr.update(10, "updated".to_string()).unwrap();
+    assert_eq!(arr.get(10).unwrap(), "updated");
+    assert_eq!(arr.remove(10).unwrap(), "updated");
+    assert_eq!(arr.get(10).unwrap(), refv[11]);
+}
@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+
+use super::{kv::Bloom, measured::MeasuredVector, Vector};
+use crate::{Error, Measure, Op, OpId, OpKind, Result};
+
+// item in the sequence, left behind as a tombstone once removed, so that
+// concurrent inserts after it can still be placed.
+#[derive(Clone)]
+struct Atom<T> {
+    id: OpId,
+    value: Option<T>,
+}
+
+// number of items not removed, and bloom filter over the identities of all
+// items, under a node.
+#[derive(Clone, Default)]
+struct AtomMeasure {
+    visible: usize,
+    ids: Bloom,
+}
+
+impl<T> Measure<Atom<T>> for AtomMeasure {
+    fn from_item(atom: &Atom<T>) -> Self {
+        AtomMeasure {
+            visible: usize::from(atom.value.is_some()),
+            ids: Bloom::build(std::iter::once(Bloom::hash_key(&atom.id))),
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        AtomMeasure {
+            visible: self.visible + other.visible,
+            ids: self.ids.join(&other.ids),
+        }
+    }
+
+    // filter over identities is sized to the number of items under a node.
+    fn from_items<'a, I>(atoms: I) -> Option<Self>
+    where
+        Atom<T>: 'a,
+        I: Iterator<Item = &'a Atom<T>>,
+    {
+        let mut visible = 0;
+        let ids = Bloom::build(atoms.map(|atom| {
+            visible += usize::from(atom.value.is_some());
+            Bloom::hash_key(&atom.id)
+        }));
+        Some(AtomMeasure { visible, ids })
+    }
+
+    fn update(&mut self, old: Option<&Atom<T>>, new: Option<&Atom<T>>) -> bool {
+        let visible =
+            |atom: Option<&Atom<T>>| atom.map_or(0, |a| usize::from(a.value.is_some()));
+        self.visible = self.visible + visible(new) - visible(old);
+        let id = |atom: Option<&Atom<T>>| atom.map(|a| Bloom::hash_key(&a.id));
+        self.ids.replace(id(old), id(new))
+    }
+}
+
+/// Replicated sequence, a conflict free replicated data type using the
+/// Replicated Growable Array (RGA) algorithm.
+///
+/// Every item is identified by the [OpId] of the operation that inserted
+/// it. Edits made locally, via [RgaVector::local_insert] and
+/// [RgaVector::local_remove], return an [Op] to be shipped to other
+/// replicas, which apply it via [RgaVector::remote_apply]. Replicas that
+/// have applied the same set of operations hold the same sequence,
+/// irrespective of the order in which they received them.
+///
+/// Items are held in a [MeasuredVector], in sequence order, whose nodes
+/// cache the number of items not removed, and a bloom filter over item
+/// identities sized to the number of items under them. Hence locating an
+/// item by its index, or by its identity, visits O(log n) nodes instead of
+/// scanning all items. Removed items are kept as
+/// tombstones and are never collected.
+#[derive(Clone)]
+pub struct RgaVector<T> {
+    replica: u64,
+    // Lamport clock, highest counter seen so far.
+    clock: u64,
+    atoms: MeasuredVector<Atom<T>, AtomMeasure>,
+    // highest sequence number applied, per replica.
+    version: BTreeMap<u64, u64>,
+    // operations applied, in causal order.
+    log: Vec<Op<T>>,
+    // operations received before the operations they depend on.
+    pending: Vec<Op<T>>,
+}
+
+impl<T> RgaVector<T>
+where
+    T: Clone,
+{
+    /// Create an empty sequence for `replica`, which shall be unique among
+    /// the replicas exchanging operations.
+    pub fn new(replica: u64) -> RgaVector<T> {
+        RgaVector {
+            replica,
+            clock: 0,
+            atoms: MeasuredVector::default(),
+            version: BTreeMap::new(),
+            log: vec![],
+            pending: vec![],
+        }
+    }
+
+    /// Return the replica identifier.
+    pub fn replica(&self) -> u64 {
+        self.replica
+    }
+
+    /// Return the number of items, excluding removed items.
+    pub fn len(&self) -> usize {
+        self.atoms.total().visible
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the item at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let off = self.to_offset(index)?;
+        self.atoms.get(off).ok()?.value.as_ref()
+    }
+
+    /// Return an iterator over items.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.atoms.iter().filter_map(|atom| atom.value.as_ref())
+    }
+
+    /// Return the items as a vector.
+    pub fn to_vector(&self) -> Vector<T> {
+        self.iter().cloned().collect()
+    }
+
+    /// Return the highest sequence number applied, per replica. Pass this
+    /// to [RgaVector::export] on another replica, to learn the operations
+    /// missing here.
+    pub fn version(&self) -> &BTreeMap<u64, u64> {
+        &self.version
+    }
+
+    /// Return the number of operations received, that are waiting on the
+    /// operations they depend on.
+    pub fn n_pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Insert `value` at `index`, and return the operation to be shipped
+    /// to other replicas, or `IndexOutOfBounds` error.
+    pub fn local_insert(&mut self, index: usize, value: T) -> Result<Op<T>> {
+        let origin = match index {
+            0 => None,
+            index if index <= self.len() => {
+                let off = self.to_offset(index - 1).unwrap();
+                Some(self.atoms.get(off).unwrap().id)
+            }
+            index => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            })?,
+        };
+
+        let op = self.next_op(OpKind::Insert { origin, value });
+        self.integrate(op.clone());
+        Ok(op)
+    }
+
+    /// Remove the item at `index`, and return the operation to be shipped
+    /// to other replicas, or `IndexOutOfBounds` error.
+    pub fn local_remove(&mut self, index: usize) -> Result<Op<T>> {
+        let target = match self.to_offset(index) {
+            Some(off) => self.atoms.get(off).unwrap().id,
+            None => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            })?,
+        };
+
+        let op = self.next_op(OpKind::Remove { target });
+        self.integrate(op.clone());
+        Ok(op)
+    }
+
+    /// Apply `op` received from another replica, and return the number of
+    /// operations applied. Operations can be received in any order, and
+    /// more than once. An operation received before the operations it
+    /// depends on, that is, earlier operations from the same replica, and
+    /// the item it inserts after or removes, is held back until they are
+    /// applied.
+    pub fn remote_apply(&mut self, op: Op<T>) -> usize {
+        if self.is_applied(&op) {
+            return 0;
+        }
+        self.pending.push(op);
+
+        let mut n = 0;
+        loop {
+            let version = &self.version;
+            self.pending
+                .retain(|op| op.seq > version.get(&op.id.replica).copied().unwrap_or(0));
+            match self.pending.iter().position(|op| self.is_ready(op)) {
+                Some(i) => {
+                    let op = self.pending.swap_remove(i);
+                    self.integrate(op);
+                    n += 1;
+                }
+                None => break n,
+            }
+        }
+    }
+
+    /// Return the operations applied on this replica, and missing on a
+    /// replica at `version`, refer [RgaVector::version], in causal order.
+    /// Pass an empty version to export all operations.
+    pub fn export(&self, version: &BTreeMap<u64, u64>) -> Vec<Op<T>> {
+        self.log
+            .iter()
+            .filter(|op| op.seq > version.get(&op.id.replica).copied().unwrap_or(0))
+            .cloned()
+            .collect()
+    }
+
+    fn next_op(&mut self, kind: OpKind<T>) -> Op<T> {
+        let id = OpId {
+            counter: self.clock + 1,
+            replica: self.replica,
+        };
+        let seq = self.version.get(&self.replica).copied().unwrap_or(0) + 1;
+        Op { id, seq, kind }
+    }
+
+    fn is_applied(&self, op: &Op<T>) -> bool {
+        op.seq <= self.version.get(&op.id.replica).copied().unwrap_or(0)
+    }
+
+    fn is_ready(&self, op: &Op<T>) -> bool {
+        let seq = self.version.get(&op.id.replica).copied().unwrap_or(0);
+        op.seq == seq + 1
+            && match &op.kind {
+                OpKind::Insert { origin: None, .. } => true,
+                OpKind::Insert {
+                    origin: Some(id), ..
+                } => self.find(id).is_some(),
+                OpKind::Remove { target } => self.find(target).is_some(),
+            }
+    }
+
+    // apply `op`, whose dependencies are already applied.
+    fn integrate(&mut self, op: Op<T>) {
+        match &op.kind {
+            OpKind::Insert { origin, value } => {
+                // skip items inserted concurrently after the same origin,
+                // and their successors, that win over this item.
+                let mut off = match origin {
+                    Some(id) => self.find(id).unwrap() + 1,
+                    None => 0,
+                };
+                while let Ok(atom) = self.atoms.get(off) {
+                    match atom.id > op.id {
+                        true => off += 1,
+                        false => break,
+                    }
+                }
+                let atom = Atom {
+                    id: op.id,
+                    value: Some(value.clone()),
+                };
+                self.atoms.insert(off, atom).unwrap();
+            }
+            OpKind::Remove { target } => {
+                let off = self.find(target).unwrap();
+                let id = self.atoms.get(off).unwrap().id;
+                self.atoms.update(off, Atom { id, value: None }).unwrap();
+            }
+        }
+
+        self.clock = std::cmp::max(self.clock, op.id.counter);
+        self.version.insert(op.id.replica, op.seq);
+        self.log.push(op);
+    }
+
+    // offset of the item at `index`, among all items including removed.
+    fn to_offset(&self, index: usize) -> Option<usize> {
+        let (off, _) = self.atoms.search_by_measure(|m| m.visible > index)?;
+        Some(off)
+    }
+
+    // offset of the item identified by `id`.
+    fn find(&self, id: &OpId) -> Option<usize> {
+        let h = Bloom::hash_key(id);
+        let (off, _) = self
+            .atoms
+            .find_by_measure(|m| m.ids.may_contain(h), |atom| atom.id == *id)?;
+        Some(off)
+    }
+}
+
+#[cfg(test)]
+#[path = "crdt_test.rs"]
+mod crdt_test;
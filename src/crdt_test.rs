@@ -0,0 +1,175 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::collections::BTreeMap;
+
+use super::*;
+
+fn local_edit(
+    rng: &mut SmallRng,
+    arr: &mut RgaVector<u64>,
+    refv: &mut Vec<u64>,
+) -> Op<u64> {
+    match rng.gen::<u8>() % 3 {
+        0 | 1 => {
+            let (index, value) = (rng.gen::<usize>() % (refv.len() + 1), rng.gen());
+            refv.insert(index, value);
+            arr.local_insert(index, value).unwrap()
+        }
+        _ if refv.is_empty() => {
+            refv.push(10);
+            arr.local_insert(0, 10).unwrap()
+        }
+        _ => {
+            let index = rng.gen::<usize>() % refv.len();
+            refv.remove(index);
+            arr.local_remove(index).unwrap()
+        }
+    }
+}
+
+#[test]
+fn test_rga_local() {
+    let seed: u128 = random();
+    println!("test_rga_local seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: RgaVector<u64> = RgaVector::new(1);
+    let mut refv = vec![];
+    for _ in 0..10_000 {
+        local_edit(&mut rng, &mut arr, &mut refv);
+    }
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.iter().eq(refv.iter()));
+    assert!(arr.to_vector().iter().eq(refv.iter()));
+    for (index, val) in refv.iter().enumerate() {
+        assert_eq!(arr.get(index), Some(val));
+    }
+    assert_eq!(arr.get(refv.len()), None);
+
+    let err = Error::IndexOutOfBounds {
+        index: refv.len() + 1,
+        len: refv.len(),
+    };
+    assert_eq!(arr.local_insert(refv.len() + 1, 0).err(), Some(err));
+    assert!(arr.local_remove(refv.len()).is_err());
+}
+
+#[test]
+fn test_rga_converge() {
+    let seed: u128 = random();
+    println!("test_rga_converge seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let n_replicas = 3;
+    let mut replicas: Vec<RgaVector<u64>> = (0..n_replicas).map(RgaVector::new).collect();
+
+    for _round in 0..20 {
+        // concurrent edits on every replica.
+        let mut ops = vec![];
+        for arr in replicas.iter_mut() {
+            let mut refv: Vec<u64> = arr.iter().cloned().collect();
+            for _ in 0..(rng.gen::<usize>() % 50) {
+                ops.push(local_edit(&mut rng, arr, &mut refv));
+            }
+            assert!(arr.iter().eq(refv.iter()));
+        }
+
+        // deliver every operation to every replica, shuffled and some of
+        // them twice.
+        for arr in replicas.iter_mut() {
+            let mut ops = ops.clone();
+            for _ in 0..ops.len() {
+                let (i, j) = (
+                    rng.gen::<usize>() % ops.len(),
+                    rng.gen::<usize>() % ops.len(),
+                );
+                ops.swap(i, j);
+            }
+            ops.extend(ops[..(ops.len() / 4)].to_vec());
+            for op in ops.into_iter() {
+                arr.remote_apply(op);
+            }
+            assert_eq!(arr.n_pending(), 0);
+        }
+
+        let vals: Vec<u64> = replicas[0].iter().cloned().collect();
+        for arr in replicas.iter().skip(1) {
+            assert!(arr.iter().eq(vals.iter()));
+            assert_eq!(arr.version(), replicas[0].version());
+        }
+    }
+
+    // a new replica catches up by exporting from one of the replicas.
+    let mut arr = RgaVector::new(10);
+    for op in replicas[1].export(arr.version()).into_iter() {
+        assert_eq!(arr.remote_apply(op), 1);
+    }
+    assert!(arr.iter().eq(replicas[0].iter()));
+    assert!(replicas[1].export(arr.version()).is_empty());
+
+    let ops = replicas[2].export(&BTreeMap::new());
+    assert_eq!(ops.len(), replicas[2].export(&BTreeMap::new()).len());
+}
+
+#[test]
+fn test_rga_concurrent_inserts() {
+    let (mut a, mut b) = (RgaVector::new(1), RgaVector::new(2));
+    let op = a.local_insert(0, 'x').unwrap();
+    b.remote_apply(op);
+
+    // both replicas insert after 'x', concurrently.
+    let opa = a.local_insert(1, 'a').unwrap();
+    let opb = b.local_insert(1, 'b').unwrap();
+    assert_eq!(a.remote_apply(opb.clone()), 1);
+    assert_eq!(b.remote_apply(opa), 1);
+    assert_eq!(b.remote_apply(opb), 0);
+
+    let vals: String = a.iter().collect();
+    assert_eq!(vals, "xba");
+    assert!(a.iter().eq(b.iter()));
+
+    // remove received before the insert it removes.
+    let mut c = RgaVector::new(3);
+    let ops = a.export(&BTreeMap::new());
+    let op = a.local_remove(0).unwrap();
+    assert_eq!(c.remote_apply(op), 0);
+    assert_eq!(c.n_pending(), 1);
+    for op in ops.into_iter() {
+        c.remote_apply(op);
+    }
+    assert_eq!(c.n_pending(), 0);
+    assert_eq!(c.iter().collect::<String>(), "ba");
+}
+
+#[test]
+fn test_rga_find_visits() {
+    use std::cell::Cell;
+
+    let seed: u128 = random();
+    println!("test_rga_find_visits seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: RgaVector<u64> = RgaVector::new(1);
+    let mut refv = vec![];
+    for _ in 0..50_000 {
+        local_edit(&mut rng, &mut arr, &mut refv);
+    }
+    let ids: Vec<OpId> = arr.atoms.iter().map(|atom| atom.id).collect();
+
+    // number of nodes visited to locate an item by its identity, filters
+    // of nodes skipped are not probed.
+    let mut total = 0;
+    for _ in 0..1000 {
+        let id = ids[rng.gen::<usize>() % ids.len()];
+        let (n, h) = (Cell::new(0), Bloom::hash_key(&id));
+        let may_contain = |m: &AtomMeasure| {
+            n.set(n.get() + 1);
+            m.ids.may_contain(h)
+        };
+        let res = arr.atoms.find_by_measure(may_contain, |atom| atom.id == id);
+        assert_eq!(res.map(|(off, _)| off), arr.find(&id));
+        total += n.get();
+    }
+    println!("test_rga_find_visits {} atoms {}", ids.len(), total);
+    assert!(total < 1000 * 100, "{}", total);
+}
@@ -0,0 +1,686 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io, mem,
+};
+
+use super::*;
+use crate::{
+    ipld::{
+        self, Block, Blockstore, Cid, Codec, Decode, Decoded, Encode, Ipld, Multihash,
+    },
+    Error, Result,
+};
+
+impl<T> Vector<T>
+where
+    T: Encode,
+{
+    /// Encode this vector as a DAG of blocks, one block per tree node,
+    /// refer [crate::ipld] for the layout of a block. Blocks are encoded
+    /// with `codec`, and addressed by their digest using `hash`. Interior
+    /// nodes link to their children by CID, and the root block, first in
+    /// the returned list, links to the root and the tail of this vector.
+    /// Rest of the blocks follow in depth first order, children before
+    /// their parent.
+    ///
+    /// Identical nodes encode to the same block, which is returned once.
+    /// Fails only for `Codec::DagJson`, if items encode to DAG-CBOR that
+    /// has no DAG-JSON form, like floats.
+    pub fn encode(&self, codec: Codec, hash: Multihash) -> Result<Vec<Block>> {
+        let mut blocks = vec![];
+        self.encode_with(codec, hash, &mut |block| {
+            blocks.push(block);
+            Ok(())
+        })?;
+        // root block comes last from the encoder.
+        blocks.rotate_right(1);
+        Ok(blocks)
+    }
+
+    /// Encode this vector like [Vector::encode], handing over each block to
+    /// `sink` as soon as it is encoded, instead of collecting them. Blocks
+    /// are handed over in depth first order, children before their parent,
+    /// with the root block last. Return the CID of the root block.
+    ///
+    /// Memory used is bounded by the depth of the tree, and the CIDs of
+    /// nodes encoded so far, which suits exporting vectors larger than RAM.
+    pub fn encode_stream<F>(
+        &self,
+        codec: Codec,
+        hash: Multihash,
+        mut sink: F,
+    ) -> Result<Cid>
+    where
+        F: FnMut(Cid, &[u8]),
+    {
+        self.encode_with(codec, hash, &mut |block| {
+            sink(block.cid, &block.data);
+            Ok(())
+        })
+    }
+
+    /// Encode this vector like [Vector::encode_stream], writing each block
+    /// into `w`, as its length, CID and data, refer [ipld::write_block].
+    /// Return the CID of the root block, or `IOError`.
+    pub fn write_blocks<W>(&self, codec: Codec, hash: Multihash, w: &mut W) -> Result<Cid>
+    where
+        W: io::Write,
+    {
+        self.encode_with(codec, hash, &mut |block| ipld::write_block(w, &block))
+    }
+
+    /// Save this vector into `store`, as DAG-CBOR blocks addressed using
+    /// SHA-256, and return the CID of the root block. Refer
+    /// [Vector::save_with] to choose the codec and the hash function.
+    pub fn save(&self, store: &mut dyn Blockstore) -> Result<Cid> {
+        self.save_with(store, Codec::DagCbor, Multihash::Sha2_256)
+    }
+
+    /// Save this vector into `store`, as blocks encoded with `codec` and
+    /// addressed using `hash`, refer [Vector::encode], and return the CID
+    /// of the root block. Blocks are put into the store as they are
+    /// encoded, and then pinned, refer [Blockstore::pin].
+    pub fn save_with(
+        &self,
+        store: &mut dyn Blockstore,
+        codec: Codec,
+        hash: Multihash,
+    ) -> Result<Cid> {
+        let mut cids = vec![];
+        let root = self.encode_with(codec, hash, &mut |block| {
+            cids.push(block.cid);
+            store.put(block).map(|_| ())
+        })?;
+        cids.rotate_right(1);
+        store.pin(&root, &cids)?;
+        Ok(root)
+    }
+
+    /// Save this vector like [Vector::save], and sign the CID of its root
+    /// block using `signer`. Return the CID of the signed root block,
+    /// refer [ipld::sign_root], to be loaded by [Vector::load_signed].
+    pub fn save_signed(
+        &self,
+        store: &mut dyn Blockstore,
+        signer: &dyn ipld::Signer,
+    ) -> Result<Cid> {
+        let root = self.save(store)?;
+        ipld::sign_root(&root, signer, store)
+    }
+
+    // encode this vector, root block last, stopping at the first error
+    // from `sink`.
+    fn encode_with(
+        &self,
+        codec: Codec,
+        hash: Multihash,
+        sink: &mut dyn FnMut(Block) -> Result<()>,
+    ) -> Result<Cid> {
+        self.encode_nodes(codec, hash, sink, &mut HashMap::default())
+    }
+
+    /// Save this vector into `store`, like [Vector::save_with], encoding
+    /// and putting only the nodes created since the last flush recorded
+    /// in `flushed`. Subtrees already flushed, or loaded by
+    /// [Vector::load_flushed], are linked by their CIDs, which is cheap
+    /// for a vector edited in a few places, and for its clones. Return
+    /// the CID of the root block.
+    ///
+    /// Blocks are not pinned, since subtrees already flushed are not
+    /// walked, refer [ipld::pin].
+    pub fn flush(
+        &self,
+        store: &mut dyn Blockstore,
+        flushed: &mut Flushed<T>,
+    ) -> Result<Cid> {
+        flushed.cids.retain(|_, (node, _)| node.strong_count() > 0);
+        let (codec, hash) = (flushed.codec, flushed.hash);
+        let mut sink = |block| store.put(block).map(|_| ());
+        self.encode_nodes(codec, hash, &mut sink, &mut flushed.cids)
+    }
+
+    fn encode_nodes(
+        &self,
+        codec: Codec,
+        hash: Multihash,
+        sink: &mut dyn FnMut(Block) -> Result<()>,
+        cids: &mut Cids<T>,
+    ) -> Result<Cid> {
+        let mut enc = Encoder {
+            codec,
+            hash,
+            sink,
+            seen: HashSet::default(),
+            cids,
+        };
+
+        let root = enc.root(self)?;
+        let cid = root.cid;
+        (enc.sink)(root)?;
+        Ok(cid)
+    }
+
+    /// Return the proof that the item at `index` is included in this
+    /// vector, as saved by [Vector::save], refer [ipld::Proof]. Every node
+    /// is hashed, to link the path to the root block, hence to prove items
+    /// of a vector already saved use [ipld::prove], that reads only the
+    /// blocks on the path.
+    pub fn prove(&self, index: usize) -> Result<ipld::Proof> {
+        if index >= self.len {
+            Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len,
+            })?
+        }
+
+        let (codec, hash) = (Codec::DagCbor, Multihash::Sha2_256);
+        let mut enc = Encoder {
+            codec,
+            hash,
+            sink: &mut |_| Ok(()),
+            seen: HashSet::default(),
+            cids: &mut HashMap::default(),
+        };
+        let mut blocks = vec![enc.root(self)?];
+
+        let root_len = self.root_len();
+        let (mut node, mut off) = match &self.root {
+            Root::Tree { root, .. } if index < root_len => (root.as_ref(), index),
+            Root::Tree { tail, .. } => (tail.as_ref(), index - root_len),
+            Root::Inline(_) => {
+                let data = ipld::encode_leaf(self.tail_items());
+                blocks.push(ipld::block(codec, hash, data)?);
+                return Ok(ipld::Proof { blocks });
+            }
+        };
+        loop {
+            blocks.push(ipld::block(codec, hash, enc.data(node))?);
+            match node {
+                Node::Z { .. } => break,
+                _ => {
+                    let (i, n) = node.locate_child(off);
+                    off -= n;
+                    node = node.child(i).as_ref();
+                }
+            }
+        }
+        Ok(ipld::Proof { blocks })
+    }
+
+    /// Write this vector into `w` as a CARv1 archive, of DAG-CBOR blocks
+    /// addressed using SHA-256, with the root block as the only root, and
+    /// return the CID of the root block, or `IOError`.
+    ///
+    /// Blocks are streamed into `w` as they are encoded, children before
+    /// their parent, refer [Vector::encode_stream]. Since the archive
+    /// begins with the CID of the root block, this vector is hashed twice,
+    /// to write the archive header first.
+    pub fn export_car<W>(&self, w: &mut W) -> Result<Cid>
+    where
+        W: io::Write,
+    {
+        let (codec, hash) = (Codec::DagCbor, Multihash::Sha2_256);
+        let root = self.encode_with(codec, hash, &mut |_| Ok(()))?;
+        ipld::write_car_header(w, &[root])?;
+        self.write_blocks(codec, hash, w)
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: Encode,
+{
+    /// Save this vector into `store` in the AMT layout of IPLD vectors,
+    /// with nodes of `2^bit_width` slots, and return the CID of the root
+    /// block, refer [ipld::save_amt]. Blocks are laid out the same as by
+    /// other implementations of the AMT, like go-amt-ipld, for vectors to
+    /// be exchanged with them, refer [Vector::load_amt].
+    ///
+    /// Unlike the layout of [Vector::save], nodes are full and indexed by
+    /// position, hence an insert or a remove changes every node after it,
+    /// and versions share blocks only up to their first difference.
+    pub fn save_amt(&self, store: &mut dyn Blockstore, bit_width: usize) -> Result<Cid> {
+        ipld::save_amt(self.iter(), bit_width, store)
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: Encode + Clone,
+{
+    /// Return a copy of this vector, with leaf nodes cut at content-defined
+    /// boundaries, found by `chunker` over the encoded items, refer
+    /// [ipld::Chunker]. Leaf nodes are cut only between items.
+    ///
+    /// Leaf nodes of the copy depend only on the items, and not on the
+    /// edits that led to them. Hence vectors with similar content, like
+    /// a document built afresh from a file after every change, share the
+    /// blocks of leaf nodes away from their differences once saved, while
+    /// leaf nodes cut by count shift with every insert and remove before
+    /// them. Interior nodes on the path to differing leaf nodes are not
+    /// shared.
+    ///
+    /// Leaf node size of the copy is large enough to hold the largest
+    /// chunk, and the tree is built bottoms-up, fully balanced.
+    pub fn rechunk(&self, chunker: &ipld::Chunker) -> Vector<T> {
+        let mut roll = chunker.roller();
+        let (mut blocks, mut data, mut buf) = (vec![], vec![], vec![]);
+        for item in self.iter() {
+            buf.clear();
+            item.encode(&mut buf);
+            data.push(item.clone());
+            if roll(&buf) {
+                blocks.push(mem::take(&mut data));
+            }
+        }
+        blocks.push(data);
+
+        let k = std::cmp::max(mem::size_of::<T>(), 1);
+        let leaf_cap = std::cmp::max(self.leaf_cap, chunker.max_size() * k);
+        Vector::from_leaf_blocks(blocks, Some(leaf_cap))
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: Decode,
+{
+    /// Load a vector saved in the AMT layout of IPLD vectors, from its root
+    /// block `cid` in `store`, refer [ipld::load_amt].
+    pub fn load_amt(cid: &Cid, store: &dyn Blockstore) -> Result<Vector<T>> {
+        Ok(ipld::load_amt(cid, store)?.into_iter().collect())
+    }
+
+    /// Load a vector saved by [Vector::save], from its root block `cid` in
+    /// `store`. Blocks are verified against their CID, and decoded as per
+    /// the codec in their CID. Nodes shared within the tree are decoded
+    /// once and shared by the loaded vector.
+    ///
+    /// Return `NotFound` if a block is missing from `store`, and
+    /// `Corrupted` if a block fails to decode or breaks the tree layout.
+    pub fn load(cid: &Cid, store: &dyn Blockstore) -> Result<Vector<T>> {
+        Ok(Vector::load_nodes(cid, store)?.0)
+    }
+
+    /// Load a vector saved by [Vector::save_signed], from its signed root
+    /// block `cid` in `store`, after verifying the signature using
+    /// `verifier`, refer [ipld::verify_root]. Blocks are then verified
+    /// against their CID, hence the loaded vector is the one signed.
+    pub fn load_signed(
+        cid: &Cid,
+        store: &dyn Blockstore,
+        verifier: &dyn ipld::Verifier,
+    ) -> Result<Vector<T>> {
+        let root = ipld::verify_root(cid, verifier, store)?;
+        Vector::load(&root, store)
+    }
+
+    /// Load a vector like [Vector::load], and pin its blocks, refer
+    /// [Blockstore::pin].
+    pub fn load_pinned(cid: &Cid, store: &mut dyn Blockstore) -> Result<Vector<T>> {
+        let (arr, nodes) = Vector::load_nodes(cid, store)?;
+        let mut cids = vec![*cid];
+        cids.extend(nodes.keys().copied());
+        store.pin(cid, &cids)?;
+        Ok(arr)
+    }
+
+    /// Load a vector like [Vector::load], recording its nodes in `flushed`,
+    /// so that a [Vector::flush] after edits puts only the nodes created
+    /// since. Nodes are recorded only if their blocks are encoded with the
+    /// codec and the hash function of `flushed`.
+    pub fn load_flushed(
+        cid: &Cid,
+        store: &dyn Blockstore,
+        flushed: &mut Flushed<T>,
+    ) -> Result<Vector<T>> {
+        let (arr, nodes) = Vector::load_nodes(cid, store)?;
+        let (codec, hash) = (flushed.codec.code(), flushed.hash.code());
+        for (cid, node) in nodes.into_iter() {
+            if cid.codec() == codec && cid.hash() == hash {
+                let val = (Ref::downgrade(&node), cid);
+                flushed.cids.insert(Ref::as_ptr(&node), val);
+            }
+        }
+        Ok(arr)
+    }
+
+    /// Read a vector from a CARv1 archive in `r`, like the one written by
+    /// [Vector::export_car], with a single root. Blocks are read into
+    /// memory, and then decoded, refer [ipld::import_car] to import blocks
+    /// into a blockstore.
+    ///
+    /// Return `Corrupted` if the archive is malformed, or does not hold a
+    /// single vector, `NotFound` if a block is missing from the archive,
+    /// and `IOError` if `r` fails.
+    pub fn import_car<R>(r: &mut R) -> Result<Vector<T>>
+    where
+        R: io::Read,
+    {
+        let mut store = ipld::MemoryStore::new();
+        match ipld::import_car(r, &mut store)?.as_slice() {
+            [root] => Vector::load(root, &store),
+            roots => {
+                let detail = format!("car with {} roots, want 1", roots.len());
+                Err(Error::Corrupted { detail })
+            }
+        }
+    }
+
+    // load a vector, along with its nodes decoded by CID.
+    fn load_nodes(cid: &Cid, store: &dyn Blockstore) -> Result<(Vector<T>, Nodes<T>)> {
+        let (len, links) = match ipld::decode_node::<T>(&ipld::fetch(store, cid)?)? {
+            Decoded::Interior { len, links } if links.len() <= 2 => (len, links),
+            _ => Err(corrupted(cid, "root block is not over root and tail"))?,
+        };
+
+        let mut dec = Decoder {
+            store,
+            nodes: HashMap::default(),
+            max_items: 0,
+        };
+        let mut nodes = vec![];
+        for (_, cid) in links.iter() {
+            nodes.push(dec.node(cid)?);
+        }
+        check_counts(cid, len, &links, &nodes)?;
+
+        let mut nodes = nodes.into_iter();
+        let root = match (nodes.next(), nodes.next()) {
+            (Some(root), Some(tail)) if matches!(tail.as_ref(), Node::Z { .. }) => {
+                Root::Tree { root, tail }
+            }
+            (Some(_), Some(_)) => Err(corrupted(cid, "tail is not a leaf node"))?,
+            (Some(root), None) => Vector::split_tail(root),
+            (None, _) => Root::Inline(vec![]),
+        };
+
+        let k = std::cmp::max(mem::size_of::<T>(), 1);
+        let arr = Vector {
+            len,
+            root,
+            auto_rebalance: true,
+            strict_balance: false,
+            leaf_cap: std::cmp::max(crate::LEAF_CAP, dec.max_items * k),
+            fanout: crate::FANOUT,
+            max_depth: None,
+        };
+        Ok((arr, dec.nodes))
+    }
+}
+
+impl Vector<u8> {
+    /// Save the bytes of this vector into `store` as a UnixFS file, with
+    /// each leaf node as a chunk, and return the CID of its root block,
+    /// refer [ipld::save_unixfs]. Leaf nodes untouched since the vector was
+    /// loaded by [Vector::load_unixfs] are saved into the same blocks.
+    ///
+    /// To chunk bytes the same as `ipfs add`, build the vector with a leaf
+    /// node size of 256KB, like `Vector::from_slice(&bytes, Some(256 *
+    /// 1024))`, or cut leaf nodes at content-defined boundaries, refer
+    /// [Vector::rechunk].
+    pub fn save_unixfs(&self, store: &mut dyn Blockstore) -> Result<Cid> {
+        let mut chunks = vec![];
+        let mut index = 0;
+        while index < self.len {
+            let (leaf, off) = self.leaf_of(index)?;
+            chunks.push(leaf);
+            index = off + leaf.len();
+        }
+        ipld::save_unixfs(chunks, store)
+    }
+
+    /// Load the bytes of a UnixFS file, from its root block `cid` in
+    /// `store`, refer [ipld::load_unixfs]. Each chunk of the file is moved
+    /// into the vector as a leaf node, and the leaf node size is large
+    /// enough to hold the largest chunk.
+    pub fn load_unixfs(cid: &Cid, store: &dyn Blockstore) -> Result<Vector<u8>> {
+        let chunks = ipld::load_unixfs(cid, store)?;
+        let n = chunks.iter().map(|c| c.len()).max().unwrap_or(0);
+        let leaf_cap = std::cmp::max(crate::LEAF_CAP, n);
+        Ok(Vector::from_leaf_blocks(chunks, Some(leaf_cap)))
+    }
+}
+
+/// Vector of IPLD values as a value of the list kind, refer [Ipld::List].
+impl From<Vector<Ipld>> for Ipld {
+    fn from(val: Vector<Ipld>) -> Ipld {
+        Ipld::List(val.into())
+    }
+}
+
+/// CIDs of nodes flushed into a blockstore, refer [Vector::flush].
+///
+/// Nodes are tracked by address, without keeping them alive, and are
+/// forgotten once dropped. Blocks are encoded with `codec`, and addressed
+/// using `hash`, same as [Vector::save_with].
+pub struct Flushed<T> {
+    codec: Codec,
+    hash: Multihash,
+    cids: Cids<T>,
+}
+
+impl<T> Default for Flushed<T> {
+    fn default() -> Flushed<T> {
+        Flushed::new(Codec::DagCbor, Multihash::Sha2_256)
+    }
+}
+
+impl<T> Flushed<T> {
+    /// Create an empty record, for blocks encoded with `codec` and
+    /// addressed using `hash`.
+    pub fn new(codec: Codec, hash: Multihash) -> Flushed<T> {
+        Flushed {
+            codec,
+            hash,
+            cids: HashMap::default(),
+        }
+    }
+
+    /// Return the number of nodes recorded as flushed, including nodes
+    /// since dropped.
+    pub fn len(&self) -> usize {
+        self.cids.len()
+    }
+
+    /// Return whether no nodes are recorded as flushed.
+    pub fn is_empty(&self) -> bool {
+        self.cids.is_empty()
+    }
+}
+
+// CIDs of encoded nodes, by address. The weak reference keeps the
+// allocation, hence the address, from being reused by another node.
+type Cids<T> = HashMap<*const Node<T>, (Weak<Node<T>>, Cid)>;
+
+// decoded nodes, by CID.
+type Nodes<T> = HashMap<Cid, Ref<Node<T>>>;
+
+struct Encoder<'a, T> {
+    codec: Codec,
+    hash: Multihash,
+    sink: &'a mut dyn FnMut(Block) -> Result<()>,
+    seen: HashSet<Cid>,
+    // nodes shared within the tree, like deduplicated leaf nodes, are
+    // encoded once, and nodes already flushed are not encoded.
+    cids: &'a mut Cids<T>,
+}
+
+impl<'a, T> Encoder<'a, T>
+where
+    T: Encode,
+{
+    // encode the subtree under `node`, children before their parent, using
+    // an explicit stack, since trees that have gone deep, with
+    // auto-rebalance disabled, would overflow the call stack.
+    fn node(&mut self, node: &Ref<Node<T>>) -> Result<Cid> {
+        let mut stack = vec![(node, false)];
+        while let Some((node, visited)) = stack.pop() {
+            let key = Ref::as_ptr(node);
+            if self.cids.contains_key(&key) {
+                continue;
+            } else if !visited {
+                stack.push((node, true));
+                match node.as_ref() {
+                    Node::M { left, right, .. } => {
+                        stack.push((right, false));
+                        stack.push((left, false));
+                    }
+                    Node::N { children, .. } => {
+                        stack.extend(children.iter().rev().map(|(_, c)| (c, false)))
+                    }
+                    Node::Z { .. } => (),
+                }
+                continue;
+            }
+
+            let block = ipld::block(self.codec, self.hash, self.data(node))?;
+            let cid = block.cid;
+            if self.seen.insert(cid) {
+                (self.sink)(block)?;
+            }
+            self.cids.insert(key, (Ref::downgrade(node), cid));
+        }
+        Ok(self.cid(node))
+    }
+
+    // encode the subtrees of `arr`, and return its root block, over the
+    // root and the tail of `arr`.
+    fn root(&mut self, arr: &Vector<T>) -> Result<Block> {
+        let mut links = vec![];
+        let mut count = 0;
+        for node in arr.tree_nodes().into_iter() {
+            if node.len() > 0 {
+                count += node.len();
+                links.push((count, self.node(node)?));
+            }
+        }
+        if let Root::Inline(data) = &arr.root {
+            if !data.is_empty() {
+                let block = ipld::block(self.codec, self.hash, ipld::encode_leaf(data))?;
+                let cid = block.cid;
+                if self.seen.insert(cid) {
+                    (self.sink)(block)?;
+                }
+                links.push((data.len(), cid));
+            }
+        }
+
+        let data = ipld::encode_interior(arr.len, &links);
+        ipld::block(self.codec, self.hash, data)
+    }
+
+    // encode `node`, whose children are already encoded.
+    fn data(&self, node: &Node<T>) -> Vec<u8> {
+        match node {
+            Node::M {
+                weight,
+                left,
+                right,
+                ..
+            } => {
+                let links =
+                    vec![(*weight, self.cid(left)), (node.len(), self.cid(right))];
+                ipld::encode_interior(node.len(), &links)
+            }
+            Node::N { children, .. } => {
+                let links: Vec<(usize, Cid)> = children
+                    .iter()
+                    .map(|(count, child)| (*count, self.cid(child)))
+                    .collect();
+                ipld::encode_interior(node.len(), &links)
+            }
+            Node::Z { data } => ipld::encode_leaf(data),
+        }
+    }
+
+    // CID of a node already encoded.
+    fn cid(&self, node: &Ref<Node<T>>) -> Cid {
+        self.cids[&Ref::as_ptr(node)].1
+    }
+}
+
+struct Decoder<'a, T> {
+    store: &'a dyn Blockstore,
+    // nodes shared within the tree are decoded once.
+    nodes: Nodes<T>,
+    // number of items in the largest leaf node.
+    max_items: usize,
+}
+
+impl<'a, T> Decoder<'a, T>
+where
+    T: Decode,
+{
+    // decode the subtree under block `cid`, children before their parent,
+    // using an explicit stack. Blocks are verified against their CID, hence
+    // a subtree cannot link back to itself.
+    fn node(&mut self, cid: &Cid) -> Result<Ref<Node<T>>> {
+        let mut stack = vec![(*cid, None)];
+        while let Some((cid, interior)) = stack.pop() {
+            if self.nodes.contains_key(&cid) {
+                continue;
+            }
+
+            let node = match interior {
+                None => match ipld::decode_node(&ipld::fetch(self.store, &cid)?)? {
+                    Decoded::Leaf { items } => {
+                        self.max_items = std::cmp::max(self.max_items, items.len());
+                        Ref::new(Node::Z { data: items })
+                    }
+                    Decoded::Interior { len, links } => {
+                        let children: Vec<Cid> = links.iter().map(|(_, c)| *c).collect();
+                        stack.push((cid, Some((len, links))));
+                        stack.extend(children.into_iter().rev().map(|c| (c, None)));
+                        continue;
+                    }
+                },
+                Some((len, links)) => {
+                    let nodes: Vec<Ref<Node<T>>> = links
+                        .iter()
+                        .map(|(_, c)| Ref::clone(&self.nodes[c]))
+                        .collect();
+                    check_counts(&cid, len, &links, &nodes)?;
+                    match nodes.as_slice() {
+                        [left, right] => {
+                            Node::newm(Ref::clone(left), Ref::clone(right), left.len())
+                        }
+                        _ => Node::newn(nodes),
+                    }
+                }
+            };
+            self.nodes.insert(cid, node);
+        }
+        Ok(Ref::clone(&self.nodes[cid]))
+    }
+}
+
+// links of an interior block shall count the items under each child,
+// cumulatively, up to `len`.
+fn check_counts<T>(
+    cid: &Cid,
+    len: usize,
+    links: &[(usize, Cid)],
+    nodes: &[Ref<Node<T>>],
+) -> Result<()> {
+    let mut count = 0;
+    for ((n, _), node) in links.iter().zip(nodes.iter()) {
+        count += node.len();
+        if *n != count {
+            Err(corrupted(cid, &format!("link count {}, want {}", n, count)))?
+        }
+    }
+    match count {
+        count if count == len => Ok(()),
+        count => Err(corrupted(cid, &format!("len {}, want {}", len, count))),
+    }
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "dag_test.rs"]
+mod dag_test;
@@ -0,0 +1,617 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
+
+use super::*;
+use crate::ipld::MemoryStore;
+
+#[test]
+fn test_encode_empty() {
+    let arr: Vector<u64> = Vector::default();
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+
+    // {"len": 0, "links": [], "version": [1, 0]}
+    let mut refb = vec![0xa3, 0x63];
+    refb.extend_from_slice(b"len");
+    refb.extend_from_slice(&[0x00, 0x65]);
+    refb.extend_from_slice(b"links");
+    refb.extend_from_slice(&[0x80, 0x67]);
+    refb.extend_from_slice(b"version");
+    refb.extend_from_slice(&[0x82, 0x01, 0x00]);
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].data, refb);
+    assert_eq!(blocks[0], Block::new(Codec::DagCbor, refb));
+}
+
+#[test]
+fn test_encode_leaf() {
+    let mut arr: Vector<u64> = Vector::default();
+    arr.insert(0, 10).unwrap();
+    arr.insert(1, 20).unwrap();
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    assert_eq!(blocks.len(), 2);
+
+    // {"items": [10, 20], "version": [1, 0]}
+    let mut refb = vec![0xa2, 0x65];
+    refb.extend_from_slice(b"items");
+    refb.extend_from_slice(&[0x82, 0x0a, 0x14, 0x67]);
+    refb.extend_from_slice(b"version");
+    refb.extend_from_slice(&[0x82, 0x01, 0x00]);
+    assert_eq!(blocks[1].data, refb);
+
+    let mut link = vec![];
+    blocks[1].cid.encode(&mut link);
+    let root = &blocks[0].data;
+    assert!(root.windows(link.len()).any(|w| w == link.as_slice()));
+    assert_eq!(
+        blocks[0].data,
+        ipld::encode_interior(2, &[(2, blocks[1].cid)])
+    );
+}
+
+#[test]
+fn test_encode_dedup() {
+    let arr = Vector::from_slice(&[7_u64; 900], Some(64));
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+
+    let stats = arr.stats();
+    assert!(stats.n_leafs > 10, "{}", stats.n_leafs);
+    // a level of the tree holds at most two distinct subtrees, full ones
+    // and the one holding the remainder.
+    let n = blocks.len();
+    assert!(n <= 2 * arr.depth() + 2, "{} {}", n, arr.depth());
+
+    let cids: HashSet<Cid> = blocks.iter().map(|b| b.cid).collect();
+    assert_eq!(cids.len(), blocks.len());
+}
+
+#[test]
+fn test_encode() {
+    let seed: u128 = random();
+    println!("test_encode seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let items: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&items, Some(128));
+
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    assert_eq!(
+        blocks,
+        arr.clone()
+            .encode(Codec::DagCbor, Multihash::Sha2_256)
+            .unwrap()
+    );
+    assert_eq!(
+        blocks,
+        Vector::from_slice(&items, Some(128))
+            .encode(Codec::DagCbor, Multihash::Sha2_256)
+            .unwrap()
+    );
+
+    // every block, other than the root, is linked from another block.
+    let mut linked: HashSet<Cid> = HashSet::new();
+    linked.insert(blocks[0].cid);
+    for block in blocks.iter() {
+        for other in blocks.iter() {
+            let mut link = vec![];
+            other.cid.encode(&mut link);
+            if block.data.windows(link.len()).any(|w| w == link.as_slice()) {
+                linked.insert(other.cid);
+            }
+        }
+    }
+    assert_eq!(linked.len(), blocks.len());
+
+    // an edit re-encodes only the path from the leaf node to the root.
+    let old: HashSet<Cid> = blocks.iter().map(|b| b.cid).collect();
+    let off = rng.gen::<usize>() % items.len();
+    arr.update(off, items[off].wrapping_add(1)).unwrap();
+    let new: HashSet<Cid> = arr
+        .encode(Codec::DagCbor, Multihash::Sha2_256)
+        .unwrap()
+        .iter()
+        .map(|b| b.cid)
+        .collect();
+    let n_changed = new.difference(&old).count();
+    assert!(
+        n_changed <= arr.depth() + 2,
+        "{} {}",
+        n_changed,
+        arr.depth()
+    );
+    assert!(n_changed >= 2);
+}
+
+#[test]
+fn test_encode_deep() {
+    // with auto-rebalance disabled, every insert at the head of the vector
+    // deepens the tree by a level.
+    let n = 5_000;
+    let mut arr: Vector<u64> = Vector::with_leaf_size(16);
+    arr.set_auto_rebalance(false);
+    for i in 0..n {
+        arr.insert_mut(0, i).unwrap();
+    }
+    assert!(arr.depth() > 1_000, "{}", arr.depth());
+
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    let cids: HashSet<Cid> = blocks.iter().map(|b| b.cid).collect();
+    assert_eq!(cids.len(), blocks.len());
+    assert_eq!(
+        blocks,
+        arr.clone()
+            .encode(Codec::DagCbor, Multihash::Sha2_256)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_save_load() {
+    let seed: u128 = random();
+    println!("test_save_load seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 10, 100, 1_000, 10_000].iter() {
+        let items: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let leaf_size = [8, 64, 1024][rng.gen::<usize>() % 3];
+        let mut arr = Vector::from_slice(&items, Some(leaf_size));
+        let mut refv = items.clone();
+        // edits, so that the tail leaf node and the tree are both used.
+        for i in 0..(*n / 10) {
+            let off = rng.gen::<usize>() % (refv.len() + 1);
+            arr.insert(off, i).unwrap();
+            refv.insert(off, i);
+        }
+
+        let mut store = MemoryStore::new();
+        let cid = arr.save(&mut store).unwrap();
+        assert_eq!(
+            cid,
+            arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap()[0].cid
+        );
+        assert_eq!(
+            store.len(),
+            arr.encode(Codec::DagCbor, Multihash::Sha2_256)
+                .unwrap()
+                .len()
+        );
+
+        let loaded: Vector<u64> = Vector::load(&cid, &store).unwrap();
+        loaded.validate().unwrap();
+        assert_eq!(loaded.len(), refv.len());
+        assert_eq!(Vec::<u64>::from(loaded.clone()), refv);
+        assert_eq!(loaded.save(&mut store).unwrap(), cid);
+
+        // loaded vector is editable, like any other vector.
+        let mut loaded = loaded;
+        for i in 0..100 {
+            let off = rng.gen::<usize>() % (refv.len() + 1);
+            loaded.insert(off, i).unwrap();
+            refv.insert(off, i);
+        }
+        assert_eq!(Vec::<u64>::from(loaded), refv);
+    }
+}
+
+#[test]
+fn test_save_load_strings() {
+    let items: Vec<String> = (0..1000).map(|i| format!("item-{}", i)).collect();
+    let arr = Vector::from_slice(&items, Some(256));
+
+    let mut store = MemoryStore::new();
+    let cid = arr.save(&mut store).unwrap();
+    let loaded: Vector<String> = Vector::load(&cid, &store).unwrap();
+    assert_eq!(Vec::<String>::from(loaded), items);
+
+    // items of another type fail to decode.
+    assert!(matches!(
+        Vector::<u64>::load(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+#[test]
+fn test_load_shared() {
+    let arr = Vector::from_slice(&[7_u64; 900], Some(64));
+    let mut store = MemoryStore::new();
+    let cid = arr.save(&mut store).unwrap();
+    assert_eq!(
+        store.len(),
+        arr.encode(Codec::DagCbor, Multihash::Sha2_256)
+            .unwrap()
+            .len()
+    );
+
+    // identical leaf nodes are decoded once, and shared.
+    let loaded: Vector<u64> = Vector::load(&cid, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(loaded.clone()), vec![7; 900]);
+    let n = loaded.footprint_exclusive(&[]);
+    assert!(n * 4 < loaded.footprint(), "{} {}", n, loaded.footprint());
+}
+
+#[test]
+fn test_load_deep() {
+    let n = 5_000;
+    let mut arr: Vector<u64> = Vector::with_leaf_size(16);
+    arr.set_auto_rebalance(false);
+    for i in 0..n {
+        arr.insert_mut(0, i).unwrap();
+    }
+
+    let mut store = MemoryStore::new();
+    let cid = arr.save(&mut store).unwrap();
+    let loaded: Vector<u64> = Vector::load(&cid, &store).unwrap();
+    assert_eq!(loaded.depth(), arr.depth());
+    assert_eq!(Vec::<u64>::from(loaded), Vec::<u64>::from(arr));
+}
+
+#[derive(Default)]
+struct RawStore(HashMap<Cid, Vec<u8>>);
+
+impl Blockstore for RawStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(cid).cloned())
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.0.insert(block.cid, block.data);
+        Ok(block.cid)
+    }
+}
+
+#[test]
+fn test_load_errors() {
+    let items: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&items, Some(64));
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    let root = blocks[0].cid;
+
+    let mut store = RawStore::default();
+    assert!(matches!(
+        Vector::<u64>::load(&root, &store),
+        Err(Error::NotFound { .. })
+    ));
+    arr.save(&mut store).unwrap();
+    assert!(Vector::<u64>::load(&root, &store).is_ok());
+
+    // missing block.
+    let mut other = RawStore(store.0.clone());
+    other.0.remove(&blocks[3].cid);
+    assert!(matches!(
+        Vector::<u64>::load(&root, &other),
+        Err(Error::NotFound { .. })
+    ));
+
+    // tampered block.
+    let mut other = RawStore(store.0.clone());
+    other.0.get_mut(&blocks[3].cid).unwrap().push(0);
+    assert!(matches!(
+        Vector::<u64>::load(&root, &other),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // root block that is not over root and tail.
+    let leaf = blocks
+        .iter()
+        .find(|b| b.data[2..].starts_with(b"items"))
+        .unwrap();
+    assert!(matches!(
+        Vector::<u64>::load(&leaf.cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // link counts that do not add up.
+    let links = [(500, blocks[1].cid), (1000, blocks[1].cid)];
+    let block = Block::new(Codec::DagCbor, ipld::encode_interior(1000, &links));
+    let cid = store.put(block).unwrap();
+    assert!(matches!(
+        Vector::<u64>::load(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // nodes with missing, or unknown, fields.
+    let mut data = vec![];
+    cbor_map(&mut data, &[("len", 0)]);
+    let cid = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+    assert!(matches!(
+        Vector::<u64>::load(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+    let mut data = vec![];
+    cbor_map(&mut data, &[("len", 0), ("size", 0)]);
+    let cid = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+    assert!(matches!(
+        Vector::<u64>::load(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+fn cbor_map(buf: &mut Vec<u8>, fields: &[(&str, u64)]) {
+    buf.push(0xa0 | (fields.len() as u8));
+    for (key, val) in fields.iter() {
+        key.encode(buf);
+        val.encode(buf);
+    }
+}
+
+#[test]
+fn test_encode_stream() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&items, Some(128));
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+
+    // root block comes last, rest of the blocks in the same order.
+    let mut streamed = vec![];
+    let root = arr
+        .encode_stream(Codec::DagCbor, Multihash::Sha2_256, |cid, data| {
+            streamed.push(Block {
+                cid,
+                data: data.to_vec(),
+            })
+        })
+        .unwrap();
+    assert_eq!(root, blocks[0].cid);
+    assert_eq!(streamed.last(), blocks.first());
+    assert_eq!(&streamed[..streamed.len() - 1], &blocks[1..]);
+
+    // every block is written as length, cid and data.
+    let mut buf = vec![];
+    assert_eq!(
+        arr.write_blocks(Codec::DagCbor, Multihash::Sha2_256, &mut buf)
+            .unwrap(),
+        root
+    );
+    let mut written = vec![];
+    let mut rest = buf.as_slice();
+    while !rest.is_empty() {
+        let (mut n, mut shift) = (0, 0);
+        loop {
+            let byte = rest[0];
+            rest = &rest[1..];
+            n |= ((byte & 0x7f) as usize) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let (cid, data) = rest[..n].split_at(36);
+        let block = Block::new(Codec::DagCbor, data.to_vec());
+        assert_eq!(block.cid.to_bytes(), cid);
+        written.push(block);
+        rest = &rest[n..];
+    }
+    assert_eq!(written, streamed);
+
+    // write errors are returned, encoding stops at the first error.
+    let mut buf = [0_u8; 1000];
+    let res = arr.write_blocks(Codec::DagCbor, Multihash::Sha2_256, &mut buf.as_mut());
+    assert!(matches!(res, Err(Error::IOError { .. })), "{:?}", res);
+}
+
+#[test]
+fn test_save_load_codecs() {
+    let items: Vec<String> = (0..2000).map(|i| format!("item-\"{}\"\n", i)).collect();
+    let arr = Vector::from_slice(&items, Some(512));
+    let refb = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+
+    let codecs = [Codec::DagCbor, Codec::DagJson];
+    let hashes = [Multihash::Sha2_256, Multihash::Blake3];
+    for (codec, hash) in codecs
+        .iter()
+        .flat_map(|c| hashes.iter().map(move |h| (c, h)))
+    {
+        let blocks = arr.encode(*codec, *hash).unwrap();
+        assert_eq!(blocks.len(), refb.len());
+        for block in blocks.iter() {
+            assert_eq!(block.cid.codec(), codec.code());
+            assert_eq!(block.cid.hash(), hash.code());
+            assert_eq!(*block, Block::with_hash(*codec, *hash, block.data.clone()));
+        }
+        if let Codec::DagJson = codec {
+            let text = std::str::from_utf8(&blocks[0].data).unwrap();
+            assert!(text.starts_with(r#"{"len":2000,"links":[["#), "{}", text);
+            let leaf = blocks.iter().find(|b| b.data.starts_with(b"{\"items\""));
+            assert!(leaf.is_some());
+        }
+
+        let mut store = MemoryStore::new();
+        let cid = arr.save_with(&mut store, *codec, *hash).unwrap();
+        assert_eq!(cid, blocks[0].cid);
+        assert_eq!(store.len(), blocks.len());
+        let loaded: Vector<String> = Vector::load(&cid, &store).unwrap();
+        loaded.validate().unwrap();
+        assert_eq!(Vec::<String>::from(loaded), items);
+    }
+}
+
+#[test]
+fn test_schema_versions() {
+    // vector [10, 20], as a leaf and a root block over it, with `fields`
+    // appended to every node.
+    let blocks = |fields: &[u8], n: u8| {
+        let mut leaf = vec![0xa1 + n, 0x65];
+        leaf.extend_from_slice(b"items");
+        leaf.extend_from_slice(&[0x82, 0x0a, 0x14]);
+        leaf.extend_from_slice(fields);
+        let leaf = Block::new(Codec::DagCbor, leaf);
+
+        let mut root = vec![0xa2 + n, 0x63];
+        root.extend_from_slice(b"len");
+        root.extend_from_slice(&[0x02, 0x65]);
+        root.extend_from_slice(b"links");
+        root.extend_from_slice(&[0x81, 0x82, 0x02]);
+        leaf.cid.encode(&mut root);
+        root.extend_from_slice(fields);
+        vec![Block::new(Codec::DagCbor, root), leaf]
+    };
+    let version = |major: u8, minor: u8| {
+        let mut buf = vec![0x67];
+        buf.extend_from_slice(b"version");
+        buf.extend_from_slice(&[0x82, major, minor]);
+        buf
+    };
+    let load = |blocks: Vec<Block>| {
+        let mut store = MemoryStore::new();
+        let cid = blocks[0].cid;
+        blocks.into_iter().for_each(|b| {
+            store.put(b).unwrap();
+        });
+        Vector::<u64>::load(&cid, &store)
+    };
+
+    let arr = Vector::from_slice(&[10_u64, 20], None);
+    let refb = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    assert_eq!(blocks(&version(1, 0), 1), refb);
+
+    // schema 0.0, without the version field.
+    let val = load(blocks(&[], 0)).unwrap();
+    assert_eq!(Vec::<u64>::from(val), vec![10, 20]);
+
+    // schema 1.1, with a field not known to 1.0.
+    let mut fields = version(1, 1);
+    fields.push(0x64);
+    fields.extend_from_slice(b"zone");
+    fields.extend_from_slice(&[0x82, 0x61, b'a', 0xf6]);
+    let val = load(blocks(&fields, 2)).unwrap();
+    assert_eq!(Vec::<u64>::from(val), vec![10, 20]);
+
+    // schema 1.0 with an unknown field, and schema 2.0.
+    let mut fields = version(1, 0);
+    fields.push(0x64);
+    fields.extend_from_slice(b"zone");
+    fields.push(0x00);
+    assert!(matches!(
+        load(blocks(&fields, 2)),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(matches!(
+        load(blocks(&version(2, 0), 1)),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+#[derive(Default)]
+struct CountStore {
+    store: MemoryStore,
+    puts: usize,
+}
+
+impl Blockstore for CountStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.store.get(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.puts += 1;
+        self.store.put(block)
+    }
+}
+
+#[test]
+fn test_flush() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let blocks = arr.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+
+    let mut store = CountStore::default();
+    let mut flushed = Flushed::default();
+    assert!(flushed.is_empty());
+    let cid = arr.flush(&mut store, &mut flushed).unwrap();
+    assert_eq!(cid, blocks[0].cid);
+    assert_eq!(store.puts, blocks.len());
+    assert_eq!(flushed.len(), blocks.len() - 1);
+
+    // unchanged, only the root block is put.
+    store.puts = 0;
+    assert_eq!(arr.flush(&mut store, &mut flushed).unwrap(), cid);
+    assert_eq!(store.puts, 1);
+
+    // edit, clean subtrees are not rewritten.
+    let depth = arr.depth();
+    let mut val = arr.clone();
+    val.update(5000, 0).unwrap();
+    store.puts = 0;
+    let cid = val.flush(&mut store, &mut flushed).unwrap();
+    assert!(store.puts <= depth + 2, "{} {}", store.puts, depth);
+    let refb = val.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    assert_eq!(cid, refb[0].cid);
+    let loaded: Vector<u64> = Vector::load(&cid, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(loaded), Vec::<u64>::from(val.clone()));
+
+    // nodes of dropped versions are forgotten.
+    let n = flushed.len();
+    mem::drop(val);
+    arr.flush(&mut store, &mut flushed).unwrap();
+    assert!(flushed.len() < n);
+
+    // loaded vector, edited and flushed.
+    let mut flushed = Flushed::default();
+    let mut val: Vector<u64> = Vector::load_flushed(&cid, &store, &mut flushed).unwrap();
+    assert_eq!(flushed.len(), refb.len() - 1);
+    val.insert(val.len(), 10_000).unwrap();
+    val.update(0, 1).unwrap();
+    store.puts = 0;
+    let cid = val.flush(&mut store, &mut flushed).unwrap();
+    assert!(store.puts <= depth + 3, "{} {}", store.puts, depth);
+    let refb = val.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    assert_eq!(cid, refb[0].cid);
+
+    // blocks of another codec are not recorded.
+    let mut flushed = Flushed::new(Codec::DagJson, Multihash::Sha2_256);
+    Vector::<u64>::load_flushed(&cid, &store, &mut flushed).unwrap();
+    assert!(flushed.is_empty());
+}
+
+#[test]
+fn test_rechunk() {
+    let seed: u64 = random();
+    println!("test_rechunk seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let mut items: Vec<u8> = (0..1_000_000).map(|_| rng.gen()).collect();
+    let chunker = ipld::Chunker::default();
+    let mut arr = Vector::from_slice(&items, None);
+    let val = arr.rechunk(&chunker);
+    assert_eq!(Vec::<u8>::from(val.clone()), items);
+
+    let mut store = MemoryStore::new();
+    let blocks = val.encode(Codec::DagCbor, Multihash::Sha2_256).unwrap();
+    val.save(&mut store).unwrap();
+    let n = store.len();
+
+    // bytes inserted near the front, edited in place or built afresh, only
+    // the blocks around the edit and the path to them are new.
+    for i in 0..100 {
+        let byte = rng.gen();
+        arr.insert(1000 + i, byte).unwrap();
+        items.insert(1000 + i, byte);
+    }
+    let val = arr.rechunk(&chunker);
+    val.save(&mut store).unwrap();
+    let depth = (blocks.len() as f64).log2().ceil() as usize;
+    assert!(
+        store.len() - n <= depth + 4,
+        "{} {} {}",
+        store.len(),
+        n,
+        depth
+    );
+    let m = store.len();
+    let val = Vector::from_slice(&items, None).rechunk(&chunker);
+    val.save(&mut store).unwrap();
+    assert_eq!(store.len(), m);
+
+    // while leaf nodes cut by count shift.
+    let mut store = MemoryStore::new();
+    arr.save(&mut store).unwrap();
+    let n = store.len();
+    Vector::from_slice(&items, None).save(&mut store).unwrap();
+    assert!(store.len() - n > blocks.len() / 2);
+
+    let arr: Vector<u8> = Vector::default();
+    assert!(arr.rechunk(&chunker).is_empty());
+}
@@ -0,0 +1,130 @@
+use std::iter::FromIterator;
+
+use super::*;
+use crate::Result;
+
+/// Persistent double ended queue, a thin wrapper over [Vector].
+///
+/// Items are pushed and popped at either end, like a message queue where
+/// producers `push_back` and consumers `pop_front`. Snapshot, that is
+/// clone, is O(1) and shares the tree, edits on a snapshot copy only the
+/// path from the root to the edited leaf node. Pushing at the back goes
+/// into the tail leaf node of the vector, refer [Vector] for details.
+#[derive(Clone)]
+pub struct PersistentDeque<T> {
+    arr: Vector<T>,
+}
+
+impl<T> Default for PersistentDeque<T> {
+    fn default() -> Self {
+        PersistentDeque {
+            arr: Vector::default(),
+        }
+    }
+}
+
+impl<T> From<Vector<T>> for PersistentDeque<T> {
+    fn from(arr: Vector<T>) -> PersistentDeque<T> {
+        PersistentDeque { arr }
+    }
+}
+
+impl<T> FromIterator<T> for PersistentDeque<T>
+where
+    T: Clone,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        PersistentDeque {
+            arr: Vector::from_iter(iter),
+        }
+    }
+}
+
+impl<T> PersistentDeque<T> {
+    /// Create an empty queue.
+    pub fn new() -> PersistentDeque<T> {
+        PersistentDeque::default()
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.arr.len() == 0
+    }
+
+    /// Return the item at the front, None if empty.
+    pub fn front(&self) -> Option<&T> {
+        self.arr.get(0).ok()
+    }
+
+    /// Return the item at the back, None if empty.
+    pub fn back(&self) -> Option<&T> {
+        self.arr
+            .len()
+            .checked_sub(1)
+            .and_then(|i| self.arr.get(i).ok())
+    }
+
+    /// Return the item at `index`, counting from the front, or
+    /// `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        self.arr.get(index)
+    }
+
+    /// Return the underlying vector.
+    pub fn as_vector(&self) -> &Vector<T> {
+        &self.arr
+    }
+
+    /// Return the underlying vector.
+    pub fn into_vector(self) -> Vector<T> {
+        self.arr
+    }
+
+    /// Return an iterator over items, from front to back.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.arr.iter()
+    }
+}
+
+impl<T> PersistentDeque<T>
+where
+    T: Clone,
+{
+    /// Append `value` at the back.
+    pub fn push_back(&mut self, value: T) {
+        // insert at the end never fails.
+        self.arr.insert(self.arr.len(), value).ok();
+    }
+
+    /// Prepend `value` at the front.
+    pub fn push_front(&mut self, value: T) {
+        // insert at the start never fails.
+        self.arr.insert(0, value).ok();
+    }
+
+    /// Remove the item at the front and return it, None if empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        match self.arr.len() {
+            0 => None,
+            _ => self.arr.remove(0).ok(),
+        }
+    }
+
+    /// Remove the item at the back and return it, None if empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let n = self.arr.len().checked_sub(1)?;
+        self.arr.remove(n).ok()
+    }
+}
+
+#[cfg(test)]
+#[path = "deque_test.rs"]
+mod deque_test;
@@ -0,0 +1,58 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::collections::VecDeque;
+
+use super::*;
+
+fn validate(q: &PersistentDeque<u64>, refq: &VecDeque<u64>) {
+    assert_eq!(q.len(), refq.len());
+    assert_eq!(q.front(), refq.front());
+    assert_eq!(q.back(), refq.back());
+    assert!(q.iter().eq(refq.iter()));
+}
+
+#[test]
+fn test_deque_ops() {
+    let seed: u128 = random();
+    println!("test_deque_ops seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut q: PersistentDeque<u64> = (0..1000).collect();
+    let mut refq: VecDeque<u64> = (0..1000).collect();
+    let mut snaps = vec![];
+
+    for i in 0..50_000 {
+        match rng.gen::<u8>() % 5 {
+            0 | 1 => {
+                q.push_back(i);
+                refq.push_back(i);
+            }
+            2 => {
+                q.push_front(i);
+                refq.push_front(i);
+            }
+            3 => assert_eq!(q.pop_front(), refq.pop_front()),
+            _ => assert_eq!(q.pop_back(), refq.pop_back()),
+        }
+        if i % 5000 == 0 {
+            validate(&q, &refq);
+            snaps.push((q.clone(), refq.clone()));
+        }
+    }
+    validate(&q, &refq);
+
+    // snapshots are left untouched by edits.
+    for (q, refq) in snaps.iter() {
+        validate(q, refq);
+    }
+
+    while let Some(val) = refq.pop_front() {
+        assert_eq!(q.pop_front(), Some(val));
+    }
+    assert!(q.is_empty());
+    assert_eq!(q.pop_front(), None);
+    assert_eq!(q.pop_back(), None);
+    assert_eq!(q.front(), None);
+    assert_eq!(q.back(), None);
+    assert!(q.get(0).is_err());
+}
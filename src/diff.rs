@@ -0,0 +1,402 @@
+use std::{collections::HashMap, iter::once, ops::Range};
+
+use super::*;
+use crate::{Edit, Error, PositionMap, Result};
+
+// gaps that differ by more than these many inserts and removes are diffed
+// position wise instead.
+const DIFF_EDITS: usize = 1024;
+
+/// Result of a three-way merge, refer [Vector::merge].
+#[derive(Clone)]
+pub enum MergeResult<T> {
+    /// Changes on both sides do not overlap, and are merged.
+    Clean(Vector<T>),
+    /// Changes on both sides overlap in one or more regions. Merged vector
+    /// holds our side of each conflict, along with every other change.
+    Conflict(Vector<T>, Vec<Conflict<T>>),
+}
+
+/// Region of the base version changed differently on both sides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict<T> {
+    /// Range of items in the base version.
+    pub base: Range<usize>,
+    /// Offset of our side within the merged vector.
+    pub offset: usize,
+    /// Items replacing the range, on our side.
+    pub ours: Vec<T>,
+    /// Items replacing the range, on their side.
+    pub theirs: Vec<T>,
+}
+
+// contiguous change on the base version, replacing items in [start, end).
+struct Hunk<T> {
+    start: usize,
+    end: usize,
+    items: Vec<T>,
+}
+
+impl<T> Vector<T> {
+    /// Return the edit script that transforms this vector into `other`,
+    /// such that `self.apply(&edits)` returns a vector equal to `other`.
+    /// Offsets in the script refer to positions within this vector, before
+    /// applying any of the edits, refer [Vector::apply].
+    ///
+    /// Versions derived from one another share most of their leaf nodes.
+    /// Shared leaf nodes are matched by pointer and skipped, and only items
+    /// in between them are compared, using Myers' difference algorithm.
+    /// Hence diffing two snapshots that share 99% of their structure costs
+    /// in proportion to the changed leaf nodes, not to `n * m`.
+    pub fn diff(&self, other: &Vector<T>) -> Vec<Edit<T>>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut edits = vec![];
+        if self.ptr_eq(other) {
+            return edits;
+        }
+
+        let (xs, ys) = (leaf_nodes(self), leaf_nodes(other));
+        let (mut i, mut j, mut off) = (0, 0, 0);
+        for (ai, aj) in anchor_leafs(&xs, &ys)
+            .into_iter()
+            .chain(once((xs.len(), ys.len())))
+        {
+            let a: Vec<&T> = xs[i..ai].iter().flat_map(|(_, data)| data.iter()).collect();
+            let b: Vec<&T> = ys[j..aj].iter().flat_map(|(_, data)| data.iter()).collect();
+            diff_items(&a, &b, off, &mut edits);
+
+            off += a.len() + xs.get(ai).map(|(_, data)| data.len()).unwrap_or(0);
+            i = ai + 1;
+            j = aj + 1;
+        }
+
+        edits
+    }
+
+    /// Return the map from positions in this vector to positions in
+    /// `other`, refer [PositionMap], computed from the edit script returned
+    /// by [Vector::diff].
+    pub fn position_map(&self, other: &Vector<T>) -> PositionMap
+    where
+        T: Clone + PartialEq,
+    {
+        PositionMap::new(&self.diff(other))
+    }
+
+    /// Replay `patch`, an edit script returned by [Vector::diff], on this
+    /// vector and return the patched vector, leaving this vector untouched.
+    /// The inverse of diff, such that `a.apply_patch(&a.diff(&b))` returns
+    /// a vector equal to `b`. Patches can be shipped across processes and
+    /// machines and replayed on a copy of the same version, a simple
+    /// replication primitive.
+    ///
+    /// Unlike [Vector::apply], edits shall be in the order diff returns
+    /// them, sorted by offset, with inserts ahead of the update or remove at
+    /// the same offset, so that a patch replays the same on every replica.
+    /// Return `OutOfOrder` error with the position of the first edit out of
+    /// order, `IndexOutOfBounds` error if an offset is beyond this vector,
+    /// or `DuplicateEdit` error if an element is edited more than once.
+    pub fn apply_patch(&self, patch: &[Edit<T>]) -> Result<Vector<T>>
+    where
+        T: Clone,
+    {
+        let key = |e: &Edit<T>| (e.to_offset(), !matches!(e, Edit::Insert(..)));
+        for (i, w) in patch.windows(2).enumerate() {
+            if key(&w[0]) > key(&w[1]) {
+                Err(Error::OutOfOrder { index: i + 1 })?
+            }
+        }
+        self.apply(patch)
+    }
+}
+
+impl<T> Vector<T> {
+    /// Merge divergent versions `ours` and `theirs`, both derived from
+    /// `base`. Each side is diffed against `base`, refer [Vector::diff],
+    /// and changes that do not overlap are applied on `base`, sharing rest
+    /// of its tree. Regions changed on both sides, including inserts at the
+    /// same offset, merge cleanly if both sides made the same change, else
+    /// they are returned as conflicts, holding our side in the merged
+    /// vector.
+    pub fn merge(base: &Vector<T>, ours: &Vector<T>, theirs: &Vector<T>) -> MergeResult<T>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut hunks: Vec<(bool, Hunk<T>)> = vec![];
+        hunks.extend(to_hunks(base.diff(ours)).into_iter().map(|h| (true, h)));
+        hunks.extend(to_hunks(base.diff(theirs)).into_iter().map(|h| (false, h)));
+        hunks.sort_by_key(|(_, h)| (h.start, h.end));
+
+        let (mut edits, mut conflicts, mut delta) = (vec![], vec![], 0_isize);
+        let mut hunks = hunks.into_iter().peekable();
+        while let Some((side, hunk)) = hunks.next() {
+            let (start, mut end) = (hunk.start, hunk.end);
+            let mut cluster = vec![(side, hunk)];
+            while let Some((_, next)) = hunks.peek() {
+                let overlap = next.start == start || next.start < end;
+                match overlap {
+                    true => {
+                        let (side, next) = hunks.next().unwrap();
+                        end = std::cmp::max(end, next.end);
+                        cluster.push((side, next));
+                    }
+                    false => break,
+                }
+            }
+
+            let olds: Vec<&T> = (start..end).map(|i| base.get(i).unwrap()).collect();
+            let ours = splice_hunks(&olds, start, cluster.iter().filter(|(s, _)| *s));
+            let theirs = splice_hunks(&olds, start, cluster.iter().filter(|(s, _)| !*s));
+            let items = match cluster.iter().all(|(s, _)| *s == side) {
+                true if side => &ours,
+                true => &theirs,
+                false if ours == theirs => &ours,
+                false => {
+                    conflicts.push(Conflict {
+                        base: start..end,
+                        offset: ((start as isize) + delta) as usize,
+                        ours: ours.clone(),
+                        theirs: theirs.clone(),
+                    });
+                    &ours
+                }
+            };
+            let news: Vec<&T> = items.iter().collect();
+            push_run(&olds, &news, start, &mut edits);
+            delta += (items.len() as isize) - (olds.len() as isize);
+        }
+
+        let merged = base.apply(&edits).unwrap();
+        match conflicts.len() {
+            0 => MergeResult::Clean(merged),
+            _ => MergeResult::Conflict(merged, conflicts),
+        }
+    }
+}
+
+// group an edit script, as returned by diff, into hunks of contiguous
+// changes.
+fn to_hunks<T>(edits: Vec<Edit<T>>) -> Vec<Hunk<T>> {
+    let mut hunks: Vec<Hunk<T>> = vec![];
+    for edit in edits.into_iter() {
+        let (start, end, item) = match edit {
+            Edit::Insert(off, val) => (off, off, Some(val)),
+            Edit::Update(off, val) => (off, off + 1, Some(val)),
+            Edit::Remove(off) => (off, off + 1, None),
+        };
+        match hunks.last_mut() {
+            Some(hunk) if hunk.end == start => {
+                hunk.end = end;
+                hunk.items.extend(item);
+            }
+            _ => hunks.push(Hunk {
+                start,
+                end,
+                items: item.into_iter().collect(),
+            }),
+        }
+    }
+    hunks
+}
+
+// items `olds`, starting at offset `start` within the base version, with
+// `hunks` applied on them.
+fn splice_hunks<'a, T, I>(olds: &[&T], start: usize, hunks: I) -> Vec<T>
+where
+    T: 'a + Clone,
+    I: Iterator<Item = &'a (bool, Hunk<T>)>,
+{
+    let (mut items, mut i) = (vec![], 0);
+    for (_, hunk) in hunks {
+        items.extend(olds[i..(hunk.start - start)].iter().map(|x| (*x).clone()));
+        items.extend(hunk.items.iter().cloned());
+        i = hunk.end - start;
+    }
+    items.extend(olds[i..].iter().map(|x| (*x).clone()));
+    items
+}
+
+// non-empty leaf nodes of `arr`, in order, as (pointer, items). Leaf
+// nodes are identified by their items' buffer, which is shared along with
+// the leaf node, items held inline form a leaf of their own.
+fn leaf_nodes<T>(arr: &Vector<T>) -> Vec<(*const T, &[T])> {
+    let mut leafs = vec![];
+    let mut stack = arr.tree_nodes();
+    stack.reverse();
+    while let Some(node) = stack.pop() {
+        match node.as_ref() {
+            Node::M { left, right, .. } => {
+                stack.push(right);
+                stack.push(left);
+            }
+            Node::N { children, .. } => {
+                stack.extend(children.iter().rev().map(|(_, c)| c))
+            }
+            Node::Z { data } if data.is_empty() => (),
+            Node::Z { data } => leafs.push((data.as_ptr(), data.as_slice())),
+        }
+    }
+    match arr.tree() {
+        Some(_) => leafs,
+        None if arr.is_empty() => leafs,
+        None => vec![(arr.tail_items().as_ptr(), arr.tail_items())],
+    }
+}
+
+// match leaf nodes shared by `xs` and `ys`, and return the longest chain
+// of matches, as (index-in-xs, index-in-ys), that is in order on both
+// sides.
+fn anchor_leafs<T>(
+    xs: &[(*const T, &[T])],
+    ys: &[(*const T, &[T])],
+) -> Vec<(usize, usize)> {
+    let mut index: HashMap<*const T, usize> = HashMap::new();
+    for (j, (ptr, _)) in ys.iter().enumerate() {
+        index.entry(*ptr).or_insert(j);
+    }
+    let matches: Vec<(usize, usize)> = xs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (ptr, _))| index.get(ptr).map(|j| (i, *j)))
+        .collect();
+
+    // longest increasing subsequence on index-in-ys, patience style.
+    let (mut tails, mut prevs): (Vec<usize>, Vec<Option<usize>>) = (vec![], vec![]);
+    for (k, (_, j)) in matches.iter().enumerate() {
+        let p = tails.partition_point(|t| matches[*t].1 < *j);
+        prevs.push(p.checked_sub(1).map(|p| tails[p]));
+        match tails.get_mut(p) {
+            Some(t) => *t = k,
+            None => tails.push(k),
+        }
+    }
+
+    let mut anchors = vec![];
+    let mut k = tails.last().copied();
+    while let Some(m) = k {
+        anchors.push(matches[m]);
+        k = prevs[m];
+    }
+    anchors.reverse();
+    anchors
+}
+
+// diff items `a`, starting at offset `base` within the source vector,
+// against items `b`, and push the edits into `edits`.
+fn diff_items<T>(a: &[&T], b: &[&T], base: usize, edits: &mut Vec<Edit<T>>)
+where
+    T: Clone + PartialEq,
+{
+    let p = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+    let (a, b, base) = (&a[p..], &b[p..], base + p);
+    let s = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[..a.len() - s], &b[..b.len() - s]);
+
+    let snakes = match (a.len(), b.len()) {
+        (0, _) | (_, 0) => None,
+        (_, _) => shortest_path(a, b),
+    };
+    let (mut start, mut from) = (0, 0);
+    for (i, j) in snakes.unwrap_or_default().into_iter() {
+        push_run(&a[start..i], &b[from..j], base + start, edits);
+        start = i + 1;
+        from = j + 1;
+    }
+    push_run(&a[start..], &b[from..], base + start, edits);
+}
+
+// shortest edit path from `a` to `b`, as in Myers' O(ND) difference
+// algorithm, returning the matching items as (index-in-a, index-in-b).
+// Return None if the path needs more than DIFF_EDITS inserts and removes.
+fn shortest_path<T>(a: &[&T], b: &[&T]) -> Option<Vec<(usize, usize)>>
+where
+    T: PartialEq,
+{
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let dmax = std::cmp::min(a.len() + b.len(), DIFF_EDITS) as isize;
+
+    // v[k + dmax + 1] is the furthest x reached on diagonal k = x - y, and
+    // trace[d][k + d] is a copy of v after d edits.
+    let mut v = vec![0_isize; (2 * dmax + 3) as usize];
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let at = |k: isize| (k + dmax + 1) as usize;
+
+    let mut found = false;
+    for d in 0..=dmax {
+        for k in (-d..=d).step_by(2) {
+            let mut x = match k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                true => v[at(k + 1)],
+                false => v[at(k - 1)] + 1,
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            found = found || (x >= n && y >= m);
+        }
+        trace.push(v[at(-d)..=at(d)].to_vec());
+        if found {
+            break;
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let mut snakes = vec![];
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let prev = &trace[(d - 1) as usize];
+        let get = |k: isize| prev[(k + d - 1) as usize];
+        let k = x - y;
+        let k = match k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let (px, py) = (get(k), get(k) - k);
+        while x > px && y > py {
+            x -= 1;
+            y -= 1;
+            snakes.push((x as usize, y as usize));
+        }
+        x = px;
+        y = py;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        snakes.push((x as usize, y as usize));
+    }
+    snakes.reverse();
+
+    Some(snakes)
+}
+
+// replace items `a`, starting at offset `base`, with items `b`, updating
+// them pair wise and removing or inserting the rest.
+fn push_run<T>(a: &[&T], b: &[&T], base: usize, edits: &mut Vec<Edit<T>>)
+where
+    T: Clone + PartialEq,
+{
+    let k = std::cmp::min(a.len(), b.len());
+    for (off, (x, y)) in a.iter().zip(b.iter()).enumerate() {
+        if x != y {
+            edits.push(Edit::Update(base + off, (*y).clone()))
+        }
+    }
+    edits.extend((k..a.len()).map(|off| Edit::Remove(base + off)));
+    edits.extend(b[k..].iter().map(|y| Edit::Insert(base + k, (*y).clone())));
+}
+
+#[cfg(test)]
+#[path = "diff_test.rs"]
+mod diff_test;
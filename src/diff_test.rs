@@ -0,0 +1,236 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{Edit, Error};
+
+fn edit(rng: &mut SmallRng, arr: &mut Vector<u64>, refv: &mut Vec<u64>) {
+    match rng.gen::<u8>() % 3 {
+        0 => {
+            let (off, val) = (rng.gen::<usize>() % (refv.len() + 1), rng.gen());
+            arr.insert(off, val).unwrap();
+            refv.insert(off, val);
+        }
+        1 if !refv.is_empty() => {
+            let (off, val) = (rng.gen::<usize>() % refv.len(), rng.gen());
+            arr.update(off, val).unwrap();
+            refv[off] = val;
+        }
+        _ if !refv.is_empty() => {
+            let off = rng.gen::<usize>() % refv.len();
+            assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+        }
+        _ => (),
+    }
+}
+
+#[test]
+fn test_diff() {
+    let seed: u128 = random();
+    println!("test_diff seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let base = Vector::from_slice(&vals, Some(1024));
+    assert!(base.diff(&base.clone()).is_empty());
+
+    for n_ops in [1, 10, 100, 1000].iter() {
+        let (mut arr, mut refv) = (base.clone(), vals.clone());
+        for _ in 0..*n_ops {
+            edit(&mut rng, &mut arr, &mut refv);
+        }
+
+        let edits = base.diff(&arr);
+        assert!(edits.len() <= n_ops * 2, "{} {}", edits.len(), n_ops);
+        let res = base.apply(&edits).unwrap();
+        assert!(res.iter().eq(refv.iter()), "n_ops:{}", n_ops);
+
+        let edits = arr.diff(&base);
+        assert!(edits.len() <= n_ops * 2, "{} {}", edits.len(), n_ops);
+        let res = arr.apply(&edits).unwrap();
+        assert!(res.iter().eq(vals.iter()), "n_ops:{}", n_ops);
+    }
+}
+
+#[test]
+fn test_diff_unshared() {
+    let seed: u128 = random();
+    println!("test_diff_unshared seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for _ in 0..100 {
+        let a: Vec<u64> = (0..rng.gen::<usize>() % 200)
+            .map(|_| rng.gen::<u64>() % 8)
+            .collect();
+        let b: Vec<u64> = (0..rng.gen::<usize>() % 200)
+            .map(|_| rng.gen::<u64>() % 8)
+            .collect();
+        let (x, y) = (
+            Vector::from_slice(&a, Some(64)),
+            Vector::from_slice(&b, Some(64)),
+        );
+
+        let res = x.apply(&x.diff(&y)).unwrap();
+        assert!(res.iter().eq(b.iter()));
+    }
+
+    // large unrelated vectors are diffed position wise.
+    let a: Vec<u64> = (0..10_000).collect();
+    let b: Vec<u64> = (0..10_000).rev().collect();
+    let (x, y) = (Vector::from_slice(&a, None), Vector::from_slice(&b, None));
+    let edits = x.diff(&y);
+    assert_eq!(edits.len(), 10_000);
+    assert!(x.apply(&edits).unwrap().iter().eq(b.iter()));
+}
+
+#[test]
+fn test_apply_patch() {
+    let seed: u128 = random();
+    println!("test_apply_patch seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    // replica replays the patches shipped by the primary.
+    let vals: Vec<u64> = (0..10_000).collect();
+    let mut primary = Vector::from_slice(&vals, Some(256));
+    let mut replica = Vector::from_slice(&vals, Some(256));
+    let mut refv = vals.clone();
+    for _ in 0..100 {
+        let old = primary.clone();
+        for _ in 0..rng.gen::<usize>() % 20 {
+            edit(&mut rng, &mut primary, &mut refv);
+        }
+        replica = replica.apply_patch(&old.diff(&primary)).unwrap();
+        assert!(replica.iter().eq(refv.iter()));
+    }
+
+    let arr = Vector::from_slice(&[10_u64, 20, 30], None);
+    let patch = vec![Edit::Insert(1, 15), Edit::Remove(1), Edit::Insert(3, 40)];
+    let res = arr.apply_patch(&patch).unwrap();
+    assert_eq!(Vec::from(res), vec![10, 15, 30, 40]);
+
+    let patch = vec![Edit::Remove(1), Edit::Insert(1, 15)];
+    assert_eq!(
+        arr.apply_patch(&patch).err(),
+        Some(Error::OutOfOrder { index: 1 })
+    );
+    let patch = vec![Edit::Update(0, 1), Edit::Update(2, 3), Edit::Update(1, 2)];
+    assert_eq!(
+        arr.apply_patch(&patch).err(),
+        Some(Error::OutOfOrder { index: 2 })
+    );
+    let patch = vec![Edit::Update(0, 1), Edit::Insert(4, 50)];
+    assert_eq!(
+        arr.apply_patch(&patch).err(),
+        Some(Error::IndexOutOfBounds { index: 4, len: 3 })
+    );
+    let patch = vec![Edit::Update(1, 1), Edit::Remove(1)];
+    assert_eq!(
+        arr.apply_patch(&patch).err(),
+        Some(Error::DuplicateEdit { index: 1 })
+    );
+}
+
+#[test]
+fn test_merge() {
+    let seed: u128 = random();
+    println!("test_merge seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let (n, half) = (10_000, 5_000);
+    let vals: Vec<u64> = (0..n).collect();
+    let base = Vector::from_slice(&vals, Some(256));
+
+    for _ in 0..10 {
+        // ours edits the head and theirs edits the tail.
+        let (mut ours, mut ors) = (base.clone(), vals.clone());
+        let (mut theirs, mut trs) = (base.clone(), vals.clone());
+        for _ in 0..100 {
+            let (off, val) = (rng.gen::<usize>() % 1000, rng.gen::<u64>());
+            match rng.gen::<u8>() % 3 {
+                0 => {
+                    ours.insert(off, val).unwrap();
+                    ors.insert(off, val);
+                }
+                1 => {
+                    ours.update(off, val).unwrap();
+                    ors[off] = val;
+                }
+                _ => assert_eq!(ours.remove(off).unwrap(), ors.remove(off)),
+            }
+            let off = trs.len() - 1 - (rng.gen::<usize>() % 1000);
+            match rng.gen::<u8>() % 3 {
+                0 => {
+                    theirs.insert(off, val).unwrap();
+                    trs.insert(off, val);
+                }
+                1 => {
+                    theirs.update(off, val).unwrap();
+                    trs[off] = val;
+                }
+                _ => assert_eq!(theirs.remove(off).unwrap(), trs.remove(off)),
+            }
+        }
+
+        let mut refv = ors[..(ors.len() - half)].to_vec();
+        refv.extend_from_slice(&trs[half..]);
+        match Vector::merge(&base, &ours, &theirs) {
+            MergeResult::Clean(merged) => assert!(merged.iter().eq(refv.iter())),
+            MergeResult::Conflict(_, conflicts) => panic!("{:?}", conflicts),
+        }
+        match Vector::merge(&base, &ours, &ours) {
+            MergeResult::Clean(merged) => assert!(merged.iter().eq(ors.iter())),
+            MergeResult::Conflict(_, conflicts) => panic!("{:?}", conflicts),
+        }
+    }
+}
+
+#[test]
+fn test_merge_conflict() {
+    let base: Vector<u64> = (1..=10).collect();
+    let ours = base
+        .apply(&[Edit::Update(3, 100), Edit::Insert(8, 80)])
+        .unwrap();
+    let theirs = base
+        .apply(&[Edit::Remove(0), Edit::Update(3, 200)])
+        .unwrap();
+
+    match Vector::merge(&base, &ours, &theirs) {
+        MergeResult::Conflict(merged, conflicts) => {
+            assert_eq!(Vec::from(merged), vec![2, 3, 100, 5, 6, 7, 8, 80, 9, 10]);
+            let conflict = Conflict {
+                base: 3..4,
+                offset: 2,
+                ours: vec![100],
+                theirs: vec![200],
+            };
+            assert_eq!(conflicts, vec![conflict]);
+        }
+        MergeResult::Clean(_) => panic!("expected conflict"),
+    }
+
+    // inserts at the same offset.
+    let ours = base.apply(&[Edit::Insert(5, 50)]).unwrap();
+    let theirs = base.apply(&[Edit::Insert(5, 55)]).unwrap();
+    match Vector::merge(&base, &ours, &theirs) {
+        MergeResult::Conflict(merged, conflicts) => {
+            assert!(merged.iter().eq(ours.iter()));
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].base, 5..5);
+            assert_eq!(conflicts[0].theirs, vec![55]);
+        }
+        MergeResult::Clean(_) => panic!("expected conflict"),
+    }
+}
+
+#[test]
+fn test_position_map() {
+    let base: Vector<u64> = (0..100).collect();
+    let mut arr = base.clone();
+    arr.remove(10).unwrap();
+    arr.insert(0, 1000).unwrap();
+    arr.update(50, 2000).unwrap();
+
+    let pmap = base.position_map(&arr);
+    assert_eq!(pmap.map_position(5), Some(6));
+    assert_eq!(pmap.map_position(10), None);
+    assert_eq!(pmap.map_positions(&[11, 99]), vec![Some(11), Some(99)]);
+}
@@ -0,0 +1,131 @@
+use crossbeam_epoch::{self as epoch, Atomic, Owned};
+
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+
+use super::Vector;
+use crate::Result;
+
+/// Shared head for [Vector], reclaiming retired versions using
+/// [crossbeam-epoch](https://docs.rs/crossbeam-epoch).
+///
+/// Similar to `SharedVector`, but readers access the current version
+/// in place, via [EpochVector::read], under an epoch guard, without cloning
+/// the version or touching the reference count of its root. Each version
+/// replaced by a writer is retired and destroyed only after all readers that
+/// might have observed it are done. Suitable for writers producing thousands
+/// of versions per second, with many concurrent readers.
+///
+/// Publishing and reading versions are model-checked with
+/// [loom](https://docs.rs/loom), with crossbeam-epoch built over loom's
+/// atomics.
+pub struct EpochVector<T>
+where
+    T: Send + Sync,
+{
+    head: Atomic<Vector<T>>,
+}
+
+impl<T> Default for EpochVector<T>
+where
+    T: Send + Sync,
+{
+    fn default() -> EpochVector<T> {
+        EpochVector::new(Vector::default())
+    }
+}
+
+impl<T> From<Vector<T>> for EpochVector<T>
+where
+    T: Send + Sync,
+{
+    fn from(arr: Vector<T>) -> EpochVector<T> {
+        EpochVector::new(arr)
+    }
+}
+
+impl<T> Drop for EpochVector<T>
+where
+    T: Send + Sync,
+{
+    fn drop(&mut self) {
+        // we have exclusive access to the head, no reader can observe it.
+        unsafe {
+            let guard = epoch::unprotected();
+            drop(self.head.load(Relaxed, guard).into_owned());
+        }
+    }
+}
+
+impl<T> EpochVector<T>
+where
+    T: Send + Sync,
+{
+    /// Create a new shared head with `arr` as its current version.
+    pub fn new(arr: Vector<T>) -> EpochVector<T> {
+        EpochVector {
+            head: Atomic::new(arr),
+        }
+    }
+
+    /// Call `f` with a reference to the current version, and return its
+    /// result. The version stays alive for the duration of the call, even
+    /// if a writer publishes a new version in the mean time.
+    pub fn read<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&Vector<T>) -> R,
+    {
+        let guard = epoch::pin();
+        let head = self.head.load(Acquire, &guard);
+        // head is never null, and is destroyed only after guard is unpinned.
+        f(unsafe { head.deref() })
+    }
+
+    /// Return a snapshot of the current version.
+    pub fn load(&self) -> Vector<T>
+    where
+        T: Clone,
+    {
+        self.read(|arr| arr.clone())
+    }
+
+    /// Replace the current version with `arr`, unconditionally, retiring the
+    /// older version.
+    pub fn store(&self, arr: Vector<T>) {
+        let guard = epoch::pin();
+        let old = self.head.swap(Owned::new(arr), AcqRel, &guard);
+        unsafe { guard.defer_destroy(old) };
+    }
+
+    /// Apply a functional edit `f` on the current version and publish the
+    /// result as the new version, retiring the older version. Refer to
+    /// `SharedVector::update` for the semantics of `f`.
+    pub fn update<F, R>(&self, mut f: F) -> Result<R>
+    where
+        T: Clone,
+        F: FnMut(&mut Vector<T>) -> Result<R>,
+    {
+        loop {
+            let guard = epoch::pin();
+            let current = self.head.load(Acquire, &guard);
+            let mut arr = unsafe { current.deref() }.clone();
+            let res = f(&mut arr)?;
+
+            let new = Owned::new(arr);
+            let ok = self
+                .head
+                .compare_exchange(current, new, AcqRel, Acquire, &guard);
+            if ok.is_ok() {
+                unsafe { guard.defer_destroy(current) };
+                break Ok(res);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+#[path = "epoch_test.rs"]
+mod epoch_test;
+
+#[cfg(all(test, crossbeam_loom))]
+#[path = "epoch_loom_test.rs"]
+mod epoch_loom_test;
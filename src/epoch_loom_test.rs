@@ -0,0 +1,78 @@
+// Run these tests with,
+//
+// RUSTFLAGS="--cfg crossbeam_loom" cargo test --release --features epoch --lib epoch_loom_test
+//
+// crossbeam-epoch is built over loom's atomics under `crossbeam_loom`, so
+// these tests model-check the orderings used by EpochVector itself.
+
+use loom::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    },
+    thread,
+};
+
+use super::*;
+
+fn model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.check(f)
+}
+
+#[test]
+fn test_loom_epoch_publish() {
+    model(|| {
+        let shared: Arc<EpochVector<Arc<AtomicUsize>>> = Arc::new(EpochVector::default());
+
+        let reader = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                // items written before a version is published are visible
+                // to readers observing that version.
+                shared.read(|arr| {
+                    for (i, item) in arr.iter().enumerate() {
+                        assert_eq!(item.load(Relaxed), i + 1);
+                    }
+                    arr.len()
+                })
+            })
+        };
+
+        for i in 0..2 {
+            let item = Arc::new(AtomicUsize::new(0));
+            item.store(i + 1, Relaxed);
+            shared
+                .update(|arr| arr.insert(i, Arc::clone(&item)))
+                .unwrap();
+        }
+
+        assert!(reader.join().unwrap() <= 2);
+        assert_eq!(shared.read(|arr| arr.len()), 2);
+    });
+}
+
+#[test]
+fn test_loom_epoch_writers() {
+    model(|| {
+        let shared: Arc<EpochVector<u64>> = Arc::new(EpochVector::default());
+
+        let writer = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                shared.update(|arr| arr.insert(arr.len(), 1)).unwrap();
+            })
+        };
+        shared.update(|arr| arr.insert(arr.len(), 0)).unwrap();
+        writer.join().unwrap();
+
+        // compare-and-swap retry shall not lose any update.
+        let mut vals: Vec<u64> = shared.load().into();
+        vals.sort_unstable();
+        assert_eq!(vals, vec![0, 1]);
+    });
+}
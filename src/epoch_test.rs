@@ -0,0 +1,85 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::{sync::Arc, thread};
+
+use super::*;
+
+#[test]
+fn test_epoch_update() {
+    // leaf nodes of a single item, to share the tree across versions.
+    let shared: EpochVector<u64> = EpochVector::new(Vector::with_leaf_size(1));
+    assert!(shared.read(|arr| arr.is_empty()));
+
+    shared.update(|arr| arr.insert(0, 10)).unwrap();
+    shared.update(|arr| arr.insert(1, 20)).unwrap();
+    let snapshot = shared.load();
+
+    assert!(shared.update(|arr| arr.insert(10, 30)).is_err());
+    assert!(shared.read(|arr| arr.ptr_eq(&snapshot)));
+
+    let old = shared.update(|arr| arr.update(0, 11)).unwrap();
+    assert_eq!(old, 10);
+
+    let a: Vec<u64> = snapshot.into();
+    let b: Vec<u64> = shared.load().into();
+    assert_eq!(a, vec![10, 20]);
+    assert_eq!(b, vec![11, 20]);
+
+    shared.store(Vector::default());
+    assert_eq!(shared.read(|arr| arr.len()), 0);
+}
+
+#[test]
+fn test_epoch_concurrent() {
+    let seed: u128 = random();
+    println!("test_epoch_concurrent seed {}", seed);
+
+    let (n_writers, n_readers, n_ops) = (4_u64, 8, 10_000_usize);
+    let total = (n_writers as usize) * n_ops;
+
+    let shared: Arc<EpochVector<u64>> = Arc::new(EpochVector::default());
+
+    let mut writers = vec![];
+    for id in 0..n_writers {
+        let shared = Arc::clone(&shared);
+        let seed = seed + ((id as u128) * 123);
+        writers.push(thread::spawn(move || {
+            let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+            for _ in 0..n_ops {
+                shared
+                    .update(|arr| {
+                        let off = rng.gen::<usize>() % (arr.len() + 1);
+                        arr.insert(off, id)
+                    })
+                    .unwrap();
+            }
+        }));
+    }
+
+    let mut readers = vec![];
+    for _ in 0..n_readers {
+        let shared = Arc::clone(&shared);
+        readers.push(thread::spawn(move || {
+            let mut len = 0;
+            while len < total {
+                let n = shared.read(|arr| {
+                    assert_eq!(arr.iter().take(100).count(), arr.len().min(100));
+                    arr.len()
+                });
+                assert!(n >= len, "{} {}", n, len);
+                len = n;
+            }
+        }));
+    }
+
+    for h in writers.into_iter().chain(readers) {
+        h.join().unwrap()
+    }
+
+    let arr = shared.load();
+    assert_eq!(arr.len(), total);
+    for id in 0..n_writers {
+        let n = arr.iter().filter(|x| **x == id).count();
+        assert_eq!(n, n_ops, "writer-{}", id);
+    }
+}
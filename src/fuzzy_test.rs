@@ -282,7 +282,7 @@ macro_rules! fuzzy_ops {
                         let b: Vec<T> = vec.iter().map(|x| x.clone()).collect();
                         assert_eq!(a, b);
                     }
-                    Op::SplitOff(Index(off)) if off < arr.len() => {
+                    Op::SplitOff(Index(off)) if off <= arr.len() => {
                         let a = arr.split_off(off).unwrap();
                         arr.append(a);
                         let mut b = vec.split_off(off);
@@ -0,0 +1,51 @@
+//! Harness for randomized tests of the sequence types, applying random
+//! operations to a sequence and to its reference model, like a `Vec`.
+//!
+//! Refer `bits_test.rs` for usage.
+
+use rand::{prelude::random, rngs::SmallRng, SeedableRng};
+
+/// Return a random number generator with a random seed, printed along
+/// with `name`, to reproduce a failing run.
+pub fn seeded_rng(name: &str) -> SmallRng {
+    let seed: u128 = random();
+    println!("{} seed {}", name, seed);
+    SmallRng::from_seed(seed.to_le_bytes())
+}
+
+/// Apply `n_ops` random operations, using `op`, to `arr` and to its
+/// reference model `refv`, where `op` is also passed the operation's
+/// sequence number. Both are checked by `validate` before and after the
+/// run, and at every tenth of the run, where a copy of both is retained,
+/// to check in the end that older versions are left untouched by edits.
+pub fn check_ops<A, X, F, V>(
+    rng: &mut SmallRng,
+    arr: &mut A,
+    refv: &mut Vec<X>,
+    n_ops: usize,
+    mut op: F,
+    validate: V,
+) where
+    A: Clone,
+    X: Clone,
+    F: FnMut(&mut SmallRng, &mut A, &mut Vec<X>, usize),
+    V: Fn(&A, &[X]),
+{
+    let every = std::cmp::max(n_ops / 10, 1);
+    let mut versions = vec![];
+
+    validate(arr, refv);
+    for i in 0..n_ops {
+        op(rng, arr, refv, i);
+        if i % every == 0 {
+            validate(arr, refv);
+            versions.push((arr.clone(), refv.clone()));
+        }
+    }
+    validate(arr, refv);
+
+    // older versions are left untouched by edits.
+    for (arr, refv) in versions.iter() {
+        validate(arr, refv);
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::*;
+
+/// Undo and redo history over versions of a vector.
+///
+/// Every new version is recorded, and `undo` and `redo` move between the
+/// recorded versions. Since versions share their trees, each version
+/// retained costs only the nodes copied by the edits that made it. Like
+/// editors do, rapid edits can be coalesced into a single version, refer
+/// [History::set_coalesce], and old history can be truncated once it holds
+/// more memory than a bound, refer [History::set_max_footprint].
+pub struct History<T> {
+    versions: VecDeque<Version<T>>,
+    // index of the current version.
+    at: usize,
+    coalesce: Option<Duration>,
+    max_footprint: Option<usize>,
+}
+
+struct Version<T> {
+    arr: Vector<T>,
+    label: Option<String>,
+    recorded: Instant,
+    // memory held by this version, excluding nodes shared with the version
+    // after it, computed only when footprint is bounded.
+    cost: usize,
+}
+
+impl<T> Version<T> {
+    fn new(arr: Vector<T>) -> Version<T> {
+        Version {
+            arr,
+            label: None,
+            recorded: Instant::now(),
+            cost: 0,
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Create a history starting with `arr` as the current version.
+    pub fn new(arr: Vector<T>) -> History<T> {
+        let mut versions = VecDeque::new();
+        versions.push_back(Version::new(arr));
+        History {
+            versions,
+            at: 0,
+            coalesce: None,
+            max_footprint: None,
+        }
+    }
+
+    /// Coalesce versions recorded within `window` of the previous record
+    /// into the current version, instead of recording a new one. Versions
+    /// are not coalesced into a checkpoint, or into a version undone to.
+    /// Default is None, where every version is recorded.
+    pub fn set_coalesce(&mut self, window: Option<Duration>) -> &mut Self {
+        self.coalesce = window;
+        self
+    }
+
+    /// Truncate the oldest versions, once versions older than the current
+    /// one hold more than `bytes` of memory. Memory held by a version is
+    /// computed against the version after it, refer
+    /// [Vector::footprint_exclusive], which walks the tree of both versions
+    /// for every version recorded. Default is None, no bound.
+    pub fn set_max_footprint(&mut self, bytes: Option<usize>) -> &mut Self {
+        self.max_footprint = bytes;
+        if bytes.is_some() {
+            for i in 0..self.versions.len().saturating_sub(1) {
+                self.compute_cost(i);
+            }
+            self.truncate();
+        }
+        self
+    }
+
+    /// Return the current version.
+    pub fn current(&self) -> &Vector<T> {
+        &self.versions[self.at].arr
+    }
+
+    /// Return the label of the current version, if it is a checkpoint.
+    pub fn label(&self) -> Option<&str> {
+        self.versions[self.at].label.as_deref()
+    }
+
+    /// Return the number of versions retained, including the current one.
+    pub fn n_versions(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Return whether there is an older version to undo to.
+    pub fn can_undo(&self) -> bool {
+        self.at > 0
+    }
+
+    /// Return whether there is a newer version to redo to.
+    pub fn can_redo(&self) -> bool {
+        self.at + 1 < self.versions.len()
+    }
+
+    /// Return the memory held by versions older than the current one,
+    /// excluding nodes shared with the current version, always zero if
+    /// footprint is not bounded.
+    pub fn footprint(&self) -> usize {
+        self.versions.iter().take(self.at).map(|v| v.cost).sum()
+    }
+
+    /// Record `arr` as the current version, discarding versions that could
+    /// be redone.
+    pub fn record(&mut self, arr: Vector<T>) {
+        let redo = self.can_redo();
+        self.versions.truncate(self.at + 1);
+
+        let current = &self.versions[self.at];
+        let coalesce = match self.coalesce {
+            Some(window) if !redo && current.label.is_none() => {
+                current.recorded.elapsed() <= window
+            }
+            _ => false,
+        };
+
+        match coalesce {
+            true => {
+                let current = &mut self.versions[self.at];
+                current.arr = arr;
+                current.recorded = Instant::now();
+            }
+            false => {
+                self.versions.push_back(Version::new(arr));
+                self.at += 1;
+            }
+        }
+
+        if self.max_footprint.is_some() && self.at > 0 {
+            self.compute_cost(self.at - 1);
+            self.truncate();
+        }
+    }
+
+    /// Mark the current version as a checkpoint with `label`. Later
+    /// versions are not coalesced into a checkpoint.
+    pub fn checkpoint(&mut self, label: &str) {
+        self.versions[self.at].label = Some(label.to_string());
+    }
+
+    /// Return the labels of checkpoints retained, from the oldest to the
+    /// newest.
+    pub fn checkpoints(&self) -> Vec<&str> {
+        self.versions
+            .iter()
+            .filter_map(|v| v.label.as_deref())
+            .collect()
+    }
+
+    /// Move to the previous version and return it, None if there is no
+    /// older version.
+    pub fn undo(&mut self) -> Option<&Vector<T>> {
+        match self.can_undo() {
+            true => {
+                self.at -= 1;
+                Some(self.current())
+            }
+            false => None,
+        }
+    }
+
+    /// Move to the next version and return it, None if there is no newer
+    /// version.
+    pub fn redo(&mut self) -> Option<&Vector<T>> {
+        match self.can_redo() {
+            true => {
+                self.at += 1;
+                Some(self.current())
+            }
+            false => None,
+        }
+    }
+
+    fn compute_cost(&mut self, i: usize) {
+        let next = &self.versions[i + 1].arr;
+        let cost = self.versions[i].arr.footprint_exclusive(&[next]);
+        self.versions[i].cost = cost;
+    }
+
+    fn truncate(&mut self) {
+        let max = self.max_footprint.unwrap_or(usize::MAX);
+        let mut n = self.footprint();
+        while n > max && self.at > 0 {
+            n -= self.versions.pop_front().unwrap().cost;
+            self.at -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "history_test.rs"]
+mod history_test;
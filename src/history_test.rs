@@ -0,0 +1,121 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::time::Duration;
+
+use super::*;
+
+#[test]
+fn test_history() {
+    let seed: u128 = random();
+    println!("test_history seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..1000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(256));
+    let mut h = History::new(arr.clone());
+    let mut refvs = vec![vals];
+    assert!(!h.can_undo() && !h.can_redo());
+
+    for i in 0..100 {
+        let mut refv = refvs.last().unwrap().clone();
+        let off = rng.gen::<usize>() % refv.len();
+        arr.update(off, i).unwrap();
+        refv[off] = i;
+        h.record(arr.clone());
+        refvs.push(refv);
+    }
+    assert_eq!(h.n_versions(), 101);
+    assert_eq!(h.footprint(), 0);
+
+    // undo all the way back, and redo half way.
+    for refv in refvs.iter().rev().skip(1) {
+        assert!(h.undo().unwrap().iter().eq(refv.iter()));
+    }
+    assert!(h.undo().is_none());
+    for refv in refvs.iter().skip(1).take(50) {
+        assert!(h.redo().unwrap().iter().eq(refv.iter()));
+    }
+    assert!(h.can_undo() && h.can_redo());
+
+    // recording after undo discards the versions to redo.
+    let mut arr = h.current().clone();
+    arr.insert(0, 1000).unwrap();
+    h.record(arr.clone());
+    assert!(!h.can_redo());
+    assert_eq!(h.n_versions(), 52);
+    assert!(h.undo().unwrap().iter().eq(refvs[50].iter()));
+    assert!(h.redo().unwrap().ptr_eq(&arr));
+}
+
+#[test]
+fn test_history_coalesce() {
+    // small vectors are held inline, and copied on clone.
+    let mut arr: Vector<u64> = Vector::build(0..100, Some(64));
+    let mut h = History::new(arr.clone());
+    h.set_coalesce(Some(Duration::from_secs(3600)));
+
+    for i in 0..10 {
+        arr.update(i, 1000).unwrap();
+        h.record(arr.clone());
+    }
+    assert_eq!(h.n_versions(), 1);
+    assert!(h.current().ptr_eq(&arr));
+
+    h.checkpoint("first");
+    assert_eq!(h.label(), Some("first"));
+    for i in 10..20 {
+        arr.update(i, 1000).unwrap();
+        h.record(arr.clone());
+    }
+    assert_eq!(h.n_versions(), 2);
+    assert_eq!(h.label(), None);
+    assert_eq!(h.undo().unwrap().get(15).unwrap(), &15);
+    assert_eq!(h.label(), Some("first"));
+
+    // no coalescing into the version undone to.
+    let mut arr = h.current().clone();
+    arr.update(0, 0).unwrap();
+    h.record(arr.clone());
+    h.record(arr);
+    assert_eq!(h.n_versions(), 2);
+    assert_eq!(h.checkpoints(), vec!["first"]);
+
+    h.set_coalesce(Some(Duration::from_millis(1)));
+    std::thread::sleep(Duration::from_millis(10));
+    h.record(h.current().clone());
+    assert_eq!(h.n_versions(), 3);
+}
+
+#[test]
+fn test_history_truncate() {
+    let seed: u128 = random();
+    println!("test_history_truncate seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    let mut h = History::new(arr.clone());
+    for i in 0..200 {
+        let off = rng.gen::<usize>() % vals.len();
+        arr.update(off, i).unwrap();
+        h.record(arr.clone());
+    }
+    assert_eq!(h.n_versions(), 201);
+
+    // each version holds a path from root to the updated leaf node.
+    let max = arr.footprint() / 10;
+    h.set_max_footprint(Some(max));
+    let n = h.n_versions();
+    assert!(n < 201 && n > 1, "{}", n);
+    assert!(h.footprint() <= max);
+
+    for i in 0..200 {
+        let off = rng.gen::<usize>() % vals.len();
+        arr.update(off, i).unwrap();
+        h.record(arr.clone());
+        assert!(h.footprint() <= max);
+    }
+    assert!(h.current().ptr_eq(&arr));
+    while h.undo().is_some() {}
+    assert!(h.n_versions() <= n + 1, "{} {}", h.n_versions(), n);
+}
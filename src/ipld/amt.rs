@@ -0,0 +1,268 @@
+//! Array mapped trie, the AMT layout of IPLD vectors, as implemented by
+//! go-amt-ipld and its ports.
+
+use std::cmp;
+
+use super::{
+    cbor::{self, malformed, read_major, take, write_head},
+    fetch, Block, Blockstore, Cid, Codec, Decode, Encode,
+};
+use crate::{Error, Result};
+
+/// Default bit-width of AMT nodes, 8 slots a node, refer [save_amt].
+pub const AMT_BIT_WIDTH: usize = 3;
+// beyond which the bitmap of a node gets too large.
+const MAX_BIT_WIDTH: usize = 18;
+
+/// Save `items` into `store` in the AMT layout, and return the CID of the
+/// root block. Blocks are DAG-CBOR, addressed using SHA-256, and laid out
+/// as:
+///
+/// * root block, `[bitWidth, height, count, node]`, with the top node
+///   inline.
+/// * node, `[bitmap, [cid, ...], [value, ...]]`, where bit `i` of the
+///   bitmap, `bitmap[i / 8] & (1 << (i % 8))`, is set if slot `i` is
+///   occupied, and nodes at height 0 hold values while nodes above hold
+///   links to nodes a level below.
+///
+/// Each node has `2^bitWidth` slots, `bit_width` is clamped to `1..=18`.
+/// Items are at indexes `0..count`, hence every node but the last one at a
+/// height is full. Blocks are put into the store as they are encoded,
+/// children before their parent, and then pinned, refer
+/// [Blockstore::pin]. Refer `Vector::save_amt`.
+pub fn save_amt<'a, T, I>(
+    items: I,
+    bit_width: usize,
+    store: &mut dyn Blockstore,
+) -> Result<Cid>
+where
+    T: 'a + Encode,
+    I: IntoIterator<Item = &'a T>,
+{
+    let bit_width = bit_width.clamp(1, MAX_BIT_WIDTH);
+    let width = 1 << bit_width;
+    let mut cids = vec![];
+
+    // nodes at height 0, and then a level up, till the top node fits in
+    // a single node.
+    let mut iter = items.into_iter().peekable();
+    let mut values: Vec<&T> = iter.by_ref().take(width).collect();
+    let (mut count, mut height, mut links) = (values.len(), 0, vec![]);
+    if iter.peek().is_some() {
+        links.push(put_node(store, width, &[], &values, &mut cids)?);
+        while iter.peek().is_some() {
+            values = iter.by_ref().take(width).collect();
+            count += values.len();
+            links.push(put_node(store, width, &[], &values, &mut cids)?);
+        }
+        values.clear();
+        while links.len() > width {
+            let mut level = vec![];
+            for chunk in links.chunks(width) {
+                level.push(put_node::<T>(store, width, chunk, &[], &mut cids)?);
+            }
+            links = level;
+            height += 1;
+        }
+        height += 1;
+    }
+
+    let mut data = vec![];
+    write_head(&mut data, cbor::ARRAY, 4);
+    bit_width.encode(&mut data);
+    height.encode(&mut data);
+    count.encode(&mut data);
+    encode_node(&mut data, width, &links, &values);
+    let root = store.put(Block::new(Codec::DagCbor, data))?;
+
+    cids.push(root);
+    cids.rotate_right(1);
+    store.pin(&root, &cids)?;
+    Ok(root)
+}
+
+/// Load items saved in the AMT layout, refer [save_amt], from its root
+/// block `cid` in `store`. Blocks are verified against their CID, and
+/// decoded as per the codec in their CID. Only dense AMTs, with items at
+/// indexes `0..count`, are loaded, sparse ones are not vectors.
+///
+/// Return `NotFound` if a block is missing from `store`, and `Corrupted`
+/// if a block fails to decode, breaks the layout, or the AMT is sparse,
+/// with the range of indexes missing before the first item past a gap.
+/// Refer `Vector::load_amt`.
+pub fn load_amt<T>(cid: &Cid, store: &dyn Blockstore) -> Result<Vec<T>>
+where
+    T: Decode,
+{
+    let data = fetch(store, cid)?;
+    let buf = &mut data.as_slice();
+    if read_major(buf, cbor::ARRAY)? != 4 {
+        Err(malformed(
+            "amt root, want [bitWidth, height, count, node]".to_string(),
+        ))?
+    }
+    let bit_width = u64::decode(buf)?;
+    let height = u64::decode(buf)?;
+    let count = u64::decode(buf)?;
+    let levels = height.saturating_add(1).saturating_mul(bit_width);
+    if bit_width == 0 || bit_width > MAX_BIT_WIDTH as u64 || levels > 64 {
+        let msg = format!("amt bit-width {} and height {}", bit_width, height);
+        Err(corrupted(cid, &msg))?
+    }
+    let width = 1_usize << bit_width;
+    let root: Node<T> = Node::decode(buf, width)?;
+    if !buf.is_empty() {
+        Err(corrupted(cid, "bytes past the amt root"))?
+    }
+
+    // nodes in index order, with the height and the first index of each.
+    let mut items = Vec::with_capacity(cmp::min(count, 1 << 16) as usize);
+    let mut stack: Vec<(Option<Cid>, u64, u64)> = vec![(None, height, 0)];
+    let mut root = Some(root);
+    while let Some((link, height, off)) = stack.pop() {
+        let (node, cid) = match link {
+            None => (root.take().unwrap(), *cid),
+            Some(cid) => {
+                let node = Node::decode(&mut fetch(store, &cid)?.as_slice(), width)?;
+                if node.slots.is_empty() {
+                    Err(corrupted(&cid, "empty amt node"))?
+                }
+                (node, cid)
+            }
+        };
+        match node {
+            Node { links, .. } if height == 0 && !links.is_empty() => {
+                Err(corrupted(&cid, "links in amt node at height 0"))?
+            }
+            Node { values, .. } if height > 0 && !values.is_empty() => {
+                Err(corrupted(&cid, "values in amt node above height 0"))?
+            }
+            Node { slots, values, .. } if height == 0 => {
+                for (slot, val) in slots.into_iter().zip(values) {
+                    match off + slot as u64 {
+                        index if index == items.len() as u64 => items.push(val),
+                        index => {
+                            let msg = format!(
+                                "sparse amt, missing indexes {}..{}",
+                                items.len(),
+                                index
+                            );
+                            Err(corrupted(&cid, &msg))?
+                        }
+                    }
+                }
+            }
+            Node { slots, links, .. } => {
+                let span = 1_u64 << (bit_width * height);
+                for (slot, link) in slots.into_iter().zip(links).rev() {
+                    stack.push((Some(link), height - 1, off + (slot as u64) * span));
+                }
+            }
+        }
+    }
+
+    match items.len() as u64 {
+        n if n == count => Ok(items),
+        n => Err(corrupted(
+            cid,
+            &format!("amt of {} items, want {}", n, count),
+        )),
+    }
+}
+
+// node decoded from a block, with its occupied slots in order.
+struct Node<T> {
+    slots: Vec<usize>,
+    links: Vec<Cid>,
+    values: Vec<T>,
+}
+
+impl<T> Node<T>
+where
+    T: Decode,
+{
+    fn decode(buf: &mut &[u8], width: usize) -> Result<Node<T>> {
+        if read_major(buf, cbor::ARRAY)? != 3 {
+            Err(malformed(
+                "amt node, want [bitmap, links, values]".to_string(),
+            ))?
+        }
+        let n = read_major(buf, cbor::BYTES)?;
+        if n != bitmap_len(width) as u64 {
+            Err(malformed(format!("amt bitmap of {} bytes", n)))?
+        }
+        let bitmap = take(buf, n)?;
+        let mut slots = vec![];
+        for (i, byte) in bitmap.iter().enumerate() {
+            slots.extend((0..8).filter(|j| byte & (1 << j) != 0).map(|j| i * 8 + j));
+        }
+        if slots.last().map(|slot| *slot >= width).unwrap_or(false) {
+            Err(malformed(format!("amt bitmap beyond {} slots", width)))?
+        }
+
+        let links = Vec::<Cid>::decode(buf)?;
+        let values = Vec::<T>::decode(buf)?;
+        match links.len() + values.len() {
+            n if n != slots.len() => Err(malformed(format!(
+                "amt node, {} elements for {} slots",
+                n,
+                slots.len()
+            ))),
+            _ if !links.is_empty() && !values.is_empty() => {
+                Err(malformed("amt node with links and values".to_string()))
+            }
+            _ => Ok(Node {
+                slots,
+                links,
+                values,
+            }),
+        }
+    }
+}
+
+fn encode_node<T>(buf: &mut Vec<u8>, width: usize, links: &[Cid], values: &[&T])
+where
+    T: Encode,
+{
+    let mut bitmap = vec![0_u8; bitmap_len(width)];
+    for i in 0..(links.len() + values.len()) {
+        bitmap[i / 8] |= 1 << (i % 8);
+    }
+    write_head(buf, cbor::ARRAY, 3);
+    write_head(buf, cbor::BYTES, bitmap.len() as u64);
+    buf.extend_from_slice(&bitmap);
+    links.encode(buf);
+    write_head(buf, cbor::ARRAY, values.len() as u64);
+    values.iter().for_each(|val| val.encode(buf));
+}
+
+fn put_node<T>(
+    store: &mut dyn Blockstore,
+    width: usize,
+    links: &[Cid],
+    values: &[&T],
+    cids: &mut Vec<Cid>,
+) -> Result<Cid>
+where
+    T: Encode,
+{
+    let mut data = vec![];
+    encode_node(&mut data, width, links, values);
+    let cid = store.put(Block::new(Codec::DagCbor, data))?;
+    cids.push(cid);
+    Ok(cid)
+}
+
+// bitmap is a byte for upto 8 slots, and a bit a slot beyond.
+fn bitmap_len(width: usize) -> usize {
+    cmp::max(width / 8, 1)
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "amt_test.rs"]
+mod amt_test;
@@ -0,0 +1,154 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{
+    ipld::{import_car, MemoryStore},
+    rc::Vector,
+};
+
+#[test]
+fn test_amt() {
+    let seed: u64 = random();
+    println!("test_amt seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let mut store = MemoryStore::new();
+    for n in [0, 1, 7, 8, 9, 64, 65, 1000, 10_000].iter() {
+        let items: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        let arr = Vector::from_slice(&items, None);
+        for bit_width in [1, 3, 5, 8].iter() {
+            let root = arr.save_amt(&mut store, *bit_width).unwrap();
+            let val: Vector<u64> = Vector::load_amt(&root, &store).unwrap();
+            assert_eq!(Vec::<u64>::from(val), items, "n:{} bw:{}", n, bit_width);
+            assert_eq!(load_amt::<u64>(&root, &store).unwrap(), items);
+        }
+    }
+
+    // bit-width is clamped.
+    let arr = Vector::from_slice(&[1_u64, 2, 3], None);
+    let root = arr.save_amt(&mut store, 0).unwrap();
+    assert_eq!(store.get(&root).unwrap().unwrap()[1], 0x01);
+
+    let missing = Block::new(Codec::DagCbor, vec![0x80]).cid;
+    assert!(matches!(
+        Vector::<u64>::load_amt(&missing, &store),
+        Err(Error::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_amt_fixture() {
+    // vectors laid out as by the AMT spec, with a bit-width of 3.
+    let mut store = MemoryStore::new();
+    let arr = Vector::from_slice(&[1_u64, 2, 3], None);
+    let root = arr.save_amt(&mut store, AMT_BIT_WIDTH).unwrap();
+    let data = vec![
+        0x84, 0x03, 0x00, 0x03, 0x83, 0x41, 0x07, 0x80, 0x83, 0x01, 0x02, 0x03,
+    ];
+    assert_eq!(store.get(&root).unwrap(), Some(data));
+    assert_eq!(store.len(), 1);
+
+    let items: Vec<u64> = (0..9).collect();
+    let arr = Vector::from_slice(&items, None);
+    let mut store = MemoryStore::new();
+    let root = arr.save_amt(&mut store, AMT_BIT_WIDTH).unwrap();
+    let leaf0 = Block::new(
+        Codec::DagCbor,
+        vec![0x83, 0x41, 0xff, 0x80, 0x88, 0, 1, 2, 3, 4, 5, 6, 7],
+    );
+    let leaf1 = Block::new(Codec::DagCbor, vec![0x83, 0x41, 0x01, 0x80, 0x81, 0x08]);
+    let mut data = vec![0x84, 0x03, 0x01, 0x09, 0x83, 0x41, 0x03, 0x82];
+    leaf0.cid.encode(&mut data);
+    leaf1.cid.encode(&mut data);
+    data.push(0x80);
+    assert_eq!(store.get(&root).unwrap(), Some(data.clone()));
+    assert_eq!(store.get(&leaf0.cid).unwrap(), Some(leaf0.data.clone()));
+    assert_eq!(store.get(&leaf1.cid).unwrap(), Some(leaf1.data.clone()));
+    assert_eq!(store.len(), 3);
+
+    // same blocks, written by hand, are read back.
+    let mut other = MemoryStore::new();
+    other.put(leaf0).unwrap();
+    other.put(leaf1).unwrap();
+    let root = other.put(Block::new(Codec::DagCbor, data)).unwrap();
+    let val: Vector<u64> = Vector::load_amt(&root, &other).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+}
+
+#[test]
+fn test_amt_go_fixture() {
+    // archives in the block layout of go-amt-ipld, refer testdata/README.md.
+    let import = |car: &[u8]| {
+        let mut store = MemoryStore::new();
+        let roots = import_car(&mut &car[..], &mut store).unwrap();
+        assert_eq!(roots.len(), 1);
+        (roots[0], store)
+    };
+
+    let (root, store) = import(include_bytes!("testdata/amt_empty.car"));
+    assert_eq!(load_amt::<u64>(&root, &store).unwrap(), Vec::<u64>::new());
+    let (mut other, items) = (MemoryStore::new(), Vec::<u64>::new());
+    assert_eq!(save_amt(&items, AMT_BIT_WIDTH, &mut other).unwrap(), root);
+
+    let (root, store) = import(include_bytes!("testdata/amt_dense.car"));
+    assert_eq!(store.len(), 3);
+    let items: Vec<u64> = (0..9).collect();
+    let val: Vector<u64> = Vector::load_amt(&root, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+    let mut other = MemoryStore::new();
+    assert_eq!(save_amt(&items, AMT_BIT_WIDTH, &mut other).unwrap(), root);
+
+    // sparse AMTs are not vectors, the gap is named by the error.
+    let (root, store) = import(include_bytes!("testdata/amt_sparse.car"));
+    match load_amt::<u64>(&root, &store) {
+        Err(Error::Corrupted { detail }) => {
+            assert!(
+                detail.ends_with("sparse amt, missing indexes 2..9"),
+                "{}",
+                detail
+            )
+        }
+        res => panic!("{:?}", res),
+    }
+}
+
+#[test]
+fn test_amt_corrupted() {
+    let load = |data: Vec<u8>| {
+        let mut store = MemoryStore::new();
+        let cid = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+        load_amt::<u64>(&cid, &store)
+    };
+    let ok = vec![
+        0x84, 0x03, 0x00, 0x03, 0x83, 0x41, 0x07, 0x80, 0x83, 0x01, 0x02, 0x03,
+    ];
+    assert_eq!(load(ok.clone()).unwrap(), vec![1, 2, 3]);
+
+    let cases: Vec<(usize, u8)> = vec![
+        (1, 0x00), // bit-width of 0
+        (1, 0x13), // bit-width beyond 18
+        (2, 0x17), // height beyond the index space
+        (3, 0x04), // count more than the items
+        (6, 0x05), // sparse, missing index 1
+        (6, 0x0f), // bitmap with more slots than values
+    ];
+    for (off, byte) in cases.into_iter() {
+        let mut bad = ok.clone();
+        bad[off] = byte;
+        assert!(
+            matches!(load(bad), Err(Error::Corrupted { .. })),
+            "{} {:x}",
+            off,
+            byte
+        );
+    }
+    assert!(matches!(
+        load([&ok[..], &[0x00]].concat()),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // values in a node above height 0.
+    let mut bad = ok.clone();
+    bad[2] = 0x01;
+    assert!(matches!(load(bad), Err(Error::Corrupted { .. })));
+}
@@ -0,0 +1,135 @@
+//! CARv1 archives, refer [spec](https://ipld.io/specs/transport/car/carv1/).
+
+use std::{collections::HashSet, io, io::Read};
+
+use super::{
+    cbor, cid::read_cid, verify, walk::links_of, write_block, write_car_header, Block,
+    Blockstore, Cid, Decode,
+};
+use crate::{Error, Result};
+
+/// Write the blocks reachable from `roots`, vectors saved into `store`,
+/// into `w` as a CARv1 archive, and return the number of blocks written.
+/// Blocks are written in depth first order, parents before their
+/// children, and shared blocks are written once.
+///
+/// Return `NotFound` if a reachable block is missing from `store`,
+/// `Corrupted` if a block fails to decode, and `IOError` if `w` fails.
+pub fn export_car<W>(roots: &[Cid], store: &dyn Blockstore, w: &mut W) -> Result<usize>
+where
+    W: io::Write,
+{
+    write_car_header(w, roots)?;
+
+    let mut seen = HashSet::new();
+    let mut stack: Vec<Cid> = roots.iter().rev().copied().collect();
+    while let Some(cid) = stack.pop() {
+        if seen.insert(cid) {
+            let data = match store.get(&cid)? {
+                Some(data) => data,
+                None => Err(Error::NotFound {
+                    detail: format!("block {}", cid),
+                })?,
+            };
+            let links = links_of(&verify(&cid, data.clone())?)?;
+            stack.extend(links.into_iter().rev().map(|(_, cid)| cid));
+            write_block(w, &Block { cid, data })?;
+        }
+    }
+    Ok(seen.len())
+}
+
+/// Read a CARv1 archive from `r`, putting its blocks into `store`, and
+/// return the CIDs of its roots. Blocks are verified against their CID
+/// before putting them into the store.
+///
+/// Return `Corrupted` if the archive is malformed, or a block does not
+/// match its CID, and `IOError` if `r` fails.
+pub fn import_car<R>(r: &mut R, store: &mut dyn Blockstore) -> Result<Vec<Cid>>
+where
+    R: io::Read,
+{
+    let roots = match read_section(r)? {
+        Some(header) => read_header(&header)?,
+        None => Err(malformed("archive without header".to_string()))?,
+    };
+
+    while let Some(section) = read_section(r)? {
+        let mut data = section.as_slice();
+        let cid = read_cid(&mut data)?;
+        verify(&cid, data.to_vec())?;
+        store.put(Block {
+            cid,
+            data: data.to_vec(),
+        })?;
+    }
+    Ok(roots)
+}
+
+fn read_header(mut data: &[u8]) -> Result<Vec<Cid>> {
+    let buf = &mut data;
+    let (mut roots, mut version) = (None, None);
+    for _ in 0..cbor::read_major(buf, cbor::MAP)? {
+        match String::decode(buf)?.as_str() {
+            "roots" => roots = Some(Vec::<Cid>::decode(buf)?),
+            "version" => version = Some(u64::decode(buf)?),
+            _ => cbor::skip(buf)?,
+        }
+    }
+    match (roots, version) {
+        (Some(roots), Some(1)) => Ok(roots),
+        (_, Some(version)) => Err(malformed(format!("version {}", version))),
+        _ => Err(malformed("header with missing fields".to_string())),
+    }
+}
+
+// read a section, prefixed with its length as an unsigned varint, None at
+// the end of the archive.
+fn read_section<R>(r: &mut R) -> Result<Option<Vec<u8>>>
+where
+    R: io::Read,
+{
+    let mut n: u64 = 0;
+    for i in 0..9 {
+        let mut byte = [0];
+        match r.read_exact(&mut byte) {
+            Ok(()) => (),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof && i == 0 => {
+                return Ok(None)
+            }
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(malformed("truncated section length".to_string()))?
+            }
+            Err(err) => Err(io_error(err))?,
+        }
+        n |= u64::from(byte[0] & 0x7f) << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            let mut section = vec![];
+            let m = r
+                .by_ref()
+                .take(n)
+                .read_to_end(&mut section)
+                .map_err(io_error)?;
+            return match m as u64 {
+                m if m == n => Ok(Some(section)),
+                m => Err(malformed(format!("section of {} bytes, want {}", m, n))),
+            };
+        }
+    }
+    Err(malformed("section length overflow".to_string()))
+}
+
+fn malformed(detail: String) -> Error {
+    let detail = format!("car {}", detail);
+    Error::Corrupted { detail }
+}
+
+fn io_error(err: io::Error) -> Error {
+    Error::IOError {
+        detail: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "car_test.rs"]
+mod car_test;
@@ -0,0 +1,122 @@
+use super::*;
+use crate::{
+    ipld::{reachable, Codec, MemoryStore, Multihash},
+    rc::Vector,
+};
+
+#[test]
+fn test_car() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let v1 = Vector::from_slice(&items, Some(256));
+    let mut v2 = v1.clone();
+    v2.update(5000, 0).unwrap();
+
+    let mut store = MemoryStore::new();
+    let roots = [v1.save(&mut store).unwrap(), v2.save(&mut store).unwrap()];
+    let stray = store.put(Block::new(Codec::DagCbor, vec![0xa0])).unwrap();
+
+    // only reachable blocks are exported, shared blocks once.
+    let mut buf = vec![];
+    let n = export_car(&roots, &store, &mut buf).unwrap();
+    assert_eq!(n, reachable(&roots, &store).unwrap().len());
+    assert_eq!(n + 1, store.len());
+
+    let mut other = MemoryStore::new();
+    assert_eq!(import_car(&mut buf.as_slice(), &mut other).unwrap(), roots);
+    assert_eq!(other.len(), n);
+    assert!(!other.contains(&stray));
+    let val: Vector<u64> = Vector::load(&roots[1], &other).unwrap();
+    assert_eq!(Vec::<u64>::from(val), Vec::<u64>::from(v2.clone()));
+
+    // vector streamed into an archive, same as the one saved.
+    let mut buf = vec![];
+    assert_eq!(v1.export_car(&mut buf).unwrap(), roots[0]);
+    let val: Vector<u64> = Vector::import_car(&mut buf.as_slice()).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+    let mut other = MemoryStore::new();
+    assert_eq!(
+        import_car(&mut buf.as_slice(), &mut other).unwrap(),
+        &roots[..1]
+    );
+    assert_eq!(other.len(), reachable(&roots[..1], &store).unwrap().len());
+
+    // blocks of any codec and hash function.
+    let root = v2
+        .save_with(&mut store, Codec::DagJson, Multihash::Blake3)
+        .unwrap();
+    let mut buf = vec![];
+    export_car(&[root], &store, &mut buf).unwrap();
+    let val: Vector<u64> = Vector::import_car(&mut buf.as_slice()).unwrap();
+    assert_eq!(Vec::<u64>::from(val), Vec::<u64>::from(v2));
+
+    let missing = Block::new(Codec::DagCbor, vec![0x80]).cid;
+    assert!(matches!(
+        export_car(&[missing], &store, &mut vec![]),
+        Err(Error::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_car_fixture() {
+    // header of an archive without roots, and a block, as laid out by
+    // the CARv1 spec.
+    let block = Block::new(Codec::DagCbor, vec![0xa0]);
+    let mut buf = vec![];
+    export_car(&[], &MemoryStore::new(), &mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [
+            &[0x11, 0xa2, 0x65][..],
+            b"roots",
+            &[0x80, 0x67],
+            b"version",
+            &[0x01],
+        ]
+        .concat()
+    );
+    write_block(&mut buf, &block).unwrap();
+    assert_eq!(buf[18], 0x25);
+    assert_eq!(&buf[19..55], block.cid.to_bytes().as_slice());
+
+    let mut store = MemoryStore::new();
+    assert!(import_car(&mut buf.as_slice(), &mut store)
+        .unwrap()
+        .is_empty());
+    assert_eq!(store.get(&block.cid).unwrap(), Some(vec![0xa0]));
+    assert!(matches!(
+        Vector::<u64>::import_car(&mut buf.as_slice()),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+#[test]
+fn test_car_malformed() {
+    let arr = Vector::from_slice(&(0..1000).collect::<Vec<u64>>(), Some(64));
+    let mut buf = vec![];
+    arr.export_car(&mut buf).unwrap();
+    let import = |buf: &[u8]| import_car(&mut &buf[..], &mut MemoryStore::new());
+    assert!(import(&buf).is_ok());
+
+    // empty, truncated, and a header of another version.
+    assert!(matches!(import(&[]), Err(Error::Corrupted { .. })));
+    assert!(matches!(
+        import(&buf[..buf.len() - 1]),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(matches!(import(&buf[..10]), Err(Error::Corrupted { .. })));
+    let mut bad = buf.clone();
+    let off = bad.windows(7).position(|w| w == b"version").unwrap();
+    bad[off + 7] = 0x02;
+    assert!(matches!(import(&bad), Err(Error::Corrupted { .. })));
+
+    // block that does not match its CID.
+    let mut bad = buf.clone();
+    let n = bad.len();
+    bad[n - 1] ^= 0xff;
+    assert!(matches!(import(&bad), Err(Error::Corrupted { .. })));
+
+    // section length overflowing a varint.
+    let mut bad = buf.clone();
+    bad.extend_from_slice(&[0xff; 10]);
+    assert!(matches!(import(&bad), Err(Error::Corrupted { .. })));
+}
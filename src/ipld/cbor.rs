@@ -0,0 +1,323 @@
+//! DAG-CBOR encoding, refer [spec](https://ipld.io/specs/codecs/dag-cbor/spec/).
+
+use std::convert::TryFrom;
+
+use super::Cid;
+use crate::{Error, Result};
+
+// CBOR major types, in the high 3 bits of the initial byte.
+pub(crate) const UINT: u8 = 0;
+pub(crate) const NINT: u8 = 1;
+pub(crate) const BYTES: u8 = 2;
+pub(crate) const TEXT: u8 = 3;
+pub(crate) const ARRAY: u8 = 4;
+pub(crate) const MAP: u8 = 5;
+pub(crate) const TAG: u8 = 6;
+pub(crate) const SIMPLE: u8 = 7;
+
+// CBOR tag for CID links, as registered for DAG-CBOR.
+pub(crate) const TAG_CID: u64 = 42;
+
+/// Types that can be encoded in DAG-CBOR, items of a vector shall
+/// implement this to be encoded into blocks, refer `Vector::encode`.
+///
+/// Encoding shall be deterministic, same value encoding to the same
+/// bytes, so that equal nodes get the same CID.
+pub trait Encode {
+    /// Append the DAG-CBOR encoding of this value to `buf`.
+    fn encode(&self, buf: &mut Vec<u8>);
+}
+
+/// Types that can be decoded from DAG-CBOR, items of a vector shall
+/// implement this to be loaded from blocks, refer `Vector::load`.
+pub trait Decode: Sized {
+    /// Decode a value from the front of `buf`, and advance `buf` past it.
+    fn decode(buf: &mut &[u8]) -> Result<Self>;
+}
+
+// write the initial byte, and argument, of a data item using the shortest
+// form, as required by DAG-CBOR.
+pub(crate) fn write_head(buf: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    match arg {
+        0..=23 => buf.push(major | (arg as u8)),
+        24..=0xff => buf.extend_from_slice(&[major | 24, arg as u8]),
+        0x100..=0xffff => {
+            buf.push(major | 25);
+            buf.extend_from_slice(&(arg as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            buf.push(major | 26);
+            buf.extend_from_slice(&(arg as u32).to_be_bytes());
+        }
+        _ => {
+            buf.push(major | 27);
+            buf.extend_from_slice(&arg.to_be_bytes());
+        }
+    }
+}
+
+macro_rules! encode_uint {
+    ($($t:ty),*) => {$(
+        impl Encode for $t {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                write_head(buf, UINT, *self as u64)
+            }
+        }
+    )*};
+}
+
+macro_rules! encode_int {
+    ($($t:ty),*) => {$(
+        impl Encode for $t {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                // negative integers are encoded as -1 - n.
+                match *self as i64 {
+                    n if n < 0 => write_head(buf, NINT, !(n as u64)),
+                    n => write_head(buf, UINT, n as u64),
+                }
+            }
+        }
+    )*};
+}
+
+encode_uint!(u8, u16, u32, u64, usize);
+encode_int!(i8, i16, i32, i64, isize);
+
+impl Encode for bool {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        write_head(buf, SIMPLE, if *self { 21 } else { 20 })
+    }
+}
+
+impl Encode for str {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        write_head(buf, TEXT, self.len() as u64);
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.as_str().encode(buf)
+    }
+}
+
+impl<T> Encode for Option<T>
+where
+    T: Encode,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        match self {
+            Some(val) => val.encode(buf),
+            None => write_head(buf, SIMPLE, 22),
+        }
+    }
+}
+
+impl<T> Encode for [T]
+where
+    T: Encode,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        write_head(buf, ARRAY, self.len() as u64);
+        self.iter().for_each(|item| item.encode(buf));
+    }
+}
+
+impl<T> Encode for Vec<T>
+where
+    T: Encode,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        self.as_slice().encode(buf)
+    }
+}
+
+impl Encode for Cid {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        // links are byte strings prefixed with the identity multibase, 0x00.
+        let bytes = self.to_bytes();
+        write_head(buf, TAG, TAG_CID);
+        write_head(buf, BYTES, (bytes.len() + 1) as u64);
+        buf.push(0x00);
+        buf.extend_from_slice(&bytes);
+    }
+}
+
+pub(crate) fn malformed(detail: String) -> Error {
+    let detail = format!("dag-cbor {}", detail);
+    Error::Corrupted { detail }
+}
+
+// return the leading `n` bytes of `buf`, and advance `buf` past them.
+pub(crate) fn take<'a>(buf: &mut &'a [u8], n: u64) -> Result<&'a [u8]> {
+    match usize::try_from(n) {
+        Ok(n) if n <= buf.len() => {
+            let (head, rest) = buf.split_at(n);
+            *buf = rest;
+            Ok(head)
+        }
+        _ => Err(malformed(format!("truncated, want {} of {}", n, buf.len()))),
+    }
+}
+
+// read the initial byte, and argument, of a data item. Indefinite lengths
+// and arguments not in their shortest form are rejected, as required by
+// DAG-CBOR. Floats, major type 7, are always 64 bits and not checked.
+pub(crate) fn read_head(buf: &mut &[u8]) -> Result<(u8, u64)> {
+    let initial = take(buf, 1)?[0];
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    let (arg, min) = match info {
+        0..=23 => (u64::from(info), 0),
+        24 => (u64::from(take(buf, 1)?[0]), 24),
+        25 => (u64::from(u16::from_be_bytes(array(take(buf, 2)?))), 0x100),
+        26 => (
+            u64::from(u32::from_be_bytes(array(take(buf, 4)?))),
+            0x1_0000,
+        ),
+        27 => (u64::from_be_bytes(array(take(buf, 8)?)), 0x1_0000_0000),
+        _ => Err(malformed(format!("initial byte {:#x}", initial)))?,
+    };
+    match major {
+        SIMPLE => Ok((major, arg)),
+        _ if arg < min => {
+            Err(malformed(format!("argument {} not in shortest form", arg)))
+        }
+        _ => Ok((major, arg)),
+    }
+}
+
+// read the head of a data item of `major` type, and return its argument.
+pub(crate) fn read_major(buf: &mut &[u8], major: u8) -> Result<u64> {
+    match read_head(buf)? {
+        (m, arg) if m == major => Ok(arg),
+        (m, _) => Err(malformed(format!("major type {}, want {}", m, major))),
+    }
+}
+
+// skip a data item, along with nested items, without recursion.
+pub(crate) fn skip(buf: &mut &[u8]) -> Result<()> {
+    let mut n_items: u64 = 1;
+    while n_items > 0 {
+        n_items -= 1;
+        match read_head(buf)? {
+            (BYTES, n) | (TEXT, n) => {
+                take(buf, n)?;
+            }
+            (ARRAY, n) => n_items = n_items.saturating_add(n),
+            (MAP, n) => n_items = n_items.saturating_add(n.saturating_mul(2)),
+            (TAG, _) => n_items += 1,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn array<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    let mut arr = [0; N];
+    arr.copy_from_slice(bytes);
+    arr
+}
+
+macro_rules! decode_uint {
+    ($($t:ty),*) => {$(
+        impl Decode for $t {
+            fn decode(buf: &mut &[u8]) -> Result<$t> {
+                let n = read_major(buf, UINT)?;
+                <$t>::try_from(n).map_err(|_| {
+                    malformed(format!("{} overflows {}", n, stringify!($t)))
+                })
+            }
+        }
+    )*};
+}
+
+macro_rules! decode_int {
+    ($($t:ty),*) => {$(
+        impl Decode for $t {
+            fn decode(buf: &mut &[u8]) -> Result<$t> {
+                // negative integers are encoded as -1 - n.
+                let n = match read_head(buf)? {
+                    (UINT, n) => i64::try_from(n).ok(),
+                    (NINT, n) => i64::try_from(n).ok().map(|n| -1 - n),
+                    (m, _) => Err(malformed(format!("major type {}, want int", m)))?,
+                };
+                n.and_then(|n| <$t>::try_from(n).ok()).ok_or_else(|| {
+                    malformed(format!("integer overflows {}", stringify!($t)))
+                })
+            }
+        }
+    )*};
+}
+
+decode_uint!(u8, u16, u32, u64, usize);
+decode_int!(i8, i16, i32, i64, isize);
+
+impl Decode for bool {
+    fn decode(buf: &mut &[u8]) -> Result<bool> {
+        match read_major(buf, SIMPLE)? {
+            20 => Ok(false),
+            21 => Ok(true),
+            n => Err(malformed(format!("simple value {}, want bool", n))),
+        }
+    }
+}
+
+impl Decode for String {
+    fn decode(buf: &mut &[u8]) -> Result<String> {
+        let n = read_major(buf, TEXT)?;
+        match std::str::from_utf8(take(buf, n)?) {
+            Ok(s) => Ok(s.to_string()),
+            Err(err) => Err(malformed(format!("text {}", err))),
+        }
+    }
+}
+
+impl<T> Decode for Option<T>
+where
+    T: Decode,
+{
+    fn decode(buf: &mut &[u8]) -> Result<Option<T>> {
+        match buf.first() {
+            Some(0xf6) => {
+                *buf = &buf[1..];
+                Ok(None)
+            }
+            _ => Ok(Some(T::decode(buf)?)),
+        }
+    }
+}
+
+impl<T> Decode for Vec<T>
+where
+    T: Decode,
+{
+    fn decode(buf: &mut &[u8]) -> Result<Vec<T>> {
+        let n = read_major(buf, ARRAY)?;
+        // every item takes at least a byte, do not trust `n` beyond that.
+        let mut items = Vec::with_capacity(std::cmp::min(n, buf.len() as u64) as usize);
+        for _ in 0..n {
+            items.push(T::decode(buf)?);
+        }
+        Ok(items)
+    }
+}
+
+impl Decode for Cid {
+    fn decode(buf: &mut &[u8]) -> Result<Cid> {
+        match read_major(buf, TAG)? {
+            TAG_CID => (),
+            tag => Err(malformed(format!("tag {}, want {}", tag, TAG_CID)))?,
+        }
+        let n = read_major(buf, BYTES)?;
+        match take(buf, n)? {
+            [0x00, bytes @ ..] => Cid::from_bytes(bytes),
+            _ => Err(malformed("link without the identity multibase".to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "cbor_test.rs"]
+mod cbor_test;
@@ -0,0 +1,170 @@
+use super::*;
+use crate::{
+    ipld::{Block, Codec},
+    Error,
+};
+
+fn to_cbor<T: Encode + ?Sized>(val: &T) -> Vec<u8> {
+    let mut buf = vec![];
+    val.encode(&mut buf);
+    buf
+}
+
+#[test]
+fn test_encode_int() {
+    // examples from RFC 8949, appendix A.
+    assert_eq!(to_cbor(&0_u64), vec![0x00]);
+    assert_eq!(to_cbor(&23_u8), vec![0x17]);
+    assert_eq!(to_cbor(&24_u8), vec![0x18, 0x18]);
+    assert_eq!(to_cbor(&100_u32), vec![0x18, 0x64]);
+    assert_eq!(to_cbor(&1000_u16), vec![0x19, 0x03, 0xe8]);
+    assert_eq!(
+        to_cbor(&1_000_000_usize),
+        vec![0x1a, 0x00, 0x0f, 0x42, 0x40]
+    );
+    assert_eq!(
+        to_cbor(&1_000_000_000_000_u64),
+        vec![0x1b, 0x00, 0x00, 0x00, 0xe8, 0xd4, 0xa5, 0x10, 0x00]
+    );
+    assert_eq!(
+        to_cbor(&u64::MAX),
+        vec![0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert_eq!(to_cbor(&10_i32), vec![0x0a]);
+    assert_eq!(to_cbor(&-1_i8), vec![0x20]);
+    assert_eq!(to_cbor(&-10_isize), vec![0x29]);
+    assert_eq!(to_cbor(&-100_i16), vec![0x38, 0x63]);
+    assert_eq!(to_cbor(&-1000_i64), vec![0x39, 0x03, 0xe7]);
+    assert_eq!(
+        to_cbor(&i64::MIN),
+        vec![0x3b, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(to_cbor(&false), vec![0xf4]);
+    assert_eq!(to_cbor(&true), vec![0xf5]);
+    assert_eq!(to_cbor(&None::<u64>), vec![0xf6]);
+    assert_eq!(to_cbor(&Some(1_u64)), vec![0x01]);
+    assert_eq!(to_cbor(""), vec![0x60]);
+    assert_eq!(to_cbor("IETF"), vec![0x64, 0x49, 0x45, 0x54, 0x46]);
+    assert_eq!(to_cbor(&"\u{00fc}".to_string()), vec![0x62, 0xc3, 0xbc]);
+    assert_eq!(to_cbor(&Vec::<u64>::new()), vec![0x80]);
+    assert_eq!(to_cbor(&vec![1_u64, 2, 3]), vec![0x83, 0x01, 0x02, 0x03]);
+    assert_eq!(
+        to_cbor(&vec![vec![1_u8], vec![2, 3]]),
+        vec![0x82, 0x81, 0x01, 0x82, 0x02, 0x03]
+    );
+
+    let items: Vec<u64> = (1..=25).collect();
+    let mut refb = vec![0x98, 0x19];
+    refb.extend(1..=23);
+    refb.extend_from_slice(&[0x18, 0x18, 0x18, 0x19]);
+    assert_eq!(to_cbor(&items), refb);
+}
+
+#[test]
+fn test_encode_cid() {
+    let cid = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    let buf = to_cbor(&cid);
+    // tag 42, byte string of 37 bytes, identity multibase prefix.
+    assert_eq!(&buf[..5], &[0xd8, 0x2a, 0x58, 0x25, 0x00]);
+    assert_eq!(&buf[5..], cid.to_bytes().as_slice());
+}
+
+fn from_cbor<T: Decode>(mut buf: &[u8]) -> Result<T> {
+    let val = T::decode(&mut buf)?;
+    assert!(buf.is_empty(), "{} bytes left", buf.len());
+    Ok(val)
+}
+
+fn round_trip<T>(val: T)
+where
+    T: Encode + Decode + std::fmt::Debug + PartialEq,
+{
+    assert_eq!(from_cbor::<T>(&to_cbor(&val)).unwrap(), val);
+}
+
+#[test]
+fn test_decode() {
+    for n in [0, 23, 24, 255, 256, 65535, 65536, u32::MAX as u64, u64::MAX].iter() {
+        round_trip(*n);
+    }
+    for n in [
+        0,
+        -1,
+        -24,
+        -25,
+        -256,
+        -257,
+        i32::MIN as i64,
+        i64::MIN,
+        i64::MAX,
+    ]
+    .iter()
+    {
+        round_trip(*n);
+    }
+    round_trip(u8::MAX);
+    round_trip(i8::MIN);
+    round_trip(usize::MAX);
+    round_trip(isize::MIN);
+    round_trip(true);
+    round_trip(false);
+    round_trip(None::<u64>);
+    round_trip(Some(10_u64));
+    round_trip(String::new());
+    round_trip("\u{00fc}ber".to_string());
+    round_trip(Vec::<u64>::new());
+    round_trip(vec![vec![1_u8], vec![], vec![2, 3]]);
+    round_trip(vec![Some("a".to_string()), None]);
+    round_trip(Block::new(Codec::DagCbor, vec![0xa0]).cid);
+}
+
+#[test]
+fn test_decode_malformed() {
+    let is_corrupted = |res: Result<()>| matches!(res, Err(Error::Corrupted { .. }));
+
+    // truncated.
+    assert!(is_corrupted(from_cbor::<u64>(&[]).map(|_| ())));
+    assert!(is_corrupted(from_cbor::<u64>(&[0x19, 0x03]).map(|_| ())));
+    assert!(is_corrupted(from_cbor::<String>(&[0x64, 0x49]).map(|_| ())));
+    assert!(is_corrupted(
+        from_cbor::<Vec<u8>>(&[0x83, 0x01]).map(|_| ())
+    ));
+    // argument not in the shortest form, and indefinite length.
+    assert!(is_corrupted(from_cbor::<u64>(&[0x18, 0x17]).map(|_| ())));
+    assert!(is_corrupted(
+        from_cbor::<u64>(&[0x19, 0x00, 0xff]).map(|_| ())
+    ));
+    assert!(is_corrupted(
+        from_cbor::<Vec<u8>>(&[0x9f, 0xff]).map(|_| ())
+    ));
+    // type mismatch, and overflow.
+    assert!(is_corrupted(from_cbor::<u64>(&[0x20]).map(|_| ())));
+    assert!(is_corrupted(from_cbor::<bool>(&[0xf6]).map(|_| ())));
+    assert!(is_corrupted(
+        from_cbor::<u8>(&[0x19, 0x01, 0x00]).map(|_| ())
+    ));
+    assert!(is_corrupted(from_cbor::<i8>(&[0x38, 0x80]).map(|_| ())));
+    let buf = [0x3b, 0x80, 0, 0, 0, 0, 0, 0, 0];
+    assert!(is_corrupted(from_cbor::<i64>(&buf).map(|_| ())));
+    // invalid utf8, and a huge array length.
+    assert!(is_corrupted(
+        from_cbor::<String>(&[0x62, 0xc3, 0x28]).map(|_| ())
+    ));
+    let buf = [0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert!(is_corrupted(from_cbor::<Vec<u64>>(&buf).map(|_| ())));
+
+    // links, wrong tag, missing multibase prefix and unknown multihash.
+    let mut buf = to_cbor(&Block::new(Codec::DagCbor, vec![0xa0]).cid);
+    buf[1] = 0x2b;
+    assert!(is_corrupted(from_cbor::<Cid>(&buf).map(|_| ())));
+    let mut buf = to_cbor(&Block::new(Codec::DagCbor, vec![0xa0]).cid);
+    buf[4] = 0x01;
+    assert!(is_corrupted(from_cbor::<Cid>(&buf).map(|_| ())));
+    let mut buf = to_cbor(&Block::new(Codec::DagCbor, vec![0xa0]).cid);
+    buf[7] = 0x13;
+    assert!(is_corrupted(from_cbor::<Cid>(&buf).map(|_| ())));
+}
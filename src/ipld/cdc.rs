@@ -0,0 +1,149 @@
+//! Content-defined chunking, using a gear rolling hash, as in FastCDC.
+
+use std::cmp;
+
+/// Content-defined chunking, to cut a sequence of bytes, or of items, at
+/// boundaries that depend only on the content around them. Inserting
+/// bytes near the front of a sequence changes the chunks around the edit,
+/// while the chunks downstream stay the same, and so do their blocks once
+/// saved into a blockstore.
+///
+/// Boundaries are found using a gear rolling hash over the last 64 bytes,
+/// with chunks between `min` and `max` bytes, and `avg` bytes on average,
+/// refer [FastCDC][fastcdc]. Chunks smaller than `avg` are cut with a
+/// harder condition than chunks larger, which narrows the spread of their
+/// sizes.
+///
+/// [fastcdc]: https://www.usenix.org/conference/atc16/technical-sessions/presentation/xia
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunker {
+    min: usize,
+    avg: usize,
+    max: usize,
+    bits: u32,
+}
+
+impl Default for Chunker {
+    /// Chunks of 2KB to 32KB, 8KB on average, around the default leaf
+    /// node size, refer [crate::LEAF_CAP].
+    fn default() -> Self {
+        Chunker::new(2 * 1024, 8 * 1024, 32 * 1024)
+    }
+}
+
+impl Chunker {
+    /// Create a chunker for chunks of `min` to `max` bytes, and `avg` bytes
+    /// on average. `avg` is rounded down to a power of two, and clamped to
+    /// `64..=2^30`, and then `min` and `max` are clamped around it.
+    pub fn new(min: usize, avg: usize, max: usize) -> Chunker {
+        let bits = cmp::max(avg, 64).ilog2().clamp(6, 30);
+        let avg = 1 << bits;
+        Chunker {
+            min: min.clamp(1, avg),
+            avg,
+            max: cmp::max(max, avg),
+            bits,
+        }
+    }
+
+    /// Return the largest chunk, in bytes.
+    pub fn max_size(&self) -> usize {
+        self.max
+    }
+
+    /// Return an iterator over the chunks of `data`.
+    pub fn chunks<'a>(&self, data: &'a [u8]) -> Chunks<'a> {
+        Chunks {
+            chunker: *self,
+            data,
+        }
+    }
+
+    /// Return the length of the first chunk of `data`.
+    pub fn cut(&self, data: &[u8]) -> usize {
+        let mut state = Rolling::default();
+        for (i, byte) in data.iter().enumerate() {
+            if self.roll(&mut state, &[*byte]) {
+                return i + 1;
+            }
+        }
+        data.len()
+    }
+
+    // return a function rolling the bytes of each item into the hash, and
+    // returning whether the chunk ends after that item.
+    pub(crate) fn roller(&self) -> impl FnMut(&[u8]) -> bool + '_ {
+        let mut state = Rolling::default();
+        move |bytes| self.roll(&mut state, bytes)
+    }
+
+    // roll `bytes` into `state`, and return whether the chunk ends after
+    // them, in which case the state is reset for the next chunk.
+    fn roll(&self, state: &mut Rolling, bytes: &[u8]) -> bool {
+        for byte in bytes.iter() {
+            state.hash = (state.hash << 1).wrapping_add(GEAR[*byte as usize]);
+        }
+        state.size += bytes.len();
+
+        let bits = match state.size {
+            n if n < self.min => return false,
+            n if n >= self.max => 0,
+            n if n < self.avg => self.bits + 2,
+            _ => self.bits - 2,
+        };
+        match bits {
+            0 => (),
+            bits if state.hash >> (64 - bits) == 0 => (),
+            _ => return false,
+        }
+        *state = Rolling::default();
+        true
+    }
+}
+
+/// Iterator over the chunks of a byte slice, refer [Chunker::chunks].
+pub struct Chunks<'a> {
+    chunker: Chunker,
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.chunker.cut(self.data) {
+            0 => None,
+            n => {
+                let (chunk, data) = self.data.split_at(n);
+                self.data = data;
+                Some(chunk)
+            }
+        }
+    }
+}
+
+// state of the rolling hash, along with the size of the chunk so far.
+#[derive(Default)]
+struct Rolling {
+    hash: u64,
+    size: usize,
+}
+
+// random value for each byte, generated using splitmix64, the hash shifts
+// out each byte after 64 more bytes.
+const GEAR: [u64; 256] = {
+    let (mut table, mut seed, mut i) = ([0; 256], 0x6765_6172_5f63_6463_u64, 0);
+    while i < 256 {
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
+#[cfg(test)]
+#[path = "cdc_test.rs"]
+mod cdc_test;
@@ -0,0 +1,55 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_chunker() {
+    let seed: u64 = random();
+    println!("test_chunker seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let chunker = Chunker::default();
+    let data: Vec<u8> = (0..1_000_000).map(|_| rng.gen()).collect();
+    let chunks: Vec<&[u8]> = chunker.chunks(&data).collect();
+    assert_eq!(chunks.concat(), data);
+    let n = chunks.len();
+    for chunk in chunks[..n - 1].iter() {
+        assert!(chunk.len() >= 2 * 1024 && chunk.len() <= 32 * 1024);
+    }
+    // average size is around 8KB.
+    assert!(
+        n > 1_000_000 / (16 * 1024) && n < 1_000_000 / (4 * 1024),
+        "{}",
+        n
+    );
+
+    // bytes inserted near the front change the chunks around them.
+    let mut other = data.clone();
+    let off = rng.gen::<usize>() % 10_000;
+    other.splice(off..off, (0..100).map(|_| rng.gen::<u8>()));
+    let others: Vec<&[u8]> = chunker.chunks(&other).collect();
+    let common = others.iter().filter(|c| chunks.contains(c)).count();
+    assert!(common + 3 >= n, "{} {}", common, n);
+
+    // empty, and content without boundaries.
+    assert_eq!(chunker.chunks(&[]).count(), 0);
+    let zeros = vec![0_u8; 100_000];
+    for chunk in chunker.chunks(&zeros) {
+        assert!(chunk.len() <= chunker.max_size());
+    }
+}
+
+#[test]
+fn test_chunker_new() {
+    let chunker = Chunker::new(1000, 5000, 100);
+    assert_eq!(chunker, Chunker::new(1000, 4096, 4096));
+    assert_eq!(chunker.max_size(), 4096);
+    assert_eq!(Chunker::new(0, 0, 0).max_size(), 64);
+
+    let mut rng = SmallRng::seed_from_u64(random());
+    let data: Vec<u8> = (0..100_000).map(|_| rng.gen()).collect();
+    let chunker = Chunker::new(64, 256, 4096);
+    let lens: Vec<usize> = chunker.chunks(&data).map(|c| c.len()).collect();
+    assert_eq!(chunker.cut(&data), lens[0]);
+    assert_eq!(lens.iter().sum::<usize>(), data.len());
+}
@@ -0,0 +1,252 @@
+//! Content identifier, refer [spec](https://github.com/multiformats/cid).
+
+use std::{fmt, str::FromStr};
+
+use sha2::Digest;
+
+use super::cbor::malformed;
+use crate::Result;
+
+// length of digests, for all supported hash functions.
+const DIGEST_LEN: u64 = 32;
+
+// lower case base32 alphabet, of RFC 4648, with the `b` multibase prefix.
+const BASE32: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// Codec used to encode blocks, carried in their CID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Codec {
+    /// [DAG-CBOR](https://ipld.io/specs/codecs/dag-cbor/spec/), a strict
+    /// subset of CBOR with CIDs as links.
+    DagCbor,
+    /// [DAG-JSON](https://ipld.io/specs/codecs/dag-json/spec/), a strict
+    /// subset of JSON with CIDs as links, readable by humans.
+    DagJson,
+}
+
+impl Codec {
+    /// Return the multicodec code for this codec.
+    pub fn code(&self) -> u64 {
+        match self {
+            Codec::DagCbor => 0x71,
+            Codec::DagJson => 0x0129,
+        }
+    }
+
+    /// Return the codec for multicodec `code`, if supported.
+    pub fn from_code(code: u64) -> Option<Codec> {
+        match code {
+            0x71 => Some(Codec::DagCbor),
+            0x0129 => Some(Codec::DagJson),
+            _ => None,
+        }
+    }
+}
+
+/// Hash function addressing blocks, carried in their CID as a multihash.
+/// Supported hash functions produce 32 byte digests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Multihash {
+    /// SHA-256, as specified by FIPS 180-4, the default.
+    Sha2_256,
+    /// BLAKE3, with 32 bytes of output, faster than SHA-256 in software.
+    Blake3,
+}
+
+impl Multihash {
+    /// Return the multihash code for this hash function.
+    pub fn code(&self) -> u64 {
+        match self {
+            Multihash::Sha2_256 => 0x12,
+            Multihash::Blake3 => 0x1e,
+        }
+    }
+
+    /// Return the hash function for multihash `code`, if supported.
+    pub fn from_code(code: u64) -> Option<Multihash> {
+        match code {
+            0x12 => Some(Multihash::Sha2_256),
+            0x1e => Some(Multihash::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Return the digest of `data`.
+    pub fn digest(&self, data: &[u8]) -> [u8; 32] {
+        match self {
+            Multihash::Sha2_256 => sha2::Sha256::digest(data).into(),
+            Multihash::Blake3 => blake3::hash(data).into(),
+        }
+    }
+}
+
+/// Content identifier, version 1, addressing a block by the digest of its
+/// data.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cid {
+    codec: u64,
+    hash: u64,
+    digest: [u8; 32],
+}
+
+impl Cid {
+    /// Create a CID for a block encoded with `codec`, whose digest, using
+    /// `hash`, is `digest`.
+    pub fn new(codec: Codec, hash: Multihash, digest: [u8; 32]) -> Cid {
+        Cid {
+            codec: codec.code(),
+            hash: hash.code(),
+            digest,
+        }
+    }
+
+    // create a CID for a block encoded with a codec other than the ones
+    // supported for vectors, by its multicodec `code`.
+    pub(crate) fn with_code(code: u64, hash: Multihash, digest: [u8; 32]) -> Cid {
+        Cid {
+            codec: code,
+            hash: hash.code(),
+            digest,
+        }
+    }
+
+    /// Return the multicodec code of the block's codec.
+    pub fn codec(&self) -> u64 {
+        self.codec
+    }
+
+    /// Return the multihash code of the hash function.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Return the digest of the block.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Return the binary form, version, codec and multihash, as unsigned
+    /// varints followed by the digest.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for n in [1, self.codec, self.hash, DIGEST_LEN].iter() {
+            write_varint(&mut buf, *n);
+        }
+        buf.extend_from_slice(&self.digest);
+        buf
+    }
+
+    /// Parse the binary form, refer [Cid::to_bytes].
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Cid> {
+        let buf = &mut bytes;
+        match read_cid(buf)? {
+            cid if buf.is_empty() => Ok(cid),
+            _ => Err(malformed(format!("{} bytes past the cid", buf.len()))),
+        }
+    }
+}
+
+// read a CID in binary form from the front of `buf`, and advance `buf`
+// past it, refer [Cid::to_bytes].
+pub(crate) fn read_cid(buf: &mut &[u8]) -> Result<Cid> {
+    let (version, codec) = (read_varint(buf)?, read_varint(buf)?);
+    let (code, n) = (read_varint(buf)?, read_varint(buf)?);
+    match (version, Multihash::from_code(code), n) {
+        (1, Some(_), DIGEST_LEN) if buf.len() >= 32 => {
+            let mut digest = [0; 32];
+            digest.copy_from_slice(&buf[..32]);
+            *buf = &buf[32..];
+            Ok(Cid {
+                codec,
+                hash: code,
+                digest,
+            })
+        }
+        _ => Err(malformed(format!(
+            "cid version {}, multihash {:#x}/{} with {} bytes",
+            version,
+            code,
+            n,
+            buf.len()
+        ))),
+    }
+}
+
+/// Format the CID in its canonical string form, lower case base32 with
+/// the `b` multibase prefix.
+impl fmt::Display for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::from("b");
+        let (mut acc, mut n_bits) = (0_u32, 0);
+        for byte in self.to_bytes().into_iter() {
+            acc = (acc << 8) | u32::from(byte);
+            n_bits += 8;
+            while n_bits >= 5 {
+                n_bits -= 5;
+                s.push(BASE32[((acc >> n_bits) & 0x1f) as usize] as char);
+            }
+        }
+        if n_bits > 0 {
+            s.push(BASE32[((acc << (5 - n_bits)) & 0x1f) as usize] as char);
+        }
+        write!(f, "{}", s)
+    }
+}
+
+/// Parse the canonical string form, refer [fmt::Display] for [Cid].
+impl FromStr for Cid {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Cid> {
+        let text = match s.strip_prefix('b') {
+            Some(text) => text,
+            None => Err(malformed(format!("cid {:?} not in base32", s)))?,
+        };
+
+        let mut bytes = vec![];
+        let (mut acc, mut n_bits) = (0_u32, 0);
+        for ch in text.bytes() {
+            match BASE32.iter().position(|x| *x == ch) {
+                Some(val) => acc = (acc << 5) | (val as u32),
+                None => Err(malformed(format!("cid {:?} not in base32", s)))?,
+            }
+            n_bits += 5;
+            if n_bits >= 8 {
+                n_bits -= 8;
+                bytes.push((acc >> n_bits) as u8);
+            }
+        }
+        Cid::from_bytes(&bytes)
+    }
+}
+
+impl fmt::Debug for Cid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cid({})", self)
+    }
+}
+
+pub(crate) fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8)
+}
+
+// read an unsigned varint, of at most 9 bytes, as in multiformats.
+fn read_varint(buf: &mut &[u8]) -> Result<u64> {
+    let mut n = 0;
+    for (i, byte) in buf.iter().enumerate().take(9) {
+        n |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *buf = &buf[i + 1..];
+            return Ok(n);
+        }
+    }
+    Err(malformed("cid varint truncated".to_string()))
+}
+
+#[cfg(test)]
+#[path = "cid_test.rs"]
+mod cid_test;
@@ -0,0 +1,120 @@
+use super::*;
+use crate::ipld::Block;
+
+#[test]
+fn test_cid() {
+    // empty map, `{}`, in DAG-CBOR.
+    let block = Block::new(Codec::DagCbor, vec![0xa0]);
+    let cid = block.cid;
+    assert_eq!(
+        cid.to_string(),
+        "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua"
+    );
+    assert_eq!(cid.codec(), 0x71);
+    assert_eq!(&cid.to_bytes()[..4], &[0x01, 0x71, 0x12, 0x20]);
+    assert_eq!(&cid.to_bytes()[4..], &cid.digest()[..]);
+
+    let other = Block::new(Codec::DagCbor, vec![]).cid;
+    assert_ne!(cid, other);
+    assert_eq!(
+        other.to_string(),
+        "bafyreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+    );
+}
+
+#[test]
+fn test_varint() {
+    let testcases: Vec<(u64, Vec<u8>)> = vec![
+        (0, vec![0x00]),
+        (1, vec![0x01]),
+        (127, vec![0x7f]),
+        (128, vec![0x80, 0x01]),
+        (300, vec![0xac, 0x02]),
+        (16384, vec![0x80, 0x80, 0x01]),
+    ];
+    for (n, bytes) in testcases.into_iter() {
+        let mut buf = vec![];
+        write_varint(&mut buf, n);
+        assert_eq!(buf, bytes, "{}", n);
+    }
+}
+
+#[test]
+fn test_cid_bytes() {
+    let cid = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    assert_eq!(Cid::from_bytes(&cid.to_bytes()).unwrap(), cid);
+
+    let bytes = cid.to_bytes();
+    assert!(Cid::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Cid::from_bytes(&[bytes.as_slice(), &[0]].concat()).is_err());
+    assert!(Cid::from_bytes(&bytes[..2]).is_err());
+    assert!(Cid::from_bytes(&[0x80; 12]).is_err());
+    // version 0, multihash other than SHA-256.
+    let mut other = bytes.clone();
+    other[0] = 0;
+    assert!(Cid::from_bytes(&other).is_err());
+    let mut other = bytes;
+    other[2] = 0x13;
+    assert!(Cid::from_bytes(&other).is_err());
+}
+
+#[test]
+fn test_read_varint() {
+    for n in [0, 1, 127, 128, 300, 16384, u64::MAX >> 1].iter() {
+        let mut buf = vec![];
+        write_varint(&mut buf, *n);
+        let mut bytes = buf.as_slice();
+        assert_eq!(read_varint(&mut bytes).unwrap(), *n);
+        assert!(bytes.is_empty());
+    }
+}
+
+#[test]
+fn test_cid_codecs() {
+    let block = Block::with_hash(Codec::DagJson, Multihash::Blake3, b"{}".to_vec());
+    let cid = block.cid;
+    assert_eq!(cid.codec(), 0x0129);
+    assert_eq!(cid.hash(), 0x1e);
+    assert_eq!(cid.digest(), &Multihash::Blake3.digest(b"{}"));
+    assert_eq!(&cid.to_bytes()[..5], &[0x01, 0xa9, 0x02, 0x1e, 0x20]);
+    assert_eq!(Cid::from_bytes(&cid.to_bytes()).unwrap(), cid);
+
+    for codec in [Codec::DagCbor, Codec::DagJson].iter() {
+        assert_eq!(Codec::from_code(codec.code()), Some(*codec));
+    }
+    for hash in [Multihash::Sha2_256, Multihash::Blake3].iter() {
+        assert_eq!(Multihash::from_code(hash.code()), Some(*hash));
+    }
+    assert_eq!(Codec::from_code(0x55), None);
+    assert_eq!(Multihash::from_code(0x13), None);
+}
+
+#[test]
+fn test_multihash_digest() {
+    let hex = |digest: [u8; 32]| -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    };
+    assert_eq!(
+        hex(Multihash::Sha2_256.digest(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_eq!(
+        hex(Multihash::Blake3.digest(b"")),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+}
+
+#[test]
+fn test_cid_string() {
+    let cid = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    let s = "bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua";
+    assert_eq!(s.parse::<Cid>().unwrap(), cid);
+
+    let cid = Block::with_hash(Codec::DagJson, Multihash::Blake3, vec![]).cid;
+    assert_eq!(cid.to_string().parse::<Cid>().unwrap(), cid);
+
+    assert!("".parse::<Cid>().is_err());
+    assert!("zafyrei".parse::<Cid>().is_err());
+    assert!("bafyrei!".parse::<Cid>().is_err());
+    assert!(s[..s.len() - 2].parse::<Cid>().is_err());
+}
@@ -0,0 +1,149 @@
+//! Blockstore encrypting blocks at rest, using XChaCha20-Poly1305.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use super::{Block, Blockstore, Cid};
+use crate::{Error, Result};
+
+/// Default padding of blocks, in bytes, refer [CryptStore::set_padding].
+pub const PADDING: usize = 256;
+
+/// Encrypting decorator over a blockstore, encrypting blocks on `put` and
+/// decrypting them on `get`, with a key supplied by the caller, so that
+/// vectors saved into untrusted storage leak neither their items nor the
+/// exact size of their leaf nodes.
+///
+/// Each block is padded to a multiple of the padding size, and sealed
+/// using XChaCha20-Poly1305, with its CID as associated data, hence a
+/// block swapped for another, or tampered with, fails to decrypt. Blocks
+/// are still put under their CID, the digest of their plain data, which
+/// leaks whether two blocks are the same, and lets a party guessing the
+/// content of a block confirm the guess. The nonce is derived from the
+/// digest in the CID, the same nonce is used only for the same data.
+///
+/// Blocks put before wrapping the store, or with another key, fail to
+/// decrypt and are reported as `Corrupted`.
+pub struct CryptStore<S> {
+    store: S,
+    cipher: XChaCha20Poly1305,
+    padding: usize,
+}
+
+impl<S> CryptStore<S>
+where
+    S: Blockstore,
+{
+    /// Create an encrypting store over `store`, using the 256-bit `key`.
+    pub fn new(store: S, key: &[u8; 32]) -> CryptStore<S> {
+        CryptStore {
+            store,
+            cipher: XChaCha20Poly1305::new(&(*key).into()),
+            padding: PADDING,
+        }
+    }
+
+    /// Set the padding of blocks, in bytes, blocks are padded to a multiple
+    /// of `padding`, at least 1, before encryption. Larger padding leaks
+    /// less about the size of blocks, at the cost of storage.
+    pub fn set_padding(&mut self, padding: usize) -> &mut Self {
+        self.padding = std::cmp::max(padding, 1);
+        self
+    }
+
+    /// Return a reference to the inner store.
+    pub fn as_inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Return the inner store.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    fn seal(&self, cid: &Cid, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        // pad with 0x80 followed by zeros, upto a multiple of padding.
+        data.push(0x80);
+        let n = data.len().div_ceil(self.padding) * self.padding;
+        data.resize(n, 0);
+
+        let aad = cid.to_bytes();
+        let payload = Payload {
+            msg: &data,
+            aad: &aad,
+        };
+        match self.cipher.encrypt(&nonce(cid), payload) {
+            Ok(data) => Ok(data),
+            Err(_) => Err(corrupted(cid, "fails to encrypt")),
+        }
+    }
+
+    fn open(&self, cid: &Cid, data: &[u8]) -> Result<Vec<u8>> {
+        let aad = cid.to_bytes();
+        let payload = Payload {
+            msg: data,
+            aad: &aad,
+        };
+        let mut data = match self.cipher.decrypt(&nonce(cid), payload) {
+            Ok(data) => data,
+            Err(_) => Err(corrupted(cid, "fails to decrypt"))?,
+        };
+        match data.iter().rposition(|byte| *byte != 0) {
+            Some(n) if data[n] == 0x80 => {
+                data.truncate(n);
+                Ok(data)
+            }
+            _ => Err(corrupted(cid, "bad padding")),
+        }
+    }
+}
+
+impl<S> Blockstore for CryptStore<S>
+where
+    S: Blockstore,
+{
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        match self.store.get(cid)? {
+            Some(data) => Ok(Some(self.open(cid, &data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        let data = self.seal(&block.cid, block.data)?;
+        self.store.put(Block {
+            cid: block.cid,
+            data,
+        })
+    }
+
+    fn prefetch(&self, cids: &[Cid]) {
+        self.store.prefetch(cids)
+    }
+
+    fn pin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.pin(root, cids)
+    }
+
+    fn unpin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.unpin(root, cids)
+    }
+}
+
+// nonce for block `cid`, the first 24 bytes of its digest.
+fn nonce(cid: &Cid) -> XNonce {
+    let mut nonce = [0; 24];
+    nonce.copy_from_slice(&cid.digest()[..24]);
+    nonce.into()
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "crypt_test.rs"]
+mod crypt_test;
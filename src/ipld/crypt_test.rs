@@ -0,0 +1,99 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{
+    ipld::{Codec, MemoryStore},
+    rc::Vector,
+};
+
+#[test]
+fn test_crypt_store() {
+    let seed: u64 = random();
+    println!("test_crypt_store seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let key: [u8; 32] = rng.gen();
+    let items: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+
+    let mut store = CryptStore::new(MemoryStore::new(), &key);
+    let root = arr.save(&mut store).unwrap();
+    let val: Vector<u64> = Vector::load(&root, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+
+    // blocks at rest are padded, and none of them is in plain.
+    let mut plain = MemoryStore::new();
+    arr.save(&mut plain).unwrap();
+    assert_eq!(store.as_inner().len(), plain.len());
+    for cid in plain.cids() {
+        let data = plain.get(cid).unwrap().unwrap();
+        let sealed = store.as_inner().get(cid).unwrap().unwrap();
+        assert_eq!(sealed.len(), (data.len() / PADDING + 1) * PADDING + 16);
+        assert!(!sealed.windows(16).any(|w| w == &data[..16]));
+        assert_eq!(store.get(cid).unwrap(), Some(data));
+    }
+
+    // another key, and the store without the key, fail to load.
+    let other = CryptStore::new(store.as_inner().clone(), &[0xa5; 32]);
+    assert!(matches!(
+        Vector::<u64>::load(&root, &other),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(Vector::<u64>::load(&root, store.as_inner()).is_err());
+}
+
+#[test]
+fn test_crypt_padding() {
+    let mut store = CryptStore::new(MemoryStore::new(), &[7; 32]);
+    store.set_padding(1);
+    for n in [0, 1, 100, 255, 256, 1000].iter() {
+        let block = Block::new(Codec::DagCbor, vec![0; *n]);
+        let cid = store.put(block.clone()).unwrap();
+        let sealed = store.as_inner().get(&cid).unwrap().unwrap();
+        assert_eq!(sealed.len(), n + 1 + 16);
+        assert_eq!(store.get(&cid).unwrap(), Some(block.data));
+    }
+
+    store.set_padding(0).set_padding(64);
+    let block = Block::new(Codec::DagCbor, vec![0x80; 64]);
+    let cid = store.put(block.clone()).unwrap();
+    let sealed = store.as_inner().get(&cid).unwrap().unwrap();
+    assert_eq!(sealed.len(), 128 + 16);
+    assert_eq!(store.get(&cid).unwrap(), Some(block.data));
+    assert_eq!(
+        store
+            .get(&Block::new(Codec::DagCbor, vec![0xf6]).cid)
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_crypt_tampered() {
+    let mut store = CryptStore::new(MemoryStore::new(), &[7; 32]);
+    let (a, b) = (
+        Block::new(Codec::DagCbor, vec![0x01]),
+        Block::new(Codec::DagCbor, vec![0x02]),
+    );
+    store.put(a.clone()).unwrap();
+    store.put(b.clone()).unwrap();
+
+    // flipped bit, and a block put under the CID of another.
+    let mut inner = store.into_inner();
+    let mut sealed = inner.remove(&a.cid).unwrap();
+    sealed[10] ^= 0x01;
+    inner
+        .put(Block {
+            cid: a.cid,
+            data: sealed,
+        })
+        .unwrap();
+    let cid = Block::new(Codec::DagCbor, vec![0x03]).cid;
+    let data = inner.get(&b.cid).unwrap().unwrap();
+    inner.put(Block { cid, data }).unwrap();
+
+    let store = CryptStore::new(inner, &[7; 32]);
+    assert!(matches!(store.get(&a.cid), Err(Error::Corrupted { .. })));
+    assert_eq!(store.get(&b.cid).unwrap(), Some(b.data));
+    assert!(matches!(store.get(&cid), Err(Error::Corrupted { .. })));
+}
@@ -0,0 +1,150 @@
+//! Blockstore over a directory of block files.
+
+use std::{
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::{Block, Blockstore, Cid};
+use crate::{Error, Result};
+
+/// Blockstore persisting each block as a file, in a directory.
+///
+/// Block files are named by their CID, in its string form, and sharded
+/// into sub-directories named by the next to last two characters of the
+/// CID, so that no directory grows too large. Blocks are written into a
+/// temporary file, and renamed into place, hence a block file is either
+/// complete or absent. With sync enabled, which is the default, block
+/// files and their directory are synced to disk before `put` returns,
+/// refer [DirStore::set_sync].
+pub struct DirStore {
+    root: PathBuf,
+    sync: bool,
+}
+
+impl DirStore {
+    /// Open the store in directory `root`, creating it if missing.
+    pub fn open<P>(root: P) -> Result<DirStore>
+    where
+        P: AsRef<Path>,
+    {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).map_err(|err| io_error(&root, err))?;
+        Ok(DirStore { root, sync: true })
+    }
+
+    /// Enable, or disable, syncing block files to disk on `put`. Disabling
+    /// is faster, at the cost of losing recently put blocks on a crash.
+    pub fn set_sync(&mut self, sync: bool) -> &mut Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Return the path of block file `cid`, whether the block is in the
+    /// store or not.
+    pub fn to_path(&self, cid: &Cid) -> PathBuf {
+        let name = cid.to_string();
+        let n = name.len();
+        self.root.join(&name[n - 3..n - 1]).join(name)
+    }
+
+    /// Return whether block `cid` is in the store.
+    pub fn contains(&self, cid: &Cid) -> bool {
+        self.to_path(cid).is_file()
+    }
+
+    /// Remove block `cid` from the store, and return whether it was in the
+    /// store.
+    pub fn remove(&mut self, cid: &Cid) -> Result<bool> {
+        let path = self.to_path(cid);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(io_error(&path, err)),
+        }
+    }
+
+    /// Return the CIDs of blocks in the store, in no particular order.
+    /// Files that are not named by a CID, like temporary files left by a
+    /// crash, are skipped.
+    pub fn cids(&self) -> Result<Vec<Cid>> {
+        let mut cids = vec![];
+        for shard in read_dir(&self.root)? {
+            if shard.is_dir() {
+                for path in read_dir(&shard)? {
+                    let name = path.file_name().and_then(|name| name.to_str());
+                    if let Some(Ok(cid)) = name.map(|name| name.parse::<Cid>()) {
+                        cids.push(cid)
+                    }
+                }
+            }
+        }
+        Ok(cids)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir)?;
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(data)?;
+        if self.sync {
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)?;
+        if self.sync {
+            sync_dir(dir)?;
+        }
+        Ok(())
+    }
+}
+
+impl Blockstore for DirStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        let path = self.to_path(cid);
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(io_error(&path, err)),
+        }
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        let path = self.to_path(&block.cid);
+        if !path.is_file() {
+            self.write(&path, &block.data)
+                .map_err(|err| io_error(&path, err))?;
+        }
+        Ok(block.cid)
+    }
+}
+
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(dir).map_err(|err| io_error(dir, err))? {
+        paths.push(entry.map_err(|err| io_error(dir, err))?.path());
+    }
+    Ok(paths)
+}
+
+// sync the directory entries, like a renamed file, to disk. Directories
+// cannot be opened for syncing on all platforms, like windows.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    fs::File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn io_error(path: &Path, err: io::Error) -> Error {
+    let detail = format!("{:?}, {}", path, err);
+    Error::IOError { detail }
+}
+
+#[cfg(test)]
+#[path = "dir_test.rs"]
+mod dir_test;
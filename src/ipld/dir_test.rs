@@ -0,0 +1,77 @@
+use rand::prelude::random;
+
+use super::*;
+use crate::{ipld::Codec, rc::Vector};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let name = format!("ppar-{}-{:x}", name, random::<u64>());
+    std::env::temp_dir().join(name)
+}
+
+#[test]
+fn test_dir_store() {
+    let root = temp_dir("dir-store");
+    let mut store = DirStore::open(&root).unwrap();
+    assert!(store.cids().unwrap().is_empty());
+
+    let block = Block::new(Codec::DagCbor, vec![0xa0]);
+    let cid = block.cid;
+    assert_eq!(store.get(&cid).unwrap(), None);
+    assert!(!store.contains(&cid));
+    assert_eq!(store.put(block.clone()).unwrap(), cid);
+    assert_eq!(store.put(block).unwrap(), cid);
+    assert!(store.contains(&cid));
+    assert_eq!(store.get(&cid).unwrap(), Some(vec![0xa0]));
+    assert_eq!(store.cids().unwrap(), vec![cid]);
+
+    // sharded by the next to last two characters of the CID.
+    let path = store.to_path(&cid);
+    let name = cid.to_string();
+    assert_eq!(path.file_name().unwrap().to_str().unwrap(), name);
+    let shard = path.parent().unwrap();
+    assert_eq!(shard.parent().unwrap(), root);
+    assert_eq!(
+        shard.file_name().unwrap().to_str().unwrap(),
+        &name[name.len() - 3..][..2]
+    );
+
+    // stray files are skipped.
+    fs::write(shard.join("stray.tmp"), b"").unwrap();
+    assert_eq!(store.cids().unwrap(), vec![cid]);
+
+    assert!(store.remove(&cid).unwrap());
+    assert!(!store.remove(&cid).unwrap());
+    assert_eq!(store.get(&cid).unwrap(), None);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_dir_store_save_load() {
+    let root = temp_dir("dir-store-save");
+    let items: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+
+    let cid = {
+        let mut store = DirStore::open(&root).unwrap();
+        store.set_sync(false);
+        arr.save(&mut store).unwrap()
+    };
+    let store = DirStore::open(&root).unwrap();
+    let blocks = arr
+        .encode(Codec::DagCbor, crate::ipld::Multihash::Sha2_256)
+        .unwrap();
+    assert_eq!(store.cids().unwrap().len(), blocks.len());
+    let val: Vector<u64> = Vector::load(&cid, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+
+    // truncated block file fails verification.
+    let path = store.to_path(&blocks[1].cid);
+    fs::write(&path, &blocks[1].data[1..]).unwrap();
+    assert!(matches!(
+        Vector::<u64>::load(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+
+    fs::remove_dir_all(&root).unwrap();
+}
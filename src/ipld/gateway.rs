@@ -0,0 +1,169 @@
+//! Blockstore fetching blocks from an IPFS HTTP gateway.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+use super::{verify_digest, Block, Blockstore, Cid};
+use crate::{Error, Result};
+
+/// Default timeout of a request to the gateway, refer
+/// [GatewayStore::set_timeout].
+pub const GATEWAY_TIMEOUT: Duration = Duration::from_secs(30);
+// largest block exchanged by IPFS peers.
+const MAX_BLOCK_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Read-only blockstore, fetching blocks by CID from an IPFS HTTP
+/// gateway, to load a vector published into IPFS by the CID of its root
+/// block, without running an IPFS node, refer `Vector::load` and
+/// `LazyVector`.
+///
+/// Blocks are fetched as `GET {url}/ipfs/{cid}?format=raw`, the raw block
+/// of a trustless gateway, and verified against their CID, hence the
+/// gateway need not be trusted. A block not found on the gateway is
+/// `None`, and a failed request is `IOError`. Putting a block fails with
+/// `IOError`, wrap this store with a `CacheStore` to hold blocks fetched.
+///
+/// `get` blocks on the request, while [GatewayStore::get_async] returns a
+/// future, for async code. Either is usable from any runtime.
+#[derive(Clone)]
+pub struct GatewayStore {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl GatewayStore {
+    /// Create a store over the gateway at `url`, like `https://ipfs.io`.
+    pub fn new(url: &str) -> GatewayStore {
+        GatewayStore {
+            url: url.trim_end_matches('/').to_string(),
+            agent: new_agent(GATEWAY_TIMEOUT),
+        }
+    }
+
+    /// Set the timeout of a request to the gateway, including reading the
+    /// block, default is [GATEWAY_TIMEOUT].
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.agent = new_agent(timeout);
+        self
+    }
+
+    /// Return the URL of block `cid` on the gateway.
+    pub fn to_url(&self, cid: &Cid) -> String {
+        format!("{}/ipfs/{}?format=raw", self.url, cid)
+    }
+
+    /// Fetch block `cid` like `get`, without blocking the caller. The
+    /// request runs on a thread of its own, waking the returned future
+    /// once done.
+    pub fn get_async(&self, cid: &Cid) -> GatewayFetch {
+        GatewayFetch {
+            store: Some(self.clone()),
+            cid: *cid,
+            state: Arc::new(Mutex::new(FetchState::default())),
+        }
+    }
+
+    fn fetch(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        let url = self.to_url(cid);
+        let resp = self
+            .agent
+            .get(&url)
+            .header("Accept", "application/vnd.ipld.raw")
+            .call();
+        let mut resp = match resp {
+            Ok(resp) => resp,
+            Err(err) => Err(http_error(&url, err))?,
+        };
+        match resp.status().as_u16() {
+            200 => (),
+            404 | 410 => return Ok(None),
+            status => Err(http_error(&url, format!("status {}", status)))?,
+        }
+        let data = resp
+            .body_mut()
+            .with_config()
+            .limit(MAX_BLOCK_SIZE)
+            .read_to_vec()
+            .map_err(|err| http_error(&url, err))?;
+        verify_digest(cid, &data)?;
+        Ok(Some(data))
+    }
+}
+
+impl Blockstore for GatewayStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.fetch(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        let detail = format!("gateway {} is read-only, put {}", self.url, block.cid);
+        Err(Error::IOError { detail })
+    }
+}
+
+/// Future of a block fetched from a gateway, refer
+/// [GatewayStore::get_async].
+pub struct GatewayFetch {
+    // taken by the thread making the request, on first poll.
+    store: Option<GatewayStore>,
+    cid: Cid,
+    state: Arc<Mutex<FetchState>>,
+}
+
+#[derive(Default)]
+struct FetchState {
+    res: Option<Result<Option<Vec<u8>>>>,
+    waker: Option<Waker>,
+}
+
+impl Future for GatewayFetch {
+    type Output = Result<Option<Vec<u8>>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(res) = state.res.take() {
+            return Poll::Ready(res);
+        }
+        state.waker = Some(cx.waker().clone());
+        drop(state);
+
+        if let Some(store) = self.store.take() {
+            let (cid, state) = (self.cid, Arc::clone(&self.state));
+            thread::spawn(move || {
+                let res = store.fetch(&cid);
+                let mut state = state.lock().unwrap_or_else(|err| err.into_inner());
+                state.res = Some(res);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake()
+                }
+            });
+        }
+        Poll::Pending
+    }
+}
+
+fn new_agent(timeout: Duration) -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+fn http_error<E>(url: &str, err: E) -> Error
+where
+    E: std::fmt::Display,
+{
+    let detail = format!("gateway {}, {}", url, err);
+    Error::IOError { detail }
+}
+
+#[cfg(test)]
+#[path = "gateway_test.rs"]
+mod gateway_test;
@@ -0,0 +1,112 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::Arc,
+    task::Wake,
+    thread::Thread,
+};
+
+use super::*;
+use crate::{
+    ipld::{Codec, MemoryStore},
+    rc::Vector,
+};
+
+// serve blocks in `store` over http, as a trustless gateway, on a local
+// port, and return its url. Block `bad` is served with bytes flipped.
+fn serve(store: MemoryStore, bad: Option<Cid>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for conn in listener.incoming() {
+            let mut conn = conn.unwrap();
+            let mut lines = BufReader::new(conn.try_clone().unwrap()).lines();
+            let line = lines.next().unwrap().unwrap();
+            while !lines.next().unwrap().unwrap().is_empty() {}
+
+            let path = line.split(' ').nth(1).unwrap();
+            let cid = path
+                .strip_prefix("/ipfs/")
+                .and_then(|p| p.strip_suffix("?format=raw"))
+                .and_then(|p| p.parse::<Cid>().ok());
+            let (status, data) = match cid.and_then(|c| Some((c, store.get(&c).ok()??))) {
+                Some((cid, mut data)) => {
+                    if Some(cid) == bad {
+                        data[0] ^= 0xff;
+                    }
+                    ("200 OK", data)
+                }
+                None if path.starts_with("/error") => ("500 Server Error", vec![]),
+                None => ("404 Not Found", vec![]),
+            };
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                data.len()
+            );
+            conn.write_all(head.as_bytes()).unwrap();
+            conn.write_all(&data).unwrap();
+        }
+    });
+    url
+}
+
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = Box::pin(fut);
+    loop {
+        match fut.as_mut().poll(&mut cx) {
+            Poll::Ready(val) => break val,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[test]
+fn test_gateway_store() {
+    let items: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&items, Some(64));
+    let mut store = MemoryStore::new();
+    let root = arr.save(&mut store).unwrap();
+    let leaf = *store.cids().find(|c| **c != root).unwrap();
+
+    let mut gateway = GatewayStore::new(&(serve(store.clone(), None) + "/"));
+    gateway.set_timeout(Duration::from_secs(10));
+    assert!(gateway
+        .to_url(&root)
+        .ends_with(&format!("/ipfs/{}?format=raw", root)));
+    let val: Vector<u64> = Vector::load(&root, &gateway).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+
+    let block = Block::new(Codec::DagCbor, vec![0xf6]);
+    assert_eq!(gateway.get(&block.cid).unwrap(), None);
+    assert_eq!(block_on(gateway.get_async(&block.cid)).unwrap(), None);
+    assert_eq!(
+        block_on(gateway.get_async(&root)).unwrap(),
+        store.get(&root).unwrap()
+    );
+    assert!(matches!(gateway.put(block), Err(Error::IOError { .. })));
+
+    // blocks tampered by the gateway, and gateways failing.
+    let gateway = GatewayStore::new(&serve(store.clone(), Some(leaf)));
+    assert!(matches!(gateway.get(&leaf), Err(Error::Corrupted { .. })));
+    assert!(matches!(
+        block_on(gateway.get_async(&leaf)),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(gateway.get(&root).unwrap().is_some());
+
+    let failing = GatewayStore::new(&(gateway.url.clone() + "/error"));
+    assert!(matches!(failing.get(&root), Err(Error::IOError { .. })));
+    let closed = GatewayStore::new("http://127.0.0.1:1");
+    assert!(matches!(closed.get(&root), Err(Error::IOError { .. })));
+}
@@ -0,0 +1,627 @@
+//! Persistent map, as a hash array mapped trie, persisted into a blockstore.
+
+use std::{collections::HashMap, iter::FromIterator, mem, rc::Rc, slice};
+
+use super::{
+    cbor::{self, malformed, read_major, take, write_head},
+    fetch, Block, Blockstore, Cid, Codec, Decode, Encode, Multihash,
+};
+use crate::{Error, Result};
+
+// number of bits of the key digest used at each level, 32 slots a node.
+const BIT_WIDTH: usize = 5;
+// maximum number of entries in a bucket, beyond which the bucket is pushed
+// down into a child node.
+const BUCKET_SIZE: usize = 3;
+// levels beyond which the digest is used up, and buckets are not bounded.
+const MAX_DEPTH: usize = 256_usize.div_ceil(BIT_WIDTH);
+// multihash code of the hash function for keys, SHA-256.
+const HASH_ALG: u64 = 0x12;
+
+/// Persistent map, of keys to values, the map kind of the IPLD data model
+/// along with `Vector` for the list kind.
+///
+/// Map is a hash array mapped trie, over the SHA-256 digest of keys encoded
+/// as DAG-CBOR, where each node has up to 32 slots, and each slot holds a
+/// bucket of up to 3 entries or a child node. Cloning a map is cheap, and
+/// nodes are shared between clones until they are edited, using `Rc`, like
+/// `rc::Vector`.
+///
+/// The layout of the map is canonical, the same set of entries gives the
+/// same layout whatever the order of edits, hence the same CID when saved
+/// into a blockstore, refer [Hamt::save]. Blocks are laid out as in the
+/// [IPLD HashMap spec](https://ipld.io/specs/advanced-data-layouts/hamt/spec/),
+/// with a bit-width of 5 and a bucket size of 3.
+pub struct Hamt<K, V> {
+    root: Rc<Node<K, V>>,
+    len: usize,
+}
+
+struct Node<K, V> {
+    // bitmap of occupied slots, and the slots in that order.
+    map: u32,
+    slots: Vec<Slot<K, V>>,
+}
+
+enum Slot<K, V> {
+    // entries sorted by the digest of their key.
+    Bucket(Vec<Entry<K, V>>),
+    Child(Rc<Node<K, V>>),
+}
+
+#[derive(Clone)]
+struct Entry<K, V> {
+    digest: [u8; 32],
+    key: K,
+    val: V,
+}
+
+impl<K, V> Clone for Hamt<K, V> {
+    fn clone(&self) -> Self {
+        Hamt {
+            root: Rc::clone(&self.root),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for Hamt<K, V> {
+    fn default() -> Self {
+        Hamt {
+            root: Rc::new(Node::default()),
+            len: 0,
+        }
+    }
+}
+
+impl<K, V> Clone for Node<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Node {
+            map: self.map,
+            slots: self.slots.clone(),
+        }
+    }
+}
+
+impl<K, V> Clone for Slot<K, V>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Slot::Bucket(entries) => Slot::Bucket(entries.clone()),
+            Slot::Child(child) => Slot::Child(Rc::clone(child)),
+        }
+    }
+}
+
+impl<K, V> Default for Node<K, V> {
+    fn default() -> Self {
+        Node {
+            map: 0,
+            slots: vec![],
+        }
+    }
+}
+
+impl<K, V> Hamt<K, V> {
+    /// Create an empty map.
+    pub fn new() -> Hamt<K, V> {
+        Hamt::default()
+    }
+
+    /// Return the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return an iterator over the entries, in the order of the digest of
+    /// their keys.
+    pub fn iter(&self) -> HamtIter<'_, K, V> {
+        HamtIter {
+            stack: vec![self.root.slots.iter()],
+            bucket: [].iter(),
+        }
+    }
+}
+
+impl<K, V> Hamt<K, V>
+where
+    K: Encode + Eq,
+{
+    /// Return the value for `key`, None if `key` is not in the map.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let digest = digest_of(key);
+        let (mut node, mut depth) = (self.root.as_ref(), 0);
+        loop {
+            match node.slot(&digest, depth)? {
+                Slot::Bucket(entries) => {
+                    let entry = entries.iter().find(|e| e.key == *key)?;
+                    break Some(&entry.val);
+                }
+                Slot::Child(child) => {
+                    node = child;
+                    depth += 1;
+                }
+            }
+        }
+    }
+
+    /// Return whether `key` is in the map.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+}
+
+impl<K, V> Hamt<K, V>
+where
+    K: Encode + Eq + Clone,
+    V: Clone,
+{
+    /// Insert `val` for `key`, and return the old value for `key`, if any.
+    /// Nodes on the path to the key are copied if shared with clones.
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let entry = Entry {
+            digest: digest_of(&key),
+            key,
+            val,
+        };
+        let old = Rc::make_mut(&mut self.root).put(entry, 0);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Remove `key` from the map, and return its value, if any. Child nodes
+    /// left with few entries are collapsed into a bucket of their parent.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let digest = digest_of(key);
+        let val = Rc::make_mut(&mut self.root).delete(&digest, key, 0);
+        if val.is_some() {
+            self.len -= 1;
+        }
+        val
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Hamt<K, V>
+where
+    K: Encode + Eq + Clone,
+    V: Clone,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = Hamt::new();
+        iter.into_iter().for_each(|(key, val)| {
+            map.insert(key, val);
+        });
+        map
+    }
+}
+
+impl<K, V> Hamt<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    /// Save this map into `store`, as DAG-CBOR blocks addressed using
+    /// SHA-256, and return the CID of the root block. Blocks are put into
+    /// the store as they are encoded, children before their parent, and
+    /// then pinned, refer [Blockstore::pin]. Nodes shared within the map
+    /// are saved once.
+    pub fn save(&self, store: &mut dyn Blockstore) -> Result<Cid> {
+        let mut cids: HashMap<*const Node<K, V>, Cid> = HashMap::new();
+        let mut pins = vec![];
+        let mut stack: Vec<(&Rc<Node<K, V>>, bool)> =
+            self.root.children().map(|c| (c, false)).collect();
+        while let Some((node, visited)) = stack.pop() {
+            if cids.contains_key(&Rc::as_ptr(node)) {
+                continue;
+            } else if !visited {
+                stack.push((node, true));
+                stack.extend(node.children().map(|c| (c, false)));
+                continue;
+            }
+            let mut data = vec![];
+            node.encode(&mut data, &cids);
+            let block = Block::new(Codec::DagCbor, data);
+            cids.insert(Rc::as_ptr(node), block.cid);
+            pins.push(block.cid);
+            store.put(block)?;
+        }
+
+        // {"hamt": node, "hashAlg": 0x12, "bitWidth": 5, "bucketSize": 3}
+        let mut data = vec![];
+        write_head(&mut data, cbor::MAP, 4);
+        "hamt".encode(&mut data);
+        self.root.encode(&mut data, &cids);
+        "hashAlg".encode(&mut data);
+        HASH_ALG.encode(&mut data);
+        "bitWidth".encode(&mut data);
+        BIT_WIDTH.encode(&mut data);
+        "bucketSize".encode(&mut data);
+        BUCKET_SIZE.encode(&mut data);
+        let root = store.put(Block::new(Codec::DagCbor, data))?;
+
+        pins.push(root);
+        pins.rotate_right(1);
+        store.pin(&root, &pins)?;
+        Ok(root)
+    }
+}
+
+impl<K, V> Hamt<K, V>
+where
+    K: Decode + Encode,
+    V: Decode,
+{
+    /// Load a map saved by [Hamt::save], from its root block `cid` in
+    /// `store`. Blocks are verified against their CID, and entries against
+    /// their position in the map.
+    ///
+    /// Return `NotFound` if a block is missing from `store`, and
+    /// `Corrupted` if a block fails to decode or breaks the map layout.
+    pub fn load(cid: &Cid, store: &dyn Blockstore) -> Result<Hamt<K, V>> {
+        let data = fetch(store, cid)?;
+        let buf = &mut data.as_slice();
+        let (mut root, mut params) = (None, vec![]);
+        for _ in 0..read_major(buf, cbor::MAP)? {
+            match String::decode(buf)?.as_str() {
+                "hamt" => root = Some(Node::decode(buf, &[])?),
+                key => params.push((key.to_string(), u64::decode(buf)?)),
+            }
+        }
+        params.sort();
+        let want = [
+            ("bitWidth", BIT_WIDTH as u64),
+            ("bucketSize", BUCKET_SIZE as u64),
+            ("hashAlg", HASH_ALG),
+        ];
+        let want: Vec<(String, u64)> =
+            want.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        let root = match root {
+            Some(root) if params == want => root,
+            _ => Err(corrupted(cid, &format!("hamt parameters {:?}", params)))?,
+        };
+
+        // load child nodes, bounded by the depth of the digest.
+        let mut len = 0;
+        let root = root.load(store, &[], &mut len)?;
+        Ok(Hamt {
+            root: Rc::new(root),
+            len,
+        })
+    }
+}
+
+impl<K, V> Node<K, V> {
+    // position of slot `bit` among the occupied slots.
+    fn pos(&self, bit: u32) -> usize {
+        (self.map & (bit - 1)).count_ones() as usize
+    }
+
+    fn slot(&self, digest: &[u8; 32], depth: usize) -> Option<&Slot<K, V>> {
+        let bit = 1 << slot_of(digest, depth);
+        match self.map & bit {
+            0 => None,
+            _ => Some(&self.slots[self.pos(bit)]),
+        }
+    }
+
+    fn children(&self) -> impl Iterator<Item = &Rc<Node<K, V>>> {
+        self.slots.iter().filter_map(|slot| match slot {
+            Slot::Child(child) => Some(child),
+            Slot::Bucket(_) => None,
+        })
+    }
+
+    // entries of this node, if it is small enough to be a bucket of its
+    // parent, that is without child nodes, and up to the bucket size.
+    fn collapse(&self) -> Option<Vec<Entry<K, V>>>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut items = vec![];
+        for slot in self.slots.iter() {
+            match slot {
+                Slot::Bucket(entries) => items.extend_from_slice(entries),
+                Slot::Child(_) => return None,
+            }
+            if items.len() > BUCKET_SIZE {
+                return None;
+            }
+        }
+        items.sort_by_key(|e| e.digest);
+        Some(items)
+    }
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Eq + Clone,
+    V: Clone,
+{
+    // put `entry` into the subtree under this node, at `depth`, and return
+    // the old value of its key.
+    fn put(&mut self, entry: Entry<K, V>, depth: usize) -> Option<V> {
+        let bit = 1 << slot_of(&entry.digest, depth);
+        let pos = self.pos(bit);
+        if self.map & bit == 0 {
+            self.map |= bit;
+            self.slots.insert(pos, Slot::Bucket(vec![entry]));
+            return None;
+        }
+
+        let entries = match &mut self.slots[pos] {
+            Slot::Child(child) => return Rc::make_mut(child).put(entry, depth + 1),
+            Slot::Bucket(entries) => entries,
+        };
+        if let Some(old) = entries.iter_mut().find(|e| e.key == entry.key) {
+            return Some(mem::replace(&mut old.val, entry.val));
+        }
+        if entries.len() < BUCKET_SIZE || depth + 1 >= MAX_DEPTH {
+            let off = entries.partition_point(|e| e.digest <= entry.digest);
+            entries.insert(off, entry);
+        } else {
+            let mut child = Node::default();
+            for entry in entries.drain(..).chain(Some(entry)) {
+                child.put(entry, depth + 1);
+            }
+            self.slots[pos] = Slot::Child(Rc::new(child));
+        }
+        None
+    }
+
+    // delete `key` from the subtree under this node, at `depth`, and return
+    // its value.
+    fn delete(&mut self, digest: &[u8; 32], key: &K, depth: usize) -> Option<V> {
+        let bit = 1 << slot_of(digest, depth);
+        if self.map & bit == 0 {
+            return None;
+        }
+        let pos = self.pos(bit);
+        let (val, collapsed) = match &mut self.slots[pos] {
+            Slot::Bucket(entries) => {
+                let off = entries.iter().position(|e| e.key == *key)?;
+                let val = entries.remove(off).val;
+                if entries.is_empty() {
+                    self.map &= !bit;
+                    self.slots.remove(pos);
+                }
+                return Some(val);
+            }
+            Slot::Child(child) => {
+                let child = Rc::make_mut(child);
+                let val = child.delete(digest, key, depth + 1)?;
+                (val, child.collapse())
+            }
+        };
+        if let Some(entries) = collapsed {
+            self.slots[pos] = Slot::Bucket(entries);
+        }
+        Some(val)
+    }
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Encode,
+    V: Encode,
+{
+    // `[bitmap, [element, ...]]`, where bitmap is a big-endian byte string,
+    // and element is either a link to a child node, already encoded, or a
+    // bucket of `[key, value]` pairs.
+    fn encode(&self, buf: &mut Vec<u8>, cids: &HashMap<*const Node<K, V>, Cid>) {
+        write_head(buf, cbor::ARRAY, 2);
+        write_head(buf, cbor::BYTES, 4);
+        buf.extend_from_slice(&self.map.to_be_bytes());
+        write_head(buf, cbor::ARRAY, self.slots.len() as u64);
+        for slot in self.slots.iter() {
+            match slot {
+                Slot::Bucket(entries) => {
+                    write_head(buf, cbor::ARRAY, entries.len() as u64);
+                    for entry in entries.iter() {
+                        write_head(buf, cbor::ARRAY, 2);
+                        entry.key.encode(buf);
+                        entry.val.encode(buf);
+                    }
+                }
+                Slot::Child(child) => cids[&Rc::as_ptr(child)].encode(buf),
+            }
+        }
+    }
+}
+
+// node decoded from a block, with links to child nodes yet to be loaded.
+struct Decoded<K, V> {
+    map: u32,
+    slots: Vec<(usize, Element<K, V>)>,
+}
+
+enum Element<K, V> {
+    Bucket(Vec<Entry<K, V>>),
+    Link(Cid),
+}
+
+impl<K, V> Node<K, V>
+where
+    K: Decode + Encode,
+    V: Decode,
+{
+    // decode a node at `path`, the slots of its ancestors from the root.
+    fn decode(buf: &mut &[u8], path: &[usize]) -> Result<Decoded<K, V>> {
+        let depth = path.len();
+        if read_major(buf, cbor::ARRAY)? != 2 || read_major(buf, cbor::BYTES)? != 4 {
+            Err(malformed("hamt node, want [bitmap, elements]".to_string()))?
+        }
+        let mut bitmap = [0; 4];
+        bitmap.copy_from_slice(take(buf, 4)?);
+        let map = u32::from_be_bytes(bitmap);
+
+        let n = read_major(buf, cbor::ARRAY)?;
+        if n != u64::from(map.count_ones()) {
+            Err(malformed(format!(
+                "hamt node, {} elements for {:#x}",
+                n, map
+            )))?
+        }
+        let mut slots = vec![];
+        let mut idxs = (0..32).filter(|i| map & (1 << i) != 0);
+        for idx in (0..n).filter_map(|_| idxs.next()) {
+            match buf.first() {
+                Some(initial) if initial >> 5 == cbor::TAG => {
+                    slots.push((idx, Element::Link(Cid::decode(buf)?)))
+                }
+                _ => {
+                    let mut entries: Vec<Entry<K, V>> = vec![];
+                    let n = read_major(buf, cbor::ARRAY)? as usize;
+                    for _ in 0..n {
+                        if read_major(buf, cbor::ARRAY)? != 2 {
+                            Err(malformed("hamt entry, want [key, value]".to_string()))?
+                        }
+                        let key = K::decode(buf)?;
+                        let entry = Entry {
+                            digest: digest_of(&key),
+                            key,
+                            val: V::decode(buf)?,
+                        };
+                        match entries.last() {
+                            _ if !in_slot(&entry.digest, path, idx) => {
+                                Err(malformed("hamt entry in another slot".to_string()))?
+                            }
+                            Some(last) if last.digest >= entry.digest => {
+                                Err(malformed("hamt bucket not sorted".to_string()))?
+                            }
+                            _ => entries.push(entry),
+                        }
+                    }
+                    match n {
+                        n if n == 0 || (n > BUCKET_SIZE && depth + 1 < MAX_DEPTH) => {
+                            Err(malformed(format!("hamt bucket of {} entries", n)))?
+                        }
+                        _ => slots.push((idx, Element::Bucket(entries))),
+                    }
+                }
+            }
+        }
+        Ok(Decoded { map, slots })
+    }
+}
+
+impl<K, V> Decoded<K, V>
+where
+    K: Decode + Encode,
+    V: Decode,
+{
+    // load child nodes from `store`, counting entries into `len`. Recursion
+    // is bounded by the depth of the digest.
+    fn load(
+        self,
+        store: &dyn Blockstore,
+        path: &[usize],
+        len: &mut usize,
+    ) -> Result<Node<K, V>> {
+        let mut slots = vec![];
+        for (idx, slot) in self.slots.into_iter() {
+            let path = [path, &[idx]].concat();
+            match slot {
+                Element::Bucket(entries) => {
+                    *len += entries.len();
+                    slots.push(Slot::Bucket(entries))
+                }
+                Element::Link(cid) if path.len() < MAX_DEPTH => {
+                    let data = fetch(store, &cid)?;
+                    let child = Node::decode(&mut data.as_slice(), &path)?;
+                    slots.push(Slot::Child(Rc::new(child.load(store, &path, len)?)))
+                }
+                Element::Link(cid) => {
+                    Err(corrupted(&cid, "hamt deeper than its digest"))?
+                }
+            }
+        }
+        Ok(Node {
+            map: self.map,
+            slots,
+        })
+    }
+}
+
+/// Iterator over the entries of a [Hamt].
+pub struct HamtIter<'a, K, V> {
+    stack: Vec<slice::Iter<'a, Slot<K, V>>>,
+    bucket: slice::Iter<'a, Entry<K, V>>,
+}
+
+impl<'a, K, V> Iterator for HamtIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.bucket.next() {
+                break Some((&entry.key, &entry.val));
+            }
+            match self.stack.last_mut()?.next() {
+                Some(Slot::Bucket(entries)) => self.bucket = entries.iter(),
+                Some(Slot::Child(child)) => self.stack.push(child.slots.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+fn digest_of<K>(key: &K) -> [u8; 32]
+where
+    K: Encode,
+{
+    let mut buf = vec![];
+    key.encode(&mut buf);
+    Multihash::Sha2_256.digest(&buf)
+}
+
+// slot index at `depth`, from the bits of `digest` at that depth, most
+// significant bit first, padded with zeros beyond the digest.
+fn slot_of(digest: &[u8; 32], depth: usize) -> usize {
+    let mut idx = 0;
+    for i in (depth * BIT_WIDTH)..((depth + 1) * BIT_WIDTH) {
+        let bit = digest
+            .get(i / 8)
+            .map(|b| (b >> (7 - i % 8)) & 1)
+            .unwrap_or(0);
+        idx = (idx << 1) | usize::from(bit);
+    }
+    idx
+}
+
+// whether `digest` belongs in slot `idx` of the node at `path`.
+fn in_slot(digest: &[u8; 32], path: &[usize], idx: usize) -> bool {
+    let mut slots = path.iter().chain(Some(&idx)).enumerate();
+    slots.all(|(depth, idx)| slot_of(digest, depth) == *idx)
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "hamt_test.rs"]
+mod hamt_test;
@@ -0,0 +1,181 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::ipld::MemoryStore;
+
+#[test]
+fn test_hamt() {
+    let seed: u64 = random();
+    println!("test_hamt seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let mut map: Hamt<u64, String> = Hamt::new();
+    let mut refm: BTreeMap<u64, String> = BTreeMap::new();
+    let mut clones = vec![];
+    for i in 0..20_000 {
+        let key = rng.gen::<u64>() % 5000;
+        match rng.gen::<u8>() % 3 {
+            0 => assert_eq!(map.remove(&key), refm.remove(&key)),
+            _ => assert_eq!(
+                map.insert(key, i.to_string()),
+                refm.insert(key, i.to_string())
+            ),
+        }
+        if i % 2000 == 0 {
+            clones.push((map.clone(), refm.clone()));
+        }
+    }
+    assert_eq!(map.len(), refm.len());
+    assert_eq!(map.is_empty(), refm.is_empty());
+    for key in 0..5000 {
+        assert_eq!(map.get(&key), refm.get(&key));
+        assert_eq!(map.contains_key(&key), refm.contains_key(&key));
+    }
+    let mut entries: Vec<(u64, String)> =
+        map.iter().map(|(k, v)| (*k, v.clone())).collect();
+    entries.sort();
+    assert_eq!(entries, refm.clone().into_iter().collect::<Vec<_>>());
+
+    // clones are not affected by later edits.
+    for (map, refm) in clones.into_iter() {
+        assert_eq!(map.len(), refm.len());
+        assert!(refm.iter().all(|(k, v)| map.get(k) == Some(v)));
+    }
+
+    // removing every key leaves an empty map.
+    for key in refm.keys() {
+        assert!(map.remove(key).is_some());
+    }
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+}
+
+#[test]
+fn test_hamt_canonical() {
+    let seed: u64 = random();
+    println!("test_hamt_canonical seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    // same entries, inserted in another order, or left after removing
+    // others, give the same root.
+    let mut keys: Vec<u64> = (0..2000).collect();
+    let map1: Hamt<u64, u64> = keys.iter().map(|k| (*k, k * 2)).collect();
+    let mut store = MemoryStore::new();
+    let root = map1.save(&mut store).unwrap();
+
+    for i in (1..keys.len()).rev() {
+        keys.swap(i, rng.gen::<usize>() % (i + 1));
+    }
+    let mut map2: Hamt<u64, u64> = Hamt::new();
+    for key in keys.iter() {
+        map2.insert(*key + 10_000, 0);
+        map2.insert(*key, key * 2);
+    }
+    for key in keys.iter() {
+        assert_eq!(map2.remove(&(*key + 10_000)), Some(0));
+    }
+    assert_eq!(map2.save(&mut store).unwrap(), root);
+    assert_eq!(
+        map1.iter().collect::<Vec<_>>(),
+        map2.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_hamt_save() {
+    let mut map: Hamt<String, u64> =
+        (0..10_000).map(|i| (format!("key-{}", i), i)).collect();
+    let mut store = MemoryStore::new();
+    let root = map.save(&mut store).unwrap();
+    let n = store.len();
+
+    let val: Hamt<String, u64> = Hamt::load(&root, &store).unwrap();
+    assert_eq!(val.len(), map.len());
+    assert_eq!(
+        val.iter().collect::<Vec<_>>(),
+        map.iter().collect::<Vec<_>>()
+    );
+
+    // an edit saves only the blocks on its path.
+    map.insert("key-1".to_string(), 0);
+    let root2 = map.save(&mut store).unwrap();
+    assert_ne!(root2, root);
+    assert!(store.len() - n <= 4);
+    let val: Hamt<String, u64> = Hamt::load(&root2, &store).unwrap();
+    assert_eq!(val.get(&"key-1".to_string()), Some(&0));
+
+    // empty map.
+    let empty: Hamt<u64, u64> = Hamt::new();
+    let root = empty.save(&mut store).unwrap();
+    assert!(Hamt::<u64, u64>::load(&root, &store).unwrap().is_empty());
+
+    let missing = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    assert!(matches!(
+        Hamt::<u64, u64>::load(&missing, &store),
+        Err(Error::NotFound { .. })
+    ));
+}
+
+#[test]
+fn test_hamt_fixture() {
+    // map with an entry, as laid out by the IPLD HashMap spec.
+    let map: Hamt<String, u64> = Some(("a".to_string(), 1)).into_iter().collect();
+    let mut store = MemoryStore::new();
+    let root = map.save(&mut store).unwrap();
+    assert_eq!(store.len(), 1);
+
+    let slot = slot_of(&digest_of(&"a".to_string()), 0);
+    let mut node = vec![0x82, 0x44];
+    node.extend_from_slice(&(1_u32 << slot).to_be_bytes());
+    node.extend_from_slice(&[0x81, 0x81, 0x82, 0x61, b'a', 0x01]);
+    let data = [
+        &[0xa4, 0x64][..],
+        b"hamt",
+        &node,
+        &[0x67],
+        b"hashAlg",
+        &[0x12, 0x68],
+        b"bitWidth",
+        &[0x05, 0x6a],
+        b"bucketSize",
+        &[0x03],
+    ]
+    .concat();
+    assert_eq!(store.get(&root).unwrap(), Some(data));
+}
+
+#[test]
+fn test_hamt_corrupted() {
+    let map: Hamt<u64, u64> = (0..100).map(|i| (i, i)).collect();
+    let mut store = MemoryStore::new();
+    let root = map.save(&mut store).unwrap();
+    let data = store.get(&root).unwrap().unwrap();
+    let load = |data: Vec<u8>| {
+        let mut store = store.clone();
+        let cid = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+        Hamt::<u64, u64>::load(&cid, &store)
+    };
+    assert!(load(data.clone()).is_ok());
+
+    // parameters other than the ones supported.
+    let mut bad = data.clone();
+    let off = bad.windows(8).position(|w| w == b"bitWidth").unwrap();
+    bad[off + 8] = 0x08;
+    assert!(matches!(load(bad), Err(Error::Corrupted { .. })));
+
+    // bitmap not matching the number of elements.
+    let mut bad = data.clone();
+    let off = bad.windows(4).position(|w| w == b"hamt").unwrap();
+    bad[off + 7] ^= 0x01;
+    assert!(matches!(load(bad), Err(Error::Corrupted { .. })));
+
+    // entries moved to another slot.
+    let mut bad = data;
+    let off = bad.windows(4).position(|w| w == b"hamt").unwrap();
+    let map =
+        u32::from_be_bytes([bad[off + 6], bad[off + 7], bad[off + 8], bad[off + 9]]);
+    bad[off + 6..off + 10].copy_from_slice(&map.rotate_left(1).to_be_bytes());
+    assert!(matches!(load(bad), Err(Error::Corrupted { .. })));
+}
@@ -0,0 +1,480 @@
+//! DAG-JSON encoding, refer [spec](https://ipld.io/specs/codecs/dag-json/spec/).
+//!
+//! Nodes are encoded in DAG-CBOR, and transcoded to and from DAG-JSON,
+//! which shares the same data model. Both directions walk nested items
+//! using an explicit stack.
+
+use super::{
+    cbor::{self, malformed, read_head, take, write_head},
+    Cid, Encode,
+};
+use crate::Result;
+
+// standard base64 alphabet, of RFC 4648, used without padding.
+const BASE64: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// array or map being transcoded, with the number of items in it, counting
+// keys and values for maps, and the number of items done.
+struct Container {
+    is_map: bool,
+    n_items: u64,
+    done: u64,
+}
+
+// transcode a DAG-CBOR data item into DAG-JSON.
+pub(crate) fn from_cbor(mut data: &[u8]) -> Result<Vec<u8>> {
+    let buf = &mut data;
+    let mut out = vec![];
+
+    let mut stack: Vec<Container> = vec![];
+    loop {
+        let mut is_key = false;
+        if let Some(c) = stack.last_mut() {
+            match c.done {
+                0 => (),
+                n if c.is_map && n % 2 == 1 => out.push(b':'),
+                _ => out.push(b','),
+            }
+            is_key = c.is_map && c.done % 2 == 0;
+            c.done += 1;
+        }
+
+        match read_head(buf)? {
+            (cbor::TEXT, n) => write_string(&mut out, take(buf, n)?)?,
+            (major, _) if is_key => Err(malformed(format!("map key of type {}", major)))?,
+            (cbor::UINT, n) => out.extend_from_slice(n.to_string().as_bytes()),
+            (cbor::NINT, n) => {
+                let n = -1 - i128::from(n);
+                out.extend_from_slice(n.to_string().as_bytes())
+            }
+            (cbor::BYTES, n) => {
+                out.extend_from_slice(br#"{"/":{"bytes":""#);
+                out.extend_from_slice(base64_encode(take(buf, n)?).as_bytes());
+                out.extend_from_slice(br#""}}"#);
+            }
+            (cbor::ARRAY, n) => {
+                out.push(b'[');
+                stack.push(Container {
+                    is_map: false,
+                    n_items: n,
+                    done: 0,
+                });
+            }
+            (cbor::MAP, n) => {
+                out.push(b'{');
+                stack.push(Container {
+                    is_map: true,
+                    n_items: n.saturating_mul(2),
+                    done: 0,
+                });
+            }
+            (cbor::TAG, cbor::TAG_CID) => {
+                let n = cbor::read_major(buf, cbor::BYTES)?;
+                let cid = match take(buf, n)? {
+                    [0x00, bytes @ ..] => Cid::from_bytes(bytes)?,
+                    _ => Err(malformed("link without the identity multibase".into()))?,
+                };
+                out.extend_from_slice(format!(r#"{{"/":"{}"}}"#, cid).as_bytes());
+            }
+            (cbor::SIMPLE, 20) => out.extend_from_slice(b"false"),
+            (cbor::SIMPLE, 21) => out.extend_from_slice(b"true"),
+            (cbor::SIMPLE, 22) => out.extend_from_slice(b"null"),
+            (major, arg) => {
+                let msg = format!("item {}/{} has no dag-json form", major, arg);
+                Err(malformed(msg))?
+            }
+        }
+
+        while let Some(c) = stack.last() {
+            match c.done == c.n_items {
+                true if c.is_map => out.push(b'}'),
+                true => out.push(b']'),
+                false => break,
+            }
+            stack.pop();
+        }
+        if stack.is_empty() {
+            break;
+        }
+    }
+
+    match buf.len() {
+        0 => Ok(out),
+        n => Err(malformed(format!("{} bytes past the item", n))),
+    }
+}
+
+// array or map being parsed, along with its items encoded in DAG-CBOR,
+// counting key-value pairs for maps.
+struct Frame {
+    is_map: bool,
+    n_items: u64,
+    buf: Vec<u8>,
+}
+
+// transcode a DAG-JSON value into DAG-CBOR.
+pub(crate) fn to_cbor(data: &[u8]) -> Result<Vec<u8>> {
+    let mut p = Parser { data, off: 0 };
+
+    // the outermost frame collects the top-level value.
+    let mut stack = vec![Frame {
+        is_map: false,
+        n_items: 0,
+        buf: vec![],
+    }];
+    let mut want_value = true;
+    loop {
+        let depth = stack.len();
+        let top = stack.last_mut().unwrap();
+        if want_value {
+            match p.peek()? {
+                b'[' if p.empty(b'[', b']')? => write_head(&mut top.buf, cbor::ARRAY, 0),
+                b'[' => {
+                    p.expect(b'[')?;
+                    stack.push(Frame {
+                        is_map: false,
+                        n_items: 0,
+                        buf: vec![],
+                    });
+                    continue;
+                }
+                b'{' if p.empty(b'{', b'}')? => write_head(&mut top.buf, cbor::MAP, 0),
+                b'{' => match p.kind()? {
+                    Some(bytes) => top.buf.extend_from_slice(&bytes),
+                    None => {
+                        let mut frame = Frame {
+                            is_map: true,
+                            n_items: 0,
+                            buf: vec![],
+                        };
+                        p.expect(b'{')?;
+                        p.key(&mut frame.buf)?;
+                        stack.push(frame);
+                        continue;
+                    }
+                },
+                b'"' => {
+                    let s = p.string()?;
+                    write_head(&mut top.buf, cbor::TEXT, s.len() as u64);
+                    top.buf.extend_from_slice(s.as_bytes());
+                }
+                b't' => {
+                    p.literal(b"true")?;
+                    write_head(&mut top.buf, cbor::SIMPLE, 21);
+                }
+                b'f' => {
+                    p.literal(b"false")?;
+                    write_head(&mut top.buf, cbor::SIMPLE, 20);
+                }
+                b'n' => {
+                    p.literal(b"null")?;
+                    write_head(&mut top.buf, cbor::SIMPLE, 22);
+                }
+                _ => p.number(&mut top.buf)?,
+            }
+            top.n_items += 1;
+            want_value = false;
+        } else if depth == 1 {
+            break;
+        } else {
+            match (p.next()?, top.is_map) {
+                (b',', true) => {
+                    p.key(&mut top.buf)?;
+                    want_value = true;
+                }
+                (b',', false) => want_value = true,
+                (b'}', true) | (b']', false) => {
+                    let frame = stack.pop().unwrap();
+                    let top = stack.last_mut().unwrap();
+                    let major = if frame.is_map { cbor::MAP } else { cbor::ARRAY };
+                    write_head(&mut top.buf, major, frame.n_items);
+                    top.buf.extend_from_slice(&frame.buf);
+                    top.n_items += 1;
+                }
+                (ch, _) => Err(p.unexpected(ch))?,
+            }
+        }
+    }
+
+    p.skip_ws();
+    match p.data.len() - p.off {
+        0 => Ok(stack.pop().unwrap().buf),
+        n => Err(malformed(format!("{} bytes past the json value", n))),
+    }
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    off: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.data.get(self.off) {
+            self.off += 1
+        }
+    }
+
+    // return the next byte, after white space, without consuming it.
+    fn peek(&mut self) -> Result<u8> {
+        self.skip_ws();
+        match self.data.get(self.off) {
+            Some(ch) => Ok(*ch),
+            None => Err(malformed("json truncated".to_string())),
+        }
+    }
+
+    fn next(&mut self) -> Result<u8> {
+        let ch = self.peek()?;
+        self.off += 1;
+        Ok(ch)
+    }
+
+    fn expect(&mut self, want: u8) -> Result<()> {
+        match self.next()? {
+            ch if ch == want => Ok(()),
+            ch => Err(self.unexpected(ch)),
+        }
+    }
+
+    fn unexpected(&self, ch: u8) -> crate::Error {
+        malformed(format!("json {:?} at {}", ch as char, self.off))
+    }
+
+    fn literal(&mut self, word: &[u8]) -> Result<()> {
+        match self.data.get(self.off..self.off + word.len()) {
+            Some(s) if s == word => {
+                self.off += word.len();
+                Ok(())
+            }
+            _ => Err(self.unexpected(self.data[self.off])),
+        }
+    }
+
+    // consume an empty array or map, if that is what follows.
+    fn empty(&mut self, open: u8, close: u8) -> Result<bool> {
+        let off = self.off;
+        self.expect(open)?;
+        match self.peek()? {
+            ch if ch == close => {
+                self.off += 1;
+                Ok(true)
+            }
+            _ => {
+                self.off = off;
+                Ok(false)
+            }
+        }
+    }
+
+    // consume a map key, and the colon following it, and append the key
+    // to `buf`.
+    fn key(&mut self, buf: &mut Vec<u8>) -> Result<()> {
+        let key = self.string()?;
+        write_head(buf, cbor::TEXT, key.len() as u64);
+        buf.extend_from_slice(key.as_bytes());
+        self.expect(b':')
+    }
+
+    // a map whose only key is "/" is a link, `{"/": cid}`, or bytes,
+    // `{"/": {"bytes": base64}}`, return them encoded, else None.
+    fn kind(&mut self) -> Result<Option<Vec<u8>>> {
+        let off = self.off;
+        self.expect(b'{')?;
+        if self.peek()? != b'"' || self.string()? != "/" {
+            self.off = off;
+            return Ok(None);
+        }
+        self.expect(b':')?;
+
+        let mut buf = vec![];
+        match self.peek()? {
+            b'"' => {
+                let cid: Cid = self.string()?.parse()?;
+                cid.encode(&mut buf);
+            }
+            _ => {
+                self.expect(b'{')?;
+                match self.string()?.as_str() {
+                    "bytes" => (),
+                    key => Err(malformed(format!("json bytes with key {:?}", key)))?,
+                }
+                self.expect(b':')?;
+                let bytes = base64_decode(&self.string()?)?;
+                write_head(&mut buf, cbor::BYTES, bytes.len() as u64);
+                buf.extend_from_slice(&bytes);
+                self.expect(b'}')?;
+            }
+        }
+        self.expect(b'}')?;
+        Ok(Some(buf))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut s = vec![];
+        loop {
+            let ch = match self.data.get(self.off) {
+                Some(ch) => *ch,
+                None => Err(malformed("json string truncated".to_string()))?,
+            };
+            self.off += 1;
+            match ch {
+                b'"' => break,
+                b'\\' => {
+                    let ch = match self.data.get(self.off) {
+                        Some(ch) => *ch,
+                        None => Err(malformed("json string truncated".to_string()))?,
+                    };
+                    self.off += 1;
+                    match ch {
+                        b'"' | b'\\' | b'/' => s.push(ch),
+                        b'b' => s.push(0x08),
+                        b'f' => s.push(0x0c),
+                        b'n' => s.push(b'\n'),
+                        b'r' => s.push(b'\r'),
+                        b't' => s.push(b'\t'),
+                        b'u' => {
+                            let ch = self.unicode()?;
+                            s.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                        }
+                        ch => Err(self.unexpected(ch))?,
+                    }
+                }
+                ch if ch < 0x20 => Err(self.unexpected(ch))?,
+                ch => s.push(ch),
+            }
+        }
+        String::from_utf8(s).map_err(|err| malformed(format!("json string {}", err)))
+    }
+
+    // escaped code point, `\uXXXX`, or a surrogate pair of them.
+    fn unicode(&mut self) -> Result<char> {
+        let hi = self.hex4()?;
+        let code = match hi {
+            0xd800..=0xdbff => {
+                self.literal(b"\\u")?;
+                match self.hex4()? {
+                    lo @ 0xdc00..=0xdfff => {
+                        0x10000 + ((hi - 0xd800) << 10) + (lo - 0xdc00)
+                    }
+                    lo => Err(malformed(format!("json surrogate pair {} {}", hi, lo)))?,
+                }
+            }
+            code => code,
+        };
+        char::from_u32(code).ok_or_else(|| malformed(format!("json code point {}", code)))
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self.data.get(self.off..self.off + 4);
+        let code = digits
+            .and_then(|d| std::str::from_utf8(d).ok())
+            .and_then(|d| u32::from_str_radix(d, 16).ok());
+        match code {
+            Some(code) => {
+                self.off += 4;
+                Ok(code)
+            }
+            None => Err(malformed(format!("json escape at {}", self.off))),
+        }
+    }
+
+    // integer, appended to `buf`, fractions and exponents are not
+    // supported, since floats are not a part of the encoding.
+    fn number(&mut self, buf: &mut Vec<u8>) -> Result<()> {
+        let start = self.off;
+        if self.data.get(self.off) == Some(&b'-') {
+            self.off += 1;
+        }
+        while let Some(b'0'..=b'9') = self.data.get(self.off) {
+            self.off += 1;
+        }
+
+        let text = std::str::from_utf8(&self.data[start..self.off]).unwrap_or("");
+        let digits = text.trim_start_matches('-');
+        let n = match digits.parse::<u128>() {
+            Ok(n) if digits.len() == 1 || !digits.starts_with('0') => n,
+            _ => Err(malformed(format!("json number at {}", start)))?,
+        };
+        if let Some(b'.' | b'e' | b'E') = self.data.get(self.off) {
+            Err(malformed(format!("json float at {}, not supported", start)))?
+        }
+
+        match (text.starts_with('-'), n) {
+            (false, n) if n <= u128::from(u64::MAX) => {
+                write_head(buf, cbor::UINT, n as u64);
+                Ok(())
+            }
+            (true, n) if n > 0 && n <= u128::from(u64::MAX) + 1 => {
+                write_head(buf, cbor::NINT, (n - 1) as u64);
+                Ok(())
+            }
+            (true, 0) => {
+                write_head(buf, cbor::UINT, 0);
+                Ok(())
+            }
+            _ => Err(malformed(format!("json integer {} overflows", text))),
+        }
+    }
+}
+
+// write `s` as a json string, escaping quotes, backslash and control
+// characters, and nothing else.
+fn write_string(out: &mut Vec<u8>, s: &[u8]) -> Result<()> {
+    let s = std::str::from_utf8(s).map_err(|err| malformed(format!("text {}", err)))?;
+    out.push(b'"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.extend_from_slice(br#"\""#),
+            '\\' => out.extend_from_slice(br#"\\"#),
+            '\u{8}' => out.extend_from_slice(br#"\b"#),
+            '\u{c}' => out.extend_from_slice(br#"\f"#),
+            '\n' => out.extend_from_slice(br#"\n"#),
+            '\r' => out.extend_from_slice(br#"\r"#),
+            '\t' => out.extend_from_slice(br#"\t"#),
+            ch if (ch as u32) < 0x20 => {
+                out.extend_from_slice(format!("\\u{:04x}", ch as u32).as_bytes())
+            }
+            ch => out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    out.push(b'"');
+    Ok(())
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for group in bytes.chunks(3) {
+        let mut acc = [0; 3];
+        acc[..group.len()].copy_from_slice(group);
+        let n = u32::from_be_bytes([0, acc[0], acc[1], acc[2]]);
+        for i in 0..=group.len() {
+            s.push(BASE64[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+        }
+    }
+    s
+}
+
+fn base64_decode(s: &str) -> Result<Vec<u8>> {
+    let mut bytes = vec![];
+    for group in s.as_bytes().chunks(4) {
+        let mut n = 0_u32;
+        for (i, ch) in group.iter().enumerate() {
+            match BASE64.iter().position(|x| x == ch) {
+                Some(val) => n |= (val as u32) << (18 - 6 * i),
+                None => Err(malformed(format!("base64 {:?}", s)))?,
+            }
+        }
+        match group.len() {
+            1 => Err(malformed(format!("base64 {:?} truncated", s)))?,
+            k => bytes.extend_from_slice(&n.to_be_bytes()[1..k]),
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+#[path = "json_test.rs"]
+mod json_test;
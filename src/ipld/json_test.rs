@@ -0,0 +1,123 @@
+use super::*;
+use crate::{
+    ipld::{self, Block, Codec},
+    Error,
+};
+
+fn json(cbor: &[u8]) -> String {
+    String::from_utf8(from_cbor(cbor).unwrap()).unwrap()
+}
+
+#[test]
+fn test_json() {
+    let cid = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    let data = ipld::encode_interior(2, &[(2, cid)]);
+    let refs = format!(
+        r#"{{"len":2,"links":[[2,{{"/":"{}"}}]],"version":[1,0]}}"#,
+        cid
+    );
+    assert_eq!(json(&data), refs);
+    assert_eq!(to_cbor(refs.as_bytes()).unwrap(), data);
+
+    let items: Vec<Option<i64>> = vec![Some(0), Some(-1), Some(i64::MIN), None];
+    let data = ipld::encode_leaf(&items);
+    let refs = r#"{"items":[0,-1,-9223372036854775808,null],"version":[1,0]}"#;
+    assert_eq!(json(&data), refs);
+    assert_eq!(to_cbor(refs.as_bytes()).unwrap(), data);
+
+    let items = vec!["a\"b\\c".to_string(), "\n\t\u{1}\u{fc}".to_string()];
+    let data = ipld::encode_leaf(&items);
+    let refs = r#"{"items":["a\"b\\c","\n\t\u0001ü"],"version":[1,0]}"#;
+    assert_eq!(json(&data), refs.replace("\\u00fc", "\u{fc}"));
+    assert_eq!(to_cbor(refs.as_bytes()).unwrap(), data);
+
+    // bytes, with and without padding in base64, and nested arrays.
+    let testcases: Vec<(Vec<u8>, &str)> = vec![
+        (vec![0x40], r#"{"/":{"bytes":""}}"#),
+        (vec![0x41, 0xff], r#"{"/":{"bytes":"/w"}}"#),
+        (vec![0x42, 0xff, 0xfe], r#"{"/":{"bytes":"//4"}}"#),
+        (vec![0x43, 0x01, 0x02, 0x03], r#"{"/":{"bytes":"AQID"}}"#),
+        (vec![0x82, 0x80, 0x81, 0x80], "[[],[[]]]"),
+        (vec![0xa1, 0x61, 0x61, 0xa0], r#"{"a":{}}"#),
+        (vec![0xf5], "true"),
+        (vec![0xf4], "false"),
+        (
+            vec![0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            "18446744073709551615",
+        ),
+        (
+            vec![0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+            "-18446744073709551616",
+        ),
+    ];
+    for (data, refs) in testcases.into_iter() {
+        assert_eq!(json(&data), refs);
+        assert_eq!(to_cbor(refs.as_bytes()).unwrap(), data, "{}", refs);
+    }
+
+    // white space, and escapes, are tolerated.
+    let refs = " { \"items\" : [ 1 , \"\\ud83d\\ude00\\/\" ] } ";
+    let item = "\u{1f600}/".to_string();
+    let mut data = vec![0xa1, 0x65];
+    data.extend_from_slice(b"items");
+    data.extend_from_slice(&[0x82, 0x01]);
+    item.encode(&mut data);
+    assert_eq!(to_cbor(refs.as_bytes()).unwrap(), data);
+}
+
+#[test]
+fn test_json_malformed() {
+    let testcases = [
+        "",
+        "[1,2",
+        "[1,]",
+        "[1 2]",
+        r#"{"a" 1}"#,
+        r#"{"a":1,}"#,
+        r#"{1:1}"#,
+        "1.5",
+        "1e3",
+        "01",
+        "-",
+        "18446744073709551616",
+        "-18446744073709551617",
+        "tru",
+        r#""a"#,
+        "\"\u{1}\"",
+        r#""\x""#,
+        r#""\ud83d""#,
+        r#"{"/":"bafy"}"#,
+        r#"{"/":{"bytes":"A"}}"#,
+        r#"{"/":{"bytes":"**"}}"#,
+        r#"{"/":{"text":""}}"#,
+        "1 2",
+    ];
+    for json in testcases.iter() {
+        let res = to_cbor(json.as_bytes());
+        assert!(
+            matches!(res, Err(Error::Corrupted { .. })),
+            "{:?} {:?}",
+            json,
+            res
+        );
+    }
+
+    // map keys other than text, floats, undefined, and trailing bytes.
+    let testcases: Vec<Vec<u8>> = vec![
+        vec![0xa1, 0x01, 0x01],
+        vec![0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0],
+        vec![0xf7],
+        vec![0x01, 0x01],
+        vec![0x82, 0x01],
+        vec![0xc1, 0x01],
+    ];
+    for data in testcases.iter() {
+        let res = from_cbor(data);
+        assert!(
+            matches!(res, Err(Error::Corrupted { .. })),
+            "{:?} {:?}",
+            data,
+            res
+        );
+    }
+}
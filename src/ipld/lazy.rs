@@ -0,0 +1,374 @@
+//! Vector read from a blockstore on demand, block by block.
+
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    rc::Rc,
+};
+
+use super::{decode_node, fetch, lru::Lru, Blockstore, Cid, Decode, Decoded};
+use crate::{Error, Result};
+
+// default number of blocks hinted ahead of an iteration.
+const READAHEAD: usize = 4;
+
+/// Vector saved into a blockstore, by `Vector::save`, read on demand.
+///
+/// Opening the vector reads only its root block. Rest of the blocks are
+/// read when an item under them is accessed for the first time, interior
+/// nodes are then kept, and leaf nodes are cached by their CID, so that
+/// loading a vector larger than memory does not read every block before
+/// the first [LazyVector::get]. Items are returned by value.
+///
+/// Memory used by leaf nodes can be bounded by a budget of items, refer
+/// [LazyVector::set_budget], in which case least recently used leaf nodes
+/// are dropped from the cache, and read again when accessed again.
+///
+/// While iterating, blocks ahead of the iteration are hinted to the store,
+/// refer [Blockstore::prefetch] and [LazyVector::set_readahead], so that
+/// stores backed by slow storage can fetch them in the background.
+pub struct LazyVector<'a, T> {
+    store: &'a dyn Blockstore,
+    len: usize,
+    links: Vec<Link>,
+    // leaf nodes, sized by their number of items.
+    leaves: RefCell<Lru<Rc<Vec<T>>>>,
+    readahead: usize,
+    // number of blocks read from the store.
+    n_reads: Cell<usize>,
+}
+
+// link to a child node, read on first access.
+struct Link {
+    // number of items up to and including this child.
+    count: usize,
+    cid: Cid,
+    node: OnceCell<Lazy>,
+}
+
+enum Lazy {
+    Interior { links: Vec<Link> },
+    // items are held by the leaf cache.
+    Leaf,
+}
+
+impl Link {
+    fn new((count, cid): (usize, Cid)) -> Link {
+        Link {
+            count,
+            cid,
+            node: OnceCell::new(),
+        }
+    }
+}
+
+impl<'a, T> LazyVector<'a, T>
+where
+    T: Decode + Clone,
+{
+    /// Open the vector saved in `store`, by the CID of its root block,
+    /// reading only the root block.
+    pub fn open(cid: &Cid, store: &'a dyn Blockstore) -> Result<LazyVector<'a, T>> {
+        let (len, links) = match decode_node::<T>(&fetch(store, cid)?)? {
+            Decoded::Interior { len, links } if links.len() <= 2 => (len, links),
+            _ => Err(corrupted(cid, "root block is not over root and tail"))?,
+        };
+        check_counts(cid, len, &links)?;
+
+        Ok(LazyVector {
+            store,
+            len,
+            links: links.into_iter().map(Link::new).collect(),
+            leaves: RefCell::new(Lru::new(|items: &Rc<Vec<T>>| items.len())),
+            readahead: READAHEAD,
+            n_reads: Cell::new(1),
+        })
+    }
+
+    /// Bound the number of items held by cached leaf nodes to `budget`,
+    /// None for no bound, which is the default. The last leaf node read
+    /// is cached even if it exceeds the budget. Interior nodes, that make
+    /// up the spine of the tree, are not counted.
+    pub fn set_budget(&mut self, budget: Option<usize>) -> &mut Self {
+        self.leaves.get_mut().set_capacity(budget);
+        self
+    }
+
+    /// Hint up to `n` blocks, ahead of the leaf node being iterated, to the
+    /// store, refer [Blockstore::prefetch]. Blocks hinted are read on
+    /// entering the next leaf node, and count towards the budget, refer
+    /// [LazyVector::set_budget]. Zero disables the hints, default is 4.
+    pub fn set_readahead(&mut self, n: usize) -> &mut Self {
+        self.readahead = n;
+        self
+    }
+
+    /// Return the number of items held by cached leaf nodes.
+    pub fn cached_items(&self) -> usize {
+        self.leaves.borrow().size()
+    }
+
+    /// Return the number of items in the vector.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether the vector is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the number of blocks read from the store so far.
+    pub fn n_reads(&self) -> usize {
+        self.n_reads.get()
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error. Blocks on
+    /// the path to the item are read, if not read before, returning
+    /// `NotFound` or `Corrupted` if they are missing or fail to decode.
+    pub fn get(&self, index: usize) -> Result<T> {
+        let (items, off) = self.leaf_of(index)?;
+        Ok(items[index - off].clone())
+    }
+
+    /// Return an iterator over the items, reading blocks as the iteration
+    /// reaches them. Iteration stops after the first error.
+    pub fn iter(&self) -> LazyIter<'_, 'a, T> {
+        LazyIter {
+            arr: self,
+            index: 0,
+            leaf: None,
+            hinted: vec![],
+        }
+    }
+
+    // return the items in the leaf node holding `index`, along with the
+    // offset of its first item.
+    fn leaf_of(&self, index: usize) -> Result<(Rc<Vec<T>>, usize)> {
+        if index >= self.len {
+            Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len,
+            })?
+        }
+
+        let (mut links, mut off) = (&self.links, 0);
+        loop {
+            let k = links.partition_point(|link| off + link.count <= index);
+            let (link, base) = match k {
+                0 => (&links[k], off),
+                k => (&links[k], off + links[k - 1].count),
+            };
+            let len = off + link.count - base;
+            match self.node(link, len)? {
+                Lazy::Interior { links: children } => {
+                    links = children;
+                    off = base;
+                }
+                Lazy::Leaf => break Ok((self.leaf(&link.cid, len)?, base)),
+            }
+        }
+    }
+
+    // CIDs of up to `n` blocks, following the leaf node holding `index` in
+    // the order of iteration, that are yet to be read. Interior nodes are
+    // descended into if already read, or read if `hinted` before, so that
+    // the store had a chance to fetch them ahead.
+    fn upcoming(&self, index: usize, n: usize, hinted: &[Cid]) -> Vec<Cid> {
+        let mut path = vec![];
+        let (mut links, mut off) = (&self.links, 0);
+        loop {
+            let k = links.partition_point(|link| off + link.count <= index);
+            path.push(siblings(links, k + 1));
+            match links[k].node.get() {
+                Some(Lazy::Interior { links: children }) => {
+                    off += if k == 0 { 0 } else { links[k - 1].count };
+                    links = children;
+                }
+                _ => break,
+            }
+        }
+
+        // nearest siblings, those on the deepest level, come first. Leaf
+        // nodes already read count towards `n`, keeping the window from
+        // running ahead of the iteration.
+        let mut stack: Vec<(&Link, usize)> = path.into_iter().flatten().collect();
+        let (mut cids, mut ahead) = (vec![], 0);
+        while let Some((link, len)) = stack.pop() {
+            if ahead == n {
+                break;
+            }
+            let node = match link.node.get() {
+                None if hinted.contains(&link.cid) => self.node(link, len).ok(),
+                node => node,
+            };
+            match node {
+                Some(Lazy::Interior { links }) => stack.extend(siblings(links, 0)),
+                Some(Lazy::Leaf) if self.leaves.borrow().contains(&link.cid) => {
+                    ahead += 1
+                }
+                _ => {
+                    cids.push(link.cid);
+                    ahead += 1
+                }
+            }
+        }
+        cids
+    }
+
+    // read the node under `link`, holding `len` items, if not read before.
+    fn node<'s>(&'s self, link: &'s Link, len: usize) -> Result<&'s Lazy> {
+        if let Some(node) = link.node.get() {
+            return Ok(node);
+        }
+        let node = match self.read(&link.cid)? {
+            Decoded::Interior { len: n, links } => {
+                check_counts(&link.cid, n, &links)?;
+                match n {
+                    n if n == len && !links.is_empty() => Lazy::Interior {
+                        links: links.into_iter().map(Link::new).collect(),
+                    },
+                    n => Err(corrupted(&link.cid, &format!("len {}, want {}", n, len)))?,
+                }
+            }
+            Decoded::Leaf { items } => {
+                self.cache(&link.cid, len, items)?;
+                Lazy::Leaf
+            }
+        };
+        Ok(link.node.get_or_init(|| node))
+    }
+
+    // items of leaf `cid`, holding `len` items, from the cache, or read
+    // from the store.
+    fn leaf(&self, cid: &Cid, len: usize) -> Result<Rc<Vec<T>>> {
+        if let Some(items) = self.leaves.borrow_mut().get(cid) {
+            return Ok(Rc::clone(items));
+        }
+        match self.read(cid)? {
+            Decoded::Leaf { items } => self.cache(cid, len, items),
+            Decoded::Interior { .. } => Err(corrupted(cid, "not a leaf node")),
+        }
+    }
+
+    fn cache(&self, cid: &Cid, len: usize, items: Vec<T>) -> Result<Rc<Vec<T>>> {
+        match items.len() {
+            n if n == len => {
+                let items = Rc::new(items);
+                self.leaves.borrow_mut().insert(*cid, Rc::clone(&items));
+                Ok(items)
+            }
+            n => Err(corrupted(cid, &format!("{} items, want {}", n, len))),
+        }
+    }
+
+    fn read(&self, cid: &Cid) -> Result<Decoded<T>> {
+        self.n_reads.set(self.n_reads.get() + 1);
+        decode_node(&fetch(self.store, cid)?)
+    }
+}
+
+/// Iterator over the items of a [LazyVector].
+pub struct LazyIter<'s, 'a, T> {
+    arr: &'s LazyVector<'a, T>,
+    index: usize,
+    // leaf node holding `index`, along with the offset of its first item.
+    leaf: Option<(Rc<Vec<T>>, usize)>,
+    // blocks recently hinted to the store.
+    hinted: Vec<Cid>,
+}
+
+impl<'s, 'a, T> Iterator for LazyIter<'s, 'a, T>
+where
+    T: Decode + Clone,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.index >= self.arr.len {
+            return None;
+        }
+
+        let (items, off) = match self.leaf.take() {
+            Some((items, off)) if self.index < off + items.len() => (items, off),
+            _ => match self.arr.leaf_of(self.index) {
+                Ok(leaf) => {
+                    self.readahead();
+                    leaf
+                }
+                Err(err) => {
+                    self.index = self.arr.len;
+                    return Some(Err(err));
+                }
+            },
+        };
+        let item = items[self.index - off].clone();
+        self.index += 1;
+        self.leaf = Some((items, off));
+        Some(Ok(item))
+    }
+}
+
+impl<'s, 'a, T> LazyIter<'s, 'a, T>
+where
+    T: Decode + Clone,
+{
+    // hint blocks ahead of the leaf node holding `index`, leaving out those
+    // hinted recently. Nodes hinted, and pushed out of the window by the
+    // children of a node read since, come back into the window later.
+    fn readahead(&mut self) {
+        let n = self.arr.readahead;
+        if n > 0 {
+            let fresh: Vec<Cid> = self
+                .arr
+                .upcoming(self.index, n, &self.hinted)
+                .into_iter()
+                .filter(|cid| !self.hinted.contains(cid))
+                .collect();
+            if !fresh.is_empty() {
+                self.arr.store.prefetch(&fresh)
+            }
+            self.hinted.extend_from_slice(&fresh);
+            let k = self.hinted.len().saturating_sub(n * 4);
+            self.hinted.drain(..k);
+        }
+    }
+}
+
+// links from `from` onwards, along with the number of items under each,
+// in reverse order.
+fn siblings(links: &[Link], from: usize) -> Vec<(&Link, usize)> {
+    let mut items: Vec<(&Link, usize)> = (from..links.len())
+        .map(|k| match k {
+            0 => (&links[k], links[k].count),
+            k => (&links[k], links[k].count - links[k - 1].count),
+        })
+        .collect();
+    items.reverse();
+    items
+}
+
+// links of an interior block shall count items cumulatively, up to `len`.
+pub(crate) fn check_counts(cid: &Cid, len: usize, links: &[(usize, Cid)]) -> Result<()> {
+    let mut count = 0;
+    for (n, _) in links.iter() {
+        match *n {
+            n if n < count => {
+                Err(corrupted(cid, &format!("link count {} after {}", n, count)))?
+            }
+            n => count = n,
+        }
+    }
+    match count {
+        count if count == len => Ok(()),
+        count => Err(corrupted(cid, &format!("len {}, want {}", len, count))),
+    }
+}
+
+pub(crate) fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "lazy_test.rs"]
+mod lazy_test;
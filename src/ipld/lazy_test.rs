@@ -0,0 +1,266 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashSet,
+    mem,
+};
+
+use super::*;
+use crate::{
+    ipld::{self, Block, CacheStore, Codec, MemoryStore},
+    rc::Vector,
+};
+
+// store counting the blocks read from it.
+#[derive(Default)]
+struct CountStore {
+    store: MemoryStore,
+    gets: Cell<usize>,
+}
+
+impl Blockstore for CountStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.gets.set(self.gets.get() + 1);
+        self.store.get(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+}
+
+#[test]
+fn test_lazy_vector() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let mut store = CountStore::default();
+    let cid = arr.save(&mut store).unwrap();
+    let n_blocks = store.store.len();
+
+    let val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    assert_eq!(val.len(), items.len());
+    assert!(!val.is_empty());
+    assert_eq!(store.gets.get(), 1);
+    assert_eq!(val.n_reads(), 1);
+
+    // first get reads only the path to the item.
+    assert_eq!(val.get(50_000).unwrap(), 50_000);
+    let n = store.gets.get();
+    assert!(n <= arr.depth() + 2, "{} {}", n, arr.depth());
+    assert_eq!(val.get(50_001).unwrap(), 50_001);
+    assert_eq!(store.gets.get(), n);
+    assert_eq!(val.n_reads(), n);
+
+    for index in [0, 1023, 1024, 99_999].iter() {
+        assert_eq!(val.get(*index).unwrap(), *index as u64);
+    }
+    assert!(matches!(
+        val.get(100_000),
+        Err(Error::IndexOutOfBounds { .. })
+    ));
+
+    let iterd: Vec<u64> = val.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(iterd, items);
+    assert_eq!(store.gets.get(), n_blocks);
+}
+
+#[test]
+fn test_lazy_vector_edits() {
+    let mut arr: Vector<u64> = Vector::default();
+    let mut store = MemoryStore::new();
+    let cid = arr.save(&mut store).unwrap();
+    let val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    assert!(val.is_empty());
+    assert_eq!(val.iter().count(), 0);
+
+    let mut items = vec![];
+    for i in 0..5000 {
+        let off = (i * 7) % (items.len() + 1);
+        items.insert(off, i as u64);
+        arr.insert(off, i as u64).unwrap();
+    }
+    let cid = arr.save(&mut store).unwrap();
+    let val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    for (index, item) in items.iter().enumerate().step_by(97) {
+        assert_eq!(val.get(index).unwrap(), *item);
+    }
+    let iterd: Vec<u64> = val.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(iterd, items);
+
+    let val: LazyVector<String> = {
+        let items: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let arr = Vector::from_slice(&items, Some(256));
+        let cid = arr.save(&mut store).unwrap();
+        LazyVector::open(&cid, &store).unwrap()
+    };
+    assert_eq!(val.get(999).unwrap(), "999".to_string());
+}
+
+#[test]
+fn test_lazy_vector_errors() {
+    let items: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&items, Some(64));
+    let blocks = arr
+        .encode(Codec::DagCbor, ipld::Multihash::Sha2_256)
+        .unwrap();
+    let mut store = MemoryStore::new();
+    let cid = arr.save(&mut store).unwrap();
+
+    // missing block fails only when it is reached.
+    let mut other = store.clone();
+    other.remove(&blocks[1].cid);
+    let val: LazyVector<u64> = LazyVector::open(&cid, &other).unwrap();
+    let res: Result<Vec<u64>> = val.iter().collect();
+    assert!(matches!(res, Err(Error::NotFound { .. })));
+
+    // root block that is not over root and tail.
+    assert!(matches!(
+        LazyVector::<u64>::open(&blocks[1].cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // link counts that do not match the nodes under them.
+    let links = [(500, blocks[1].cid), (1000, blocks[1].cid)];
+    let block = Block::new(Codec::DagCbor, ipld::encode_interior(1000, &links));
+    let cid = store.put(block).unwrap();
+    let val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    assert!(matches!(val.get(0), Err(Error::Corrupted { .. })));
+    let block = Block::new(Codec::DagCbor, ipld::encode_interior(2000, &links));
+    let cid = store.put(block).unwrap();
+    assert!(matches!(
+        LazyVector::<u64>::open(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+#[test]
+fn test_lazy_vector_budget() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let mut store = CountStore::default();
+    let cid = arr.save(&mut store).unwrap();
+    let n_blocks = store.store.len();
+
+    let mut val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    val.set_budget(Some(1000));
+    let iterd: Vec<u64> = val.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(iterd, items);
+    assert!(val.cached_items() <= 1000, "{}", val.cached_items());
+    assert_eq!(val.n_reads(), n_blocks);
+
+    // recently used leaf nodes are cached, rest are read again.
+    let mut rng = SmallRng::seed_from_u64(random());
+    for _ in 0..1000 {
+        let index = rng.gen::<usize>() % items.len();
+        assert_eq!(val.get(index).unwrap(), items[index]);
+        assert!(val.cached_items() <= 1000, "{}", val.cached_items());
+    }
+    let n = val.n_reads();
+    assert!(n > n_blocks);
+    assert_eq!(val.get(10).unwrap(), 10);
+    assert_eq!(val.get(11).unwrap(), 11);
+    assert_eq!(val.get(10).unwrap(), 10);
+    assert!(val.n_reads() <= n + 1);
+
+    // leaf nodes larger than the budget.
+    val.set_budget(Some(1));
+    assert_eq!(val.cached_items(), 128);
+    assert_eq!(val.get(99_999).unwrap(), 99_999);
+    assert!(val.cached_items() <= 128, "{}", val.cached_items());
+    val.set_budget(None);
+    let iterd: Vec<u64> = val.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(iterd, items);
+    assert_eq!(val.cached_items(), items.len());
+}
+
+// store recording the blocks hinted to it, and counting the leaf blocks
+// read from it, with or without a hint.
+#[derive(Default)]
+struct HintStore {
+    store: MemoryStore,
+    gets: Cell<usize>,
+    hinted_gets: Cell<usize>,
+    hints: RefCell<Vec<Cid>>,
+}
+
+impl Blockstore for HintStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        let data = self.store.get(cid)?;
+        if data
+            .as_ref()
+            .map(|d| d[2..].starts_with(b"items"))
+            .unwrap_or(false)
+        {
+            self.gets.set(self.gets.get() + 1);
+            if self.hints.borrow().contains(cid) {
+                self.hinted_gets.set(self.hinted_gets.get() + 1);
+            }
+        }
+        Ok(data)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+
+    fn prefetch(&self, cids: &[Cid]) {
+        self.hints.borrow_mut().extend_from_slice(cids)
+    }
+}
+
+#[test]
+fn test_lazy_vector_readahead() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let mut store = HintStore::default();
+    let cid = arr.save(&mut store).unwrap();
+
+    let mut val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    let iterd: Vec<u64> = val.iter().map(|item| item.unwrap()).collect();
+    assert_eq!(iterd, items);
+
+    // leaf blocks are hinted ahead of being read, and blocks are mostly
+    // hinted once.
+    let (gets, hints) = (store.gets.get(), store.hints.borrow());
+    let uniq: HashSet<&Cid> = hints.iter().collect();
+    assert!(
+        hints.len() * 2 < uniq.len() * 3,
+        "{} {}",
+        hints.len(),
+        uniq.len()
+    );
+    assert!(hints.iter().all(|cid| store.store.contains(cid)));
+    let n = store.hinted_gets.get();
+    assert!(n * 10 > gets * 9, "{} {}", n, gets);
+    mem::drop(hints);
+
+    // no hints without readahead.
+    store.hints.borrow_mut().clear();
+    val.set_readahead(0);
+    assert_eq!(val.iter().count(), items.len());
+    assert_eq!(val.get(500).unwrap(), 500);
+    assert!(store.hints.borrow().is_empty());
+}
+
+#[test]
+fn test_lazy_vector_cache_store() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let mut store = CacheStore::new(CountStore::default(), 1 << 20);
+    let cid = arr.save(&mut store).unwrap();
+
+    // leaf nodes dropped from the budget are read again from the cache.
+    let mut val: LazyVector<u64> = LazyVector::open(&cid, &store).unwrap();
+    val.set_budget(Some(1000));
+    let mut rng = SmallRng::seed_from_u64(random());
+    for _ in 0..2000 {
+        let index = rng.gen::<usize>() % items.len();
+        assert_eq!(val.get(index).unwrap(), items[index]);
+    }
+    let (hits, misses) = store.hits();
+    assert_eq!(misses, store.as_inner().gets.get());
+    assert!(misses <= store.as_inner().store.len());
+    assert_eq!(hits + misses, val.n_reads());
+    assert!(hits > 0);
+}
@@ -0,0 +1,80 @@
+//! Cache of values by CID, evicting least recently used values first.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::Cid;
+
+// values, by CID, evicted least recently used first once the total size
+// of values exceed the capacity.
+pub(crate) struct Lru<V> {
+    entries: HashMap<Cid, (V, u64)>,
+    // CIDs by the tick of their last access.
+    order: BTreeMap<u64, Cid>,
+    tick: u64,
+    size: usize,
+    capacity: Option<usize>,
+    size_of: fn(&V) -> usize,
+}
+
+impl<V> Lru<V> {
+    // create an empty cache, without a bound on capacity, sizing values
+    // using `size_of`.
+    pub(crate) fn new(size_of: fn(&V) -> usize) -> Lru<V> {
+        Lru {
+            entries: HashMap::default(),
+            order: BTreeMap::default(),
+            tick: 0,
+            size: 0,
+            capacity: None,
+            size_of,
+        }
+    }
+
+    // total size of values in the cache.
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn contains(&self, cid: &Cid) -> bool {
+        self.entries.contains_key(cid)
+    }
+
+    pub(crate) fn get(&mut self, cid: &Cid) -> Option<&V> {
+        let (val, tick) = self.entries.get_mut(cid)?;
+        self.order.remove(tick);
+        self.tick += 1;
+        *tick = self.tick;
+        self.order.insert(self.tick, *cid);
+        Some(val)
+    }
+
+    pub(crate) fn insert(&mut self, cid: Cid, val: V) {
+        self.tick += 1;
+        self.size += (self.size_of)(&val);
+        if let Some((val, tick)) = self.entries.insert(cid, (val, self.tick)) {
+            self.size -= (self.size_of)(&val);
+            self.order.remove(&tick);
+        }
+        self.order.insert(self.tick, cid);
+        self.evict();
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    // evict values beyond the capacity, always keeping the last one.
+    fn evict(&mut self) {
+        let capacity = self.capacity.unwrap_or(usize::MAX);
+        while self.size > capacity && self.entries.len() > 1 {
+            let (_, cid) = self.order.pop_first().unwrap();
+            let (val, _) = self.entries.remove(&cid).unwrap();
+            self.size -= (self.size_of)(&val);
+        }
+    }
+}
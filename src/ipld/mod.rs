@@ -0,0 +1,346 @@
+//! Module implement the IPLD wire representation of persistent arrays.
+//!
+//! A vector is encoded as a DAG of blocks, one block per tree node, where
+//! interior nodes link to their children by CID and leaf nodes hold the
+//! items, refer `Vector::encode`. Blocks are addressed by a CIDv1, whose
+//! multihash is the digest of the encoded block, SHA-256 by default or
+//! BLAKE3, refer [Multihash].
+//!
+//! Encoding of a block, in DAG-CBOR, or the same in DAG-JSON, refer
+//! [Codec]:
+//!
+//! * interior node, `{"len": n, "links": [[count, cid], ...], "version":
+//!   [major, minor]}`, where `count` is the number of items up to and
+//!   including that child.
+//! * leaf node, `{"items": [item, ...], "version": [major, minor]}`.
+//!
+//! Every node carries the version of its schema, currently 1.0. Nodes
+//! without the version field are of schema 0.0, written before the field
+//! was introduced, and are decoded the same. Nodes of a newer minor
+//! version are decoded ignoring the fields not known to this version,
+//! while nodes of a newer major version are rejected.
+//!
+//! The root block is an interior node, over the root and the tail of the
+//! vector, and comes first in the list of blocks.
+//!
+//! Items are any type implementing [Encode] and [Decode], including
+//! values of the IPLD data model, [Ipld], hence `Vector<Ipld>` is the list
+//! kind of the data model, converted to and from `Vec<Ipld>` and
+//! [Ipld::List]. Values of a vector saved into a store are resolved by
+//! path, refer [resolve], or selected using IPLD selectors, refer
+//! [select], reading only the blocks holding them.
+//!
+//! Blocks are persisted into a [Blockstore], by `Vector::save`, and a
+//! vector is loaded back by the CID of its root block, using
+//! `Vector::load`, or read on demand, block by block, using [LazyVector].
+//! [MemoryStore] is a blockstore held in memory, [DirStore] persists
+//! blocks as files in a directory, and [CacheStore] caches recently read
+//! blocks over another store. With the `aead` feature, [CryptStore]
+//! encrypts blocks over another store, to persist vectors in untrusted
+//! storage. With the `lz4` feature, [ZipStore] compresses blocks over
+//! another store. With the `gateway` feature, [GatewayStore] fetches
+//! blocks from an IPFS HTTP gateway, to load vectors published into IPFS.
+//!
+//! Blocks no more reachable from live vectors can be found using
+//! [reachable] and [orphans], to garbage collect a store. Stores can also
+//! pin blocks of vectors saved, and unpin blocks of versions retired,
+//! refer [Blockstore::pin] and [retire]. The cost of retaining versions
+//! is reported by [sharing_report]. Blocks missing from a store, to be
+//! fetched from peers, are reported by [want_list], and the difference
+//! between two versions in a store by [diff_cids]. Blocks are exchanged
+//! with IPFS tooling as CARv1 archives, refer [export_car] and
+//! [import_car].
+//!
+//! Inclusion of an item in a vector, by the CID of its root block, is
+//! proven by the blocks on the path to the item, refer [Proof]. A vector
+//! as a whole is authenticated by signing the CID of its root block,
+//! using a pluggable [Signer], ed25519 with the `ed25519` feature, and
+//! verifying the signature on load, refer [sign_root] and [verify_root].
+//!
+//! Leaf nodes cut at content-defined boundaries, refer [Chunker], are
+//! saved into the same blocks across versions of a vector edited by
+//! inserts and removes, refer `Vector::rechunk`.
+//!
+//! Vectors of bytes are saved as, and loaded from, UnixFS files, with
+//! each leaf node as a chunk, to edit files added to IPFS and write them
+//! back, refer [save_unixfs] and [load_unixfs].
+//!
+//! Vectors are also saved in, and loaded from, the AMT layout of IPLD
+//! vectors, to exchange them with other implementations of the AMT, refer
+//! [save_amt] and [load_amt]. AMTs are sparse arrays, while vectors are
+//! dense, hence only AMTs with items at indexes `0..count` are loaded, and
+//! loading a sparse AMT fails with an error naming its first gap. AMTs
+//! addressed by hash functions other than SHA-256 and BLAKE3, like the
+//! BLAKE2b-256 of Filecoin, are not loaded.
+//!
+//! Map kind of the data model is [Hamt], a persistent map saved into, and
+//! loaded from, a blockstore the same way as vectors. Walks over blocks,
+//! like [reachable] and [export_car], understand vector blocks only.
+
+mod amt;
+mod car;
+mod cbor;
+mod cdc;
+mod cid;
+#[cfg(feature = "aead")]
+mod crypt;
+mod dir;
+#[cfg(feature = "gateway")]
+mod gateway;
+mod hamt;
+mod json;
+mod lazy;
+mod lru;
+mod proof;
+mod select;
+mod sign;
+mod store;
+mod unixfs;
+mod value;
+mod walk;
+#[cfg(feature = "lz4")]
+mod zip;
+
+pub use amt::{load_amt, save_amt, AMT_BIT_WIDTH};
+pub use car::{export_car, import_car};
+pub use cbor::{Decode, Encode};
+pub use cdc::{Chunker, Chunks};
+pub use cid::{Cid, Codec, Multihash};
+#[cfg(feature = "aead")]
+pub use crypt::{CryptStore, PADDING};
+pub use dir::DirStore;
+#[cfg(feature = "gateway")]
+pub use gateway::{GatewayFetch, GatewayStore, GATEWAY_TIMEOUT};
+pub use hamt::{Hamt, HamtIter};
+pub use lazy::{LazyIter, LazyVector};
+pub use proof::{prove, Proof};
+pub use select::{resolve, select, Selector};
+pub use sign::{sign_root, verify_root, Signer, Verifier};
+pub use store::{Blockstore, CacheStore, MemoryStore};
+pub use unixfs::{load_unixfs, save_unixfs};
+pub use value::Ipld;
+pub use walk::{
+    diff_cids, orphans, pin, reachable, retire, sharing_report, want_list, DagDiff,
+    SharingReport, Usage,
+};
+#[cfg(feature = "lz4")]
+pub use zip::ZipStore;
+
+use std::io;
+
+use crate::{Error, Result};
+
+// version of the schema of nodes, as major and minor.
+pub(crate) const SCHEMA_VERSION: (u64, u64) = (1, 0);
+
+/// Encoded tree node, along with its content address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    /// Content address of `data`.
+    pub cid: Cid,
+    /// Encoded node.
+    pub data: Vec<u8>,
+}
+
+impl Block {
+    /// Create a block from its encoded `data`, computing its CID using
+    /// SHA-256.
+    pub fn new(codec: Codec, data: Vec<u8>) -> Block {
+        Block::with_hash(codec, Multihash::Sha2_256, data)
+    }
+
+    /// Create a block from its encoded `data`, computing its CID using
+    /// `hash`.
+    pub fn with_hash(codec: Codec, hash: Multihash, data: Vec<u8>) -> Block {
+        let cid = Cid::new(codec, hash, hash.digest(&data));
+        Block { cid, data }
+    }
+}
+
+// block for a node encoded as DAG-CBOR in `data`, transcoded to `codec`.
+pub(crate) fn block(codec: Codec, hash: Multihash, data: Vec<u8>) -> Result<Block> {
+    match codec {
+        Codec::DagCbor => Ok(Block::with_hash(codec, hash, data)),
+        Codec::DagJson => Ok(Block::with_hash(codec, hash, json::from_cbor(&data)?)),
+    }
+}
+
+// interior node, with cumulative item count for each child. Fields are
+// in the order of DAG-CBOR, length first, which is also the order of
+// DAG-JSON, bytewise.
+pub(crate) fn encode_interior(len: usize, links: &[(usize, Cid)]) -> Vec<u8> {
+    let mut buf = vec![];
+    cbor::write_head(&mut buf, cbor::MAP, 3);
+    "len".encode(&mut buf);
+    len.encode(&mut buf);
+    "links".encode(&mut buf);
+    cbor::write_head(&mut buf, cbor::ARRAY, links.len() as u64);
+    for (count, cid) in links.iter() {
+        cbor::write_head(&mut buf, cbor::ARRAY, 2);
+        count.encode(&mut buf);
+        cid.encode(&mut buf);
+    }
+    encode_version(&mut buf);
+    buf
+}
+
+pub(crate) fn encode_leaf<T>(items: &[T]) -> Vec<u8>
+where
+    T: Encode,
+{
+    let mut buf = vec![];
+    cbor::write_head(&mut buf, cbor::MAP, 2);
+    "items".encode(&mut buf);
+    items.encode(&mut buf);
+    encode_version(&mut buf);
+    buf
+}
+
+fn encode_version(buf: &mut Vec<u8>) {
+    let (major, minor) = SCHEMA_VERSION;
+    "version".encode(buf);
+    vec![major, minor].encode(buf);
+}
+
+/// Write `block` into `w`, as an unsigned varint of the length of its CID
+/// and data, followed by its CID in binary form and its data, which is
+/// also the layout of a block in a CAR file.
+pub fn write_block<W>(w: &mut W, block: &Block) -> Result<()>
+where
+    W: io::Write,
+{
+    let cid = block.cid.to_bytes();
+    let mut buf = vec![];
+    cid::write_varint(&mut buf, (cid.len() + block.data.len()) as u64);
+    buf.extend_from_slice(&cid);
+    let res = w.write_all(&buf).and_then(|_| w.write_all(&block.data));
+    res.map_err(|err| Error::IOError {
+        detail: err.to_string(),
+    })
+}
+
+// write the header of a CARv1 archive into `w`, a DAG-CBOR map
+// `{"roots": [cid, ...], "version": 1}`, prefixed with its length as an
+// unsigned varint.
+pub(crate) fn write_car_header<W>(w: &mut W, roots: &[Cid]) -> Result<()>
+where
+    W: io::Write,
+{
+    let mut header = vec![];
+    cbor::write_head(&mut header, cbor::MAP, 2);
+    "roots".encode(&mut header);
+    roots.encode(&mut header);
+    "version".encode(&mut header);
+    1_u64.encode(&mut header);
+
+    let mut buf = vec![];
+    cid::write_varint(&mut buf, header.len() as u64);
+    buf.extend_from_slice(&header);
+    w.write_all(&buf).map_err(|err| Error::IOError {
+        detail: err.to_string(),
+    })
+}
+
+// decoded node, refer encode_interior and encode_leaf.
+pub(crate) enum Decoded<T> {
+    Interior {
+        len: usize,
+        links: Vec<(usize, Cid)>,
+    },
+    Leaf {
+        items: Vec<T>,
+    },
+}
+
+pub(crate) fn decode_node<T>(mut data: &[u8]) -> Result<Decoded<T>>
+where
+    T: Decode,
+{
+    let buf = &mut data;
+    let (mut len, mut links, mut items) = (None, None, None);
+    let (mut version, mut unknown) = ((0, 0), None);
+    for _ in 0..cbor::read_major(buf, cbor::MAP)? {
+        match String::decode(buf)?.as_str() {
+            "len" => len = Some(usize::decode(buf)?),
+            "links" => {
+                let n = cbor::read_major(buf, cbor::ARRAY)?;
+                let mut items = vec![];
+                for _ in 0..n {
+                    match cbor::read_major(buf, cbor::ARRAY)? {
+                        2 => items.push((usize::decode(buf)?, Cid::decode(buf)?)),
+                        n => Err(cbor::malformed(format!("link of {} items", n)))?,
+                    }
+                }
+                links = Some(items)
+            }
+            "items" => items = Some(Vec::<T>::decode(buf)?),
+            "version" => match Vec::<u64>::decode(buf)?.as_slice() {
+                [major, minor] => version = (*major, *minor),
+                v => Err(cbor::malformed(format!("schema version {:?}", v)))?,
+            },
+            key => {
+                unknown = Some(key.to_string());
+                cbor::skip(buf)?
+            }
+        }
+    }
+
+    match (version, unknown) {
+        ((major, _), _) if major > SCHEMA_VERSION.0 => {
+            let detail = format!("schema version {}.{} not supported", major, version.1);
+            Err(Error::Corrupted { detail })?
+        }
+        (version, Some(key)) if version <= SCHEMA_VERSION => {
+            Err(cbor::malformed(format!("unknown field {:?}", key)))?
+        }
+        _ => (),
+    }
+
+    match (len, links, items) {
+        _ if !buf.is_empty() => Err(cbor::malformed(format!(
+            "{} bytes past the node",
+            buf.len()
+        ))),
+        (Some(len), Some(links), None) => Ok(Decoded::Interior { len, links }),
+        (None, None, Some(items)) => Ok(Decoded::Leaf { items }),
+        _ => Err(cbor::malformed("node with missing fields".to_string())),
+    }
+}
+
+// return the data of block `cid` from `store`, verified against its CID,
+// and transcoded to DAG-CBOR.
+pub(crate) fn fetch(store: &dyn Blockstore, cid: &Cid) -> Result<Vec<u8>> {
+    match store.get(cid)? {
+        Some(data) => verify(cid, data),
+        None => Err(Error::NotFound {
+            detail: format!("block {}", cid),
+        }),
+    }
+}
+
+// verify `data` of block `cid` against its CID, and transcode it to
+// DAG-CBOR.
+pub(crate) fn verify(cid: &Cid, data: Vec<u8>) -> Result<Vec<u8>> {
+    verify_digest(cid, &data)?;
+    match Codec::from_code(cid.codec()) {
+        Some(Codec::DagCbor) => Ok(data),
+        Some(Codec::DagJson) => json::to_cbor(&data),
+        None => {
+            let detail = format!("block {} with codec {:#x}", cid, cid.codec());
+            Err(Error::Corrupted { detail })
+        }
+    }
+}
+
+// verify `data` of block `cid` against the digest in its CID, for blocks
+// of any codec.
+pub(crate) fn verify_digest(cid: &Cid, data: &[u8]) -> Result<()> {
+    let hash = Multihash::from_code(cid.hash());
+    match hash.map(|hash| hash.digest(data) == *cid.digest()) {
+        Some(true) => Ok(()),
+        _ => {
+            let detail = format!("block {} does not match its digest", cid);
+            Err(Error::Corrupted { detail })
+        }
+    }
+}
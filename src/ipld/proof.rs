@@ -0,0 +1,126 @@
+//! Inclusion proofs, for items of persisted vectors.
+
+use super::{
+    decode_node,
+    lazy::{check_counts, corrupted},
+    verify,
+    walk::Skipped,
+    Block, Blockstore, Cid, Decode, Decoded,
+};
+use crate::{Error, Result};
+
+/// Proof that an item is included in a vector, by the CID of its root
+/// block, refer [prove] and `Vector::prove`.
+///
+/// Proof is the hash path from the root block to the leaf node holding
+/// the item, that is the blocks on the path, root block first. Each
+/// interior block carries the CIDs of its children, and the number of
+/// items under them, hence the path is tied to the root CID by digests,
+/// and the item to its index by counts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Proof {
+    /// Blocks from the root block to the leaf node, in that order.
+    pub blocks: Vec<Block>,
+}
+
+impl Proof {
+    /// Return whether this proof proves that `value` is the item at
+    /// `index`, in the vector with root block `root`. Only the blocks in
+    /// the proof are used, hence a light client can verify an item
+    /// without the vector, or a store.
+    ///
+    /// Proofs with blocks that do not match their digest, or that are not
+    /// linked by their parent, with counts that do not add up, or with
+    /// blocks missing or left over, are rejected.
+    pub fn verify<T>(&self, root: &Cid, index: usize, value: &T) -> bool
+    where
+        T: Decode + PartialEq,
+    {
+        matches!(self.item::<T>(root, index), Ok(item) if item == *value)
+    }
+
+    // return the item at `index`, as proven by the blocks of this proof.
+    fn item<T>(&self, root: &Cid, index: usize) -> Result<T>
+    where
+        T: Decode,
+    {
+        let mut blocks = self.blocks.iter();
+        let (mut cid, mut off, mut want) = (*root, index, None);
+        loop {
+            let block = match blocks.next() {
+                Some(block) if block.cid == cid => block,
+                _ => Err(corrupted(&cid, "missing from proof"))?,
+            };
+            let node = decode_node::<T>(&verify(&cid, block.data.clone())?)?;
+            let len = match &node {
+                Decoded::Interior { len, .. } => *len,
+                Decoded::Leaf { items } => items.len(),
+            };
+            match want {
+                Some(want) if want != len => {
+                    Err(corrupted(&cid, &format!("len {}, want {}", len, want)))?
+                }
+                _ if off >= len => Err(Error::IndexOutOfBounds { index, len })?,
+                _ => (),
+            }
+
+            match node {
+                Decoded::Interior { links, .. } => {
+                    check_counts(&cid, len, &links)?;
+                    let k = links.partition_point(|(count, _)| *count <= off);
+                    let base = k.checked_sub(1).map(|k| links[k].0).unwrap_or(0);
+                    want = Some(links[k].0 - base);
+                    off -= base;
+                    cid = links[k].1;
+                }
+                Decoded::Leaf { items } if blocks.next().is_none() => {
+                    break Ok(items.into_iter().nth(off).unwrap());
+                }
+                Decoded::Leaf { .. } => Err(corrupted(&cid, "blocks past the leaf"))?,
+            }
+        }
+    }
+}
+
+/// Return the proof that the item at `index` is included in the vector
+/// saved under root block `root` in `store`, refer [Proof]. Only the
+/// blocks on the path to the item are read.
+///
+/// Return `IndexOutOfBounds` if `index` is beyond the vector, `NotFound`
+/// if a block on the path is missing from `store`, and `Corrupted` if a
+/// block fails to decode.
+pub fn prove(root: &Cid, index: usize, store: &dyn Blockstore) -> Result<Proof> {
+    let (mut cid, mut off) = (*root, index);
+    let mut blocks = vec![];
+    loop {
+        let data = match store.get(&cid)? {
+            Some(data) => data,
+            None => Err(Error::NotFound {
+                detail: format!("block {}", cid),
+            })?,
+        };
+        let node = decode_node::<Skipped>(&verify(&cid, data.clone())?)?;
+        blocks.push(Block { cid, data });
+        match node {
+            Decoded::Interior { len, .. } if off >= len && blocks.len() == 1 => {
+                Err(Error::IndexOutOfBounds { index, len })?
+            }
+            Decoded::Interior { links, .. } => {
+                let k = links.partition_point(|(count, _)| *count <= off);
+                let base = k.checked_sub(1).map(|k| links[k].0).unwrap_or(0);
+                match (links.get(k), off.checked_sub(base)) {
+                    (Some((_, child)), Some(n)) => {
+                        cid = *child;
+                        off = n
+                    }
+                    _ => Err(corrupted(&cid, &format!("no link for offset {}", off)))?,
+                }
+            }
+            Decoded::Leaf { .. } => break Ok(Proof { blocks }),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "proof_test.rs"]
+mod proof_test;
@@ -0,0 +1,157 @@
+use super::*;
+use crate::{
+    ipld::{encode_interior, encode_leaf, Codec, MemoryStore, Multihash},
+    rc::Vector,
+};
+
+#[test]
+fn test_prove() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let mut arr = Vector::from_slice(&items, Some(1024));
+    (0..100).for_each(|i| arr.insert(arr.len(), i).unwrap());
+    let mut store = MemoryStore::new();
+    let root = arr.save(&mut store).unwrap();
+
+    for index in [0, 1023, 1024, 50_000, 99_999, 100_000, 100_099].iter() {
+        let proof = prove(&root, *index, &store).unwrap();
+        assert_eq!(proof, arr.prove(*index).unwrap());
+
+        // path of blocks from the root block, each linked by its parent.
+        assert_eq!(proof.blocks[0].cid, root);
+        assert!(
+            proof.blocks.len() <= arr.depth() + 2,
+            "{}",
+            proof.blocks.len()
+        );
+        for (parent, child) in proof.blocks.iter().zip(proof.blocks[1..].iter()) {
+            assert_eq!(store.get(&child.cid).unwrap(), Some(child.data.clone()));
+            let links = match decode_node::<u64>(&parent.data).unwrap() {
+                Decoded::Interior { links, .. } => links,
+                Decoded::Leaf { .. } => unreachable!(),
+            };
+            assert!(links.iter().any(|(_, cid)| *cid == child.cid));
+        }
+        let leaf = proof.blocks.last().unwrap();
+        match decode_node::<u64>(&leaf.data).unwrap() {
+            Decoded::Leaf { items } => assert!(items.contains(arr.get(*index).unwrap())),
+            Decoded::Interior { .. } => unreachable!(),
+        }
+    }
+
+    assert!(matches!(
+        prove(&root, 100_100, &store),
+        Err(Error::IndexOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        arr.prove(100_100),
+        Err(Error::IndexOutOfBounds { .. })
+    ));
+    let missing = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    assert!(matches!(
+        prove(&missing, 0, &store),
+        Err(Error::NotFound { .. })
+    ));
+
+    // blocks are carried as stored, in the codec of the vector.
+    let root = arr
+        .save_with(&mut store, Codec::DagJson, Multihash::Blake3)
+        .unwrap();
+    let proof = prove(&root, 500, &store).unwrap();
+    assert_eq!(proof.blocks[0].cid, root);
+    assert!(proof.blocks.iter().all(|b| b.data[0] == b'{'));
+}
+
+#[test]
+fn test_proof_verify() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&items, Some(256));
+    let mut store = MemoryStore::new();
+    let root = arr.save(&mut store).unwrap();
+
+    for index in (0..items.len()).step_by(997).chain(Some(9_999)) {
+        let proof = prove(&root, index, &store).unwrap();
+        assert!(proof.verify(&root, index, &items[index]));
+        assert!(!proof.verify(&root, index, &(items[index] + 1)));
+        assert!(!proof.verify(&root, index + 1, &items[index]));
+        assert!(!proof.verify(&root, index + 10_000, &items[index]));
+        assert!(!proof.verify(&root, index, &items[index].to_string()));
+        assert!(arr
+            .prove(index)
+            .unwrap()
+            .verify(&root, index, &items[index]));
+    }
+
+    let proof = prove(&root, 5000, &store).unwrap();
+    let leaf = proof.blocks.len() - 1;
+
+    // proof of another vector.
+    let other = Vector::from_slice(&items[..9_000], Some(256));
+    let other = other.save(&mut store).unwrap();
+    assert!(!proof.verify(&other, 5000, &5000_u64));
+
+    // missing, reordered, and left over blocks.
+    assert!(!Proof::default().verify(&root, 5000, &5000_u64));
+    let mut bad = proof.clone();
+    bad.blocks.pop();
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+    let mut bad = proof.clone();
+    bad.blocks.remove(1);
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+    let mut bad = proof.clone();
+    bad.blocks.swap(0, 1);
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+    let mut bad = proof.clone();
+    bad.blocks.push(proof.blocks[leaf].clone());
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+
+    // tampered item, with and without a matching CID.
+    let mut items = match decode_node::<u64>(&proof.blocks[leaf].data).unwrap() {
+        Decoded::Leaf { items } => items,
+        Decoded::Interior { .. } => unreachable!(),
+    };
+    let off = items.iter().position(|item| *item == 5000).unwrap();
+    items[off] = 0;
+    let mut bad = proof.clone();
+    bad.blocks[leaf].data = encode_leaf(&items);
+    assert!(!bad.verify(&root, 5000, &0_u64));
+    bad.blocks[leaf] = Block::new(Codec::DagCbor, encode_leaf(&items));
+    assert!(!bad.verify(&root, 5000, &0_u64));
+
+    // malformed blocks.
+    let mut bad = proof.clone();
+    bad.blocks[leaf] = Block::new(Codec::DagCbor, vec![0xff, 0x00]);
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+    let mut bad = proof.clone();
+    bad.blocks[leaf].data.truncate(10);
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+
+    // forged path, re-linked up to a forged root, with counts shifted to
+    // point the index to another item.
+    let forged = |count: &dyn Fn(usize) -> usize| {
+        let mut bad = proof.clone();
+        for i in (0..leaf).rev() {
+            let (len, links) = match decode_node::<u64>(&bad.blocks[i].data).unwrap() {
+                Decoded::Interior { len, links } => (len, links),
+                Decoded::Leaf { .. } => unreachable!(),
+            };
+            let child = bad.blocks[i + 1].cid;
+            let links: Vec<(usize, Cid)> = links
+                .into_iter()
+                .map(|(n, cid)| match cid == proof.blocks[i + 1].cid {
+                    true if i == 0 => (count(n), child),
+                    true => (n, child),
+                    false => (n, cid),
+                })
+                .collect();
+            bad.blocks[i] = Block::new(Codec::DagCbor, encode_interior(len, &links));
+        }
+        bad
+    };
+    let bad = forged(&|n| n);
+    let root = bad.blocks[0].cid;
+    assert!(bad.verify(&root, 5000, &5000_u64));
+    let bad = forged(&|n| n + 1);
+    let root = bad.blocks[0].cid;
+    assert!(!bad.verify(&root, 5000, &5000_u64));
+    assert!(!bad.verify(&root, 5001, &5000_u64));
+}
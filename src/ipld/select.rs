@@ -0,0 +1,220 @@
+//! Path and selector resolution, against vectors of IPLD values.
+
+use std::{cmp, collections::BTreeMap};
+
+use super::{Blockstore, Cid, Ipld, LazyVector};
+use crate::{Error, Result};
+
+/// Selector, a subset of [IPLD selectors][spec], to select values from a
+/// vector of IPLD values saved in a blockstore, refer [select].
+///
+/// The vector is explored as a list, by index, or by range of indexes,
+/// and its items are explored like any other IPLD value. Links within
+/// items are not followed.
+///
+/// [spec]: https://ipld.io/specs/selectors/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Selector {
+    /// Select the value, `{".": {}}`.
+    Matcher,
+    /// Explore every item of a list, or every value of a map, using the
+    /// next selector, `{"a": {">": next}}`.
+    ExploreAll(Box<Selector>),
+    /// Explore the item at index of a list, using the next selector,
+    /// `{"i": {"i": index, ">": next}}`.
+    ExploreIndex(usize, Box<Selector>),
+    /// Explore the items of a list in range `start..end`, using the next
+    /// selector, `{"r": {"^": start, "$": end, ">": next}}`.
+    ExploreRange(usize, usize, Box<Selector>),
+    /// Explore the named values of a map, each using its own selector,
+    /// `{"f": {"f>": {name: next, ...}}}`.
+    ExploreFields(BTreeMap<String, Selector>),
+}
+
+impl Selector {
+    /// Parse a selector from its IPLD form, as in the selector spec. Return
+    /// `Corrupted` for selectors not supported.
+    pub fn from_ipld(val: &Ipld) -> Result<Selector> {
+        let (kind, body) = match val {
+            Ipld::Map(map) if map.len() == 1 => map.iter().next().unwrap(),
+            _ => Err(invalid(val, "not a selector"))?,
+        };
+        let next = || -> Result<Box<Selector>> {
+            Ok(Box::new(Selector::from_ipld(field(body, ">")?)?))
+        };
+        match kind.as_str() {
+            "." => Ok(Selector::Matcher),
+            "a" => Ok(Selector::ExploreAll(next()?)),
+            "i" => Ok(Selector::ExploreIndex(index(field(body, "i")?)?, next()?)),
+            "r" => {
+                let start = index(field(body, "^")?)?;
+                let end = index(field(body, "$")?)?;
+                Ok(Selector::ExploreRange(start, end, next()?))
+            }
+            "f" => match field(body, "f>")? {
+                Ipld::Map(fields) => {
+                    let mut sels = BTreeMap::new();
+                    for (name, val) in fields.iter() {
+                        sels.insert(name.clone(), Selector::from_ipld(val)?);
+                    }
+                    Ok(Selector::ExploreFields(sels))
+                }
+                val => Err(invalid(val, "fields are not a map")),
+            },
+            kind => Err(invalid(val, &format!("selector {:?} not supported", kind))),
+        }
+    }
+}
+
+/// Resolve `path` against the vector of IPLD values saved under root block
+/// `root` in `store`, and return the value at `path`. Path is a list of
+/// segments separated by `/`, like `/123/name/0`, where the first segment
+/// is an index into the vector, and rest of the segments are indexes into
+/// lists, or keys into maps, of the item. An empty path resolves to the
+/// vector as a list. Only the blocks on the path to the item are read.
+///
+/// Links within items are not followed. Return `IndexOutOfBounds` if an
+/// index is beyond the vector, and `NotFound` if a segment is missing.
+pub fn resolve(root: &Cid, path: &str, store: &dyn Blockstore) -> Result<Ipld> {
+    let arr: LazyVector<Ipld> = LazyVector::open(root, store)?;
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+
+    let mut val = match segments.next() {
+        None => return Ok(Ipld::List(arr.iter().collect::<Result<Vec<Ipld>>>()?)),
+        Some(seg) => match seg.parse::<usize>() {
+            Ok(index) => arr.get(index)?,
+            Err(_) => Err(not_found(path, seg))?,
+        },
+    };
+    for seg in segments {
+        val = match val {
+            Ipld::List(mut items) => match seg.parse::<usize>() {
+                Ok(index) if index < items.len() => items.swap_remove(index),
+                _ => Err(not_found(path, seg))?,
+            },
+            Ipld::Map(mut map) => match map.remove(seg) {
+                Some(val) => val,
+                None => Err(not_found(path, seg))?,
+            },
+            _ => Err(not_found(path, seg))?,
+        };
+    }
+    Ok(val)
+}
+
+/// Select values from the vector of IPLD values saved under root block
+/// `root` in `store`, using `selector`, and return the values matched
+/// along with their path, in the order of exploration. Only the blocks
+/// holding the items explored are read.
+pub fn select(
+    root: &Cid,
+    selector: &Selector,
+    store: &dyn Blockstore,
+) -> Result<Vec<(String, Ipld)>> {
+    let arr: LazyVector<Ipld> = LazyVector::open(root, store)?;
+    let mut items = vec![];
+    match selector {
+        Selector::Matcher => {
+            let items = arr.iter().collect::<Result<Vec<Ipld>>>()?;
+            return Ok(vec![(String::default(), Ipld::List(items))]);
+        }
+        Selector::ExploreAll(next) => {
+            for (index, item) in arr.iter().enumerate() {
+                items.push((index, item?, next.as_ref()));
+            }
+        }
+        Selector::ExploreIndex(index, next) if *index < arr.len() => {
+            items.push((*index, arr.get(*index)?, next.as_ref()))
+        }
+        Selector::ExploreRange(start, end, next) => {
+            for index in *start..cmp::min(*end, arr.len()) {
+                items.push((index, arr.get(index)?, next.as_ref()))
+            }
+        }
+        Selector::ExploreIndex(_, _) | Selector::ExploreFields(_) => (),
+    }
+
+    let mut matched = vec![];
+    for (index, item, next) in items.into_iter() {
+        explore(index.to_string(), item, next, &mut matched);
+    }
+    Ok(matched)
+}
+
+// explore `val`, at `path`, using `selector`, with an explicit stack.
+fn explore(
+    path: String,
+    val: Ipld,
+    selector: &Selector,
+    matched: &mut Vec<(String, Ipld)>,
+) {
+    let mut stack = vec![(path, val, selector)];
+    while let Some((path, val, selector)) = stack.pop() {
+        let join = |seg: &dyn ToString| format!("{}/{}", path, seg.to_string());
+        let mut children = vec![];
+        match (selector, val) {
+            (Selector::Matcher, val) => matched.push((path.clone(), val)),
+            (Selector::ExploreAll(next), Ipld::List(items)) => {
+                for (index, item) in items.into_iter().enumerate() {
+                    children.push((join(&index), item, next.as_ref()))
+                }
+            }
+            (Selector::ExploreAll(next), Ipld::Map(map)) => {
+                for (key, item) in map.into_iter() {
+                    children.push((join(&key), item, next.as_ref()))
+                }
+            }
+            (Selector::ExploreIndex(index, next), Ipld::List(mut items))
+                if *index < items.len() =>
+            {
+                children.push((join(index), items.swap_remove(*index), next.as_ref()))
+            }
+            (Selector::ExploreRange(start, end, next), Ipld::List(items)) => {
+                let items = items.into_iter().enumerate();
+                for (index, item) in items.skip(*start).take(end.saturating_sub(*start)) {
+                    children.push((join(&index), item, next.as_ref()))
+                }
+            }
+            (Selector::ExploreFields(fields), Ipld::Map(mut map)) => {
+                for (key, next) in fields.iter() {
+                    if let Some(item) = map.remove(key) {
+                        children.push((join(key), item, next))
+                    }
+                }
+            }
+            (_, _) => (),
+        }
+        stack.extend(children.into_iter().rev());
+    }
+}
+
+fn field<'a>(body: &'a Ipld, name: &str) -> Result<&'a Ipld> {
+    match body {
+        Ipld::Map(map) => match map.get(name) {
+            Some(val) => Ok(val),
+            None => Err(invalid(body, &format!("missing field {:?}", name))),
+        },
+        _ => Err(invalid(body, "selector body is not a map")),
+    }
+}
+
+fn index(val: &Ipld) -> Result<usize> {
+    match val {
+        Ipld::Integer(n) if *n >= 0 => Ok(cmp::min(*n, usize::MAX as i128) as usize),
+        val => Err(invalid(val, "not an index")),
+    }
+}
+
+fn invalid(val: &Ipld, msg: &str) -> Error {
+    let detail = format!("selector {:?}, {}", val, msg);
+    Error::Corrupted { detail }
+}
+
+fn not_found(path: &str, seg: &str) -> Error {
+    let detail = format!("path {:?}, segment {:?}", path, seg);
+    Error::NotFound { detail }
+}
+
+#[cfg(test)]
+#[path = "select_test.rs"]
+mod select_test;
@@ -0,0 +1,175 @@
+use std::cell::Cell;
+
+use super::*;
+use crate::{
+    ipld::{Block, MemoryStore},
+    rc::Vector,
+};
+
+// store counting the blocks read from it.
+#[derive(Default)]
+struct CountStore {
+    store: MemoryStore,
+    gets: Cell<usize>,
+}
+
+impl Blockstore for CountStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.gets.set(self.gets.get() + 1);
+        self.store.get(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+}
+
+fn item(i: i128) -> Ipld {
+    let mut map = BTreeMap::new();
+    map.insert("id".to_string(), Ipld::Integer(i));
+    map.insert(
+        "tags".to_string(),
+        Ipld::List(vec![
+            Ipld::String(format!("t{}", i)),
+            Ipld::String(format!("u{}", i)),
+        ]),
+    );
+    Ipld::Map(map)
+}
+
+fn save(n: i128, store: &mut dyn Blockstore) -> Cid {
+    let items: Vec<Ipld> = (0..n).map(item).collect();
+    Vector::from_slice(&items, None).save(store).unwrap()
+}
+
+#[test]
+fn test_resolve() {
+    let mut store = CountStore::default();
+    let root = save(10_000, &mut store);
+
+    store.gets.set(0);
+    assert_eq!(resolve(&root, "/5000", &store).unwrap(), item(5000));
+    assert!(store.gets.get() < 10, "{}", store.gets.get());
+    assert_eq!(
+        resolve(&root, "5000/id", &store).unwrap(),
+        Ipld::Integer(5000)
+    );
+    assert_eq!(
+        resolve(&root, "/9999/tags/1/", &store).unwrap(),
+        Ipld::String("u9999".to_string())
+    );
+    match resolve(&root, "", &store).unwrap() {
+        Ipld::List(items) => assert_eq!(items.len(), 10_000),
+        val => panic!("{:?}", val),
+    }
+
+    assert!(matches!(
+        resolve(&root, "/10000", &store),
+        Err(Error::IndexOutOfBounds { .. })
+    ));
+    for path in ["/x", "/1/name", "/1/tags/2", "/1/id/0", "/1/tags/x"].iter() {
+        assert!(matches!(
+            resolve(&root, path, &store),
+            Err(Error::NotFound { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_select() {
+    let mut store = CountStore::default();
+    let root = save(10_000, &mut store);
+
+    let all = |next| Selector::ExploreAll(Box::new(next));
+    let mut fields = BTreeMap::new();
+    fields.insert("id".to_string(), Selector::Matcher);
+    let ids = Selector::ExploreFields(fields);
+
+    // range of items, reading only the blocks holding them.
+    store.gets.set(0);
+    let sel = Selector::ExploreRange(100, 103, Box::new(ids.clone()));
+    let vals = select(&root, &sel, &store).unwrap();
+    let want: Vec<(String, Ipld)> = (100..103)
+        .map(|i| (format!("{}/id", i), Ipld::Integer(i)))
+        .collect();
+    assert_eq!(vals, want);
+    assert!(store.gets.get() < 10, "{}", store.gets.get());
+
+    let sel = Selector::ExploreIndex(7, Box::new(all(Selector::Matcher)));
+    let vals = select(&root, &sel, &store).unwrap();
+    assert_eq!(vals.len(), 2);
+    assert_eq!(vals[0], ("7/id".to_string(), Ipld::Integer(7)));
+    assert_eq!(vals[1].0, "7/tags");
+
+    let sel = all(Selector::ExploreFields({
+        let mut fields = BTreeMap::new();
+        let tag = Selector::ExploreIndex(1, Box::new(Selector::Matcher));
+        fields.insert("tags".to_string(), tag);
+        fields
+    }));
+    let vals = select(&root, &sel, &store).unwrap();
+    assert_eq!(vals.len(), 10_000);
+    assert_eq!(
+        vals[42],
+        ("42/tags/1".to_string(), Ipld::String("u42".to_string()))
+    );
+
+    // selectors that select nothing.
+    for sel in [
+        Selector::ExploreIndex(10_000, Box::new(Selector::Matcher)),
+        Selector::ExploreRange(20_000, 30_000, Box::new(Selector::Matcher)),
+        ids,
+    ]
+    .iter()
+    {
+        assert!(select(&root, sel, &store).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_selector_ipld() {
+    // {"r": {"^": 1, "$": 3, ">": {"a": {">": {".": {}}}}}}
+    let map = |entries: Vec<(&str, Ipld)>| {
+        let entries = entries.into_iter().map(|(k, v)| (k.to_string(), v));
+        Ipld::Map(entries.collect())
+    };
+    let matcher = map(vec![(".", map(vec![]))]);
+    let all = map(vec![("a", map(vec![(">", matcher.clone())]))]);
+    let val = map(vec![(
+        "r",
+        map(vec![
+            ("^", Ipld::Integer(1)),
+            ("$", Ipld::Integer(3)),
+            (">", all.clone()),
+        ]),
+    )]);
+    let sel = Selector::from_ipld(&val).unwrap();
+    let want = Selector::ExploreRange(
+        1,
+        3,
+        Box::new(Selector::ExploreAll(Box::new(Selector::Matcher))),
+    );
+    assert_eq!(sel, want);
+
+    let fields = map(vec![("f", map(vec![("f>", map(vec![("id", matcher)]))]))]);
+    let mut want = BTreeMap::new();
+    want.insert("id".to_string(), Selector::Matcher);
+    assert_eq!(
+        Selector::from_ipld(&fields).unwrap(),
+        Selector::ExploreFields(want)
+    );
+
+    let bad = [
+        Ipld::Null,
+        map(vec![]),
+        map(vec![("R", map(vec![]))]),
+        map(vec![("a", map(vec![]))]),
+        map(vec![("i", map(vec![("i", Ipld::Integer(-1)), (">", all)]))]),
+    ];
+    for val in bad.iter() {
+        assert!(matches!(
+            Selector::from_ipld(val),
+            Err(Error::Corrupted { .. })
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+//! Signed root blocks, authenticating a vector by the CID of its root.
+
+use super::{
+    cbor::{self, malformed, read_major, take, write_head},
+    fetch, Block, Blockstore, Cid, Codec, Decode, Encode,
+};
+use crate::{Error, Result};
+
+// prefix of the message signed, binding the signature to its use.
+const DOMAIN: &[u8] = b"ppar signed root v1:";
+
+/// Signer of root CIDs, refer [sign_root]. Implemented for
+/// `ed25519_dalek::SigningKey` with the `ed25519` feature.
+pub trait Signer {
+    /// Return the signature of `msg`.
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Verifier of signed root CIDs, holding the key, or keys, trusted to
+/// sign them, refer [verify_root]. Implemented for
+/// `ed25519_dalek::VerifyingKey` with the `ed25519` feature.
+pub trait Verifier {
+    /// Return whether `sig` is a signature of `msg` by a trusted key.
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> bool;
+}
+
+/// Sign `root`, the CID of a root block, using `signer`, and put the
+/// signed root into `store` as a DAG-CBOR block, `{"root": cid, "sig":
+/// bytes}`. Return the CID of the signed root block, which is pinned
+/// along with the root block, refer [Blockstore::pin].
+///
+/// Since the root block links to every other block, a signed root
+/// authenticates the whole vector, for peers replicating a log to not
+/// forge its history. Refer `Vector::save_signed`.
+pub fn sign_root(
+    root: &Cid,
+    signer: &dyn Signer,
+    store: &mut dyn Blockstore,
+) -> Result<Cid> {
+    let sig = signer.sign(&message(root))?;
+    let mut data = vec![];
+    write_head(&mut data, cbor::MAP, 2);
+    "sig".encode(&mut data);
+    write_head(&mut data, cbor::BYTES, sig.len() as u64);
+    data.extend_from_slice(&sig);
+    "root".encode(&mut data);
+    root.encode(&mut data);
+    let cid = store.put(Block::new(Codec::DagCbor, data))?;
+    store.pin(&cid, &[cid, *root])?;
+    Ok(cid)
+}
+
+/// Verify the signed root block `cid` in `store`, using `verifier`, and
+/// return the CID of the root block it signs, refer [sign_root].
+///
+/// Return `NotFound` if the block is missing from `store`, and
+/// `Corrupted` if it fails to decode, or its signature fails to verify.
+/// Refer `Vector::load_signed`.
+pub fn verify_root(
+    cid: &Cid,
+    verifier: &dyn Verifier,
+    store: &dyn Blockstore,
+) -> Result<Cid> {
+    let data = fetch(store, cid)?;
+    let buf = &mut data.as_slice();
+    let (mut root, mut sig) = (None, None);
+    for _ in 0..read_major(buf, cbor::MAP)? {
+        match String::decode(buf)?.as_str() {
+            "root" => root = Some(Cid::decode(buf)?),
+            "sig" => {
+                let n = read_major(buf, cbor::BYTES)?;
+                sig = Some(take(buf, n)?);
+            }
+            key => Err(malformed(format!("signed root, key {:?}", key)))?,
+        }
+    }
+    match (root, sig) {
+        (Some(root), Some(sig)) if verifier.verify(&message(&root), sig) => Ok(root),
+        (Some(_), Some(_)) => Err(corrupted(cid, "signature fails to verify")),
+        _ => Err(malformed("signed root, want {root, sig}".to_string())),
+    }
+}
+
+fn message(root: &Cid) -> Vec<u8> {
+    let mut msg = DOMAIN.to_vec();
+    msg.extend_from_slice(&root.to_bytes());
+    msg
+}
+
+#[cfg(feature = "ed25519")]
+impl Signer for ed25519_dalek::SigningKey {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        use ed25519_dalek::Signer as _;
+
+        match self.try_sign(msg) {
+            Ok(sig) => Ok(sig.to_bytes().to_vec()),
+            Err(err) => {
+                let detail = format!("ed25519 {}", err);
+                Err(Error::IOError { detail })
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl Verifier for ed25519_dalek::VerifyingKey {
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
+        match ed25519_dalek::Signature::from_slice(sig) {
+            Ok(sig) => self.verify_strict(msg, &sig).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "sign_test.rs"]
+mod sign_test;
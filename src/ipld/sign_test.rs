@@ -0,0 +1,93 @@
+use super::*;
+use crate::{
+    ipld::{MemoryStore, Multihash},
+    rc::Vector,
+};
+
+// keyed digest, standing in for a signature scheme.
+struct Keyed(u8);
+
+impl Signer for Keyed {
+    fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let mut data = vec![self.0];
+        data.extend_from_slice(msg);
+        Ok(Multihash::Sha2_256.digest(&data).to_vec())
+    }
+}
+
+impl Verifier for Keyed {
+    fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
+        self.sign(msg).unwrap() == sig
+    }
+}
+
+#[test]
+fn test_signed_root() {
+    let items: Vec<u64> = (0..1000).collect();
+    let arr = Vector::from_slice(&items, Some(64));
+    let mut store = MemoryStore::new();
+    let cid = arr.save_signed(&mut store, &Keyed(1)).unwrap();
+    let root = verify_root(&cid, &Keyed(1), &store).unwrap();
+    assert_eq!(root, arr.save(&mut store).unwrap());
+    let val: Vector<u64> = Vector::load_signed(&cid, &store, &Keyed(1)).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+
+    // another key, and a root swapped for another vector.
+    assert!(matches!(
+        Vector::<u64>::load_signed(&cid, &store, &Keyed(2)),
+        Err(Error::Corrupted { .. })
+    ));
+    let other = Vector::from_slice(&items[1..], Some(64));
+    let forged = other.save_signed(&mut store, &Keyed(2)).unwrap();
+    assert!(matches!(
+        verify_root(&forged, &Keyed(1), &store),
+        Err(Error::Corrupted { .. })
+    ));
+    let sig = Keyed(1).sign(&message(&root)).unwrap();
+    let mut data = vec![];
+    write_head(&mut data, cbor::MAP, 2);
+    "sig".encode(&mut data);
+    write_head(&mut data, cbor::BYTES, sig.len() as u64);
+    data.extend_from_slice(&sig);
+    "root".encode(&mut data);
+    other.save(&mut store).unwrap().encode(&mut data);
+    let forged = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+    assert!(matches!(
+        verify_root(&forged, &Keyed(1), &store),
+        Err(Error::Corrupted { .. })
+    ));
+
+    // signed root without its signature, and the root itself.
+    let mut data = vec![];
+    write_head(&mut data, cbor::MAP, 1);
+    "root".encode(&mut data);
+    root.encode(&mut data);
+    let cid = store.put(Block::new(Codec::DagCbor, data)).unwrap();
+    assert!(matches!(
+        verify_root(&cid, &Keyed(1), &store),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(matches!(
+        verify_root(&root, &Keyed(1), &store),
+        Err(Error::Corrupted { .. })
+    ));
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn test_signed_root_ed25519() {
+    let key = ed25519_dalek::SigningKey::from_bytes(&[7; 32]);
+    let arr = Vector::from_slice(&[1_u64, 2, 3], None);
+    let mut store = MemoryStore::new();
+    let cid = arr.save_signed(&mut store, &key).unwrap();
+    let val: Vector<u64> =
+        Vector::load_signed(&cid, &store, &key.verifying_key()).unwrap();
+    assert_eq!(Vec::<u64>::from(val), vec![1, 2, 3]);
+
+    let other = ed25519_dalek::SigningKey::from_bytes(&[8; 32]);
+    assert!(matches!(
+        Vector::<u64>::load_signed(&cid, &store, &other.verifying_key()),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(!key.verifying_key().verify(b"msg", &[0; 10]));
+}
@@ -0,0 +1,207 @@
+//! Blockstore, holding blocks by their CID.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
+use super::{lru::Lru, Block, Cid};
+use crate::Result;
+
+/// Store of blocks, addressed by their CID, refer `Vector::save` and
+/// `Vector::load`.
+///
+/// Blocks are immutable, and a CID always addresses the same data, hence
+/// putting a block already in the store shall be a no-op.
+pub trait Blockstore {
+    /// Return the data of block `cid`, None if the block is not in the
+    /// store.
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>>;
+
+    /// Put `block` into the store, and return its CID.
+    fn put(&mut self, block: Block) -> Result<Cid>;
+
+    /// Hint that blocks `cids` are likely to be read soon, in that order,
+    /// refer `LazyVector`. Stores backed by slow storage can fetch them in
+    /// the background, default is a no-op.
+    fn prefetch(&self, _cids: &[Cid]) {}
+
+    /// Pin blocks `cids`, that are reachable from root block `root`,
+    /// including the root block, first in the list. Called on saving, and
+    /// on loading, a vector, refer `Vector::save` and `Vector::load_pinned`,
+    /// default is a no-op.
+    fn pin(&mut self, _root: &Cid, _cids: &[Cid]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Unpin blocks `cids`, that are reachable from root block `root` of a
+    /// retired version, and not from versions still live. Called on
+    /// retiring a version, refer `ipld::retire`, default is a no-op.
+    fn unpin(&mut self, _root: &Cid, _cids: &[Cid]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Blockstore held in memory.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    blocks: HashMap<Cid, Vec<u8>>,
+}
+
+impl MemoryStore {
+    /// Create an empty store.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Return the number of blocks in the store.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Return whether the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Return the total size of blocks in the store, in bytes.
+    pub fn size(&self) -> usize {
+        self.blocks.values().map(|data| data.len()).sum()
+    }
+
+    /// Return whether block `cid` is in the store.
+    pub fn contains(&self, cid: &Cid) -> bool {
+        self.blocks.contains_key(cid)
+    }
+
+    /// Remove block `cid` from the store, and return its data.
+    pub fn remove(&mut self, cid: &Cid) -> Option<Vec<u8>> {
+        self.blocks.remove(cid)
+    }
+
+    /// Return an iterator over the CIDs of blocks in the store, in no
+    /// particular order.
+    pub fn cids(&self) -> impl Iterator<Item = &Cid> + '_ {
+        self.blocks.keys()
+    }
+}
+
+impl Blockstore for MemoryStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        Ok(self.blocks.get(cid).cloned())
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.blocks.entry(block.cid).or_insert(block.data);
+        Ok(block.cid)
+    }
+}
+
+/// Caching decorator over a blockstore, holding recently read blocks in
+/// memory, upto a capacity in bytes.
+///
+/// Blocks are cached as they are read from the inner store, and the least
+/// recently used blocks are evicted beyond the capacity. Blocks put are
+/// written through to the inner store. Decoded nodes are cached by
+/// `LazyVector`, refer `LazyVector::set_budget`, this cache saves
+/// re-fetching blocks that were dropped from there, or shared across
+/// vectors.
+pub struct CacheStore<S> {
+    store: S,
+    cache: RefCell<Lru<Vec<u8>>>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+impl<S> CacheStore<S>
+where
+    S: Blockstore,
+{
+    /// Create a cache over `store`, holding upto `capacity` bytes of
+    /// blocks.
+    pub fn new(store: S, capacity: usize) -> CacheStore<S> {
+        let mut cache = Lru::new(|data: &Vec<u8>| data.len());
+        cache.set_capacity(Some(capacity));
+        CacheStore {
+            store,
+            cache: RefCell::new(cache),
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+        }
+    }
+
+    /// Set the capacity of the cache, in bytes, evicting blocks beyond it.
+    pub fn set_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.cache.get_mut().set_capacity(Some(capacity));
+        self
+    }
+
+    /// Return the number of blocks in the cache.
+    pub fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Return whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the total size of blocks in the cache, in bytes.
+    pub fn size(&self) -> usize {
+        self.cache.borrow().size()
+    }
+
+    /// Return the number of reads served from the cache, and the number of
+    /// reads served by the inner store.
+    pub fn hits(&self) -> (usize, usize) {
+        (self.hits.get(), self.misses.get())
+    }
+
+    /// Return a reference to the inner store.
+    pub fn as_inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Return the inner store, dropping the cache.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S> Blockstore for CacheStore<S>
+where
+    S: Blockstore,
+{
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        if let Some(data) = self.cache.borrow_mut().get(cid) {
+            self.hits.set(self.hits.get() + 1);
+            return Ok(Some(data.clone()));
+        }
+        self.misses.set(self.misses.get() + 1);
+        let data = self.store.get(cid)?;
+        if let Some(data) = &data {
+            self.cache.borrow_mut().insert(*cid, data.clone());
+        }
+        Ok(data)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+
+    fn prefetch(&self, cids: &[Cid]) {
+        self.store.prefetch(cids)
+    }
+
+    fn pin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.pin(root, cids)
+    }
+
+    fn unpin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.unpin(root, cids)
+    }
+}
+
+#[cfg(test)]
+#[path = "store_test.rs"]
+mod store_test;
@@ -0,0 +1,69 @@
+use super::*;
+use crate::ipld::Codec;
+
+#[test]
+fn test_memory_store() {
+    let mut store = MemoryStore::new();
+    assert!(store.is_empty());
+
+    let block = Block::new(Codec::DagCbor, vec![0xa0]);
+    let cid = block.cid;
+    assert_eq!(store.get(&cid).unwrap(), None);
+    assert_eq!(store.put(block.clone()).unwrap(), cid);
+    assert_eq!(store.put(block).unwrap(), cid);
+    assert_eq!(store.len(), 1);
+    assert_eq!(store.size(), 1);
+    assert!(store.contains(&cid));
+    assert_eq!(store.get(&cid).unwrap(), Some(vec![0xa0]));
+    assert_eq!(store.cids().collect::<Vec<&Cid>>(), vec![&cid]);
+
+    let other = Block::new(Codec::DagCbor, vec![0x80]);
+    store.put(other.clone()).unwrap();
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.remove(&cid), Some(vec![0xa0]));
+    assert_eq!(store.get(&cid).unwrap(), None);
+    assert_eq!(store.get(&other.cid).unwrap(), Some(vec![0x80]));
+}
+
+#[test]
+fn test_cache_store() {
+    let blocks: Vec<Block> = (0..100_u64)
+        .map(|i| Block::new(Codec::DagCbor, vec![0x19, 0x01, i as u8]))
+        .collect();
+
+    let mut store = CacheStore::new(MemoryStore::new(), 30);
+    assert!(store.is_empty());
+    for block in blocks.iter() {
+        store.put(block.clone()).unwrap();
+    }
+    assert!(store.is_empty());
+    assert_eq!(store.as_inner().len(), 100);
+
+    // upto 10 blocks of 3 bytes, least recently used evicted.
+    for block in blocks.iter() {
+        assert_eq!(store.get(&block.cid).unwrap(), Some(block.data.clone()));
+    }
+    assert_eq!(store.hits(), (0, 100));
+    assert_eq!(store.len(), 10);
+    assert_eq!(store.size(), 30);
+    for block in blocks[90..].iter() {
+        assert_eq!(store.get(&block.cid).unwrap(), Some(block.data.clone()));
+    }
+    assert_eq!(store.hits(), (10, 100));
+    store.get(&blocks[90].cid).unwrap();
+    store.get(&blocks[0].cid).unwrap();
+    assert_eq!(store.hits(), (11, 101));
+    store.get(&blocks[90].cid).unwrap();
+    store.get(&blocks[91].cid).unwrap();
+    assert_eq!(store.hits(), (12, 102));
+
+    // missing blocks are not cached.
+    let other = Block::new(Codec::DagCbor, vec![0x80]);
+    assert_eq!(store.get(&other.cid).unwrap(), None);
+    assert_eq!(store.get(&other.cid).unwrap(), None);
+    assert_eq!(store.hits(), (12, 104));
+
+    store.set_capacity(6);
+    assert_eq!(store.len(), 2);
+    assert_eq!(store.into_inner().len(), 100);
+}
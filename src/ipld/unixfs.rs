@@ -0,0 +1,278 @@
+//! UnixFS files, refer [spec](https://github.com/ipfs/specs/blob/main/UNIXFS.md).
+
+use super::{verify_digest, Block, Blockstore, Cid, Multihash};
+use crate::{Error, Result};
+
+// multicodec code of raw blocks, holding the bytes of a chunk.
+const RAW: u64 = 0x55;
+// multicodec code of DAG-PB blocks, holding file nodes.
+const DAG_PB: u64 = 0x70;
+// links per file node, same as `ipfs add`, for a balanced layout.
+const MAX_LINKS: usize = 174;
+// UnixFS data types, of file nodes.
+const TYPE_RAW: u64 = 0;
+const TYPE_FILE: u64 = 2;
+
+/// Save `chunks`, the content of a file, into `store` as a UnixFS file,
+/// and return the CID of its root block. Blocks are laid out the same as
+/// `ipfs add --cid-version=1`, for the same chunks:
+///
+/// * each chunk is a raw block, addressed by a CIDv1 using SHA-256.
+/// * file nodes are DAG-PB blocks, linking to upto 174 chunks, or file
+///   nodes a level below, in a balanced tree.
+/// * a file of a single chunk is the raw block of that chunk, and an empty
+///   file is an empty raw block.
+///
+/// Chunks shall be within 1MB, the largest block exchanged by IPFS peers.
+/// Blocks are put into the store as they are encoded, children before
+/// their parent, and then pinned, refer [Blockstore::pin]. Refer
+/// `Vector::save_unixfs`.
+pub fn save_unixfs<'a, I>(chunks: I, store: &mut dyn Blockstore) -> Result<Cid>
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut cids = vec![];
+    // cid, size of the content, and size of the blocks, under each link.
+    let mut level: Vec<(Cid, u64, u64)> = vec![];
+    for chunk in chunks.into_iter().filter(|chunk| !chunk.is_empty()) {
+        let block = new_block(RAW, chunk.to_vec());
+        let n = chunk.len() as u64;
+        level.push((block.cid, n, n));
+        cids.push(store.put(block)?);
+    }
+    if level.is_empty() {
+        let block = new_block(RAW, vec![]);
+        level.push((block.cid, 0, 0));
+        cids.push(store.put(block)?);
+    }
+
+    while level.len() > 1 {
+        let mut links = vec![];
+        for chunk in level.chunks(MAX_LINKS) {
+            let (data, size) = encode_file_node(chunk);
+            let block = new_block(DAG_PB, data);
+            let tsize = block.data.len() as u64 + chunk.iter().map(|l| l.2).sum::<u64>();
+            links.push((block.cid, size, tsize));
+            cids.push(store.put(block)?);
+        }
+        level = links;
+    }
+
+    let root = level[0].0;
+    cids.rotate_right(1);
+    store.pin(&root, &cids)?;
+    Ok(root)
+}
+
+/// Load the content of a UnixFS file, from its root block `cid` in
+/// `store`, as a list of chunks, in the order of the file. Blocks are
+/// verified against their CID. Raw blocks, and DAG-PB file nodes, in any
+/// layout, with or without raw leaves, are read, as long as their CIDs
+/// are CIDv1.
+///
+/// Return `NotFound` if a block is missing from `store`, and `Corrupted`
+/// if a block fails to decode, is not a file, or file sizes do not add
+/// up. Refer `Vector::load_unixfs`.
+pub fn load_unixfs(cid: &Cid, store: &dyn Blockstore) -> Result<Vec<Vec<u8>>> {
+    let mut chunks = vec![];
+    let mut filesize = None;
+    let mut stack = vec![*cid];
+    while let Some(cid) = stack.pop() {
+        let data = match store.get(&cid)? {
+            Some(data) => data,
+            None => Err(Error::NotFound {
+                detail: format!("block {}", cid),
+            })?,
+        };
+        verify_digest(&cid, &data)?;
+        let node = match cid.codec() {
+            RAW => {
+                chunks.push(data);
+                continue;
+            }
+            DAG_PB => decode_file_node(&cid, &data)?,
+            code => Err(corrupted(&cid, &format!("codec {:#x}, not a file", code)))?,
+        };
+        filesize = filesize.or(Some(node.filesize));
+        if !node.data.is_empty() {
+            chunks.push(node.data);
+        }
+        stack.extend(node.links.into_iter().rev());
+    }
+
+    let n: u64 = chunks.iter().map(|c| c.len() as u64).sum();
+    match filesize {
+        Some(size) if size != n => {
+            let msg = format!("file of {} bytes, want {}", n, size);
+            Err(corrupted(cid, &msg))
+        }
+        _ => Ok(chunks.into_iter().filter(|c| !c.is_empty()).collect()),
+    }
+}
+
+// file node, as DAG-PB, `PBNode {Links: [PBLink], Data: bytes}`, where
+// each link is `PBLink {Hash: cid, Name: "", Tsize: n}`, and the data is
+// UnixFS `Data {Type: File, filesize: n, blocksizes: [n]}`. Return the
+// encoded node and the size of its content.
+fn encode_file_node(links: &[(Cid, u64, u64)]) -> (Vec<u8>, u64) {
+    let size: u64 = links.iter().map(|l| l.1).sum();
+    let mut data = vec![];
+    write_uint(&mut data, 1, TYPE_FILE);
+    write_uint(&mut data, 3, size);
+    for (_, n, _) in links.iter() {
+        write_uint(&mut data, 4, *n);
+    }
+
+    let mut node = vec![];
+    for (cid, _, tsize) in links.iter() {
+        let mut link = vec![];
+        write_bytes(&mut link, 1, &cid.to_bytes());
+        write_bytes(&mut link, 2, b"");
+        write_uint(&mut link, 3, *tsize);
+        write_bytes(&mut node, 2, &link);
+    }
+    write_bytes(&mut node, 1, &data);
+    (node, size)
+}
+
+// file node decoded from a DAG-PB block.
+struct FileNode {
+    links: Vec<Cid>,
+    data: Vec<u8>,
+    filesize: u64,
+}
+
+fn decode_file_node(cid: &Cid, mut buf: &[u8]) -> Result<FileNode> {
+    let (mut links, mut unixfs) = (vec![], None);
+    while !buf.is_empty() {
+        match read_field(&mut buf)? {
+            (2, Field::Bytes(mut link)) => {
+                while !link.is_empty() {
+                    if let (1, Field::Bytes(hash)) = read_field(&mut link)? {
+                        links.push(Cid::from_bytes(hash)?);
+                    }
+                }
+            }
+            (1, Field::Bytes(data)) => unixfs = Some(data),
+            _ => (),
+        }
+    }
+    let (mut typ, mut data, mut filesize, mut blocksizes) = (None, vec![], None, vec![]);
+    let mut buf = match unixfs {
+        Some(buf) => buf,
+        None => Err(corrupted(cid, "dag-pb node without unixfs data"))?,
+    };
+    while !buf.is_empty() {
+        match read_field(&mut buf)? {
+            (1, Field::Uint(n)) => typ = Some(n),
+            (2, Field::Bytes(bytes)) => data = bytes.to_vec(),
+            (3, Field::Uint(n)) => filesize = Some(n),
+            (4, Field::Uint(n)) => blocksizes.push(n),
+            (4, Field::Bytes(mut packed)) => {
+                while !packed.is_empty() {
+                    blocksizes.push(read_varint(&mut packed)?);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    match typ {
+        Some(TYPE_FILE) | Some(TYPE_RAW) => (),
+        Some(typ) => Err(corrupted(cid, &format!("unixfs type {}, not a file", typ)))?,
+        None => Err(corrupted(cid, "unixfs data without type"))?,
+    }
+    if !links.is_empty() && blocksizes.len() != links.len() {
+        let msg = format!("{} links with {} blocksizes", links.len(), blocksizes.len());
+        Err(corrupted(cid, &msg))?
+    }
+    let size = data.len() as u64 + blocksizes.iter().sum::<u64>();
+    match filesize {
+        Some(filesize) if filesize != size => {
+            let msg = format!("filesize {}, want {}", filesize, size);
+            Err(corrupted(cid, &msg))
+        }
+        _ => Ok(FileNode {
+            links,
+            data,
+            filesize: size,
+        }),
+    }
+}
+
+// protobuf field, varint or length delimited, other wire types are not
+// used by DAG-PB and UnixFS.
+enum Field<'a> {
+    Uint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn read_field<'a>(buf: &mut &'a [u8]) -> Result<(u64, Field<'a>)> {
+    let key = read_varint(buf)?;
+    match key & 0x7 {
+        0 => Ok((key >> 3, Field::Uint(read_varint(buf)?))),
+        2 => match read_varint(buf)? {
+            n if n <= buf.len() as u64 => {
+                let (bytes, rest) = buf.split_at(n as usize);
+                *buf = rest;
+                Ok((key >> 3, Field::Bytes(bytes)))
+            }
+            n => Err(malformed(format!("field of {} bytes", n))),
+        },
+        wire => Err(malformed(format!("wire type {}", wire))),
+    }
+}
+
+// read a protobuf varint, of at most 10 bytes.
+fn read_varint(buf: &mut &[u8]) -> Result<u64> {
+    let mut n = 0;
+    for (i, byte) in buf.iter().enumerate().take(10) {
+        n |= u64::from(byte & 0x7f)
+            .checked_shl(7 * i as u32)
+            .unwrap_or(0);
+        if byte & 0x80 == 0 {
+            *buf = &buf[i + 1..];
+            return Ok(n);
+        }
+    }
+    Err(malformed("varint truncated".to_string()))
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8)
+}
+
+fn write_uint(buf: &mut Vec<u8>, field: u64, n: u64) {
+    write_varint(buf, field << 3);
+    write_varint(buf, n);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_varint(buf, (field << 3) | 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn new_block(code: u64, data: Vec<u8>) -> Block {
+    let hash = Multihash::Sha2_256;
+    let cid = Cid::with_code(code, hash, hash.digest(&data));
+    Block { cid, data }
+}
+
+fn malformed(detail: String) -> Error {
+    let detail = format!("unixfs {}", detail);
+    Error::Corrupted { detail }
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "unixfs_test.rs"]
+mod unixfs_test;
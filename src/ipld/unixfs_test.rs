@@ -0,0 +1,142 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{ipld::MemoryStore, rc::Vector};
+
+#[test]
+fn test_unixfs() {
+    let seed: u64 = random();
+    println!("test_unixfs seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    let bytes: Vec<u8> = (0..1_000_000).map(|_| rng.gen()).collect();
+    let arr = Vector::from_slice(&bytes, Some(256 * 1024));
+    let mut store = MemoryStore::new();
+    let root = arr.save_unixfs(&mut store).unwrap();
+    assert_eq!(root.codec(), DAG_PB);
+    assert_eq!(store.len(), 5);
+
+    let val = Vector::load_unixfs(&root, &store).unwrap();
+    assert_eq!(Vec::<u8>::from(val), bytes);
+    let chunks = load_unixfs(&root, &store).unwrap();
+    let lens: Vec<usize> = chunks.iter().map(|c| c.len()).collect();
+    assert_eq!(lens, vec![262144, 262144, 262144, 213568]);
+
+    // edits save only the chunks edited, split in two if full, and the
+    // file node.
+    let mut val = Vector::load_unixfs(&root, &store).unwrap();
+    val.insert(300_000, 0xff).unwrap();
+    val.remove(10).unwrap();
+    let root2 = val.save_unixfs(&mut store).unwrap();
+    assert!(store.len() - 5 <= 4, "{}", store.len());
+    let mut refv = bytes.clone();
+    refv.insert(300_000, 0xff);
+    refv.remove(10);
+    let val = Vector::load_unixfs(&root2, &store).unwrap();
+    assert_eq!(Vec::<u8>::from(val), refv);
+
+    // files of more than 174 chunks, two levels of file nodes.
+    let arr = Vector::from_slice(&bytes[..200_000], Some(1000));
+    let root = arr.save_unixfs(&mut store).unwrap();
+    let val = Vector::load_unixfs(&root, &store).unwrap();
+    assert_eq!(Vec::<u8>::from(val), &bytes[..200_000]);
+    assert_eq!(load_unixfs(&root, &store).unwrap().len(), 200);
+}
+
+#[test]
+fn test_unixfs_fixture() {
+    // empty file, and a file of a single chunk, are raw blocks.
+    let mut store = MemoryStore::new();
+    let root = Vector::<u8>::default().save_unixfs(&mut store).unwrap();
+    assert_eq!(
+        root.to_string(),
+        "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+    );
+    assert!(Vector::load_unixfs(&root, &store).unwrap().is_empty());
+
+    let root = Vector::from_slice(b"hello", None)
+        .save_unixfs(&mut store)
+        .unwrap();
+    assert_eq!(root.codec(), RAW);
+    assert_eq!(store.get(&root).unwrap(), Some(b"hello".to_vec()));
+
+    // file node, laid out as DAG-PB with UnixFS data.
+    let root = save_unixfs(vec![&b"ab"[..], &b"cde"[..]], &mut store).unwrap();
+    let (a, b) = (
+        new_block(RAW, b"ab".to_vec()),
+        new_block(RAW, b"cde".to_vec()),
+    );
+    let mut node = vec![];
+    for (cid, n) in [(a.cid, 2), (b.cid, 3)].iter() {
+        node.extend_from_slice(&[0x12, 0x2a, 0x0a, 0x24]);
+        node.extend_from_slice(&cid.to_bytes());
+        node.extend_from_slice(&[0x12, 0x00, 0x18, *n]);
+    }
+    node.extend_from_slice(&[0x0a, 0x08, 0x08, 0x02, 0x18, 0x05, 0x20, 0x02, 0x20, 0x03]);
+    assert_eq!(store.get(&root).unwrap(), Some(node.clone()));
+    assert_eq!(root, new_block(DAG_PB, node).cid);
+
+    // leaves as DAG-PB file nodes, with inline data, as `ipfs add` does
+    // without raw leaves.
+    let leaf = new_block(
+        DAG_PB,
+        vec![0x0a, 0x07, 0x08, 0x02, 0x12, 0x01, b'x', 0x18, 0x01],
+    );
+    let mut data = vec![0x12, 0x2a, 0x0a, 0x24];
+    data.extend_from_slice(&leaf.cid.to_bytes());
+    data.extend_from_slice(&[0x12, 0x00, 0x18, 0x09]);
+    data.extend_from_slice(&[
+        0x0a, 0x09, 0x08, 0x02, 0x12, 0x01, b'w', 0x18, 0x02, 0x20, 0x01,
+    ]);
+    store.put(leaf).unwrap();
+    let root = store.put(new_block(DAG_PB, data)).unwrap();
+    let val = Vector::load_unixfs(&root, &store).unwrap();
+    assert_eq!(Vec::<u8>::from(val), b"wx".to_vec());
+}
+
+#[test]
+fn test_unixfs_corrupted() {
+    let load = |data: Vec<u8>| {
+        let mut store = MemoryStore::new();
+        let cid = store.put(new_block(DAG_PB, data)).unwrap();
+        load_unixfs(&cid, &store)
+    };
+    // {Data: {Type: File, Data: "ab", filesize: 2}}
+    let ok = vec![0x0a, 0x08, 0x08, 0x02, 0x12, 0x02, b'a', b'b', 0x18, 0x02];
+    assert_eq!(load(ok.clone()).unwrap(), vec![b"ab".to_vec()]);
+
+    let cases: Vec<(usize, u8)> = vec![
+        (3, 0x01), // directory, not a file
+        (9, 0x03), // filesize not adding up
+        (1, 0x20), // truncated field
+        (2, 0x0b), // wire type 3
+    ];
+    for (off, byte) in cases.into_iter() {
+        let mut bad = ok.clone();
+        bad[off] = byte;
+        assert!(
+            matches!(load(bad), Err(Error::Corrupted { .. })),
+            "{} {:x}",
+            off,
+            byte
+        );
+    }
+    assert!(matches!(load(vec![]), Err(Error::Corrupted { .. })));
+
+    // link to a missing block, and a block of another codec.
+    let mut store = MemoryStore::new();
+    let root = save_unixfs(vec![&b"ab"[..], &b"cde"[..]], &mut store).unwrap();
+    let chunk = new_block(RAW, b"ab".to_vec()).cid;
+    let mut other = store.clone();
+    other.remove(&chunk).unwrap();
+    assert!(matches!(
+        load_unixfs(&root, &other),
+        Err(Error::NotFound { .. })
+    ));
+    let arr = Vector::from_slice(&[1_u8, 2, 3], None);
+    let cid = arr.save(&mut store).unwrap();
+    assert!(matches!(
+        load_unixfs(&cid, &store),
+        Err(Error::Corrupted { .. })
+    ));
+}
@@ -0,0 +1,207 @@
+//! IPLD data-model values, refer [spec](https://ipld.io/docs/data-model/).
+
+use std::{cmp, collections::BTreeMap};
+
+use super::{
+    cbor::{self, malformed, read_head, take, write_head},
+    Cid, Decode, Encode,
+};
+use crate::Result;
+
+/// Value of the IPLD data model, encoded as DAG-CBOR.
+///
+/// A vector of values, `Vector<Ipld>`, is the list kind of the data model,
+/// with items encoded in place, and can be held in a document as
+/// [Ipld::List], or as a [Ipld::Link] to its root block once saved. Nested
+/// values are encoded and decoded using an explicit stack, hence
+/// documents nested deep do not overflow the call stack.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Ipld {
+    /// Null value.
+    Null,
+    /// Boolean value.
+    Bool(bool),
+    /// Integer, in the range of DAG-CBOR integers, `-2^64..2^64`. Integers
+    /// beyond the range are saturated when encoded.
+    Integer(i128),
+    /// Float, encoded in 64 bits. DAG-CBOR does not allow NaN and
+    /// infinities, and DAG-JSON does not allow floats.
+    Float(f64),
+    /// UTF-8 string.
+    String(String),
+    /// Byte string.
+    Bytes(Vec<u8>),
+    /// List of values.
+    List(Vec<Ipld>),
+    /// Map of string keys to values.
+    Map(BTreeMap<String, Ipld>),
+    /// Link to a block.
+    Link(Cid),
+}
+
+// item being encoded, a map key or a value.
+enum Item<'a> {
+    Key(&'a str),
+    Value(&'a Ipld),
+}
+
+impl Encode for Ipld {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        let mut stack = vec![Item::Value(self)];
+        while let Some(item) = stack.pop() {
+            let val = match item {
+                Item::Key(key) => {
+                    key.encode(buf);
+                    continue;
+                }
+                Item::Value(val) => val,
+            };
+            match val {
+                Ipld::Null => write_head(buf, cbor::SIMPLE, 22),
+                Ipld::Bool(val) => val.encode(buf),
+                Ipld::Integer(n) if *n < 0 => {
+                    let n = cmp::min(-1 - *n, i128::from(u64::MAX));
+                    write_head(buf, cbor::NINT, n as u64)
+                }
+                Ipld::Integer(n) => {
+                    let n = cmp::min(*n, i128::from(u64::MAX));
+                    write_head(buf, cbor::UINT, n as u64)
+                }
+                Ipld::Float(val) => {
+                    buf.push(0xfb);
+                    buf.extend_from_slice(&val.to_bits().to_be_bytes())
+                }
+                Ipld::String(val) => val.encode(buf),
+                Ipld::Bytes(val) => {
+                    write_head(buf, cbor::BYTES, val.len() as u64);
+                    buf.extend_from_slice(val)
+                }
+                Ipld::List(items) => {
+                    write_head(buf, cbor::ARRAY, items.len() as u64);
+                    stack.extend(items.iter().rev().map(Item::Value))
+                }
+                Ipld::Map(map) => {
+                    // keys are sorted length first, as required by DAG-CBOR.
+                    write_head(buf, cbor::MAP, map.len() as u64);
+                    let mut entries: Vec<(&String, &Ipld)> = map.iter().collect();
+                    entries.sort_by(|(a, _), (b, _)| key_order(a, b));
+                    for (key, val) in entries.into_iter().rev() {
+                        stack.push(Item::Value(val));
+                        stack.push(Item::Key(key));
+                    }
+                }
+                Ipld::Link(cid) => cid.encode(buf),
+            }
+        }
+    }
+}
+
+// list or map being decoded, with the number of items in it, and the
+// items decoded so far.
+enum Partial {
+    List(u64, Vec<Ipld>),
+    Map(u64, Vec<(String, Ipld)>, Option<String>),
+}
+
+impl Decode for Ipld {
+    fn decode(buf: &mut &[u8]) -> Result<Ipld> {
+        let mut stack: Vec<Partial> = vec![];
+        loop {
+            if let Some(Partial::Map(_, entries, key @ None)) = stack.last_mut() {
+                let k = String::decode(buf)?;
+                match entries.last() {
+                    Some((prev, _)) if key_order(prev, &k) != cmp::Ordering::Less => {
+                        Err(malformed(format!("map key {:?} after {:?}", k, prev)))?
+                    }
+                    _ => *key = Some(k),
+                }
+            }
+
+            let mut val = match buf.first().copied() {
+                Some(0xfb) => {
+                    take(buf, 1)?;
+                    let mut bits = [0; 8];
+                    bits.copy_from_slice(take(buf, 8)?);
+                    Ipld::Float(f64::from_bits(u64::from_be_bytes(bits)))
+                }
+                Some(initial) if initial >> 5 == cbor::TAG => {
+                    Ipld::Link(Cid::decode(buf)?)
+                }
+                _ => match read_head(buf)? {
+                    (cbor::UINT, n) => Ipld::Integer(i128::from(n)),
+                    (cbor::NINT, n) => Ipld::Integer(-1 - i128::from(n)),
+                    (cbor::BYTES, n) => Ipld::Bytes(take(buf, n)?.to_vec()),
+                    (cbor::TEXT, n) => match std::str::from_utf8(take(buf, n)?) {
+                        Ok(s) => Ipld::String(s.to_string()),
+                        Err(err) => Err(malformed(format!("text {}", err)))?,
+                    },
+                    (cbor::ARRAY, 0) => Ipld::List(vec![]),
+                    (cbor::ARRAY, n) => {
+                        // every item takes at least a byte.
+                        let k = cmp::min(n, buf.len() as u64) as usize;
+                        stack.push(Partial::List(n, Vec::with_capacity(k)));
+                        continue;
+                    }
+                    (cbor::MAP, 0) => Ipld::Map(BTreeMap::new()),
+                    (cbor::MAP, n) => {
+                        stack.push(Partial::Map(n, vec![], None));
+                        continue;
+                    }
+                    (cbor::SIMPLE, 20) => Ipld::Bool(false),
+                    (cbor::SIMPLE, 21) => Ipld::Bool(true),
+                    (cbor::SIMPLE, 22) => Ipld::Null,
+                    (major, arg) => {
+                        Err(malformed(format!("data item {}/{:#x}", major, arg)))?
+                    }
+                },
+            };
+
+            // add the value to its list or map, that are done when full.
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(val),
+                    Some(Partial::List(n, items)) => {
+                        items.push(val);
+                        if items.len() as u64 != *n {
+                            break;
+                        }
+                    }
+                    Some(Partial::Map(n, entries, key)) => {
+                        entries.push((key.take().unwrap_or_default(), val));
+                        if entries.len() as u64 != *n {
+                            break;
+                        }
+                    }
+                }
+                val = match stack.pop() {
+                    Some(Partial::List(_, items)) => Ipld::List(items),
+                    Some(Partial::Map(_, entries, _)) => {
+                        Ipld::Map(entries.into_iter().collect())
+                    }
+                    None => unreachable!(),
+                };
+            }
+        }
+    }
+}
+
+impl From<Vec<Ipld>> for Ipld {
+    fn from(items: Vec<Ipld>) -> Ipld {
+        Ipld::List(items)
+    }
+}
+
+impl From<Cid> for Ipld {
+    fn from(cid: Cid) -> Ipld {
+        Ipld::Link(cid)
+    }
+}
+
+// order of map keys in DAG-CBOR, shorter keys first, and then bytewise.
+fn key_order(a: &str, b: &str) -> cmp::Ordering {
+    (a.len(), a.as_bytes()).cmp(&(b.len(), b.as_bytes()))
+}
+
+#[cfg(test)]
+#[path = "value_test.rs"]
+mod value_test;
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::{
+    ipld::{json, Block, Codec, MemoryStore},
+    rc::Vector,
+    Error,
+};
+
+fn encode(val: &Ipld) -> Vec<u8> {
+    let mut buf = vec![];
+    val.encode(&mut buf);
+    buf
+}
+
+fn decode(mut buf: &[u8]) -> Result<Ipld> {
+    let val = Ipld::decode(&mut buf)?;
+    assert!(buf.is_empty());
+    Ok(val)
+}
+
+fn document() -> Ipld {
+    let cid = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    let mut map = BTreeMap::new();
+    map.insert("name".to_string(), Ipld::String("ppar".to_string()));
+    map.insert("id".to_string(), Ipld::Integer(-42));
+    map.insert("ok".to_string(), Ipld::Bool(true));
+    map.insert("link".to_string(), Ipld::Link(cid));
+    map.insert("none".to_string(), Ipld::Null);
+    map.insert("bytes".to_string(), Ipld::Bytes(vec![1, 2, 3]));
+    map.insert(
+        "list".to_string(),
+        Ipld::List(vec![
+            Ipld::Integer(u64::MAX.into()),
+            Ipld::Map(BTreeMap::new()),
+        ]),
+    );
+    Ipld::Map(map)
+}
+
+#[test]
+fn test_ipld_value() {
+    let val = document();
+    let buf = encode(&val);
+    assert_eq!(decode(&buf).unwrap(), val);
+
+    // keys are sorted length first, and the encoding is canonical.
+    let json = json::from_cbor(&buf).unwrap();
+    assert_eq!(
+        String::from_utf8(json.clone()).unwrap(),
+        concat!(
+            r#"{"id":-42,"ok":true,"link":{"/":"#,
+            r#""bafyreigbtj4x7ip5legnfznufuopl4sg4knzc2cof6duas4b3q2fy6swua"},"#,
+            r#""list":[18446744073709551615,{}],"name":"ppar","none":null,"#,
+            r#""bytes":{"/":{"bytes":"AQID"}}}"#,
+        )
+    );
+    assert_eq!(json::to_cbor(&json).unwrap(), buf);
+
+    let val = Ipld::Float(1.5);
+    assert_eq!(encode(&val), [0xfb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(decode(&encode(&val)).unwrap(), val);
+
+    // integers beyond DAG-CBOR are saturated.
+    let n = i128::from(u64::MAX);
+    assert_eq!(encode(&Ipld::Integer(n + 1)), encode(&Ipld::Integer(n)));
+    let val = decode(&encode(&Ipld::Integer(-n - 5))).unwrap();
+    assert_eq!(val, Ipld::Integer(-n - 1));
+
+    // deeply nested documents.
+    let mut val = Ipld::Null;
+    for _ in 0..100_000 {
+        val = Ipld::List(vec![val]);
+    }
+    let buf = encode(&val);
+    assert_eq!(buf.len(), 100_001);
+    // dropping is recursive, like other nested types, hence leaked.
+    std::mem::forget(val);
+    let val = decode(&buf).unwrap();
+    let mut depth = 0;
+    let mut items = match &val {
+        Ipld::List(items) => items,
+        _ => unreachable!(),
+    };
+    while let Some(Ipld::List(next)) = items.first() {
+        items = next;
+        depth += 1;
+    }
+    assert_eq!(depth, 99_999);
+    std::mem::forget(val);
+}
+
+#[test]
+fn test_ipld_malformed() {
+    let map = |keys: &[&str]| {
+        let mut buf = vec![0xa0 + keys.len() as u8];
+        keys.iter().for_each(|k| {
+            k.encode(&mut buf);
+            buf.push(0xf6)
+        });
+        buf
+    };
+    assert!(decode(&map(&["a", "b", "aa"])).is_ok());
+    for keys in [&["b", "a"][..], &["aa", "b"], &["a", "a"]].iter() {
+        assert!(matches!(decode(&map(keys)), Err(Error::Corrupted { .. })));
+    }
+
+    let bad: [&[u8]; 7] = [
+        &[],
+        &[0x82, 0x01],
+        &[0xf9, 0x3e, 0x00],
+        &[0xf7],
+        &[0xc1, 0x00],
+        &[0xa1, 0x01, 0x01],
+        &[0x9f, 0xff],
+    ];
+    for buf in bad.iter() {
+        assert!(
+            matches!(decode(buf), Err(Error::Corrupted { .. })),
+            "{:?}",
+            buf
+        );
+    }
+}
+
+#[test]
+fn test_ipld_vector() {
+    let items: Vec<Ipld> = (0..1000)
+        .map(|i| match i % 3 {
+            0 => Ipld::Integer(i),
+            1 => Ipld::String(i.to_string()),
+            _ => document(),
+        })
+        .collect();
+    let arr: Vector<Ipld> = items.iter().cloned().collect();
+    assert_eq!(Ipld::from(arr.clone()), Ipld::List(items.clone()));
+
+    let mut store = MemoryStore::new();
+    let root = arr.save(&mut store).unwrap();
+    let val: Vector<Ipld> = Vector::load(&root, &store).unwrap();
+    assert_eq!(Vec::<Ipld>::from(val), items);
+
+    // vector saved, and linked from a document.
+    let doc = Ipld::List(vec![Ipld::from(root), Ipld::from(arr)]);
+    assert_eq!(decode(&encode(&doc)).unwrap(), doc);
+}
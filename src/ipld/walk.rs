@@ -0,0 +1,364 @@
+//! Walks over the DAG of persisted vectors, without decoding their items.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use super::{cbor, decode_node, fetch, verify, Blockstore, Cid, Decode, Decoded};
+use crate::{Error, Result};
+
+// item of a leaf node, skipped without decoding.
+pub(crate) struct Skipped;
+
+impl Decode for Skipped {
+    fn decode(buf: &mut &[u8]) -> Result<Skipped> {
+        cbor::skip(buf)?;
+        Ok(Skipped)
+    }
+}
+
+// links of block `cid`, empty for a leaf node.
+fn links(store: &dyn Blockstore, cid: &Cid) -> Result<Vec<(usize, Cid)>> {
+    links_of(&fetch(store, cid)?)
+}
+
+pub(crate) fn links_of(data: &[u8]) -> Result<Vec<(usize, Cid)>> {
+    match decode_node::<Skipped>(data)? {
+        Decoded::Interior { links, .. } => Ok(links),
+        Decoded::Leaf { .. } => Ok(vec![]),
+    }
+}
+
+/// Return the CIDs of blocks reachable from `roots`, the root blocks of
+/// vectors saved into `store`, including the root blocks. Shared blocks
+/// are read once.
+///
+/// Blocks of `store` that are not reachable from its live roots can be
+/// dropped, to garbage collect the store. Return `NotFound` if a
+/// reachable block is missing from `store`, and `Corrupted` if a block
+/// fails to decode.
+pub fn reachable(roots: &[Cid], store: &dyn Blockstore) -> Result<HashSet<Cid>> {
+    let mut seen = HashSet::default();
+    walk(roots, store, &mut seen)?;
+    Ok(seen)
+}
+
+/// Return the CIDs of blocks reachable from `retired`, the root block of a
+/// version no more in use, that are not reachable from `live` roots, in
+/// depth first order, parents before their children.
+///
+/// These are the blocks orphaned by retiring the version, that can be
+/// dropped from `store`. Blocks reachable from `live` are walked first,
+/// refer [reachable].
+pub fn orphans(retired: &Cid, live: &[Cid], store: &dyn Blockstore) -> Result<Vec<Cid>> {
+    let mut seen = reachable(live, store)?;
+    walk(&[*retired], store, &mut seen)
+}
+
+/// Pin the blocks reachable from root block `root`, refer
+/// [Blockstore::pin], for vectors persisted without pinning, like by
+/// `Vector::flush`.
+pub fn pin(root: &Cid, store: &mut dyn Blockstore) -> Result<()> {
+    let cids = walk(&[*root], store, &mut HashSet::default())?;
+    store.pin(root, &cids)
+}
+
+/// Retire the version under root block `retired`, unpinning the blocks
+/// orphaned by it, refer [orphans] and [Blockstore::unpin]. Return the
+/// orphaned blocks, that can be dropped from `store`.
+pub fn retire(
+    retired: &Cid,
+    live: &[Cid],
+    store: &mut dyn Blockstore,
+) -> Result<Vec<Cid>> {
+    let cids = orphans(retired, live, store)?;
+    store.unpin(retired, &cids)?;
+    Ok(cids)
+}
+
+/// Return the CIDs of blocks missing from `store`, that are reachable from
+/// root block `root`, in depth first order. Blocks under a missing block
+/// are not known till it is fetched, hence a sync layer shall fetch the
+/// wanted blocks from peers, put them into `store`, and repeat till the
+/// list is empty, after which the vector can be loaded.
+///
+/// Return `Corrupted` if a block in `store` fails to decode.
+pub fn want_list(root: &Cid, store: &dyn Blockstore) -> Result<Vec<Cid>> {
+    let mut seen = HashSet::new();
+    let (mut stack, mut cids) = (vec![*root], vec![]);
+    while let Some(cid) = stack.pop() {
+        if seen.insert(cid) {
+            match store.get(&cid)? {
+                Some(data) => {
+                    let links = links_of(&verify(&cid, data)?)?;
+                    stack.extend(links.into_iter().rev().map(|(_, cid)| cid));
+                }
+                None => cids.push(cid),
+            }
+        }
+    }
+    Ok(cids)
+}
+
+/// Difference between two versions of a vector saved into a blockstore,
+/// refer [diff_cids].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DagDiff {
+    /// Index ranges that changed, as pairs of ranges in `a` and `b`, in
+    /// order. Items outside these ranges are the same, and in the same
+    /// order, in both versions. Ranges are at the granularity of leaf
+    /// nodes, and items within them can also be the same.
+    pub ranges: Vec<(Range<usize>, Range<usize>)>,
+    /// Blocks reachable from `a` and not from `b`, parents before their
+    /// children.
+    pub only_a: Vec<Cid>,
+    /// Blocks reachable from `b` and not from `a`, parents before their
+    /// children.
+    pub only_b: Vec<Cid>,
+}
+
+// subtree of a version, as its CID, number of items, and whether it is
+// read and found to be a leaf node.
+#[derive(Clone, Copy)]
+struct Segment {
+    cid: Cid,
+    len: usize,
+    leaf: bool,
+}
+
+/// Return the difference between the vectors saved under root blocks `a`
+/// and `b` in `store`, refer [DagDiff], for replicating, or syncing, large
+/// vectors.
+///
+/// Both DAGs are descended level by level, and subtrees with the same CID
+/// on both sides are skipped without reading them. Hence the blocks read
+/// are in proportion to the blocks unique to each side. Blocks that also
+/// repeat under a skipped subtree, like duplicate leaf nodes, can be
+/// reported as unique.
+pub fn diff_cids(a: &Cid, b: &Cid, store: &dyn Blockstore) -> Result<DagDiff> {
+    let mut diff = DagDiff::default();
+    if a == b {
+        return Ok(diff);
+    }
+
+    let segment = |cid: &Cid| Segment {
+        cid: *cid,
+        len: 0,
+        leaf: false,
+    };
+    let (mut xs, mut ys) = (vec![segment(a)], vec![segment(b)]);
+    let (mut known_a, mut known_b) = (HashSet::new(), HashSet::new());
+    known_a.insert(*a);
+    known_b.insert(*b);
+    loop {
+        let cids: HashSet<Cid> = xs.iter().map(|x| x.cid).collect();
+        let common: HashSet<Cid> = ys
+            .iter()
+            .map(|y| y.cid)
+            .filter(|c| cids.contains(c))
+            .collect();
+        let n = diff.only_a.len() + diff.only_b.len();
+        xs = expand(xs, &common, store, &mut known_a, &mut diff.only_a)?;
+        ys = expand(ys, &common, store, &mut known_b, &mut diff.only_b)?;
+        if n == diff.only_a.len() + diff.only_b.len() {
+            break;
+        }
+    }
+    diff.only_a.retain(|cid| !known_b.contains(cid));
+    diff.only_b.retain(|cid| !known_a.contains(cid));
+
+    // changed ranges are the gaps between subtrees matched in order.
+    let (mut i, mut j, mut off_a, mut off_b) = (0, 0, 0, 0);
+    for (ai, bj) in anchors(&xs, &ys)
+        .into_iter()
+        .chain(Some((xs.len(), ys.len())))
+    {
+        let n_a: usize = xs[i..ai].iter().map(|x| x.len).sum();
+        let n_b: usize = ys[j..bj].iter().map(|y| y.len).sum();
+        if n_a > 0 || n_b > 0 {
+            diff.ranges.push((off_a..off_a + n_a, off_b..off_b + n_b));
+        }
+        off_a += n_a + xs.get(ai).map(|x| x.len).unwrap_or(0);
+        off_b += n_b + ys.get(bj).map(|y| y.len).unwrap_or(0);
+        i = ai + 1;
+        j = bj + 1;
+    }
+
+    Ok(diff)
+}
+
+// read segments that are not `common`, and not leaf nodes, replacing the
+// interior nodes by their children. CIDs of blocks read are pushed into
+// `cids`, and CIDs seen are added to `known`.
+fn expand(
+    segments: Vec<Segment>,
+    common: &HashSet<Cid>,
+    store: &dyn Blockstore,
+    known: &mut HashSet<Cid>,
+    cids: &mut Vec<Cid>,
+) -> Result<Vec<Segment>> {
+    let mut items = vec![];
+    for seg in segments.into_iter() {
+        if seg.leaf || common.contains(&seg.cid) {
+            items.push(seg);
+            continue;
+        }
+        cids.push(seg.cid);
+        match links(store, &seg.cid)? {
+            links if links.is_empty() => items.push(Segment { leaf: true, ..seg }),
+            links => {
+                let mut count = 0;
+                for (n, cid) in links.into_iter() {
+                    known.insert(cid);
+                    let len = n.saturating_sub(count);
+                    count = n;
+                    items.push(Segment {
+                        cid,
+                        len,
+                        leaf: false,
+                    });
+                }
+            }
+        }
+    }
+    Ok(items)
+}
+
+// match segments with the same CID, on both sides, and return the longest
+// chain of matches that is in order on both sides, refer `Vector::diff`.
+fn anchors(xs: &[Segment], ys: &[Segment]) -> Vec<(usize, usize)> {
+    let mut index: HashMap<Cid, usize> = HashMap::new();
+    for (j, y) in ys.iter().enumerate() {
+        index.entry(y.cid).or_insert(j);
+    }
+    let matches: Vec<(usize, usize)> = xs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, x)| index.get(&x.cid).map(|j| (i, *j)))
+        .collect();
+
+    // longest increasing subsequence on index-in-ys, patience style.
+    let (mut tails, mut prevs): (Vec<usize>, Vec<Option<usize>>) = (vec![], vec![]);
+    for (k, (_, j)) in matches.iter().enumerate() {
+        let p = tails.partition_point(|t| matches[*t].1 < *j);
+        prevs.push(p.checked_sub(1).map(|p| tails[p]));
+        match tails.get_mut(p) {
+            Some(t) => *t = k,
+            None => tails.push(k),
+        }
+    }
+
+    let mut anchors = vec![];
+    let mut k = tails.last().copied();
+    while let Some(m) = k {
+        anchors.push(matches[m]);
+        k = prevs[m];
+    }
+    anchors.reverse();
+    anchors
+}
+
+/// Number of blocks, and their size in bytes as stored, refer
+/// [SharingReport].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Number of blocks.
+    pub blocks: usize,
+    /// Total size of blocks, in bytes.
+    pub bytes: usize,
+}
+
+impl Usage {
+    fn add(&mut self, bytes: usize) {
+        self.blocks += 1;
+        self.bytes += bytes;
+    }
+}
+
+/// Blocks shared across versions of a vector, and unique to each, refer
+/// [sharing_report].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharingReport {
+    /// Blocks reachable from any of the roots, shared blocks counted once.
+    pub total: Usage,
+    /// Blocks reachable from more than one root.
+    pub shared: Usage,
+    /// Blocks reachable only from the root, for each root, in the same
+    /// order as the roots. This is the cost of retaining the version.
+    pub unique: Vec<Usage>,
+}
+
+/// Return the blocks, and bytes, shared across `roots`, the root blocks of
+/// versions of a vector saved into `store`, and those unique to each
+/// version. Every block is read once, and the links of interior nodes
+/// are held in memory while walking.
+///
+/// In-memory sharing, between versions of a vector that are not saved, is
+/// reported by `Vector::footprint_exclusive`.
+pub fn sharing_report(roots: &[Cid], store: &dyn Blockstore) -> Result<SharingReport> {
+    // for each block, its size, links, number of roots reaching it, and
+    // the last root reaching it.
+    let mut blocks: HashMap<Cid, (usize, Vec<Cid>, usize, usize)> = HashMap::default();
+    for (i, root) in roots.iter().enumerate() {
+        let mut stack = vec![*root];
+        while let Some(cid) = stack.pop() {
+            let block = match blocks.get_mut(&cid) {
+                Some(block) if block.3 == i => continue,
+                Some(block) => block,
+                None => {
+                    let data = match store.get(&cid)? {
+                        Some(data) => data,
+                        None => Err(Error::NotFound {
+                            detail: format!("block {}", cid),
+                        })?,
+                    };
+                    let n = data.len();
+                    let links = links_of(&verify(&cid, data)?)?;
+                    let links = links.into_iter().map(|(_, cid)| cid).collect();
+                    blocks.entry(cid).or_insert((n, links, 0, i))
+                }
+            };
+            block.2 += 1;
+            block.3 = i;
+            stack.extend(block.1.iter().copied());
+        }
+    }
+
+    let mut report = SharingReport {
+        unique: vec![Usage::default(); roots.len()],
+        ..SharingReport::default()
+    };
+    for (n, _, n_roots, i) in blocks.values() {
+        report.total.add(*n);
+        match n_roots {
+            1 => report.unique[*i].add(*n),
+            _ => report.shared.add(*n),
+        }
+    }
+    Ok(report)
+}
+
+// walk the blocks under `roots`, that are not in `seen`, and return them
+// in depth first order, parents before their children. Blocks are
+// content addressed, hence a seen block is seen along with its subtree.
+fn walk(
+    roots: &[Cid],
+    store: &dyn Blockstore,
+    seen: &mut HashSet<Cid>,
+) -> Result<Vec<Cid>> {
+    let mut cids = vec![];
+    let mut stack: Vec<Cid> = roots.iter().rev().copied().collect();
+    while let Some(cid) = stack.pop() {
+        if seen.insert(cid) {
+            cids.push(cid);
+            let links = links(store, &cid)?;
+            stack.extend(links.into_iter().rev().map(|(_, cid)| cid));
+        }
+    }
+    Ok(cids)
+}
+
+#[cfg(test)]
+#[path = "walk_test.rs"]
+mod walk_test;
@@ -0,0 +1,331 @@
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+use crate::{
+    ipld::{Block, Codec, MemoryStore},
+    rc::{Flushed, Vector},
+    Error,
+};
+
+#[test]
+fn test_reachable() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let v1 = Vector::from_slice(&items, Some(1024));
+    let mut v2 = v1.clone();
+    v2.update(5000, 0).unwrap();
+    let mut v3 = v2.clone();
+    (0..1000).for_each(|i| v3.insert(v3.len(), i).unwrap());
+
+    let mut store = MemoryStore::new();
+    let roots: Vec<Cid> = [&v1, &v2, &v3]
+        .iter()
+        .map(|v| v.save(&mut store).unwrap())
+        .collect();
+    let stray = store.put(Block::new(Codec::DagCbor, vec![0xa0])).unwrap();
+
+    let all = reachable(&roots, &store).unwrap();
+    assert_eq!(all.len() + 1, store.len());
+    assert!(!all.contains(&stray));
+    let one = reachable(&roots[..1], &store).unwrap();
+    assert!(one.len() < all.len());
+    assert_eq!(reachable(&[], &store).unwrap().len(), 0);
+
+    // orphans of a retired version, root first.
+    let live = reachable(&roots[1..], &store).unwrap();
+    let orphans = orphans(&roots[0], &roots[1..], &store).unwrap();
+    assert_eq!(orphans[0], roots[0]);
+    let refs: HashSet<Cid> = one.difference(&live).copied().collect();
+    assert_eq!(orphans.iter().copied().collect::<HashSet<Cid>>(), refs);
+    assert_eq!(orphans.len(), refs.len());
+    assert!(
+        orphans.len() < one.len() / 10,
+        "{} {}",
+        orphans.len(),
+        one.len()
+    );
+
+    // garbage collect, keeping the live versions.
+    let dead: Vec<Cid> = store
+        .cids()
+        .filter(|cid| !live.contains(cid))
+        .copied()
+        .collect();
+    assert_eq!(dead.len(), orphans.len() + 1);
+    dead.iter().for_each(|cid| {
+        store.remove(cid);
+    });
+    assert_eq!(store.len(), live.len());
+    let val: Vector<u64> = Vector::load(&roots[1], &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), Vec::<u64>::from(v2));
+    let val: Vector<u64> = Vector::load(&roots[2], &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), Vec::<u64>::from(v3));
+    assert!(matches!(
+        Vector::<u64>::load(&roots[0], &store),
+        Err(Error::NotFound { .. })
+    ));
+    assert!(matches!(
+        reachable(&roots, &store),
+        Err(Error::NotFound { .. })
+    ));
+}
+
+// store recording the blocks pinned, by root.
+#[derive(Default)]
+struct PinStore {
+    store: MemoryStore,
+    pins: HashMap<Cid, Vec<Cid>>,
+    unpins: HashMap<Cid, Vec<Cid>>,
+}
+
+impl Blockstore for PinStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.store.get(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+
+    fn pin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.pins.insert(*root, cids.to_vec());
+        Ok(())
+    }
+
+    fn unpin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.unpins.insert(*root, cids.to_vec());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pin() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let v1 = Vector::from_slice(&items, Some(1024));
+    let mut v2 = v1.clone();
+    v2.update(0, 1).unwrap();
+
+    // pinned on save, and on load, root first.
+    let mut store = PinStore::default();
+    let r1 = v1.save(&mut store).unwrap();
+    let r2 = v2.save(&mut store).unwrap();
+    let set = |cids: &[Cid]| cids.iter().copied().collect::<HashSet<Cid>>();
+    for root in [r1, r2].iter() {
+        assert_eq!(store.pins[root][0], *root);
+        assert_eq!(set(&store.pins[root]), reachable(&[*root], &store).unwrap());
+    }
+    let pinned = store.pins.remove(&r1).unwrap();
+    let val: Vector<u64> = Vector::load_pinned(&r1, &mut store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+    assert_eq!(store.pins[&r1][0], r1);
+    assert_eq!(set(&store.pins[&r1]), set(&pinned));
+    assert_eq!(store.pins[&r1].len(), pinned.len());
+
+    // not pinned on flush, till pinned explicitly.
+    let mut v3 = v2.clone();
+    v3.update(1, 2).unwrap();
+    let r3 = v3.flush(&mut store, &mut Flushed::default()).unwrap();
+    assert!(!store.pins.contains_key(&r3));
+    pin(&r3, &mut store).unwrap();
+    assert_eq!(set(&store.pins[&r3]), reachable(&[r3], &store).unwrap());
+
+    // unpinned on retire.
+    let cids = retire(&r1, &[r2, r3], &mut store).unwrap();
+    assert_eq!(cids, orphans(&r1, &[r2, r3], &store).unwrap());
+    assert_eq!(store.unpins[&r1], cids);
+    assert!(!cids.is_empty());
+}
+
+#[test]
+fn test_sharing_report() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let v1 = Vector::from_slice(&items, Some(1024));
+    let mut v2 = v1.clone();
+    v2.update(5000, 0).unwrap();
+    let mut v3 = v2.clone();
+    (0..1000).for_each(|i| v3.insert(v3.len(), i).unwrap());
+
+    let mut store = MemoryStore::new();
+    let roots: Vec<Cid> = [&v1, &v2, &v3]
+        .iter()
+        .map(|v| v.save(&mut store).unwrap())
+        .collect();
+
+    let report = sharing_report(&roots, &store).unwrap();
+    assert_eq!(report.total.blocks, store.len());
+    assert_eq!(report.total.bytes, store.size());
+    let unique: Usage = report.unique.iter().fold(report.shared, |mut acc, u| {
+        acc.blocks += u.blocks;
+        acc.bytes += u.bytes;
+        acc
+    });
+    assert_eq!(unique, report.total);
+    for (i, root) in roots.iter().enumerate() {
+        let others: Vec<Cid> = roots.iter().filter(|r| *r != root).copied().collect();
+        let orphans = orphans(root, &others, &store).unwrap();
+        let bytes: usize = orphans
+            .iter()
+            .map(|cid| store.get(cid).unwrap().unwrap().len())
+            .sum();
+        let refu = Usage {
+            blocks: orphans.len(),
+            bytes,
+        };
+        assert_eq!(report.unique[i], refu, "{}", i);
+    }
+    assert!(report.shared.blocks * 2 > report.total.blocks);
+
+    // single version, and the same version twice.
+    let report = sharing_report(&roots[..1], &store).unwrap();
+    assert_eq!(report.shared, Usage::default());
+    assert_eq!(report.unique, vec![report.total]);
+    let report = sharing_report(&[roots[0], roots[0]], &store).unwrap();
+    assert_eq!(report.shared, report.total);
+    assert_eq!(report.unique, vec![Usage::default(); 2]);
+    assert_eq!(
+        sharing_report(&[], &store).unwrap(),
+        SharingReport::default()
+    );
+}
+
+#[test]
+fn test_want_list() {
+    let items: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&items, Some(1024));
+    let mut peer = MemoryStore::new();
+    let root = arr.save(&mut peer).unwrap();
+    assert!(want_list(&root, &peer).unwrap().is_empty());
+
+    // fetch wanted blocks from the peer, till none are missing.
+    let mut store = MemoryStore::new();
+    let mut rounds = 0;
+    loop {
+        let cids = want_list(&root, &store).unwrap();
+        if cids.is_empty() {
+            break;
+        }
+        assert!(cids.iter().all(|cid| !store.contains(cid)));
+        for cid in cids.into_iter() {
+            let data = peer.get(&cid).unwrap().unwrap();
+            store.put(Block { cid, data }).unwrap();
+        }
+        rounds += 1;
+    }
+    assert!(rounds <= arr.depth() + 2, "{} {}", rounds, arr.depth());
+    assert_eq!(store.len(), peer.len());
+    let val: Vector<u64> = Vector::load(&root, &store).unwrap();
+    assert_eq!(Vec::<u64>::from(val), items);
+
+    // partially present, only the missing leaf nodes are wanted.
+    let blocks = arr
+        .encode(Codec::DagCbor, crate::ipld::Multihash::Sha2_256)
+        .unwrap();
+    let leaves: Vec<Cid> = blocks
+        .iter()
+        .filter(|b| b.data[2..].starts_with(b"items"))
+        .map(|b| b.cid)
+        .take(3)
+        .collect();
+    leaves.iter().for_each(|cid| {
+        store.remove(cid);
+    });
+    assert_eq!(want_list(&root, &store).unwrap(), leaves);
+}
+
+// store counting the blocks read from it.
+#[derive(Default)]
+struct CountStore {
+    store: MemoryStore,
+    gets: std::cell::Cell<usize>,
+}
+
+impl Blockstore for CountStore {
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        self.gets.set(self.gets.get() + 1);
+        self.store.get(cid)
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        self.store.put(block)
+    }
+}
+
+// items outside the changed ranges shall be the same on both sides.
+fn check_diff(diff: &DagDiff, a: &[u64], b: &[u64]) {
+    let (mut i, mut j) = (0, 0);
+    for (ra, rb) in diff.ranges.iter() {
+        assert_eq!(ra.start - i, rb.start - j, "{:?}", diff.ranges);
+        assert_eq!(a[i..ra.start], b[j..rb.start]);
+        i = ra.end;
+        j = rb.end;
+    }
+    assert_eq!(a[i..], b[j..]);
+}
+
+#[test]
+fn test_diff_cids() {
+    let items: Vec<u64> = (0..100_000).collect();
+    let v1 = Vector::from_slice(&items, Some(1024));
+    let mut v2 = v1.clone();
+    v2.update(50_000, 0).unwrap();
+    let mut v3 = v2.clone();
+    (0..1000).for_each(|i| v3.insert(v3.len(), 100_000 + i).unwrap());
+    let mut v4 = v1.clone();
+    v4.insert(20_000, 0).unwrap();
+    v4.remove(80_000).unwrap();
+
+    let mut store = CountStore::default();
+    let vs = [&v1, &v2, &v3, &v4];
+    let roots: Vec<Cid> = vs.iter().map(|v| v.save(&mut store).unwrap()).collect();
+    let n_blocks = store.store.len();
+
+    assert_eq!(
+        diff_cids(&roots[0], &roots[0], &store).unwrap(),
+        DagDiff::default()
+    );
+    assert_eq!(store.gets.get(), 0);
+
+    for (x, y) in [(0, 1), (1, 0), (1, 2), (0, 3), (3, 2)].iter() {
+        let (a, b) = (roots[*x], roots[*y]);
+        let (va, vb) = (
+            Vec::<u64>::from(vs[*x].clone()),
+            Vec::<u64>::from(vs[*y].clone()),
+        );
+        store.gets.set(0);
+        let diff = diff_cids(&a, &b, &store).unwrap();
+        assert!(!diff.ranges.is_empty());
+        check_diff(&diff, &va, &vb);
+
+        // blocks unique to each side, without reading the common blocks.
+        let (ra, rb) = (
+            reachable(&[a], &store.store).unwrap(),
+            reachable(&[b], &store.store).unwrap(),
+        );
+        let only_a: HashSet<Cid> = ra.difference(&rb).copied().collect();
+        let only_b: HashSet<Cid> = rb.difference(&ra).copied().collect();
+        assert_eq!(
+            diff.only_a.iter().copied().collect::<HashSet<Cid>>(),
+            only_a
+        );
+        assert_eq!(
+            diff.only_b.iter().copied().collect::<HashSet<Cid>>(),
+            only_b
+        );
+        assert_eq!(diff.only_a[0], a);
+        assert_eq!(diff.only_b[0], b);
+        let n = store.gets.get();
+        assert!(n * 4 < n_blocks, "{} {}", n, n_blocks);
+    }
+
+    // single update, changes a single leaf node.
+    let diff = diff_cids(&roots[0], &roots[1], &store).unwrap();
+    assert_eq!(diff.ranges.len(), 1);
+    let (ra, rb) = &diff.ranges[0];
+    assert!(ra.contains(&50_000) && ra == rb, "{:?}", diff.ranges);
+    assert!(ra.len() <= 1024, "{:?}", ra);
+
+    let missing = Block::new(Codec::DagCbor, vec![0xa0]).cid;
+    assert!(matches!(
+        diff_cids(&roots[0], &missing, &store),
+        Err(Error::NotFound { .. })
+    ));
+}
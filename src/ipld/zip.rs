@@ -0,0 +1,127 @@
+//! Blockstore compressing blocks at rest, using lz4.
+
+use std::convert::TryInto;
+
+use super::{Block, Blockstore, Cid};
+use crate::{Error, Result};
+
+// codec byte, heading each block at rest, block is as is.
+const NONE: u8 = 0x00;
+// codec byte, heading each block at rest, block is lz4 compressed.
+const LZ4: u8 = 0x01;
+// lz4 decompresses no more than 255 bytes for a byte of input.
+const MAX_RATIO: usize = 255;
+
+/// Compressing decorator over a blockstore, compressing blocks on `put`
+/// and decompressing them on `get`. Leaf nodes of numeric items, like
+/// time series, often compress several times over, while interior nodes,
+/// a list of digests, do not compress.
+///
+/// Each block at rest is headed by a codec byte, `0x00` for a block
+/// stored as is, and `0x01` for a block compressed using lz4, and a block
+/// is compressed only when that saves space. Blocks are read as per their
+/// codec byte, hence stores can switch codecs without rewriting blocks
+/// already put. Blocks are still put under their CID, the digest of their
+/// plain data.
+///
+/// To compress and encrypt blocks, wrap the encrypting store with this
+/// store, compressed data does not compress any further once encrypted,
+/// refer `CryptStore`.
+pub struct ZipStore<S> {
+    store: S,
+}
+
+impl<S> ZipStore<S>
+where
+    S: Blockstore,
+{
+    /// Create a compressing store over `store`.
+    pub fn new(store: S) -> ZipStore<S> {
+        ZipStore { store }
+    }
+
+    /// Return a reference to the inner store.
+    pub fn as_inner(&self) -> &S {
+        &self.store
+    }
+
+    /// Return the inner store.
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<S> Blockstore for ZipStore<S>
+where
+    S: Blockstore,
+{
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>> {
+        match self.store.get(cid)? {
+            Some(data) => Ok(Some(unzip(cid, &data)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, block: Block) -> Result<Cid> {
+        let data = zip(&block.data);
+        self.store.put(Block {
+            cid: block.cid,
+            data,
+        })
+    }
+
+    fn prefetch(&self, cids: &[Cid]) {
+        self.store.prefetch(cids)
+    }
+
+    fn pin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.pin(root, cids)
+    }
+
+    fn unpin(&mut self, root: &Cid, cids: &[Cid]) -> Result<()> {
+        self.store.unpin(root, cids)
+    }
+}
+
+fn zip(data: &[u8]) -> Vec<u8> {
+    let mut buf = vec![LZ4];
+    buf.extend_from_slice(&lz4_flex::compress_prepend_size(data));
+    if buf.len() > data.len() {
+        buf.clear();
+        buf.push(NONE);
+        buf.extend_from_slice(data);
+    }
+    buf
+}
+
+fn unzip(cid: &Cid, data: &[u8]) -> Result<Vec<u8>> {
+    match data.split_first() {
+        Some((&NONE, data)) => Ok(data.to_vec()),
+        Some((&LZ4, data)) => {
+            // size of the block, prepended to the compressed data.
+            let n = match data.get(..4) {
+                Some(n) => u32::from_le_bytes(n.try_into().unwrap()) as usize,
+                None => Err(corrupted(cid, "lz4 size truncated"))?,
+            };
+            if n > data.len().saturating_mul(MAX_RATIO) {
+                let msg = format!("lz4 size {} from {} bytes", n, data.len());
+                Err(corrupted(cid, &msg))?
+            }
+            match lz4_flex::decompress_size_prepended(data) {
+                Ok(data) => Ok(data),
+                Err(err) => Err(corrupted(cid, &format!("lz4 {}", err))),
+            }
+        }
+        Some((codec, _)) => Err(corrupted(cid, &format!("codec byte {:#x}", codec))),
+        None => Err(corrupted(cid, "without codec byte")),
+    }
+}
+
+fn corrupted(cid: &Cid, msg: &str) -> Error {
+    let detail = format!("block {}, {}", cid, msg);
+    Error::Corrupted { detail }
+}
+
+#[cfg(test)]
+#[path = "zip_test.rs"]
+mod zip_test;
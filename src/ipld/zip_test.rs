@@ -0,0 +1,81 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::{
+    ipld::{Codec, MemoryStore},
+    rc::Vector,
+};
+
+#[test]
+fn test_zip_store() {
+    let seed: u64 = random();
+    println!("test_zip_store seed {}", seed);
+    let mut rng = SmallRng::seed_from_u64(seed);
+
+    // time series, compressing well, and random items, that do not.
+    let series: Vec<u64> = (0..10_000).map(|i| 1_600_000_000 + i / 4).collect();
+    let noise: Vec<u64> = (0..10_000).map(|_| rng.gen()).collect();
+    for items in [series, noise].iter() {
+        let arr = Vector::from_slice(items, Some(1024));
+        let mut store = ZipStore::new(MemoryStore::new());
+        let root = arr.save(&mut store).unwrap();
+        let val: Vector<u64> = Vector::load(&root, &store).unwrap();
+        assert_eq!(&Vec::<u64>::from(val), items);
+
+        let mut plain = MemoryStore::new();
+        arr.save(&mut plain).unwrap();
+        assert_eq!(store.as_inner().len(), plain.len());
+        for cid in plain.cids() {
+            let data = plain.get(cid).unwrap().unwrap();
+            let zipped = store.as_inner().get(cid).unwrap().unwrap();
+            assert!(zipped.len() <= data.len() + 1);
+            assert_eq!(store.get(cid).unwrap(), Some(data));
+        }
+        match items[0] {
+            1_600_000_000 => assert!(store.as_inner().size() * 2 < plain.size()),
+            _ => assert!(store.as_inner().size() <= plain.size() + plain.len()),
+        }
+    }
+}
+
+#[test]
+fn test_zip_codec() {
+    let mut store = ZipStore::new(MemoryStore::new());
+    let (a, b) = (
+        Block::new(Codec::DagCbor, vec![0x01]),
+        Block::new(Codec::DagCbor, vec![0x80; 1000]),
+    );
+    store.put(a.clone()).unwrap();
+    store.put(b.clone()).unwrap();
+    assert_eq!(
+        store.as_inner().get(&a.cid).unwrap(),
+        Some(vec![NONE, 0x01])
+    );
+    assert_eq!(store.as_inner().get(&b.cid).unwrap().unwrap()[0], LZ4);
+    assert_eq!(store.get(&a.cid).unwrap(), Some(a.data.clone()));
+    assert_eq!(store.get(&b.cid).unwrap(), Some(b.data.clone()));
+
+    // bad codec byte, missing codec byte, truncated, and bogus sizes.
+    let cases: Vec<Vec<u8>> = vec![
+        vec![0x02, 0x01],
+        vec![],
+        vec![LZ4, 0x10, 0x00],
+        vec![LZ4, 0xff, 0xff, 0xff, 0x7f, 0x00],
+        vec![LZ4, 0x10, 0x00, 0x00, 0x00, 0xff, 0xff],
+    ];
+    for data in cases.into_iter() {
+        let mut inner = MemoryStore::new();
+        inner
+            .put(Block {
+                cid: a.cid,
+                data: data.clone(),
+            })
+            .unwrap();
+        let store = ZipStore::new(inner);
+        assert!(
+            matches!(store.get(&a.cid), Err(Error::Corrupted { .. })),
+            "{:?}",
+            data
+        );
+    }
+}
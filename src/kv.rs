@@ -0,0 +1,286 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+};
+
+use super::{measured::MeasuredVector, Vector};
+use crate::{Measure, Result};
+
+// bits per key, when a filter is built, a filter is rebuilt once it holds
+// twice as many keys as it was built for.
+const BITS_PER_KEY: usize = 10;
+// number of bits probed per key.
+const N_PROBES: u64 = 3;
+// leaf node size, in bytes, for vectors holding the filter bits, small so
+// that copy-on-write of a filter copies a few words instead of all.
+const BLOOM_LEAF_SIZE: usize = 256;
+
+// bloom filter over keys under a node, sized to the number of keys when
+// built. Bits are held in a persistent vector, so that copying a node on
+// write shares its filter, and setting a bit copies O(log n) words.
+#[derive(Clone, Default)]
+pub(crate) struct Bloom {
+    // keys added, and keys removed, since the filter was built. Bits of
+    // removed keys are left set.
+    n_keys: usize,
+    n_stale: usize,
+    // filter combined from other filters, that may contain any key.
+    any: bool,
+    // None for a filter without keys.
+    words: Option<Vector<u64>>,
+}
+
+impl Bloom {
+    pub(crate) fn hash_key<K: Hash>(key: &K) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // build a filter over keys of `hashes`.
+    pub(crate) fn build<I>(hashes: I) -> Bloom
+    where
+        I: Iterator<Item = u64>,
+    {
+        let hashes: Vec<u64> = hashes.collect();
+        if hashes.is_empty() {
+            return Bloom::default();
+        }
+
+        let n_bits = std::cmp::max((hashes.len() * BITS_PER_KEY).next_power_of_two(), 64);
+        let mut words = vec![0_u64; n_bits / 64];
+        for h in hashes.iter() {
+            for bit in Bloom::probes(*h, n_bits) {
+                words[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Bloom {
+            n_keys: hashes.len(),
+            n_stale: 0,
+            any: false,
+            words: Some(Vector::from_slice(&words, Some(BLOOM_LEAF_SIZE))),
+        }
+    }
+
+    // bits probed for key of hash `h`, in a filter of `n_bits`, a power
+    // of two, by double hashing.
+    fn probes(h: u64, n_bits: usize) -> impl Iterator<Item = usize> {
+        let step = (h >> 32) | 1;
+        let mask = (n_bits - 1) as u64;
+        (0..N_PROBES).map(move |i| (h.wrapping_add(i.wrapping_mul(step)) & mask) as usize)
+    }
+
+    pub(crate) fn may_contain(&self, h: u64) -> bool {
+        match &self.words {
+            _ if self.any => true,
+            Some(words) => Bloom::probes(h, words.len() * 64).all(|bit| {
+                let word = words.get(bit / 64).copied().unwrap_or(0);
+                (word & (1 << (bit % 64))) != 0
+            }),
+            None => false,
+        }
+    }
+
+    // return a filter that may contain keys of both filters. Filters of
+    // different sizes can't be merged, hence the result may contain any
+    // key, unless either of them is empty.
+    pub(crate) fn join(&self, other: &Bloom) -> Bloom {
+        match (self.n_keys, other.n_keys) {
+            (_, 0) if !other.any => self.clone(),
+            (0, _) if !self.any => other.clone(),
+            (_, _) => Bloom {
+                any: true,
+                ..Bloom::default()
+            },
+        }
+    }
+
+    // update the filter for key of hash `old` removed and key of hash `new`
+    // added. Return false if the filter is due for a rebuild, because it is
+    // too full or it holds too many removed keys.
+    pub(crate) fn replace(&mut self, old: Option<u64>, new: Option<u64>) -> bool {
+        match (old, new) {
+            _ if self.any => false,
+            (old, new) if old == new => true,
+            (None, Some(new)) => self.insert(new),
+            (Some(_), None) => self.remove(),
+            (Some(_), Some(new)) => self.remove() && self.insert(new),
+            (None, None) => true,
+        }
+    }
+
+    fn insert(&mut self, h: u64) -> bool {
+        let words = match &mut self.words {
+            Some(words) => words,
+            None => return false,
+        };
+        let n_bits = words.len() * 64;
+        if (self.n_keys + self.n_stale + 1) * BITS_PER_KEY > n_bits * 2 {
+            return false;
+        }
+        for bit in Bloom::probes(h, n_bits) {
+            let word = words.get(bit / 64).copied().unwrap_or(0);
+            words.update_mut(bit / 64, word | (1 << (bit % 64))).ok();
+        }
+        self.n_keys += 1;
+        true
+    }
+
+    fn remove(&mut self) -> bool {
+        self.n_keys = self.n_keys.saturating_sub(1);
+        self.n_stale += 1;
+        self.n_stale <= self.n_keys
+    }
+}
+
+impl<K, V> Measure<(K, V)> for Bloom
+where
+    K: Hash,
+{
+    fn from_item((key, _): &(K, V)) -> Self {
+        Bloom::build(std::iter::once(Bloom::hash_key(key)))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        self.join(other)
+    }
+
+    fn from_items<'a, I>(items: I) -> Option<Self>
+    where
+        (K, V): 'a,
+        I: Iterator<Item = &'a (K, V)>,
+    {
+        Some(Bloom::build(items.map(|(key, _)| Bloom::hash_key(key))))
+    }
+
+    fn update(&mut self, old: Option<&(K, V)>, new: Option<&(K, V)>) -> bool {
+        let old = old.map(|(key, _)| Bloom::hash_key(key));
+        let new = new.map(|(key, _)| Bloom::hash_key(key));
+        self.replace(old, new)
+    }
+}
+
+/// Persistent sequence of (key, value) pairs, with lookup by key.
+///
+/// Pairs are ordered by position, like an association list, and keys need
+/// not be unique. Pairs are held in a B-tree whose nodes cache a bloom
+/// filter over the keys under them, sized to their number, hence
+/// [KvRope::find_key] skips subtrees that can't hold the key, and visits
+/// O(log n) nodes instead of comparing every key. Filters are updated in
+/// place by edits, and rebuilt from the keys under a node when they fill
+/// up, or when a node is split or merged, amortized O(1) per node. Get,
+/// insert, update and remove by position are O(log n). Cloning is cheap
+/// and shares the tree, edits on a clone copy only the path from the root
+/// to the edited pair.
+#[derive(Clone)]
+pub struct KvRope<K, V> {
+    pairs: MeasuredVector<(K, V), Bloom>,
+}
+
+impl<K, V> Default for KvRope<K, V>
+where
+    K: Hash,
+{
+    fn default() -> Self {
+        KvRope {
+            pairs: MeasuredVector::default(),
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for KvRope<K, V>
+where
+    K: Hash,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        KvRope {
+            pairs: MeasuredVector::from_iter(iter),
+        }
+    }
+}
+
+impl<K, V> KvRope<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Create an empty sequence.
+    pub fn new() -> KvRope<K, V> {
+        KvRope::default()
+    }
+
+    /// Return the number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Return the pair at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<(&K, &V)> {
+        let (key, value) = self.pairs.get(index)?;
+        Ok((key, value))
+    }
+
+    /// Return the position and value of the first pair with `key`, None if
+    /// there is no such pair.
+    pub fn find_key(&self, key: &K) -> Option<(usize, &V)> {
+        let h = Bloom::hash_key(key);
+        let (off, (_, value)) = self
+            .pairs
+            .find_by_measure(|m| m.may_contain(h), |(k, _)| k == key)?;
+        Some((off, value))
+    }
+
+    /// Return whether there is a pair with `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find_key(key).is_some()
+    }
+
+    /// Return an iterator over pairs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.pairs.iter().map(|(key, value)| (key, value))
+    }
+}
+
+impl<K, V> KvRope<K, V>
+where
+    K: Clone + Hash + Eq,
+    V: Clone,
+{
+    /// Insert the pair (`key`, `value`) at `index`, or `IndexOutOfBounds`
+    /// error.
+    pub fn insert(&mut self, index: usize, key: K, value: V) -> Result<()> {
+        self.pairs.insert(index, (key, value))
+    }
+
+    /// Append the pair (`key`, `value`) at the end.
+    pub fn push(&mut self, key: K, value: V) {
+        // insert at the end never fails.
+        self.pairs.insert(self.len(), (key, value)).ok();
+    }
+
+    /// Replace the value of the pair at `index` and return the old value,
+    /// or `IndexOutOfBounds` error.
+    pub fn update(&mut self, index: usize, value: V) -> Result<V> {
+        let key = self.get(index)?.0.clone();
+        let (_, old) = self.pairs.update(index, (key, value))?;
+        Ok(old)
+    }
+
+    /// Remove the pair at `index` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, index: usize) -> Result<(K, V)> {
+        self.pairs.remove(index)
+    }
+}
+
+#[cfg(test)]
+#[path = "kv_test.rs"]
+mod kv_test;
@@ -0,0 +1,138 @@
+use rand::{rngs::SmallRng, Rng};
+
+use super::*;
+use crate::{
+    harness::{check_ops, seeded_rng},
+    Error,
+};
+
+fn validate(arr: &KvRope<u64, u64>, refv: &[(u64, u64)]) {
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.iter().eq(refv.iter().map(|(k, v)| (k, v))));
+}
+
+#[test]
+fn test_kv_ops() {
+    let mut rng = seeded_rng("test_kv_ops");
+
+    let mut refv: Vec<(u64, u64)> =
+        (0..1000).map(|i| (rng.gen::<u64>() % 10_000, i)).collect();
+    let mut arr: KvRope<u64, u64> = refv.iter().cloned().collect();
+
+    type Pairs = Vec<(u64, u64)>;
+    let op = |rng: &mut SmallRng, arr: &mut KvRope<u64, u64>, refv: &mut Pairs, i| {
+        let i = i as u64;
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        let key = rng.gen::<u64>() % 10_000;
+        match rng.gen::<u8>() % 8 {
+            0 | 1 => {
+                arr.insert(off, key, i).unwrap();
+                refv.insert(off, (key, i));
+            }
+            2 => {
+                arr.push(key, i);
+                refv.push((key, i));
+            }
+            3 if off < n => {
+                assert_eq!(arr.update(off, i).unwrap(), refv[off].1);
+                refv[off].1 = i;
+            }
+            4 if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            5 if off < n => {
+                let (k, v) = refv[off];
+                assert_eq!(arr.get(off).unwrap(), (&k, &v));
+            }
+            _ => {
+                // lookup keys present and keys likely absent.
+                let key = match refv.is_empty() {
+                    false if key % 2 == 0 => refv[off % n].0,
+                    _ => key,
+                };
+                let refr = refv.iter().position(|(k, _)| *k == key);
+                let refr = refr.map(|off| (off, &refv[off].1));
+                assert_eq!(arr.find_key(&key), refr);
+                assert_eq!(arr.contains_key(&key), refr.is_some());
+            }
+        }
+    };
+    check_ops(&mut rng, &mut arr, &mut refv, 20_000, op, validate);
+
+    let n = arr.len();
+    assert_eq!(
+        arr.get(n).err(),
+        Some(Error::IndexOutOfBounds { index: n, len: n })
+    );
+    assert!(arr.insert(n + 1, 0, 0).is_err());
+    assert!(arr.update(n, 0).is_err());
+    assert!(arr.remove(n).is_err());
+}
+
+#[test]
+fn test_kv_fields() {
+    // struct like field list, with a repeated key.
+    let mut arr: KvRope<String, u64> = KvRope::new();
+    for (i, name) in ["id", "name", "tags", "name"].iter().enumerate() {
+        arr.push(name.to_string(), i as u64);
+    }
+    assert_eq!(arr.find_key(&"name".to_string()), Some((1, &1)));
+    assert_eq!(arr.find_key(&"size".to_string()), None);
+    arr.remove(1).unwrap();
+    assert_eq!(arr.find_key(&"name".to_string()), Some((2, &3)));
+}
+
+#[test]
+fn test_kv_find_visits() {
+    use std::cell::Cell;
+
+    let mut rng = seeded_rng("test_kv_find_visits");
+
+    // number of nodes visited by find_key, filters of nodes skipped are
+    // not probed, hence the number of probes is the number of visits.
+    let visits = |arr: &KvRope<u64, u64>, key: u64| -> usize {
+        let n = Cell::new(0);
+        let h = Bloom::hash_key(&key);
+        let may_contain = |m: &Bloom| {
+            n.set(n.get() + 1);
+            m.may_contain(h)
+        };
+        let res = arr.pairs.find_by_measure(may_contain, |(k, _)| *k == key);
+        assert_eq!(res.map(|(_, (_, v))| *v), Some(key * 10));
+        n.get()
+    };
+
+    let n: u64 = 100_000;
+    let mut arr: KvRope<u64, u64> = (0..n).map(|k| (k, k * 10)).collect();
+    let total: usize = (0..1000).map(|_| visits(&arr, rng.gen::<u64>() % n)).sum();
+    println!("test_kv_find_visits built {}", total);
+    assert!(total < 1000 * 100, "{}", total);
+
+    // filters are kept in shape by edits, on shared and on owned nodes.
+    let mut keys: Vec<u64> = (0..n).collect();
+    let old = arr.clone();
+    for i in 0..20_000 {
+        let off = rng.gen::<usize>() % keys.len();
+        match i % 3 {
+            0 => arr.update(off, keys[off] * 10).map(|_| ()).unwrap(),
+            1 => {
+                arr.remove(off).unwrap();
+                keys.remove(off);
+            }
+            _ => {
+                let key = n + i;
+                arr.insert(off, key, key * 10).unwrap();
+                keys.insert(off, key);
+            }
+        }
+    }
+    let total: usize = (0..1000)
+        .map(|_| visits(&arr, keys[rng.gen::<usize>() % keys.len()]))
+        .sum();
+    println!("test_kv_find_visits edited {}", total);
+    assert!(total < 1000 * 100, "{}", total);
+
+    let total: usize = (0..1000).map(|_| visits(&old, rng.gen::<u64>() % n)).sum();
+    assert!(total < 1000 * 100, "{}", total);
+}
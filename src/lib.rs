@@ -26,7 +26,31 @@
 //! single-ownership insert_mut, remove_mut, delete_mut gives better
 //! performance because the underlying tree is mutated in-place. To help decide
 //! what method to use when, methods that perform in-place mutation are
-//! suffixed with `_mut`.
+//! suffixed with `_mut`. Nodes shared with other versions are copied on first
+//! touch by the `_mut` methods, after which they are mutated in-place. For a
+//! sequence of such edits, `thaw` a vector into `VectorMut` and `freeze` it
+//! back once done.
+//!
+//! Versions and editing
+//! ====================
+//!
+//! * `SnapshotRing`, retain the last N versions of a vector, retiring the
+//!   oldest version once full.
+//! * `History`, record versions for undo and redo, coalescing rapid edits
+//!   and truncating old versions beyond a memory bound.
+//! * `Snapshots`, named versions, like user facing saved states.
+//! * `shared_fraction`, report how much of its tree a version shares with
+//!   another.
+//! * `PositionMap`, carry positions, like cursors, forward across edits.
+//! * `focus`, edit around a cursor, holding items typed at the cursor in a
+//!   gap buffer, outside the tree, until a `snapshot`.
+//! * `finger_mut`, sequences of nearby reads and updates, like `get(i)`,
+//!   `get(i+1)` and `update(i+2)`, holding the items of the leaf node last
+//!   accessed, without descending the tree again.
+//! * `diff`, compare versions, skipping leaf nodes shared by both, and
+//!   return an edit script that `apply_patch` replays.
+//! * `merge`, combine divergent versions of a common base, applying
+//!   changes that do not overlap and returning the rest as conflicts.
 //!
 //! Thread Safety
 //! =============
@@ -38,6 +62,170 @@
 //! traits are not available for `rc::Vector` type while it is available
 //! for `arc::Vector` type.
 //!
+//! `ppar::Vector` is an alias to `rc::Vector`, enabling the `threadsafe`
+//! feature switches it to `arc::Vector`.
+//!
+//! To share a single mutable array across threads, enable the `shared`
+//! feature and use `arc::SharedVector`. It holds the current version of the
+//! array behind an atomic pointer, readers load a snapshot without blocking,
+//! while writers apply their edits using compare-and-swap. Subscribers of a
+//! shared vector receive the old and new version on every update.
+//!
+//! Text
+//! ====
+//!
+//! `RopeString` is a persistent text, held as UTF-8 chunks in a tree whose
+//! nodes cache the number of bytes, chars and line feeds under them, for
+//! char indexed editing, and for converting between char offsets, byte
+//! offsets and lines in O(log n).
+//!
+//! With the `unicode` feature, `RopeString` iterates over grapheme
+//! clusters, locates cluster boundaries to slice text without cutting
+//! emoji and combining sequences, and measures its display width.
+//!
+//! `RopeReader` and `RopeWriter` adapt byte vectors, `Vector<u8>`, to
+//! `std::io`, reading from a snapshot with `Read`, `BufRead` and `Seek`,
+//! and building a new vector with `Write`. Their async counterparts, from
+//! `futures` or `tokio`, are available as features.
+//!
+//! Bits
+//! ====
+//!
+//! `BitRope` is a persistent sequence of bits, packed into words, whose
+//! tree nodes cache the number of set bits under them, for counting the set
+//! bits within a range in O(log n).
+//!
+//! Sequences
+//! =========
+//!
+//! `SortedRope` is a persistent sorted sequence, whose tree nodes cache the
+//! largest item under them, for inserting, removing and looking up items,
+//! and iterating over a range of items, by comparisons in O(log n).
+//!
+//! `RleRope` is a persistent sequence, run length encoded, taking space in
+//! proportion to the number of runs of equal items, splitting and joining
+//! runs on edits.
+//!
+//! `PersistentDeque` is a double ended queue over a vector, with O(1)
+//! snapshots.
+//!
+//! `MarkedVector` pairs a vector with sticky markers, like editor bookmarks,
+//! whose positions follow the items they are placed at, shifted by inserts
+//! and removes in O(log m), for `m` markers.
+//!
+//! `KvRope` is a persistent sequence of (key, value) pairs, like a list of
+//! struct fields, whose tree nodes cache a bloom filter over the keys under
+//! them, sized to their number, so that looking up a key skips subtrees
+//! that can't hold it, visiting O(log n) nodes.
+//!
+//! `TimeRope` is a persistent event log, of items ordered by timestamp,
+//! whose tree nodes cache the earliest and latest timestamp under them, so
+//! that items within a time range are located in O(log n).
+//!
+//! `ColumnVector` stores records column wise, a vector per field, as laid
+//! out by an implementation of `Columns`, so that scanning a single field
+//! touches only its column. With the `derive` feature, `Columns` can be
+//! derived for structs with named fields.
+//!
+//! `CompressedVector`, with the `lz4` feature, stores items in chunks, where
+//! chunks older than the last few are held compressed, and decompressed on
+//! access through a small cache. `set_compression` configures the policy,
+//! and `footprint` reports the logical size of items against their size
+//! as stored. Suits append-mostly logs, whose items are mostly cold.
+//!
+//! Measured
+//! ========
+//!
+//! `MeasuredVector` is a persistent sequence whose tree nodes cache a user
+//! supplied `Measure` for the items under them, like sums, counts or
+//! extents, which is a reusable engine for the sequences above. Measure of
+//! any range of items, and search by a threshold on the running measure,
+//! are O(log n). `Aggregate` is a ready-made measure over numbers, for
+//! `range_sum`, `range_min` and `range_max` queries, and for `find_by_prefix`
+//! locating the item where the running sum reaches a target, in O(log n).
+//!
+//! Allocation
+//! ==========
+//!
+//! Every copy-on-write edit allocates a fresh path of nodes, from the leaf
+//! node up to the root, using the global allocator, and nodes retired by
+//! dropped versions are handed back to it. There is no node pool within
+//! this package, since `Rc` and `Arc` can allocate only from the global
+//! allocator on stable Rust. For workloads with high churn, applications
+//! can plug in a pooling allocator, like [mimalloc](https://docs.rs/mimalloc)
+//! or [jemallocator](https://docs.rs/jemallocator), using the
+//! `#[global_allocator]` attribute. For single ownership, use the `_mut`
+//! methods, which edit the tree in-place without allocating new nodes.
+//!
+//! Panics
+//! ======
+//!
+//! Read and write paths, that is `get`, `insert`, `update`, `remove` and
+//! their in-place variants, on `Vector`, `Finger` and `FingerMut`, are free
+//! of panics. Indexing and offset arithmetic are checked, and a corrupted
+//! tree is reported as `Error::Corrupted`. In-place edits verify the path
+//! before touching it, hence a corrupted tree is not left half edited. Panics
+//! from the items themselves, like a panicking `Clone`, and from allocation
+//! failures are not covered. The `verify` feature, meant for testing, turns
+//! corruption into debug assertions.
+//!
+//! Cargo features
+//! ==============
+//!
+//! * `threadsafe`, export `arc::Vector` as `ppar::Vector`.
+//! * `shared`, `arc::SharedVector`, a shared head holding the current
+//!   version behind an atomic pointer, using
+//!   [arc-swap](https://docs.rs/arc-swap).
+//! * `rayon`, parallel operations on `arc::Vector`, like `from_slice_par`,
+//!   `rebalance_par`, `par_fold` and `par_reduce`, using
+//!   [rayon](https://docs.rs/rayon).
+//! * `epoch`, `arc::EpochVector`, a shared head that reclaims retired
+//!   versions using [crossbeam-epoch](https://docs.rs/crossbeam-epoch).
+//! * `tracing`, debug level [tracing](https://docs.rs/tracing) spans around
+//!   bulk builds, `split_off`, `append`, `concat_all`, `apply`, `rebalance`
+//!   and `compact`, recording the time taken and the shape of the resulting
+//!   tree.
+//! * `verify`, in debug builds, verify the nodes along the path of every
+//!   insert, update and remove, catching corruption at the edit introducing
+//!   it. Refer `Vector::validate` for verifying the entire tree.
+//! * `model`, model based tests comparing `Vector` against `Vec`, over
+//!   randomized operations.
+//! * `unicode`, grapheme cluster and display width operations on
+//!   `RopeString`, using
+//!   [unicode-segmentation](https://docs.rs/unicode-segmentation) and
+//!   [unicode-width](https://docs.rs/unicode-width).
+//! * `crdt`, `RgaVector`, a replicated sequence that converges across
+//!   replicas exchanging operations, using a measured vector as its local
+//!   storage.
+//! * `ipld`, `Vector::encode`, encoding a vector as a DAG of DAG-CBOR, or
+//!   DAG-JSON, blocks linked by CID, and `Vector::save` and `Vector::load`,
+//!   persisting it into a blockstore, refer the `ipld` module. Blocks are
+//!   addressed using [sha2](https://docs.rs/sha2) or
+//!   [blake3](https://docs.rs/blake3).
+//! * `derive`, `#[derive(Columns)]` for structs with named fields, from the
+//!   companion `ppar-derive` crate.
+//! * `futures`, `AsyncRead`, `AsyncBufRead` and `AsyncSeek` for
+//!   `RopeReader`, and `AsyncWrite` for `RopeWriter`, using
+//!   [futures-io](https://docs.rs/futures-io).
+//! * `tokio`, same as `futures`, using the io traits of
+//!   [tokio](https://docs.rs/tokio).
+//! * `lz4`, `CompressedVector`, a sequence storing cold items compressed,
+//!   and, along with `ipld`, `ipld::ZipStore`, compressing blocks persisted
+//!   into a blockstore, using [lz4_flex](https://docs.rs/lz4_flex).
+//! * `aead`, `ipld::CryptStore`, encrypting blocks persisted into a
+//!   blockstore, using
+//!   [chacha20poly1305](https://docs.rs/chacha20poly1305), implies `ipld`.
+//! * `ed25519`, `ipld::Signer` and `ipld::Verifier` for ed25519 keys, to
+//!   sign and verify the root of a vector, using
+//!   [ed25519-dalek](https://docs.rs/ed25519-dalek), implies `ipld`.
+//! * `gateway`, `ipld::GatewayStore`, fetching blocks from an IPFS HTTP
+//!   gateway, using [ureq](https://docs.rs/ureq), implies `ipld`.
+//!
+//! Without features, there are no dependencies, not even a logging
+//! facade. Diagnostics on the shape of the tree, like depth, leaf
+//! nodes and their fill, are available via `Vector::stats`, and optionally
+//! as tracing spans.
+//!
 //! **Alternate libraries**:
 //!
 //! * _[im](https://github.com/bodil/im-rs)_
@@ -45,71 +233,383 @@
 
 use std::{error, fmt, result};
 
-// Short form to compose Error values.
-//
-// Here are few possible ways:
-//
-// ```ignore
-// use crate::Error;
-// err_at!(Invalid, msg: format!("bad argument"));
-// ```
-//
-// ```ignore
-// use crate::Error;
-// err_at!(Invalid, std::io::read(buf));
-// ```
-//
-// ```ignore
-// use crate::Error;
-// err_at!(Invalid, std::fs::read(file_path), format!("read failed"));
-// ```
-//
-macro_rules! err_at {
-    ($v:ident, msg: $($arg:expr),+) => {{
-        let prefix = format!("{}:{}", file!(), line!());
-        Err(Error::$v(prefix, format!($($arg),+)))
-    }};
-    ($v:ident, $e:expr) => {{
-        match $e {
-            Ok(val) => Ok(val),
-            Err(err) => {
-                let prefix = format!("{}:{}", file!(), line!());
-                Err(Error::$v(prefix, format!("{}", err)))
-            }
+// code generated by `ppar-derive` refers this crate as `::ppar`.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as ppar;
+
+// Evaluate `$op` within a debug level tracing span named `$name`, `$op`
+// shall evaluate to a vector, or a reference to a vector, bound to `$arr`.
+// Time taken by `$op`, and the shape of the resulting tree, are recorded
+// only when the span is enabled, since walking the tree is O(n).
+#[cfg(feature = "tracing")]
+macro_rules! traced {
+    ($name:literal, $arr:ident, $op:expr) => {{
+        use tracing::field::Empty;
+
+        let span = tracing::debug_span!(
+            $name,
+            n_items = Empty,
+            depth = Empty,
+            n_leafs = Empty,
+            elapsed_us = Empty
+        );
+        let start = std::time::Instant::now();
+        let $arr = {
+            let _enter = span.enter();
+            $op
+        };
+        if !span.is_disabled() {
+            let elapsed = start.elapsed().as_micros() as u64;
+            let stats = $arr.stats();
+            span.record("n_items", stats.n_items);
+            span.record("depth", stats.depth);
+            span.record("n_leafs", stats.n_leafs);
+            span.record("elapsed_us", elapsed);
         }
+        $arr
     }};
-    ($v:ident, $e:expr, $($arg:expr),+) => {{
-        match $e {
-            Ok(val) => Ok(val),
-            Err(err) => {
-                let prefix = format!("{}:{}", file!(), line!());
-                let msg = format!($($arg),+);
-                Err(Error::$v(prefix, format!("{} {}", err, msg)));
-            }
-        }
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! traced {
+    ($name:literal, $arr:ident, $op:expr) => {{
+        $op
     }};
 }
 
 pub mod arc;
+#[cfg(feature = "ipld")]
+pub mod ipld;
 pub mod rc;
 
+mod columns;
+#[cfg(feature = "lz4")]
+mod pack;
+mod position;
+
+pub use columns::{ColumnVector, Columns};
+#[cfg(feature = "lz4")]
+pub use pack::{CompressedFootprint, Compression, Pack};
+pub use position::PositionMap;
+#[cfg(feature = "derive")]
+pub use ppar_derive::Columns;
+
+#[cfg(test)]
+mod harness;
+#[cfg(all(test, feature = "model"))]
+mod shrink;
+
+/// Default persistent array, `arc::Vector` with the `threadsafe` feature,
+/// `rc::Vector` otherwise.
+#[cfg(feature = "threadsafe")]
+pub use arc::Vector;
+/// Default persistent array, `arc::Vector` with the `threadsafe` feature,
+/// `rc::Vector` otherwise.
+#[cfg(not(feature = "threadsafe"))]
+pub use rc::Vector;
+
 /// Leaf node shall not exceed this default size.
 ///
 /// Refer `Vector::set_leaf_size` for optimal configuration.
 pub const LEAF_CAP: usize = 10 * 1024; // in bytes.
 
+/// Default number of children for interior nodes.
+///
+/// Refer `Vector::set_fanout` for optimal configuration.
+pub const FANOUT: usize = 2;
+
 /// Threshold on tree depth, beyond which auto-rebalance will kick in.
 pub const REBALANCE_THRESHOLD: usize = 30;
 
+/// Hint on how a vector is accessed, used to pick the leaf node size,
+/// refer `Vector::set_access_profile` for details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessProfile {
+    /// Mix of reads and edits, leaf node size is [LEAF_CAP].
+    Balanced,
+    /// Mostly appends, larger leaf nodes move fewer tail leaf nodes into
+    /// the tree.
+    AppendMostly,
+    /// Mostly sequential reads, larger leaf nodes make a shallower tree
+    /// and iterate with fewer hops.
+    ScanMostly,
+    /// Mostly random inserts, updates and removes, smaller leaf nodes cut
+    /// the cost of copying leaf nodes on write.
+    RandomEdit,
+}
+
+impl AccessProfile {
+    /// Return the leaf node size, in bytes, for this profile.
+    pub fn to_leaf_size(&self) -> usize {
+        match self {
+            AccessProfile::Balanced => LEAF_CAP,
+            AccessProfile::AppendMostly | AccessProfile::ScanMostly => LEAF_CAP * 4,
+            AccessProfile::RandomEdit => LEAF_CAP / 4,
+        }
+    }
+}
+
+/// Shape of the tree backing a vector, refer `Vector::stats` for details.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Number of items in the vector.
+    pub n_items: usize,
+    /// Number of nodes along the longest path from root to a leaf node.
+    pub depth: usize,
+    /// Number of interior nodes.
+    pub n_nodes: usize,
+    /// Number of non-empty leaf nodes, including the tail leaf node, or
+    /// items held inline by small vectors.
+    pub n_leafs: usize,
+    /// Number of items a leaf node can hold.
+    pub leaf_capacity: usize,
+    /// Number of items in the least filled leaf node.
+    pub min_leaf_items: usize,
+    /// Number of items in the most filled leaf node.
+    pub max_leaf_items: usize,
+    /// Average fill factor of leaf nodes, between 0.0 and 1.0.
+    pub fill: f64,
+}
+
+impl Stats {
+    // statistics for a tree of `n_items`, whose leaf nodes can hold
+    // `leaf_capacity` items, before accounting for its nodes.
+    pub(crate) fn new(n_items: usize, leaf_capacity: usize) -> Stats {
+        Stats {
+            n_items,
+            leaf_capacity,
+            min_leaf_items: usize::MAX,
+            ..Stats::default()
+        }
+    }
+
+    // account for a leaf node at `depth`, holding `n` items.
+    pub(crate) fn add_leaf(&mut self, depth: usize, n: usize) {
+        self.depth = std::cmp::max(self.depth, depth);
+        if n > 0 {
+            self.n_leafs += 1;
+            self.min_leaf_items = std::cmp::min(self.min_leaf_items, n);
+            self.max_leaf_items = std::cmp::max(self.max_leaf_items, n);
+        }
+    }
+
+    // account for an interior node at `depth`.
+    pub(crate) fn add_node(&mut self, depth: usize) {
+        self.depth = std::cmp::max(self.depth, depth);
+        self.n_nodes += 1;
+    }
+
+    // compute the fill factor, once all nodes are accounted for.
+    pub(crate) fn finish(mut self) -> Stats {
+        if self.n_leafs == 0 {
+            self.min_leaf_items = 0;
+        } else {
+            let cap = self.n_leafs * self.leaf_capacity;
+            self.fill = (self.n_items as f64) / (cap as f64);
+        }
+        self
+    }
+}
+
+/// Nodes of a vector shared with another version, by pointer identity,
+/// refer `Vector::shared_fraction` for details.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SharingStats {
+    /// Number of interior nodes.
+    pub n_nodes: usize,
+    /// Number of non-empty leaf nodes, including the tail leaf node, or
+    /// items held inline by small vectors.
+    pub n_leafs: usize,
+    /// Memory held by the tree, in bytes.
+    pub bytes: usize,
+    /// Number of interior nodes shared with the other version.
+    pub shared_nodes: usize,
+    /// Number of non-empty leaf nodes shared with the other version.
+    pub shared_leafs: usize,
+    /// Memory held by the nodes shared with the other version, in bytes.
+    pub shared_bytes: usize,
+}
+
+impl SharingStats {
+    /// Return the fraction of memory shared with the other version, between
+    /// 0.0 and 1.0.
+    pub fn fraction(&self) -> f64 {
+        match self.bytes {
+            0 => 0.0,
+            bytes => (self.shared_bytes as f64) / (bytes as f64),
+        }
+    }
+}
+
+/// Edit operation on Vector, refer `Vector::apply` for details.
+///
+/// Offset in each variant refers to the position within the vector, before
+/// applying any of the edits.
+#[derive(Clone, Debug)]
+pub enum Edit<T> {
+    /// Insert value before the element at offset, or at the end of the
+    /// vector if offset is equal to its length.
+    Insert(usize, T),
+    /// Replace the element at offset with value.
+    Update(usize, T),
+    /// Remove the element at offset.
+    Remove(usize),
+}
+
+impl<T> Edit<T> {
+    /// Return the offset of this edit.
+    pub fn to_offset(&self) -> usize {
+        match self {
+            Edit::Insert(off, _) | Edit::Update(off, _) | Edit::Remove(off) => *off,
+        }
+    }
+}
+
+/// Measure cached by every node of a measured vector, for the items under
+/// it, refer `MeasuredVector` for details.
+///
+/// Measures form a monoid, `Default` shall return the identity, and
+/// `combine` shall be associative, though not necessarily commutative.
+/// Like, number of bytes and newlines in a text, or the sum, minimum and
+/// maximum of numbers.
+pub trait Measure<T>: Clone + Default {
+    /// Return the measure of a single item.
+    fn from_item(item: &T) -> Self;
+
+    /// Return the measure of `self` followed by `other`.
+    fn combine(&self, other: &Self) -> Self;
+
+    /// Return the measure of all `items` under a node, or None, the
+    /// default, to combine measures of its child nodes, or of its items
+    /// for leaf nodes. Measures that lose precision when combined, like
+    /// bloom filters sized to the number of items, are built here instead.
+    fn from_items<'a, I>(_items: I) -> Option<Self>
+    where
+        T: 'a,
+        I: Iterator<Item = &'a T>,
+    {
+        None
+    }
+
+    /// Update this measure, of the items under a node, for `old` item
+    /// removed and `new` item added, either being None for inserts and
+    /// removes. Return false, the default, to have the measure recomputed
+    /// instead, refer [Measure::from_items].
+    fn update(&mut self, _old: Option<&T>, _new: Option<&T>) -> bool {
+        false
+    }
+}
+
+/// Aggregate [Measure] over numbers, for range queries on a measured
+/// vector, refer `MeasuredVector::range_sum` for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Aggregate<T> {
+    /// Number of items.
+    pub count: usize,
+    /// Sum of items, `T::default()` is taken as zero.
+    pub sum: T,
+    /// Smallest item, None if there are no items.
+    pub min: Option<T>,
+    /// Largest item, None if there are no items.
+    pub max: Option<T>,
+}
+
+impl<T> Measure<T> for Aggregate<T>
+where
+    T: Copy + Default + PartialOrd + std::ops::Add<Output = T>,
+{
+    fn from_item(item: &T) -> Self {
+        Aggregate {
+            count: 1,
+            sum: *item,
+            min: Some(*item),
+            max: Some(*item),
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        let pick = |x: Option<T>, y: Option<T>, less: bool| match (x, y) {
+            (Some(x), Some(y)) if (y < x) == less => Some(y),
+            (Some(x), _) => Some(x),
+            (None, y) => y,
+        };
+        Aggregate {
+            count: self.count + other.count,
+            sum: self.sum + other.sum,
+            min: pick(self.min, other.min, true),
+            max: pick(self.max, other.max, false),
+        }
+    }
+}
+
+/// Item carrying a timestamp, refer `TimeRope` for details.
+pub trait Timestamped {
+    /// Type of timestamp, like seconds or nanoseconds since epoch.
+    type Time: Clone + Ord;
+
+    /// Return the timestamp of this item.
+    fn to_timestamp(&self) -> Self::Time;
+}
+
+/// Unique identity of an operation on a replicated sequence, refer
+/// `RgaVector` for details.
+///
+/// Ordered by the Lamport `counter`, and ties broken by `replica`, giving a
+/// total order agreed upon by all replicas.
+#[cfg(feature = "crdt")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OpId {
+    /// Lamport timestamp of the operation.
+    pub counter: u64,
+    /// Replica that generated the operation.
+    pub replica: u64,
+}
+
+/// Operation on a replicated sequence, refer `RgaVector` for details.
+#[cfg(feature = "crdt")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Op<T> {
+    /// Identity of this operation, also the identity of the inserted item.
+    pub id: OpId,
+    /// Sequence number of this operation, among the operations generated by
+    /// its replica, starting from 1.
+    pub seq: u64,
+    /// Insert or remove.
+    pub kind: OpKind<T>,
+}
+
+/// Kind of operation on a replicated sequence.
+#[cfg(feature = "crdt")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OpKind<T> {
+    /// Insert `value` right after the item identified by `origin`, or at the
+    /// start of the sequence if None.
+    Insert { origin: Option<OpId>, value: T },
+    /// Remove the item identified by `target`.
+    Remove { target: OpId },
+}
+
 /// Type alias for Result return type, used by this package.
 pub type Result<T> = result::Result<T, Error>;
 
 /// Error variants that can be returned by this package's API.
-///
-/// Each variant carries a prefix, typically identifying the
-/// error location.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Error {
-    IndexFail(String, String),
+    /// Index is out of bounds, for a vector holding `len` elements.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Element at `index` is updated or removed more than once, within a
+    /// batch of edits.
+    DuplicateEdit { index: usize },
+    /// Item inserted at `index` breaks the order of the sequence, like a
+    /// timestamp older than the one before it, or an edit at `index` within
+    /// a patch is out of order.
+    OutOfOrder { index: usize },
+    /// Tree invariants are violated, refer `Vector::validate`.
+    Corrupted { detail: String },
+    /// Block is missing, like a CID that is not in a blockstore.
+    NotFound { detail: String },
+    /// Reading from, or writing to, an underlying io stream failed.
+    IOError { detail: String },
 }
 
 impl fmt::Display for Error {
@@ -117,15 +617,18 @@ impl fmt::Display for Error {
         use Error::*;
 
         match self {
-            IndexFail(p, msg) => write!(f, "{} IndexFail: {}", p, msg),
+            IndexOutOfBounds { index, len } => {
+                write!(f, "IndexOutOfBounds: index {} for length {}", index, len)
+            }
+            DuplicateEdit { index } => {
+                write!(f, "DuplicateEdit: index {} edited more than once", index)
+            }
+            OutOfOrder { index } => write!(f, "OutOfOrder: item at index {}", index),
+            Corrupted { detail } => write!(f, "Corrupted: {}", detail),
+            NotFound { detail } => write!(f, "NotFound: {}", detail),
+            IOError { detail } => write!(f, "IOError: {}", detail),
         }
     }
 }
 
-impl fmt::Debug for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
-        write!(f, "{}", self)
-    }
-}
-
 impl error::Error for Error {}
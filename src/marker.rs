@@ -0,0 +1,226 @@
+use std::ops::Range;
+
+use super::measured::MeasuredVector;
+use super::*;
+use crate::{Error, Measure, Result};
+
+/// Identifies a marker within [MarkedVector], refer
+/// [MarkedVector::add_marker].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct MarkerId(u64);
+
+// marker, positioned at `delta` items after the previous marker.
+#[derive(Clone)]
+struct Mark {
+    id: MarkerId,
+    delta: usize,
+}
+
+// measure of markers, the position of the last marker.
+#[derive(Clone, Copy, Default)]
+struct Offset {
+    pos: usize,
+}
+
+impl Measure<Mark> for Offset {
+    fn from_item(mark: &Mark) -> Self {
+        Offset { pos: mark.delta }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Offset {
+            pos: self.pos + other.pos,
+        }
+    }
+}
+
+/// Vector with sticky markers, like editor bookmarks and anchors of syntax
+/// regions, whose positions follow the items they are placed at.
+///
+/// Inserting or removing an item before a marker shifts it, and inserting
+/// at a marker's position pushes the marker along with the item it is
+/// placed at. Removing the item under a marker leaves the marker at the
+/// next item. Markers are held in a B-tree, as offsets from the previous
+/// marker, hence shifting markers on an edit is O(log m), for `m` markers,
+/// and cloning shares both the vector and the markers, every version
+/// carries its own marker positions.
+#[derive(Clone)]
+pub struct MarkedVector<T> {
+    arr: Vector<T>,
+    marks: MeasuredVector<Mark, Offset>,
+    next_id: u64,
+}
+
+impl<T> From<Vector<T>> for MarkedVector<T> {
+    fn from(arr: Vector<T>) -> MarkedVector<T> {
+        MarkedVector::new(arr)
+    }
+}
+
+impl<T> MarkedVector<T> {
+    /// Create a marked vector over `arr`, without markers.
+    pub fn new(arr: Vector<T>) -> MarkedVector<T> {
+        MarkedVector {
+            arr,
+            marks: MeasuredVector::default(),
+            next_id: 0,
+        }
+    }
+
+    /// Return the underlying vector.
+    pub fn as_vector(&self) -> &Vector<T> {
+        &self.arr
+    }
+
+    /// Return the underlying vector, dropping the markers.
+    pub fn into_vector(self) -> Vector<T> {
+        self.arr
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.arr.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.arr.len() == 0
+    }
+
+    /// Return the number of markers.
+    pub fn n_markers(&self) -> usize {
+        self.marks.len()
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        self.arr.get(index)
+    }
+
+    /// Return the position of marker `id`, None if there is no such
+    /// marker. This is O(m), for `m` markers.
+    pub fn marker(&self, id: MarkerId) -> Option<usize> {
+        self.markers().find(|(x, _)| *x == id).map(|(_, pos)| pos)
+    }
+
+    /// Return an iterator over markers, as (id, position), ordered by
+    /// position.
+    pub fn markers(&self) -> impl Iterator<Item = (MarkerId, usize)> + '_ {
+        self.iter_marks(0, 0)
+    }
+
+    /// Return an iterator over markers positioned within `range`, as
+    /// (id, position), ordered by position.
+    pub fn markers_in(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (MarkerId, usize)> + '_ {
+        let (k, before) = match self.marks.search_by_measure(|m| m.pos >= range.start) {
+            Some((k, before)) => (k, before.pos),
+            None => (self.marks.len(), 0),
+        };
+        self.iter_marks(k, before)
+            .take_while(move |(_, pos)| *pos < range.end)
+    }
+}
+
+impl<T> MarkedVector<T>
+where
+    T: Clone,
+{
+    /// Add a marker at position `pos`, after markers already at `pos`, and
+    /// return its id, or `IndexOutOfBounds` error. Position equal to the
+    /// length of the vector places the marker at the end.
+    pub fn add_marker(&mut self, pos: usize) -> Result<MarkerId> {
+        let n = self.arr.len();
+        if pos > n {
+            Err(Error::IndexOutOfBounds { index: pos, len: n })?
+        }
+
+        let id = MarkerId(self.next_id);
+        self.next_id += 1;
+        match self.marks.search_by_measure(|m| m.pos > pos) {
+            Some((k, before)) => {
+                let delta = pos - before.pos;
+                self.marks.insert(k, Mark { id, delta })?;
+                self.shift(k + 1, |x| x - delta)?;
+            }
+            None => {
+                let delta = pos - self.marks.total().pos;
+                self.marks.insert(self.marks.len(), Mark { id, delta })?;
+            }
+        }
+        Ok(id)
+    }
+
+    /// Remove marker `id` and return its position, None if there is no
+    /// such marker. This is O(m), for `m` markers.
+    pub fn remove_marker(&mut self, id: MarkerId) -> Option<usize> {
+        let k = self.marks.iter().position(|mark| mark.id == id)?;
+        let pos = self.marks.measure(..=k).ok()?.pos;
+        let mark = self.marks.remove(k).ok()?;
+        // offset of the next marker absorbs the removed one.
+        self.shift(k, |x| x + mark.delta).ok()?;
+        Some(pos)
+    }
+
+    /// Insert `value` at `off`, shifting markers at and after `off`, or
+    /// `IndexOutOfBounds` error.
+    pub fn insert(&mut self, off: usize, value: T) -> Result<()> {
+        self.arr.insert(off, value)?;
+        if let Some((k, _)) = self.marks.search_by_measure(|m| m.pos >= off) {
+            self.shift(k, |x| x + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Replace the item at `off` with `value` and return the old item, or
+    /// `IndexOutOfBounds` error. Markers are left as is.
+    pub fn update(&mut self, off: usize, value: T) -> Result<T> {
+        self.arr.update(off, value)
+    }
+
+    /// Remove the item at `off` and return it, shifting markers after
+    /// `off`, or `IndexOutOfBounds` error.
+    pub fn remove(&mut self, off: usize) -> Result<T> {
+        let value = self.arr.remove(off)?;
+        if let Some((k, _)) = self.marks.search_by_measure(|m| m.pos > off) {
+            self.shift(k, |x| x - 1)?;
+        }
+        Ok(value)
+    }
+
+    // adjust the offset of `k`-th marker, if any, shifting it along with
+    // all markers after it.
+    fn shift<F>(&mut self, k: usize, f: F) -> Result<()>
+    where
+        F: FnOnce(usize) -> usize,
+    {
+        if let Ok(mark) = self.marks.get(k) {
+            let mark = Mark {
+                id: mark.id,
+                delta: f(mark.delta),
+            };
+            self.marks.update(k, mark)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> MarkedVector<T> {
+    // iterate over markers from the `k`-th marker, positioned after `pos`.
+    fn iter_marks(
+        &self,
+        k: usize,
+        pos: usize,
+    ) -> impl Iterator<Item = (MarkerId, usize)> + '_ {
+        self.marks.iter_from(k).scan(pos, |pos, mark| {
+            *pos += mark.delta;
+            Some((mark.id, *pos))
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "marker_test.rs"]
+mod marker_test;
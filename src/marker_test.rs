@@ -0,0 +1,110 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+
+// reference markers, as (id, position), ordered by position and by the
+// order in which they were placed at that position.
+fn validate(arr: &MarkedVector<u64>, refv: &[u64], refm: &[(MarkerId, usize)]) {
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.as_vector().iter().eq(refv.iter()));
+    assert_eq!(arr.n_markers(), refm.len());
+    assert_eq!(arr.markers().collect::<Vec<(MarkerId, usize)>>(), refm);
+}
+
+#[test]
+fn test_marker_ops() {
+    let seed: u128 = random();
+    println!("test_marker_ops seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut refv: Vec<u64> = (0..1000).collect();
+    let mut arr = MarkedVector::new(Vector::from_slice(&refv, Some(128)));
+    let mut refm: Vec<(MarkerId, usize)> = vec![];
+    let mut versions = vec![];
+
+    for i in 0..20_000 {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        match rng.gen::<u8>() % 10 {
+            0 | 1 => {
+                let id = arr.add_marker(off).unwrap();
+                let k = refm
+                    .iter()
+                    .position(|(_, p)| *p > off)
+                    .unwrap_or(refm.len());
+                refm.insert(k, (id, off));
+            }
+            2 if !refm.is_empty() => {
+                let (id, pos) = refm.remove(rng.gen::<usize>() % refm.len());
+                assert_eq!(arr.remove_marker(id), Some(pos));
+                assert_eq!(arr.remove_marker(id), None);
+            }
+            3..=5 => {
+                arr.insert(off, i).unwrap();
+                refv.insert(off, i);
+                refm.iter_mut()
+                    .filter(|(_, p)| *p >= off)
+                    .for_each(|(_, p)| *p += 1);
+            }
+            6..=8 if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+                refm.iter_mut()
+                    .filter(|(_, p)| *p > off)
+                    .for_each(|(_, p)| *p -= 1);
+            }
+            _ if !refm.is_empty() => {
+                let (id, pos) = refm[rng.gen::<usize>() % refm.len()];
+                assert_eq!(arr.marker(id), Some(pos));
+
+                let (a, b) = (off, off + rng.gen::<usize>() % 100);
+                let refs: Vec<(MarkerId, usize)> = refm
+                    .iter()
+                    .filter(|(_, p)| (a..b).contains(p))
+                    .cloned()
+                    .collect();
+                assert_eq!(
+                    arr.markers_in(a..b).collect::<Vec<(MarkerId, usize)>>(),
+                    refs
+                );
+            }
+            _ => (),
+        }
+        if i % 2000 == 0 {
+            validate(&arr, &refv, &refm);
+            versions.push((arr.clone(), refv.clone(), refm.clone()));
+        }
+    }
+    validate(&arr, &refv, &refm);
+
+    // older versions carry their own markers.
+    for (arr, refv, refm) in versions.iter() {
+        validate(arr, refv, refm);
+    }
+}
+
+#[test]
+fn test_marker_sticky() {
+    let mut arr: MarkedVector<char> = Vector::from_slice(&['a', 'b', 'c'], None).into();
+    let b = arr.add_marker(1).unwrap();
+    let end = arr.add_marker(3).unwrap();
+    assert!(arr.add_marker(4).is_err());
+
+    // insert at the marker pushes it along with 'b'.
+    arr.insert(1, 'x').unwrap();
+    assert_eq!(arr.marker(b), Some(2));
+    assert_eq!(arr.get(2), Ok(&'b'));
+    // update leaves markers as is.
+    arr.update(0, 'y').unwrap();
+    assert_eq!(arr.marker(b), Some(2));
+    // removing the item under the marker leaves it at the next item.
+    arr.remove(2).unwrap();
+    assert_eq!(arr.marker(b), Some(2));
+    assert_eq!(arr.get(2), Ok(&'c'));
+    assert_eq!(arr.marker(end), Some(3));
+    arr.insert(3, 'z').unwrap();
+    assert_eq!(arr.marker(end), Some(4));
+    assert_eq!(arr.markers_in(0..4).count(), 1);
+
+    let arr = arr.into_vector();
+    assert_eq!(arr.len(), 4);
+}
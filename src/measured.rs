@@ -0,0 +1,913 @@
+//! Persistent B-tree, where every node caches the number of items and the
+//! measure of items under it, so that a sequence can be addressed by its
+//! measure, like bytes or chars in a text, in O(log n).
+//!
+//! Unlike [Vector], all leaf nodes are at the same depth, and nodes are
+//! split when full and merged when underfull, hence no rebalancing is
+//! needed. Edits copy the path from root to the leaf node, unless the path
+//! is exclusively owned, in which case nodes are mutated in-place.
+//!
+//! Measures are kept in a tree of their own, instead of on the nodes of
+//! [Vector], for following reasons,
+//!
+//! * a measure is a type parameter of the node, `Vector<T>` would become
+//!   `Vector<T, M>` for every user, and nodes of a plain vector would pay
+//!   for a measure they do not use, which is a cost on the common case.
+//! * `Vector` trees are unbalanced by design, cheap `append` and
+//!   `split_off` link subtrees as they are, and are rebalanced on demand.
+//!   With measures, every edit re-combines the measures along its path,
+//!   whose cost is bound by the depth only when the tree stays balanced.
+//!
+//! Statistics, validation, and the rule for underfull nodes, are shared
+//! with [Vector], refer [Vector::stats] and [Vector::validate].
+
+use std::{
+    iter::FromIterator,
+    mem,
+    ops::{Bound, RangeBounds},
+    slice,
+};
+
+use super::*;
+use crate::{Aggregate, Error, Measure, Result};
+
+// maximum number of items in a leaf node.
+const MAX_ITEMS: usize = 64;
+// maximum number of children in an interior node.
+const MAX_CHILDREN: usize = 16;
+
+/// Persistent sequence, indexed by offset and by a user supplied
+/// [Measure].
+///
+/// Every node caches the measure of items under it, maintained through
+/// insert, update and remove. Hence the measure of any range of items, and
+/// the item at which the running measure crosses a threshold, are computed
+/// in O(log n). Cloning is cheap and shares the tree, edits on a clone copy
+/// only the path from the root to the edited leaf node.
+pub struct MeasuredVector<T, M> {
+    root: Ref<Node<T, M>>,
+}
+
+impl<T, M> Clone for MeasuredVector<T, M> {
+    fn clone(&self) -> Self {
+        MeasuredVector {
+            root: Ref::clone(&self.root),
+        }
+    }
+}
+
+impl<T, M> Default for MeasuredVector<T, M>
+where
+    M: Measure<T>,
+{
+    fn default() -> Self {
+        MeasuredVector {
+            root: Ref::new(Node::new_leaf(vec![])),
+        }
+    }
+}
+
+impl<T, M> FromIterator<T> for MeasuredVector<T, M>
+where
+    M: Measure<T>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut leafs = vec![];
+        let mut data = Vec::with_capacity(MAX_ITEMS);
+        for item in iter {
+            data.push(item);
+            if data.len() == MAX_ITEMS {
+                leafs.push(mem::replace(&mut data, Vec::with_capacity(MAX_ITEMS)));
+            }
+        }
+        if !data.is_empty() || leafs.is_empty() {
+            leafs.push(data);
+        }
+        even_tail(&mut leafs, MAX_ITEMS);
+
+        let mut nodes: Vec<Ref<Node<T, M>>> = leafs
+            .into_iter()
+            .map(|data| Ref::new(Node::new_leaf(data)))
+            .collect();
+        while nodes.len() > 1 {
+            let mut level: Vec<Vec<Ref<Node<T, M>>>> =
+                nodes.chunks(MAX_CHILDREN).map(|x| x.to_vec()).collect();
+            even_tail(&mut level, MAX_CHILDREN);
+            nodes = level
+                .into_iter()
+                .map(|x| Ref::new(Node::new_branch(x)))
+                .collect();
+        }
+        MeasuredVector {
+            root: nodes.pop().unwrap(),
+        }
+    }
+}
+
+impl<T, M> MeasuredVector<T, M>
+where
+    M: Measure<T>,
+{
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.root.len
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.len == 0
+    }
+
+    /// Return the measure of all items.
+    pub fn total(&self) -> &M {
+        &self.root.measure
+    }
+
+    /// Return the measure of items within `range`, or `IndexOutOfBounds`
+    /// error.
+    pub fn measure<R>(&self, range: R) -> Result<M>
+    where
+        R: RangeBounds<usize>,
+    {
+        let n = self.len();
+        match self.to_offsets(range)? {
+            (start, end) if start == end => Ok(M::default()),
+            (0, end) if end == n => Ok(self.root.measure.clone()),
+            (start, end) => Ok(self.root.measure_range(start, end)),
+        }
+    }
+
+    /// Return the item at `off`, or `IndexOutOfBounds` error.
+    pub fn get(&self, off: usize) -> Result<&T> {
+        match self.root.get(off) {
+            Some(item) => Ok(item),
+            None => Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            }),
+        }
+    }
+
+    /// Search for the first item, at which the measure of items from the
+    /// start, up to and including the item, satisfies `pred`, and return
+    /// its offset along with the measure of items before it. `pred` shall be
+    /// monotonic, that is, once true for a prefix, it stays true for longer
+    /// prefixes.
+    pub fn search_by_measure<F>(&self, mut pred: F) -> Option<(usize, M)>
+    where
+        F: FnMut(&M) -> bool,
+    {
+        let (mut node, mut acc, mut off) = (self.root.as_ref(), M::default(), 0);
+        'outer: loop {
+            match &node.kind {
+                Kind::Leaf(data) => {
+                    for item in data.iter() {
+                        let next = acc.combine(&M::from_item(item));
+                        if pred(&next) {
+                            break 'outer Some((off, acc));
+                        }
+                        acc = next;
+                        off += 1;
+                    }
+                    break None;
+                }
+                Kind::Branch(children) => {
+                    for child in children.iter() {
+                        let next = acc.combine(&child.measure);
+                        if pred(&next) {
+                            node = child.as_ref();
+                            continue 'outer;
+                        }
+                        acc = next;
+                        off += child.len;
+                    }
+                    break None;
+                }
+            }
+        }
+    }
+
+    /// Return the offset and the first item satisfying `pred`, skipping
+    /// subtrees whose cached measure fails `may_contain`. `may_contain`
+    /// shall hold for the measure of any subtree holding a matching item,
+    /// like a bloom filter over keys, false positives only cost a scan.
+    pub fn find_by_measure<P, F>(&self, may_contain: P, pred: F) -> Option<(usize, &T)>
+    where
+        P: Fn(&M) -> bool,
+        F: Fn(&T) -> bool,
+    {
+        match may_contain(&self.root.measure) {
+            true => self.root.find(0, &may_contain, &pred),
+            false => None,
+        }
+    }
+
+    /// Return an iterator over items, starting from `off`.
+    pub fn iter_from(&self, mut off: usize) -> MeasuredIter<'_, T, M> {
+        let mut iter = MeasuredIter {
+            stack: vec![],
+            leaf: [].iter(),
+        };
+        let mut node = self.root.as_ref();
+        loop {
+            match &node.kind {
+                Kind::Leaf(data) => {
+                    iter.leaf = data[off.min(data.len())..].iter();
+                    break iter;
+                }
+                Kind::Branch(children) => match Node::locate(children, off) {
+                    Some((i, start)) => {
+                        iter.stack.push(children[(i + 1)..].iter());
+                        off -= start;
+                        node = children[i].as_ref();
+                    }
+                    None => break iter,
+                },
+            }
+        }
+    }
+
+    /// Return an iterator over items.
+    pub fn iter(&self) -> MeasuredIter<'_, T, M> {
+        self.iter_from(0)
+    }
+
+    // resolve `range` into (start, end) offsets, or `IndexOutOfBounds`
+    // error.
+    fn to_offsets<R>(&self, range: R) -> Result<(usize, usize)>
+    where
+        R: RangeBounds<usize>,
+    {
+        let n = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end.saturating_add(1),
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => n,
+        };
+        if start > end || end > n {
+            Err(Error::IndexOutOfBounds {
+                index: std::cmp::max(start, end),
+                len: n,
+            })?
+        }
+        Ok((start, end))
+    }
+
+    // collapse interior nodes left with a single child, or none.
+    fn collapse(&mut self) {
+        loop {
+            self.root = match &self.root.kind {
+                Kind::Branch(children) if children.len() == 1 => Ref::clone(&children[0]),
+                Kind::Branch(children) if children.is_empty() => {
+                    Ref::new(Node::new_leaf(vec![]))
+                }
+                _ => break,
+            };
+        }
+    }
+
+    /// Return statistics on the shape of the tree, like its depth and how
+    /// full its leaf nodes are, refer [Vector::stats].
+    pub fn stats(&self) -> crate::Stats {
+        let mut stats = crate::Stats::new(self.len(), MAX_ITEMS);
+
+        let mut stack = vec![(self.root.as_ref(), 1)];
+        while let Some((node, depth)) = stack.pop() {
+            match &node.kind {
+                Kind::Leaf(data) => stats.add_leaf(depth, data.len()),
+                Kind::Branch(children) => {
+                    stats.add_node(depth);
+                    stack.extend(children.iter().map(|c| (c.as_ref(), depth + 1)));
+                }
+            }
+        }
+
+        stats.finish()
+    }
+
+    /// Verify the invariants of the tree and return its statistics, refer
+    /// [Self::stats], or `Corrupted` error describing the first violation
+    /// found. Verifies that,
+    ///
+    /// * cached length of every node is the number of items under it.
+    /// * cached measure of every node is the measure of the items under
+    ///   it, combined from its entries, refer [Measure::from_items].
+    /// * leaf nodes are not empty, unless the tree is empty, and hold at
+    ///   most leaf-capacity items.
+    /// * interior nodes have at least one child, the root at least two,
+    ///   and at most as many children as their capacity.
+    /// * all leaf nodes are at the same depth.
+    ///
+    /// This walks the entire tree, useful for testing and fuzzing, like
+    /// [Vector::validate].
+    pub fn validate(&self) -> Result<crate::Stats>
+    where
+        M: PartialEq,
+    {
+        // number of items under each node is computed bottoms-up, children
+        // are visited before their parent, and their counts are stacked.
+        let mut counts: Vec<usize> = vec![];
+        let mut leaf_depth: Option<usize> = None;
+        let mut stack = vec![(self.root.as_ref(), 1, false)];
+        while let Some((node, depth, visited)) = stack.pop() {
+            let is_root = depth == 1;
+            match &node.kind {
+                Kind::Leaf(data) if data.len() > MAX_ITEMS => Err(Error::Corrupted {
+                    detail: format!(
+                        "leaf node holds {} items, cap {}",
+                        data.len(),
+                        MAX_ITEMS
+                    ),
+                })?,
+                Kind::Leaf(data) if data.is_empty() && !is_root => {
+                    Err(Error::Corrupted {
+                        detail: format!("empty leaf node at depth {}", depth),
+                    })?
+                }
+                Kind::Leaf(_) if leaf_depth.unwrap_or(depth) != depth => {
+                    Err(Error::Corrupted {
+                        detail: format!(
+                            "leaf node at depth {}, and at {}",
+                            depth,
+                            leaf_depth.unwrap()
+                        ),
+                    })?
+                }
+                Kind::Leaf(data) => {
+                    leaf_depth = Some(depth);
+                    node.verify_len(data.len())?;
+                    node.verify_measure()?;
+                    counts.push(data.len());
+                }
+                Kind::Branch(children)
+                    if children.is_empty()
+                        || children.len() > MAX_CHILDREN
+                        || (is_root && children.len() < 2) =>
+                {
+                    Err(Error::Corrupted {
+                        detail: format!(
+                            "interior node at depth {} has {} children",
+                            depth,
+                            children.len()
+                        ),
+                    })?
+                }
+                Kind::Branch(children) if !visited => {
+                    stack.push((node, depth, true));
+                    stack.extend(children.iter().map(|c| (c.as_ref(), depth + 1, false)));
+                }
+                Kind::Branch(children) => {
+                    let n = children.iter().map(|_| counts.pop().unwrap()).sum();
+                    node.verify_len(n)?;
+                    node.verify_measure()?;
+                    counts.push(n);
+                }
+            }
+        }
+
+        Ok(self.stats())
+    }
+}
+
+impl<T, M> MeasuredVector<T, M>
+where
+    T: Clone,
+    M: Measure<T>,
+{
+    /// Insert `item` at `off`, or `IndexOutOfBounds` error.
+    pub fn insert(&mut self, off: usize, item: T) -> Result<()> {
+        if off > self.len() {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            })?
+        }
+
+        if let Some(right) = Ref::make_mut(&mut self.root).insert(off, item) {
+            let left = mem::replace(&mut self.root, Ref::new(Node::new_leaf(vec![])));
+            self.root = Ref::new(Node::new_branch(vec![left, Ref::new(right)]));
+        }
+        Ok(())
+    }
+
+    /// Replace the item at `off` with `item`, return the old item, or
+    /// `IndexOutOfBounds` error.
+    pub fn update(&mut self, off: usize, item: T) -> Result<T> {
+        if off >= self.len() {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            })?
+        }
+        Ok(Ref::make_mut(&mut self.root).update(off, item))
+    }
+
+    /// Remove the item at `off` and return it, or `IndexOutOfBounds` error.
+    pub fn remove(&mut self, off: usize) -> Result<T> {
+        if off >= self.len() {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            })?
+        }
+
+        let item = Ref::make_mut(&mut self.root).remove(off);
+        self.collapse();
+        Ok(item)
+    }
+
+    /// Split the sequence at `off`, items from `off` onwards are moved
+    /// into the returned sequence, or `IndexOutOfBounds` error. Only the
+    /// nodes along the path to `off` are copied, rest of the tree is
+    /// shared by both sequences.
+    pub fn split_off(&mut self, off: usize) -> Result<MeasuredVector<T, M>> {
+        if off > self.len() {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            })?
+        }
+
+        let mut val = match off {
+            off if off == self.len() => MeasuredVector::default(),
+            off => MeasuredVector {
+                root: Ref::new(Ref::make_mut(&mut self.root).split_off(off)),
+            },
+        };
+        self.collapse();
+        val.collapse();
+        Ok(val)
+    }
+
+    /// Return the items within `range` as a new sequence, or
+    /// `IndexOutOfBounds` error. Like [Self::split_off], the new sequence
+    /// shares the tree with this one, except for the nodes along the path
+    /// to either end of `range`.
+    pub fn slice<R>(&self, range: R) -> Result<MeasuredVector<T, M>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.to_offsets(range)?;
+        let mut val = self.clone();
+        val.split_off(end)?;
+        val.split_off(start)
+    }
+}
+
+impl<T> MeasuredVector<T, Aggregate<T>>
+where
+    T: Copy + Default + PartialOrd + std::ops::Add<Output = T>,
+{
+    /// Return the sum of items within `range`, or `IndexOutOfBounds` error.
+    /// Computed from cached partial sums in O(log n).
+    pub fn range_sum<R>(&self, range: R) -> Result<T>
+    where
+        R: RangeBounds<usize>,
+    {
+        Ok(self.measure(range)?.sum)
+    }
+
+    /// Return the smallest item within `range`, None if the range is
+    /// empty, or `IndexOutOfBounds` error.
+    pub fn range_min<R>(&self, range: R) -> Result<Option<T>>
+    where
+        R: RangeBounds<usize>,
+    {
+        Ok(self.measure(range)?.min)
+    }
+
+    /// Return the largest item within `range`, None if the range is empty,
+    /// or `IndexOutOfBounds` error.
+    pub fn range_max<R>(&self, range: R) -> Result<Option<T>>
+    where
+        R: RangeBounds<usize>,
+    {
+        Ok(self.measure(range)?.max)
+    }
+
+    /// Return the offset of the first item at which the running sum, from
+    /// the start up to and including the item, reaches `target`, along with
+    /// the sum of items before it. Like, the row where cumulative weight
+    /// is at least `target`. Return None if the sum of all items is less
+    /// than `target`. Items shall not be negative.
+    pub fn find_by_prefix(&self, target: T) -> Option<(usize, T)> {
+        let (off, before) = self.search_by_measure(|m| m.sum >= target)?;
+        Some((off, before.sum))
+    }
+}
+
+#[derive(Clone)]
+struct Node<T, M> {
+    len: usize,
+    measure: M,
+    kind: Kind<T, M>,
+}
+
+#[derive(Clone)]
+enum Kind<T, M> {
+    Leaf(Vec<T>),
+    Branch(Vec<Ref<Node<T, M>>>),
+}
+
+impl<T, M> Node<T, M>
+where
+    M: Measure<T>,
+{
+    fn new_leaf(data: Vec<T>) -> Node<T, M> {
+        let mut node = Node {
+            len: 0,
+            measure: M::default(),
+            kind: Kind::Leaf(data),
+        };
+        node.refresh();
+        node
+    }
+
+    fn new_branch(children: Vec<Ref<Node<T, M>>>) -> Node<T, M> {
+        let mut node = Node {
+            len: 0,
+            measure: M::default(),
+            kind: Kind::Branch(children),
+        };
+        node.refresh();
+        node
+    }
+
+    // recompute the cached length and measure from the entries.
+    fn refresh(&mut self) {
+        self.len = match &self.kind {
+            Kind::Leaf(data) => data.len(),
+            Kind::Branch(children) => children.iter().map(|c| c.len).sum(),
+        };
+        self.measure = self.measure_entries();
+    }
+
+    // return the measure of this node from its entries, or from all items
+    // under it, refer Measure::from_items.
+    fn measure_entries(&self) -> M {
+        match M::from_items(self.items()) {
+            Some(measure) => measure,
+            None => match &self.kind {
+                Kind::Leaf(data) => data
+                    .iter()
+                    .fold(M::default(), |acc, x| acc.combine(&M::from_item(x))),
+                Kind::Branch(children) => children
+                    .iter()
+                    .fold(M::default(), |acc, c| acc.combine(&c.measure)),
+            },
+        }
+    }
+
+    // iterate over all items under this node, without allocating until
+    // the first item is asked for.
+    fn items(&self) -> impl Iterator<Item = &T> {
+        let mut iter = MeasuredIter {
+            stack: vec![],
+            leaf: [].iter(),
+        };
+        let mut node = Some(self);
+        std::iter::from_fn(move || {
+            match node.take().map(|node| &node.kind) {
+                Some(Kind::Leaf(data)) => iter.leaf = data.iter(),
+                Some(Kind::Branch(children)) => iter.stack.push(children.iter()),
+                None => (),
+            }
+            iter.next()
+        })
+    }
+
+    // number of items in leaf node, or number of children in interior node.
+    fn n_entries(&self) -> usize {
+        match &self.kind {
+            Kind::Leaf(data) => data.len(),
+            Kind::Branch(children) => children.len(),
+        }
+    }
+
+    // return the measure of items within `start..end`, relative to this
+    // node, where `start < end <= len`.
+    fn measure_range(&self, start: usize, end: usize) -> M {
+        match &self.kind {
+            Kind::Leaf(data) => data
+                .get(start..end)
+                .unwrap_or(&[])
+                .iter()
+                .fold(M::default(), |acc, x| acc.combine(&M::from_item(x))),
+            Kind::Branch(children) => {
+                let (mut acc, mut lo) = (M::default(), 0);
+                for child in children.iter() {
+                    let hi = lo + child.len;
+                    if hi <= start {
+                        // child is before the range.
+                    } else if lo >= end {
+                        break;
+                    } else if start <= lo && hi <= end {
+                        acc = acc.combine(&child.measure);
+                    } else {
+                        let (a, b) = (start.max(lo) - lo, end.min(hi) - lo);
+                        acc = acc.combine(&child.measure_range(a, b));
+                    }
+                    lo = hi;
+                }
+                acc
+            }
+        }
+    }
+
+    // depth first search for the first item satisfying `pred`, under this
+    // node starting at `off`, pruned by `may_contain`.
+    fn find<P, F>(&self, off: usize, may_contain: &P, pred: &F) -> Option<(usize, &T)>
+    where
+        P: Fn(&M) -> bool,
+        F: Fn(&T) -> bool,
+    {
+        match &self.kind {
+            Kind::Leaf(data) => {
+                let i = data.iter().position(pred)?;
+                Some((off + i, &data[i]))
+            }
+            Kind::Branch(children) => {
+                let mut start = off;
+                for child in children.iter() {
+                    if may_contain(&child.measure) {
+                        if let Some(res) = child.find(start, may_contain, pred) {
+                            return Some(res);
+                        }
+                    }
+                    start += child.len;
+                }
+                None
+            }
+        }
+    }
+
+    fn get(&self, off: usize) -> Option<&T> {
+        self.kind.get(off)
+    }
+
+    // cached length shall match the number of items under this node.
+    fn verify_len(&self, n: usize) -> Result<()> {
+        match self.len == n {
+            true => Ok(()),
+            false => Err(Error::Corrupted {
+                detail: format!("len {} for {} items", self.len, n),
+            }),
+        }
+    }
+
+    // cached measure shall match the measure of the entries, children are
+    // verified before their parent.
+    fn verify_measure(&self) -> Result<()>
+    where
+        M: PartialEq,
+    {
+        match self.measure == self.measure_entries() {
+            true => Ok(()),
+            false => Err(Error::Corrupted {
+                detail: format!("stale measure for {} items", self.len),
+            }),
+        }
+    }
+
+    // return (index, start) of the child holding `off`.
+    fn locate(children: &[Ref<Node<T, M>>], off: usize) -> Option<(usize, usize)> {
+        let mut start = 0;
+        for (i, child) in children.iter().enumerate() {
+            if off < start + child.len {
+                return Some((i, start));
+            }
+            start += child.len;
+        }
+        None
+    }
+}
+
+impl<T, M> Kind<T, M>
+where
+    M: Measure<T>,
+{
+    fn get(&self, mut off: usize) -> Option<&T> {
+        let mut kind = self;
+        loop {
+            match kind {
+                Kind::Leaf(data) => break data.get(off),
+                Kind::Branch(children) => {
+                    let (i, start) = Node::locate(children, off)?;
+                    off -= start;
+                    kind = &children[i].kind;
+                }
+            }
+        }
+    }
+}
+
+// Edits update the cached measure of every node along the path, in-place
+// if the measure supports it, refer Measure::update, else the measure is
+// recomputed.
+impl<T, M> Node<T, M>
+where
+    T: Clone,
+    M: Measure<T>,
+{
+    // insert item at `off`, return the right half if this node was split.
+    fn insert(&mut self, off: usize, item: T) -> Option<Node<T, M>> {
+        let updated = self.measure.update(None, Some(&item));
+        match &mut self.kind {
+            Kind::Leaf(data) => data.insert(off, item),
+            Kind::Branch(children) => {
+                // inserting at the end of a child appends to it.
+                let (mut i, mut start) = (0, 0);
+                while i + 1 < children.len() && off > start + children[i].len {
+                    start += children[i].len;
+                    i += 1;
+                }
+                if let Some(right) =
+                    Ref::make_mut(&mut children[i]).insert(off - start, item)
+                {
+                    children.insert(i + 1, Ref::new(right));
+                }
+            }
+        }
+
+        match self.n_entries() {
+            n if n > self.max_entries() => {
+                let right = self.split();
+                self.refresh();
+                Some(right)
+            }
+            _ if updated => {
+                self.len += 1;
+                None
+            }
+            _ => {
+                self.refresh();
+                None
+            }
+        }
+    }
+
+    fn update(&mut self, off: usize, item: T) -> T {
+        let updated = self.measure.update(self.kind.get(off), Some(&item));
+        let old = match &mut self.kind {
+            Kind::Leaf(data) => mem::replace(&mut data[off], item),
+            Kind::Branch(children) => {
+                let (i, start) = Node::locate(children, off).unwrap();
+                Ref::make_mut(&mut children[i]).update(off - start, item)
+            }
+        };
+        if !updated {
+            self.refresh();
+        }
+        old
+    }
+
+    fn remove(&mut self, off: usize) -> T {
+        let updated = self.measure.update(self.kind.get(off), None);
+        let item = match &mut self.kind {
+            Kind::Leaf(data) => data.remove(off),
+            Kind::Branch(children) => {
+                let (i, start) = Node::locate(children, off).unwrap();
+                let item = Ref::make_mut(&mut children[i]).remove(off - start);
+                Node::fix_underflow(children, i);
+                item
+            }
+        };
+        match updated {
+            true => self.len -= 1,
+            false => self.refresh(),
+        }
+        item
+    }
+
+    // split this node at `off`, where `off < len`, keep items before
+    // `off` and return a node, at the same depth, holding the rest. Either
+    // node can be left empty, or with a single child.
+    fn split_off(&mut self, off: usize) -> Node<T, M> {
+        let right = match &mut self.kind {
+            Kind::Leaf(data) => Node::new_leaf(data.split_off(off)),
+            Kind::Branch(children) => {
+                let (i, start) = Node::locate(children, off).unwrap();
+                let mut right = children.split_off(i + 1);
+                let head = Ref::make_mut(&mut children[i]).split_off(off - start);
+                right.insert(0, Ref::new(head));
+                // only the children along the cut could have shrunk.
+                Node::fix_underflow(children, i);
+                Node::fix_underflow(&mut right, 0);
+                Node::new_branch(right)
+            }
+        };
+        self.refresh();
+        right
+    }
+
+    // child at `i` has shrunk after a remove, drop it if empty, or merge it
+    // with its sibling if underfull, splitting the merged node if it
+    // overflows.
+    fn fix_underflow(children: &mut Vec<Ref<Node<T, M>>>, i: usize) {
+        let n = children[i].n_entries();
+        if n == 0 {
+            children.remove(i);
+            return;
+        } else if children.len() == 1 || !is_underfull(n, children[i].max_entries()) {
+            return;
+        }
+
+        let (a, b) = if (i + 1) < children.len() {
+            (i, i + 1)
+        } else {
+            (i - 1, i)
+        };
+        let right = children.remove(b);
+        let right = Ref::try_unwrap(right).unwrap_or_else(|r| r.as_ref().clone());
+        let left = Ref::make_mut(&mut children[a]);
+        left.merge(right);
+        if left.n_entries() > left.max_entries() {
+            let right = left.split();
+            left.refresh();
+            children.insert(a + 1, Ref::new(right));
+        }
+    }
+
+    // move entries from `right` sibling into this node.
+    fn merge(&mut self, right: Node<T, M>) {
+        match (&mut self.kind, right.kind) {
+            (Kind::Leaf(a), Kind::Leaf(b)) => a.extend(b),
+            (Kind::Branch(a), Kind::Branch(b)) => a.extend(b),
+            (_, _) => unreachable!(),
+        }
+        self.refresh();
+    }
+
+    // split this node in half, return the right half.
+    fn split(&mut self) -> Node<T, M> {
+        let n = self.n_entries() / 2;
+        match &mut self.kind {
+            Kind::Leaf(data) => Node::new_leaf(data.split_off(n)),
+            Kind::Branch(children) => Node::new_branch(children.split_off(n)),
+        }
+    }
+
+    fn max_entries(&self) -> usize {
+        match &self.kind {
+            Kind::Leaf(_) => MAX_ITEMS,
+            Kind::Branch(_) => MAX_CHILDREN,
+        }
+    }
+}
+
+// even out the last two chunks of entries, cut for nodes of upto `cap`
+// entries, if the last one is underfull, refer Node::fix_underflow.
+fn even_tail<X>(chunks: &mut Vec<Vec<X>>, cap: usize) {
+    match chunks.len() {
+        n if n > 1 && is_underfull(chunks[n - 1].len(), cap) => {
+            let last = chunks.pop().unwrap();
+            let prev = chunks.last_mut().unwrap();
+            prev.extend(last);
+            let right = prev.split_off(prev.len() / 2);
+            chunks.push(right);
+        }
+        _ => (),
+    }
+}
+
+/// Iterator over items of [MeasuredVector].
+pub struct MeasuredIter<'a, T, M> {
+    stack: Vec<slice::Iter<'a, Ref<Node<T, M>>>>,
+    leaf: slice::Iter<'a, T>,
+}
+
+impl<'a, T, M> Iterator for MeasuredIter<'a, T, M> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.leaf.next() {
+                break Some(item);
+            }
+            let child = loop {
+                match self.stack.last_mut()?.next() {
+                    Some(child) => break child,
+                    None => {
+                        self.stack.pop();
+                    }
+                }
+            };
+            match &child.kind {
+                Kind::Leaf(data) => self.leaf = data.iter(),
+                Kind::Branch(children) => self.stack.push(children.iter()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "measured_test.rs"]
+mod measured_test;
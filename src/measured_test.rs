@@ -0,0 +1,353 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::ops::Bound;
+
+use super::*;
+
+// measure counting items and summing them up.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Sum {
+    count: usize,
+    sum: u64,
+}
+
+impl Measure<u64> for Sum {
+    fn from_item(item: &u64) -> Self {
+        Sum {
+            count: 1,
+            sum: *item,
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        Sum {
+            count: self.count + other.count,
+            sum: self.sum + other.sum,
+        }
+    }
+}
+
+fn validate(arr: &MeasuredVector<u64, Sum>, refv: &[u64]) {
+    let stats = arr.validate().unwrap();
+    assert_eq!(stats.n_items, refv.len());
+    assert_eq!(arr.len(), refv.len());
+    assert_eq!(arr.total().sum, refv.iter().sum::<u64>());
+    assert!(arr.iter().eq(refv.iter()));
+}
+
+#[test]
+fn test_measured_ops() {
+    let seed: u128 = random();
+    println!("test_measured_ops seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: MeasuredVector<u64, Sum> = MeasuredVector::default();
+    let mut refv: Vec<u64> = vec![];
+    let mut versions = vec![];
+
+    for i in 0..50_000 {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        let val = rng.gen::<u64>() % 1000;
+        match rng.gen::<u8>() % 10 {
+            0..=4 => {
+                arr.insert(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            5 | 6 if off < n => {
+                assert_eq!(arr.update(off, val).unwrap(), refv[off]);
+                refv[off] = val;
+            }
+            7 | 8 if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            _ => assert_eq!(arr.get(off).ok(), refv.get(off)),
+        }
+        if i % 5000 == 0 {
+            validate(&arr, &refv);
+            versions.push((arr.clone(), refv.clone()));
+        }
+    }
+    validate(&arr, &refv);
+
+    // older versions are left untouched by edits.
+    for (arr, refv) in versions.iter() {
+        validate(arr, refv);
+    }
+
+    while !refv.is_empty() {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+    }
+    validate(&arr, &refv);
+    arr.insert(0, 10).unwrap();
+    validate(&arr, &[10]);
+}
+
+#[test]
+fn test_measured_errors() {
+    let mut arr: MeasuredVector<u64, Sum> = (0..100).collect();
+    let err = Error::IndexOutOfBounds {
+        index: 101,
+        len: 100,
+    };
+    assert_eq!(arr.insert(101, 0).err(), Some(err));
+    assert!(arr.update(100, 0).is_err());
+    assert!(arr.remove(100).is_err());
+    assert_eq!(
+        arr.get(100).err(),
+        Some(Error::IndexOutOfBounds {
+            index: 100,
+            len: 100
+        })
+    );
+}
+
+#[test]
+fn test_measured_search() {
+    let refv: Vec<u64> = (0..10_000).map(|i| i % 7).collect();
+    let arr: MeasuredVector<u64, Sum> = refv.iter().cloned().collect();
+    validate(&arr, &refv);
+
+    let total: u64 = refv.iter().sum();
+    for target in [0, 1, 6, 7, 1000, total - 1].iter() {
+        let (off, before) = arr.search_by_measure(|m| m.sum > *target).unwrap();
+        let sum: u64 = refv[..off].iter().sum();
+        assert_eq!(before, Sum { count: off, sum });
+        assert!(sum <= *target && sum + refv[off] > *target);
+    }
+    assert_eq!(arr.search_by_measure(|m| m.sum > total), None);
+
+    for off in [0, 1, 63, 64, 5000, 9999, 10_000].iter() {
+        assert!(arr.iter_from(*off).eq(refv[*off..].iter()));
+    }
+}
+
+#[test]
+fn test_measured_range() {
+    let seed: u128 = random();
+    println!("test_measured_range seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut refv: Vec<u64> = (0..10_000).map(|_| rng.gen::<u64>() % 1000).collect();
+    let mut arr: MeasuredVector<u64, Sum> = refv.iter().cloned().collect();
+
+    let measure = |refv: &[u64]| Sum {
+        count: refv.len(),
+        sum: refv.iter().sum(),
+    };
+    for _ in 0..2000 {
+        let off = rng.gen::<usize>() % refv.len();
+        let val = rng.gen::<u64>() % 1000;
+        arr.insert(off, val).unwrap();
+        refv.insert(off, val);
+
+        let n = refv.len();
+        let (a, b) = (rng.gen::<usize>() % (n + 1), rng.gen::<usize>() % (n + 1));
+        let (a, b) = (a.min(b), a.max(b));
+        assert_eq!(arr.measure(a..b).unwrap(), measure(&refv[a..b]));
+        assert_eq!(arr.measure(..b).unwrap(), measure(&refv[..b]));
+        assert_eq!(arr.measure(a..).unwrap(), measure(&refv[a..]));
+    }
+    let n = refv.len();
+    assert_eq!(arr.measure(..).unwrap(), measure(&refv));
+    assert_eq!(arr.measure(10..=20).unwrap(), measure(&refv[10..=20]));
+    assert_eq!(arr.measure(5..5).unwrap(), Sum::default());
+
+    let err = Error::IndexOutOfBounds {
+        index: n + 1,
+        len: n,
+    };
+    assert_eq!(arr.measure(0..(n + 1)).err(), Some(err));
+    assert!(arr.measure(..=n).is_err());
+    assert!(arr
+        .measure((Bound::Excluded(10), Bound::Excluded(5)))
+        .is_err());
+}
+
+#[test]
+fn test_measured_aggregate() {
+    let seed: u128 = random();
+    println!("test_measured_aggregate seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut refv: Vec<i64> = (0..10_000).map(|_| rng.gen::<i64>() % 1000).collect();
+    let mut arr: MeasuredVector<i64, Aggregate<i64>> = refv.iter().cloned().collect();
+
+    for _ in 0..2000 {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % n;
+        let val = rng.gen::<i64>() % 1000;
+        match rng.gen::<u8>() % 3 {
+            0 => {
+                arr.insert(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            1 => {
+                arr.update(off, val).unwrap();
+                refv[off] = val;
+            }
+            _ => assert_eq!(arr.remove(off).unwrap(), refv.remove(off)),
+        }
+
+        let n = refv.len();
+        let (a, b) = (rng.gen::<usize>() % (n + 1), rng.gen::<usize>() % (n + 1));
+        let (a, b) = (a.min(b), a.max(b));
+        let refs = &refv[a..b];
+        assert_eq!(arr.range_sum(a..b).unwrap(), refs.iter().sum::<i64>());
+        assert_eq!(arr.range_min(a..b).unwrap(), refs.iter().min().cloned());
+        assert_eq!(arr.range_max(a..b).unwrap(), refs.iter().max().cloned());
+    }
+    assert_eq!(arr.total().count, refv.len());
+    assert!(arr.range_sum(..(refv.len() + 1)).is_err());
+
+    let arr: MeasuredVector<f64, Aggregate<f64>> =
+        vec![1.5, -2.0, 4.0].into_iter().collect();
+    assert_eq!(arr.range_sum(..).unwrap(), 3.5);
+    assert_eq!(arr.range_min(1..).unwrap(), Some(-2.0));
+    assert_eq!(arr.range_max(..2).unwrap(), Some(1.5));
+}
+
+#[test]
+fn test_measured_find_by_prefix() {
+    let seed: u128 = random();
+    println!("test_measured_find_by_prefix seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    // weights, with zeros, as row heights for pagination.
+    let refv: Vec<u64> = (0..10_000).map(|_| rng.gen::<u64>() % 10).collect();
+    let arr: MeasuredVector<u64, Aggregate<u64>> = refv.iter().cloned().collect();
+    let prefix: Vec<u64> = refv
+        .iter()
+        .scan(0, |acc, x| {
+            *acc += x;
+            Some(*acc)
+        })
+        .collect();
+    let total = prefix[prefix.len() - 1];
+
+    for _ in 0..1000 {
+        let target = rng.gen::<u64>() % (total + 1);
+        let (off, before) = arr.find_by_prefix(target).unwrap();
+        assert_eq!(off, prefix.iter().position(|x| *x >= target).unwrap());
+        assert_eq!(before, prefix[off] - refv[off]);
+    }
+    assert_eq!(arr.find_by_prefix(0), Some((0, 0)));
+    assert_eq!(arr.find_by_prefix(total + 1), None);
+}
+
+#[test]
+fn test_measured_validate() {
+    let arr: MeasuredVector<u64, Sum> = (0..2000).collect();
+    let stats = arr.validate().unwrap();
+    assert_eq!(stats, arr.stats());
+    assert_eq!(stats.n_leafs, 2000_usize.div_ceil(MAX_ITEMS));
+    assert_eq!(stats.depth, 3);
+    let arr: MeasuredVector<u64, Sum> = MeasuredVector::default();
+    assert_eq!(arr.validate().unwrap().n_leafs, 0);
+
+    let leaf = |n: u64| Ref::new(Node::new_leaf((0..n).collect()));
+    let corrupted = |root: Node<u64, Sum>| {
+        let arr = MeasuredVector {
+            root: Ref::new(root),
+        };
+        matches!(arr.validate(), Err(Error::Corrupted { .. }))
+    };
+
+    // cached length does not match the items under the node.
+    let mut root = Node::new_branch(vec![leaf(10), leaf(10)]);
+    root.len = 21;
+    assert!(corrupted(root));
+
+    // cached measure does not match the items under the node.
+    let mut root = Node::new_branch(vec![leaf(10), leaf(10)]);
+    root.measure.sum += 1;
+    assert!(corrupted(root));
+    let mut child: Node<u64, Sum> = Node::new_leaf((0..10).collect());
+    child.measure.count = 9;
+    assert!(corrupted(Node::new_branch(vec![leaf(10), Ref::new(child)])));
+
+    // leaf node beyond its capacity, and empty leaf node.
+    let root = Node::new_branch(vec![leaf(10), leaf(MAX_ITEMS as u64 + 1)]);
+    assert!(corrupted(root));
+    assert!(corrupted(Node::new_branch(vec![leaf(10), leaf(0)])));
+
+    // root with a single child, and leaf nodes at different depths.
+    assert!(corrupted(Node::new_branch(vec![leaf(10)])));
+    let child = Ref::new(Node::new_branch(vec![leaf(10), leaf(10)]));
+    assert!(corrupted(Node::new_branch(vec![leaf(10), child])));
+}
+
+#[test]
+fn test_measured_from_iter() {
+    // trailing leaf nodes, and interior nodes, are not left underfull.
+    let ns = [
+        1,
+        63,
+        64,
+        65,
+        64 * 16,
+        64 * 16 + 1,
+        64 * 17,
+        64 * 16 * 17 + 100,
+    ];
+    for n in ns.iter() {
+        let refv: Vec<u64> = (0..*n).collect();
+        let arr: MeasuredVector<u64, Sum> = refv.iter().copied().collect();
+        validate(&arr, &refv);
+
+        let mut stack = match &arr.root.kind {
+            Kind::Branch(children) => children.iter().collect(),
+            Kind::Leaf(_) => vec![],
+        };
+        while let Some(node) = stack.pop() {
+            let (n_entries, cap) = (node.n_entries(), node.max_entries());
+            assert!(!is_underfull(n_entries, cap), "n:{} {}", n, n_entries);
+            if let Kind::Branch(children) = &node.kind {
+                stack.extend(children.iter());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_measured_split() {
+    let seed: u128 = random();
+    println!("test_measured_split seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: MeasuredVector<u64, Sum> = MeasuredVector::default();
+    let mut refv: Vec<u64> = vec![];
+    for _ in 0..20_000 {
+        let off = rng.gen::<usize>() % (refv.len() + 1);
+        let val = rng.gen::<u64>() % 1000;
+        arr.insert(off, val).unwrap();
+        refv.insert(off, val);
+    }
+
+    let n = refv.len();
+    let mut offs: Vec<usize> = (0..100).map(|_| rng.gen::<usize>() % (n + 1)).collect();
+    offs.extend_from_slice(&[0, 1, MAX_ITEMS, n - 1, n]);
+    for off in offs.into_iter() {
+        let mut left = arr.clone();
+        let mut right = left.split_off(off).unwrap();
+        validate(&left, &refv[..off]);
+        validate(&right, &refv[off..]);
+
+        // halves can be edited without touching the original.
+        left.insert(0, 1).unwrap();
+        right.insert(right.len(), 1).unwrap();
+        validate(&arr, &refv);
+
+        let end = off + rng.gen::<usize>() % (n - off + 1);
+        validate(&arr.slice(off..end).unwrap(), &refv[off..end]);
+    }
+    validate(&arr.slice(..).unwrap(), &refv);
+
+    let err = Error::IndexOutOfBounds {
+        index: n + 1,
+        len: n,
+    };
+    assert_eq!(arr.clone().split_off(n + 1).err(), Some(err.clone()));
+    assert_eq!(arr.slice(10..=n).err(), Some(err));
+}
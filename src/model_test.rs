@@ -0,0 +1,378 @@
+//! Model based testing, every operation on Vector is also applied on a
+//! reference Vec, and the results are compared. Enabled with the `model`
+//! feature. To add coverage for a new api, add a variant to [Op] and its
+//! counterpart on the reference Vec in [Model::apply].
+//!
+//! On failure, the sequence of operations is shrunk to the shortest
+//! sequence that still fails, and printed as Rust statements that can be
+//! pasted into a test within this module.
+
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::{collections::BTreeMap, panic, thread};
+
+use super::*;
+use crate::shrink::{panics, shrink};
+
+const N_OPS: usize = 10_000;
+
+// prints the seed, of a failing test, so that it can be replayed.
+struct Seed(&'static str, u128);
+
+impl Drop for Seed {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            println!("{} failed with seed {}", self.0, self.1);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Insert(usize, u64),
+    InsertMut(usize, u64),
+    Update(usize, u64),
+    UpdateMut(usize, u64),
+    Remove(usize),
+    RemoveMut(usize),
+    Get(usize),
+    Iter,
+    IntoIter,
+    SplitOff(usize),
+    Append(Vec<u64>),
+    Apply(Vec<Edit<u64>>),
+    Rebalance(bool),
+    Persist(usize, u64),
+}
+
+impl Op {
+    fn name(&self) -> &'static str {
+        match self {
+            Op::Insert(_, _) => "insert",
+            Op::InsertMut(_, _) => "insert_mut",
+            Op::Update(_, _) => "update",
+            Op::UpdateMut(_, _) => "update_mut",
+            Op::Remove(_) => "remove",
+            Op::RemoveMut(_) => "remove_mut",
+            Op::Get(_) => "get",
+            Op::Iter => "iter",
+            Op::IntoIter => "into_iter",
+            Op::SplitOff(_) => "split_off",
+            Op::Append(_) => "append",
+            Op::Apply(_) => "apply",
+            Op::Rebalance(_) => "rebalance",
+            Op::Persist(_, _) => "persist",
+        }
+    }
+
+    // format the operation as a rust expression.
+    fn to_rust(&self) -> String {
+        match self {
+            Op::Append(vals) => format!("Op::Append(vec!{:?})", vals),
+            Op::Apply(edits) => {
+                let edits: Vec<String> = edits
+                    .iter()
+                    .map(|e| match e {
+                        Edit::Insert(off, val) => {
+                            format!("Edit::Insert({}, {})", off, val)
+                        }
+                        Edit::Update(off, val) => {
+                            format!("Edit::Update({}, {})", off, val)
+                        }
+                        Edit::Remove(off) => format!("Edit::Remove({})", off),
+                    })
+                    .collect();
+                format!("Op::Apply(vec![{}])", edits.join(", "))
+            }
+            op => format!("Op::{:?}", op),
+        }
+    }
+
+    // generate an operation, offsets are mostly within bounds, `n` being
+    // the length of the vector.
+    fn generate(rng: &mut SmallRng, n: usize) -> Op {
+        let off = match rng.gen::<u8>() % 16 {
+            0 => n + (rng.gen::<usize>() % 3),
+            _ => rng.gen::<usize>() % (n + 1),
+        };
+        let val = rng.gen::<u64>();
+
+        match rng.gen::<u8>() % 20 {
+            0..=2 => Op::Insert(off, val),
+            3..=5 => Op::InsertMut(off, val),
+            6 | 7 => Op::Update(off, val),
+            8 | 9 => Op::UpdateMut(off, val),
+            10 | 11 => Op::Remove(off),
+            12 | 13 => Op::RemoveMut(off),
+            14 => Op::Get(off),
+            15 if rng.gen::<bool>() => Op::Iter,
+            15 => Op::IntoIter,
+            16 => Op::SplitOff(off),
+            17 => {
+                let m = rng.gen::<usize>() % 1000;
+                Op::Append((0..m).map(|_| rng.gen()).collect())
+            }
+            18 if rng.gen::<u8>() % 10 == 0 => Op::Rebalance(rng.gen()),
+            18 => Op::Persist(off, val),
+            _ => {
+                let edits = (0..(rng.gen::<usize>() % 20))
+                    .map(|_| {
+                        let off = rng.gen::<usize>() % (n + 1);
+                        match rng.gen::<u8>() % 3 {
+                            0 => Edit::Insert(off, rng.gen()),
+                            1 => Edit::Update(off, rng.gen()),
+                            _ => Edit::Remove(off),
+                        }
+                    })
+                    .collect();
+                Op::Apply(edits)
+            }
+        }
+    }
+}
+
+// initial state of the model, items are 0..n.
+#[derive(Clone, Copy, Debug)]
+struct Config {
+    n: u64,
+    leaf_size: usize,
+    auto_rebalance: bool,
+    strict_balance: bool,
+    fanout: usize,
+}
+
+impl Config {
+    fn generate(rng: &mut SmallRng) -> Config {
+        Config {
+            n: [0, 1, 1000, 10_000][rng.gen::<usize>() % 4],
+            leaf_size: [16, 128, 1024, 8192][rng.gen::<usize>() % 4],
+            auto_rebalance: rng.gen(),
+            strict_balance: rng.gen(),
+            fanout: [2, 4, 16][rng.gen::<usize>() % 3],
+        }
+    }
+
+    // format the config as a rust expression.
+    fn to_rust(self) -> String {
+        format!(
+            "Config {{ n: {}, leaf_size: {}, auto_rebalance: {}, strict_balance: {}, fanout: {} }}",
+            self.n, self.leaf_size, self.auto_rebalance, self.strict_balance, self.fanout
+        )
+    }
+}
+
+struct Model {
+    arr: Vector<u64>,
+    refv: Vec<u64>,
+    counts: BTreeMap<&'static str, usize>,
+}
+
+impl Model {
+    fn new(config: Config) -> Model {
+        let refv: Vec<u64> = (0..config.n).collect();
+        let mut arr = Vector::from_slice(&refv, Some(config.leaf_size));
+        arr.set_auto_rebalance(config.auto_rebalance)
+            .set_strict_balance(config.strict_balance)
+            .set_fanout(config.fanout);
+        Model {
+            arr,
+            refv,
+            counts: BTreeMap::new(),
+        }
+    }
+
+    fn apply(&mut self, op: Op) {
+        *self.counts.entry(op.name()).or_default() += 1;
+
+        let (arr, refv) = (&mut self.arr, &mut self.refv);
+        let n = refv.len();
+        match op {
+            Op::Insert(off, val) if off <= n => {
+                arr.insert(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            Op::InsertMut(off, val) if off <= n => {
+                arr.insert_mut(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            Op::Insert(off, val) => assert!(arr.insert(off, val).is_err()),
+            Op::InsertMut(off, val) => assert!(arr.insert_mut(off, val).is_err()),
+            Op::Update(off, val) if off < n => {
+                let old = std::mem::replace(&mut refv[off], val);
+                assert_eq!(arr.update(off, val).unwrap(), old);
+            }
+            Op::UpdateMut(off, val) if off < n => {
+                let old = std::mem::replace(&mut refv[off], val);
+                assert_eq!(arr.update_mut(off, val).unwrap(), old);
+            }
+            Op::Update(off, val) => assert!(arr.update(off, val).is_err()),
+            Op::UpdateMut(off, val) => assert!(arr.update_mut(off, val).is_err()),
+            Op::Remove(off) if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            Op::RemoveMut(off) if off < n => {
+                assert_eq!(arr.remove_mut(off).unwrap(), refv.remove(off));
+            }
+            Op::Remove(off) => assert!(arr.remove(off).is_err()),
+            Op::RemoveMut(off) => assert!(arr.remove_mut(off).is_err()),
+            Op::Get(off) => assert_eq!(arr.get(off).ok(), refv.get(off)),
+            Op::Iter => assert!(arr.iter().eq(refv.iter())),
+            Op::IntoIter => assert!(arr.clone().into_iter().eq(refv.clone())),
+            Op::SplitOff(off) if off <= n => {
+                let a = arr.split_off(off).unwrap();
+                let b = refv.split_off(off);
+                a.validate().unwrap();
+                assert!(a.iter().eq(b.iter()));
+                arr.append(a);
+                refv.extend(b);
+            }
+            Op::SplitOff(off) => assert!(arr.split_off(off).is_err()),
+            Op::Append(vals) => {
+                arr.append(Vector::from_slice(&vals, Some(arr.leaf_size())));
+                refv.extend(vals);
+            }
+            Op::Apply(edits) => match apply_edits(refv, &edits) {
+                Some(vals) => {
+                    *arr = arr.apply(&edits).unwrap();
+                    *refv = vals;
+                }
+                None => assert!(arr.apply(&edits).is_err()),
+            },
+            Op::Rebalance(packed) => *arr = arr.rebalance(packed).unwrap(),
+            // edits on a clone leave the original untouched.
+            Op::Persist(off, val) => {
+                let mut a = arr.clone();
+                match a.insert(off, val) {
+                    Ok(()) => assert_eq!(a.len(), n + 1),
+                    Err(_) => assert!(off > n),
+                }
+                assert!(arr.iter().eq(refv.iter()));
+            }
+        }
+
+        if let Err(err) = arr.validate() {
+            panic!("{}", err)
+        }
+        assert_eq!(arr.len(), refv.len());
+    }
+}
+
+// reference implementation of Vector::apply, return None if edits are
+// invalid.
+fn apply_edits(refv: &[u64], edits: &[Edit<u64>]) -> Option<Vec<u64>> {
+    let n = refv.len();
+    let mut edits: Vec<&Edit<u64>> = edits.iter().collect();
+    edits.sort_by_key(|e| (e.to_offset(), !matches!(e, Edit::Insert(..))));
+
+    let mut vals = vec![];
+    let mut iter = edits.into_iter().peekable();
+    for off in 0..=n {
+        let mut edited = false;
+        while let Some(edit) = iter.next_if(|e| e.to_offset() == off) {
+            match edit {
+                Edit::Insert(_, val) => vals.push(*val),
+                _ if edited || off == n => return None,
+                Edit::Update(_, val) => {
+                    vals.push(*val);
+                    edited = true;
+                }
+                Edit::Remove(_) => edited = true,
+            }
+        }
+        match refv.get(off) {
+            Some(val) if !edited => vals.push(*val),
+            _ => (),
+        }
+    }
+
+    match iter.next() {
+        Some(_) => None,
+        None => Some(vals),
+    }
+}
+
+// replay `ops` on a fresh model, return whether it fails.
+fn replay(config: Config, ops: &[Op]) -> bool {
+    panics(|| {
+        let mut model = Model::new(config);
+        ops.iter().for_each(|op| model.apply(op.clone()));
+    })
+}
+
+#[test]
+fn test_model() {
+    let seed: u128 = random();
+    println!("test_model seed {}", seed);
+    let _seed = Seed("test_model", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let config = Config::generate(&mut rng);
+    println!("test_model {:?}", config);
+
+    let mut ops = vec![];
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let mut model = Model::new(config);
+        for _i in 0..N_OPS {
+            let op = Op::generate(&mut rng, model.refv.len());
+            ops.push(op.clone());
+            model.apply(op);
+        }
+        model
+    }));
+
+    let model = match res {
+        Ok(model) => model,
+        Err(err) => {
+            let shrunk = shrink(&ops, |ops| replay(config, ops));
+            println!("test_model shrunk {} ops to {}", ops.len(), shrunk.len());
+            println!("let mut model = Model::new({});", config.to_rust());
+            for op in shrunk.iter() {
+                println!("model.apply({});", op.to_rust());
+            }
+            panic::resume_unwind(err)
+        }
+    };
+
+    // memory ratio is not checked, leaf nodes are fragmented by random ops.
+    assert!(model.arr.iter().eq(model.refv.iter()));
+    let stats = model.arr.validate().unwrap();
+    println!("test_model {:?}", stats);
+    for (name, count) in model.counts.iter() {
+        println!("test_model {:12}: {}", name, count);
+    }
+}
+
+#[test]
+fn test_shrink() {
+    let ops: Vec<usize> = (0..1000).collect();
+    let shrunk = shrink(&ops, |ops| ops.contains(&17) && ops.contains(&500));
+    assert_eq!(shrunk, vec![17, 500]);
+
+    assert_eq!(shrink(&ops, |_| true).len(), 1);
+
+    // fail once the model has grown by three items.
+    let config = Config {
+        n: 1000,
+        leaf_size: 16,
+        auto_rebalance: false,
+        strict_balance: false,
+        fanout: 2,
+    };
+    let mut rng = SmallRng::from_seed(1_u128.to_le_bytes());
+    let ops: Vec<Op> = (0..200).map(|_| Op::generate(&mut rng, 1000)).collect();
+    let grows = |ops: &[Op]| {
+        panics(|| {
+            let mut model = Model::new(config);
+            ops.iter().for_each(|op| model.apply(op.clone()));
+            assert!(model.refv.len() < 1003);
+        })
+    };
+    if grows(&ops) {
+        let shrunk = shrink(&ops, grows);
+        assert!(grows(&shrunk));
+        assert!(shrunk.len() <= 3, "{}", shrunk.len());
+        shrunk
+            .iter()
+            .for_each(|op| assert!(!op.to_rust().is_empty()));
+    }
+}
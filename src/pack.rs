@@ -0,0 +1,169 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    mem,
+};
+
+use crate::{Error, Result};
+
+/// Items that can be packed into bytes, for compressed storage, refer
+/// `CompressedVector`.
+///
+/// Implemented for integers, `bool`, `String` and `Vec<u8>`. Integers are
+/// packed in little endian, strings and byte vectors are prefixed with
+/// their length.
+pub trait Pack: Sized {
+    /// Append the packed form of `items` to `buf`.
+    fn pack(items: &[Self], buf: &mut Vec<u8>);
+
+    /// Return the items packed in `buf`, by [Pack::pack].
+    fn unpack(buf: &[u8]) -> Result<Vec<Self>>;
+}
+
+/// Compression policy for `CompressedVector`.
+///
+/// Items are held in chunks of `chunk_items`. Except the last `hot_chunks`,
+/// chunks are cold, stored packed and lz4 compressed, and decompressed on
+/// access. The last `cache_chunks` decompressed chunks are cached, so that
+/// reads in the vicinity of each other decompress a chunk only once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compression {
+    /// Number of items in a chunk.
+    pub chunk_items: usize,
+    /// Number of chunks, at the end, held uncompressed. `usize::MAX`
+    /// disables compression.
+    pub hot_chunks: usize,
+    /// Number of decompressed cold chunks cached for reads.
+    pub cache_chunks: usize,
+}
+
+impl Default for Compression {
+    fn default() -> Compression {
+        Compression {
+            chunk_items: 1024,
+            hot_chunks: 2,
+            cache_chunks: 4,
+        }
+    }
+}
+
+/// Memory footprint of a `CompressedVector`, logical and as stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompressedFootprint {
+    /// Size of items, `mem::size_of::<T>()` per item, as if uncompressed.
+    pub logical: usize,
+    /// Size of items as stored, that is, compressed bytes for cold chunks,
+    /// and the same as logical for hot chunks.
+    pub stored: usize,
+}
+
+fn truncated(n: usize, want: usize) -> Error {
+    let detail = format!("packed items truncated at {}, want {} bytes", n, want);
+    Error::Corrupted { detail }
+}
+
+impl Pack for u8 {
+    fn pack(items: &[u8], buf: &mut Vec<u8>) {
+        buf.extend_from_slice(items)
+    }
+
+    fn unpack(buf: &[u8]) -> Result<Vec<u8>> {
+        Ok(buf.to_vec())
+    }
+}
+
+impl Pack for bool {
+    fn pack(items: &[bool], buf: &mut Vec<u8>) {
+        buf.extend(items.iter().map(|item| u8::from(*item)))
+    }
+
+    fn unpack(buf: &[u8]) -> Result<Vec<bool>> {
+        Ok(buf.iter().map(|b| *b != 0).collect())
+    }
+}
+
+macro_rules! pack_int {
+    ($($t:ty),*) => {$(
+        impl Pack for $t {
+            fn pack(items: &[$t], buf: &mut Vec<u8>) {
+                items.iter().for_each(|item| buf.extend_from_slice(&item.to_le_bytes()))
+            }
+
+            fn unpack(buf: &[u8]) -> Result<Vec<$t>> {
+                let n = mem::size_of::<$t>();
+                match buf.len() % n {
+                    0 => Ok(buf
+                        .chunks_exact(n)
+                        .map(|b| <$t>::from_le_bytes(b.try_into().unwrap()))
+                        .collect()),
+                    m => Err(truncated(buf.len(), buf.len() - m + n)),
+                }
+            }
+        }
+    )*};
+}
+
+pack_int!(u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+// items prefixed by their length, as u64 in little endian.
+fn pack_bytes<'a, I>(items: I, buf: &mut Vec<u8>)
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    for item in items {
+        buf.extend_from_slice(&(item.len() as u64).to_le_bytes());
+        buf.extend_from_slice(item);
+    }
+}
+
+fn unpack_bytes(mut buf: &[u8]) -> Result<Vec<&[u8]>> {
+    let mut items = vec![];
+    while !buf.is_empty() {
+        let (head, rest) = match buf.len() {
+            n if n < 8 => Err(truncated(n, 8))?,
+            _ => buf.split_at(8),
+        };
+        let n = u64::from_le_bytes(head.try_into().unwrap());
+        match usize::try_from(n) {
+            Ok(n) if n <= rest.len() => {
+                items.push(&rest[..n]);
+                buf = &rest[n..];
+            }
+            _ => Err(truncated(rest.len(), n as usize))?,
+        }
+    }
+    Ok(items)
+}
+
+impl Pack for Vec<u8> {
+    fn pack(items: &[Vec<u8>], buf: &mut Vec<u8>) {
+        pack_bytes(items.iter().map(|item| item.as_slice()), buf)
+    }
+
+    fn unpack(buf: &[u8]) -> Result<Vec<Vec<u8>>> {
+        Ok(unpack_bytes(buf)?.into_iter().map(|b| b.to_vec()).collect())
+    }
+}
+
+impl Pack for String {
+    fn pack(items: &[String], buf: &mut Vec<u8>) {
+        pack_bytes(items.iter().map(|item| item.as_bytes()), buf)
+    }
+
+    fn unpack(buf: &[u8]) -> Result<Vec<String>> {
+        let mut items = vec![];
+        for b in unpack_bytes(buf)?.into_iter() {
+            match std::str::from_utf8(b) {
+                Ok(s) => items.push(s.to_string()),
+                Err(err) => {
+                    let detail = format!("packed string {}", err);
+                    Err(Error::Corrupted { detail })?
+                }
+            }
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+#[path = "pack_test.rs"]
+mod pack_test;
@@ -0,0 +1,51 @@
+use super::*;
+
+fn round_trip<T>(items: Vec<T>)
+where
+    T: Pack + PartialEq + std::fmt::Debug,
+{
+    let mut buf = vec![];
+    T::pack(&items, &mut buf);
+    assert_eq!(T::unpack(&buf).unwrap(), items);
+}
+
+#[test]
+fn test_pack() {
+    round_trip::<u8>((0..=255).collect());
+    round_trip(vec![true, false, true]);
+    round_trip(vec![0_u64, 1, u64::MAX]);
+    round_trip(vec![i32::MIN, -1, 0, i32::MAX]);
+    round_trip(vec![0_u128, u128::MAX]);
+    round_trip(vec![
+        String::new(),
+        "hello".to_string(),
+        "world".repeat(100),
+    ]);
+    round_trip(vec![vec![], vec![1_u8, 2, 3]]);
+    round_trip::<u64>(vec![]);
+    round_trip::<String>(vec![]);
+}
+
+#[test]
+fn test_unpack_corrupted() {
+    let mut buf = vec![];
+    u32::pack(&[1, 2], &mut buf);
+    assert!(matches!(
+        u32::unpack(&buf[..7]),
+        Err(Error::Corrupted { .. })
+    ));
+
+    let mut buf = vec![];
+    String::pack(&["hello".to_string()], &mut buf);
+    let n = buf.len();
+    assert!(matches!(
+        String::unpack(&buf[..n - 1]),
+        Err(Error::Corrupted { .. })
+    ));
+    assert!(matches!(
+        String::unpack(&buf[..4]),
+        Err(Error::Corrupted { .. })
+    ));
+    buf[n - 1] = 0xff;
+    assert!(matches!(String::unpack(&buf), Err(Error::Corrupted { .. })));
+}
@@ -0,0 +1,196 @@
+//! Parallel operations on thread-safe Vector, using [rayon].
+
+use rayon::prelude::*;
+
+use super::{max_leaf_items, Node, Ref, Vector};
+use crate::Result;
+
+/// Subtrees with fewer leaf nodes than this are built serially.
+const PAR_LEAFS: usize = 64;
+
+impl<T> Vector<T>
+where
+    T: Clone + Send + Sync,
+{
+    /// Construct a new vector with an initial array of values, same as
+    /// [Vector::from_slice], but leaf nodes and subtrees are built on
+    /// rayon's worker threads.
+    pub fn from_slice_par(slice: &[T], leaf_node_size: Option<usize>) -> Vector<T> {
+        let mut arr = Vector::default();
+        arr.leaf_cap = leaf_node_size.unwrap_or(crate::LEAF_CAP);
+        let n = max_leaf_items::<T>(arr.leaf_cap);
+
+        let leafs: Vec<Ref<Node<T>>> = slice
+            .par_chunks(n)
+            .map(|x| Ref::new(Node::from(x)))
+            .collect();
+        arr.with_leafs_par(leafs)
+    }
+
+    /// Same as [Vector::rebalance], but leaf nodes are collected, packed
+    /// and rebuilt into a balanced tree on rayon's worker threads. Useful
+    /// to cut down rebalance pauses on very large vectors.
+    pub fn rebalance_par(&self, packed: bool) -> Result<Self> {
+        let arr = traced!("ppar::rebalance_par", arr, {
+            let leafs = collect_par(&self.root_with_tail());
+            let leafs = match packed {
+                true => leafs
+                    .par_chunks(PAR_LEAFS)
+                    .flat_map_iter(|x| Node::pack_leaf_nodes(x.to_vec(), self.leaf_cap))
+                    .collect(),
+                false => leafs,
+            };
+            let (root, len) = build_par(&leafs, self.fanout);
+            assert!(len == self.len);
+
+            self.with_root(root, len)
+        });
+        Ok(arr)
+    }
+
+    /// Fold all elements in the vector, the left and right subtrees are
+    /// folded on separate threads, starting from the value returned by
+    /// `identity`, and their results are combined using `reduce_op`. Elements
+    /// within a leaf node are folded serially using `fold_op`.
+    ///
+    /// Like `rayon`, `reduce_op` is expected to be associative, with
+    /// `identity` as its identity value.
+    pub fn par_fold<A, ID, F, R>(&self, identity: ID, fold_op: F, reduce_op: R) -> A
+    where
+        A: Send,
+        ID: Fn() -> A + Sync,
+        F: Fn(A, &T) -> A + Sync,
+        R: Fn(A, A) -> A + Sync,
+    {
+        fold_par(&self.root_with_tail(), &identity, &fold_op, &reduce_op)
+    }
+
+    /// Reduce all elements in the vector using `op`, the left and right
+    /// subtrees are reduced on separate threads. Return the value returned
+    /// by `identity` for an empty vector.
+    ///
+    /// Like `rayon`, `op` is expected to be associative, with `identity`
+    /// as its identity value.
+    pub fn par_reduce<ID, OP>(&self, identity: ID, op: OP) -> T
+    where
+        ID: Fn() -> T + Sync,
+        OP: Fn(T, T) -> T + Sync,
+    {
+        self.par_fold(&identity, |acc, item| op(acc, item.clone()), &op)
+    }
+}
+
+impl<T> FromParallelIterator<T> for Vector<T>
+where
+    T: Send + Sync,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let n = max_leaf_items::<T>(crate::LEAF_CAP);
+
+        // elements are moved into blocks of leaf size, chunked on rayon's
+        // worker threads, and each block is moved into its leaf node.
+        let items: Vec<T> = par_iter.into_par_iter().collect();
+        let leafs: Vec<Ref<Node<T>>> = items
+            .into_par_iter()
+            .chunks(n)
+            .map(|data| Ref::new(Node::Z { data }))
+            .collect();
+
+        Vector::default().with_leafs_par(leafs)
+    }
+}
+
+impl<T> Vector<T>
+where
+    T: Send + Sync,
+{
+    // construct a new vector over `leafs`, with the same configuration as
+    // this vector, a single leaf node is held inline, or as the tail leaf
+    // node when shared, refer split_tail.
+    fn with_leafs_par(&self, leafs: Vec<Ref<Node<T>>>) -> Vector<T> {
+        traced!("ppar::build_par", arr, {
+            let (root, len) = build_par(&leafs, self.fanout);
+            self.with_root(root, len)
+        })
+    }
+}
+
+// collect leaf nodes, from left to right, walking the left and right
+// subtrees on separate threads.
+fn collect_par<T>(node: &Ref<Node<T>>) -> Vec<Ref<Node<T>>>
+where
+    T: Send + Sync,
+{
+    match node.as_ref() {
+        Node::M { left, right, .. } => {
+            let (mut left, mut right) =
+                rayon::join(|| collect_par(left), || collect_par(right));
+            left.append(&mut right);
+            left
+        }
+        Node::N { children, .. } => children
+            .par_iter()
+            .flat_map_iter(|(_, child)| collect_par(child))
+            .collect(),
+        Node::Z { .. } => vec![Ref::clone(node)],
+    }
+}
+
+fn fold_par<T, A, ID, F, R>(
+    node: &Node<T>,
+    identity: &ID,
+    fold_op: &F,
+    reduce_op: &R,
+) -> A
+where
+    T: Send + Sync,
+    A: Send,
+    ID: Fn() -> A + Sync,
+    F: Fn(A, &T) -> A + Sync,
+    R: Fn(A, A) -> A + Sync,
+{
+    match node {
+        Node::M { left, right, .. } => {
+            let (a, b) = rayon::join(
+                || fold_par(left, identity, fold_op, reduce_op),
+                || fold_par(right, identity, fold_op, reduce_op),
+            );
+            reduce_op(a, b)
+        }
+        Node::N { children, .. } => children
+            .par_iter()
+            .map(|(_, child)| fold_par(child, identity, fold_op, reduce_op))
+            .reduce(identity, reduce_op),
+        Node::Z { data } => data.iter().fold(identity(), fold_op),
+    }
+}
+
+// build a balanced tree over `leafs`, joining the left and right halves
+// on separate threads, subtrees smaller than PAR_LEAFS are built with
+// interior nodes of `fanout` children. Return (root, number-of-items).
+fn build_par<T>(leafs: &[Ref<Node<T>>], fanout: usize) -> (Ref<Node<T>>, usize)
+where
+    T: Send + Sync,
+{
+    match leafs.len() {
+        0 => (Node::empty_leaf(), 0),
+        n if n < PAR_LEAFS => {
+            let (root, _depth) = Node::build_leaf_nodes(leafs.to_vec(), fanout);
+            let m = root.len();
+            (root, m)
+        }
+        n => {
+            let (left, right) = leafs.split_at(n / 2);
+            let ((left, weight), (right, m)) =
+                rayon::join(|| build_par(left, fanout), || build_par(right, fanout));
+            (Node::newm(left, right, weight), weight + m)
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "par_test.rs"]
+mod par_test;
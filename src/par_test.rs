@@ -0,0 +1,109 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use super::*;
+use crate::arc::validate;
+
+#[test]
+fn test_from_slice_par() {
+    let seed: u128 = random();
+    println!("test_from_slice_par seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 10, 1000, 1_000_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+        for leaf_size in [None, Some(128), Some(1024)].iter() {
+            let arr = Vector::from_slice_par(&vals, *leaf_size);
+            validate(&arr, &vals);
+
+            // same shape as the serial build, single leaf goes into the tail.
+            let refa = Vector::from_slice(&vals, *leaf_size);
+            assert_eq!(arr.leaf_cap, refa.leaf_cap);
+            assert_eq!(arr.fanout, refa.fanout);
+            assert_eq!(arr.tail.len(), refa.tail.len());
+        }
+    }
+}
+
+#[test]
+fn test_from_par_iter() {
+    let vals: Vec<u64> = (0..1_000_000).collect();
+    let arr: Vector<u64> = vals.par_iter().copied().collect();
+    validate(&arr, &vals);
+
+    // elements are moved into the leaf nodes, they need not be Clone.
+    #[derive(Debug, PartialEq)]
+    struct Item(u64);
+
+    let arr: Vector<Item> = (0..100_000).into_par_iter().map(Item).collect();
+    assert_eq!(arr.len(), 100_000);
+    arr.validate().unwrap();
+    for (i, item) in arr.iter().enumerate() {
+        assert_eq!(item, &Item(i as u64));
+    }
+}
+
+#[test]
+fn test_rebalance_par() {
+    let seed: u128 = random();
+    println!("test_rebalance_par seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr = Vector::default();
+    arr.set_leaf_size(1024).set_auto_rebalance(false);
+    let mut refv: Vec<u64> = vec![];
+
+    for _i in 0..100_000 {
+        let val = rng.gen::<u64>();
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        refv.insert(off, val);
+        arr.insert(off, val).unwrap();
+    }
+
+    for packed in [false, true].iter() {
+        let arr = arr.rebalance_par(*packed).unwrap();
+        validate(&arr, &refv);
+    }
+
+    arr.set_fanout(16);
+    let arr = arr.rebalance_par(true).unwrap();
+    validate(&arr, &refv);
+
+    let sum = arr.par_fold(|| 0_u64, |acc, x| acc.wrapping_add(*x), u64::wrapping_add);
+    let val = refv.iter().fold(0_u64, |acc, x| acc.wrapping_add(*x));
+    assert_eq!(sum, val);
+}
+
+#[test]
+fn test_par_fold() {
+    let seed: u128 = random();
+    println!("test_par_fold seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 1000, 1_000_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen::<u64>() % 1000).collect();
+        let arr = Vector::from_slice(&vals, Some(1024));
+
+        let sum = arr.par_fold(|| 0_u64, |acc, x| acc + x, |a, b| a + b);
+        assert_eq!(sum, vals.iter().sum::<u64>());
+
+        let sum = arr.par_reduce(|| 0, |a, b| a + b);
+        assert_eq!(sum, vals.iter().sum::<u64>());
+
+        let max = arr.par_reduce(|| 0, std::cmp::max);
+        assert_eq!(max, vals.iter().copied().max().unwrap_or(0));
+
+        let items: Vec<u64> = arr.par_fold(
+            Vec::new,
+            |mut acc, x| {
+                acc.push(*x);
+                acc
+            },
+            |mut a, mut b| {
+                a.append(&mut b);
+                a
+            },
+        );
+        assert_eq!(items, vals);
+    }
+}
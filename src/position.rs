@@ -0,0 +1,60 @@
+use crate::Edit;
+
+/// Map positions in a vector to their positions after a batch of edits.
+///
+/// Cursors, selections and external references into a vector can be
+/// carried forward across edits, like an editor keeping its cursors in
+/// place while a remote change is applied. Built from an edit script, with
+/// offsets referring to positions before the edits, as taken by
+/// `Vector::apply` and returned by `Vector::diff`.
+#[derive(Clone, Debug, Default)]
+pub struct PositionMap {
+    // sorted offsets of inserts and removes.
+    inserts: Vec<usize>,
+    removes: Vec<usize>,
+}
+
+impl PositionMap {
+    /// Create a position map for `edits`.
+    pub fn new<T>(edits: &[Edit<T>]) -> PositionMap {
+        let (mut inserts, mut removes) = (vec![], vec![]);
+        for edit in edits.iter() {
+            match edit {
+                Edit::Insert(off, _) => inserts.push(*off),
+                Edit::Update(_, _) => (),
+                Edit::Remove(off) => removes.push(*off),
+            }
+        }
+        inserts.sort_unstable();
+        removes.sort_unstable();
+        removes.dedup();
+
+        PositionMap { inserts, removes }
+    }
+
+    /// Return the position, after the edits, of the item at `index` before
+    /// the edits, or None if the item is removed. Items inserted at `index`
+    /// go before the item, and an updated item keeps its position.
+    pub fn map_position(&self, index: usize) -> Option<usize> {
+        match self.removes.binary_search(&index) {
+            Ok(_) => None,
+            Err(n) => {
+                let m = self.inserts.partition_point(|off| *off <= index);
+                Some(index + m - n)
+            }
+        }
+    }
+
+    /// Batch version of [PositionMap::map_position], mapping each of
+    /// `indices`.
+    pub fn map_positions(&self, indices: &[usize]) -> Vec<Option<usize>> {
+        indices
+            .iter()
+            .map(|index| self.map_position(*index))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "position_test.rs"]
+mod position_test;
@@ -0,0 +1,40 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+use crate::rc::Vector;
+
+#[test]
+fn test_position_map() {
+    let seed: u128 = random();
+    println!("test_position_map seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let n = 1000_u64;
+    let arr: Vector<u64> = (0..n).collect();
+
+    for _ in 0..100 {
+        let mut edits = vec![];
+        let mut seen = vec![false; n as usize];
+        for i in 0..(rng.gen::<usize>() % 50) {
+            let off = rng.gen::<usize>() % (n as usize);
+            match rng.gen::<u8>() % 3 {
+                0 => edits.push(Edit::Insert(off, n + (i as u64))),
+                1 if !seen[off] => edits.push(Edit::Update(off, off as u64)),
+                _ if !seen[off] => edits.push(Edit::Remove(off)),
+                _ => continue,
+            }
+            seen[off] = seen[off] || !matches!(edits.last(), Some(Edit::Insert(..)));
+        }
+        let res: Vec<u64> = arr.apply(&edits).unwrap().into();
+
+        let pmap = PositionMap::new(&edits);
+        let indices: Vec<usize> = (0..(n as usize)).collect();
+        for (index, pos) in pmap.map_positions(&indices).into_iter().enumerate() {
+            let refpos = res.iter().position(|val| *val == index as u64);
+            assert_eq!(pos, refpos, "index:{}", index);
+        }
+    }
+
+    let pmap = PositionMap::new::<u64>(&[]);
+    assert_eq!(pmap.map_position(10), Some(10));
+}
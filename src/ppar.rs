@@ -1,7 +1,14 @@
-use std::{borrow::Borrow, mem};
+use std::{
+    borrow::Borrow,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    mem,
+    ops::Deref,
+};
 
 use super::*;
-use crate::{Error, Result};
+use crate::{Edit, Error, Result};
 
 // TODO: replace assert!() with debug_assert!()
 
@@ -10,19 +17,74 @@ pub struct Vector<T>
 where
     T: Sized,
 {
-    len: usize,
-    root: Ref<Node<T>>,
-    auto_rebalance: bool,
-    leaf_cap: usize,
+    pub(crate) len: usize,
+    pub(crate) root: Root<T>,
+    pub(crate) auto_rebalance: bool,
+    pub(crate) strict_balance: bool,
+    pub(crate) leaf_cap: usize,
+    pub(crate) fanout: usize,
+    pub(crate) max_depth: Option<usize>,
+}
+
+// Vectors holding up to a leaf node worth of items keep them inline,
+// without allocating tree nodes, and are promoted to a tree when they
+// fill past a single leaf node.
+pub(crate) enum Root<T> {
+    Inline(Vec<T>),
+    Tree {
+        root: Ref<Node<T>>,
+        // last leaf node, kept out of the tree, to append without
+        // descending the tree. Always a leaf node, possibly empty.
+        tail: Ref<Node<T>>,
+    },
+}
+
+impl<T> Clone for Root<T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Root<T> {
+        match self {
+            Root::Inline(data) => Root::Inline(data.clone()),
+            Root::Tree { root, tail } => Root::Tree {
+                root: Ref::clone(root),
+                tail: Ref::clone(tail),
+            },
+        }
+    }
 }
 
-impl<T> Clone for Vector<T> {
+impl<T> Clone for Vector<T>
+where
+    T: Clone,
+{
     fn clone(&self) -> Vector<T> {
         Vector {
             len: self.len,
-            root: Ref::clone(&self.root),
+            root: self.root.clone(),
             auto_rebalance: self.auto_rebalance,
+            strict_balance: self.strict_balance,
             leaf_cap: self.leaf_cap,
+            fanout: self.fanout,
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+impl<T> Drop for Vector<T> {
+    fn drop(&mut self) {
+        // trees that have gone deep, with auto-rebalance disabled, would
+        // overflow the call stack on recursive drop.
+        if let Root::Tree { root, .. } = &mut self.root {
+            if let Some(root) = Ref::get_mut(root) {
+                mem::replace(
+                    root,
+                    Node::Z {
+                        data: Vec::default(),
+                    },
+                )
+                .release()
+            }
         }
     }
 }
@@ -31,10 +93,14 @@ impl<T> From<Vector<T>> for Vec<T>
 where
     T: Clone,
 {
-    fn from(val: Vector<T>) -> Vec<T> {
+    fn from(mut val: Vector<T>) -> Vec<T> {
+        if let Root::Inline(data) = &mut val.root {
+            return mem::take(data);
+        }
+
         let mut arr = vec![];
 
-        let root = Ref::clone(&val.root);
+        let root = val.root_with_tail();
         for leaf in Node::collect_leaf_nodes(root, false, val.leaf_cap) {
             match leaf.borrow() {
                 Node::Z { data } => arr.extend_from_slice(data),
@@ -54,7 +120,7 @@ where
     fn arbitrary(
         u: &mut arbitrary::unstructured::Unstructured,
     ) -> arbitrary::Result<Self> {
-        let k = std::mem::size_of::<T>();
+        let k = std::cmp::max(std::mem::size_of::<T>(), 1);
         let leaf_cap = *u.choose(&[k, k * 2, k * 100, k * 1000, k * 10000])?;
         let auto_reb = *u.choose(&[true, false])?; // auto_rebalance
         let arr: Vec<T> = u.arbitrary()?;
@@ -65,6 +131,15 @@ where
     }
 }
 
+impl<T> FromIterator<T> for Vector<T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Vector::build(iter, None)
+    }
+}
+
 impl<T> IntoIterator for Vector<T>
 where
     T: Clone,
@@ -72,13 +147,23 @@ where
     type Item = T;
     type IntoIter = IntoIter<T>;
 
-    fn into_iter(self) -> Self::IntoIter {
+    fn into_iter(mut self) -> Self::IntoIter {
         let mut iter = IntoIter {
             stack: Vec::default(),
             node: None,
             off: 0,
+            remaining: self.len,
         };
-        Node::build_into_iter_stack(&self.root, &mut iter);
+        match &mut self.root {
+            Root::Inline(data) => {
+                let data = mem::take(data);
+                iter.node = Some(Ref::new(Node::Z { data }));
+            }
+            Root::Tree { root, tail } => {
+                iter.stack.push(Ref::clone(tail));
+                Node::build_into_iter_stack(root, &mut iter);
+            }
+        }
         iter
     }
 }
@@ -87,9 +172,12 @@ impl<T> Default for Vector<T> {
     fn default() -> Vector<T> {
         Vector {
             len: 0,
-            root: Node::empty_leaf(),
+            root: Root::Inline(Vec::default()),
             auto_rebalance: true,
+            strict_balance: false,
             leaf_cap: crate::LEAF_CAP,
+            fanout: crate::FANOUT,
+            max_depth: None,
         }
     }
 }
@@ -103,26 +191,310 @@ where
     where
         T: Clone,
     {
-        let n = max_leaf_items::<T>(leaf_node_size.unwrap_or(crate::LEAF_CAP));
+        let leaf_cap = leaf_node_size.unwrap_or(crate::LEAF_CAP);
+        let n = max_leaf_items::<T>(leaf_cap);
 
-        let mut leafs: Vec<Ref<Node<T>>> =
+        let leafs: Vec<Ref<Node<T>>> =
             slice.chunks(n).map(|x| Ref::new(Node::from(x))).collect();
-        leafs.reverse();
 
-        let depth = (leafs.len() as f64).log2().ceil() as usize;
-        let (root, _) = Node::build_bottoms_up(depth, &mut leafs);
-        assert!(leafs.is_empty());
+        Self::from_leaf_nodes(leafs, leaf_cap)
+    }
+
+    /// Construct a new vector from blocks of values, each block is moved
+    /// into the tree as a leaf node, without copying its values. Blocks
+    /// larger than the leaf node size are split up and empty blocks are
+    /// skipped. Tree is built bottoms-up, in O(n) time, and fully balanced.
+    pub fn from_leaf_blocks(
+        blocks: Vec<Vec<T>>,
+        leaf_node_size: Option<usize>,
+    ) -> Vector<T> {
+        let leaf_cap = leaf_node_size.unwrap_or(crate::LEAF_CAP);
+        let n = max_leaf_items::<T>(leaf_cap);
+
+        let mut leafs: Vec<Ref<Node<T>>> = vec![];
+        for data in blocks.into_iter() {
+            match data.len() {
+                0 => (),
+                m if m <= n => leafs.push(Ref::new(Node::Z { data })),
+                _ => leafs.extend(Self::chunk_leaf_nodes(data.into_iter(), n)),
+            }
+        }
+
+        Self::from_leaf_nodes(leafs, leaf_cap)
+    }
+
+    /// Construct a new vector from an iterator of values. Values are
+    /// moved into fully packed leaf nodes, and the tree is built bottoms-up,
+    /// in O(n) time, and fully balanced.
+    pub fn build<I>(iter: I, leaf_node_size: Option<usize>) -> Vector<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let leaf_cap = leaf_node_size.unwrap_or(crate::LEAF_CAP);
+        let n = max_leaf_items::<T>(leaf_cap);
+
+        let leafs = Self::chunk_leaf_nodes(iter.into_iter(), n);
+
+        Self::from_leaf_nodes(leafs, leaf_cap)
+    }
+
+    fn chunk_leaf_nodes<I>(mut iter: I, n: usize) -> Vec<Ref<Node<T>>>
+    where
+        I: Iterator<Item = T>,
+    {
+        let mut leafs = vec![];
+        loop {
+            let mut data: Vec<T> = Vec::with_capacity(n);
+            data.extend(iter.by_ref().take(n));
+            match data.len() {
+                0 => break leafs,
+                m if m < n => {
+                    data.shrink_to_fit();
+                    leafs.push(Ref::new(Node::Z { data }));
+                    break leafs;
+                }
+                _ => leafs.push(Ref::new(Node::Z { data })),
+            }
+        }
+    }
+
+    fn from_leaf_nodes(mut leafs: Vec<Ref<Node<T>>>, leaf_cap: usize) -> Vector<T> {
+        traced!("ppar::build", arr, {
+            leafs.reverse();
+
+            let depth = (leafs.len() as f64).log2().ceil() as usize;
+            let (root, len) = Node::build_bottoms_up(depth, &mut leafs);
+            assert!(leafs.is_empty());
+
+            Vector {
+                len,
+                root: Self::split_tail(root),
+                auto_rebalance: true,
+                strict_balance: false,
+                leaf_cap,
+                fanout: crate::FANOUT,
+                max_depth: None,
+            }
+        })
+    }
 
+    // construct a new vector for `root`, holding `len` items, with the same
+    // configuration as this vector.
+    pub(crate) fn with_root(&self, root: Ref<Node<T>>, len: usize) -> Vector<T> {
         Vector {
-            len: slice.len(),
-            root,
-            auto_rebalance: true,
-            leaf_cap: leaf_node_size.unwrap_or(crate::LEAF_CAP),
+            len,
+            root: Self::split_tail(root),
+            auto_rebalance: self.auto_rebalance,
+            strict_balance: self.strict_balance,
+            leaf_cap: self.leaf_cap,
+            fanout: self.fanout,
+            max_depth: self.max_depth,
+        }
+    }
+
+    // return the root for a new vector, items that fit within a single leaf
+    // node are held inline, skipping the tree entirely, which gets built
+    // only when they fill past the leaf node. A leaf node shared with other
+    // versions is kept as the tail leaf node, instead of copying its items.
+    pub(crate) fn split_tail(root: Ref<Node<T>>) -> Root<T> {
+        match root.as_ref() {
+            Node::Z { .. } => match Ref::try_unwrap(root) {
+                Ok(Node::Z { data }) => Root::Inline(data),
+                Ok(_) => unreachable!(),
+                Err(tail) => Root::Tree {
+                    root: Node::empty_leaf(),
+                    tail,
+                },
+            },
+            _ => Root::Tree {
+                root,
+                tail: Node::empty_leaf(),
+            },
+        }
+    }
+
+    // return the tree's root and tail leaf node for mutation, items held
+    // inline are moved into the tail leaf node.
+    fn tree_mut(&mut self) -> (&mut Ref<Node<T>>, &mut Ref<Node<T>>) {
+        if let Root::Inline(data) = &mut self.root {
+            let data = mem::take(data);
+            self.root = Root::Tree {
+                root: Node::empty_leaf(),
+                tail: Ref::new(Node::Z { data }),
+            };
+        }
+        match &mut self.root {
+            Root::Tree { root, tail } => (root, tail),
+            Root::Inline(_) => unreachable!(),
+        }
+    }
+
+    // return the tree's root, None if items are held inline.
+    pub(crate) fn tree(&self) -> Option<&Ref<Node<T>>> {
+        match &self.root {
+            Root::Inline(_) => None,
+            Root::Tree { root, .. } => Some(root),
+        }
+    }
+
+    // tree's root and tail leaf node, to walk the tree.
+    pub(crate) fn tree_nodes(&self) -> Vec<&Ref<Node<T>>> {
+        match &self.root {
+            Root::Inline(_) => vec![],
+            Root::Tree { root, tail } => vec![root, tail],
+        }
+    }
+
+    // replace the root, older root is dropped without recursion, refer
+    // Node::release.
+    fn set_root(&mut self, root: Ref<Node<T>>) {
+        if let Some(node) = Ref::into_inner(mem::replace(self.tree_mut().0, root)) {
+            node.release()
+        }
+    }
+
+    // tree including the tail leaf node, if any. Items held inline are
+    // copied into a leaf node.
+    pub(crate) fn root_with_tail(&self) -> Ref<Node<T>>
+    where
+        T: Clone,
+    {
+        match &self.root {
+            Root::Inline(data) => Ref::new(Node::Z { data: data.clone() }),
+            Root::Tree { root, tail } if tail.len() == 0 => Ref::clone(root),
+            Root::Tree { root, tail } => {
+                let n = self.len - tail.len();
+                Node::newm(Ref::clone(root), Ref::clone(tail), n)
+            }
+        }
+    }
+
+    // items in the tail leaf node, or held inline.
+    #[inline]
+    pub(crate) fn tail_items(&self) -> &[T] {
+        match &self.root {
+            Root::Inline(data) => data,
+            Root::Tree { tail, .. } => match tail.as_ref() {
+                Node::Z { data } => data,
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    // number of items in the tree, excluding the tail leaf node.
+    #[inline]
+    pub(crate) fn root_len(&self) -> usize {
+        self.len.saturating_sub(self.tail_items().len())
+    }
+
+    // move the tail leaf node into the tree, as its right most leaf node,
+    // items held inline become the tree's only leaf node.
+    fn flush_tail(&mut self)
+    where
+        T: Clone,
+    {
+        if let Root::Inline(data) = &mut self.root {
+            let data = mem::take(data);
+            self.root = Root::Tree {
+                root: Ref::new(Node::Z { data }),
+                tail: Node::empty_leaf(),
+            };
+        } else if !self.tail_items().is_empty() {
+            let rn = Rebalance::new(self);
+            let root_len = self.root_len();
+            let (root, tail) = self.tree_mut();
+            let tail = mem::replace(tail, Node::empty_leaf());
+            *root = Node::push_leaf(root, tail, root_len, &rn).0;
+            self.rebalance_strict();
+        }
+    }
+
+    // insert `value` into the tail leaf node, if `off` falls within it, a
+    // full tail leaf node is moved into the tree before insert. Return
+    // `value` back if `off` falls within the tree.
+    fn insert_tail(&mut self, off: usize, value: T) -> Result<Option<T>>
+    where
+        T: Clone,
+    {
+        if off < self.root_len() {
+            return Ok(Some(value));
+        }
+
+        if self.tail_items().len() >= max_leaf_items::<T>(self.leaf_cap) {
+            self.flush_tail();
+        }
+
+        match self.root_len() {
+            root_len if off < root_len => Ok(Some(value)),
+            root_len => {
+                let n = max_leaf_items::<T>(self.leaf_cap);
+                let data = self.tail_mut();
+                match off.checked_sub(root_len) {
+                    Some(off) if off <= data.len() => {
+                        Node::leaf_insert_mut(data, off, value, n)
+                    }
+                    _ => Err(Node::<T>::overrun(off, root_len, data.len()))?,
+                }
+                self.len += 1;
+                Ok(None)
+            }
+        }
+    }
+
+    // return the tail leaf node's item at `off`, relative to the tail leaf
+    // node, for in-place update.
+    fn tail_item_mut(&mut self, off: usize) -> Result<&mut T>
+    where
+        T: Clone,
+    {
+        let data = self.tail_mut();
+        let n = data.len();
+        data.get_mut(off)
+            .ok_or_else(|| Node::<T>::overrun(off, 0, n))
+    }
+
+    // `off` is relative to the tail leaf node.
+    fn remove_tail(&mut self, off: usize) -> Result<T>
+    where
+        T: Clone,
+    {
+        let data = self.tail_mut();
+        if off >= data.len() {
+            Err(Node::<T>::overrun(off, 0, data.len()))?
+        }
+        let val = data.remove(off);
+        if (data.len() * 2) < data.capacity() {
+            data.shrink_to_fit()
+        }
+        Ok(val)
+    }
+
+    // return the tail leaf node's items for in-place mutation, tail leaf
+    // node is copied if it is shared with other versions.
+    fn tail_mut(&mut self) -> &mut Vec<T>
+    where
+        T: Clone,
+    {
+        match &mut self.root {
+            Root::Inline(data) => data,
+            Root::Tree { tail, .. } => match Node::make_mut(tail) {
+                Node::Z { data } => data,
+                _ => unreachable!(),
+            },
         }
     }
 
+    /// Construct a new empty vector, with leaf node size set to `leaf_size`
+    /// bytes. Refer [Self::set_leaf_size] for details.
+    pub fn with_leaf_size(leaf_size: usize) -> Vector<T> {
+        let mut arr = Vector::default();
+        arr.set_leaf_size(leaf_size);
+        arr
+    }
+
     /// Set the size of the leaf node in bytes. Number of items inside
-    /// the leaf node is computed as `(leaf_size / mem::size_of::<T>()) + 1`
+    /// the leaf node is computed as `(leaf_size / mem::size_of::<T>()) + 1`,
+    /// except for zero-sized types, where `leaf_size` is the number of items.
     /// Setting a large value will make the tree shallow giving better
     /// read performance, at the expense of write performance.
     pub fn set_leaf_size(&mut self, leaf_size: usize) -> &mut Self {
@@ -130,6 +502,40 @@ where
         self
     }
 
+    /// Set the size of the leaf node based on how this vector is accessed,
+    /// refer [crate::AccessProfile] for details. Like [Self::set_leaf_size],
+    /// existing leaf nodes are resized only when the tree is rebuilt, say
+    /// using [Self::rebalance] with `packed` as true, or [Self::compact].
+    pub fn set_access_profile(&mut self, profile: crate::AccessProfile) -> &mut Self {
+        self.set_leaf_size(profile.to_leaf_size())
+    }
+
+    /// Return the size of the leaf node in bytes, that is configured for this
+    /// instance. This setting is carried over to vectors derived from this
+    /// instance, via clone, split_off, rebalance etc.
+    pub fn leaf_size(&self) -> usize {
+        self.leaf_cap
+    }
+
+    /// Set the maximum number of children for interior nodes, default is
+    /// [crate::FANOUT], that is, binary interior nodes. With a larger
+    /// fanout, say 8 to 32, interior nodes hold cumulative weights of their
+    /// children, making the tree shallower and cutting down pointer chasing
+    /// for every read and write, at the expense of copying larger interior
+    /// nodes on copy-on-write operations. Fanout applies to subtrees that
+    /// are built by auto-rebalance and [Self::rebalance], values less
+    /// than 2 are treated as 2.
+    pub fn set_fanout(&mut self, fanout: usize) -> &mut Self {
+        self.fanout = std::cmp::max(fanout, 2);
+        self
+    }
+
+    /// Return the maximum number of children for interior nodes, that is
+    /// configured for this instance.
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
     /// Auto rebalance is enabled by default. This has some penalty for write
     /// heavy situations, since every write op will try to rebalance the tree
     /// when it goes too much off-balance. Application can disable
@@ -140,6 +546,41 @@ where
         self.auto_rebalance = rebalance;
         self
     }
+
+    /// Guard the depth of the tree, default is None. When set, an insert
+    /// whose path goes deeper than `max_depth` forces a rebalance of the
+    /// entire tree, irrespective of auto-rebalance and its heuristics, so
+    /// that hostile or unlucky insert patterns cannot degrade reads to O(n).
+    /// `max_depth` should be comfortably larger than `log2(n)`, `n` being
+    /// the number of leaf nodes, else every insert would rebuild the tree.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) -> &mut Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Return the maximum depth of the tree, that is configured for this
+    /// instance.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Strict balance is disabled by default. When enabled, along with
+    /// auto-rebalance, every write operation makes sure that the depth of
+    /// the tree stays within `2 * log2(n) + 2`, where `n` is the number of
+    /// items in the tree over leaf capacity, a lower bound on the number of
+    /// leaf nodes, rebuilding off-balanced subtrees with packed leaf nodes.
+    /// This guards against pathological insert patterns, at the expense of
+    /// more frequent rebuilds, and `split_off` and `append` becoming O(n) in
+    /// the number of leaf nodes. In debug builds the
+    /// invariant is checked after every write operation.
+    pub fn set_strict_balance(&mut self, strict: bool) -> &mut Self
+    where
+        T: Clone,
+    {
+        self.strict_balance = strict;
+        self.rebalance_strict();
+        self
+    }
 }
 
 impl<T> Vector<T>
@@ -159,214 +600,830 @@ where
         self.len() == 0
     }
 
+    /// Return whether both vectors share the same underlying tree, that is,
+    /// one is an unmodified clone of the other. Small vectors, holding up to
+    /// a leaf node worth of items, keep them inline and copy them on clone,
+    /// hence they are pointer-equal only to themselves, or when empty.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        let ok = match (&self.root, &other.root) {
+            (Root::Inline(a), Root::Inline(b)) => a.as_ptr() == b.as_ptr(),
+            (Root::Tree { root: a, tail: x }, Root::Tree { root: b, tail: y }) => {
+                Ref::ptr_eq(a, b) && Ref::ptr_eq(x, y)
+            }
+            (_, _) => false,
+        };
+        ok && self.len == other.len
+    }
+
     /// Return the memory foot-print for this instance.
     pub fn footprint(&self) -> usize {
-        mem::size_of_val(self) + self.root.footprint()
+        let n: usize = self.tree_nodes().iter().map(|node| node.footprint()).sum();
+        mem::size_of_val(self) + self.inline_footprint() + n
+    }
+
+    // memory held by items kept inline.
+    fn inline_footprint(&self) -> usize {
+        match &self.root {
+            Root::Inline(data) => data.capacity() * mem::size_of::<T>(),
+            Root::Tree { .. } => 0,
+        }
+    }
+
+    /// Return the memory footprint held exclusively by this vector, that is,
+    /// excluding nodes shared with any of the `others` vectors. Nodes shared
+    /// within this vector are counted only once. Useful to learn how much
+    /// memory is released by dropping this version, while keeping `others`
+    /// alive. Unlike [Self::footprint], this walks every vector in `others`.
+    pub fn footprint_exclusive(&self, others: &[&Vector<T>]) -> usize {
+        let mut seen: HashSet<*const Node<T>> = HashSet::default();
+
+        let mut stack: Vec<&Ref<Node<T>>> = vec![];
+        for other in others.iter() {
+            stack.extend(other.tree_nodes());
+        }
+        while let Some(node) = stack.pop() {
+            if seen.insert(Ref::as_ptr(node)) {
+                stack.extend(node.child_refs());
+            }
+        }
+
+        let mut n = mem::size_of_val(self) + self.inline_footprint();
+        let mut stack = self.tree_nodes();
+        while let Some(node) = stack.pop() {
+            if seen.insert(Ref::as_ptr(node)) {
+                n += node.local_footprint();
+                stack.extend(node.child_refs());
+            }
+        }
+        n
+    }
+
+    /// Return the memory footprint held by all of `arrs` together, where
+    /// nodes shared among them are counted only once. Useful to learn how
+    /// much memory a set of versions retain. Walks every vector in `arrs`.
+    pub fn footprint_union(arrs: &[&Vector<T>]) -> usize {
+        let mut seen: HashSet<*const Node<T>> = HashSet::default();
+
+        let mut n = 0;
+        for arr in arrs.iter() {
+            n += mem::size_of_val(*arr) + arr.inline_footprint();
+            let mut stack = arr.tree_nodes();
+            while let Some(node) = stack.pop() {
+                if seen.insert(Ref::as_ptr(node)) {
+                    n += node.local_footprint();
+                    stack.extend(node.child_refs());
+                }
+            }
+        }
+        n
+    }
+
+    /// Return, for each vector in `arrs`, the memory footprint held by it
+    /// alone, that is, excluding nodes reachable from any other vector in
+    /// `arrs`, same as [Self::footprint_exclusive] against all the others.
+    /// Walks every vector in `arrs` once, counting the vectors sharing each
+    /// node.
+    pub fn footprint_marginal(arrs: &[&Vector<T>]) -> Vec<usize> {
+        // node -> (last vector reaching it, number of vectors, bytes).
+        let mut nodes: HashMap<*const Node<T>, (usize, usize, usize)> =
+            HashMap::default();
+
+        for (i, arr) in arrs.iter().enumerate() {
+            let mut stack = arr.tree_nodes();
+            while let Some(node) = stack.pop() {
+                let ptr = Ref::as_ptr(node);
+                match nodes.get_mut(&ptr) {
+                    Some((last, _, _)) if *last == i => continue,
+                    Some((last, count, _)) => {
+                        *last = i;
+                        *count += 1;
+                    }
+                    None => {
+                        nodes.insert(ptr, (i, 1, node.local_footprint()));
+                    }
+                }
+                stack.extend(node.child_refs());
+            }
+        }
+
+        let mut ns: Vec<usize> = arrs
+            .iter()
+            .map(|arr| mem::size_of_val(*arr) + arr.inline_footprint())
+            .collect();
+        for (i, count, bytes) in nodes.into_values() {
+            if count == 1 {
+                ns[i] += bytes;
+            }
+        }
+        ns
+    }
+
+    /// Return how much of this vector's tree is shared with `other`, by
+    /// pointer identity, counting nodes shared within this vector only
+    /// once. Useful to quantify the cost of a snapshot, and to verify that
+    /// an operation preserves sharing as intended. Walks both vectors.
+    pub fn shared_fraction(&self, other: &Vector<T>) -> crate::SharingStats {
+        let mut others: HashSet<*const Node<T>> = HashSet::default();
+        let mut stack = other.tree_nodes();
+        while let Some(node) = stack.pop() {
+            if others.insert(Ref::as_ptr(node)) {
+                stack.extend(node.child_refs());
+            }
+        }
+
+        let mut stats = crate::SharingStats::default();
+        if let Root::Inline(data) = &self.root {
+            stats.bytes = self.inline_footprint();
+            stats.n_leafs = usize::from(!data.is_empty());
+        }
+        let mut seen: HashSet<*const Node<T>> = HashSet::default();
+        let mut stack = self.tree_nodes();
+        while let Some(node) = stack.pop() {
+            let ptr = Ref::as_ptr(node);
+            if !seen.insert(ptr) {
+                continue;
+            }
+            let (shared, bytes) = (others.contains(&ptr), node.local_footprint());
+            stats.bytes += bytes;
+            stats.shared_bytes += if shared { bytes } else { 0 };
+            match node.as_ref() {
+                Node::Z { data } if data.is_empty() => (),
+                Node::Z { .. } => {
+                    stats.n_leafs += 1;
+                    stats.shared_leafs += usize::from(shared);
+                }
+                Node::M { .. } | Node::N { .. } => {
+                    stats.n_nodes += 1;
+                    stats.shared_nodes += usize::from(shared);
+                }
+            }
+            stack.extend(node.child_refs());
+        }
+        stats
     }
 
-    /// Return a reference to the element at that position or `IndexFail` error
-    /// if out of bounds.
+    /// Return a reference to the element at that position or `IndexOutOfBounds`
+    /// error if out of bounds.
     pub fn get(&self, index: usize) -> Result<&T> {
-        if index < self.len {
-            Ok(self.root.get(index))
-        } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", index)?
+        let root_len = self.root_len();
+        match self.tree() {
+            Some(root) if index < root_len => root.get(index),
+            _ if index < self.len => Ok(&self.tail_items()[index - root_len]),
+            _ => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len,
+            })?,
+        }
+    }
+
+    // return the items in the leaf node holding `index`, along with the
+    // offset of its first item.
+    pub(crate) fn leaf_of(&self, index: usize) -> Result<(&[T], usize)> {
+        let root_len = self.root_len();
+        match self.tree() {
+            Some(root) if index < root_len => root.leaf_at(index),
+            _ if index < self.len => Ok((self.tail_items(), root_len)),
+            _ => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len,
+            })?,
         }
     }
 
-    /// Insert an element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for copy-on-write insert, especially
-    /// when `Vector` is shared among multiple owners. In cases of
-    /// single-ownership use `insert_mut`, which does in-place mutation, for
-    /// better performance.
+    /// Insert an element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for copy-on-write
+    /// insert, especially when `Vector` is shared among multiple owners. In
+    /// cases of single-ownership use `insert_mut`, which does in-place
+    /// mutation, for better performance.
     pub fn insert(&mut self, off: usize, value: T) -> Result<()>
     where
         T: Clone,
     {
-        let (root, _) = if off <= self.len {
-            let rn = Rebalance::new(self);
-            self.root.insert(off, value, &rn)?
+        let value = if off > self.len {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
         } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", off)?
+            match self.insert_tail(off, value)? {
+                Some(value) => value,
+                None => {
+                    self.verify(off);
+                    return Ok(());
+                }
+            }
         };
 
-        self.root = root;
+        let rn = Rebalance::new(self);
+        let (root, depth) = self.tree_mut().0.insert(off, value, &rn)?;
+
+        self.set_root(root);
         self.len += 1;
+        self.guard_depth(depth);
+        self.check_strict();
+        self.verify(off);
 
         Ok(())
     }
 
-    /// Insert an element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for in-place insert and only when
-    /// `Vector` is under single ownership. In cases of shared-ownership
-    /// use `insert` api which does copy-on-write.
+    /// Insert an element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for in-place
+    /// insert, when `Vector` is under single ownership. In cases of
+    /// shared-ownership use `insert` api which does copy-on-write.
     ///
-    /// **causes panic when used under shared-ownership**
+    /// Nodes along the insertion path that are shared with other versions
+    /// are copied on first touch, leaving other versions untouched.
     pub fn insert_mut(&mut self, off: usize, value: T) -> Result<()>
     where
         T: Clone,
     {
-        if off <= self.len {
-            let rn = Rebalance::new(self);
-
-            let depth = Ref::get_mut(&mut self.root)
-                .unwrap()
-                .insert_mut(off, value, &rn)?;
+        let value = if off > self.len {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
+        } else {
+            match self.insert_tail(off, value)? {
+                Some(value) => value,
+                None => {
+                    self.verify(off);
+                    return Ok(());
+                }
+            }
+        };
 
-            let packed = false;
-            let force = false;
-            let (root, _) =
-                Node::auto_rebalance(Ref::clone(&self.root), depth, packed, force, &rn);
+        // in-place edits are applied on the way down, verify the path
+        // before touching it.
+        self.tree_mut().0.get(off)?;
+        let rn = Rebalance::new(self);
+        let depth = Node::make_mut(self.tree_mut().0).insert_mut(off, value, &rn)?;
 
-            self.root = root;
-            self.len += 1;
-            Ok(())
-        } else {
-            err_at!(IndexFail, msg: "index {} out of bounds", off)?
-        }
+        self.len += 1;
+        self.guard_depth(depth);
+        self.check_strict();
+        self.verify(off);
+        Ok(())
     }
 
-    /// Update the element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for copy-on-write update, especially
-    /// when `Vector` is shared among multiple owners. In cases of
-    /// single-ownership use `update_mut`, which does in-place mutation, for
-    /// better performance.
+    /// Update the element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for copy-on-write
+    /// update, especially when `Vector` is shared among multiple owners. In
+    /// cases of single-ownership use `update_mut`, which does in-place
+    /// mutation, for better performance.
     pub fn update(&mut self, off: usize, value: T) -> Result<T>
     where
         T: Clone,
     {
-        let (root, val) = if off < self.len {
-            self.root.update(off, value)
+        let root_len = self.root_len();
+        let (root, val) = if off < root_len {
+            self.tree_mut().0.update(off, value)?
+        } else if off < self.len {
+            let old = mem::replace(self.tail_item_mut(off - root_len)?, value);
+            return Ok(old);
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
         };
 
-        self.root = root;
+        self.set_root(root);
+        self.verify(off);
         Ok(val)
     }
 
-    /// Update an element at `off` position within the vector, or `IndexFail`
-    /// error if out of bounds. Call this for in-place update and only when
-    /// `Vector` is under single ownership. In cases of shared-ownership
-    /// use `update` api which does copy-on-write.
+    /// Update an element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for in-place
+    /// update, when `Vector` is under single ownership. In cases of
+    /// shared-ownership use `update` api which does copy-on-write.
     ///
-    /// **causes panic when used under shared-ownership**
+    /// Nodes along the path that are shared with other versions are copied
+    /// on first touch, leaving other versions untouched.
     pub fn update_mut(&mut self, off: usize, value: T) -> Result<T>
     where
         T: Clone,
     {
-        if off < self.len {
-            Ok(Ref::get_mut(&mut self.root).unwrap().update_mut(off, value))
+        let root_len = self.root_len();
+        if off < root_len {
+            self.tree_mut().0.get(off)?;
+            let val = Node::make_mut(self.tree_mut().0).update_mut(off, value)?;
+            self.verify(off);
+            Ok(val)
+        } else if off < self.len {
+            Ok(mem::replace(self.tail_item_mut(off - root_len)?, value))
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })
         }
     }
 
-    /// Remove and return the element at `off` position within the vector,
-    /// or `IndexFail` error if out of bounds. Call this for copy-on-write
-    /// remove, especially when `Vector` is shared among multiple owners.
-    /// In cases of single-ownership use `remove_mut`, which does in-place
+    /// Remove and return the element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for copy-on-write
+    /// remove, especially when `Vector` is shared among multiple owners. In
+    /// cases of single-ownership use `remove_mut`, which does in-place
     /// mutation, for better performance.
     pub fn remove(&mut self, off: usize) -> Result<T>
     where
         T: Clone,
     {
-        let (root, val) = if off < self.len {
-            self.root.remove(off)
+        let root_len = self.root_len();
+        let (root, val) = if off < root_len {
+            let rn = Rebalance::new(self);
+            self.tree_mut().0.remove(off, &rn)?
+        } else if off < self.len {
+            let val = self.remove_tail(off - root_len)?;
+            self.len -= 1;
+            self.verify(off);
+            return Ok(val);
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
         };
 
-        self.root = root;
+        self.set_root(root);
         self.len -= 1;
+        self.rebalance_strict();
+        self.verify(off);
         Ok(val)
     }
 
-    /// Remove and return the element at `off` position within the vector,
-    /// or `IndexFail` error if out of bounds. Call this for in-place update
-    /// and only when `Vector` is under single ownership. In cases of
+    /// Remove and return the element at `off` position within the vector, or
+    /// `IndexOutOfBounds` error if out of bounds. Call this for in-place
+    /// remove, when `Vector` is under single ownership. In cases of
     /// shared-ownership use `remove` api which does copy-on-write.
     ///
-    /// **causes panic when used under shared-ownership**
+    /// Nodes along the path that are shared with other versions are copied
+    /// on first touch, leaving other versions untouched.
     pub fn remove_mut(&mut self, off: usize) -> Result<T>
     where
         T: Clone,
     {
-        let val = if off < self.len {
-            Ref::get_mut(&mut self.root).unwrap().remove_mut(off)
+        let root_len = self.root_len();
+        let val = if off < root_len {
+            self.tree_mut().0.get(off)?;
+            let rn = Rebalance::new(self);
+            Node::make_mut(self.tree_mut().0).remove_mut(off, &rn)?
+        } else if off < self.len {
+            self.remove_tail(off - root_len)?
         } else {
-            err_at!(IndexFail, msg: "offset {} out of bounds", off)?
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
         };
 
         self.len -= 1;
+        self.rebalance_strict();
+        self.verify(off);
         Ok(val)
     }
 
-    /// Return an iterator over each element in Vector.
-    pub fn iter(&self) -> Iter<T> {
-        Iter::new(&self.root)
-    }
-
-    /// Splits the collection into two at the given index.
-    ///
-    /// Returns a new Vector containing the elements in the range [at, len).
-    /// After the call, the original vector will be left containing the
-    /// elements [0, at) with its previous capacity unchanged.
+    /// Apply a batch of `edits` and return the edited vector, leaving this
+    /// vector untouched. Offsets in `edits` refer to positions within this
+    /// vector, before applying any of the edits, hence the order of edits does
+    /// not matter, except for inserts at the same offset, which are inserted in
+    /// the order they appear in `edits`. Return `IndexOutOfBounds` error if an
+    /// offset is out of bounds, or `DuplicateEdit` error if an element is
+    /// updated or removed more than once.
     ///
-    /// Optionally, application can call [Self::rebalance] on `self` and
-    /// the returned vector to make the vectors fully balanced.
-    pub fn split_off(&mut self, off: usize) -> Result<Vector<T>>
+    /// Edits are sorted and applied in a single walk of the tree, where each
+    /// node along the paths to edited leaf nodes is copied only once, and
+    /// rest of the tree is shared as is. Hence applying `k` edits costs
+    /// `O(k + log(n) * paths)` instead of `k` copy-on-write operations.
+    pub fn apply(&self, edits: &[Edit<T>]) -> Result<Vector<T>>
     where
         T: Clone,
     {
-        let val = match off {
-            off if off > self.len => {
-                err_at!(IndexFail, msg: "offset {} out of bounds", off)?
-            }
-            off if off == self.len => Vector {
-                len: 0,
-                root: Node::empty_leaf(),
-                auto_rebalance: self.auto_rebalance,
-                leaf_cap: self.leaf_cap,
-            },
-            off => {
-                let (node, root, n) = self.root.split_off(off, self.len);
-                self.root = node;
-                self.len -= n;
-                Vector {
-                    len: n,
-                    root,
-                    auto_rebalance: self.auto_rebalance,
-                    leaf_cap: self.leaf_cap,
+        let mut edits: Vec<&Edit<T>> = edits.iter().collect();
+        edits.sort_by_key(|e| (e.to_offset(), !matches!(e, Edit::Insert(..))));
+
+        let mut prev: Option<usize> = None;
+        for edit in edits.iter() {
+            match edit {
+                Edit::Insert(off, _) if *off > self.len => {
+                    Err(Error::IndexOutOfBounds {
+                        index: *off,
+                        len: self.len,
+                    })?
+                }
+                Edit::Insert(_, _) => (),
+                Edit::Update(off, _) | Edit::Remove(off) if *off >= self.len => {
+                    Err(Error::IndexOutOfBounds {
+                        index: *off,
+                        len: self.len,
+                    })?
                 }
+                Edit::Update(off, _) | Edit::Remove(off) if prev == Some(*off) => {
+                    Err(Error::DuplicateEdit { index: *off })?
+                }
+                Edit::Update(off, _) | Edit::Remove(off) => prev = Some(*off),
             }
-        };
+        }
 
-        Ok(val)
-    }
+        let arr = traced!("ppar::apply", arr, {
+            let mut arr = self.clone();
+            arr.flush_tail();
 
-    /// Join `other` Vector into this vector.
+            let rn = Rebalance::new(self);
+            let (root, _depth) = Node::apply(arr.tree_mut().0, &edits, 0, &rn);
+            let len = root.len();
+
+            let mut arr = self.with_root(root, len);
+            arr.rebalance_strict();
+            arr
+        });
+        Ok(arr)
+    }
+
+    /// Insert a batch of `(offset, value)` items and return the new vector,
+    /// leaving this vector untouched. Offsets refer to positions within this
+    /// vector, before inserting any of the items, and values at the same
+    /// offset are inserted in iteration order. Items are woven in during a
+    /// single walk of the tree, refer [Vector::apply] for details.
+    pub fn insert_many<I>(&self, items: I) -> Result<Vector<T>>
+    where
+        T: Clone,
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let edits: Vec<Edit<T>> = items
+            .into_iter()
+            .map(|(off, value)| Edit::Insert(off, value))
+            .collect();
+        self.apply(&edits)
+    }
+
+    /// Remove elements at `indices` and return the new vector, leaving this
+    /// vector untouched. Indices refer to positions within this vector, before
+    /// removing any of the elements, and need not be sorted. Return
+    /// `IndexOutOfBounds` error if an index is out of bounds, or
+    /// `DuplicateEdit` error if an index is repeated. Elements are removed
+    /// during a single walk of the tree, refer [Vector::apply] for details.
+    pub fn delete_many(&self, indices: &[usize]) -> Result<Vector<T>>
+    where
+        T: Clone,
+    {
+        let edits: Vec<Edit<T>> = indices.iter().map(|off| Edit::Remove(*off)).collect();
+        self.apply(&edits)
+    }
+
+    /// Share leaf nodes holding identical items, by pointer, across this
+    /// vector, and return the deduplicated vector along with the number of
+    /// bytes held by duplicate leaf nodes that were replaced. Useful for
+    /// sparse or repetitive data. To share leaf nodes across versions, or
+    /// across vectors, use [Self::dedup_leaves_with].
+    ///
+    /// Since deduplicated leaf nodes are shared, the `_mut` methods copy
+    /// them on first touch, like they do for nodes shared across versions.
+    pub fn dedup_leaves(&self) -> (Vector<T>, usize)
+    where
+        T: Clone + Eq + Hash,
+    {
+        self.dedup_leaves_with(&mut Interner::default())
+    }
+
+    /// Same as [Self::dedup_leaves], but leaf nodes are interned with
+    /// `interner`, which can be used across several vectors.
+    pub fn dedup_leaves_with(&self, interner: &mut Interner<T>) -> (Vector<T>, usize)
+    where
+        T: Clone + Eq + Hash,
+    {
+        let leafs = Node::collect_leaf_nodes(self.root_with_tail(), false, self.leaf_cap);
+
+        let mut saved = 0;
+        let leafs = leafs
+            .into_iter()
+            .map(|leaf| {
+                let ileaf = interner.intern(Ref::clone(&leaf));
+                if !Ref::ptr_eq(&ileaf, &leaf) {
+                    saved += leaf.footprint();
+                }
+                ileaf
+            })
+            .collect();
+        let (root, _depth) = Node::build_leaf_nodes(leafs, self.fanout);
+
+        (self.with_root(root, self.len), saved)
+    }
+
+    /// Return statistics on the shape of the tree, like its depth and how
+    /// full its leaf nodes are. Useful to decide when to call
+    /// [Self::rebalance] or [Self::compact].
+    pub fn stats(&self) -> crate::Stats {
+        let mut stats = crate::Stats::new(self.len, max_leaf_items::<T>(self.leaf_cap));
+
+        if let Root::Inline(data) = &self.root {
+            stats.add_leaf(1, data.len())
+        }
+        let mut stack: Vec<(&Node<T>, usize)> = self
+            .tree_nodes()
+            .into_iter()
+            .map(|n| (n.as_ref(), 1))
+            .collect();
+        while let Some((node, depth)) = stack.pop() {
+            match node {
+                Node::M { left, right, .. } => {
+                    stats.add_node(depth);
+                    stack.push((left, depth + 1));
+                    stack.push((right, depth + 1));
+                }
+                Node::N { children, .. } => {
+                    stats.add_node(depth);
+                    stack.extend(children.iter().map(|(_, c)| (c.as_ref(), depth + 1)));
+                }
+                Node::Z { data } => stats.add_leaf(depth, data.len()),
+            }
+        }
+
+        stats.finish()
+    }
+
+    /// Verify the invariants of the tree and return its statistics, refer
+    /// [Self::stats], or `Corrupted` error describing the first violation
+    /// found. Verifies that,
+    ///
+    /// * weight of every binary node is the number of items in its left
+    ///   subtree, and its length is the number of items under it.
+    /// * cumulative count of every child in multi-way nodes is the number
+    ///   of items under it and its preceding children.
+    /// * interior nodes have at least two children, and leaf nodes hold at
+    ///   most leaf-capacity items.
+    /// * sum of items across all leaf nodes is the length of the vector,
+    ///   and items held inline fit within a leaf node.
+    /// * depth of the tree does not exceed its number of leaf nodes, and is
+    ///   within the strict bound, refer [Self::set_strict_balance].
+    ///
+    /// This walks the entire tree, useful for testing and fuzzing.
+    pub fn validate(&self) -> Result<crate::Stats> {
+        let max_items = max_leaf_items::<T>(self.leaf_cap);
+
+        let (root, tail) = match &self.root {
+            Root::Inline(data) if data.len() > max_items || data.len() != self.len => {
+                Err(Error::Corrupted {
+                    detail: format!(
+                        "inline holds {} items, len {}, cap {}",
+                        data.len(),
+                        self.len,
+                        max_items
+                    ),
+                })?
+            }
+            Root::Inline(_) => return Ok(self.stats()),
+            Root::Tree { root, tail } => (root, tail),
+        };
+
+        match tail.as_ref() {
+            Node::Z { data } if data.len() <= max_items => (),
+            Node::Z { data } => Err(Error::Corrupted {
+                detail: format!("tail holds {} items, cap {}", data.len(), max_items),
+            })?,
+            _ => Err(Error::Corrupted {
+                detail: "tail is not a leaf node".to_string(),
+            })?,
+        }
+
+        // number of items under each node is computed bottoms-up, children
+        // are visited before their parent, and their counts are stacked.
+        let mut counts: Vec<usize> = vec![];
+        let (mut n_leafs, mut max_depth) = (0, 1);
+        let mut stack = vec![(root.as_ref(), 1, false)];
+        while let Some((node, depth, visited)) = stack.pop() {
+            match node {
+                Node::Z { data } if data.len() > max_items => Err(Error::Corrupted {
+                    detail: format!(
+                        "leaf node holds {} items, cap {}",
+                        data.len(),
+                        max_items
+                    ),
+                })?,
+                Node::Z { data } => {
+                    counts.push(data.len());
+                    n_leafs += 1;
+                    max_depth = std::cmp::max(max_depth, depth);
+                }
+                Node::M { left, right, .. } if !visited => {
+                    stack.push((node, depth, true));
+                    stack.push((left, depth + 1, false));
+                    stack.push((right, depth + 1, false));
+                }
+                Node::M { weight, len, .. } => {
+                    let (left, right) = (counts.pop().unwrap(), counts.pop().unwrap());
+                    if *weight != left {
+                        Err(Error::Corrupted {
+                            detail: format!(
+                                "weight {} for left subtree {}",
+                                weight, left
+                            ),
+                        })?
+                    } else if *len != left + right {
+                        Err(Error::Corrupted {
+                            detail: format!(
+                                "len {} for subtrees {}+{}",
+                                len, left, right
+                            ),
+                        })?
+                    }
+                    node.verify_depth(depth)?;
+                    counts.push(*len)
+                }
+                Node::N { children, .. } if children.len() < 2 => {
+                    Err(Error::Corrupted {
+                        detail: format!(
+                            "interior node at depth {} has {} children",
+                            depth,
+                            children.len()
+                        ),
+                    })?
+                }
+                Node::N { children, .. } if !visited => {
+                    stack.push((node, depth, true));
+                    stack.extend(
+                        children.iter().map(|(_, c)| (c.as_ref(), depth + 1, false)),
+                    );
+                }
+                Node::N { children, .. } => {
+                    let mut weight = 0;
+                    for (i, (w, _)) in children.iter().enumerate() {
+                        weight += counts.pop().unwrap();
+                        if *w != weight {
+                            Err(Error::Corrupted {
+                                detail: format!(
+                                    "child {} weight {} for {}",
+                                    i, w, weight
+                                ),
+                            })?
+                        }
+                    }
+                    node.verify_depth(depth)?;
+                    counts.push(weight)
+                }
+            }
+        }
+
+        let n = counts.pop().unwrap() + tail.len();
+        if n != self.len {
+            Err(Error::Corrupted {
+                detail: format!("leaf nodes hold {} items, len {}", n, self.len),
+            })?
+        }
+
+        if max_depth > n_leafs {
+            Err(Error::Corrupted {
+                detail: format!("depth {} for {} leaf nodes", max_depth, n_leafs),
+            })?
+        } else if self.strict_balance
+            && self.auto_rebalance
+            && !root.is_strictly_balanced(max_items)
+        {
+            Err(Error::Corrupted {
+                detail: format!("strict balance violated, depth {}", max_depth),
+            })?
+        }
+
+        Ok(self.stats())
+    }
+
+    /// Return an iterator over each element in Vector.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(self)
+    }
+
+    /// Return an access finger into this vector, refer [Finger] for details.
+    pub fn finger(&self) -> Finger<'_, T> {
+        Finger {
+            arr: self,
+            leaf: &[],
+            start: 0,
+        }
+    }
+
+    /// Return a mutable access finger into this vector, refer [FingerMut]
+    /// for details.
+    pub fn finger_mut(&mut self) -> FingerMut<'_, T>
+    where
+        T: Clone,
+    {
+        FingerMut {
+            arr: self,
+            path: vec![],
+            start: 0,
+            len: 0,
+            in_tail: false,
+            #[cfg(test)]
+            misses: 0,
+        }
+    }
+
+    /// Splits the collection into two at the given index.
+    ///
+    /// Returns a new Vector containing the elements in the range [at, len).
+    /// After the call, the original vector will be left containing the
+    /// elements [0, at) with its previous capacity unchanged.
+    ///
+    /// Optionally, application can call [Self::rebalance] on `self` and
+    /// the returned vector to make the vectors fully balanced.
+    pub fn split_off(&mut self, off: usize) -> Result<Vector<T>>
+    where
+        T: Clone,
+    {
+        if off > self.len {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
+        }
+
+        let val = traced!("ppar::split_off", arr, {
+            self.flush_tail();
+
+            let mut val = match off {
+                off if off == self.len => self.with_root(Node::empty_leaf(), 0),
+                off => {
+                    let len = self.len;
+                    let (node, root, n) = self.tree_mut().0.split_off(off, len);
+                    *self.tree_mut().0 = node;
+                    self.len -= n;
+                    self.with_root(root, n)
+                }
+            };
+
+            self.rebalance_strict();
+            val.rebalance_strict();
+            val
+        });
+
+        Ok(val)
+    }
+
+    /// Join `other` Vector into this vector.
     ///
     /// Call [Self::rebalance] on `self` to make the vectors fully balanced.
     pub fn append(&mut self, other: Vector<T>)
     where
         T: Clone,
     {
-        let other = if other.leaf_cap != self.leaf_cap {
-            println!("append long");
+        let mut other = if other.leaf_cap != self.leaf_cap {
             let arr: Vec<T> = other.into();
             Vector::from_slice(&arr, Some(self.leaf_cap))
         } else {
             other
         };
 
-        let root = {
-            let left = Ref::clone(&self.root);
-            let right = Ref::clone(&other.root);
-            Node::newm(left, right, self.len)
+        let _arr = traced!("ppar::append", arr, {
+            self.flush_tail();
+
+            let (right, tail) = {
+                let (root, tail) = other.tree_mut();
+                (Ref::clone(root), Ref::clone(tail))
+            };
+            let root = Node::newm(Ref::clone(self.tree_mut().0), right, self.len);
+            self.root = Root::Tree { root, tail };
+            self.len += other.len;
+            self.rebalance_strict();
+            &*self
+        });
+    }
+
+    /// Concatenate `arrs` into a single vector, linking their leaf nodes
+    /// under new interior nodes, without copying items. Configuration is
+    /// taken from the first vector, vectors with a different leaf node size
+    /// are rebuilt to match it. Useful for assembling documents from
+    /// persistent fragments, the resulting tree is balanced and fragments
+    /// continue to share their leaf nodes with it.
+    pub fn concat_all<I>(arrs: I) -> Vector<T>
+    where
+        T: Clone,
+        I: IntoIterator<Item = Vector<T>>,
+    {
+        let mut arrs = arrs.into_iter();
+        let first = match arrs.next() {
+            Some(first) => first,
+            None => return Vector::default(),
         };
-        self.root = root;
-        self.len += other.len;
+
+        traced!("ppar::concat_all", arr, {
+            let mut leafs = vec![];
+            let mut len = 0;
+            for arr in std::iter::once(first.clone()).chain(arrs) {
+                let arr = if arr.leaf_cap != first.leaf_cap {
+                    let vals: Vec<T> = arr.into();
+                    Vector::from_slice(&vals, Some(first.leaf_cap))
+                } else {
+                    arr
+                };
+                let root = arr.root_with_tail();
+                let iter =
+                    Node::collect_leaf_nodes(root, false, arr.leaf_cap).into_iter();
+                leafs.extend(iter.filter(|leaf| leaf.len() > 0));
+                len += arr.len;
+            }
+            let (root, _depth) = Node::build_leaf_nodes(leafs, first.fanout);
+            first.with_root(root, len)
+        })
     }
 
     /// When auto-rebalance is disabled, use this method to rebalance the tree.
@@ -376,43 +1433,187 @@ where
     where
         T: Clone,
     {
-        let rn = Rebalance::new(self);
-        let root = Ref::clone(&self.root);
-        let (root, _depth) = Node::auto_rebalance(root, 0, packed, true, &rn);
-        let val = Vector {
-            len: self.len,
-            root,
-            auto_rebalance: self.auto_rebalance,
-            leaf_cap: self.leaf_cap,
+        let arr = traced!("ppar::rebalance", arr, {
+            let rn = Rebalance::new(self);
+            let root = self.root_with_tail();
+            let (root, _) = Node::auto_rebalance(root, 0, self.len, packed, true, &rn);
+            self.with_root(root, self.len)
+        });
+        Ok(arr)
+    }
+
+    /// Rewrite the vector with fully packed leaf nodes, each allocated to
+    /// its exact size, and a freshly built balanced tree. Unlike
+    /// [Self::rebalance], which reuses existing leaf nodes as is, every item
+    /// is copied into new leaf nodes, releasing memory held by partially
+    /// filled and over-allocated leaf nodes. Useful for long-lived vectors
+    /// that have accumulated fragmentation, compare [Self::footprint] before
+    /// and after to learn the savings.
+    pub fn compact(&self) -> Vector<T>
+    where
+        T: Clone,
+    {
+        traced!("ppar::compact", arr, {
+            let n = max_leaf_items::<T>(self.leaf_cap);
+            let leafs = Self::chunk_leaf_nodes(self.iter().cloned(), n);
+            let (root, _depth) = Node::build_leaf_nodes(leafs, self.fanout);
+            self.with_root(root, self.len)
+        })
+    }
+
+    // in strict mode, rebuild the tree if it has gone off-balance.
+    fn rebalance_strict(&mut self)
+    where
+        T: Clone,
+    {
+        let n_items = max_leaf_items::<T>(self.leaf_cap);
+        match self.tree() {
+            Some(root) if self.strict_balance && !root.is_strictly_balanced(n_items) => {
+                let root = Ref::clone(root);
+                let leafs = Node::collect_leaf_nodes(root, true, self.leaf_cap);
+                *self.tree_mut().0 = Node::build_leaf_nodes(leafs, self.fanout).0;
+            }
+            _ => (),
+        }
+        self.check_strict();
+    }
+
+    // rebalance the entire tree, if an insert at `depth` went deeper than
+    // the configured max_depth.
+    fn guard_depth(&mut self, depth: usize)
+    where
+        T: Clone,
+    {
+        match self.max_depth {
+            Some(max_depth) if depth > max_depth => {
+                let rn = Rebalance::new(self);
+                let root = Ref::clone(self.tree_mut().0);
+                let n = root.len();
+                *self.tree_mut().0 = Node::auto_rebalance(root, 0, n, false, true, &rn).0;
+            }
+            _ => (),
+        }
+    }
+
+    #[inline]
+    fn check_strict(&self) {
+        debug_assert!(
+            !self.strict_balance
+                || !self.auto_rebalance
+                || self.tree().into_iter().all(|root| {
+                    root.is_strictly_balanced(max_leaf_items::<T>(self.leaf_cap))
+                }),
+            "strict balance violated"
+        );
+    }
+
+    // verify nodes along the path to `off`, after an edit at `off`, so that
+    // corruption is caught by the edit introducing it. Enabled with the
+    // `verify` feature, in debug builds. Refer Self::validate for checking
+    // the entire tree.
+    #[cfg(feature = "verify")]
+    fn verify(&self, off: usize) {
+        let max_items = max_leaf_items::<T>(self.leaf_cap);
+        let n = self.tail_items().len();
+        debug_assert!(n <= max_items, "tail holds {}", n);
+        let root = match self.tree() {
+            Some(root) => {
+                debug_assert_eq!(root.len() + n, self.len, "len mismatch");
+                root
+            }
+            None => return debug_assert_eq!(n, self.len, "len mismatch"),
         };
-        Ok(val)
+
+        let mut node = root.as_ref();
+        let mut off = std::cmp::min(off, node.len().saturating_sub(1));
+        loop {
+            debug_assert_eq!(node.depth(), node.joined_depth(), "depth mismatch");
+            node = match node {
+                Node::M {
+                    weight,
+                    len,
+                    left,
+                    right,
+                    ..
+                } => {
+                    debug_assert_eq!(*weight, left.len(), "weight mismatch");
+                    debug_assert_eq!(*len, left.len() + right.len(), "len mismatch");
+                    if off < *weight {
+                        left
+                    } else {
+                        off -= weight;
+                        right
+                    }
+                }
+                Node::N { children, .. } => {
+                    debug_assert!(!children.is_empty(), "empty interior node");
+                    let mut weight = 0;
+                    for (w, child) in children.iter() {
+                        weight += child.len();
+                        debug_assert_eq!(*w, weight, "child weight mismatch");
+                    }
+                    let (i, start) = Node::locate(children, off);
+                    off -= start;
+                    &children[i].1
+                }
+                Node::Z { data } => {
+                    debug_assert!(data.len() <= max_items, "leaf holds {}", data.len());
+                    debug_assert!(off <= data.len(), "offset {} out of leaf", off);
+                    break;
+                }
+            };
+        }
     }
 
+    #[cfg(not(feature = "verify"))]
+    #[inline]
+    fn verify(&self, _off: usize) {}
+
     // return only nodes that is referenced in multiple-versions. and
     // the total number of nodes in the tree.
     #[cfg(test)]
     pub fn fetch_multiversions(&self) -> (Vec<*const u8>, usize) {
         let mut acc = vec![];
-        let n = self.root.fetch_multiversions(&mut acc);
+        let n = match self.tree() {
+            Some(root) => root.fetch_multiversions(&mut acc),
+            None => 0,
+        };
         (acc, n)
     }
 
+    #[cfg(test)]
+    pub fn depth(&self) -> usize {
+        self.tree().map(|root| root.depth()).unwrap_or(1)
+    }
+
     #[cfg(test)]
     #[allow(dead_code)]
     pub fn pretty_print(&self) {
-        self.root.pretty_print("".to_string(), self.len)
+        if let Some(root) = self.tree() {
+            root.pretty_print("".to_string(), self.len)
+        }
     }
 }
 
-enum Node<T>
+pub(crate) enum Node<T>
 where
     T: Sized,
 {
     M {
         weight: usize,
+        // number of items under this node, so that len() is O(1).
+        len: usize,
+        // depth of this subtree, so that strict balance is checked in O(1).
+        depth: u32,
         left: Ref<Node<T>>,
         right: Ref<Node<T>>,
     },
+    // multi-way interior node, each child is paired with the cumulative
+    // number of items, up to and including that child.
+    N {
+        children: Vec<(usize, Ref<Node<T>>)>,
+        depth: u32,
+    },
     Z {
         data: Vec<T>,
     },
@@ -431,39 +1632,143 @@ impl<T> Node<T>
 where
     T: Sized,
 {
-    fn newm(left: Ref<Node<T>>, right: Ref<Node<T>>, weight: usize) -> Ref<Node<T>> {
+    pub(crate) fn newm(
+        left: Ref<Node<T>>,
+        right: Ref<Node<T>>,
+        weight: usize,
+    ) -> Ref<Node<T>> {
+        let len = weight + right.len();
+        let depth = join_depth([left.depth(), right.depth()]);
         Ref::new(Node::M {
+            weight,
+            len,
+            depth,
             left,
             right,
-            weight,
         })
     }
 
-    fn empty_leaf() -> Ref<Node<T>> {
+    // multi-way interior node over `children`, a lone child is returned as is.
+    pub(crate) fn newn(children: Vec<Ref<Node<T>>>) -> Ref<Node<T>> {
+        match children.len() {
+            0 => Node::empty_leaf(),
+            1 => children.into_iter().next().unwrap(),
+            _ => {
+                let mut weight = 0;
+                let children = children
+                    .into_iter()
+                    .map(|child| {
+                        weight += child.len();
+                        (weight, child)
+                    })
+                    .collect();
+                Ref::new(Node::from_children(children))
+            }
+        }
+    }
+
+    // multi-way interior node over `children`, already paired with their
+    // cumulative number of items.
+    fn from_children(children: Vec<(usize, Ref<Node<T>>)>) -> Node<T> {
+        let depth = join_depth(children.iter().map(|(_, c)| c.depth()));
+        Node::N { children, depth }
+    }
+
+    pub(crate) fn empty_leaf() -> Ref<Node<T>> {
         Ref::new(Node::Z {
             data: Vec::default(),
         })
     }
 
-    fn len(&self) -> usize {
+    pub(crate) fn len(&self) -> usize {
         match self {
-            Node::M { weight, right, .. } => weight + right.len(),
+            Node::M { len, .. } => *len,
+            Node::N { children, .. } => children.last().map(|(w, _)| *w).unwrap_or(0),
             Node::Z { data } => data.len(),
         }
     }
 
+    // return the index of the child holding `off`, along with the number
+    // of items before that child. Offset past the last item falls on the
+    // last child.
+    fn locate(children: &[(usize, Ref<Node<T>>)], off: usize) -> (usize, usize) {
+        let i = children.partition_point(|(w, _)| *w <= off);
+        let i = std::cmp::min(i, children.len().saturating_sub(1));
+        match i {
+            0 => (i, 0),
+            i => (i, children[i - 1].0),
+        }
+    }
+
+    fn child_refs(&self) -> Vec<&Ref<Node<T>>> {
+        match self {
+            Node::M { left, right, .. } => vec![left, right],
+            Node::N { children, .. } => children.iter().map(|(_, c)| c).collect(),
+            Node::Z { .. } => vec![],
+        }
+    }
+
+    fn child_nodes(&self) -> Vec<Ref<Node<T>>> {
+        match self {
+            Node::M { left, right, .. } => vec![Ref::clone(left), Ref::clone(right)],
+            Node::N { children, .. } => {
+                children.iter().map(|(_, c)| Ref::clone(c)).collect()
+            }
+            Node::Z { .. } => vec![],
+        }
+    }
+
+    // copy of this node, for interior nodes the child nodes are shared
+    // with the copy.
     fn cow(&self) -> Node<T>
     where
         T: Clone,
     {
         match self {
+            Node::M {
+                weight,
+                len,
+                depth,
+                left,
+                right,
+            } => Node::M {
+                weight: *weight,
+                len: *len,
+                depth: *depth,
+                left: Ref::clone(left),
+                right: Ref::clone(right),
+            },
+            Node::N { children, depth } => Node::N {
+                children: children.clone(),
+                depth: *depth,
+            },
             Node::Z { data } => Node::Z {
                 data: data.to_vec(),
             },
-            _ => unreachable!(),
         }
     }
 
+    // return a mutable reference to the node held by `node`, a node shared
+    // with other versions is copied on first touch.
+    fn make_mut(node: &mut Ref<Node<T>>) -> &mut Node<T>
+    where
+        T: Clone,
+    {
+        if Ref::get_mut(node).is_none() {
+            *node = Ref::new(node.cow());
+        }
+        Ref::get_mut(node).unwrap()
+    }
+
+    // return the node held by `node`, copied if it is shared with other
+    // versions.
+    fn into_node(node: Ref<Node<T>>) -> Node<T>
+    where
+        T: Clone,
+    {
+        Ref::try_unwrap(node).unwrap_or_else(|node| node.cow())
+    }
+
     fn pack(&mut self, other: &Self, cap: usize) -> Option<Self>
     where
         T: Clone,
@@ -474,6 +1779,7 @@ where
             (Node::Z { data }, Node::Z { data: other }) => {
                 let other = if data.len() < cap {
                     let n = min(cap - data.len(), other.len());
+                    data.reserve_exact(n);
                     data.extend_from_slice(&other[..n]);
                     &other[n..]
                 } else {
@@ -492,224 +1798,687 @@ where
     }
 
     fn footprint(&self) -> usize {
+        self.local_footprint()
+            + match self {
+                Node::Z { .. } => 0,
+                Node::M { left, right, .. } => left.footprint() + right.footprint(),
+                Node::N { children, .. } => {
+                    children.iter().map(|(_, c)| c.footprint()).sum()
+                }
+            }
+    }
+
+    // footprint of this node alone, excluding its child nodes.
+    fn local_footprint(&self) -> usize {
         let n = mem::size_of_val(self);
         n + match self {
             Node::Z { data } => data.capacity() * mem::size_of::<T>(),
-            Node::M { left, right, .. } => left.footprint() + right.footprint(),
+            Node::M { .. } => 0,
+            Node::N { children, .. } => {
+                children.capacity() * mem::size_of::<(usize, Ref<Node<T>>)>()
+            }
+        }
+    }
+
+    // return the index of the child holding `off`, along with the number
+    // of items before that child, only for interior nodes.
+    pub(crate) fn locate_child(&self, off: usize) -> (usize, usize) {
+        match self {
+            Node::M { weight, .. } if off < *weight => (0, 0),
+            Node::M { weight, .. } => (1, *weight),
+            Node::N { children, .. } => Node::locate(children, off),
+            Node::Z { .. } => unreachable!(),
         }
     }
 
-    fn get(&self, off: usize) -> &T {
+    pub(crate) fn child(&self, i: usize) -> &Ref<Node<T>> {
         match self {
-            Node::M { weight, left, .. } if off < *weight => left.get(off),
-            Node::M { weight, right, .. } => right.get(off - *weight),
-            Node::Z { data } => &data[off],
+            Node::M { left, .. } if i == 0 => left,
+            Node::M { right, .. } => right,
+            Node::N { children, .. } => &children[i].1,
+            Node::Z { .. } => unreachable!(),
         }
     }
 
-    // return (value, max_depth)
-    fn insert(&self, off: usize, val: T, rn: &Rebalance) -> Result<(Ref<Node<T>>, usize)>
+    // same as child(), for in-place edits, refer make_mut().
+    fn child_mut(&mut self, i: usize) -> &mut Node<T>
     where
         T: Clone,
     {
-        let (node, depth) = match self {
-            Node::M {
-                weight,
-                left,
-                right,
-            } => {
-                let weight = *weight;
-                let (weight, left, right, depth) = if off < weight {
-                    let (left, depth) = left.insert(off, val, rn)?;
-                    (weight + 1, left, Ref::clone(right), depth)
-                } else {
-                    let off = off - weight;
-                    let (right, depth) = right.insert(off, val, rn)?;
-                    (weight, Ref::clone(left), right, depth)
-                };
-                (Node::newm(left, right, weight), depth + 1)
+        let child = match self {
+            Node::M { left, .. } if i == 0 => left,
+            Node::M { right, .. } => right,
+            Node::N { children, .. } => &mut children[i].1,
+            Node::Z { .. } => unreachable!(),
+        };
+        Node::make_mut(child)
+    }
+
+    // descend along child indices in `path`, for in-place edits.
+    fn descend_mut(&mut self, path: &[usize]) -> &mut Node<T>
+    where
+        T: Clone,
+    {
+        let mut node = self;
+        for i in path.iter() {
+            node = node.child_mut(*i);
+        }
+        node
+    }
+
+    // Read path is panic free, if the tree is corrupted, like weights
+    // that don't match the items under the node, `Corrupted` error is
+    // returned instead of indexing out of bounds or underflow.
+
+    fn get(&self, off: usize) -> Result<&T> {
+        let (data, start) = self.leaf_at(off)?;
+        match off.checked_sub(start).and_then(|off| data.get(off)) {
+            Some(val) => Ok(val),
+            None => Err(Node::<T>::overrun(off, start, data.len())),
+        }
+    }
+
+    // return the items in the leaf node holding `off`, along with the
+    // offset of its first item, relative to this node.
+    fn leaf_at(&self, off: usize) -> Result<(&[T], usize)> {
+        let (mut node, mut start) = (self, 0);
+        loop {
+            match node {
+                Node::Z { data } => break Ok((data, start)),
+                Node::N { children, .. } if children.is_empty() => {
+                    let detail = format!("empty interior node for offset {}", off);
+                    break Err(Error::Corrupted { detail });
+                }
+                _ => match off.checked_sub(start) {
+                    Some(off) => {
+                        let (i, n) = node.locate_child(off);
+                        start += n;
+                        node = node.child(i);
+                    }
+                    None => break Err(Node::<T>::overrun(off, start, 0)),
+                },
             }
-            Node::Z { data } if data.len() < max_leaf_items::<T>(rn.leaf_cap) => {
-                let mut ndata = data[..off].to_vec();
-                ndata.push(val);
-                ndata.extend_from_slice(&data[off..]);
-                (Ref::new(Node::Z { data: ndata }), 1)
+        }
+    }
+
+    fn overrun(off: usize, start: usize, n: usize) -> Error {
+        let detail = format!("offset {} beyond leaf node {}+{}", off, start, n);
+        Error::Corrupted { detail }
+    }
+
+    // Write path is panic free as well, offsets are descended with checked
+    // arithmetic and weights are updated with saturating arithmetic. In-place
+    // edits update weights on the way down, hence callers verify the path,
+    // using get(), before the descent.
+
+    // same as locate_child(), failing on interior nodes without children.
+    fn locate_step(&self, off: usize) -> Result<(usize, usize)> {
+        match self {
+            Node::N { children, .. } if children.is_empty() => {
+                let detail = format!("empty interior node for offset {}", off);
+                Err(Error::Corrupted { detail })
+            }
+            _ => Ok(self.locate_child(off)),
+        }
+    }
+
+    // return `off` relative to the child starting at `start`.
+    fn descend(off: usize, start: usize) -> Result<usize> {
+        off.checked_sub(start)
+            .ok_or_else(|| Node::<T>::overrun(off, start, 0))
+    }
+
+    // paths only hold interior nodes, a leaf node on the path means that the
+    // tree has changed under the descent.
+    fn leaf_on_path() -> Error {
+        let detail = "leaf node along the path of a write".to_string();
+        Error::Corrupted { detail }
+    }
+
+    // Return (value, depth), where depth is the depth of the insertion path.
+    //
+    // Subtrees along the insertion path, that go too much off-balance, are
+    // rebuilt locally, without touching rest of the tree. Like all write
+    // operations, the path is walked with an explicit stack, so that trees
+    // that have gone deep, with auto-rebalance disabled, won't overflow
+    // the call stack.
+    fn insert(
+        &self,
+        mut off: usize,
+        val: T,
+        rn: &Rebalance,
+    ) -> Result<(Ref<Node<T>>, usize)>
+    where
+        T: Clone,
+    {
+        let (mut path, mut node) = (vec![], self);
+        let (leaf, depth) = loop {
+            match node {
+                Node::Z { data } if off > data.len() => {
+                    Err(Node::<T>::overrun(off, 0, data.len()))?
+                }
+                Node::Z { data } if data.len() < max_leaf_items::<T>(rn.leaf_cap) => {
+                    let data = Node::leaf_insert(data, off, val);
+                    break (Ref::new(Node::Z { data }), 1);
+                }
+                Node::Z { data } => break (Self::split_insert(data, off, val), 2),
+                _ => {
+                    let (i, start) = node.locate_step(off)?;
+                    path.push((node, i));
+                    off = Node::<T>::descend(off, start)?;
+                    node = node.child(i);
+                }
             }
-            Node::Z { data } => (Self::split_insert(data, off, val), 2),
         };
 
-        let (node, depth) = Node::auto_rebalance(node, depth, false, false, rn);
+        let (mut node, mut depth) = (leaf, depth);
+        while let Some((parent, i)) = path.pop() {
+            let parent = match parent {
+                Node::M { weight, right, .. } if i == 0 => {
+                    Node::newm(node, Ref::clone(right), weight.saturating_add(1))
+                }
+                Node::M { weight, left, .. } => {
+                    Node::newm(Ref::clone(left), node, *weight)
+                }
+                Node::N { children, .. } => {
+                    let mut children = children.clone();
+                    children[i].1 = node;
+                    let iter = children[i..].iter_mut();
+                    iter.for_each(|(w, _)| *w = w.saturating_add(1));
+                    Ref::new(Node::from_children(children))
+                }
+                Node::Z { .. } => Err(Node::<T>::leaf_on_path())?,
+            };
+            let n = parent.len();
+            let res = Node::auto_rebalance(parent, depth + 1, n, false, false, rn);
+            node = res.0;
+            depth = res.1;
+        }
 
         Ok((node, depth))
     }
 
-    fn insert_mut(&mut self, off: usize, val: T, rn: &Rebalance) -> Result<usize>
+    // in-place version of insert(), return depth of the insertion path.
+    // Caller shall verify the path to `off`, weights are incremented on the
+    // way down.
+    fn insert_mut(&mut self, mut off: usize, val: T, rn: &Rebalance) -> Result<usize>
     where
         T: Clone,
     {
-        let depth = match self {
-            Node::M {
-                weight,
-                left,
-                right,
-            } => {
-                if off < *weight {
-                    let depth = Ref::get_mut(left).unwrap().insert_mut(off, val, rn)?;
-                    *weight += 1;
-                    depth
-                } else {
-                    let off = off - *weight;
-                    Ref::get_mut(right).unwrap().insert_mut(off, val, rn)?
+        // (child-index, number-of-items) for interior nodes along the path.
+        let mut path: Vec<(usize, usize)> = vec![];
+        let mut node = &mut *self;
+        let mut depth = loop {
+            let (i, start) = match node {
+                Node::Z { data } if off > data.len() => {
+                    Err(Node::<T>::overrun(off, 0, data.len()))?
                 }
-            }
-            Node::Z { data } if data.len() < max_leaf_items::<T>(rn.leaf_cap) => {
-                data.insert(off, val);
-                1
-            }
-            Node::Z { data } => {
-                *self = Ref::try_unwrap(Self::split_insert(data, off, val))
-                    .ok()
-                    .unwrap();
-                2
-            }
+                Node::Z { data } if data.len() < rn.n_items => {
+                    Node::leaf_insert_mut(data, off, val, rn.n_items);
+                    break 1;
+                }
+                Node::Z { data } => {
+                    let leaf = Self::split_insert(data, off, val);
+                    *node = Node::into_node(leaf);
+                    break 2;
+                }
+                Node::M { weight, len, .. } => {
+                    *len = len.saturating_add(1);
+                    match off < *weight {
+                        true => {
+                            *weight = weight.saturating_add(1);
+                            (0, 0)
+                        }
+                        false => (1, *weight),
+                    }
+                }
+                Node::N { children, .. } if children.is_empty() => {
+                    Err(Node::<T>::overrun(off, 0, 0))?
+                }
+                Node::N { children, .. } => {
+                    let (i, start) = Node::locate(children, off);
+                    let iter = children[i..].iter_mut();
+                    iter.for_each(|(w, _)| *w = w.saturating_add(1));
+                    (i, start)
+                }
+            };
+            path.push((i, node.len()));
+            off = Node::<T>::descend(off, start)?;
+            node = node.child_mut(i);
+        };
+
+        // a split leaf node, or a rebuilt subtree, changes the depth of
+        // every subtree above it, upto the top most of them.
+        let levels: Vec<usize> = path.iter().map(|(i, _)| *i).collect();
+        let mut top = match depth {
+            1 => None,
+            _ => Some(levels.len()),
         };
+
+        // walk back up the path and rebuild the smallest subtree that has
+        // gone too much off-balance.
+        while let Some((_, n)) = path.pop() {
+            depth += 1;
+            if rn.auto_rebalance && rn.can_rebalance(depth, n) {
+                depth = self.descend_mut(&levels[..path.len()]).rebuild_mut(rn);
+                top = Some(path.len());
+            }
+        }
+
+        if let Some(top) = top {
+            self.redepth_path(&levels[..top]);
+        }
+
         Ok(depth)
     }
 
-    fn update(&self, off: usize, value: T) -> (Ref<Node<T>>, T)
+    // rebuild the subtree under this node, in-place, return its depth.
+    fn rebuild_mut(&mut self, rn: &Rebalance) -> usize
     where
         T: Clone,
     {
-        match self {
-            Node::M {
-                weight,
-                left,
-                right,
-            } if off < *weight => {
-                let (left, old) = left.update(off, value);
-                (Node::newm(left, Ref::clone(right), *weight), old)
-            }
-            Node::M {
-                weight,
-                left,
-                right,
-            } => {
-                let (right, old) = right.update(off - *weight, value);
-                (Node::newm(Ref::clone(left), right, *weight), old)
-            }
-            Node::Z { data } => {
-                let old = data[off].clone();
+        let cap = rn.leaf_cap;
+        let leafs: Vec<Ref<Node<T>>> = self
+            .child_nodes()
+            .into_iter()
+            .flat_map(|child| Node::collect_leaf_nodes(child, false, cap))
+            .collect();
+        let leafs = match rn.strict {
+            true => Node::pack_leaf_nodes(leafs, cap),
+            false => leafs,
+        };
+        let (node, depth) = Node::build_leaf_nodes(leafs, rn.fanout);
+        mem::replace(self, Node::into_node(node)).release();
+        depth
+    }
 
-                let mut data = data.to_vec();
-                data[off] = value;
-                (Ref::new(Node::Z { data }), old)
+    fn update(&self, mut off: usize, value: T) -> Result<(Ref<Node<T>>, T)>
+    where
+        T: Clone,
+    {
+        let (mut path, mut node) = (vec![], self);
+        let (leaf, old) = loop {
+            match node {
+                Node::Z { data } if off >= data.len() => {
+                    Err(Node::<T>::overrun(off, 0, data.len()))?
+                }
+                Node::Z { data } => {
+                    let mut data = data.to_vec();
+                    let old = mem::replace(&mut data[off], value);
+                    break (Ref::new(Node::Z { data }), old);
+                }
+                _ => {
+                    let (i, start) = node.locate_step(off)?;
+                    path.push((node, i));
+                    off = Node::<T>::descend(off, start)?;
+                    node = node.child(i);
+                }
             }
+        };
+
+        let mut node = leaf;
+        while let Some((parent, i)) = path.pop() {
+            node = match parent {
+                Node::M { weight, right, .. } if i == 0 => {
+                    Node::newm(node, Ref::clone(right), *weight)
+                }
+                Node::M { weight, left, .. } => {
+                    Node::newm(Ref::clone(left), node, *weight)
+                }
+                Node::N { children, .. } => {
+                    let mut children = children.clone();
+                    children[i].1 = node;
+                    Ref::new(Node::from_children(children))
+                }
+                Node::Z { .. } => Err(Node::<T>::leaf_on_path())?,
+            };
         }
+
+        Ok((node, old))
     }
 
-    fn update_mut(&mut self, off: usize, value: T) -> T
+    fn update_mut(&mut self, mut off: usize, value: T) -> Result<T>
     where
         T: Clone,
     {
-        match self {
-            Node::M { weight, left, .. } if off < *weight => {
-                Ref::get_mut(left).unwrap().update_mut(off, value)
+        let mut node = self;
+        loop {
+            match node {
+                Node::Z { data } => {
+                    let n = data.len();
+                    match data.get_mut(off) {
+                        Some(item) => break Ok(mem::replace(item, value)),
+                        None => break Err(Node::<T>::overrun(off, 0, n)),
+                    }
+                }
+                _ => {
+                    let (i, start) = node.locate_step(off)?;
+                    off = Node::<T>::descend(off, start)?;
+                    node = node.child_mut(i);
+                }
             }
-            Node::M { weight, right, .. } => Ref::get_mut(right)
-                .unwrap()
-                .update_mut(off - *weight, value),
-            Node::Z { data } => {
-                let old = data[off].clone();
-                data[off] = value;
-                old
+        }
+    }
+
+    // leaf nodes that go underfull, on remove, are merged with their
+    // sibling leaf node or their items are redistributed with them.
+    fn remove(&self, mut off: usize, rn: &Rebalance) -> Result<(Ref<Node<T>>, T)>
+    where
+        T: Clone,
+    {
+        let (mut path, mut node) = (vec![], self);
+        let (leaf, old) = loop {
+            match node {
+                Node::Z { data } if off >= data.len() => {
+                    Err(Node::<T>::overrun(off, 0, data.len()))?
+                }
+                Node::Z { data } => {
+                    let ndata = Node::leaf_remove(data, off);
+                    break (Ref::new(Node::Z { data: ndata }), data[off].clone());
+                }
+                _ => {
+                    let (i, start) = node.locate_step(off)?;
+                    path.push((node, i));
+                    off = Node::<T>::descend(off, start)?;
+                    node = node.child(i);
+                }
             }
+        };
+
+        let mut node = leaf;
+        while let Some((parent, i)) = path.pop() {
+            node = match parent {
+                Node::M {
+                    weight,
+                    left,
+                    right,
+                    ..
+                } => {
+                    let (left, right, weight) = match i {
+                        0 => (node, Ref::clone(right), weight.saturating_sub(1)),
+                        _ => (Ref::clone(left), node, *weight),
+                    };
+                    match Node::merge_leafs(&left, &right, rn) {
+                        Some(leafs) => Node::join_leafs(leafs),
+                        None => Node::newm(left, right, weight),
+                    }
+                }
+                Node::N { children, .. } => {
+                    let mut children = children.clone();
+                    children[i].1 = node;
+                    let iter = children[i..].iter_mut();
+                    iter.for_each(|(w, _)| *w = w.saturating_sub(1));
+                    match children[i].1.is_underfull(rn) {
+                        true => Node::merge_children(children, i, rn),
+                        false => Ref::new(Node::from_children(children)),
+                    }
+                }
+                Node::Z { .. } => Err(Node::<T>::leaf_on_path())?,
+            };
         }
+
+        Ok((node, old))
     }
 
-    fn remove(&self, off: usize) -> (Ref<Node<T>>, T)
+    // in-place version of remove(). Caller shall verify the path to `off`,
+    // weights are decremented on the way down.
+    fn remove_mut(&mut self, mut off: usize, rn: &Rebalance) -> Result<T>
     where
         T: Clone,
     {
-        match self {
-            Node::M {
-                weight,
-                left,
-                right,
-            } => {
-                let weight = *weight;
-                if off < weight {
-                    let (left, old) = left.remove(off);
-                    (Node::newm(left, Ref::clone(right), weight - 1), old)
-                } else {
-                    let (right, old) = right.remove(off - weight);
-                    (Node::newm(Ref::clone(left), right, weight), old)
+        // child-index for interior nodes along the path.
+        let mut path: Vec<usize> = vec![];
+        let mut node = &mut *self;
+        let old = loop {
+            let (i, start) = match node {
+                Node::Z { data } if off >= data.len() => {
+                    Err(Node::<T>::overrun(off, 0, data.len()))?
+                }
+                Node::Z { data } => {
+                    let old = data.remove(off);
+                    if (data.len() * 2) < data.capacity() {
+                        data.shrink_to_fit()
+                    }
+                    break old;
+                }
+                Node::M { weight, len, .. } => {
+                    *len = len.saturating_sub(1);
+                    match off < *weight {
+                        true => {
+                            *weight -= 1;
+                            (0, 0)
+                        }
+                        false => (1, *weight),
+                    }
+                }
+                Node::N { children, .. } if children.is_empty() => {
+                    Err(Node::<T>::overrun(off, 0, 0))?
+                }
+                Node::N { children, .. } => {
+                    let (i, start) = Node::locate(children, off);
+                    let iter = children[i..].iter_mut();
+                    iter.for_each(|(w, _)| *w = w.saturating_sub(1));
+                    (i, start)
                 }
+            };
+            path.push(i);
+            off = Node::<T>::descend(off, start)?;
+            node = node.child_mut(i);
+        };
+
+        // walk back up the path merging underfull leaf nodes, for as long
+        // as the child along the path is a leaf node. Merged leaf nodes
+        // change the depth of every subtree above them.
+        let levels = path.clone();
+        let mut top = None;
+        while let Some(i) = path.pop() {
+            let node = self.descend_mut(&path);
+            match node {
+                Node::M { left, right, .. } => {
+                    if let Some(leafs) = Node::merge_leafs(left, right, rn) {
+                        *node = Node::into_node(Node::join_leafs(leafs));
+                        top = Some(path.len());
+                    }
+                }
+                Node::N { children, .. } if children[i].1.is_underfull(rn) => {
+                    let children = mem::take(children);
+                    let child = Node::merge_children(children, i, rn);
+                    // a lone child left behind after merge might be shared.
+                    *node = Node::into_node(child);
+                    top = Some(path.len());
+                }
+                Node::N { .. } => (),
+                Node::Z { .. } => Err(Node::<T>::leaf_on_path())?,
             }
-            Node::Z { data } => {
-                let old = data[off].clone();
+            if !matches!(node, Node::Z { .. }) {
+                break;
+            }
+        }
+
+        if let Some(top) = top {
+            self.redepth_path(&levels[..top]);
+        }
+
+        Ok(old)
+    }
 
-                let mut ndata = data[..off].to_vec();
-                ndata.extend_from_slice(&data[(off + 1)..]);
-                (Ref::new(Node::Z { data: ndata }), old)
+    // drop this node and the subtree under it, without recursion, so that
+    // trees that have gone deep won't overflow the call stack. Subtrees
+    // shared with other trees are only released.
+    fn release(self) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::M { left, right, .. } => {
+                    stack.extend(Ref::into_inner(left));
+                    stack.extend(Ref::into_inner(right));
+                }
+                Node::N { children, .. } => {
+                    let iter =
+                        children.into_iter().filter_map(|(_, c)| Ref::into_inner(c));
+                    stack.extend(iter)
+                }
+                Node::Z { .. } => (),
             }
         }
     }
 
-    fn remove_mut(&mut self, off: usize) -> T
+    fn is_underfull(&self, rn: &Rebalance) -> bool {
+        match self {
+            Node::Z { data } => rn.is_underfull(data.len()),
+            _ => false,
+        }
+    }
+
+    // if `left` and `right` are leaf nodes, and either of them is underfull,
+    // merge them into a single leaf node, or, if they won't fit in a single
+    // leaf node, redistribute their items evenly across two leaf nodes.
+    fn merge_leafs(
+        left: &Node<T>,
+        right: &Node<T>,
+        rn: &Rebalance,
+    ) -> Option<Vec<Node<T>>>
     where
         T: Clone,
     {
-        match self {
-            Node::M {
-                weight,
-                left,
-                right,
-            } => {
-                if off < *weight {
-                    *weight -= 1;
-                    Ref::get_mut(left).unwrap().remove_mut(off)
-                } else {
-                    Ref::get_mut(right).unwrap().remove_mut(off - *weight)
+        match (left, right) {
+            (Node::Z { data: l }, Node::Z { data: r })
+                if rn.is_underfull(l.len()) || rn.is_underfull(r.len()) =>
+            {
+                match l.len() + r.len() {
+                    n if n <= rn.n_items => Some(vec![Node::Z {
+                        data: Node::leaf_concat(&[l, r]),
+                    }]),
+                    n if (n / 2) <= l.len() => {
+                        let (a, b) = l.split_at(n / 2);
+                        let data = Node::leaf_concat(&[a]);
+                        let rdata = Node::leaf_concat(&[b, r]);
+                        Some(vec![Node::Z { data }, Node::Z { data: rdata }])
+                    }
+                    n => {
+                        let (a, b) = r.split_at((n / 2) - l.len());
+                        let data = Node::leaf_concat(&[l, a]);
+                        let rdata = Node::leaf_concat(&[b]);
+                        Some(vec![Node::Z { data }, Node::Z { data: rdata }])
+                    }
                 }
             }
-            Node::Z { data } => {
-                let old = data[off].clone();
-                data.remove(off);
-                if (data.len() * 2) < data.capacity() {
-                    data.shrink_to_fit()
-                }
-                old
+            (_, _) => None,
+        }
+    }
+
+    fn join_leafs(leafs: Vec<Node<T>>) -> Ref<Node<T>> {
+        let mut iter = leafs.into_iter().map(Ref::new);
+        match (iter.next(), iter.next()) {
+            (Some(left), Some(right)) => {
+                let weight = left.len();
+                Node::newm(left, right, weight)
             }
+            (Some(leaf), None) => leaf,
+            (None, _) => Node::empty_leaf(),
         }
     }
 
-    fn split_insert(data: &[T], off: usize, val: T) -> Ref<Node<T>>
+    // underfull child at `i` is merged with its adjacent leaf node, if any,
+    // or dropped if it is empty.
+    fn merge_children(
+        children: Vec<(usize, Ref<Node<T>>)>,
+        i: usize,
+        rn: &Rebalance,
+    ) -> Ref<Node<T>>
     where
         T: Clone,
     {
-        let (mut ld, mut rd) = {
-            let m = data.len() / 2;
-            match data.len() {
-                0 => (vec![], vec![]),
-                1 => (data.to_vec(), vec![]),
-                _ => (data[..m].to_vec(), data[m..].to_vec()),
+        let mut nodes: Vec<Ref<Node<T>>> = children.into_iter().map(|(_, c)| c).collect();
+
+        let pairs = [(i, i + 1), (i.saturating_sub(1), i)];
+        let pairs = pairs.iter().filter(|(a, b)| a < b && *b < nodes.len());
+        let mut merged = false;
+        for (a, b) in pairs {
+            if let Some(leafs) = Node::merge_leafs(&nodes[*a], &nodes[*b], rn) {
+                nodes.splice(*a..=*b, leafs.into_iter().map(Ref::new));
+                merged = true;
+                break;
             }
-        };
-        let weight = match ld.len() {
+        }
+        if !merged && nodes.len() > 1 && nodes[i].len() == 0 {
+            nodes.remove(i);
+        }
+
+        Node::newn(nodes)
+    }
+
+    fn split_insert(data: &[T], off: usize, val: T) -> Ref<Node<T>>
+    where
+        T: Clone,
+    {
+        let (ld, rd) = data.split_at(data.len() / 2);
+        let (ld, rd, weight) = match ld.len() {
+            // `val` and the single item, if any, land in separate leaf nodes.
+            0 if off == 0 => (vec![val], rd.to_vec(), 1),
+            0 => (rd.to_vec(), vec![val], 1),
             w if off < w => {
-                ld.insert(off, val);
-                ld.len()
-            }
-            w => {
-                rd.insert(off - w, val);
-                w
+                let ld = Node::leaf_insert(ld, off, val);
+                let weight = ld.len();
+                (ld, rd.to_vec(), weight)
             }
+            w => (ld.to_vec(), Node::leaf_insert(rd, off - w, val), w),
         };
-        Ref::new(Node::M {
-            weight,
-            left: Ref::new(Node::Z { data: ld }),
-            right: Ref::new(Node::Z { data: rd }),
-        })
+        let (left, right) = (
+            Ref::new(Node::Z { data: ld }),
+            Ref::new(Node::Z { data: rd }),
+        );
+        Node::newm(left, right, weight)
+    }
+
+    // Leaf items are copied as slices, using `to_vec()` and
+    // `extend_from_slice()`, which the standard library lowers to `memcpy`
+    // when `T` is `Copy`. For plain-data items the compiler lowers clones
+    // the same way, `perf --copy-vs-clone` measures no difference between
+    // Copy and Clone items for insert, remove and to_vec.
+
+    // concatenate leaf items from `slices`, allocated to their exact size.
+    fn leaf_concat(slices: &[&[T]]) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut data = Vec::with_capacity(slices.iter().map(|s| s.len()).sum());
+        slices.iter().for_each(|s| data.extend_from_slice(s));
+        data
+    }
+
+    // copy of leaf items `data`, with `val` inserted at `off`, allocated to
+    // its exact size.
+    fn leaf_insert(data: &[T], off: usize, val: T) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut ndata = Vec::with_capacity(data.len() + 1);
+        ndata.extend_from_slice(&data[..off]);
+        ndata.push(val);
+        ndata.extend_from_slice(&data[off..]);
+        ndata
+    }
+
+    // copy of leaf items `data`, with item at `off` removed, allocated to
+    // its exact size.
+    fn leaf_remove(data: &[T], off: usize) -> Vec<T>
+    where
+        T: Clone,
+    {
+        Node::leaf_concat(&[&data[..off], &data[(off + 1)..]])
+    }
+
+    // insert `val` at `off` within leaf items `data`, capacity grows like
+    // Vec's, but never beyond `n` items that a leaf node can hold.
+    fn leaf_insert_mut(data: &mut Vec<T>, off: usize, val: T, n: usize) {
+        use std::cmp::{max, min};
+
+        if data.len() == data.capacity() {
+            let cap = min(max(data.len() * 2, 4), max(n, data.len() + 1));
+            data.reserve_exact(cap - data.len());
+        }
+        data.insert(off, val)
     }
 
     fn split_off(&self, off: usize, len: usize) -> (Ref<Node<T>>, Ref<Node<T>>, usize)
@@ -721,21 +2490,39 @@ where
                 left,
                 right,
                 weight,
+                ..
             } if off < *weight => {
                 let (left, root, n) = left.split_off(off, *weight);
                 let root = Node::newm(root, Ref::clone(right), n);
-                let node = Node::newm(left, Node::empty_leaf(), weight - n);
-                (node, root, n + (len - weight))
+                // left subtree holds all the remaining items.
+                (left, root, n + (len - weight))
             }
             Node::M {
                 left,
                 right,
                 weight,
+                ..
             } => {
                 let (right, root, n) = right.split_off(off - weight, len - weight);
-                let node = Node::newm(Ref::clone(left), right, *weight);
+                let node = match right.len() {
+                    0 => Ref::clone(left),
+                    _ => Node::newm(Ref::clone(left), right, *weight),
+                };
                 (node, root, n)
             }
+            Node::N { children, .. } => {
+                let (i, start) = Node::locate(children, off);
+                let (end, child) = &children[i];
+                let (left, right, n) = child.split_off(off - start, end - start);
+
+                let mut lefts: Vec<Ref<Node<T>>> =
+                    children[..i].iter().map(|(_, c)| Ref::clone(c)).collect();
+                lefts.push(left);
+                let mut rights = vec![right];
+                rights.extend(children[(i + 1)..].iter().map(|(_, c)| Ref::clone(c)));
+
+                (Node::newn(lefts), Node::newn(rights), n + (len - end))
+            }
             Node::Z { data } if off == 0 => {
                 let node = Node::empty_leaf();
                 let root = Ref::new(Node::Z {
@@ -755,9 +2542,165 @@ where
         }
     }
 
+    // apply `edits`, sorted by offset, on the subtree under `node`. Offsets
+    // in `edits` are relative to `base`, the offset of the first item under
+    // `node`. Subtrees without edits are shared as is. Return (node, depth),
+    // where depth is the depth of the longest edited path.
+    fn apply(
+        node: &Ref<Node<T>>,
+        edits: &[&Edit<T>],
+        base: usize,
+        rn: &Rebalance,
+    ) -> (Ref<Node<T>>, usize)
+    where
+        T: Clone,
+    {
+        if edits.is_empty() {
+            return (Ref::clone(node), 0);
+        }
+
+        let (node, depth) = match node.as_ref() {
+            Node::M {
+                weight,
+                left,
+                right,
+                ..
+            } => {
+                let n = edits.partition_point(|e| (e.to_offset() - base) < *weight);
+                let (left, l) = Node::apply(left, &edits[..n], base, rn);
+                let (right, r) = Node::apply(right, &edits[n..], base + weight, rn);
+                let depth = std::cmp::max(l, r) + 1;
+                match (left.len(), right.len()) {
+                    (0, _) => (right, depth),
+                    (_, 0) => (left, depth),
+                    (weight, _) => (Node::newm(left, right, weight), depth),
+                }
+            }
+            Node::N { children, .. } => {
+                let (mut nodes, mut depth, mut edits) = (vec![], 0, edits);
+                for (i, (end, child)) in children.iter().enumerate() {
+                    let start = end - child.len();
+                    let n = match i == children.len() - 1 {
+                        true => edits.len(),
+                        false => edits.partition_point(|e| (e.to_offset() - base) < *end),
+                    };
+                    let (child, d) = Node::apply(child, &edits[..n], base + start, rn);
+                    edits = &edits[n..];
+                    depth = std::cmp::max(depth, d);
+                    if child.len() > 0 {
+                        nodes.push(child)
+                    }
+                }
+                (Node::newn(nodes), depth + 1)
+            }
+            Node::Z { data } => {
+                let data = Node::apply_leaf(data, edits, base);
+                let n = max_leaf_items::<T>(rn.leaf_cap);
+                match data.len() {
+                    m if m <= n => (Ref::new(Node::Z { data }), 1),
+                    _ => {
+                        let leafs = Vector::chunk_leaf_nodes(data.into_iter(), n);
+                        let (node, depth) = Node::build_leaf_nodes(leafs, rn.fanout);
+                        (node, depth + 1)
+                    }
+                }
+            }
+        };
+
+        let n = node.len();
+        Node::auto_rebalance(node, depth, n, false, false, rn)
+    }
+
+    fn apply_leaf(data: &[T], edits: &[&Edit<T>], base: usize) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let n = edits.iter().fold(data.len(), |n, edit| match edit {
+            Edit::Insert(_, _) => n + 1,
+            Edit::Update(_, _) => n,
+            Edit::Remove(_) => n - 1,
+        });
+        let mut ndata = Vec::with_capacity(n);
+        let mut i = 0;
+        for edit in edits.iter() {
+            let off = edit.to_offset() - base;
+            if off > i {
+                ndata.extend_from_slice(&data[i..off]);
+                i = off;
+            }
+            match edit {
+                Edit::Insert(_, val) => ndata.push(val.clone()),
+                Edit::Update(_, val) => {
+                    ndata.push(val.clone());
+                    i += 1;
+                }
+                Edit::Remove(_) => i += 1,
+            }
+        }
+        ndata.extend_from_slice(&data[i..]);
+        ndata
+    }
+
+    // push a leaf node to the right end of the tree under `node`, holding
+    // `len` items. Return (node, depth), where depth is the depth of the
+    // right most path.
+    //
+    // Instead of descending all the way down, `leaf` is joined with the
+    // first subtree, on the right most path, that is as full as its left
+    // sibling, so that pushing a sequence of leaf nodes builds a balanced
+    // tree, like incrementing a counter.
+    fn push_leaf(
+        node: &Ref<Node<T>>,
+        leaf: Ref<Node<T>>,
+        len: usize,
+        rn: &Rebalance,
+    ) -> (Ref<Node<T>>, usize)
+    where
+        T: Clone,
+    {
+        let (node, depth) = match node.as_ref() {
+            Node::M {
+                weight,
+                left,
+                right,
+                ..
+            } if right.len() < *weight => {
+                let (right, depth) = Node::push_leaf(right, leaf, len - weight, rn);
+                (Node::newm(Ref::clone(left), right, *weight), depth + 1)
+            }
+            Node::N { children, .. } => {
+                let first = children[0].0;
+                let (i, start) = (children.len() - 1, children[children.len() - 2].0);
+                let last = &children[i].1;
+                match len - start {
+                    n if n < first => {
+                        let (child, depth) = Node::push_leaf(last, leaf, n, rn);
+                        let mut nodes = Node::child_nodes(node);
+                        nodes[i] = child;
+                        (Node::newn(nodes), depth + 1)
+                    }
+                    _ if children.len() < rn.fanout => {
+                        let mut nodes = Node::child_nodes(node);
+                        nodes.push(leaf);
+                        (Node::newn(nodes), 2)
+                    }
+                    _ => (Node::newn(vec![Ref::clone(node), leaf]), 2),
+                }
+            }
+            Node::Z { data } if data.is_empty() => (leaf, 1),
+            _ if rn.fanout > 2 => (Node::newn(vec![Ref::clone(node), leaf]), 2),
+            _ => (Node::newm(Ref::clone(node), leaf, len), 2),
+        };
+
+        let n = node.len();
+        Node::auto_rebalance(node, depth, n, false, false, rn)
+    }
+
+    // `len` is the number of items under `node`.
     fn auto_rebalance(
         node: Ref<Node<T>>,
         depth: usize,
+        len: usize,
         packed: bool,
         force: bool,
         rn: &Rebalance,
@@ -765,21 +2708,44 @@ where
     where
         T: Clone,
     {
-        let doit = force || rn.auto_rebalance && rn.can_rebalance(depth);
+        let doit = force || rn.auto_rebalance && rn.can_rebalance(depth, len);
+        let packed = packed || rn.strict;
 
         match doit {
             false => (node, depth),
             true => {
-                let mut leafs = Node::collect_leaf_nodes(node, packed, rn.leaf_cap);
-                leafs.reverse();
-
-                let depth = (leafs.len() as f64).log2().ceil() as usize;
-                let (nroot, _) = Node::build_bottoms_up(depth, &mut leafs);
-                assert!(leafs.is_empty());
+                let leafs = Node::collect_leaf_nodes(node, packed, rn.leaf_cap);
+                Node::build_leaf_nodes(leafs, rn.fanout)
+            }
+        }
+    }
 
-                (nroot, depth)
+    // build a balanced tree from leaf nodes, with interior nodes of
+    // `fanout` children, return (root, depth)
+    pub(crate) fn build_leaf_nodes(
+        mut leafs: Vec<Ref<Node<T>>>,
+        fanout: usize,
+    ) -> (Ref<Node<T>>, usize) {
+        if fanout > 2 {
+            let mut depth = 0;
+            while leafs.len() > 1 {
+                leafs = leafs
+                    .chunks(fanout)
+                    .map(|x| Node::newn(x.to_vec()))
+                    .collect();
+                depth += 1;
             }
+            let root = leafs.pop().unwrap_or_else(Node::empty_leaf);
+            return (root, depth);
         }
+
+        leafs.reverse();
+
+        let depth = (leafs.len() as f64).log2().ceil() as usize;
+        let (root, _) = Node::build_bottoms_up(depth, &mut leafs);
+        assert!(leafs.is_empty());
+
+        (root, depth)
     }
 
     fn collect_leaf_nodes(
@@ -806,29 +2772,43 @@ where
                     stack.push(Ref::clone(right));
                     node = Ref::clone(left);
                 }
+                Node::N { children, .. } => {
+                    stack.extend(children[1..].iter().rev().map(|(_, c)| Ref::clone(c)));
+                    node = Ref::clone(&children[0].1);
+                }
             }
         };
 
         if packed {
-            let mut packed_leafs: Vec<Node<T>> = vec![];
-            let cap = max_leaf_items::<T>(leaf_cap);
-            for leaf in leafs.into_iter() {
-                match packed_leafs.last_mut() {
-                    None => packed_leafs.push(leaf.cow()),
-                    Some(last) => {
-                        if let Some(next) = last.pack(leaf.borrow(), cap) {
-                            packed_leafs.push(next)
-                        }
+            Self::pack_leaf_nodes(leafs, leaf_cap)
+        } else {
+            leafs
+        }
+    }
+
+    pub(crate) fn pack_leaf_nodes(
+        leafs: Vec<Ref<Node<T>>>,
+        leaf_cap: usize,
+    ) -> Vec<Ref<Node<T>>>
+    where
+        T: Clone,
+    {
+        let mut packed_leafs: Vec<Node<T>> = vec![];
+        let cap = max_leaf_items::<T>(leaf_cap);
+        for leaf in leafs.into_iter() {
+            match packed_leafs.last_mut() {
+                None => packed_leafs.push(leaf.cow()),
+                Some(last) => {
+                    if let Some(next) = last.pack(leaf.borrow(), cap) {
+                        packed_leafs.push(next)
                     }
                 }
             }
-            packed_leafs.into_iter().map(Ref::new).collect()
-        } else {
-            leafs
         }
+        packed_leafs.into_iter().map(Ref::new).collect()
     }
 
-    fn build_bottoms_up(
+    pub(crate) fn build_bottoms_up(
         depth: usize,
         leafs: &mut Vec<Ref<Node<T>>>,
     ) -> (Ref<Node<T>>, usize) {
@@ -845,13 +2825,7 @@ where
                 let weight = left.len();
                 let n = weight + right.len();
 
-                let node = Node::M {
-                    weight,
-                    left,
-                    right,
-                };
-
-                (Ref::new(node), n)
+                (Node::newm(left, right, weight), n)
             }
             (_, 1) => Self::build_bottoms_up(1, leafs),
             (_, 2) => Self::build_bottoms_up(1, leafs),
@@ -862,21 +2836,11 @@ where
                     1 => {
                         let right = leafs.pop().unwrap();
                         let m = right.len();
-                        let node = Node::M {
-                            weight,
-                            left,
-                            right,
-                        };
-                        (Ref::new(node), weight + m)
+                        (Node::newm(left, right, weight), weight + m)
                     }
                     _ => {
                         let (right, m) = Self::build_bottoms_up(depth - 1, leafs);
-                        let node = Node::M {
-                            weight,
-                            left,
-                            right,
-                        };
-                        (Ref::new(node), weight + m)
+                        (Node::newm(left, right, weight), weight + m)
                     }
                 }
             }
@@ -885,26 +2849,42 @@ where
         (root, n)
     }
 
-    fn build_iter_stack<'a, 'b>(node: &'a Node<T>, iter: &'b mut Iter<'a, T>) {
-        match node {
-            Node::M { left, right, .. } => {
-                iter.stack.push(&right);
-                Self::build_iter_stack(left, iter);
-            }
-            node @ Node::Z { .. } => {
-                iter.node = Some(node);
+    fn build_iter_stack<'a>(mut node: &'a Node<T>, iter: &mut Iter<'a, T>) {
+        loop {
+            match node {
+                Node::M { left, right, .. } => {
+                    iter.stack.push(right);
+                    node = left;
+                }
+                Node::N { children, .. } => {
+                    iter.stack
+                        .extend(children[1..].iter().rev().map(|(_, c)| c.as_ref()));
+                    node = &children[0].1;
+                }
+                Node::Z { data } => {
+                    iter.leaf = data;
+                    break;
+                }
             }
         }
     }
 
-    fn build_into_iter_stack(node: &Ref<Node<T>>, iter: &mut IntoIter<T>) {
-        match node.as_ref() {
-            Node::M { left, right, .. } => {
-                iter.stack.push(Ref::clone(right));
-                Self::build_into_iter_stack(left, iter);
-            }
-            Node::Z { .. } => {
-                iter.node = Some(Ref::clone(node));
+    fn build_into_iter_stack(mut node: &Ref<Node<T>>, iter: &mut IntoIter<T>) {
+        loop {
+            match node.as_ref() {
+                Node::M { left, right, .. } => {
+                    iter.stack.push(Ref::clone(right));
+                    node = left;
+                }
+                Node::N { children, .. } => {
+                    let rest = children[1..].iter().rev().map(|(_, c)| Ref::clone(c));
+                    iter.stack.extend(rest);
+                    node = &children[0].1;
+                }
+                Node::Z { .. } => {
+                    iter.node = Some(Ref::clone(node));
+                    break;
+                }
             }
         }
     }
@@ -927,10 +2907,115 @@ where
                 n += right.fetch_multiversions(acc);
                 n + 1
             }
+            Node::N { children, .. } => {
+                let mut n = 0;
+                for (_, child) in children.iter() {
+                    if Ref::strong_count(child) > 1 {
+                        let ptr = Ref::as_ptr(child);
+                        acc.push(ptr as *const u8);
+                    }
+                    n += child.fetch_multiversions(acc);
+                }
+                n + 1
+            }
+            Node::Z { .. } => 1,
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Node::M { depth, .. } | Node::N { depth, .. } => *depth as usize,
+            Node::Z { .. } => 1,
+        }
+    }
+
+    // depth of this node, computed from its child nodes.
+    fn joined_depth(&self) -> usize {
+        let depth = match self {
+            Node::M { left, right, .. } => join_depth([left.depth(), right.depth()]),
+            Node::N { children, .. } => {
+                join_depth(children.iter().map(|(_, c)| c.depth()))
+            }
+            Node::Z { .. } => 1,
+        };
+        depth as usize
+    }
+
+    // recompute the depth cached in interior nodes along `path`, after
+    // editing the subtree at the end of the path in-place. Instead of
+    // recursing, or descending from the top for every node along the path,
+    // depths of child nodes off the path are collected on the way down, and
+    // depths along the path are computed bottoms-up and set on a second
+    // walk down.
+    fn redepth_path(&mut self, path: &[usize])
+    where
+        T: Clone,
+    {
+        let mut sides: Vec<usize> = Vec::with_capacity(path.len());
+        let mut node = &*self;
+        for i in path.iter() {
+            let side = match node {
+                Node::M { right, .. } if *i == 0 => right.depth(),
+                Node::M { left, .. } => left.depth(),
+                Node::N { children, .. } => {
+                    let iter = children.iter().enumerate().filter(|(j, _)| j != i);
+                    iter.map(|(_, (_, c))| c.depth()).max().unwrap_or(0)
+                }
+                Node::Z { .. } => unreachable!(),
+            };
+            sides.push(side);
+            node = node.child(*i);
+        }
+
+        let mut depth = node.depth();
+        let mut depths: Vec<usize> = sides
+            .into_iter()
+            .rev()
+            .map(|side| {
+                depth = std::cmp::max(side, depth) + 1;
+                depth
+            })
+            .collect();
+
+        let mut node = &mut *self;
+        for i in path.iter() {
+            if let Node::M { depth, .. } | Node::N { depth, .. } = node {
+                *depth = depths.pop().unwrap() as u32;
+            }
+            node = node.child_mut(*i);
+        }
+    }
+
+    #[cfg(test)]
+    fn n_leafs(&self) -> usize {
+        match self {
+            Node::M { left, right, .. } => left.n_leafs() + right.n_leafs(),
+            Node::N { children, .. } => children.iter().map(|(_, c)| c.n_leafs()).sum(),
             Node::Z { .. } => 1,
         }
     }
 
+    // depth cached in an interior node shall match its child nodes, whose
+    // depths are verified before their parent, refer Vector::validate.
+    fn verify_depth(&self, depth: usize) -> Result<()> {
+        match (self.depth(), self.joined_depth()) {
+            (a, b) if a == b => Ok(()),
+            (a, b) => Err(Error::Corrupted {
+                detail: format!(
+                    "cached depth {} at depth {}, for child nodes of depth {}",
+                    a, depth, b
+                ),
+            }),
+        }
+    }
+
+    // number of leaf nodes is never less than len / n_items, hence checking
+    // against this estimate is stricter, and O(1), compared to counting the
+    // leaf nodes, refer Rebalance::can_rebalance.
+    fn is_strictly_balanced(&self, n_items: usize) -> bool {
+        self.depth() <= strict_depth(self.len() / n_items)
+    }
+
     #[cfg(test)]
     #[allow(dead_code)]
     fn pretty_print(&self, mut prefix: String, len: usize) {
@@ -939,12 +3024,22 @@ where
                 left,
                 right,
                 weight,
+                ..
             } => {
                 println!("{}nodem:{}", prefix, len);
                 prefix.push_str("  ");
                 left.pretty_print(prefix.clone(), *weight);
                 right.pretty_print(prefix, len - *weight);
             }
+            Node::N { children, .. } => {
+                println!("{}noden:{}", prefix, len);
+                prefix.push_str("  ");
+                let mut start = 0;
+                for (w, child) in children.iter() {
+                    child.pretty_print(prefix.clone(), w - start);
+                    start = *w;
+                }
+            }
             Node::Z { data } => {
                 println!("{}nodez:{}", prefix, data.len());
             }
@@ -953,47 +3048,571 @@ where
 }
 
 struct Rebalance {
-    n_leafs: f64,
+    n_items: usize,
     auto_rebalance: bool,
+    strict: bool,
     leaf_cap: usize,
+    fanout: usize,
 }
 
 impl Rebalance {
     fn new<T: Sized>(r: &Vector<T>) -> Rebalance {
-        let n_leafs = r.len / max_leaf_items::<T>(r.leaf_cap);
         Rebalance {
-            n_leafs: n_leafs as f64,
+            n_items: max_leaf_items::<T>(r.leaf_cap),
             auto_rebalance: r.auto_rebalance,
+            strict: r.strict_balance,
             leaf_cap: r.leaf_cap,
+            fanout: r.fanout,
         }
     }
 
-    fn can_rebalance(&self, depth: usize) -> bool {
+    fn is_underfull(&self, n: usize) -> bool {
+        is_underfull(n, self.n_items)
+    }
+
+    // check whether subtree, of `depth` and holding `len` items, has gone
+    // too much off-balance. Since the check is done for every node along
+    // the path of a write operation, only the smallest off-balanced subtree
+    // is rebuilt, instead of the whole tree.
+    fn can_rebalance(&self, depth: usize, len: usize) -> bool {
+        if self.strict {
+            // number of leaf nodes is never less than this estimate.
+            return depth > strict_depth(len / self.n_items);
+        }
+
+        let n_leafs = (len / self.n_items) as f64;
         match depth {
             n if n < crate::REBALANCE_THRESHOLD => false,
-            _ if (depth as f64) > (self.n_leafs.log2() * 3_f64) => true,
+            _ if (depth as f64) > (n_leafs.log2() * 3_f64) => true,
             _ => false,
         }
     }
 }
 
+/// An access finger into Vector, for sequences of nearby reads.
+///
+/// Created by the finger method on Vector. Finger remembers the leaf node
+/// of the most recent access, along with its offset range, so that accessing
+/// an element within the same leaf node, like `get(i)` followed by
+/// `get(i+1)`, does not descend the tree again.
+pub struct Finger<'a, T> {
+    arr: &'a Vector<T>,
+    leaf: &'a [T],
+    start: usize,
+}
+
+impl<'a, T> Finger<'a, T> {
+    /// Return a reference to the element at that position or `IndexOutOfBounds`
+    /// error if out of bounds.
+    pub fn get(&mut self, index: usize) -> Result<&'a T> {
+        if let Some(val) = index.checked_sub(self.start).and_then(|i| self.leaf.get(i)) {
+            return Ok(val);
+        }
+
+        let (leaf, start) = self.arr.leaf_of(index)?;
+        self.leaf = leaf;
+        self.start = start;
+
+        match index.checked_sub(start).and_then(|i| leaf.get(i)) {
+            Some(val) => Ok(val),
+            None => Err(Node::<T>::overrun(index, start, leaf.len())),
+        }
+    }
+}
+
+/// A mutable access finger into Vector, for sequences of nearby reads and
+/// updates.
+///
+/// Created by the finger_mut method on Vector. Like [Finger], it remembers
+/// the leaf node of the most recent access, along with the path of child
+/// nodes leading to it, so that `get(i)`, `get(i+1)` followed by
+/// `update(i+2)` do not search the tree again. Items stay in their leaf
+/// node, and leaf nodes shared with other versions are copied on first
+/// touch, as with the `_mut` methods.
+pub struct FingerMut<'a, T>
+where
+    T: Clone,
+{
+    arr: &'a mut Vector<T>,
+    // child indices from the root to the leaf node last accessed, holding
+    // `len` items starting at offset `start`.
+    path: Vec<usize>,
+    start: usize,
+    len: usize,
+    in_tail: bool,
+    #[cfg(test)]
+    misses: usize,
+}
+
+impl<'a, T> FingerMut<'a, T>
+where
+    T: Clone,
+{
+    /// Return a reference to the element at that position or `IndexOutOfBounds`
+    /// error if out of bounds.
+    pub fn get(&mut self, index: usize) -> Result<&T> {
+        let i = self.seek(index)?;
+        let root = match self.arr.tree() {
+            Some(root) if !self.in_tail => root,
+            _ => return Ok(&self.arr.tail_items()[i]),
+        };
+        match self
+            .path
+            .iter()
+            .fold(root, |node, i| node.child(*i))
+            .as_ref()
+        {
+            Node::Z { data } => Ok(&data[i]),
+            _ => Err(Node::<T>::leaf_on_path()),
+        }
+    }
+
+    /// Update the element at `index` with `value`, and return the old value,
+    /// or `IndexOutOfBounds` error if out of bounds.
+    pub fn update(&mut self, index: usize, value: T) -> Result<T> {
+        let i = self.seek(index)?;
+        let data = match self.in_tail {
+            true => self.arr.tail_mut(),
+            false => {
+                match Node::make_mut(self.arr.tree_mut().0).descend_mut(&self.path) {
+                    Node::Z { data } => data,
+                    _ => Err(Node::<T>::leaf_on_path())?,
+                }
+            }
+        };
+        Ok(mem::replace(&mut data[i], value))
+    }
+
+    // return the position of `index` within the leaf node last accessed,
+    // finding the leaf node holding `index` if it is another one.
+    fn seek(&mut self, index: usize) -> Result<usize> {
+        match index.checked_sub(self.start) {
+            Some(i) if i < self.len => return Ok(i),
+            _ => (),
+        }
+        if index >= self.arr.len {
+            Err(Error::IndexOutOfBounds {
+                index,
+                len: self.arr.len,
+            })?
+        }
+
+        #[cfg(test)]
+        {
+            self.misses += 1;
+        }
+        let root_len = self.arr.root_len();
+        self.in_tail = index >= root_len;
+        self.path.clear();
+        match self.arr.tree() {
+            Some(root) if !self.in_tail => {
+                let (mut node, mut start) = (root, 0);
+                while !matches!(node.as_ref(), Node::Z { .. }) {
+                    let (i, n) = node.locate_step(Node::<T>::descend(index, start)?)?;
+                    self.path.push(i);
+                    start += n;
+                    node = node.child(i);
+                }
+                self.start = start;
+                self.len = node.len();
+            }
+            _ => {
+                self.start = root_len;
+                self.len = self.arr.tail_items().len();
+            }
+        }
+        match index.checked_sub(self.start) {
+            Some(i) if i < self.len => Ok(i),
+            _ => Err(Node::<T>::overrun(index, self.start, self.len)),
+        }
+    }
+}
+
+/// Builder for Vector, for bulk loads.
+///
+/// Values pushed into the builder are packed into leaf nodes, and the tree
+/// is built bottoms-up only once, when calling [VectorBuilder::build], hence
+/// bulk loads don't split and rebalance the tree midway. Use
+/// [VectorBuilder::with_capacity] or [VectorBuilder::reserve] when the
+/// number of values is known upfront, to pre-allocate the list of leaf
+/// nodes.
+pub struct VectorBuilder<T> {
+    leaf_cap: usize,
+    leafs: Vec<Ref<Node<T>>>,
+    data: Vec<T>,
+}
+
+impl<T> Default for VectorBuilder<T> {
+    fn default() -> VectorBuilder<T> {
+        VectorBuilder::new(None)
+    }
+}
+
+impl<T> Extend<T> for VectorBuilder<T> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.push(value)
+        }
+    }
+}
+
+impl<T> VectorBuilder<T> {
+    /// Create a new builder, with leaf node size set to `leaf_node_size`
+    /// bytes. Refer [Vector::set_leaf_size] for details.
+    pub fn new(leaf_node_size: Option<usize>) -> VectorBuilder<T> {
+        VectorBuilder {
+            leaf_cap: leaf_node_size.unwrap_or(crate::LEAF_CAP),
+            leafs: Vec::default(),
+            data: Vec::default(),
+        }
+    }
+
+    /// Create a new builder for loading `n` values, refer [Self::new].
+    pub fn with_capacity(n: usize, leaf_node_size: Option<usize>) -> VectorBuilder<T> {
+        let mut builder = VectorBuilder::new(leaf_node_size);
+        builder.reserve(n);
+        builder
+    }
+
+    /// Reserve capacity for `additional` values to be pushed into the
+    /// builder.
+    pub fn reserve(&mut self, additional: usize) {
+        let n = max_leaf_items::<T>(self.leaf_cap);
+        let leafs = (self.data.len() + additional) / n;
+        self.leafs.reserve(leafs);
+        if self.data.capacity() == 0 && additional > 0 {
+            self.data.reserve_exact(std::cmp::min(additional, n));
+        }
+    }
+
+    /// Return the number of values pushed so far.
+    pub fn len(&self) -> usize {
+        let n = max_leaf_items::<T>(self.leaf_cap);
+        (self.leafs.len() * n) + self.data.len()
+    }
+
+    /// Return whether no value is pushed so far.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Push `value` to the end of the vector being built.
+    pub fn push(&mut self, value: T) {
+        let n = max_leaf_items::<T>(self.leaf_cap);
+        if self.data.capacity() == 0 {
+            self.data.reserve_exact(n);
+        }
+        self.data.push(value);
+        if self.data.len() == n {
+            let data = mem::take(&mut self.data);
+            self.leafs.push(Ref::new(Node::Z { data }));
+        }
+    }
+
+    /// Build the vector, tree is built bottoms-up, in O(n) time, and fully
+    /// balanced.
+    pub fn build(mut self) -> Vector<T> {
+        if !self.data.is_empty() {
+            let mut data = mem::take(&mut self.data);
+            data.shrink_to_fit();
+            self.leafs.push(Ref::new(Node::Z { data }));
+        }
+        Vector::from_leaf_nodes(self.leafs, self.leaf_cap)
+    }
+}
+
+/// Mutable form of Vector, for a sequence of in-place edits.
+///
+/// Created by the thaw method on Vector. Edits on VectorMut are done
+/// in-place, nodes shared with other versions of the vector are copied on
+/// first touch and owned by VectorMut thereafter, so that subsequent edits
+/// on the same path don't copy them again. Call [VectorMut::freeze] to get
+/// back the edited Vector, which is O(1). Read-only methods on Vector are
+/// available on VectorMut via Deref.
+pub struct VectorMut<T> {
+    arr: Vector<T>,
+}
+
+impl<T> Deref for VectorMut<T> {
+    type Target = Vector<T>;
+
+    fn deref(&self) -> &Vector<T> {
+        &self.arr
+    }
+}
+
+impl<T> Vector<T> {
+    /// Convert this vector into its mutable form, refer [VectorMut]. Nodes
+    /// shared with clones of this vector are left untouched by edits on the
+    /// mutable form. This is O(1).
+    pub fn thaw(self) -> VectorMut<T> {
+        VectorMut { arr: self }
+    }
+}
+
+impl<T> VectorMut<T>
+where
+    T: Clone,
+{
+    /// Convert back into an immutable vector, this is O(1).
+    pub fn freeze(self) -> Vector<T> {
+        self.arr
+    }
+
+    /// Same as [Vector::insert_mut].
+    pub fn insert(&mut self, off: usize, value: T) -> Result<()> {
+        self.arr.insert_mut(off, value)
+    }
+
+    /// Same as [Vector::update_mut].
+    pub fn update(&mut self, off: usize, value: T) -> Result<T> {
+        self.arr.update_mut(off, value)
+    }
+
+    /// Same as [Vector::remove_mut].
+    pub fn remove(&mut self, off: usize) -> Result<T> {
+        self.arr.remove_mut(off)
+    }
+}
+
+impl<T> Vector<Vector<T>>
+where
+    T: Clone,
+{
+    /// Flatten a vector of vectors into a single vector, refer
+    /// [Vector::concat_all].
+    pub fn flatten(&self) -> Vector<T> {
+        Vector::concat_all(self.iter().cloned())
+    }
+}
+
+/// Transient handle for editing around a single point, like a cursor in
+/// a text editor.
+///
+/// Created by the focus method on Vector. Items inserted next to each
+/// other are held in a gap buffer, outside the tree, so that consecutive
+/// inserts at the same spot don't repeatedly copy and split the leaf node
+/// under the cursor. Inserting away from the gap buffer moves it, by
+/// weaving pending items into the tree in a single walk, refer
+/// [Vector::insert_many]. Updates and removes outside the gap buffer are
+/// done in-place on the tree, like [VectorMut]. Call [Focus::snapshot] or
+/// [Focus::freeze] to get the edited Vector.
+pub struct Focus<T> {
+    arr: Vector<T>,
+    // offset, within `arr`, where pending items are to be inserted.
+    at: usize,
+    pending: Vec<T>,
+}
+
+impl<T> Vector<T> {
+    /// Convert this vector into a transient handle for editing around
+    /// `off`, refer [Focus]. Return `IndexOutOfBounds` error if `off` is
+    /// beyond the end of this vector.
+    pub fn focus(self, off: usize) -> Result<Focus<T>> {
+        if off > self.len {
+            Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len,
+            })?
+        }
+        Ok(Focus {
+            arr: self,
+            at: off,
+            pending: Vec::default(),
+        })
+    }
+}
+
+impl<T> Focus<T>
+where
+    T: Clone,
+{
+    /// Return the offset just after the items inserted at the focus, that
+    /// is, the cursor.
+    pub fn cursor(&self) -> usize {
+        self.at + self.pending.len()
+    }
+
+    /// Return the number of items, including pending items.
+    pub fn len(&self) -> usize {
+        self.arr.len + self.pending.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        match self.locate(index)? {
+            Ok(off) => Ok(&self.pending[off]),
+            Err(off) => self.arr.get(off),
+        }
+    }
+
+    /// Insert `value` at `off`, or `IndexOutOfBounds` error. Inserting at,
+    /// or within, the items pending at the focus is O(1) amortized for
+    /// inserts at the cursor.
+    pub fn insert(&mut self, off: usize, value: T) -> Result<()> {
+        match off.checked_sub(self.at) {
+            Some(i) if i <= self.pending.len() => self.pending.insert(i, value),
+            _ if off > self.len() => Err(Error::IndexOutOfBounds {
+                index: off,
+                len: self.len(),
+            })?,
+            _ => {
+                // move the focus, once pending items are in the tree,
+                // offsets of the tree are same as that of the focus.
+                self.flush()?;
+                self.at = off;
+                self.pending.push(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the item at `off` with `value` and return the old item, or
+    /// `IndexOutOfBounds` error.
+    pub fn update(&mut self, off: usize, value: T) -> Result<T> {
+        match self.locate(off)? {
+            Ok(i) => Ok(std::mem::replace(&mut self.pending[i], value)),
+            Err(off) => self.arr.update_mut(off, value),
+        }
+    }
+
+    /// Remove the item at `off` and return it, or `IndexOutOfBounds` error.
+    pub fn remove(&mut self, off: usize) -> Result<T> {
+        match self.locate(off)? {
+            Ok(i) => Ok(self.pending.remove(i)),
+            Err(off) if off < self.at => {
+                let value = self.arr.remove_mut(off)?;
+                self.at -= 1;
+                Ok(value)
+            }
+            Err(off) => self.arr.remove_mut(off),
+        }
+    }
+
+    /// Return the edited vector, weaving pending items into the tree. The
+    /// focus stays at the cursor for further edits.
+    pub fn snapshot(&mut self) -> Result<Vector<T>> {
+        self.flush()?;
+        Ok(self.arr.clone())
+    }
+
+    /// Return the edited vector, weaving pending items into the tree.
+    pub fn freeze(mut self) -> Result<Vector<T>> {
+        self.flush()?;
+        Ok(self.arr)
+    }
+
+    // return Ok(offset) within pending items, or Err(offset) within tree.
+    fn locate(&self, index: usize) -> Result<std::result::Result<usize, usize>> {
+        let n = self.pending.len();
+        match index.checked_sub(self.at) {
+            _ if index >= self.len() => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            }),
+            Some(i) if i < n => Ok(Ok(i)),
+            Some(_) => Ok(Err(index - n)),
+            None => Ok(Err(index)),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            let (at, n) = (self.at, self.pending.len());
+            let items = self.pending.drain(..).map(|value| (at, value));
+            self.arr = self.arr.insert_many(items)?;
+            self.at = at + n;
+        }
+        Ok(())
+    }
+}
+
+/// Interner for leaf nodes, used to share leaf nodes holding identical
+/// items across vectors, refer [Vector::dedup_leaves_with].
+pub struct Interner<T> {
+    leafs: HashMap<u64, Vec<Ref<Node<T>>>>,
+}
+
+impl<T> Default for Interner<T> {
+    fn default() -> Interner<T> {
+        Interner {
+            leafs: HashMap::default(),
+        }
+    }
+}
+
+impl<T> Interner<T>
+where
+    T: Eq + Hash,
+{
+    /// Return the number of distinct leaf nodes interned so far.
+    pub fn len(&self) -> usize {
+        self.leafs.values().map(|leafs| leafs.len()).sum()
+    }
+
+    /// Return whether no leaf nodes are interned so far.
+    pub fn is_empty(&self) -> bool {
+        self.leafs.is_empty()
+    }
+
+    // return the interned leaf node holding the same items as `leaf`,
+    // `leaf` is interned if there is none.
+    fn intern(&mut self, leaf: Ref<Node<T>>) -> Ref<Node<T>> {
+        let data = match leaf.as_ref() {
+            Node::Z { data } => data,
+            _ => unreachable!(),
+        };
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+
+        let leafs = self.leafs.entry(hasher.finish()).or_default();
+        let ileaf = leafs.iter().find(|ileaf| match ileaf.as_ref() {
+            Node::Z { data: idata } => idata == data,
+            _ => unreachable!(),
+        });
+        match ileaf {
+            Some(ileaf) => Ref::clone(ileaf),
+            None => {
+                leafs.push(Ref::clone(&leaf));
+                leaf
+            }
+        }
+    }
+}
+
 /// An iterator for Vector.
 ///
 /// Created by the iter method on Vector.
 pub struct Iter<'a, T> {
     stack: Vec<&'a Node<T>>,
-    node: Option<&'a Node<T>>,
+    leaf: &'a [T],
     off: usize,
+    remaining: usize,
 }
 
 impl<'a, T> Iter<'a, T> {
-    fn new(root: &'a Node<T>) -> Iter<'a, T> {
+    fn new(arr: &'a Vector<T>) -> Iter<'a, T> {
         let mut iter = Iter {
-            stack: Vec::default(),
-            node: None,
+            stack: vec![],
+            leaf: &[],
             off: 0,
+            remaining: arr.len,
         };
-        Node::build_iter_stack(root, &mut iter);
+        match &arr.root {
+            Root::Inline(data) => iter.leaf = data,
+            Root::Tree { root, tail } => {
+                iter.stack.push(tail);
+                Node::build_iter_stack(root, &mut iter);
+            }
+        }
         iter
     }
 }
@@ -1002,13 +3621,13 @@ impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<&'a T> {
-        match self.node {
-            Some(Node::Z { data }) if self.off < data.len() => {
-                let item = &data[self.off];
+        match self.leaf.get(self.off) {
+            Some(item) => {
                 self.off += 1;
+                self.remaining -= 1;
                 Some(item)
             }
-            Some(Node::Z { .. }) | None => match self.stack.pop() {
+            None => match self.stack.pop() {
                 Some(node) => {
                     self.off = 0;
                     Node::build_iter_stack(node, self);
@@ -1016,11 +3635,16 @@ impl<'a, T> Iterator for Iter<'a, T> {
                 }
                 None => None,
             },
-            Some(_) => unreachable!(),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
 /// An iterator that moves elements out of Vector.
 ///
 /// Created by the into_iter method on Vector (provided by the
@@ -1029,6 +3653,7 @@ pub struct IntoIter<T> {
     stack: Vec<Ref<Node<T>>>,
     node: Option<Ref<Node<T>>>,
     off: usize,
+    remaining: usize,
 }
 
 impl<T> Iterator for IntoIter<T>
@@ -1042,6 +3667,7 @@ where
             Some(Node::Z { data }) if self.off < data.len() => {
                 let item = data[self.off].clone();
                 self.off += 1;
+                self.remaining -= 1;
                 Some(item)
             }
             Some(Node::Z { .. }) | None => match self.stack.pop() {
@@ -1055,11 +3681,41 @@ where
             Some(_) => unreachable!(),
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> where T: Clone {}
+
+// depth of an interior node over child nodes of `depths`.
+fn join_depth<I>(depths: I) -> u32
+where
+    I: IntoIterator<Item = usize>,
+{
+    let depth = depths.into_iter().max().unwrap_or(0) + 1;
+    depth as u32
+}
+
+// maximum depth allowed, in strict mode, for a tree with `n_leafs`.
+fn strict_depth(n_leafs: usize) -> usize {
+    let n_leafs = std::cmp::max(n_leafs, 1) as f64;
+    (n_leafs.log2().ceil() as usize * 2) + 2
 }
 
-fn max_leaf_items<T>(cap: usize) -> usize {
-    let s = mem::size_of::<T>();
-    (cap / s) + if cap % s == 0 { 0 } else { 1 }
+// node holding less than a quarter of its capacity, in items for leaf
+// nodes and in children for interior nodes, is underfull.
+pub(crate) fn is_underfull(n: usize, cap: usize) -> bool {
+    (n * 4) < cap
+}
+
+pub(crate) fn max_leaf_items<T>(cap: usize) -> usize {
+    match mem::size_of::<T>() {
+        // zero-sized items take no space, leaf nodes are capped by count.
+        0 => std::cmp::max(cap, 1),
+        s => (cap / s) + if cap.is_multiple_of(s) { 0 } else { 1 },
+    }
 }
 
 #[cfg(test)]
@@ -1071,7 +3727,8 @@ where
     validate_mem_ratio(k, arr.footprint(), arr.len());
 
     assert_eq!(refv.len(), arr.len());
-    assert_eq!(arr.len(), arr.root.len());
+    assert_eq!(arr.len(), arr.root_len() + arr.tail_items().len());
+    arr.validate().unwrap();
 
     for (off, val) in refv.iter().enumerate() {
         assert_eq!(arr.get(off).unwrap(), val, "off-{}", off);
@@ -1109,3 +3766,7 @@ mod ppar_test;
 #[cfg(test)]
 #[path = "fuzzy_test.rs"]
 mod fuzzy_test;
+
+#[cfg(all(test, feature = "model"))]
+#[path = "model_test.rs"]
+mod model_test;
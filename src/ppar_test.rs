@@ -9,6 +9,29 @@ fn test_new() {
     println!("is thread-safe {}", arr.is_thread_safe());
 }
 
+#[test]
+fn test_leaf_size() {
+    let mut arr: Vector<u64> = Vector::with_leaf_size(256);
+    assert_eq!(arr.leaf_size(), 256);
+
+    let mut refv: Vec<u64> = vec![];
+    for i in 0..10_000 {
+        arr.insert(0, i).unwrap();
+        refv.insert(0, i);
+    }
+    validate(&arr, &refv);
+
+    assert_eq!(arr.clone().leaf_size(), 256);
+    assert_eq!(arr.rebalance(true).unwrap().leaf_size(), 256);
+    assert_eq!(arr.split_off(5000).unwrap().leaf_size(), 256);
+
+    let mut arr: Vector<u64> = Vector::from_slice(&refv[..5000], Some(128));
+    assert_eq!(arr.leaf_size(), 128);
+    arr.append(Vector::from_slice(&refv[5000..], Some(1024)));
+    assert_eq!(arr.leaf_size(), 128);
+    validate(&arr, &refv);
+}
+
 #[test]
 fn test_crud() {
     let seed: u128 = random();
@@ -160,6 +183,358 @@ fn test_prepend() {
     }
 }
 
+#[test]
+fn test_small_vector() {
+    let n_items = max_leaf_items::<u64>(1024);
+
+    // empty vectors hold no nodes.
+    let arr: Vector<u64> = Vector::default();
+    assert!(matches!(&arr.root, Root::Inline(data) if data.capacity() == 0));
+    assert_eq!(arr.footprint(), mem::size_of::<Vector<u64>>());
+    validate(&arr, &[]);
+
+    for n in [1, 10, n_items].iter() {
+        let vals: Vec<u64> = (0..*n as u64).collect();
+        let arrs = vec![
+            Vector::from_slice(&vals, Some(1024)),
+            Vector::build(vals.clone(), Some(1024)),
+            Vector::from_slice(&vals, Some(1024)).compact(),
+            Vector::from_slice(&vals, Some(1024))
+                .rebalance(true)
+                .unwrap(),
+        ];
+        for arr in arrs.into_iter() {
+            // items are held inline, without allocating tree nodes.
+            assert!(matches!(&arr.root, Root::Inline(data) if data.len() == *n));
+            validate(&arr, &vals);
+
+            let footprint = mem::size_of::<Vector<u64>>() + (*n * 8);
+            assert_eq!(arr.footprint(), footprint);
+
+            // and upgrades to the tree form on growth.
+            let mut arr = arr;
+            let mut refv = vals.clone();
+            for i in 0..(n_items as u64 + 1) {
+                arr.insert(arr.len(), i).unwrap();
+                refv.push(i);
+            }
+            assert!(matches!(&arr.root, Root::Tree { root, .. } if root.len() > 0));
+            validate(&arr, &refv);
+        }
+    }
+}
+
+#[test]
+fn test_zero_sized() {
+    let seed: u128 = random();
+    println!("test_zero_sized seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for leaf_size in [None, Some(1), Some(100)].iter() {
+        let mut arr: Vector<()> = Vector::from_slice(&[(); 1000], *leaf_size);
+        let mut n = arr.len();
+        for _i in 0..100_000 {
+            let off = rng.gen::<usize>() % (n + 1);
+            match rng.gen::<u8>() % 6 {
+                0 => arr.insert(off, ()).unwrap(),
+                1 => arr.insert_mut(off, ()).unwrap(),
+                2 if off < n => {
+                    arr.remove(off).unwrap();
+                    n -= 2;
+                }
+                3 if off < n => {
+                    arr.remove_mut(off).unwrap();
+                    n -= 2;
+                }
+                4 if off < n => {
+                    arr.update(off, ()).unwrap();
+                    n -= 1;
+                }
+                _ => {
+                    assert_eq!(arr.get(off).is_ok(), off < n);
+                    n -= 1;
+                }
+            }
+            n += 1;
+            assert_eq!(arr.len(), n);
+        }
+        assert_eq!(arr.iter().count(), arr.len());
+        assert_eq!(arr.rebalance(true).unwrap().len(), arr.len());
+        if leaf_size.is_none() {
+            assert!(arr.footprint() < 100_000, "{}", arr.footprint());
+        }
+    }
+}
+
+#[test]
+fn test_tail_append() {
+    let seed: u128 = random();
+    println!("test_tail_append seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for fanout in [2, 16].iter() {
+        let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+        arr.set_fanout(*fanout);
+        let mut refv: Vec<u64> = vec![];
+        let mut snapshots = vec![];
+
+        for i in 0..1_000_000 {
+            match rng.gen::<bool>() {
+                true => arr.insert(arr.len(), i).unwrap(),
+                false => arr.insert_mut(arr.len(), i).unwrap(),
+            }
+            refv.push(i);
+            if i % 100_000 == 0 {
+                snapshots.push((arr.clone(), refv.clone()));
+            }
+        }
+        validate(&arr, &refv);
+
+        let n_leafs = arr.tree().unwrap().n_leafs() as f64;
+        println!("test_tail_append fanout:{} depth:{}", fanout, arr.depth());
+        assert!((arr.depth() as f64) <= n_leafs.log2().ceil() + 2.0);
+
+        // edits within the tail leaf node.
+        for _ in 0..1000 {
+            let off = arr.len() - 1 - (rng.gen::<usize>() % 100);
+            let val = rng.gen::<u64>();
+            match rng.gen::<u8>() % 4 {
+                0 => refv[off] = arr.update(off, val).map(|_| val).unwrap(),
+                1 => refv[off] = arr.update_mut(off, val).map(|_| val).unwrap(),
+                2 => assert_eq!(arr.remove(off).unwrap(), refv.remove(off)),
+                _ => {
+                    arr.insert(off, val).unwrap();
+                    refv.insert(off, val);
+                }
+            }
+        }
+        validate(&arr, &refv);
+        assert_eq!(arr.iter().copied().collect::<Vec<u64>>(), refv);
+
+        for (arr, refv) in snapshots.iter() {
+            validate(arr, refv);
+        }
+    }
+}
+
+#[test]
+fn test_local_rebalance() {
+    let seed: u128 = random();
+    println!("test_local_rebalance seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for _ in 0..4 {
+        let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+        let mut refv: Vec<u64> = vec![];
+
+        let in_place: bool = rng.gen();
+        for i in 0..100_000 {
+            // mostly prepend, to skew the tree.
+            let off = match rng.gen::<u8>() % 10 {
+                0 => rng.gen::<usize>() % (arr.len() + 1),
+                _ => 0,
+            };
+            match in_place {
+                true => arr.insert_mut(off, i).unwrap(),
+                false => arr.insert(off, i).unwrap(),
+            }
+            refv.insert(off, i);
+        }
+
+        let n_leafs = (arr.len() / max_leaf_items::<u64>(1024)) as f64;
+        let depth = arr.depth();
+        println!("test_local_rebalance in_place:{} depth:{}", in_place, depth);
+        assert!(
+            (depth as f64)
+                <= (n_leafs.log2() * 3.0) + (crate::REBALANCE_THRESHOLD as f64),
+            "depth:{} n_leafs:{}",
+            depth,
+            n_leafs
+        );
+        validate(&arr, &refv);
+    }
+}
+
+#[test]
+fn test_strict_balance() {
+    let seed: u128 = random();
+    println!("test_strict_balance seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+    arr.set_strict_balance(true);
+    let mut refv: Vec<u64> = vec![];
+
+    // split_off can leave vectors of a single leaf held inline.
+    let check = |arr: &Vector<u64>| {
+        let n_leafs = arr.tree().map(|root| root.n_leafs()).unwrap_or(1);
+        let depth = arr.depth();
+        assert!(depth <= strict_depth(n_leafs), "{} {}", depth, n_leafs);
+    };
+
+    for i in 0..100_000 {
+        let off = match rng.gen::<u8>() % 10 {
+            0 => rng.gen::<usize>() % (arr.len() + 1),
+            _ => 0,
+        };
+        match rng.gen::<bool>() {
+            true => arr.insert_mut(off, i).unwrap(),
+            false => arr.insert(off, i).unwrap(),
+        }
+        refv.insert(off, i);
+    }
+    check(&arr);
+    validate(&arr, &refv);
+
+    for _ in 0..50_000 {
+        let off = rng.gen::<usize>() % arr.len();
+        match rng.gen::<bool>() {
+            true => arr.remove_mut(off).unwrap(),
+            false => arr.remove(off).unwrap(),
+        };
+        refv.remove(off);
+    }
+    check(&arr);
+    validate(&arr, &refv);
+
+    while arr.len() > 1000 {
+        let off = rng.gen::<usize>() % arr.len();
+        let (a, b) = (arr.split_off(off).unwrap(), refv.split_off(off));
+        check(&arr);
+        check(&a);
+        validate(&a, &b);
+
+        let mut c = a.clone();
+        c.append(arr.clone());
+        let mut d = b.clone();
+        d.extend_from_slice(&refv);
+        check(&c);
+        validate(&c, &d);
+    }
+}
+
+#[test]
+fn test_max_depth() {
+    let seed: u128 = random();
+    println!("test_max_depth seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1016);
+    arr.set_auto_rebalance(false).set_max_depth(Some(16));
+    assert_eq!(arr.clone().max_depth(), Some(16));
+    let mut refv: Vec<u64> = vec![];
+
+    // inserting at the front, with auto-rebalance disabled, would otherwise
+    // grow the depth linearly.
+    for i in 0..20_000 {
+        match rng.gen::<bool>() {
+            true => arr.insert_mut(0, i).unwrap(),
+            false => arr.insert(0, i).unwrap(),
+        }
+        refv.insert(0, i);
+        if i % 100 == 0 {
+            assert!(arr.depth() <= 16, "{} {}", i, arr.depth());
+        }
+    }
+    validate(&arr, &refv);
+
+    arr.set_max_depth(None);
+    for i in 0..2000 {
+        arr.insert(0, i).unwrap();
+        refv.insert(0, i);
+    }
+    assert!(arr.depth() > 16, "{}", arr.depth());
+    validate(&arr, &refv);
+}
+
+#[test]
+fn test_access_profile() {
+    use crate::AccessProfile::*;
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let mut depths = vec![];
+    for profile in [RandomEdit, Balanced, ScanMostly].iter() {
+        let mut arr: Vector<u64> = Vector::default();
+        arr.set_access_profile(*profile);
+        assert_eq!(arr.leaf_size(), profile.to_leaf_size());
+
+        vals.iter()
+            .for_each(|val| arr.insert_mut(arr.len(), *val).unwrap());
+        validate(&arr, &vals);
+        depths.push(arr.depth());
+    }
+    assert!(
+        depths[0] > depths[1] && depths[1] > depths[2],
+        "{:?}",
+        depths
+    );
+
+    let mut arr = Vector::from_slice(&vals, None);
+    arr.set_access_profile(AppendMostly);
+    let arr = arr.compact();
+    assert_eq!(arr.leaf_size(), AppendMostly.to_leaf_size());
+    let n_items = max_leaf_items::<u64>(AppendMostly.to_leaf_size());
+    assert_eq!(arr.tree().unwrap().n_leafs(), vals.len().div_ceil(n_items));
+    validate(&arr, &vals);
+}
+
+#[test]
+fn test_fanout() {
+    let seed: u128 = random();
+    println!("test_fanout seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for fanout in [3, 8, 32].iter() {
+        let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+        arr.set_fanout(*fanout);
+        assert_eq!(arr.fanout(), *fanout);
+        let mut refv: Vec<u64> = vec![];
+
+        for i in 0..100_000 {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            match rng.gen::<bool>() {
+                true => arr.insert_mut(off, i).unwrap(),
+                false => arr.insert(off, i).unwrap(),
+            }
+            refv.insert(off, i);
+        }
+        arr = arr.rebalance(true).unwrap();
+        validate(&arr, &refv);
+
+        let binary = Vector::from_slice(&refv, Some(1024));
+        println!("test_fanout {} {} {}", fanout, arr.depth(), binary.depth());
+        assert!(arr.depth() < binary.depth());
+
+        for _ in 0..50_000 {
+            let off = rng.gen::<usize>() % arr.len();
+            let val = rng.gen::<u64>();
+            match rng.gen::<u8>() % 4 {
+                0 => refv[off] = arr.update(off, val).map(|_| val).unwrap(),
+                1 => refv[off] = arr.update_mut(off, val).map(|_| val).unwrap(),
+                2 => assert_eq!(arr.remove(off).unwrap(), refv.remove(off)),
+                _ => assert_eq!(arr.remove_mut(off).unwrap(), refv.remove(off)),
+            }
+        }
+        validate(&arr, &refv);
+        assert_eq!(arr.iter().copied().collect::<Vec<u64>>(), refv);
+        assert_eq!(arr.clone().into_iter().collect::<Vec<u64>>(), refv);
+
+        while arr.len() > 1000 {
+            let off = rng.gen::<usize>() % arr.len();
+            let (a, b) = (arr.split_off(off).unwrap(), refv.split_off(off));
+            assert_eq!(a.fanout(), *fanout);
+            validate(&a, &b);
+            validate(&arr, &refv);
+
+            let mut c = arr.clone();
+            c.append(a);
+            let mut d = refv.clone();
+            d.extend_from_slice(&b);
+            validate(&c.rebalance(false).unwrap(), &d);
+        }
+    }
+}
+
 #[test]
 fn test_delete_skew() {
     let seed: u128 = random();
@@ -185,6 +560,62 @@ fn test_delete_skew() {
     validate(&arr, &refv);
 }
 
+#[test]
+fn test_merge_leafs() {
+    let seed: u128 = random();
+    println!("test_merge_leafs seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for fanout in [2, 16].iter() {
+        let mut refv: Vec<u64> = (0..100_000).collect();
+        let mut arr = Vector::from_slice(&refv, Some(1024));
+        arr.set_fanout(*fanout);
+        arr = arr.rebalance(false).unwrap();
+        let n_items = max_leaf_items::<u64>(1024);
+
+        for _ in 0..95_000 {
+            let off = rng.gen::<usize>() % arr.len();
+            let val = match rng.gen::<bool>() {
+                true => arr.remove(off).unwrap(),
+                false => arr.remove_mut(off).unwrap(),
+            };
+            assert_eq!(val, refv.remove(off));
+        }
+        validate(&arr, &refv);
+
+        let n_leafs = arr.tree().unwrap().n_leafs();
+        println!("test_merge_leafs fanout:{} n_leafs:{}", fanout, n_leafs);
+        assert!(n_leafs <= (arr.len() * 4 / n_items) + 1, "{}", n_leafs);
+    }
+}
+
+#[test]
+fn test_compact() {
+    let seed: u128 = random();
+    println!("test_compact seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+    let mut refv: Vec<u64> = vec![];
+    for i in 0..100_000 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        arr.insert_mut(off, i).unwrap();
+        refv.insert(off, i);
+    }
+
+    let carr = arr.compact();
+    validate(&carr, &refv);
+    println!("test_compact {} -> {}", arr.footprint(), carr.footprint());
+    assert!(carr.footprint() < arr.footprint());
+    assert_eq!(carr.leaf_size(), 1024);
+
+    let n_items = max_leaf_items::<u64>(1024);
+    assert_eq!(carr.tree().unwrap().n_leafs(), refv.len().div_ceil(n_items));
+    assert_eq!(carr.compact().footprint(), carr.footprint());
+
+    assert_eq!(Vector::<u64>::default().compact().len(), 0);
+}
+
 #[test]
 fn test_from_slice() {
     let seed: u128 = random();
@@ -196,6 +627,902 @@ fn test_from_slice() {
     validate(&arr, &vals);
 }
 
+#[test]
+fn test_from_leaf_blocks() {
+    let seed: u128 = random();
+    println!("test_from_leaf_blocks seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 10, 1000, 10_000].iter() {
+        let mut vals: Vec<u64> = vec![];
+        let blocks: Vec<Vec<u64>> = (0..*n)
+            .map(|_| {
+                let block: Vec<u64> =
+                    (0..(rng.gen::<usize>() % 300)).map(|_| rng.gen()).collect();
+                vals.extend_from_slice(&block);
+                block
+            })
+            .collect();
+
+        let arr = Vector::from_leaf_blocks(blocks, Some(1024));
+        validate(&arr, &vals);
+    }
+}
+
+#[test]
+fn test_apply() {
+    let seed: u128 = random();
+    println!("test_apply seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 100, 10_000, 100_000].iter() {
+        let mut vals: Vec<u64> = (0..*n).collect();
+        let mut arr = Vector::from_slice(&vals, Some(128));
+        arr.insert_mut(arr.len(), 0).unwrap();
+        vals.push(0);
+
+        let mut edits = vec![];
+        let (mut inserts, mut updates) =
+            (vec![vec![]; vals.len() + 1], vec![None; vals.len()]);
+        for i in 0..rng.gen::<usize>() % 1000 {
+            let i = i as u64 + 1_000_000;
+            let off = rng.gen::<usize>() % (vals.len() + 1);
+            match rng.gen::<u8>() % 3 {
+                0 => {
+                    edits.push(Edit::Insert(off, i));
+                    inserts[off].push(i);
+                }
+                _ if off == vals.len() || updates[off].is_some() => (),
+                1 => {
+                    edits.push(Edit::Update(off, i));
+                    updates[off] = Some(Some(i));
+                }
+                _ => {
+                    edits.push(Edit::Remove(off));
+                    updates[off] = Some(None);
+                }
+            }
+        }
+
+        let mut refv = vec![];
+        for (off, val) in vals.iter().enumerate() {
+            refv.extend_from_slice(&inserts[off]);
+            match updates[off] {
+                Some(Some(val)) => refv.push(val),
+                Some(None) => (),
+                None => refv.push(*val),
+            }
+        }
+        refv.extend_from_slice(&inserts[vals.len()]);
+
+        let narr = arr.apply(&edits).unwrap();
+        validate(&narr, &refv);
+        validate(&arr, &vals);
+
+        let off = vals.len();
+        assert!(arr.apply(&[Edit::Insert(off + 1, 0)]).is_err());
+        assert!(arr.apply(&[Edit::Update(off, 0)]).is_err());
+        assert!(arr.apply(&[Edit::Remove(0), Edit::Update(0, 0)]).is_err());
+    }
+}
+
+#[test]
+fn test_insert_many() {
+    let seed: u128 = random();
+    println!("test_insert_many seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+
+    let mut items: Vec<(usize, u64)> = (0..10_000)
+        .map(|i| (rng.gen::<usize>() % (vals.len() + 1), i + 1_000_000))
+        .collect();
+    let narr = arr.insert_many(items.clone()).unwrap();
+
+    items.sort_by_key(|(off, _)| *off);
+    let mut refv = vals.clone();
+    for (off, val) in items.into_iter().rev() {
+        refv.insert(off, val)
+    }
+    validate(&narr, &refv);
+    validate(&arr, &vals);
+
+    assert!(arr.insert_many(vec![(vals.len() + 1, 0)]).is_err());
+}
+
+#[test]
+fn test_delete_many() {
+    let seed: u128 = random();
+    println!("test_delete_many seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let arr = Vector::from_slice(&vals, Some(128));
+
+    let mut indices: Vec<usize> = (0..vals.len())
+        .filter(|_| rng.gen::<u8>() % 4 == 0)
+        .collect();
+    let narr = arr.delete_many(&indices).unwrap();
+    let refv: Vec<u64> = vals
+        .iter()
+        .copied()
+        .filter(|x| indices.binary_search(&(*x as usize)).is_err())
+        .collect();
+    validate(&narr, &refv);
+    validate(&arr, &vals);
+
+    let all: Vec<usize> = (0..vals.len()).rev().collect();
+    assert_eq!(arr.delete_many(&all).unwrap().len(), 0);
+
+    indices.push(vals.len());
+    assert!(arr.delete_many(&indices).is_err());
+    assert!(arr.delete_many(&[1, 1]).is_err());
+}
+
+#[test]
+fn test_deep_tree() {
+    let seed: u128 = random();
+    println!("test_deep_tree seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    // with auto-rebalance disabled and two items per leaf node, every
+    // insert at the head of the vector deepens the tree by a level.
+    let n = 20_000;
+    let mut arr: Vector<u64> = Vector::with_leaf_size(16);
+    arr.set_auto_rebalance(false);
+    let mut refv: Vec<u64> = vec![];
+    for i in 0..n {
+        arr.insert_mut(0, i).unwrap();
+        refv.insert(0, i);
+    }
+
+    // copy-on-write, while the older version is alive.
+    let mut carr = arr.clone();
+    for _i in 0..100 {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(carr.get(off).unwrap(), &refv[off]);
+        match rng.gen::<u8>() % 3 {
+            0 => {
+                carr.insert(off, off as u64).unwrap();
+                refv.insert(off, off as u64);
+            }
+            1 => {
+                let val = carr.update(off, off as u64).unwrap();
+                assert_eq!(val, mem::replace(&mut refv[off], off as u64));
+            }
+            _ => assert_eq!(carr.remove(off).unwrap(), refv.remove(off)),
+        }
+    }
+    mem::drop(arr);
+
+    // in-place, under single ownership.
+    let mut arr = carr;
+    for _i in 0..100 {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(arr.get(off).unwrap(), &refv[off]);
+        match rng.gen::<u8>() % 3 {
+            0 => {
+                arr.insert_mut(off, off as u64).unwrap();
+                refv.insert(off, off as u64);
+            }
+            1 => {
+                let val = arr.update_mut(off, off as u64).unwrap();
+                assert_eq!(val, mem::replace(&mut refv[off], off as u64));
+            }
+            _ => assert_eq!(arr.remove_mut(off).unwrap(), refv.remove(off)),
+        }
+    }
+    assert!(arr.iter().eq(refv.iter()));
+    assert!(arr.clone().into_iter().eq(refv.clone().into_iter()));
+
+    let arr = arr.rebalance(true).unwrap();
+    assert!(arr.depth() < 20, "{}", arr.depth());
+    assert!(arr.iter().eq(refv.iter()));
+}
+
+#[test]
+fn test_leaf_capacity() {
+    let seed: u128 = random();
+    println!("test_leaf_capacity seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let leaf_capacity = |root: Ref<Node<u64>>| -> Vec<(usize, usize)> {
+        Node::collect_leaf_nodes(root, false, 1000)
+            .iter()
+            .map(|leaf| match leaf.as_ref() {
+                Node::Z { data } => (data.len(), data.capacity()),
+                _ => unreachable!(),
+            })
+            .collect()
+    };
+    let n_items = max_leaf_items::<u64>(1000);
+
+    // copy-on-write leaf nodes are allocated to their exact size.
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1000);
+    let mut refv: Vec<u64> = vec![];
+    for i in 0..100_000 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        match rng.gen::<u8>() % 4 {
+            0 if off < arr.len() => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off))
+            }
+            _ => {
+                arr.insert(off, i).unwrap();
+                refv.insert(off, i);
+            }
+        }
+    }
+    validate(&arr, &refv);
+    for (n, cap) in leaf_capacity(Ref::clone(arr.tree().unwrap())).into_iter() {
+        assert_eq!(n, cap);
+    }
+    let (footprint, compact) = (arr.footprint(), arr.compact().footprint());
+    println!("test_leaf_capacity {} -> {}", footprint, compact);
+    assert!(footprint < compact * 2);
+
+    // in-place leaf nodes never grow beyond leaf capacity.
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1000);
+    for i in 0..100_000 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        arr.insert_mut(off, i).unwrap();
+    }
+    for (n, cap) in leaf_capacity(arr.root_with_tail()).into_iter() {
+        assert!(n <= cap && cap <= n_items, "{} {}", n, cap);
+    }
+
+    // leaf nodes holding a single item, are split into single item leafs.
+    for leaf_size in [1, 8].iter() {
+        let mut arr: Vector<u64> = Vector::from_slice(&[0; 100], Some(*leaf_size));
+        for i in 0..1000 {
+            let off = rng.gen::<usize>() % (arr.len() + 1);
+            match i % 2 {
+                0 => arr.insert(off, i).unwrap(),
+                _ => arr.insert_mut(off, i).unwrap(),
+            }
+        }
+        assert_eq!(arr.validate().unwrap().max_leaf_items, 1);
+    }
+}
+
+#[test]
+fn test_dedup_leaves() {
+    let n_items = max_leaf_items::<u64>(1024);
+    let leaf_ptrs = |arr: &Vector<u64>| -> Vec<*const Node<u64>> {
+        let root = arr.root_with_tail();
+        let leafs = Node::collect_leaf_nodes(root, false, arr.leaf_size());
+        leafs.iter().map(Ref::as_ptr).collect()
+    };
+
+    // sparse vector, with every tenth leaf node holding non-zero items.
+    let vals: Vec<u64> = (0..(n_items * 1000))
+        .map(|i| match (i / n_items) % 10 {
+            0 => i as u64,
+            _ => 0,
+        })
+        .collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+
+    let mut interner = Interner::default();
+    let (darr, saved) = arr.dedup_leaves_with(&mut interner);
+    validate(&darr, &vals);
+    assert_eq!(interner.len(), 101);
+
+    let mut ptrs = leaf_ptrs(&darr);
+    assert_eq!(ptrs.len(), 1000);
+    ptrs.sort_unstable();
+    ptrs.dedup();
+    assert_eq!(ptrs.len(), 101);
+    let leaf_footprint = mem::size_of::<Node<u64>>() + (n_items * 8);
+    assert_eq!(saved, (1000 - 101) * leaf_footprint);
+
+    // leaf nodes are shared across vectors using the same interner.
+    let mut vals2 = vals.clone();
+    vals2[0] = 1;
+    let arr2 = Vector::from_slice(&vals2, Some(1024));
+    let (darr2, _) = arr2.dedup_leaves_with(&mut interner);
+    validate(&darr2, &vals2);
+    assert_eq!(interner.len(), 102);
+    let (a, b) = (leaf_ptrs(&darr), leaf_ptrs(&darr2));
+    assert_ne!(a[0], b[0]);
+    assert_eq!(a[1..], b[1..]);
+
+    let (arr, saved) = Vector::<u64>::default().dedup_leaves();
+    assert_eq!((arr.len(), saved), (0, 0));
+}
+
+#[test]
+fn test_stats() {
+    let seed: u128 = random();
+    println!("test_stats seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let stats = Vector::<u64>::default().stats();
+    assert_eq!((stats.n_items, stats.n_leafs, stats.n_nodes), (0, 0, 0));
+    assert_eq!((stats.min_leaf_items, stats.fill), (0, 0.0));
+
+    let n_items = max_leaf_items::<u64>(1024);
+    let vals: Vec<u64> = (0..(n_items as u64 * 100)).collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+    let stats = arr.stats();
+    println!("test_stats {:?}", stats);
+    assert_eq!(stats.n_items, vals.len());
+    assert_eq!(stats.depth, arr.depth());
+    assert_eq!((stats.n_leafs, stats.n_nodes), (100, 99));
+    assert_eq!(
+        (stats.min_leaf_items, stats.max_leaf_items),
+        (n_items, n_items)
+    );
+    assert_eq!((stats.leaf_capacity, stats.fill), (n_items, 1.0));
+
+    let mut arr: Vector<u64> = Vector::with_leaf_size(1024);
+    for i in 0..100_000 {
+        let off = rng.gen::<usize>() % (arr.len() + 1);
+        arr.insert_mut(off, i).unwrap();
+    }
+    let stats = arr.stats();
+    println!("test_stats {:?}", stats);
+    assert!(stats.fill < 1.0 && stats.fill > 0.5, "{}", stats.fill);
+    assert!(stats.min_leaf_items <= stats.max_leaf_items);
+    assert!(stats.max_leaf_items <= n_items);
+
+    let stats = arr.compact().stats();
+    assert!(stats.fill > 0.99, "{}", stats.fill);
+}
+
+#[test]
+fn test_footprint_exclusive() {
+    let seed: u128 = random();
+    println!("test_footprint_exclusive seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    // 512 leaf nodes, all of them at the same depth.
+    let vals: Vec<u64> = (0..65536).collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+    assert_eq!(arr.footprint_exclusive(&[]), arr.footprint());
+    assert_eq!(
+        arr.footprint_exclusive(&[&arr]),
+        mem::size_of::<Vector<u64>>()
+    );
+
+    // snapshot differing by a single leaf node, and the path to it.
+    let mut snap = arr.clone();
+    let off = rng.gen::<usize>() % snap.len();
+    snap.update(off, 0).unwrap();
+    let n = snap.footprint_exclusive(&[&arr]);
+    let leaf = mem::size_of::<Node<u64>>() + 1024;
+    let path = (snap.depth() - 1) * mem::size_of::<Node<u64>>();
+    assert_eq!(n, mem::size_of::<Vector<u64>>() + leaf + path);
+    assert_eq!(arr.footprint_exclusive(&[&snap]), n);
+    assert_eq!(Vector::footprint_union(&[&arr, &snap]), arr.footprint() + n);
+    assert_eq!(Vector::footprint_union(&[&arr]), arr.footprint());
+
+    // sharing between the two versions.
+    let stats = snap.shared_fraction(&arr);
+    assert_eq!(stats.n_leafs, 512);
+    assert_eq!(stats.shared_leafs, 511);
+    assert_eq!(stats.n_nodes - stats.shared_nodes, snap.depth() - 1);
+    let m = mem::size_of::<Vector<u64>>();
+    assert_eq!(stats.bytes - stats.shared_bytes + m, n);
+    assert_eq!(stats.bytes + m, snap.footprint());
+    assert!(stats.fraction() > 0.99, "{}", stats.fraction());
+    let stats = arr.shared_fraction(&arr.clone());
+    assert_eq!(stats.shared_bytes, stats.bytes);
+    assert_eq!(stats.n_leafs, stats.shared_leafs);
+
+    // nodes shared within a vector are counted only once.
+    let (darr, saved) = Vector::from_slice(&vec![0; 100_000], Some(1024)).dedup_leaves();
+    assert_eq!(darr.footprint_exclusive(&[]) + saved, darr.footprint());
+}
+
+#[test]
+fn test_error_kinds() {
+    let mut arr = Vector::from_slice(&[1_u64, 2, 3], None);
+
+    let err = Error::IndexOutOfBounds { index: 3, len: 3 };
+    assert_eq!(arr.get(3).unwrap_err(), err);
+    assert_eq!(arr.update(3, 0).unwrap_err(), err);
+    assert_eq!(arr.remove_mut(3).unwrap_err(), err);
+    assert_eq!(arr.finger().get(3).unwrap_err(), err);
+    let err = Error::IndexOutOfBounds { index: 4, len: 3 };
+    assert_eq!(arr.insert(4, 0).unwrap_err(), err);
+    assert_eq!(arr.split_off(4).err(), Some(err.clone()));
+    println!("test_error_kinds {}", err);
+
+    let err = Error::DuplicateEdit { index: 1 };
+    assert_eq!(arr.delete_many(&[1, 0, 1]).err(), Some(err.clone()));
+    assert_eq!(
+        format!("{}", err),
+        "DuplicateEdit: index 1 edited more than once"
+    );
+}
+
+// tree form of a vector over `root`, with an empty tail leaf node.
+fn tree<T>(root: Ref<Node<T>>) -> Root<T> {
+    Root::Tree {
+        root,
+        tail: Node::empty_leaf(),
+    }
+}
+
+#[test]
+fn test_validate() {
+    let vals: Vec<u64> = (0..10_000).collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+    let stats = arr.validate().unwrap();
+    assert_eq!(stats, arr.stats());
+    assert!(Vector::<u64>::default().validate().is_ok());
+
+    let leaf = |n: u64| {
+        Ref::new(Node::Z {
+            data: (0..n).collect(),
+        })
+    };
+
+    // weight does not match the left subtree.
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    arr.root = tree(Ref::new(Node::M {
+        weight: 10,
+        len: 20,
+        depth: 2,
+        left: leaf(9),
+        right: leaf(11),
+    }));
+    arr.len = 20;
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // len does not match the subtrees.
+    arr.root = tree(Ref::new(Node::M {
+        weight: 10,
+        len: 21,
+        depth: 2,
+        left: leaf(10),
+        right: leaf(10),
+    }));
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // cumulative count of children.
+    arr.root = tree(Ref::new(Node::N {
+        children: vec![(10, leaf(10)), (21, leaf(10))],
+        depth: 2,
+    }));
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // interior node with a single child.
+    arr.root = tree(Ref::new(Node::N {
+        children: vec![(20, leaf(20))],
+        depth: 2,
+    }));
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // depth cached in the interior node does not match its child nodes.
+    arr.root = tree(Ref::new(Node::M {
+        weight: 10,
+        len: 20,
+        depth: 1,
+        left: leaf(10),
+        right: leaf(10),
+    }));
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // leaf node beyond its capacity.
+    arr.root = tree(leaf(1000));
+    arr.len = 1000;
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+
+    // length of the vector.
+    arr.root = tree(Ref::new(Node::M {
+        weight: 10,
+        len: 20,
+        depth: 2,
+        left: leaf(10),
+        right: leaf(10),
+    }));
+    arr.len = 30;
+    assert!(matches!(arr.validate(), Err(Error::Corrupted { .. })));
+    arr.len = 20;
+    assert!(arr.validate().is_ok());
+}
+
+#[test]
+fn test_panic_free_get() {
+    let leaf = |n: u64| {
+        Ref::new(Node::Z {
+            data: (0..n).collect(),
+        })
+    };
+    let roots = vec![
+        // weights beyond the items in leaf nodes.
+        Ref::new(Node::M {
+            weight: 10,
+            len: 20,
+            depth: 2,
+            left: leaf(5),
+            right: leaf(5),
+        }),
+        Ref::new(Node::M {
+            weight: 2,
+            len: 20,
+            depth: 2,
+            left: leaf(5),
+            right: leaf(5),
+        }),
+        // cumulative counts out of order, and empty interior node.
+        Ref::new(Node::N {
+            children: vec![(10, leaf(5)), (3, leaf(5))],
+            depth: 2,
+        }),
+        Ref::new(Node::N {
+            children: vec![],
+            depth: 1,
+        }),
+    ];
+
+    for root in roots.into_iter() {
+        let mut arr: Vector<u64> = Vector::default();
+        arr.root = tree(root);
+        arr.len = 20;
+        assert!(arr.validate().is_err());
+
+        // corrupted tree shall not panic, only return error.
+        let mut finger = arr.finger();
+        for off in 0..25 {
+            for res in [arr.get(off), finger.get(off)].iter() {
+                match res {
+                    Ok(_) => (),
+                    Err(Error::Corrupted { .. }) => (),
+                    Err(Error::IndexOutOfBounds { .. }) => assert!(off >= 20),
+                    Err(err) => panic!("{}", err),
+                }
+            }
+        }
+    }
+}
+
+// stands in for a link-time no-panic check, which can't hold for a vector
+// whose items clone and allocate, write operations on a corrupted tree
+// shall fail with error instead of panic.
+#[cfg(not(feature = "verify"))]
+#[test]
+fn test_panic_free_write() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let leaf = |n: u64| {
+        Ref::new(Node::Z {
+            data: (0..n).collect(),
+        })
+    };
+    let roots = vec![
+        Ref::new(Node::M {
+            weight: 10,
+            len: 20,
+            depth: 2,
+            left: leaf(5),
+            right: leaf(5),
+        }),
+        Ref::new(Node::M {
+            weight: 2,
+            len: 20,
+            depth: 2,
+            left: leaf(5),
+            right: leaf(5),
+        }),
+        Ref::new(Node::N {
+            children: vec![(10, leaf(5)), (3, leaf(5))],
+            depth: 2,
+        }),
+        Ref::new(Node::N {
+            children: vec![],
+            depth: 1,
+        }),
+    ];
+
+    type Op = fn(&mut Vector<u64>, usize) -> Result<()>;
+    let ops: Vec<(&str, Op)> = vec![
+        ("insert", |arr, off| arr.insert(off, 100)),
+        ("insert_mut", |arr, off| arr.insert_mut(off, 100)),
+        ("update", |arr, off| arr.update(off, 100).map(|_| ())),
+        ("update_mut", |arr, off| {
+            arr.update_mut(off, 100).map(|_| ())
+        }),
+        ("remove", |arr, off| arr.remove(off).map(|_| ())),
+        ("remove_mut", |arr, off| arr.remove_mut(off).map(|_| ())),
+        ("finger_mut", |arr, off| {
+            arr.finger_mut().update(off, 100).map(|_| ())
+        }),
+    ];
+
+    for root in roots.into_iter() {
+        for (name, op) in ops.iter() {
+            for (off, rebalance) in
+                (0..25).flat_map(|off| vec![(off, false), (off, true)])
+            {
+                let mut arr: Vector<u64> = Vector::default();
+                arr.root = tree(Ref::clone(&root));
+                arr.len = 20;
+                arr.set_auto_rebalance(rebalance);
+
+                let res = catch_unwind(AssertUnwindSafe(|| op(&mut arr, off)));
+                match res {
+                    Ok(Ok(_)) => (),
+                    Ok(Err(Error::Corrupted { .. })) => (),
+                    Ok(Err(Error::IndexOutOfBounds { .. })) => assert!(off >= 20),
+                    Ok(Err(err)) => panic!("{} off:{} {}", name, off, err),
+                    Err(_) => panic!("{} off:{} panicked", name, off),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing() {
+    use std::sync::{Arc, Mutex};
+    use tracing::{field, span, Event, Id, Metadata};
+
+    type Spans = Arc<Mutex<Vec<(&'static str, Vec<(String, u64)>)>>>;
+
+    // collect spans, along with their recorded fields.
+    struct Collect(Spans);
+    struct Fields<'a>(&'a mut Vec<(String, u64)>);
+
+    impl<'a> field::Visit for Fields<'a> {
+        fn record_u64(&mut self, field: &field::Field, value: u64) {
+            self.0.push((field.name().to_string(), value));
+        }
+
+        fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+    }
+
+    impl tracing::Subscriber for Collect {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes) -> Id {
+            let mut spans = self.0.lock().unwrap();
+            spans.push((span.metadata().name(), vec![]));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, id: &Id, values: &span::Record) {
+            let mut spans = self.0.lock().unwrap();
+            let fields = &mut spans[(id.into_u64() - 1) as usize].1;
+            values.record(&mut Fields(fields));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event) {}
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    let spans: Spans = Arc::default();
+    tracing::subscriber::with_default(Collect(Arc::clone(&spans)), || {
+        let mut arr = Vector::from_slice(&vec![0_u64; 10_000], Some(1024));
+        let other = arr.split_off(5000).unwrap();
+        arr.append(other);
+        arr.rebalance(true).unwrap();
+    });
+
+    let spans = spans.lock().unwrap();
+    let names: Vec<&str> = spans.iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "ppar::build",
+            "ppar::split_off",
+            "ppar::append",
+            "ppar::rebalance"
+        ]
+    );
+    for (name, fields) in spans.iter() {
+        let fields: Vec<&str> = fields.iter().map(|(f, _)| f.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["n_items", "depth", "n_leafs", "elapsed_us"],
+            "{}",
+            name
+        );
+    }
+    assert_eq!(spans[0].1[0].1, 10_000);
+}
+
+#[test]
+fn test_builder() {
+    let seed: u128 = random();
+    println!("test_builder seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 10, 1000, 1_000_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+
+        let mut builder = VectorBuilder::with_capacity(*n, Some(1024));
+        vals.iter().for_each(|val| builder.push(*val));
+        assert_eq!(builder.len(), *n);
+        let arr = builder.build();
+        validate(&arr, &vals);
+
+        let mut builder = VectorBuilder::new(Some(1024));
+        let (a, b) = vals.split_at(*n / 3);
+        builder.extend(a.iter().copied());
+        builder.reserve(b.len());
+        builder.extend(b.iter().copied());
+        assert_eq!(builder.is_empty(), vals.is_empty());
+        let arr = builder.build();
+        validate(&arr, &vals);
+    }
+}
+
+#[test]
+fn test_freeze_thaw() {
+    let seed: u128 = random();
+    println!("test_freeze_thaw seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..65536).collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+
+    // single edit copies only the path to the edited leaf node.
+    let mut marr = arr.clone().thaw();
+    let off = rng.gen::<usize>() % marr.len();
+    assert_eq!(marr.update(off, 0).unwrap(), vals[off]);
+    let snap = marr.freeze();
+    let n = snap.footprint_exclusive(&[&arr]);
+    assert!(n < (arr.footprint() / 100), "{} {}", n, arr.footprint());
+    assert_eq!(arr.get(off).unwrap(), &vals[off]);
+    assert_eq!(snap.get(off).unwrap(), &0);
+
+    let mut refv = vals.clone();
+    let mut marr = arr.clone().thaw();
+    for _i in 0..10_000 {
+        match rng.gen::<u8>() % 3 {
+            0 => {
+                let off = rng.gen::<usize>() % (refv.len() + 1);
+                let val = rng.gen::<u64>();
+                refv.insert(off, val);
+                marr.insert(off, val).unwrap();
+            }
+            1 if !refv.is_empty() => {
+                let off = rng.gen::<usize>() % refv.len();
+                let val = rng.gen::<u64>();
+                assert_eq!(marr.update(off, val).unwrap(), refv[off]);
+                refv[off] = val;
+            }
+            _ if !refv.is_empty() => {
+                let off = rng.gen::<usize>() % refv.len();
+                assert_eq!(marr.remove(off).unwrap(), refv.remove(off));
+            }
+            _ => (),
+        }
+    }
+    assert_eq!(marr.len(), refv.len());
+    let res = marr.freeze();
+    assert_eq!(res.iter().copied().collect::<Vec<u64>>(), refv);
+    validate(&arr, &vals);
+
+    // leaf nodes shared within a vector are copied on first touch.
+    let (darr, _) = Vector::from_slice(&vec![0_u64; 10_000], Some(1024)).dedup_leaves();
+    let mut marr = darr.thaw();
+    marr.update(0, 1).unwrap();
+    let darr = marr.freeze();
+    assert_eq!(darr.get(0).unwrap(), &1);
+    assert_eq!(darr.iter().filter(|x| **x == 1).count(), 1);
+}
+
+#[test]
+fn test_focus() {
+    let seed: u128 = random();
+    println!("test_focus seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..65536).collect();
+    let arr = Vector::from_slice(&vals, Some(1024));
+    assert!(arr.clone().focus(vals.len() + 1).is_err());
+
+    let mut refv = vals.clone();
+    let mut focus = arr.clone().focus(1000).unwrap();
+    let mut snaps = vec![];
+    for i in 0..20_000 {
+        // mostly typing at the cursor, with the occasional jump.
+        let off = match rng.gen::<u8>() % 10 {
+            0 => rng.gen::<usize>() % (refv.len() + 1),
+            _ => focus.cursor().min(refv.len()),
+        };
+        match rng.gen::<u8>() % 10 {
+            0..=5 => {
+                focus.insert(off, i).unwrap();
+                refv.insert(off, i);
+            }
+            6 if off < refv.len() => {
+                assert_eq!(focus.update(off, i).unwrap(), refv[off]);
+                refv[off] = i;
+            }
+            7 | 8 if off > 0 => {
+                // backspace.
+                assert_eq!(focus.remove(off - 1).unwrap(), refv.remove(off - 1));
+            }
+            _ if off < refv.len() => assert_eq!(focus.get(off).unwrap(), &refv[off]),
+            _ => (),
+        }
+        assert_eq!(focus.len(), refv.len());
+        if i % 2000 == 0 {
+            let cursor = focus.cursor();
+            snaps.push((focus.snapshot().unwrap(), refv.clone()));
+            assert_eq!(focus.cursor(), cursor);
+        }
+    }
+    let n = refv.len();
+    assert!(focus.get(n).is_err());
+    assert!(focus.insert(n + 1, 0).is_err());
+    assert!(focus.remove(n).is_err());
+
+    validate(&focus.freeze().unwrap(), &refv);
+    for (arr, refv) in snaps.iter() {
+        validate(arr, refv);
+    }
+    validate(&arr, &vals);
+}
+
+#[test]
+fn test_concat_all() {
+    let seed: u128 = random();
+    println!("test_concat_all seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut arrs = vec![];
+    let mut refv: Vec<u64> = vec![];
+    for i in 0..100 {
+        let n = rng.gen::<usize>() % 10_000;
+        let vals: Vec<u64> = (0..n).map(|_| rng.gen()).collect();
+        // fragments with a tail leaf node, and with a different leaf size.
+        let mut arr = match i % 10 {
+            5 => Vector::from_slice(&vals, Some(256)),
+            _ => Vector::from_slice(&vals, Some(1024)),
+        };
+        arr.insert(arr.len(), i).unwrap();
+        refv.extend_from_slice(&vals);
+        refv.push(i);
+        arrs.push(arr);
+    }
+
+    let arr = Vector::concat_all(arrs.clone());
+    validate(&arr, &refv);
+    assert_eq!(arr.leaf_size(), arrs[0].leaf_size());
+    // leaf nodes are shared with the fragments, except for the tenth of
+    // fragments rebuilt to the leaf size of the first.
+    let n = arr.footprint_exclusive(&arrs.iter().collect::<Vec<&Vector<u64>>>());
+    assert!(n < (arr.footprint() / 5), "{} {}", n, arr.footprint());
+
+    let nested: Vector<Vector<u64>> = arrs.iter().cloned().collect();
+    validate(&nested.flatten(), &refv);
+
+    assert!(Vector::<u64>::concat_all(vec![]).is_empty());
+    let empty = Vector::concat_all(vec![Vector::<u64>::default(), Vector::default()]);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_build() {
+    let seed: u128 = random();
+    println!("test_build seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    for n in [0, 1, 10, 1000, 1_000_000].iter() {
+        let vals: Vec<u64> = (0..*n).map(|_| rng.gen()).collect();
+
+        let arr = Vector::build(vals.clone(), Some(1024));
+        validate(&arr, &vals);
+
+        let arr: Vector<u64> = vals.iter().copied().collect();
+        validate(&arr, &vals);
+    }
+}
+
 #[test]
 fn test_to_vec() {
     let seed: u128 = random();
@@ -218,6 +1545,108 @@ fn test_iter() {
     let iter_vals: Vec<u64> = arr.iter().copied().collect();
 
     assert_eq!(vals, iter_vals);
+
+    let mut iter = arr.iter();
+    assert_eq!(iter.len(), vals.len());
+    iter.nth(1000);
+    assert_eq!(
+        iter.size_hint(),
+        (vals.len() - 1001, Some(vals.len() - 1001))
+    );
+}
+
+#[test]
+fn test_finger() {
+    let seed: u128 = random();
+    println!("test_finger seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    for _ in 0..1000 {
+        let val = rng.gen::<u64>();
+        arr.insert(arr.len(), val).unwrap();
+        vals.push(val);
+    }
+
+    let mut finger = arr.finger();
+    for (off, val) in vals.iter().enumerate() {
+        assert_eq!(finger.get(off).unwrap(), val);
+    }
+    for _ in 0..100_000 {
+        let off = rng.gen::<usize>() % vals.len();
+        assert_eq!(finger.get(off).unwrap(), &vals[off]);
+    }
+    assert!(finger.get(vals.len()).is_err());
+
+    let arr: Vector<u64> = Vector::default();
+    assert!(arr.finger().get(0).is_err());
+}
+
+#[test]
+fn test_finger_mut() {
+    let seed: u128 = random();
+    println!("test_finger_mut seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut vals: Vec<u64> = (0..100_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    for _ in 0..1000 {
+        let val = rng.gen::<u64>();
+        arr.insert(arr.len(), val).unwrap();
+        vals.push(val);
+    }
+    let old = arr.clone();
+    let old_vals = vals.clone();
+
+    // sequential access descends the tree once per leaf node.
+    let n_leafs = arr.stats().n_leafs + 1;
+    let mut finger = arr.finger_mut();
+    for (off, refval) in vals.iter_mut().enumerate() {
+        assert_eq!(finger.get(off).unwrap(), refval);
+        if off % 3 == 2 {
+            let val = rng.gen::<u64>();
+            assert_eq!(finger.update(off, val).unwrap(), *refval);
+            *refval = val;
+        }
+    }
+    assert!(finger.misses <= n_leafs, "{} {}", finger.misses, n_leafs);
+
+    // get, get and update near the last access hit the cached leaf node.
+    let misses = finger.misses;
+    finger.get(10).unwrap();
+    finger.get(11).unwrap();
+    finger.update(12, 12).unwrap();
+    vals[12] = 12;
+    assert_eq!(finger.misses, misses + 1);
+
+    for _ in 0..10_000 {
+        let off = rng.gen::<usize>() % vals.len();
+        let val = rng.gen::<u64>();
+        assert_eq!(finger.update(off, val).unwrap(), vals[off]);
+        vals[off] = val;
+        assert_eq!(finger.get(off).unwrap(), &vals[off]);
+    }
+    assert!(finger.get(vals.len()).is_err());
+    assert!(finger.update(vals.len(), 0).is_err());
+    std::mem::drop(finger);
+
+    validate(&arr, &vals);
+    arr.validate().unwrap();
+    // older versions are left untouched.
+    validate(&old, &old_vals);
+
+    // items stay in the tree, even if the finger is forgotten.
+    let mut finger = arr.finger_mut();
+    finger.update(100, 100).unwrap();
+    finger.get(50_000).unwrap();
+    std::mem::forget(finger);
+    vals[100] = 100;
+    validate(&arr, &vals);
+    arr.validate().unwrap();
+
+    let mut arr: Vector<u64> = Vector::default();
+    assert!(arr.finger_mut().get(0).is_err());
 }
 
 #[test]
@@ -228,6 +1657,11 @@ fn test_into_iter() {
 
     let vals: Vec<u64> = (0..1_000_000).map(|_| rng.gen()).collect();
     let arr = Vector::from_slice(&vals, None);
+    let mut iter = arr.clone().into_iter();
+    assert_eq!(iter.len(), vals.len());
+    iter.nth(1000);
+    assert_eq!(iter.len(), vals.len() - 1001);
+
     let iter_vals: Vec<u64> = arr.into_iter().collect();
 
     assert_eq!(vals, iter_vals);
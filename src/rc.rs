@@ -1,13 +1,153 @@
 //! Module implement persistent array, faster but not thread safe.
+//!
+//! `rc::Vector` and its iterators are neither `Send` nor `Sync`, and it stays
+//! that way when wrapped inside other types. Following won't compile:
+//!
+//! ```compile_fail
+//! let arr: ppar::rc::Vector<u64> = ppar::rc::Vector::default();
+//! std::thread::spawn(move || arr.len());
+//! ```
+//!
+//! ```compile_fail
+//! struct Wrapper(ppar::rc::Vector<u64>);
+//!
+//! let w = Wrapper(ppar::rc::Vector::default());
+//! std::thread::spawn(move || w.0.len());
+//! ```
+//!
+//! ```compile_fail
+//! use std::sync::{Arc, Mutex};
+//!
+//! let arr: ppar::rc::Vector<u64> = ppar::rc::Vector::default();
+//! let arr = Arc::new(Mutex::new(Some(arr)));
+//! std::thread::spawn(move || arr.lock().unwrap().take());
+//! ```
+//!
+//! ```compile_fail
+//! let arr: ppar::rc::Vector<u64> = ppar::rc::Vector::default();
+//! let iter = arr.into_iter();
+//! std::thread::spawn(move || iter.count());
+//! ```
 
 use std::rc::Rc as Ref;
+#[cfg(feature = "ipld")]
+use std::rc::Weak;
 
+#[allow(clippy::duplicate_mod)]
 #[path = "./ppar.rs"]
 mod ppar;
 
 pub use self::ppar::Vector;
 pub use self::ppar::*;
 
+#[allow(clippy::duplicate_mod)]
+#[path = "./measured.rs"]
+mod measured;
+
+pub use self::measured::{MeasuredIter, MeasuredVector};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./text.rs"]
+mod text;
+
+pub use self::text::RopeString;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./stream.rs"]
+mod stream;
+
+pub use self::stream::{RopeReader, RopeWriter};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./bits.rs"]
+mod bits;
+
+pub use self::bits::BitRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./sorted.rs"]
+mod sorted;
+
+pub use self::sorted::SortedRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./rle.rs"]
+mod rle;
+
+pub use self::rle::RleRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./marker.rs"]
+mod marker;
+
+pub use self::marker::{MarkedVector, MarkerId};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./kv.rs"]
+mod kv;
+
+pub use self::kv::KvRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./time.rs"]
+mod time;
+
+pub use self::time::TimeRope;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./deque.rs"]
+mod deque;
+
+pub use self::deque::PersistentDeque;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./ring.rs"]
+mod ring;
+
+pub use self::ring::SnapshotRing;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./diff.rs"]
+mod diff;
+
+pub use self::diff::{Conflict, MergeResult};
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./history.rs"]
+mod history;
+
+pub use self::history::History;
+
+#[allow(clippy::duplicate_mod)]
+#[path = "./snapshots.rs"]
+mod snapshots;
+
+pub use self::snapshots::Snapshots;
+
+#[cfg(feature = "crdt")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./crdt.rs"]
+mod crdt;
+
+#[cfg(feature = "crdt")]
+pub use self::crdt::RgaVector;
+
+#[cfg(feature = "ipld")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./dag.rs"]
+mod dag;
+
+#[cfg(feature = "ipld")]
+pub use self::dag::Flushed;
+
+#[cfg(feature = "lz4")]
+#[allow(clippy::duplicate_mod)]
+#[path = "./compressed.rs"]
+mod compressed;
+
+#[cfg(feature = "lz4")]
+pub use self::compressed::CompressedVector;
+
 impl<T> Vector<T>
 where
     T: Clone,
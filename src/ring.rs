@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+/// Bounded ring of snapshots, retaining the last N versions of a vector.
+///
+/// Like the last 300 frames of a game, or the last few versions of a
+/// document. Pushing a version is O(1), and once the ring is full, the
+/// oldest version is retired, releasing the nodes it does not share with
+/// the versions kept. Use [SnapshotRing::marginal_footprint] to learn how
+/// much memory each kept version is holding on its own.
+pub struct SnapshotRing<T> {
+    versions: VecDeque<Vector<T>>,
+    capacity: usize,
+}
+
+impl<T> SnapshotRing<T> {
+    /// Create a ring retaining upto `capacity` versions, a `capacity` of
+    /// zero is treated as one.
+    pub fn new(capacity: usize) -> SnapshotRing<T> {
+        let capacity = std::cmp::max(capacity, 1);
+        SnapshotRing {
+            versions: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Return the maximum number of versions retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Return the number of versions retained.
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Return whether no versions are retained.
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+
+    /// Push `arr` as the latest version, and return the oldest version if
+    /// it was retired to make room.
+    pub fn push(&mut self, arr: Vector<T>) -> Option<Vector<T>> {
+        let retired = match self.versions.len() {
+            n if n == self.capacity => self.versions.pop_front(),
+            _ => None,
+        };
+        self.versions.push_back(arr);
+        retired
+    }
+
+    /// Return the latest version, None if empty.
+    pub fn latest(&self) -> Option<&Vector<T>> {
+        self.versions.back()
+    }
+
+    /// Return the oldest version, None if empty.
+    pub fn oldest(&self) -> Option<&Vector<T>> {
+        self.versions.front()
+    }
+
+    /// Return the version `age` steps back from the latest, where the
+    /// latest version is at age zero, None if no such version is retained.
+    pub fn get(&self, age: usize) -> Option<&Vector<T>> {
+        let n = self.versions.len().checked_sub(age + 1)?;
+        self.versions.get(n)
+    }
+
+    /// Return an iterator over versions, from the oldest to the latest.
+    pub fn iter(&self) -> impl Iterator<Item = &Vector<T>> + '_ {
+        self.versions.iter()
+    }
+
+    /// Return, for each version from the oldest to the latest, the memory
+    /// held by it alone, that is, released by dropping it while keeping the
+    /// rest, refer [Vector::footprint_marginal]. This walks every version
+    /// once, meant for diagnostics.
+    pub fn marginal_footprint(&self) -> Vec<usize> {
+        let versions: Vec<&Vector<T>> = self.versions.iter().collect();
+        Vector::footprint_marginal(&versions)
+    }
+}
+
+#[cfg(test)]
+#[path = "ring_test.rs"]
+mod ring_test;
@@ -0,0 +1,59 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_snapshot_ring() {
+    let seed: u128 = random();
+    println!("test_snapshot_ring seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    let mut refvs: Vec<Vec<u64>> = vec![];
+
+    let mut ring = SnapshotRing::new(30);
+    assert!(ring.is_empty());
+    let mut refv = vals.clone();
+    for frame in 0..100 {
+        // every frame updates a few items.
+        for _ in 0..10 {
+            let off = rng.gen::<usize>() % refv.len();
+            arr.update(off, frame).unwrap();
+            refv[off] = frame;
+        }
+        let retired = ring.push(arr.clone());
+        refvs.push(refv.clone());
+        assert_eq!(retired.is_some(), frame >= 30);
+    }
+    assert_eq!(ring.len(), 30);
+    assert_eq!(ring.capacity(), 30);
+
+    for (arr, refv) in ring.iter().zip(refvs[70..].iter()) {
+        assert!(arr.iter().eq(refv.iter()));
+    }
+    assert!(ring.latest().unwrap().ptr_eq(&arr));
+    assert!(ring.get(0).unwrap().ptr_eq(&arr));
+    assert!(ring.get(29).unwrap().ptr_eq(ring.oldest().unwrap()));
+    assert!(ring.get(30).is_none());
+
+    // each frame holds only the paths to its few edited leaf nodes.
+    let total = arr.footprint();
+    let marginal = ring.marginal_footprint();
+    for n in marginal.iter() {
+        assert!(*n < total / 5, "{} {}", n, total);
+    }
+
+    // same as the footprint of each version against all the others.
+    let versions: Vec<&Vector<u64>> = ring.iter().collect();
+    for (i, n) in marginal.into_iter().enumerate() {
+        let mut others = versions.clone();
+        let version = others.remove(i);
+        assert_eq!(n, version.footprint_exclusive(&others), "version {}", i);
+    }
+
+    let mut ring = SnapshotRing::new(0);
+    assert_eq!(ring.capacity(), 1);
+    assert!(ring.push(arr.clone()).is_none());
+    assert!(ring.push(arr).is_some());
+}
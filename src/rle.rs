@@ -0,0 +1,280 @@
+use std::{iter::FromIterator, ops::Range};
+
+use super::measured::MeasuredVector;
+use crate::{Error, Measure, Result};
+
+// run of `count` items, all equal to `value`.
+#[derive(Clone, PartialEq)]
+struct Run<T> {
+    value: T,
+    count: usize,
+}
+
+// measure of runs, the number of items in them.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct RunMeasure {
+    len: usize,
+}
+
+impl<T> Measure<Run<T>> for RunMeasure {
+    fn from_item(run: &Run<T>) -> Self {
+        RunMeasure { len: run.count }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        RunMeasure {
+            len: self.len + other.len,
+        }
+    }
+
+    fn update(&mut self, old: Option<&Run<T>>, new: Option<&Run<T>>) -> bool {
+        self.len -= old.map_or(0, |run| run.count);
+        self.len += new.map_or(0, |run| run.count);
+        true
+    }
+}
+
+/// Persistent sequence, run length encoded.
+///
+/// Consecutive items that are equal are held as a single run of (value,
+/// count), in a B-tree whose nodes cache the number of items under them.
+/// Hence a sequence of mostly identical items, like sparse grids and fill
+/// patterns, takes space in proportion to the number of runs, while get,
+/// insert, update and remove by index are O(log n). Edits within a run
+/// update its count in-place, runs are inserted or removed only when split
+/// or joined. Cloning is cheap and shares the tree, edits on a clone copy
+/// only the path from the root to the edited run.
+#[derive(Clone)]
+pub struct RleRope<T> {
+    runs: MeasuredVector<Run<T>, RunMeasure>,
+}
+
+impl<T> Default for RleRope<T> {
+    fn default() -> Self {
+        RleRope {
+            runs: MeasuredVector::default(),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for RleRope<T>
+where
+    T: Clone + PartialEq,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut runs: Vec<Run<T>> = vec![];
+        for value in iter {
+            match runs.last_mut() {
+                Some(run) if run.value == value => run.count += 1,
+                _ => runs.push(Run { value, count: 1 }),
+            }
+        }
+        RleRope {
+            runs: MeasuredVector::from_iter(runs),
+        }
+    }
+}
+
+impl<T> RleRope<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Create an empty sequence.
+    pub fn new() -> RleRope<T> {
+        RleRope::default()
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.runs.total().len
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of runs, consecutive items that are equal make a
+    /// single run.
+    pub fn n_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        let (k, _) = self.search(index)?;
+        let run = self.runs.get(k).map_err(|_| corrupted(index))?;
+        Ok(&run.value)
+    }
+
+    /// Insert `value` at `index`, or `IndexOutOfBounds` error.
+    pub fn insert(&mut self, index: usize, value: T) -> Result<()> {
+        let n = self.len();
+        if index > n {
+            Err(Error::IndexOutOfBounds { index, len: n })?
+        } else if self.runs.is_empty() {
+            return self.runs.insert(0, Run { value, count: 1 });
+        }
+
+        let (k, pieces) = if index == n {
+            // inserting at the end appends to the last run.
+            let k = self.runs.len() - 1;
+            let last = self.runs.get(k).map_err(|_| corrupted(index))?;
+            (k, vec![last.clone(), Run { value, count: 1 }])
+        } else {
+            let (k, off) = self.search(index)?;
+            let run = self.runs.get(k).map_err(|_| corrupted(index))?;
+            let pieces = vec![
+                Run {
+                    value: run.value.clone(),
+                    count: off,
+                },
+                Run { value, count: 1 },
+                Run {
+                    value: run.value.clone(),
+                    count: run.count - off,
+                },
+            ];
+            (k, pieces)
+        };
+        self.splice(k, pieces)
+    }
+
+    /// Append `value` at the end.
+    pub fn push(&mut self, value: T) {
+        // insert at the end never fails.
+        self.insert(self.len(), value).ok();
+    }
+
+    /// Replace the item at `index` with `value` and return the old item, or
+    /// `IndexOutOfBounds` error.
+    pub fn update(&mut self, index: usize, value: T) -> Result<T> {
+        let (k, off) = self.search(index)?;
+        let run = self.runs.get(k).map_err(|_| corrupted(index))?;
+        let old = run.value.clone();
+        if old == value {
+            return Ok(old);
+        }
+
+        let pieces = vec![
+            Run {
+                value: old.clone(),
+                count: off,
+            },
+            Run { value, count: 1 },
+            Run {
+                value: old.clone(),
+                count: run.count - off - 1,
+            },
+        ];
+        self.splice(k, pieces)?;
+        Ok(old)
+    }
+
+    /// Remove the item at `index` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, index: usize) -> Result<T> {
+        let (k, _) = self.search(index)?;
+        let run = self.runs.get(k).map_err(|_| corrupted(index))?;
+        let pieces = vec![Run {
+            value: run.value.clone(),
+            count: run.count - 1,
+        }];
+        let old = run.value.clone();
+        self.splice(k, pieces)?;
+        Ok(old)
+    }
+
+    /// Return an iterator over items.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|run| std::iter::repeat_n(&run.value, run.count))
+    }
+
+    /// Return an iterator over runs, as (value, range of indices).
+    pub fn runs(&self) -> impl Iterator<Item = (&T, Range<usize>)> + '_ {
+        self.runs.iter().scan(0, |start, run| {
+            let range = *start..(*start + run.count);
+            *start = range.end;
+            Some((&run.value, range))
+        })
+    }
+
+    // replace the run at `k` with `pieces`, dropping empty runs and joining
+    // adjacent runs of equal value, including the neighbours of `k`. Runs
+    // that differ are updated in-place, and runs are inserted or removed
+    // only for the difference in their number.
+    fn splice(&mut self, k: usize, pieces: Vec<Run<T>>) -> Result<()> {
+        let start = k.saturating_sub(1);
+        let end = std::cmp::min(k + 2, self.runs.len());
+        let mut old: Vec<Run<T>> = vec![];
+        for i in start..end {
+            old.push(self.runs.get(i)?.clone());
+        }
+
+        let mut new: Vec<Run<T>> = vec![];
+        let runs = old[..(k - start)]
+            .iter()
+            .cloned()
+            .chain(pieces)
+            .chain(old[(k - start + 1)..].iter().cloned());
+        for run in runs.filter(|run| run.count > 0) {
+            match new.last_mut() {
+                Some(last) if last.value == run.value => last.count += run.count,
+                _ => new.push(run),
+            }
+        }
+
+        // skip runs that are left as is, at either end.
+        let head = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let tail = old[head..]
+            .iter()
+            .rev()
+            .zip(new[head..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let (n_old, n_new) = (old.len() - head - tail, new.len() - head - tail);
+
+        let mut off = start + head;
+        for (i, run) in new.into_iter().skip(head).take(n_new).enumerate() {
+            match i < n_old {
+                true => self.runs.update(off, run).map(|_| ())?,
+                false => self.runs.insert(off, run)?,
+            }
+            off += 1;
+        }
+        for _ in n_new..n_old {
+            self.runs.remove(off)?;
+        }
+        Ok(())
+    }
+
+    // return (run, offset within the run) holding `index`.
+    fn search(&self, index: usize) -> Result<(usize, usize)> {
+        match self.runs.search_by_measure(|m| m.len > index) {
+            Some((k, before)) => Ok((k, index - before.len)),
+            None => Err(Error::IndexOutOfBounds {
+                index,
+                len: self.len(),
+            }),
+        }
+    }
+}
+
+fn corrupted(index: usize) -> Error {
+    Error::Corrupted {
+        detail: format!("missing run for item {}", index),
+    }
+}
+
+#[cfg(test)]
+#[path = "rle_test.rs"]
+mod rle_test;
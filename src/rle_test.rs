@@ -0,0 +1,97 @@
+use rand::{rngs::SmallRng, Rng};
+
+use super::*;
+use crate::harness::{check_ops, seeded_rng};
+
+fn validate(arr: &RleRope<u8>, refv: &[u8]) {
+    arr.runs.validate().unwrap();
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.iter().eq(refv.iter()));
+
+    // runs are maximal, adjacent runs differ in value.
+    let runs: Vec<(&u8, Range<usize>)> = arr.runs().collect();
+    assert_eq!(runs.len(), arr.n_runs());
+    for w in runs.windows(2) {
+        assert_ne!(w[0].0, w[1].0);
+        assert_eq!(w[0].1.end, w[1].1.start);
+    }
+    // cached lengths of nodes, updated in-place, add up to each run.
+    for (k, (value, range)) in runs.iter().enumerate() {
+        assert_eq!(arr.runs.measure(..k).unwrap().len, range.start);
+        assert!(!range.is_empty());
+        assert!(refv[range.clone()].iter().all(|x| x == *value));
+    }
+}
+
+#[test]
+fn test_rle_ops() {
+    let mut rng = seeded_rng("test_rle_ops");
+
+    // few distinct values, to make long runs.
+    let mut refv: Vec<u8> = vec![];
+    for _ in 0..100 {
+        let n = rng.gen::<usize>() % 100;
+        refv.extend(std::iter::repeat_n(rng.gen::<u8>() % 3, n));
+    }
+    let mut arr: RleRope<u8> = refv.iter().cloned().collect();
+
+    let op = |rng: &mut SmallRng, arr: &mut RleRope<u8>, refv: &mut Vec<u8>, _| {
+        let n = refv.len();
+        let off = rng.gen::<usize>() % (n + 1);
+        let val = rng.gen::<u8>() % 3;
+        match rng.gen::<u8>() % 8 {
+            0 | 1 => {
+                arr.insert(off, val).unwrap();
+                refv.insert(off, val);
+            }
+            2 => {
+                arr.push(val);
+                refv.push(val);
+            }
+            3 | 4 if off < n => {
+                assert_eq!(arr.update(off, val).unwrap(), refv[off]);
+                refv[off] = val;
+            }
+            5 | 6 if off < n => {
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            _ => assert_eq!(arr.get(off).ok(), refv.get(off)),
+        }
+    };
+    check_ops(&mut rng, &mut arr, &mut refv, 20_000, op, validate);
+
+    while !refv.is_empty() {
+        let off = rng.gen::<usize>() % refv.len();
+        assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+    }
+    validate(&arr, &refv);
+}
+
+#[test]
+fn test_rle_runs() {
+    let mut arr: RleRope<u8> = std::iter::repeat_n(0, 1_000_000).collect();
+    assert_eq!(arr.n_runs(), 1);
+
+    arr.update(500_000, 1).unwrap();
+    assert_eq!(arr.n_runs(), 3);
+    assert_eq!(arr.get(500_000), Ok(&1));
+    arr.update(500_000, 0).unwrap();
+    assert_eq!(arr.n_runs(), 1);
+
+    arr.insert(1_000_000, 2).unwrap();
+    arr.insert(0, 2).unwrap();
+    let runs: Vec<(&u8, Range<usize>)> = arr.runs().collect();
+    assert_eq!(
+        runs,
+        vec![(&2, 0..1), (&0, 1..1_000_001), (&2, 1_000_001..1_000_002)]
+    );
+
+    let n = arr.len();
+    assert_eq!(
+        arr.get(n).err(),
+        Some(Error::IndexOutOfBounds { index: n, len: n })
+    );
+    assert!(arr.insert(n + 1, 0).is_err());
+    assert!(arr.update(n, 0).is_err());
+    assert!(arr.remove(n).is_err());
+}
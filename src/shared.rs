@@ -0,0 +1,273 @@
+#[cfg(not(all(test, crossbeam_loom)))]
+use arc_swap::ArcSwap;
+
+use std::sync::{mpsc, Arc};
+
+#[cfg(all(test, crossbeam_loom))]
+use self::loom_head::ArcSwap;
+
+use super::Vector;
+use crate::{Edit, Result};
+
+/// Shared head for [Vector], holding the current version behind an
+/// atomic pointer.
+///
+/// Readers call [SharedVector::load] to get a snapshot, which never blocks
+/// and never observes a partial update. Writers call [SharedVector::update]
+/// with a functional edit, that is applied on a private copy of the latest
+/// version and published with compare-and-swap, retrying the edit if some
+/// other writer published a new version in the mean time.
+///
+/// UI layers and caches can [SharedVector::subscribe] to be notified of
+/// every new version, instead of polling the head and diffing.
+///
+/// Memory orderings of the head are those of `arc-swap`, which is built on
+/// `std` atomics and cannot be model-checked with loom. The publish and
+/// compare-and-swap protocol of this type is model-checked over a stand-in
+/// head, with acquire loads and release stores, refer `shared_loom_test`.
+pub struct SharedVector<T> {
+    head: ArcSwap<Vector<T>>,
+    // snapshot of subscribers, replaced on every subscribe and unsubscribe,
+    // so that publishing a version never blocks on other writers.
+    subscribers: ArcSwap<Vec<Arc<mpsc::Sender<Change<T>>>>>,
+}
+
+/// Change notified to subscribers of [SharedVector], on every new version
+/// published.
+pub struct Change<T> {
+    /// Version replaced.
+    pub old: Vector<T>,
+    /// Version published.
+    pub new: Vector<T>,
+}
+
+impl<T> Change<T> {
+    /// Return the edit script from the old version to the new version,
+    /// refer [Vector::diff]. Since versions share most of their trees, this
+    /// walks only the edited parts.
+    pub fn edits(&self) -> Vec<Edit<T>>
+    where
+        T: Clone + PartialEq,
+    {
+        self.old.diff(&self.new)
+    }
+}
+
+impl<T> Default for SharedVector<T> {
+    fn default() -> SharedVector<T> {
+        SharedVector::new(Vector::default())
+    }
+}
+
+impl<T> From<Vector<T>> for SharedVector<T> {
+    fn from(arr: Vector<T>) -> SharedVector<T> {
+        SharedVector::new(arr)
+    }
+}
+
+impl<T> SharedVector<T> {
+    /// Create a new shared head with `arr` as its current version.
+    pub fn new(arr: Vector<T>) -> SharedVector<T> {
+        SharedVector {
+            head: ArcSwap::from_pointee(arr),
+            subscribers: ArcSwap::from_pointee(vec![]),
+        }
+    }
+
+    /// Return a snapshot of the current version. Snapshot is cheap, it
+    /// shares the underlying tree with the head.
+    pub fn load(&self) -> Vector<T>
+    where
+        T: Clone,
+    {
+        self.head.load().as_ref().clone()
+    }
+
+    /// Replace the current version with `arr`, unconditionally.
+    pub fn store(&self, arr: Vector<T>)
+    where
+        T: Clone,
+    {
+        let new = Arc::new(arr);
+        let old = self.head.swap(Arc::clone(&new));
+        self.notify(&old, &new);
+    }
+
+    /// Subscribe to new versions, and return the receiving end of a channel
+    /// that receives a [Change] for every version published by `store` and
+    /// `update`. Changes published by concurrent writers may be received
+    /// out of order, refer to the old and new versions in each change to
+    /// order them. Subscription ends when the receiver is dropped.
+    pub fn subscribe(&self) -> mpsc::Receiver<Change<T>> {
+        let (tx, rx) = mpsc::channel();
+        let tx = Arc::new(tx);
+        self.subscribers.rcu(|txs| {
+            let mut txs = Vec::clone(txs);
+            txs.push(Arc::clone(&tx));
+            txs
+        });
+        rx
+    }
+
+    fn notify(&self, old: &Vector<T>, new: &Vector<T>)
+    where
+        T: Clone,
+    {
+        let txs = self.subscribers.load();
+        if txs.is_empty() {
+            return;
+        }
+
+        let mut gone = vec![];
+        for tx in txs.iter() {
+            let change = Change {
+                old: old.clone(),
+                new: new.clone(),
+            };
+            if tx.send(change).is_err() {
+                gone.push(Arc::clone(tx))
+            }
+        }
+
+        if !gone.is_empty() {
+            self.subscribers.rcu(|txs| {
+                txs.iter()
+                    .filter(|tx| !gone.iter().any(|g| Arc::ptr_eq(g, tx)))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            });
+        }
+    }
+
+    /// Apply a functional edit `f` on the current version and publish the
+    /// result as the new version. `f` is called with a copy-on-write clone of
+    /// the latest version, and may be called more than once when concurrent
+    /// writers race with each other, hence it must not have side-effects
+    /// other than on its argument. The argument shares its tree with the
+    /// head, and any method is safe on it, the copy-on-write methods, like
+    /// `insert`, `update`, `remove`, as well as the `_mut` methods, like
+    /// `insert_mut`, which copy shared nodes on first touch and leave the
+    /// head untouched. If `f` returns an error, the head is left untouched
+    /// and the error is returned.
+    ///
+    /// Return the value returned by the successful call to `f`.
+    pub fn update<F, R>(&self, mut f: F) -> Result<R>
+    where
+        T: Clone,
+        F: FnMut(&mut Vector<T>) -> Result<R>,
+    {
+        loop {
+            let current = self.head.load_full();
+            let mut arr = current.as_ref().clone();
+            let res = f(&mut arr)?;
+
+            let new = Arc::new(arr);
+            let prev = self.head.compare_and_swap(&current, Arc::clone(&new));
+            if Arc::ptr_eq(&prev, &current) {
+                self.notify(&current, &new);
+                break Ok(res);
+            }
+        }
+    }
+}
+
+// Stand-in for `arc_swap::ArcSwap` when model checking with loom. Head is
+// a lock-free pointer over loom's atomics, loaded with acquire and published
+// with release, the orderings SharedVector relies on. Replaced versions are
+// retired, instead of dropped, until the head is dropped, so a reader that
+// has loaded a pointer can always take its reference count.
+#[cfg(all(test, crossbeam_loom))]
+mod loom_head {
+    use loom::sync::{
+        atomic::{
+            AtomicPtr,
+            Ordering::{AcqRel, Acquire},
+        },
+        Mutex,
+    };
+
+    use std::sync::Arc;
+
+    pub struct ArcSwap<T> {
+        ptr: AtomicPtr<T>,
+        retired: Mutex<Vec<Arc<T>>>,
+    }
+
+    impl<T> ArcSwap<T> {
+        pub fn from_pointee(val: T) -> ArcSwap<T> {
+            ArcSwap {
+                ptr: AtomicPtr::new(Arc::into_raw(Arc::new(val)) as *mut T),
+                retired: Mutex::new(vec![]),
+            }
+        }
+
+        pub fn load(&self) -> Arc<T> {
+            self.load_full()
+        }
+
+        pub fn load_full(&self) -> Arc<T> {
+            unsafe { Self::clone_raw(self.ptr.load(Acquire)) }
+        }
+
+        pub fn swap(&self, new: Arc<T>) -> Arc<T> {
+            let old = self.ptr.swap(Arc::into_raw(new) as *mut T, AcqRel);
+            self.retire(old)
+        }
+
+        pub fn compare_and_swap(&self, current: &Arc<T>, new: Arc<T>) -> Arc<T> {
+            let (current, new) = (Arc::as_ptr(current) as *mut T, Arc::into_raw(new));
+            match self
+                .ptr
+                .compare_exchange(current, new as *mut T, AcqRel, Acquire)
+            {
+                Ok(old) => self.retire(old),
+                Err(prev) => unsafe {
+                    // `new` was never published.
+                    drop(Arc::from_raw(new));
+                    Self::clone_raw(prev)
+                },
+            }
+        }
+
+        pub fn rcu<F, R>(&self, mut f: F)
+        where
+            F: FnMut(&Arc<T>) -> R,
+            R: Into<Arc<T>>,
+        {
+            loop {
+                let current = self.load_full();
+                let prev = self.compare_and_swap(&current, f(&current).into());
+                if Arc::ptr_eq(&prev, &current) {
+                    break;
+                }
+            }
+        }
+
+        // `ptr` shall be the head, or a retired version.
+        unsafe fn clone_raw(ptr: *mut T) -> Arc<T> {
+            Arc::increment_strong_count(ptr);
+            Arc::from_raw(ptr)
+        }
+
+        // take over the head's reference to a replaced version.
+        fn retire(&self, old: *mut T) -> Arc<T> {
+            let old = unsafe { Arc::from_raw(old) };
+            self.retired.lock().unwrap().push(Arc::clone(&old));
+            old
+        }
+    }
+
+    impl<T> Drop for ArcSwap<T> {
+        fn drop(&mut self) {
+            drop(unsafe { Arc::from_raw(self.ptr.load(Acquire)) })
+        }
+    }
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+#[path = "shared_test.rs"]
+mod shared_test;
+
+#[cfg(all(test, crossbeam_loom))]
+#[path = "shared_loom_test.rs"]
+mod shared_loom_test;
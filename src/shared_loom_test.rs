@@ -0,0 +1,82 @@
+// Run these tests with,
+//
+// RUSTFLAGS="--cfg crossbeam_loom" cargo test --release --features shared --lib shared_loom_test
+//
+// arc-swap is not built over loom, these tests model-check the publish and
+// compare-and-swap protocol of SharedVector over a stand-in head, refer
+// `loom_head`.
+
+use loom::{
+    sync::{
+        atomic::{AtomicUsize, Ordering::Relaxed},
+        Arc,
+    },
+    thread,
+};
+
+use super::*;
+
+fn model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let mut builder = loom::model::Builder::new();
+    builder.preemption_bound = Some(2);
+    builder.check(f)
+}
+
+#[test]
+fn test_loom_shared_publish() {
+    model(|| {
+        let shared: Arc<SharedVector<Arc<AtomicUsize>>> =
+            Arc::new(SharedVector::default());
+
+        let reader = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let (a, b) = (shared.load(), shared.load());
+                // versions are published in order, and items written before
+                // a version is published are visible to readers loading it.
+                assert!(a.len() <= b.len());
+                for (i, item) in b.iter().enumerate() {
+                    assert_eq!(item.load(Relaxed), i + 1);
+                }
+            })
+        };
+
+        for i in 0..2 {
+            let item = Arc::new(AtomicUsize::new(0));
+            item.store(i + 1, Relaxed);
+            shared
+                .update(|arr| arr.insert(i, Arc::clone(&item)))
+                .unwrap();
+        }
+
+        reader.join().unwrap();
+        assert_eq!(shared.load().len(), 2);
+    });
+}
+
+#[test]
+fn test_loom_shared_writers() {
+    model(|| {
+        let shared: Arc<SharedVector<u64>> = Arc::new(SharedVector::default());
+        let rx = shared.subscribe();
+
+        let writer = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                shared.update(|arr| arr.insert(arr.len(), 1)).unwrap();
+            })
+        };
+        shared.update(|arr| arr.insert(arr.len(), 0)).unwrap();
+        writer.join().unwrap();
+
+        // compare-and-swap retry shall not lose any update, and every
+        // published version is notified once.
+        let mut vals: Vec<u64> = shared.load().into();
+        vals.sort_unstable();
+        assert_eq!(vals, vec![0, 1]);
+        assert_eq!(rx.try_iter().count(), 2);
+    });
+}
@@ -0,0 +1,185 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::{sync::Arc, thread};
+
+use super::*;
+
+#[test]
+fn test_shared_update() {
+    // leaf nodes of a single item, to share the tree across versions.
+    let shared: SharedVector<u64> = SharedVector::new(Vector::with_leaf_size(1));
+    assert!(shared.load().is_empty());
+
+    shared.update(|arr| arr.insert(0, 10)).unwrap();
+    shared.update(|arr| arr.insert(1, 20)).unwrap();
+    let snapshot = shared.load();
+
+    assert!(shared.update(|arr| arr.insert(10, 30)).is_err());
+    assert!(shared.load().ptr_eq(&snapshot));
+
+    let old = shared.update(|arr| arr.update(0, 11)).unwrap();
+    assert_eq!(old, 10);
+
+    let a: Vec<u64> = snapshot.into();
+    let b: Vec<u64> = shared.load().into();
+    assert_eq!(a, vec![10, 20]);
+    assert_eq!(b, vec![11, 20]);
+
+    // _mut methods copy shared nodes, and leave the head untouched.
+    let snapshot = shared.load();
+    shared.update(|arr| arr.insert_mut(0, 9)).unwrap();
+    shared
+        .update(|arr| arr.update_mut(2, 21).map(|_| ()))
+        .unwrap();
+    let a: Vec<u64> = snapshot.into();
+    let b: Vec<u64> = shared.load().into();
+    assert_eq!(a, vec![11, 20]);
+    assert_eq!(b, vec![9, 11, 21]);
+
+    shared.store(Vector::default());
+    assert!(shared.load().is_empty());
+}
+
+#[test]
+fn test_shared_concurrent() {
+    let seed: u128 = random();
+    println!("test_shared_concurrent seed {}", seed);
+
+    let (n_writers, n_readers, n_ops) = (8_u64, 8, 1000_usize);
+
+    let shared: Arc<SharedVector<u64>> = Arc::new(SharedVector::default());
+
+    let mut writers = vec![];
+    for id in 0..n_writers {
+        let shared = Arc::clone(&shared);
+        let seed = seed + ((id as u128) * 123);
+        writers.push(thread::spawn(move || {
+            let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+            for _ in 0..n_ops {
+                shared
+                    .update(|arr| {
+                        let off = rng.gen::<usize>() % (arr.len() + 1);
+                        arr.insert(off, id)
+                    })
+                    .unwrap();
+            }
+        }));
+    }
+
+    let mut readers = vec![];
+    for _ in 0..n_readers {
+        let shared = Arc::clone(&shared);
+        readers.push(thread::spawn(move || {
+            let mut len = 0;
+            while len < ((n_writers as usize) * n_ops) {
+                let arr = shared.load();
+                assert!(arr.len() >= len, "{} {}", arr.len(), len);
+                assert_eq!(arr.iter().count(), arr.len());
+                len = arr.len();
+            }
+        }));
+    }
+
+    for h in writers.into_iter().chain(readers) {
+        h.join().unwrap()
+    }
+
+    let arr = shared.load();
+    assert_eq!(arr.len(), (n_writers as usize) * n_ops);
+    for id in 0..n_writers {
+        let n = arr.iter().filter(|x| **x == id).count();
+        assert_eq!(n, n_ops, "writer-{}", id);
+    }
+}
+
+#[test]
+fn test_shared_subscribe() {
+    // leaf nodes of a single item, vectors of a single item are held
+    // inline, and copied on clone.
+    let shared: SharedVector<u64> = SharedVector::new(Vector::with_leaf_size(1));
+    let rx = shared.subscribe();
+
+    shared.update(|arr| arr.insert(0, 10)).unwrap();
+    shared.update(|arr| arr.insert(1, 20)).unwrap();
+    assert!(shared.update(|arr| arr.insert(10, 30)).is_err());
+    shared.store(Vector::build(0..5, Some(1)));
+
+    let changes: Vec<Change<u64>> = rx.try_iter().collect();
+    assert_eq!(changes.len(), 3);
+    assert!(changes[0].old.is_empty());
+    assert!(changes[1].new.iter().eq([10, 20].iter()));
+    assert!(changes[1].old.iter().eq(changes[0].new.iter()));
+    assert!(changes[2].new.ptr_eq(&shared.load()));
+
+    let arr = changes[2].old.apply(&changes[2].edits()).unwrap();
+    assert!(arr.iter().eq(shared.load().iter()));
+
+    // dropped receivers are unsubscribed.
+    drop(rx);
+    let rx = shared.subscribe();
+    shared.update(|arr| arr.remove(0)).unwrap();
+    assert_eq!(shared.subscribers.load().len(), 1);
+    assert_eq!(rx.try_iter().count(), 1);
+}
+
+#[test]
+fn test_shared_unsubscribe_concurrent() {
+    let (n_writers, n_ops) = (4_u64, 1000_usize);
+
+    let shared: Arc<SharedVector<u64>> = Arc::new(SharedVector::default());
+    let rxs: Vec<mpsc::Receiver<Change<u64>>> =
+        (0..8).map(|_| shared.subscribe()).collect();
+
+    // receivers dropped while writers are publishing.
+    let writers: Vec<thread::JoinHandle<()>> = (0..n_writers)
+        .map(|id| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for _ in 0..n_ops {
+                    shared.update(|arr| arr.insert(arr.len(), id)).unwrap();
+                }
+            })
+        })
+        .collect();
+    let mut rxs = rxs.into_iter();
+    let rx = rxs.next().unwrap();
+    drop(rxs);
+    for h in writers.into_iter() {
+        h.join().unwrap()
+    }
+    shared.update(|arr| arr.remove(0)).unwrap();
+
+    assert_eq!(shared.subscribers.load().len(), 1);
+    assert_eq!(rx.try_iter().count(), (n_writers as usize) * n_ops + 1);
+}
+
+#[test]
+fn test_shared_subscribe_concurrent() {
+    let (n_writers, n_ops) = (4_u64, 1000_usize);
+
+    let shared: Arc<SharedVector<u64>> = Arc::new(SharedVector::default());
+    let rx = shared.subscribe();
+
+    let writers: Vec<thread::JoinHandle<()>> = (0..n_writers)
+        .map(|id| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                for _ in 0..n_ops {
+                    shared.update(|arr| arr.insert(arr.len(), id)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for h in writers.into_iter() {
+        h.join().unwrap()
+    }
+
+    // every version is notified once, each one longer by an item.
+    let mut changes: Vec<Change<u64>> = rx.try_iter().collect();
+    assert_eq!(changes.len(), (n_writers as usize) * n_ops);
+    changes.sort_by_key(|c| c.new.len());
+    for (i, change) in changes.iter().enumerate() {
+        assert_eq!(change.old.len(), i);
+        assert_eq!(change.new.len(), i + 1);
+    }
+}
@@ -0,0 +1,58 @@
+//! Shrink a failing sequence of operations, from randomized tests, into
+//! the shortest sequence that still reproduces the failure.
+//!
+//! Refer `model_test.rs` for usage.
+
+use std::panic;
+
+/// Return the shortest sub-sequence of `ops` for which `fails` returns
+/// true, `ops` itself is expected to fail. Uses delta debugging, chunks of
+/// decreasing size are removed from the sequence, as long as the remaining
+/// sequence continues to fail, until no single operation can be removed.
+pub fn shrink<O, F>(ops: &[O], fails: F) -> Vec<O>
+where
+    O: Clone,
+    F: Fn(&[O]) -> bool,
+{
+    let mut ops = ops.to_vec();
+    let mut n = 2;
+    while ops.len() > 1 {
+        let chunk = ops.len().div_ceil(n);
+        let reduced = (0..n)
+            .map(|i| i * chunk)
+            .take_while(|s| *s < ops.len())
+            .find_map(|start| {
+                let end = std::cmp::min(start + chunk, ops.len());
+                let mut cand = ops[..start].to_vec();
+                cand.extend_from_slice(&ops[end..]);
+                if fails(&cand) {
+                    Some(cand)
+                } else {
+                    None
+                }
+            });
+
+        match reduced {
+            Some(cand) => {
+                ops = cand;
+                n = std::cmp::max(n - 1, 2);
+            }
+            None if n >= ops.len() => break,
+            None => n = std::cmp::min(n * 2, ops.len()),
+        }
+    }
+    ops
+}
+
+/// Return whether `f` panics, panic messages are suppressed, so that
+/// replaying failing sequences doesn't flood the test output.
+pub fn panics<F>(f: F) -> bool
+where
+    F: FnOnce(),
+{
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    panic::set_hook(hook);
+    res.is_err()
+}
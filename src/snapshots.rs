@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// Registry of named snapshots, mapping labels to retained versions.
+///
+/// Applications exposing user facing saved states, like "before refactor",
+/// can keep them here instead of book keeping clones of the vector. Each
+/// snapshot is an O(1) clone sharing the tree with other versions, and the
+/// memory they hold together is reported by [Snapshots::footprint].
+pub struct Snapshots<T> {
+    snapshots: BTreeMap<String, Vector<T>>,
+}
+
+impl<T> Default for Snapshots<T> {
+    fn default() -> Self {
+        Snapshots {
+            snapshots: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Snapshots<T> {
+    /// Create an empty registry.
+    pub fn new() -> Snapshots<T> {
+        Snapshots::default()
+    }
+
+    /// Return the number of snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Return whether there are no snapshots.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Retain `arr` under `label`, and return the snapshot it replaces, if
+    /// any.
+    pub fn create(&mut self, label: &str, arr: &Vector<T>) -> Option<Vector<T>>
+    where
+        T: Clone,
+    {
+        self.snapshots.insert(label.to_string(), arr.clone())
+    }
+
+    /// Return the snapshot under `label`.
+    pub fn get(&self, label: &str) -> Option<&Vector<T>> {
+        self.snapshots.get(label)
+    }
+
+    /// Drop the snapshot under `label`, and return it, if any.
+    pub fn remove(&mut self, label: &str) -> Option<Vector<T>> {
+        self.snapshots.remove(label)
+    }
+
+    /// Return the labels of snapshots, in sort order.
+    pub fn labels(&self) -> Vec<&str> {
+        self.snapshots.keys().map(|label| label.as_str()).collect()
+    }
+
+    /// Return an iterator over (label, snapshot), in sort order of labels.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Vector<T>)> + '_ {
+        self.snapshots
+            .iter()
+            .map(|(label, arr)| (label.as_str(), arr))
+    }
+
+    /// Return the memory held by all snapshots together, counting nodes
+    /// shared among them only once, refer [Vector::footprint_union].
+    pub fn footprint(&self) -> usize {
+        let arrs: Vec<&Vector<T>> = self.snapshots.values().collect();
+        Vector::footprint_union(&arrs)
+    }
+
+    /// Return the memory released by dropping the snapshot under `label`
+    /// while keeping the rest, refer [Vector::footprint_exclusive].
+    pub fn footprint_exclusive(&self, label: &str) -> Option<usize> {
+        let arr = self.snapshots.get(label)?;
+        let others: Vec<&Vector<T>> = self
+            .snapshots
+            .iter()
+            .filter(|(l, _)| l.as_str() != label)
+            .map(|(_, arr)| arr)
+            .collect();
+        Some(arr.footprint_exclusive(&others))
+    }
+}
+
+#[cfg(test)]
+#[path = "snapshots_test.rs"]
+mod snapshots_test;
@@ -0,0 +1,47 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+
+#[test]
+fn test_snapshots() {
+    let seed: u128 = random();
+    println!("test_snapshots seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let vals: Vec<u64> = (0..100_000).collect();
+    let mut arr = Vector::from_slice(&vals, Some(1024));
+    let mut snaps = Snapshots::new();
+    assert!(snaps.is_empty());
+    assert_eq!(snaps.footprint(), 0);
+
+    let mut refvs = vec![];
+    for i in 0..10 {
+        for _ in 0..10 {
+            let off = rng.gen::<usize>() % vals.len();
+            arr.update(off, i).unwrap();
+        }
+        assert!(snaps.create(&format!("save-{}", i), &arr).is_none());
+        refvs.push(arr.iter().cloned().collect::<Vec<u64>>());
+    }
+    assert_eq!(snaps.len(), 10);
+    let labels: Vec<String> = (0..10).map(|i| format!("save-{}", i)).collect();
+    assert_eq!(snaps.labels(), labels);
+    for ((label, arr), refv) in snaps.iter().zip(refvs.iter()) {
+        assert!(arr.iter().eq(refv.iter()), "{}", label);
+    }
+
+    // snapshots share most of their tree.
+    let total = snaps.footprint();
+    assert!(total < arr.footprint() * 2, "{} {}", total, arr.footprint());
+    let n = snaps.footprint_exclusive("save-3").unwrap();
+    assert!(n < arr.footprint() / 5, "{}", n);
+    assert!(snaps.footprint_exclusive("none").is_none());
+
+    let snap = snaps.remove("save-3").unwrap();
+    assert!(snap.iter().eq(refvs[3].iter()));
+    assert!(snaps.get("save-3").is_none());
+    assert_eq!(snaps.footprint(), total - n);
+
+    assert!(snaps.create("save-0", &arr).is_some());
+    assert!(snaps.get("save-0").unwrap().ptr_eq(&arr));
+}
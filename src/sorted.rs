@@ -0,0 +1,177 @@
+use std::{
+    iter::FromIterator,
+    ops::{Bound, RangeBounds},
+};
+
+use super::measured::MeasuredVector;
+use crate::{Measure, Result};
+
+// measure of sorted items is the last item, which is also the largest.
+#[derive(Clone)]
+struct Last<T>(Option<T>);
+
+impl<T> Default for Last<T> {
+    fn default() -> Self {
+        Last(None)
+    }
+}
+
+impl<T> Measure<T> for Last<T>
+where
+    T: Clone,
+{
+    fn from_item(item: &T) -> Self {
+        Last(Some(item.clone()))
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        match &other.0 {
+            Some(_) => other.clone(),
+            None => self.clone(),
+        }
+    }
+}
+
+/// Persistent sorted sequence.
+///
+/// Items are kept in ascending order, duplicates allowed, in a B-tree
+/// whose nodes cache the largest item under them. Hence inserting, removing
+/// and looking up an item is O(log n), descending the tree by comparing
+/// items instead of by offsets. Items can also be accessed by their
+/// position in the sorted order. Cloning is cheap and shares the tree,
+/// edits on a clone copy only the path from the root to the edited leaf.
+#[derive(Clone)]
+pub struct SortedRope<T> {
+    items: MeasuredVector<T, Last<T>>,
+}
+
+impl<T> Default for SortedRope<T>
+where
+    T: Clone,
+{
+    fn default() -> Self {
+        SortedRope {
+            items: MeasuredVector::default(),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for SortedRope<T>
+where
+    T: Clone + Ord,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut items: Vec<T> = iter.into_iter().collect();
+        items.sort();
+        SortedRope {
+            items: MeasuredVector::from_iter(items),
+        }
+    }
+}
+
+impl<T> SortedRope<T>
+where
+    T: Clone + Ord,
+{
+    /// Create an empty sequence.
+    pub fn new() -> SortedRope<T> {
+        SortedRope::default()
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Return the item at `index`, in sorted order, or `IndexOutOfBounds`
+    /// error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        self.items.get(index)
+    }
+
+    /// Insert `item` in sorted order, after items equal to it, and return
+    /// its position.
+    pub fn insert_sorted(&mut self, item: T) -> usize {
+        let off = self.upper_bound(&item);
+        // offset is within bounds, insert never fails.
+        self.items.insert(off, item).ok();
+        off
+    }
+
+    /// Remove an item equal to `item`, return the removed item, or None
+    /// if there is no such item.
+    pub fn remove(&mut self, item: &T) -> Option<T> {
+        match self.position(item) {
+            Ok(off) => self.items.remove(off).ok(),
+            Err(_) => None,
+        }
+    }
+
+    /// Return whether there is an item equal to `item`.
+    pub fn contains(&self, item: &T) -> bool {
+        self.position(item).is_ok()
+    }
+
+    /// Return `Ok` with the position of the first item equal to `item`, or
+    /// `Err` with the position where `item` would be inserted, similar to
+    /// `slice::binary_search`.
+    pub fn position(&self, item: &T) -> std::result::Result<usize, usize> {
+        let off = self.lower_bound(item);
+        match self.items.get(off) {
+            Ok(x) if x == item => Ok(off),
+            _ => Err(off),
+        }
+    }
+
+    /// Return an iterator over items within `range`, in sorted order.
+    pub fn range<R>(&self, range: R) -> impl Iterator<Item = &T> + '_
+    where
+        R: RangeBounds<T>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(x) => self.lower_bound(x),
+            Bound::Excluded(x) => self.upper_bound(x),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => self.upper_bound(x),
+            Bound::Excluded(x) => self.lower_bound(x),
+            Bound::Unbounded => self.len(),
+        };
+        let n = end.saturating_sub(start);
+        self.items.iter_from(start).take(n)
+    }
+
+    /// Return an iterator over items, in sorted order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.items.iter()
+    }
+
+    // return the position of the first item not less than `item`.
+    fn lower_bound(&self, item: &T) -> usize {
+        let pred = |m: &Last<T>| matches!(&m.0, Some(x) if x >= item);
+        self.items
+            .search_by_measure(pred)
+            .map_or(self.len(), |(off, _)| off)
+    }
+
+    // return the position of the first item greater than `item`.
+    fn upper_bound(&self, item: &T) -> usize {
+        let pred = |m: &Last<T>| matches!(&m.0, Some(x) if x > item);
+        self.items
+            .search_by_measure(pred)
+            .map_or(self.len(), |(off, _)| off)
+    }
+}
+
+#[cfg(test)]
+#[path = "sorted_test.rs"]
+mod sorted_test;
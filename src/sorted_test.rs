@@ -0,0 +1,96 @@
+use rand::{rngs::SmallRng, Rng};
+
+use std::ops::Bound;
+
+use super::*;
+use crate::{
+    harness::{check_ops, seeded_rng},
+    Error,
+};
+
+fn validate(arr: &SortedRope<u64>, refv: &[u64]) {
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.iter().eq(refv.iter()));
+}
+
+#[test]
+fn test_sorted_ops() {
+    let mut rng = seeded_rng("test_sorted_ops");
+
+    let mut refv: Vec<u64> = (0..1000).map(|_| rng.gen::<u64>() % 10_000).collect();
+    let mut arr: SortedRope<u64> = refv.iter().cloned().collect();
+    refv.sort_unstable();
+
+    let op = |rng: &mut SmallRng, arr: &mut SortedRope<u64>, refv: &mut Vec<u64>, _| {
+        let val = rng.gen::<u64>() % 10_000;
+        match rng.gen::<u8>() % 6 {
+            0 | 1 => {
+                let off = arr.insert_sorted(val);
+                assert_eq!(off, refv.partition_point(|x| *x <= val));
+                refv.insert(off, val);
+            }
+            2 => match refv.binary_search(&val) {
+                Ok(_) => {
+                    assert_eq!(arr.remove(&val), Some(val));
+                    let off = refv.partition_point(|x| *x < val);
+                    refv.remove(off);
+                }
+                Err(_) => assert_eq!(arr.remove(&val), None),
+            },
+            3 => {
+                assert_eq!(arr.contains(&val), refv.binary_search(&val).is_ok());
+                let off = refv.partition_point(|x| *x < val);
+                match arr.position(&val) {
+                    Ok(n) => assert_eq!((n, refv[n]), (off, val)),
+                    Err(n) => assert_eq!(n, off),
+                }
+            }
+            4 => {
+                let hi = val + rng.gen::<u64>() % 1000;
+                let refs: Vec<&u64> =
+                    refv.iter().filter(|x| (val..hi).contains(x)).collect();
+                assert_eq!(arr.range(val..hi).collect::<Vec<&u64>>(), refs);
+                let refs: Vec<&u64> =
+                    refv.iter().filter(|x| (val..=hi).contains(x)).collect();
+                assert_eq!(arr.range(val..=hi).collect::<Vec<&u64>>(), refs);
+            }
+            _ => {
+                let off = rng.gen::<usize>() % (refv.len() + 1);
+                assert_eq!(arr.get(off).ok(), refv.get(off));
+            }
+        }
+    };
+    check_ops(&mut rng, &mut arr, &mut refv, 50_000, op, validate);
+}
+
+#[test]
+fn test_sorted_range() {
+    let arr: SortedRope<u64> = vec![5, 1, 3, 3, 9, 7, 3].into_iter().collect();
+    let range = |r: Vec<&u64>| r.into_iter().cloned().collect::<Vec<u64>>();
+
+    assert_eq!(range(arr.range(..).collect()), vec![1, 3, 3, 3, 5, 7, 9]);
+    assert_eq!(range(arr.range(3..).collect()), vec![3, 3, 3, 5, 7, 9]);
+    assert_eq!(range(arr.range(..3).collect()), vec![1]);
+    assert_eq!(range(arr.range(..=3).collect()), vec![1, 3, 3, 3]);
+    assert_eq!(range(arr.range(4..7).collect()), vec![5]);
+    assert_eq!(range(arr.range(10..).collect()), Vec::<u64>::new());
+    assert_eq!(
+        range(
+            arr.range((Bound::Included(7), Bound::Excluded(3)))
+                .collect()
+        ),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        arr.get(7).err(),
+        Some(Error::IndexOutOfBounds { index: 7, len: 7 })
+    );
+
+    let mut arr = SortedRope::new();
+    assert!(arr.is_empty());
+    assert_eq!(arr.insert_sorted(3), 0);
+    assert_eq!(arr.insert_sorted(3), 1);
+    assert_eq!(arr.insert_sorted(1), 0);
+    assert_eq!(arr.position(&2), Err(1));
+    assert_eq!(arr.position(&3), Ok(1));
+}
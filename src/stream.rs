@@ -0,0 +1,290 @@
+use std::{cmp, convert::TryFrom, io};
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::*;
+use crate::Error;
+
+/// Reader over a byte vector, implementing [io::Read], [io::BufRead] and
+/// [io::Seek].
+///
+/// Reader holds a snapshot of the vector, edits on the vector after the
+/// reader is created are not observed. Bytes are copied out of leaf nodes
+/// a run at a time, and [io::BufRead::fill_buf] hands out leaf nodes as is,
+/// without copying. Like [io::Cursor], seeking beyond the end is allowed,
+/// reads return zero bytes from there on.
+///
+/// With the `futures` or `tokio` feature, reader also implements the
+/// async read, buffered read and seek traits of that crate. Reading from a
+/// vector never blocks, hence every poll completes.
+pub struct RopeReader {
+    arr: Vector<u8>,
+    pos: u64,
+}
+
+impl From<Vector<u8>> for RopeReader {
+    fn from(arr: Vector<u8>) -> RopeReader {
+        RopeReader::new(arr)
+    }
+}
+
+impl RopeReader {
+    /// Create a new reader over `arr`, positioned at the start.
+    pub fn new(arr: Vector<u8>) -> RopeReader {
+        RopeReader { arr, pos: 0 }
+    }
+
+    /// Return the current position of the reader.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Return the underlying vector.
+    pub fn into_inner(self) -> Vector<u8> {
+        self.arr
+    }
+
+    // return the bytes from current position till the end of its leaf node.
+    fn run(&self) -> io::Result<&[u8]> {
+        let pos = match usize::try_from(self.pos) {
+            Ok(pos) if pos < self.arr.len() => pos,
+            _ => return Ok(&[]),
+        };
+        let (leaf, start) = self.arr.leaf_of(pos).map_err(to_io_error)?;
+        match pos.checked_sub(start).and_then(|off| leaf.get(off..)) {
+            Some(run) if !run.is_empty() => Ok(run),
+            _ => {
+                let detail = format!("no bytes in leaf node {}+{}", start, leaf.len());
+                Err(to_io_error(Error::Corrupted { detail }))
+            }
+        }
+    }
+}
+
+impl io::Read for RopeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut n = 0;
+        while n < buf.len() {
+            let run = self.run()?;
+            if run.is_empty() {
+                break;
+            }
+            let m = cmp::min(run.len(), buf.len() - n);
+            buf[n..(n + m)].copy_from_slice(&run[..m]);
+            self.pos += m as u64;
+            n += m;
+        }
+        Ok(n)
+    }
+}
+
+impl io::BufRead for RopeReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.run()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl io::Seek for RopeReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, off) = match pos {
+            io::SeekFrom::Start(n) => (n, 0),
+            io::SeekFrom::End(off) => (self.arr.len() as u64, off),
+            io::SeekFrom::Current(off) => (self.pos, off),
+        };
+        let pos = match off {
+            off if off >= 0 => base.checked_add(off as u64),
+            off => base.checked_sub(off.unsigned_abs()),
+        };
+        match pos {
+            Some(pos) => {
+                self.pos = pos;
+                Ok(pos)
+            }
+            None => {
+                let msg = "seek to a negative or overflowing position";
+                Err(io::Error::new(io::ErrorKind::InvalidInput, msg))
+            }
+        }
+    }
+}
+
+/// Writer building a byte vector, implementing [io::Write].
+///
+/// Bytes are appended into a [VectorBuilder], and the vector is built once,
+/// in [RopeWriter::finish]. With the `futures` or `tokio` feature, writer
+/// also implements the async write trait of that crate, every poll
+/// completes.
+pub struct RopeWriter {
+    builder: VectorBuilder<u8>,
+}
+
+impl Default for RopeWriter {
+    fn default() -> RopeWriter {
+        RopeWriter::new(None)
+    }
+}
+
+impl RopeWriter {
+    /// Create a new writer, refer [Vector::from_slice] for `leaf_node_size`.
+    pub fn new(leaf_node_size: Option<usize>) -> RopeWriter {
+        RopeWriter {
+            builder: VectorBuilder::new(leaf_node_size),
+        }
+    }
+
+    /// Return the number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.builder.len()
+    }
+
+    /// Return whether no bytes are written so far.
+    pub fn is_empty(&self) -> bool {
+        self.builder.is_empty()
+    }
+
+    /// Build the vector from bytes written so far.
+    pub fn finish(self) -> Vector<u8> {
+        self.builder.build()
+    }
+}
+
+impl io::Write for RopeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.builder.extend(buf.iter().copied());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncRead for RopeReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Read::read(self.get_mut(), buf))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncBufRead for RopeReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        Poll::Ready(self.get_mut().run())
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        io::BufRead::consume(self.get_mut(), amt)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncSeek for RopeReader {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        pos: io::SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        Poll::Ready(io::Seek::seek(self.get_mut(), pos))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncWrite for RopeWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Write::write(self.get_mut(), buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for RopeReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while buf.remaining() > 0 {
+            let run = this.run()?;
+            if run.is_empty() {
+                break;
+            }
+            let m = cmp::min(run.len(), buf.remaining());
+            buf.put_slice(&run[..m]);
+            this.pos += m as u64;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncBufRead for RopeReader {
+    fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<&[u8]>> {
+        Poll::Ready(self.get_mut().run())
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        io::BufRead::consume(self.get_mut(), amt)
+    }
+}
+
+// seek completes in start_seek(), poll_complete() returns the position.
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncSeek for RopeReader {
+    fn start_seek(self: Pin<&mut Self>, pos: io::SeekFrom) -> io::Result<()> {
+        io::Seek::seek(self.get_mut(), pos).map(|_| ())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.pos))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for RopeWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(io::Write::write(self.get_mut(), buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn to_io_error(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+#[path = "stream_test.rs"]
+mod stream_test;
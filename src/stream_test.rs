@@ -0,0 +1,190 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+
+use super::*;
+
+#[test]
+fn test_reader() {
+    let seed: u128 = random();
+    println!("test_reader seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let data: Vec<u8> = (0..100_000).map(|_| rng.gen()).collect();
+    let mut arr = Vector::from_slice(&data, Some(256));
+    // exercise the tail leaf node as well.
+    arr.insert(arr.len(), 10).unwrap();
+    let mut refv = data.clone();
+    refv.push(10);
+
+    let mut reader = RopeReader::new(arr.clone());
+    let mut out = vec![];
+    loop {
+        let mut buf = vec![0; rng.gen::<usize>() % 1000];
+        match reader.read(&mut buf).unwrap() {
+            0 if !buf.is_empty() => break,
+            n => out.extend_from_slice(&buf[..n]),
+        }
+    }
+    assert_eq!(out, refv);
+    assert_eq!(reader.position(), refv.len() as u64);
+
+    for _ in 0..1000 {
+        let pos = rng.gen::<usize>() % (refv.len() + 10);
+        let n = match rng.gen::<u8>() % 3 {
+            0 => reader.seek(SeekFrom::Start(pos as u64)).unwrap(),
+            1 => {
+                let off = (pos as i64) - (refv.len() as i64);
+                reader.seek(SeekFrom::End(off)).unwrap()
+            }
+            _ => {
+                let off = (pos as i64) - (reader.position() as i64);
+                reader.seek(SeekFrom::Current(off)).unwrap()
+            }
+        };
+        assert_eq!(n, pos as u64);
+
+        let mut buf = [0; 100];
+        let n = reader.read(&mut buf).unwrap();
+        // seeking beyond the end reads nothing.
+        let refs = refv.get(pos..).unwrap_or(&[]);
+        let m = std::cmp::min(refs.len(), 100);
+        assert_eq!(n, m);
+        assert_eq!(&buf[..n], &refs[..m]);
+    }
+
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    assert!(reader.seek(SeekFrom::Current(-1)).is_err());
+    assert_eq!(reader.position(), 0);
+
+    // edits on the vector are not observed by the reader.
+    arr.update(0, refv[0].wrapping_add(1)).unwrap();
+    let mut out = vec![];
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, refv);
+    assert_eq!(reader.into_inner().len(), refv.len());
+}
+
+#[test]
+fn test_reader_lines() {
+    let text: String = (0..10_000).map(|i| format!("line {}\n", i)).collect();
+    let arr = Vector::from_slice(text.as_bytes(), Some(64));
+
+    let reader = RopeReader::from(arr);
+    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+    let refs: Vec<String> = text.lines().map(|l| l.to_string()).collect();
+    assert_eq!(lines, refs);
+}
+
+#[test]
+fn test_writer() {
+    let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+
+    let mut writer = RopeWriter::new(Some(1024));
+    assert!(writer.is_empty());
+    for chunk in data.chunks(333) {
+        writer.write_all(chunk).unwrap();
+    }
+    writer.flush().unwrap();
+    assert_eq!(writer.len(), data.len());
+
+    let arr = writer.finish();
+    assert_eq!(arr.leaf_size(), 1024);
+    let out: Vec<u8> = arr.into();
+    assert_eq!(out, data);
+
+    let arr = RopeWriter::default().finish();
+    let mut out = vec![];
+    RopeReader::new(arr).read_to_end(&mut out).unwrap();
+    assert!(out.is_empty());
+}
+
+#[cfg(feature = "futures")]
+#[test]
+fn test_futures_io() {
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite};
+    use std::{
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let data: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+
+    let mut writer = RopeWriter::new(Some(256));
+    for chunk in data.chunks(333) {
+        let res = Pin::new(&mut writer).poll_write(&mut cx, chunk);
+        assert!(matches!(res, Poll::Ready(Ok(n)) if n == chunk.len()));
+    }
+    assert!(Pin::new(&mut writer).poll_flush(&mut cx).is_ready());
+    assert!(Pin::new(&mut writer).poll_close(&mut cx).is_ready());
+
+    let mut reader = RopeReader::new(writer.finish());
+    let mut out = vec![];
+    loop {
+        let mut buf = [0; 100];
+        match Pin::new(&mut reader).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(0)) => break,
+            Poll::Ready(Ok(n)) => out.extend_from_slice(&buf[..n]),
+            res => panic!("{:?}", res),
+        }
+    }
+    assert_eq!(out, data);
+
+    let res = Pin::new(&mut reader).poll_seek(&mut cx, SeekFrom::End(-10));
+    assert!(matches!(res, Poll::Ready(Ok(n)) if n == 9_990));
+    let run = match Pin::new(&mut reader).poll_fill_buf(&mut cx) {
+        Poll::Ready(Ok(run)) => run.to_vec(),
+        res => panic!("{:?}", res),
+    };
+    assert_eq!(run, &data[9_990..]);
+    Pin::new(&mut reader).consume(run.len());
+    assert_eq!(reader.position(), 10_000);
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_tokio_io() {
+    use std::{
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+    use tokio::io::{AsyncBufRead, AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+    let mut cx = Context::from_waker(Waker::noop());
+    let data: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+
+    let mut writer = RopeWriter::new(Some(256));
+    for chunk in data.chunks(333) {
+        let res = Pin::new(&mut writer).poll_write(&mut cx, chunk);
+        assert!(matches!(res, Poll::Ready(Ok(n)) if n == chunk.len()));
+    }
+    assert!(Pin::new(&mut writer).poll_flush(&mut cx).is_ready());
+    assert!(Pin::new(&mut writer).poll_shutdown(&mut cx).is_ready());
+
+    let mut reader = RopeReader::new(writer.finish());
+    let mut out = vec![];
+    loop {
+        let mut buf = [0; 100];
+        let mut buf = ReadBuf::new(&mut buf);
+        match Pin::new(&mut reader).poll_read(&mut cx, &mut buf) {
+            Poll::Ready(Ok(())) if buf.filled().is_empty() => break,
+            Poll::Ready(Ok(())) => out.extend_from_slice(buf.filled()),
+            res => panic!("{:?}", res),
+        }
+    }
+    assert_eq!(out, data);
+
+    Pin::new(&mut reader)
+        .start_seek(SeekFrom::End(-10))
+        .unwrap();
+    let res = Pin::new(&mut reader).poll_complete(&mut cx);
+    assert!(matches!(res, Poll::Ready(Ok(n)) if n == 9_990));
+    let run = match Pin::new(&mut reader).poll_fill_buf(&mut cx) {
+        Poll::Ready(Ok(run)) => run.to_vec(),
+        res => panic!("{:?}", res),
+    };
+    assert_eq!(run, &data[9_990..]);
+    Pin::new(&mut reader).consume(run.len());
+    assert_eq!(reader.position(), 10_000);
+}
@@ -0,0 +1,557 @@
+#[cfg(feature = "unicode")]
+use std::{collections::VecDeque, mem, result};
+use std::{fmt, iter::FromIterator, ops::Range};
+
+#[cfg(feature = "unicode")]
+use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete, UnicodeSegmentation};
+#[cfg(feature = "unicode")]
+use unicode_width::UnicodeWidthStr;
+
+use super::measured::MeasuredVector;
+use crate::{Error, Measure, Result};
+
+// chunk of text shall not exceed this size, in bytes.
+const CHUNK_SIZE: usize = 1024;
+
+// measure of a chunk of text.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct TextMeasure {
+    bytes: usize,
+    chars: usize,
+    lines: usize, // number of line feeds.
+}
+
+impl Measure<String> for TextMeasure {
+    fn from_item(chunk: &String) -> Self {
+        TextMeasure {
+            bytes: chunk.len(),
+            chars: chunk.chars().count(),
+            lines: chunk.bytes().filter(|b| *b == b'\n').count(),
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        TextMeasure {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            lines: self.lines + other.lines,
+        }
+    }
+}
+
+/// Persistent text, indexed by chars.
+///
+/// Text is held as UTF-8 chunks, of up to 1KB, in a B-tree whose nodes
+/// cache the number of bytes, chars and line feeds under them, hence
+/// locating a char offset, a byte offset or a line is O(log n), and so are
+/// edits. Cloning is cheap
+/// and shares the tree, edits on a clone copy only the path from the root
+/// to the edited chunk.
+#[derive(Clone, Default)]
+pub struct RopeString {
+    chunks: MeasuredVector<String, TextMeasure>,
+}
+
+impl From<&str> for RopeString {
+    fn from(text: &str) -> RopeString {
+        RopeString {
+            chunks: MeasuredVector::from_iter(split_chunks(text)),
+        }
+    }
+}
+
+impl From<String> for RopeString {
+    fn from(text: String) -> RopeString {
+        RopeString::from(text.as_str())
+    }
+}
+
+impl fmt::Display for RopeString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.chunks.iter().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for RopeString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl RopeString {
+    /// Create an empty text.
+    pub fn new() -> RopeString {
+        RopeString::default()
+    }
+
+    /// Return the length of text in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.chunks.total().bytes
+    }
+
+    /// Return the length of text in chars.
+    pub fn len_chars(&self) -> usize {
+        self.chunks.total().chars
+    }
+
+    /// Return the number of lines, that is one more than the number of line
+    /// feeds, the last line being empty when text ends with a line feed.
+    pub fn len_lines(&self) -> usize {
+        self.chunks.total().lines + 1
+    }
+
+    /// Return whether text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len_bytes() == 0
+    }
+
+    /// Return the char at `char_idx`, or `IndexOutOfBounds` error.
+    pub fn char_at(&self, char_idx: usize) -> Result<char> {
+        let (chunk, off) = self.locate_char(char_idx)?;
+        chunk.chars().nth(off).ok_or_else(|| corrupted(char_idx))
+    }
+
+    /// Convert `char_idx` to its byte offset, `char_idx` can be equal to
+    /// [Self::len_chars], in which case [Self::len_bytes] is returned.
+    pub fn char_to_byte(&self, char_idx: usize) -> Result<usize> {
+        if char_idx == self.len_chars() {
+            return Ok(self.len_bytes());
+        }
+        let (k, before) = self.search_char(char_idx)?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(char_idx))?;
+        Ok(before.bytes + byte_offset(chunk, char_idx - before.chars))
+    }
+
+    /// Convert `byte_idx` to the offset of the char containing that byte,
+    /// `byte_idx` can be equal to [Self::len_bytes], in which case
+    /// [Self::len_chars] is returned.
+    pub fn byte_to_char(&self, byte_idx: usize) -> Result<usize> {
+        let n = self.len_bytes();
+        if byte_idx > n {
+            Err(Error::IndexOutOfBounds {
+                index: byte_idx,
+                len: n,
+            })?
+        } else if byte_idx == n {
+            return Ok(self.len_chars());
+        }
+
+        let (k, before) = self
+            .chunks
+            .search_by_measure(|m| m.bytes > byte_idx)
+            .ok_or_else(|| corrupted(byte_idx))?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(byte_idx))?;
+        let off = byte_idx - before.bytes;
+        let n = chunk.char_indices().take_while(|(i, _)| *i <= off).count();
+        Ok(before.chars + n - 1)
+    }
+
+    /// Convert `char_idx` to the line holding it, `char_idx` can be equal
+    /// to [Self::len_chars], in which case the last line is returned.
+    pub fn char_to_line(&self, char_idx: usize) -> Result<usize> {
+        if char_idx == self.len_chars() {
+            return Ok(self.len_lines() - 1);
+        }
+        let (chunk, off) = self.locate_char(char_idx)?;
+        let (_, before) = self.search_char(char_idx)?;
+        let n = chunk.chars().take(off).filter(|ch| *ch == '\n').count();
+        Ok(before.lines + n)
+    }
+
+    /// Convert `line` to the char offset of its start, or
+    /// `IndexOutOfBounds` error.
+    pub fn line_to_char(&self, line: usize) -> Result<usize> {
+        let n = self.len_lines();
+        if line >= n {
+            Err(Error::IndexOutOfBounds {
+                index: line,
+                len: n,
+            })?
+        } else if line == 0 {
+            return Ok(0);
+        }
+
+        // line starts after the chunk holding the line-th line feed.
+        let (k, before) = self
+            .chunks
+            .search_by_measure(|m| m.lines >= line)
+            .ok_or_else(|| corrupted(line))?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(line))?;
+        let (off, _) = chunk
+            .chars()
+            .enumerate()
+            .filter(|(_, ch)| *ch == '\n')
+            .nth(line - before.lines - 1)
+            .ok_or_else(|| corrupted(line))?;
+        Ok(before.chars + off + 1)
+    }
+
+    /// Return `line`, including its line feed if any, or `IndexOutOfBounds`
+    /// error.
+    pub fn line(&self, line: usize) -> Result<String> {
+        let start = self.line_to_char(line)?;
+        let end = match line + 1 {
+            next if next < self.len_lines() => self.line_to_char(next)?,
+            _ => self.len_chars(),
+        };
+        self.slice_to_string(start..end)
+    }
+
+    /// Return an iterator over lines, each including its line feed if any.
+    /// Yields [Self::len_lines] items, the last one being empty when text
+    /// ends with a line feed.
+    pub fn lines(&self) -> impl Iterator<Item = String> + '_ {
+        let mut chunks = self.chunks.iter();
+        let (mut rest, mut done) = ("", false);
+        std::iter::from_fn(move || {
+            let mut line = String::default();
+            while !done {
+                if let Some(i) = rest.find('\n') {
+                    line.push_str(&rest[..=i]);
+                    rest = &rest[(i + 1)..];
+                    return Some(line);
+                }
+                line.push_str(rest);
+                match chunks.next() {
+                    Some(chunk) => rest = chunk,
+                    None => {
+                        done = true;
+                        return Some(line);
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Insert `text` at `char_idx`, or `IndexOutOfBounds` error.
+    pub fn insert_str(&mut self, char_idx: usize, text: &str) -> Result<()> {
+        let n = self.len_chars();
+        if char_idx > n {
+            Err(Error::IndexOutOfBounds {
+                index: char_idx,
+                len: n,
+            })?
+        } else if text.is_empty() {
+            return Ok(());
+        } else if self.chunks.is_empty() {
+            *self = RopeString::from(text);
+            return Ok(());
+        }
+
+        // inserting at the end of text appends to the last chunk.
+        let (k, off) = match char_idx {
+            char_idx if char_idx == n => {
+                let k = self.chunks.len() - 1;
+                let last = self.chunks.get(k).map_err(|_| corrupted(k))?;
+                (k, last.chars().count())
+            }
+            char_idx => {
+                let (k, before) = self.search_char(char_idx)?;
+                (k, char_idx - before.chars)
+            }
+        };
+
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(char_idx))?;
+        let b = byte_offset(chunk, off);
+        let mut s = String::with_capacity(chunk.len() + text.len());
+        s.push_str(&chunk[..b]);
+        s.push_str(text);
+        s.push_str(&chunk[b..]);
+        self.splice_chunk(k, s)
+    }
+
+    /// Insert `ch` at `char_idx`, or `IndexOutOfBounds` error.
+    pub fn insert_char(&mut self, char_idx: usize, ch: char) -> Result<()> {
+        let mut buf = [0; 4];
+        self.insert_str(char_idx, ch.encode_utf8(&mut buf))
+    }
+
+    /// Remove chars within `range`, or `IndexOutOfBounds` error.
+    pub fn remove_range(&mut self, range: Range<usize>) -> Result<()> {
+        let n = self.len_chars();
+        if range.start > range.end || range.end > n {
+            Err(Error::IndexOutOfBounds {
+                index: std::cmp::max(range.start, range.end),
+                len: n,
+            })?
+        } else if range.start == range.end {
+            return Ok(());
+        }
+
+        let (ka, a) = self.search_char(range.start)?;
+        let (kb, b) = self.search_char(range.end - 1)?;
+        let head = self.chunks.get(ka).map_err(|_| corrupted(range.start))?;
+        let tail = self.chunks.get(kb).map_err(|_| corrupted(range.end))?;
+
+        let mut s = head[..byte_offset(head, range.start - a.chars)].to_string();
+        s.push_str(&tail[byte_offset(tail, range.end - b.chars)..]);
+        for _ in ka..kb {
+            self.chunks.remove(ka + 1)?;
+        }
+        self.splice_chunk(ka, s)
+    }
+
+    /// Return the text within char `range` as String, or
+    /// `IndexOutOfBounds` error.
+    pub fn slice_to_string(&self, range: Range<usize>) -> Result<String> {
+        let n = self.len_chars();
+        if range.start > range.end || range.end > n {
+            Err(Error::IndexOutOfBounds {
+                index: std::cmp::max(range.start, range.end),
+                len: n,
+            })?
+        } else if range.start == range.end {
+            return Ok(String::default());
+        }
+
+        let (k, before) = self.search_char(range.start)?;
+        let s = self
+            .chunks
+            .iter_from(k)
+            .flat_map(|chunk| chunk.chars())
+            .skip(range.start - before.chars)
+            .take(range.end - range.start)
+            .collect();
+        Ok(s)
+    }
+
+    /// Return an iterator over chars of the text.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks.iter().flat_map(|chunk| chunk.chars())
+    }
+
+    /// Return an iterator over chunks of the text, chunks are never empty
+    /// and always split at char boundaries.
+    pub fn chunks(&self) -> impl Iterator<Item = &str> + '_ {
+        self.chunks.iter().map(|chunk| chunk.as_str())
+    }
+
+    // return (offset, measure before) of the chunk holding `char_idx`.
+    fn search_char(&self, char_idx: usize) -> Result<(usize, TextMeasure)> {
+        match self.chunks.search_by_measure(|m| m.chars > char_idx) {
+            Some(res) => Ok(res),
+            None => Err(Error::IndexOutOfBounds {
+                index: char_idx,
+                len: self.len_chars(),
+            }),
+        }
+    }
+
+    // return the chunk holding `char_idx` and the char offset within it.
+    fn locate_char(&self, char_idx: usize) -> Result<(&str, usize)> {
+        let (k, before) = self.search_char(char_idx)?;
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(char_idx))?;
+        Ok((chunk, char_idx - before.chars))
+    }
+
+    // replace chunk at `k` with `s`, splitting `s` when it exceeds the
+    // chunk size, dropping it when empty, and merging it with the next
+    // chunk when both fit within a single chunk.
+    fn splice_chunk(&mut self, k: usize, s: String) -> Result<()> {
+        if s.is_empty() {
+            self.chunks.remove(k)?;
+            return Ok(());
+        }
+
+        match self.chunks.get(k + 1) {
+            Ok(next) if s.len() + next.len() <= CHUNK_SIZE => {
+                let next = self.chunks.remove(k + 1)?;
+                self.chunks.update(k, s + &next)?;
+            }
+            _ if s.len() <= CHUNK_SIZE => {
+                self.chunks.update(k, s)?;
+            }
+            _ => {
+                let mut iter = split_chunks(&s).into_iter().enumerate();
+                if let Some((_, chunk)) = iter.next() {
+                    self.chunks.update(k, chunk)?;
+                }
+                for (i, chunk) in iter {
+                    self.chunks.insert(k + i, chunk)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "unicode")]
+impl RopeString {
+    /// Return an iterator over extended grapheme clusters, that is user
+    /// perceived characters, like an emoji with its modifiers. Clusters
+    /// spanning chunks are joined, hence they are returned as String.
+    pub fn graphemes(&self) -> impl Iterator<Item = String> + '_ {
+        let mut chunks = self.chunks.iter();
+        let (mut pending, mut ready) = (String::default(), VecDeque::default());
+        std::iter::from_fn(move || loop {
+            if let Some(g) = ready.pop_front() {
+                break Some(g);
+            }
+            match chunks.next() {
+                // last cluster might continue into the next chunk.
+                Some(chunk) => {
+                    pending.push_str(chunk);
+                    let mut gs: Vec<&str> = pending.graphemes(true).collect();
+                    let last = gs.pop().unwrap_or_default().to_string();
+                    ready.extend(gs.into_iter().map(|g| g.to_string()));
+                    pending = last;
+                }
+                None if pending.is_empty() => break None,
+                None => break Some(mem::take(&mut pending)),
+            }
+        })
+    }
+
+    /// Return the number of extended grapheme clusters, this is O(n).
+    pub fn len_graphemes(&self) -> usize {
+        self.graphemes().count()
+    }
+
+    /// Return the display width of text, in columns, as computed for each
+    /// grapheme cluster. This is O(n).
+    pub fn width(&self) -> usize {
+        self.graphemes().map(|g| g.width()).sum()
+    }
+
+    /// Return whether `char_idx` is at a grapheme cluster boundary, or
+    /// `IndexOutOfBounds` error. Start and end of text are boundaries.
+    pub fn is_grapheme_boundary(&self, char_idx: usize) -> Result<bool> {
+        let byte_idx = self.char_to_byte(char_idx)?;
+        self.with_grapheme_cursor(byte_idx, |cursor, chunk, start| {
+            cursor.is_boundary(chunk, start)
+        })
+    }
+
+    /// Return the char offset of the grapheme cluster boundary after
+    /// `char_idx`, or [Self::len_chars] at the end, or `IndexOutOfBounds`
+    /// error.
+    pub fn next_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        let byte_idx = self.char_to_byte(char_idx)?;
+        let res = self.with_grapheme_cursor(byte_idx, |cursor, chunk, start| {
+            cursor.next_boundary(chunk, start)
+        })?;
+        match res {
+            Some(byte_idx) => self.byte_to_char(byte_idx),
+            None => Ok(self.len_chars()),
+        }
+    }
+
+    /// Return the char offset of the grapheme cluster boundary before
+    /// `char_idx`, or zero at the start, or `IndexOutOfBounds` error.
+    pub fn prev_grapheme_boundary(&self, char_idx: usize) -> Result<usize> {
+        let byte_idx = self.char_to_byte(char_idx)?;
+        let res = self.with_grapheme_cursor(byte_idx, |cursor, chunk, start| {
+            cursor.prev_boundary(chunk, start)
+        })?;
+        match res {
+            Some(byte_idx) => self.byte_to_char(byte_idx),
+            None => Ok(0),
+        }
+    }
+
+    /// Return the text within char `range`, widened to grapheme cluster
+    /// boundaries, so that clusters are never cut, or `IndexOutOfBounds`
+    /// error.
+    pub fn slice_graphemes(&self, range: Range<usize>) -> Result<String> {
+        let start = match self.is_grapheme_boundary(range.start)? {
+            true => range.start,
+            false => self.prev_grapheme_boundary(range.start)?,
+        };
+        let end = match self.is_grapheme_boundary(range.end)? {
+            true => range.end,
+            false => self.next_grapheme_boundary(range.end)?,
+        };
+        self.slice_to_string(start..end)
+    }
+
+    // apply `op` on a grapheme cursor at `byte_idx`, feeding it chunks and
+    // context as it asks for them.
+    fn with_grapheme_cursor<F, R>(&self, byte_idx: usize, mut op: F) -> Result<R>
+    where
+        F: FnMut(
+            &mut GraphemeCursor,
+            &str,
+            usize,
+        ) -> result::Result<R, GraphemeIncomplete>,
+    {
+        let mut cursor = GraphemeCursor::new(byte_idx, self.len_bytes(), true);
+        let (mut chunk, mut start) = self.chunk_at_byte(byte_idx)?;
+        loop {
+            match op(&mut cursor, chunk, start) {
+                Ok(res) => break Ok(res),
+                Err(GraphemeIncomplete::PreContext(n)) => {
+                    let (c, s) = self.chunk_at_byte(n - 1)?;
+                    cursor.provide_context(&c[..(n - s)], s);
+                }
+                Err(GraphemeIncomplete::NextChunk) => {
+                    let next = self.chunk_at_byte(start + chunk.len())?;
+                    chunk = next.0;
+                    start = next.1;
+                }
+                Err(GraphemeIncomplete::PrevChunk) => {
+                    let prev = self.chunk_at_byte(start - 1)?;
+                    chunk = prev.0;
+                    start = prev.1;
+                }
+                Err(GraphemeIncomplete::InvalidOffset) => break Err(corrupted(byte_idx)),
+            }
+        }
+    }
+
+    // return the chunk holding `byte_idx`, along with its byte offset, and
+    // the last chunk for the end of text.
+    fn chunk_at_byte(&self, byte_idx: usize) -> Result<(&str, usize)> {
+        let (k, start) = match self.chunks.search_by_measure(|m| m.bytes > byte_idx) {
+            Some((k, before)) => (k, before.bytes),
+            None if self.chunks.is_empty() => return Ok(("", 0)),
+            None => {
+                let k = self.chunks.len() - 1;
+                let last = self.chunks.get(k).map_err(|_| corrupted(byte_idx))?;
+                (k, self.len_bytes() - last.len())
+            }
+        };
+        let chunk = self.chunks.get(k).map_err(|_| corrupted(byte_idx))?;
+        Ok((chunk, start))
+    }
+}
+
+// split text into chunks of at most CHUNK_SIZE bytes, at char boundaries.
+fn split_chunks(mut text: &str) -> Vec<String> {
+    let mut chunks = vec![];
+    while text.len() > CHUNK_SIZE {
+        // split in halves when the text would fit within two chunks, so that
+        // chunks are left with room to grow.
+        let mut at = match text.len() {
+            n if n <= (CHUNK_SIZE * 2) => n / 2,
+            _ => CHUNK_SIZE,
+        };
+        while !text.is_char_boundary(at) {
+            at -= 1;
+        }
+        chunks.push(text[..at].to_string());
+        text = &text[at..];
+    }
+    if !text.is_empty() {
+        chunks.push(text.to_string());
+    }
+    chunks
+}
+
+// return the byte offset of `char_idx` within `chunk`.
+fn byte_offset(chunk: &str, char_idx: usize) -> usize {
+    match chunk.char_indices().nth(char_idx) {
+        Some((i, _)) => i,
+        None => chunk.len(),
+    }
+}
+
+fn corrupted(index: usize) -> Error {
+    Error::Corrupted {
+        detail: format!("missing chunk for index {}", index),
+    }
+}
+
+#[cfg(test)]
+#[path = "text_test.rs"]
+mod text_test;
@@ -0,0 +1,270 @@
+use rand::{prelude::random, rngs::SmallRng, Rng, SeedableRng};
+
+use super::*;
+#[cfg(feature = "unicode")]
+use unicode_segmentation::UnicodeSegmentation;
+#[cfg(feature = "unicode")]
+use unicode_width::UnicodeWidthStr;
+
+const SAMPLES: [&str; 6] = ["a", "hello world", "é", "日本語", "🦀🦀", "x\ny\n"];
+
+fn validate(text: &RopeString, refs: &str) {
+    assert_eq!(text.to_string(), refs);
+    assert_eq!(text.len_bytes(), refs.len());
+    assert_eq!(text.len_chars(), refs.chars().count());
+    for chunk in text.chunks() {
+        assert!(
+            !chunk.is_empty() && chunk.len() <= CHUNK_SIZE,
+            "{}",
+            chunk.len()
+        );
+    }
+}
+
+// return a random text of upto `n` chars.
+fn gen_text(rng: &mut SmallRng, n: usize) -> String {
+    let n = rng.gen::<usize>() % (n + 1);
+    (0..n)
+        .map(|_| SAMPLES[rng.gen::<usize>() % SAMPLES.len()])
+        .collect()
+}
+
+#[test]
+fn test_text_ops() {
+    let seed: u128 = random();
+    println!("test_text_ops seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut refs = gen_text(&mut rng, 1000);
+    let mut text = RopeString::from(refs.as_str());
+    let mut versions = vec![];
+    validate(&text, &refs);
+
+    for i in 0..5_000 {
+        let n = refs.chars().count();
+        let a = rng.gen::<usize>() % (n + 1);
+        match rng.gen::<u8>() % 6 {
+            0 | 1 => {
+                let s = gen_text(&mut rng, 20);
+                text.insert_str(a, &s).unwrap();
+                let b = refs.char_indices().nth(a).map_or(refs.len(), |(i, _)| i);
+                refs.insert_str(b, &s);
+            }
+            2 => {
+                let s = gen_text(&mut rng, 2000);
+                text.insert_str(a, &s).unwrap();
+                let b = refs.char_indices().nth(a).map_or(refs.len(), |(i, _)| i);
+                refs.insert_str(b, &s);
+            }
+            3 | 4 => {
+                let b = a + (rng.gen::<usize>() % (n - a + 1)) / 4;
+                text.remove_range(a..b).unwrap();
+                let x = refs.char_indices().nth(a).map_or(refs.len(), |(i, _)| i);
+                let y = refs.char_indices().nth(b).map_or(refs.len(), |(i, _)| i);
+                refs.replace_range(x..y, "");
+            }
+            _ => {
+                let b = std::cmp::min(a + 100, n);
+                let s: String = refs.chars().skip(a).take(b - a).collect();
+                assert_eq!(text.slice_to_string(a..b).unwrap(), s);
+                if a < n {
+                    assert_eq!(text.char_at(a).unwrap(), refs.chars().nth(a).unwrap());
+                }
+            }
+        }
+        if i % 500 == 0 {
+            validate(&text, &refs);
+            versions.push((text.clone(), refs.clone()));
+        }
+    }
+    validate(&text, &refs);
+
+    // older versions are left untouched by edits.
+    for (text, refs) in versions.iter() {
+        validate(text, refs);
+    }
+
+    text.remove_range(0..text.len_chars()).unwrap();
+    validate(&text, "");
+    assert!(text.is_empty());
+}
+
+#[test]
+fn test_text_index() {
+    let refs: String = (0..2000).map(|i| SAMPLES[i % SAMPLES.len()]).collect();
+    let text = RopeString::from(refs.clone());
+    validate(&text, &refs);
+
+    for (c, (b, ch)) in refs.char_indices().enumerate() {
+        assert_eq!(text.char_to_byte(c).unwrap(), b);
+        assert_eq!(text.char_at(c).unwrap(), ch);
+        for k in 0..ch.len_utf8() {
+            assert_eq!(text.byte_to_char(b + k).unwrap(), c);
+        }
+    }
+    let (nb, nc) = (refs.len(), refs.chars().count());
+    assert_eq!(text.char_to_byte(nc).unwrap(), nb);
+    assert_eq!(text.byte_to_char(nb).unwrap(), nc);
+    assert!(text.chars().eq(refs.chars()));
+}
+
+#[test]
+fn test_text_errors() {
+    let mut text = RopeString::from("日本語");
+    let err = Error::IndexOutOfBounds { index: 4, len: 3 };
+
+    assert_eq!(text.insert_str(4, "x").err(), Some(err.clone()));
+    assert_eq!(text.insert_char(4, 'x').err(), Some(err.clone()));
+    assert_eq!(text.remove_range(1..4).err(), Some(err.clone()));
+    assert_eq!(text.slice_to_string(0..4).err(), Some(err));
+    assert!(text.char_at(3).is_err());
+    assert!(text.char_to_byte(4).is_err());
+    assert!(text.byte_to_char(10).is_err());
+    validate(&text, "日本語");
+
+    text.insert_char(3, '!').unwrap();
+    text.insert_str(0, "").unwrap();
+    text.remove_range(1..1).unwrap();
+    validate(&text, "日本語!");
+    assert_eq!(format!("{:?}", text), "\"日本語!\"");
+}
+
+#[cfg(feature = "unicode")]
+#[test]
+fn test_text_graphemes() {
+    let seed: u128 = random();
+    println!("test_text_graphemes seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let samples = [
+        "e\u{301}",
+        "👍🏽",
+        "👨\u{200d}👩\u{200d}👧",
+        "🇮🇳",
+        "\r\n",
+        "日本",
+        "ab",
+        "\u{301}\u{301}\u{301}",
+    ];
+    let mut text = RopeString::new();
+    let mut refs = String::default();
+    // edits at random offsets, to place clusters across chunk boundaries.
+    for _ in 0..3000 {
+        let s = samples[rng.gen::<usize>() % samples.len()];
+        let n = refs.chars().count();
+        let off = rng.gen::<usize>() % (n + 1);
+        text.insert_str(off, s).unwrap();
+        let b = refs.char_indices().nth(off).map_or(refs.len(), |(i, _)| i);
+        refs.insert_str(b, s);
+    }
+    validate(&text, &refs);
+    assert!(text.chunks().count() > 10);
+
+    let gs: Vec<String> = refs.graphemes(true).map(|g| g.to_string()).collect();
+    assert_eq!(text.graphemes().collect::<Vec<String>>(), gs);
+    assert_eq!(text.len_graphemes(), gs.len());
+    let width: usize = gs.iter().map(|g| g.width()).sum();
+    assert_eq!(text.width(), width);
+
+    // char offsets of cluster boundaries, including the end of text.
+    let mut bounds: Vec<usize> = vec![];
+    let mut n = 0;
+    for g in gs.iter() {
+        bounds.push(n);
+        n += g.chars().count();
+    }
+    bounds.push(n);
+
+    for _ in 0..1000 {
+        let off = rng.gen::<usize>() % (n + 1);
+        let k = bounds.partition_point(|b| *b < off);
+        let is_bound = bounds[k] == off;
+        assert_eq!(text.is_grapheme_boundary(off).unwrap(), is_bound, "{}", off);
+
+        let next = match is_bound {
+            true => bounds.get(k + 1).copied().unwrap_or(n),
+            false => bounds[k],
+        };
+        assert_eq!(text.next_grapheme_boundary(off).unwrap(), next, "{}", off);
+        let prev = if k == 0 { 0 } else { bounds[k - 1] };
+        assert_eq!(text.prev_grapheme_boundary(off).unwrap(), prev, "{}", off);
+
+        let end = std::cmp::min(off + rng.gen::<usize>() % 20, n);
+        let (a, b) = (
+            bounds[bounds.partition_point(|b| *b <= off) - 1],
+            bounds[bounds.partition_point(|b| *b < end)],
+        );
+        let refs: String = refs.chars().skip(a).take(b - a).collect();
+        assert_eq!(text.slice_graphemes(off..end).unwrap(), refs);
+    }
+    assert!(text.is_grapheme_boundary(n + 1).is_err());
+
+    let empty = RopeString::new();
+    assert_eq!(empty.graphemes().count(), 0);
+    assert!(empty.is_grapheme_boundary(0).unwrap());
+    assert_eq!(empty.next_grapheme_boundary(0).unwrap(), 0);
+}
+
+#[test]
+fn test_text_lines() {
+    let seed: u128 = random();
+    println!("test_text_lines seed {}", seed);
+    let mut rng = SmallRng::from_seed(seed.to_le_bytes());
+
+    let mut text = RopeString::new();
+    let mut refs = String::default();
+    for _ in 0..2000 {
+        let n = refs.chars().count();
+        match rng.gen::<u8>() % 4 {
+            0 if n > 0 => {
+                let a = rng.gen::<usize>() % n;
+                let b = std::cmp::min(a + rng.gen::<usize>() % 20, n);
+                text.remove_range(a..b).unwrap();
+                refs = refs.chars().take(a).chain(refs.chars().skip(b)).collect();
+            }
+            _ => {
+                let s = gen_text(&mut rng, 20);
+                let off = rng.gen::<usize>() % (n + 1);
+                text.insert_str(off, &s).unwrap();
+                let b = refs.char_indices().nth(off).map_or(refs.len(), |(i, _)| i);
+                refs.insert_str(b, &s);
+            }
+        }
+    }
+    validate(&text, &refs);
+
+    let lines: Vec<String> = refs.split_inclusive('\n').map(|l| l.to_string()).collect();
+    let mut refl = lines.clone();
+    if refs.is_empty() || refs.ends_with('\n') {
+        refl.push(String::default());
+    }
+    assert_eq!(text.len_lines(), refl.len());
+    assert_eq!(text.lines().collect::<Vec<String>>(), refl);
+
+    let mut start = 0;
+    for (i, line) in refl.iter().enumerate() {
+        assert_eq!(text.line_to_char(i).unwrap(), start);
+        assert_eq!(&text.line(i).unwrap(), line);
+        assert_eq!(text.char_to_line(start).unwrap(), i);
+        let n = line.chars().count();
+        if n > 0 {
+            let off = start + rng.gen::<usize>() % n;
+            assert_eq!(text.char_to_line(off).unwrap(), i);
+        }
+        start += n;
+    }
+    let n = text.len_lines();
+    assert_eq!(text.char_to_line(text.len_chars()).unwrap(), n - 1);
+    assert!(text.line_to_char(n).is_err());
+    assert!(text.char_to_line(text.len_chars() + 1).is_err());
+
+    let text = RopeString::from("a\n\nb");
+    assert_eq!(
+        text.lines().collect::<Vec<String>>(),
+        vec!["a\n", "\n", "b"]
+    );
+    assert_eq!(text.line_to_char(2).unwrap(), 3);
+    let empty = RopeString::new();
+    assert_eq!(empty.len_lines(), 1);
+    assert_eq!(empty.lines().collect::<Vec<String>>(), vec![""]);
+}
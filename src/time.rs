@@ -0,0 +1,174 @@
+use std::ops::Range;
+
+use super::measured::MeasuredVector;
+use crate::{Error, Measure, Result, Timestamped};
+
+// measure of items, the earliest and latest timestamp among them.
+#[derive(Clone, PartialEq)]
+struct TimeSpan<S> {
+    min: Option<S>,
+    max: Option<S>,
+}
+
+impl<S> Default for TimeSpan<S> {
+    fn default() -> Self {
+        TimeSpan {
+            min: None,
+            max: None,
+        }
+    }
+}
+
+impl<T> Measure<T> for TimeSpan<T::Time>
+where
+    T: Timestamped,
+{
+    fn from_item(item: &T) -> Self {
+        let ts = item.to_timestamp();
+        TimeSpan {
+            min: Some(ts.clone()),
+            max: Some(ts),
+        }
+    }
+
+    fn combine(&self, other: &Self) -> Self {
+        // items are ordered by timestamp, earliest is the first one present
+        // and latest is the last one present.
+        TimeSpan {
+            min: self.min.clone().or_else(|| other.min.clone()),
+            max: other.max.clone().or_else(|| self.max.clone()),
+        }
+    }
+}
+
+/// Persistent sequence of items ordered by timestamp, like an event log.
+///
+/// Timestamps are non-decreasing from one item to the next, and items are
+/// held in a B-tree whose nodes cache the earliest and the latest timestamp
+/// under them. Hence items within a time range are located in O(log n),
+/// without a separate index. Cloning is cheap and shares the tree, edits on
+/// a clone copy only the path from the root to the edited leaf.
+#[derive(Clone)]
+pub struct TimeRope<T>
+where
+    T: Timestamped,
+{
+    items: MeasuredVector<T, TimeSpan<T::Time>>,
+}
+
+impl<T> Default for TimeRope<T>
+where
+    T: Timestamped,
+{
+    fn default() -> Self {
+        TimeRope {
+            items: MeasuredVector::default(),
+        }
+    }
+}
+
+impl<T> TimeRope<T>
+where
+    T: Timestamped,
+{
+    /// Create an empty sequence.
+    pub fn new() -> TimeRope<T> {
+        TimeRope::default()
+    }
+
+    /// Return the number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Return whether empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Return the earliest and the latest timestamp, None if empty.
+    pub fn span(&self) -> Option<(T::Time, T::Time)> {
+        let m = self.items.total();
+        Some((m.min.clone()?, m.max.clone()?))
+    }
+
+    /// Return the item at `index`, or `IndexOutOfBounds` error.
+    pub fn get(&self, index: usize) -> Result<&T> {
+        self.items.get(index)
+    }
+
+    /// Return the range of indices of items whose timestamp is within
+    /// `range`.
+    pub fn index_range(&self, range: Range<T::Time>) -> Range<usize> {
+        let start = self.lower_bound(&range.start);
+        let end = self.lower_bound(&range.end);
+        start..std::cmp::max(start, end)
+    }
+
+    /// Return an iterator over items whose timestamp is within `range`.
+    pub fn iter_by_time(&self, range: Range<T::Time>) -> impl Iterator<Item = &T> + '_ {
+        let Range { start, end } = self.index_range(range);
+        self.items.iter_from(start).take(end - start)
+    }
+
+    /// Return an iterator over items.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.items.iter()
+    }
+
+    // return the index of the first item with timestamp not before `ts`.
+    fn lower_bound(&self, ts: &T::Time) -> usize {
+        let pred = |m: &TimeSpan<T::Time>| matches!(&m.max, Some(x) if x >= ts);
+        let res = self.items.search_by_measure(pred);
+        res.map_or(self.len(), |(off, _)| off)
+    }
+}
+
+impl<T> TimeRope<T>
+where
+    T: Clone + Timestamped,
+{
+    /// Return a new sequence of items whose timestamp is within `range`.
+    /// Items are located in O(log n), and the new sequence shares the tree
+    /// with this one, except for the nodes along the path to either end of
+    /// `range`.
+    pub fn range_by_time(&self, range: Range<T::Time>) -> TimeRope<T> {
+        let Range { start, end } = self.index_range(range);
+        let items = self.items.slice(start..end).unwrap();
+        TimeRope { items }
+    }
+
+    /// Append `item` at the end, or `OutOfOrder` error if its timestamp is
+    /// before the latest timestamp.
+    pub fn push(&mut self, item: T) -> Result<()> {
+        self.insert(self.len(), item)
+    }
+
+    /// Insert `item` at `index`, or `IndexOutOfBounds` error, or
+    /// `OutOfOrder` error if its timestamp is not between the timestamps
+    /// of items around `index`.
+    pub fn insert(&mut self, index: usize, item: T) -> Result<()> {
+        let ts = item.to_timestamp();
+        let prev = index.checked_sub(1).and_then(|i| self.items.get(i).ok());
+        let next = self.items.get(index).ok();
+        match (prev, next) {
+            (Some(prev), _) if prev.to_timestamp() > ts => {
+                Err(Error::OutOfOrder { index })?
+            }
+            (_, Some(next)) if next.to_timestamp() < ts => {
+                Err(Error::OutOfOrder { index })?
+            }
+            _ => self.items.insert(index, item),
+        }
+    }
+
+    /// Remove the item at `index` and return it, or `IndexOutOfBounds`
+    /// error.
+    pub fn remove(&mut self, index: usize) -> Result<T> {
+        self.items.remove(index)
+    }
+}
+
+#[cfg(test)]
+#[path = "time_test.rs"]
+mod time_test;
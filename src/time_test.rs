@@ -0,0 +1,107 @@
+use rand::{rngs::SmallRng, Rng};
+
+use super::*;
+use crate::harness::{check_ops, seeded_rng};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Event {
+    ts: u64,
+    seqno: u64,
+}
+
+impl Timestamped for Event {
+    type Time = u64;
+
+    fn to_timestamp(&self) -> u64 {
+        self.ts
+    }
+}
+
+fn validate(arr: &TimeRope<Event>, refv: &[Event]) {
+    assert_eq!(arr.len(), refv.len());
+    assert!(arr.iter().eq(refv.iter()));
+    match (refv.first(), refv.last()) {
+        (Some(a), Some(b)) => assert_eq!(arr.span(), Some((a.ts, b.ts))),
+        _ => assert_eq!(arr.span(), None),
+    }
+}
+
+#[test]
+fn test_time_ops() {
+    let mut rng = seeded_rng("test_time_ops");
+
+    let mut arr: TimeRope<Event> = TimeRope::new();
+    let mut refv: Vec<Event> = vec![];
+
+    let op = |rng: &mut SmallRng, arr: &mut TimeRope<Event>, refv: &mut Vec<Event>, i| {
+        let seqno = i as u64;
+        let n = refv.len();
+        let last = refv.last().map_or(0, |e| e.ts);
+        match rng.gen::<u8>() % 6 {
+            0..=2 => {
+                // with repeated timestamps.
+                let ev = Event {
+                    ts: last + rng.gen::<u64>() % 3,
+                    seqno,
+                };
+                arr.push(ev.clone()).unwrap();
+                refv.push(ev);
+            }
+            3 if n > 0 => {
+                let off = rng.gen::<usize>() % n;
+                // a copy of the item at `off` is in order, before and after.
+                let ev = Event {
+                    ts: refv[off].ts,
+                    seqno,
+                };
+                let off = off + rng.gen::<usize>() % 2;
+                arr.insert(off, ev.clone()).unwrap();
+                refv.insert(off, ev);
+            }
+            4 if n > 0 => {
+                let off = rng.gen::<usize>() % n;
+                assert_eq!(arr.remove(off).unwrap(), refv.remove(off));
+            }
+            _ => {
+                let t0 = rng.gen::<u64>() % (last + 2);
+                let t1 = t0 + rng.gen::<u64>() % 100;
+                let refs: Vec<&Event> =
+                    refv.iter().filter(|e| (t0..t1).contains(&e.ts)).collect();
+                assert_eq!(arr.iter_by_time(t0..t1).collect::<Vec<&Event>>(), refs);
+                let sub = arr.range_by_time(t0..t1);
+                sub.items.validate().unwrap();
+                assert!(sub.iter().eq(refs.into_iter()));
+            }
+        }
+    };
+    check_ops(&mut rng, &mut arr, &mut refv, 20_000, op, validate);
+}
+
+#[test]
+fn test_time_order() {
+    let mut arr = TimeRope::new();
+    for (i, ts) in [10, 20, 20, 30].iter().enumerate() {
+        let seqno = i as u64;
+        arr.push(Event { ts: *ts, seqno }).unwrap();
+    }
+    let ev = |ts| Event { ts, seqno: 100 };
+
+    assert_eq!(arr.push(ev(25)), Err(Error::OutOfOrder { index: 4 }));
+    assert_eq!(arr.insert(0, ev(11)), Err(Error::OutOfOrder { index: 0 }));
+    assert_eq!(arr.insert(3, ev(19)), Err(Error::OutOfOrder { index: 3 }));
+    assert!(arr.insert(6, ev(40)).is_err());
+    arr.insert(1, ev(15)).unwrap();
+    assert_eq!(
+        Error::OutOfOrder { index: 4 }.to_string(),
+        "OutOfOrder: item at index 4"
+    );
+
+    assert_eq!(arr.index_range(20..30), 2..4);
+    assert_eq!(arr.index_range(0..10), 0..0);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 30..20;
+    assert_eq!(arr.index_range(reversed), 4..4);
+    assert_eq!(arr.index_range(31..40), 5..5);
+    assert_eq!(arr.get(1).unwrap().ts, 15);
+    assert!(arr.get(5).is_err());
+}